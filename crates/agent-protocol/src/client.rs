@@ -918,6 +918,16 @@ impl AgentClient {
             upstream_id: metadata.upstream_id.clone(),
             timestamp: metadata.timestamp.clone(),
             traceparent: metadata.traceparent.clone(),
+            client_cert: metadata
+                .client_cert
+                .as_ref()
+                .map(|cert| grpc::ClientCertInfo {
+                    subject: cert.subject.clone(),
+                    issuer: cert.issuer.clone(),
+                    sans: cert.sans.clone(),
+                    serial: cert.serial.clone(),
+                    fingerprint_sha256: cert.fingerprint_sha256.clone(),
+                }),
         }
     }
 
@@ -975,11 +985,13 @@ impl AgentClient {
         let request_body_mutation = response.request_body_mutation.map(|m| BodyMutation {
             data: m.data.map(|d| String::from_utf8_lossy(&d).to_string()),
             chunk_index: m.chunk_index,
+            insert: m.insert.map(|d| String::from_utf8_lossy(&d).to_string()),
         });
 
         let response_body_mutation = response.response_body_mutation.map(|m| BodyMutation {
             data: m.data.map(|d| String::from_utf8_lossy(&d).to_string()),
             chunk_index: m.chunk_index,
+            insert: m.insert.map(|d| String::from_utf8_lossy(&d).to_string()),
         });
 
         // Convert WebSocket decision
@@ -1011,6 +1023,8 @@ impl AgentClient {
             request_body_mutation,
             response_body_mutation,
             websocket_decision,
+            cache: None,
+            routing: None,
         })
     }
 
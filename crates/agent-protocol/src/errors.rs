@@ -38,4 +38,7 @@ pub enum AgentProtocolError {
 
     #[error("Flow control paused: agent '{agent_id}' requested backpressure")]
     FlowControlPaused { agent_id: String },
+
+    #[error("Request skipped for agent '{agent_id}': {reason}")]
+    Skipped { agent_id: String, reason: String },
 }
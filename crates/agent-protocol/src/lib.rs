@@ -147,10 +147,11 @@ pub use errors::AgentProtocolError;
 // Re-export protocol types
 pub use protocol::{
     AgentRequest, AgentResponse, AuditMetadata, BinaryRequestBodyChunkEvent,
-    BinaryResponseBodyChunkEvent, BodyMutation, ConfigureEvent, Decision, DetectionSeverity,
+    BinaryResponseBodyChunkEvent, BodyMutation, CacheDirective, CacheScope, ClientCertInfo, ConfigureEvent, Decision, DetectionSeverity,
     EventType, GuardrailDetection, GuardrailInspectEvent, GuardrailInspectionType,
     GuardrailResponse, HeaderOp, RequestBodyChunkEvent, RequestCompleteEvent, RequestHeadersEvent,
-    RequestMetadata, ResponseBodyChunkEvent, ResponseHeadersEvent, TextSpan, WebSocketDecision,
+    RequestMetadata, ResponseBodyChunkEvent, ResponseHeadersEvent, RoutingOverride, TextSpan,
+    WebSocketDecision,
     WebSocketFrameEvent, WebSocketOpcode, MAX_MESSAGE_SIZE, PROTOCOL_VERSION,
 };
 
@@ -160,6 +161,7 @@ pub use client::{AgentClient, GrpcTlsConfig, HttpTlsConfig};
 // Re-export server and handler
 pub use server::{
     AgentHandler, AgentServer, DenylistAgent, EchoAgent, GrpcAgentHandler, GrpcAgentServer,
+    UppercaseEchoAgent,
 };
 
 #[cfg(test)]
@@ -205,6 +207,7 @@ mod tests {
                 upstream_id: Some("backend".to_string()),
                 timestamp: chrono::Utc::now().to_rfc3339(),
                 traceparent: None,
+                client_cert: None,
             },
             method: "GET".to_string(),
             uri: "/test".to_string(),
@@ -262,6 +265,7 @@ mod tests {
                 upstream_id: Some("backend".to_string()),
                 timestamp: chrono::Utc::now().to_rfc3339(),
                 traceparent: None,
+                client_cert: None,
             },
             method: "GET".to_string(),
             uri: "/admin/secret".to_string(),
@@ -304,6 +308,38 @@ mod tests {
         assert!(!replace.is_drop());
         assert_eq!(replace.chunk_index, 2);
         assert_eq!(replace.data, Some("modified content".to_string()));
+
+        // Test insert mutation
+        let insert = BodyMutation::insert(3, "prefix:".to_string());
+        assert!(!insert.is_pass_through());
+        assert!(!insert.is_drop());
+        assert_eq!(insert.data, None);
+    }
+
+    #[test]
+    fn test_body_mutation_apply() {
+        let chunk = b"hello";
+        assert_eq!(BodyMutation::pass_through(0).apply(chunk), b"hello");
+        assert!(BodyMutation::drop_chunk(0).apply(chunk).is_empty());
+        assert_eq!(
+            BodyMutation::replace(0, "HELLO".to_string()).apply(chunk),
+            b"HELLO"
+        );
+        assert_eq!(
+            BodyMutation::insert(0, "say ".to_string()).apply(chunk),
+            b"say hello"
+        );
+
+        // Insert combined with drop emits only the inserted data
+        let mut mutation = BodyMutation::drop_chunk(0);
+        mutation.insert = Some("bye".to_string());
+        assert!(!mutation.is_drop());
+        assert_eq!(mutation.apply(chunk), b"bye");
+
+        // Older agents omit the insert field
+        let parsed: BodyMutation =
+            serde_json::from_str(r#"{"data":null,"chunk_index":4}"#).unwrap();
+        assert!(parsed.is_pass_through());
     }
 
     #[test]
@@ -327,4 +363,45 @@ mod tests {
         let response = AgentResponse::default_allow().set_needs_more(true);
         assert!(response.needs_more);
     }
+
+    #[test]
+    fn test_agent_response_cache_directive() {
+        let response = AgentResponse::default_allow().with_cache(
+            30_000,
+            CacheScope::Header {
+                name: "authorization".to_string(),
+            },
+        );
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            json["cache"],
+            serde_json::json!({"ttl_ms": 30000, "scope": {"type": "header", "name": "authorization"}})
+        );
+
+        // Responses from older agents parse without the field
+        let mut json = serde_json::to_value(AgentResponse::default_allow()).unwrap();
+        json.as_object_mut().unwrap().remove("cache");
+        let parsed: AgentResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.cache, None);
+    }
+
+    #[test]
+    fn test_agent_response_routing_override() {
+        let response = AgentResponse::default_allow()
+            .with_upstream("canary")
+            .with_hash_key("tenant-42");
+        let routing = response.routing.as_ref().unwrap();
+        assert_eq!(routing.upstream.as_deref(), Some("canary"));
+        assert_eq!(routing.target, None);
+        assert_eq!(routing.hash_key.as_deref(), Some("tenant-42"));
+
+        let json = serde_json::to_value(&response).unwrap();
+        let parsed: AgentResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.routing, response.routing);
+
+        let mut json = serde_json::to_value(AgentResponse::default_allow()).unwrap();
+        json.as_object_mut().unwrap().remove("routing");
+        let parsed: AgentResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.routing, None);
+    }
 }
@@ -89,6 +89,7 @@ pub enum HeaderOp {
 /// - `None` data: pass through original chunk unchanged
 /// - `Some(empty)`: drop the chunk entirely
 /// - `Some(data)`: replace chunk with modified content
+/// - `insert`: emit extra content ahead of the (possibly replaced) chunk
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BodyMutation {
     /// Modified body data (base64 encoded for JSON transport)
@@ -103,6 +104,12 @@ pub struct BodyMutation {
     /// Must match the `chunk_index` from the body chunk event.
     #[serde(default)]
     pub chunk_index: u32,
+
+    /// Data to insert before this chunk
+    ///
+    /// Inserting on the final (possibly empty) chunk appends to the body.
+    #[serde(default)]
+    pub insert: Option<String>,
 }
 
 impl BodyMutation {
@@ -111,6 +118,7 @@ impl BodyMutation {
         Self {
             data: None,
             chunk_index,
+            insert: None,
         }
     }
 
@@ -119,6 +127,7 @@ impl BodyMutation {
         Self {
             data: Some(String::new()),
             chunk_index,
+            insert: None,
         }
     }
 
@@ -127,17 +136,39 @@ impl BodyMutation {
         Self {
             data: Some(data),
             chunk_index,
+            insert: None,
+        }
+    }
+
+    /// Create a mutation that inserts data before the chunk, keeping the chunk
+    pub fn insert(chunk_index: u32, data: String) -> Self {
+        Self {
+            data: None,
+            chunk_index,
+            insert: Some(data),
         }
     }
 
     /// Check if this mutation passes through unchanged
     pub fn is_pass_through(&self) -> bool {
-        self.data.is_none()
+        self.data.is_none() && self.insert.as_ref().is_none_or(|i| i.is_empty())
     }
 
     /// Check if this mutation drops the chunk
     pub fn is_drop(&self) -> bool {
         matches!(&self.data, Some(d) if d.is_empty())
+            && self.insert.as_ref().is_none_or(|i| i.is_empty())
+    }
+
+    /// Apply this mutation to the original chunk, returning the bytes to forward
+    ///
+    /// An empty result means nothing is forwarded for this chunk.
+    pub fn apply(&self, chunk: &[u8]) -> Vec<u8> {
+        let body = self.data.as_ref().map_or(chunk, |d| d.as_bytes());
+        match &self.insert {
+            Some(insert) => [insert.as_bytes(), body].concat(),
+            None => body.to_vec(),
+        }
     }
 }
 
@@ -174,6 +205,43 @@ pub struct RequestMetadata {
     /// Agents can use this to create child spans that link to the proxy's span.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub traceparent: Option<String>,
+    /// Verified client certificate identity (mTLS connections only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<ClientCertInfo>,
+}
+
+/// Identity of a verified TLS client certificate
+///
+/// Populated when the client authenticated with a certificate (mTLS).
+/// Agents can use it to authorize requests by service identity, e.g. a
+/// SPIFFE ID carried as a URI SAN.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientCertInfo {
+    /// Subject distinguished name (RFC 4514 string form)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Issuer distinguished name (RFC 4514 string form)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    /// Subject alternative names, prefixed by type
+    /// (`DNS:`, `URI:`, `email:`, `IP:`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sans: Vec<String>,
+    /// Serial number (hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+    /// SHA-256 fingerprint of the DER-encoded certificate (lowercase hex)
+    pub fingerprint_sha256: String,
+}
+
+impl ClientCertInfo {
+    /// SPIFFE ID from the first `spiffe://` URI SAN, if any
+    pub fn spiffe_id(&self) -> Option<&str> {
+        self.sans
+            .iter()
+            .filter_map(|san| san.strip_prefix("URI:"))
+            .find(|uri| uri.starts_with("spiffe://"))
+    }
 }
 
 /// Configure event
@@ -636,6 +704,21 @@ pub struct AgentResponse {
     /// Only valid for `WebSocketFrame` events. If not set, defaults to Allow.
     #[serde(default)]
     pub websocket_decision: Option<WebSocketDecision>,
+
+    /// Cache directive for this decision
+    ///
+    /// Only honored for `RequestHeaders` events. While the entry is valid,
+    /// the proxy reuses this response for requests with the same cache key
+    /// instead of calling the agent.
+    #[serde(default)]
+    pub cache: Option<CacheDirective>,
+
+    /// Upstream selection override
+    ///
+    /// Only honored for `RequestHeaders` events with an allow decision. The
+    /// proxy validates the override against the route before applying it.
+    #[serde(default)]
+    pub routing: Option<RoutingOverride>,
 }
 
 impl AgentResponse {
@@ -652,6 +735,8 @@ impl AgentResponse {
             request_body_mutation: None,
             response_body_mutation: None,
             websocket_decision: None,
+            cache: None,
+            routing: None,
         }
     }
 
@@ -672,6 +757,8 @@ impl AgentResponse {
             request_body_mutation: None,
             response_body_mutation: None,
             websocket_decision: None,
+            cache: None,
+            routing: None,
         }
     }
 
@@ -688,6 +775,8 @@ impl AgentResponse {
             request_body_mutation: None,
             response_body_mutation: None,
             websocket_decision: None,
+            cache: None,
+            routing: None,
         }
     }
 
@@ -704,6 +793,8 @@ impl AgentResponse {
             request_body_mutation: None,
             response_body_mutation: None,
             websocket_decision: None,
+            cache: None,
+            routing: None,
         }
     }
 
@@ -772,6 +863,71 @@ impl AgentResponse {
         self.audit = audit;
         self
     }
+
+    /// Allow the proxy to reuse this decision for `ttl_ms` milliseconds
+    pub fn with_cache(mut self, ttl_ms: u64, scope: CacheScope) -> Self {
+        self.cache = Some(CacheDirective { ttl_ms, scope });
+        self
+    }
+
+    /// Route the request to another upstream allowed by the route
+    pub fn with_upstream(mut self, upstream: impl Into<String>) -> Self {
+        self.routing.get_or_insert_with(Default::default).upstream = Some(upstream.into());
+        self
+    }
+
+    /// Pin the request to a specific target (`host:port`) of the upstream
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.routing.get_or_insert_with(Default::default).target = Some(target.into());
+        self
+    }
+
+    /// Use this key instead of the request attributes for hash-based load balancing
+    pub fn with_hash_key(mut self, hash_key: impl Into<String>) -> Self {
+        self.routing.get_or_insert_with(Default::default).hash_key = Some(hash_key.into());
+        self
+    }
+}
+
+/// Agent override of upstream selection
+///
+/// Every field is optional; unset fields leave the proxy's own selection in
+/// place. Overrides the route does not permit are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutingOverride {
+    /// Upstream ID to use instead of the route's upstream
+    #[serde(default)]
+    pub upstream: Option<String>,
+    /// Target (`host:port`) within the upstream to send the request to
+    #[serde(default)]
+    pub target: Option<String>,
+    /// Key for consistent-hash and Maglev load balancing
+    #[serde(default)]
+    pub hash_key: Option<String>,
+}
+
+/// Cache directive attached to an agent response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheDirective {
+    /// How long the decision stays valid, in milliseconds
+    pub ttl_ms: u64,
+    /// What the cached decision is keyed on (always within the same route)
+    pub scope: CacheScope,
+}
+
+/// Key scope for a cached agent decision
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum CacheScope {
+    /// Requests from the same client IP
+    ClientIp,
+    /// Requests carrying the same value for a header (e.g. `authorization`)
+    Header {
+        /// Header name (case-insensitive)
+        name: String,
+    },
+    /// All requests on the same route
+    Route,
 }
 
 /// Audit metadata from agent
@@ -805,6 +961,8 @@ pub enum GuardrailInspectionType {
     PromptInjection,
     /// PII detection (analyze response content)
     PiiDetection,
+    /// Streamed response inspection (analyze a window of completion deltas)
+    ResponseStream,
 }
 
 /// Guardrail inspection event
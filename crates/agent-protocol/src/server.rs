@@ -18,7 +18,7 @@ use crate::grpc::{
     self, agent_processor_server::AgentProcessor, agent_processor_server::AgentProcessorServer,
 };
 use crate::protocol::{
-    AgentRequest, AgentResponse, AuditMetadata, ConfigureEvent, Decision, EventType,
+    AgentRequest, AgentResponse, AuditMetadata, BodyMutation, ConfigureEvent, Decision, EventType,
     GuardrailInspectEvent, HeaderOp, RequestBodyChunkEvent, RequestCompleteEvent,
     RequestHeadersEvent, RequestMetadata, ResponseBodyChunkEvent, ResponseHeadersEvent,
     WebSocketDecision, WebSocketFrameEvent, MAX_MESSAGE_SIZE, PROTOCOL_VERSION,
@@ -334,8 +334,15 @@ impl AgentServer {
 }
 
 /// Reference implementation: Echo agent (for testing)
+#[derive(Debug, Default, Clone, Copy)]
 pub struct EchoAgent;
 
+impl EchoAgent {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
 #[async_trait]
 impl AgentHandler for EchoAgent {
     async fn on_request_headers(&self, event: RequestHeadersEvent) -> AgentResponse {
@@ -357,6 +364,49 @@ impl AgentHandler for EchoAgent {
     }
 }
 
+/// Reference implementation: Echo agent that rewrites bodies (for testing)
+///
+/// Behaves like [`EchoAgent`] for request headers and replaces every request
+/// and response body chunk with its ASCII upper-case form, which exercises
+/// streaming body mutations end to end.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UppercaseEchoAgent;
+
+impl UppercaseEchoAgent {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn uppercase_chunk(data: &str, chunk_index: u32) -> Option<BodyMutation> {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        let chunk = STANDARD.decode(data).ok()?;
+        let upper = String::from_utf8_lossy(&chunk.to_ascii_uppercase()).into_owned();
+        Some(BodyMutation::replace(chunk_index, upper))
+    }
+}
+
+#[async_trait]
+impl AgentHandler for UppercaseEchoAgent {
+    async fn on_request_headers(&self, event: RequestHeadersEvent) -> AgentResponse {
+        EchoAgent.on_request_headers(event).await
+    }
+
+    async fn on_request_body_chunk(&self, event: RequestBodyChunkEvent) -> AgentResponse {
+        match Self::uppercase_chunk(&event.data, event.chunk_index) {
+            Some(mutation) => AgentResponse::default_allow().with_request_body_mutation(mutation),
+            None => AgentResponse::default_allow(),
+        }
+    }
+
+    async fn on_response_body_chunk(&self, event: ResponseBodyChunkEvent) -> AgentResponse {
+        match Self::uppercase_chunk(&event.data, event.chunk_index) {
+            Some(mutation) => AgentResponse::default_allow().with_response_body_mutation(mutation),
+            None => AgentResponse::default_allow(),
+        }
+    }
+}
+
 /// Reference implementation: Denylist agent
 pub struct DenylistAgent {
     blocked_paths: Vec<String>,
@@ -742,6 +792,13 @@ impl GrpcAgentHandler {
                 upstream_id: m.upstream_id,
                 timestamp: m.timestamp,
                 traceparent: m.traceparent,
+                client_cert: m.client_cert.map(|cert| crate::ClientCertInfo {
+                    subject: cert.subject,
+                    issuer: cert.issuer,
+                    sans: cert.sans,
+                    serial: cert.serial,
+                    fingerprint_sha256: cert.fingerprint_sha256,
+                }),
             },
             None => RequestMetadata {
                 correlation_id: String::new(),
@@ -756,6 +813,7 @@ impl GrpcAgentHandler {
                 upstream_id: None,
                 timestamp: String::new(),
                 traceparent: None,
+                client_cert: None,
             },
         }
     }
@@ -821,11 +879,13 @@ impl GrpcAgentHandler {
         let request_body_mutation = response.request_body_mutation.map(|m| grpc::BodyMutation {
             data: m.data.map(|d| d.into_bytes()),
             chunk_index: m.chunk_index,
+            insert: m.insert.map(|d| d.into_bytes()),
         });
 
         let response_body_mutation = response.response_body_mutation.map(|m| grpc::BodyMutation {
             data: m.data.map(|d| d.into_bytes()),
             chunk_index: m.chunk_index,
+            insert: m.insert.map(|d| d.into_bytes()),
         });
 
         // Convert WebSocket decision
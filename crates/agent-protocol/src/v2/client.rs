@@ -820,7 +820,7 @@ fn i32_to_event_type(i: i32) -> Option<EventType> {
     }
 }
 
-fn convert_request_headers_to_grpc(event: &crate::RequestHeadersEvent) -> grpc_v2::RequestHeadersEvent {
+pub(super) fn convert_request_headers_to_grpc(event: &crate::RequestHeadersEvent) -> grpc_v2::RequestHeadersEvent {
     let metadata = Some(grpc_v2::RequestMetadata {
         correlation_id: event.metadata.correlation_id.clone(),
         request_id: event.metadata.request_id.clone(),
@@ -833,6 +833,17 @@ fn convert_request_headers_to_grpc(event: &crate::RequestHeadersEvent) -> grpc_v
         upstream_id: event.metadata.upstream_id.clone(),
         timestamp_ms: now_ms(),
         traceparent: event.metadata.traceparent.clone(),
+        client_cert: event
+            .metadata
+            .client_cert
+            .as_ref()
+            .map(|cert| grpc_v2::ClientCertInfo {
+                subject: cert.subject.clone(),
+                issuer: cert.issuer.clone(),
+                sans: cert.sans.clone(),
+                serial: cert.serial.clone(),
+                fingerprint_sha256: cert.fingerprint_sha256.clone(),
+            }),
     });
 
     // Use iter_flat helper for cleaner iteration over flattened headers
@@ -852,7 +863,7 @@ fn convert_request_headers_to_grpc(event: &crate::RequestHeadersEvent) -> grpc_v
     }
 }
 
-fn convert_body_chunk_to_grpc(event: &crate::RequestBodyChunkEvent) -> grpc_v2::BodyChunkEvent {
+pub(super) fn convert_body_chunk_to_grpc(event: &crate::RequestBodyChunkEvent) -> grpc_v2::BodyChunkEvent {
     // Convert through binary type to centralize the base64 decode logic
     let binary: crate::BinaryRequestBodyChunkEvent = event.into();
     convert_binary_body_chunk_to_grpc(&binary)
@@ -874,7 +885,7 @@ fn convert_binary_body_chunk_to_grpc(event: &crate::BinaryRequestBodyChunkEvent)
     }
 }
 
-fn convert_response_headers_to_grpc(event: &crate::ResponseHeadersEvent) -> grpc_v2::ResponseHeadersEvent {
+pub(super) fn convert_response_headers_to_grpc(event: &crate::ResponseHeadersEvent) -> grpc_v2::ResponseHeadersEvent {
     // Use iter_flat helper for cleaner iteration over flattened headers
     let headers: Vec<grpc_v2::Header> = iter_flat(&event.headers)
         .map(|(name, value)| grpc_v2::Header {
@@ -890,7 +901,7 @@ fn convert_response_headers_to_grpc(event: &crate::ResponseHeadersEvent) -> grpc
     }
 }
 
-fn convert_response_body_chunk_to_grpc(event: &crate::ResponseBodyChunkEvent) -> grpc_v2::BodyChunkEvent {
+pub(super) fn convert_response_body_chunk_to_grpc(event: &crate::ResponseBodyChunkEvent) -> grpc_v2::BodyChunkEvent {
     // Convert through binary type to centralize the base64 decode logic
     let binary: crate::BinaryResponseBodyChunkEvent = event.into();
     convert_binary_response_body_chunk_to_grpc(&binary)
@@ -912,7 +923,7 @@ fn convert_binary_response_body_chunk_to_grpc(event: &crate::BinaryResponseBodyC
     }
 }
 
-fn convert_response_from_grpc(resp: grpc_v2::AgentResponse) -> AgentResponse {
+pub(super) fn convert_response_from_grpc(resp: grpc_v2::AgentResponse) -> AgentResponse {
     let decision = match resp.decision {
         Some(grpc_v2::agent_response::Decision::Allow(_)) => Decision::Allow,
         Some(grpc_v2::agent_response::Decision::Block(b)) => Decision::Block {
@@ -955,6 +966,28 @@ fn convert_response_from_grpc(resp: grpc_v2::AgentResponse) -> AgentResponse {
         custom: a.custom.into_iter().map(|(k, v)| (k, serde_json::Value::String(v))).collect(),
     }).unwrap_or_default();
 
+    let cache = resp.cache.and_then(|c| {
+        let scope = match grpc_v2::CacheScope::try_from(c.scope).ok()? {
+            grpc_v2::CacheScope::ClientIp => crate::CacheScope::ClientIp,
+            grpc_v2::CacheScope::Header => crate::CacheScope::Header { name: c.header_name },
+            grpc_v2::CacheScope::Route => crate::CacheScope::Route,
+            grpc_v2::CacheScope::Unspecified => return None,
+        };
+        Some(crate::CacheDirective { ttl_ms: c.ttl_ms, scope })
+    });
+
+    let routing = resp.routing.map(|r| crate::RoutingOverride {
+        upstream: r.upstream,
+        target: r.target,
+        hash_key: r.hash_key,
+    });
+
+    let convert_body_mutation = |m: grpc_v2::BodyMutation| crate::BodyMutation {
+        data: m.data.map(|d| String::from_utf8_lossy(&d).into_owned()),
+        chunk_index: m.chunk_index,
+        insert: m.insert.map(|d| String::from_utf8_lossy(&d).into_owned()),
+    };
+
     AgentResponse {
         version: PROTOCOL_VERSION_2,
         decision,
@@ -963,9 +996,11 @@ fn convert_response_from_grpc(resp: grpc_v2::AgentResponse) -> AgentResponse {
         routing_metadata: HashMap::new(),
         audit,
         needs_more: resp.needs_more,
-        request_body_mutation: None,
-        response_body_mutation: None,
+        request_body_mutation: resp.request_body_mutation.map(convert_body_mutation),
+        response_body_mutation: resp.response_body_mutation.map(convert_body_mutation),
         websocket_decision: None,
+        cache,
+        routing,
     }
 }
 
@@ -1080,6 +1115,9 @@ fn convert_config_update_from_grpc(update: grpc_v2::ConfigUpdateRequest) -> crat
                 field: ce.field,
             }
         }
+        Some(grpc_v2::config_update_request::UpdateType::InvalidateCache(_)) => {
+            ConfigUpdateType::InvalidateCache
+        }
         None => ConfigUpdateType::RequestReload, // Default
     };
 
@@ -0,0 +1,1301 @@
+//! Protocol conformance kit for Agent Protocol v2 agents.
+//!
+//! The kit plays the proxy's side of the protocol against a running agent,
+//! replays scripted event sequences and checks every reply against the wire
+//! format and semantics the proxy relies on. The result is a
+//! [`ConformanceReport`] that serializes to JSON, so it can gate an agent's CI.
+//!
+//! # Transports
+//!
+//! - [`ConformanceTarget::Uds`] connects to an agent's socket and performs
+//!   the UDS handshake.
+//! - [`ConformanceTarget::Reverse`] binds a listener and waits for the agent
+//!   to register. Scenarios that end the connection need a fresh one, so the
+//!   agent must reconnect after a disconnect, as it would against the proxy.
+//! - [`ConformanceTarget::Grpc`] drives the agent through [`AgentClientV2`].
+//!   Frame-level scenarios do not apply to gRPC and are reported as skipped.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use sentinel_agent_protocol::v2::conformance::{ConformanceRunner, ConformanceTarget};
+//!
+//! let report = ConformanceRunner::new(ConformanceTarget::Uds("/var/run/waf.sock".into()))
+//!     .run()
+//!     .await;
+//!
+//! println!("{}", report.to_json()?);
+//! assert!(report.is_conformant());
+//! ```
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncWriteExt, BufReader, BufWriter};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::v2::client::{AgentClientV2, CancelReason};
+use crate::v2::reverse::{RegistrationRequest, RegistrationResponse};
+use crate::v2::uds::{
+    encode_event, read_message, write_message, MessageType, UdsCapabilities, UdsEncoding,
+    UdsHandshakeRequest, UdsHandshakeResponse, MAX_UDS_MESSAGE_SIZE,
+};
+use crate::v2::PROTOCOL_VERSION_2;
+use crate::{
+    AgentProtocolError, AgentResponse, RequestBodyChunkEvent, RequestHeadersEvent, RequestMetadata,
+};
+
+/// Type byte that no protocol version assigns.
+const UNASSIGNED_MESSAGE_TYPE: u8 = 0x7f;
+
+/// Agent under test.
+#[derive(Debug, Clone)]
+pub enum ConformanceTarget {
+    /// Agent listening on a Unix socket
+    Uds(PathBuf),
+    /// Agent serving gRPC at this endpoint (e.g. `http://127.0.0.1:50051`)
+    Grpc(String),
+    /// Agent that connects to a listener the kit binds at this path
+    Reverse(PathBuf),
+}
+
+impl ConformanceTarget {
+    fn transport(&self) -> &'static str {
+        match self {
+            ConformanceTarget::Uds(_) => "uds",
+            ConformanceTarget::Grpc(_) => "grpc",
+            ConformanceTarget::Reverse(_) => "reverse",
+        }
+    }
+
+    fn address(&self) -> String {
+        match self {
+            ConformanceTarget::Uds(path) | ConformanceTarget::Reverse(path) => {
+                path.display().to_string()
+            }
+            ConformanceTarget::Grpc(endpoint) => endpoint.clone(),
+        }
+    }
+}
+
+/// A scripted event sequence and the behaviour expected from the agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scenario {
+    /// Handshake or registration succeeds with protocol v2 and an offered encoding
+    Handshake,
+    /// A request headers event is answered with its correlation ID
+    RequestHeaders,
+    /// Every chunk of a streamed request body is answered in turn
+    BodyStreaming,
+    /// A request cancelled mid-body stops being answered and the connection stays usable
+    CancelMidBody,
+    /// Requests in flight when the agent pauses are still answered, and pauses are lifted
+    FlowControlPause,
+    /// The agent closes its end once the proxy cancels in-flight work and half-closes
+    Drain,
+    /// A frame declaring more than the 16 MB limit gets the connection closed
+    OversizedFrame,
+    /// An unassigned message type is ignored or rejected, never answered
+    UnknownMessageType,
+    /// A ping is answered with a pong
+    Ping,
+}
+
+impl Scenario {
+    /// All scenarios, in the order they run.
+    pub const ALL: [Scenario; 9] = [
+        Scenario::Handshake,
+        Scenario::RequestHeaders,
+        Scenario::BodyStreaming,
+        Scenario::CancelMidBody,
+        Scenario::FlowControlPause,
+        Scenario::Drain,
+        Scenario::OversizedFrame,
+        Scenario::UnknownMessageType,
+        Scenario::Ping,
+    ];
+}
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Skip,
+}
+
+/// Result of running one scenario.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub scenario: Scenario,
+    pub status: CheckStatus,
+    /// What was observed, or which requirement was violated
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+/// Machine-readable conformance report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConformanceReport {
+    /// Transport used: `uds`, `grpc` or `reverse`
+    pub transport: String,
+    /// Socket path or endpoint of the agent
+    pub target: String,
+    /// Agent ID announced in the handshake or registration
+    pub agent_id: Option<String>,
+    /// Negotiated protocol version
+    pub protocol_version: Option<u32>,
+    /// Negotiated payload encoding (UDS and reverse connections)
+    pub encoding: Option<UdsEncoding>,
+    /// When the run started (RFC3339)
+    pub started_at: String,
+    pub checks: Vec<CheckResult>,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+impl ConformanceReport {
+    fn new(target: &ConformanceTarget) -> Self {
+        Self {
+            transport: target.transport().to_string(),
+            target: target.address(),
+            agent_id: None,
+            protocol_version: None,
+            encoding: None,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            checks: Vec::new(),
+            passed: 0,
+            failed: 0,
+            skipped: 0,
+        }
+    }
+
+    fn record(&mut self, scenario: Scenario, verdict: Verdict, started: Instant) {
+        let (status, detail) = match verdict {
+            Verdict::Pass(detail) => {
+                self.passed += 1;
+                (CheckStatus::Pass, detail)
+            }
+            Verdict::Fail(detail) => {
+                self.failed += 1;
+                (CheckStatus::Fail, detail)
+            }
+            Verdict::Skip(detail) => {
+                self.skipped += 1;
+                (CheckStatus::Skip, detail)
+            }
+        };
+        self.checks.push(CheckResult {
+            scenario,
+            status,
+            detail,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+
+    /// Result for a scenario, if it ran.
+    pub fn check(&self, scenario: Scenario) -> Option<&CheckResult> {
+        self.checks.iter().find(|c| c.scenario == scenario)
+    }
+
+    /// Whether no check failed.
+    pub fn is_conformant(&self) -> bool {
+        self.failed == 0
+    }
+
+    /// Serialize the report as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, AgentProtocolError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| AgentProtocolError::Serialization(e.to_string()))
+    }
+}
+
+/// Conformance run settings.
+#[derive(Debug, Clone)]
+pub struct ConformanceConfig {
+    /// How long to wait for each expected reply, connection or close
+    pub timeout: Duration,
+    /// Encodings offered in the UDS handshake
+    pub encodings: Vec<UdsEncoding>,
+    /// Scenarios to run. The handshake is always checked, since every other
+    /// scenario needs a session.
+    pub scenarios: Vec<Scenario>,
+    /// Requests sent back to back by the flow control scenario
+    pub burst_size: usize,
+    /// Client ID used for gRPC connections
+    pub client_id: String,
+}
+
+impl Default for ConformanceConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            encodings: vec![UdsEncoding::Json],
+            scenarios: Scenario::ALL.to_vec(),
+            burst_size: 64,
+            client_id: "sentinel-conformance".to_string(),
+        }
+    }
+}
+
+/// Runs conformance scenarios against an agent.
+pub struct ConformanceRunner {
+    target: ConformanceTarget,
+    config: ConformanceConfig,
+}
+
+enum Verdict {
+    Pass(String),
+    Fail(String),
+    Skip(String),
+}
+
+impl From<Result<String, String>> for Verdict {
+    fn from(result: Result<String, String>) -> Self {
+        match result {
+            Ok(detail) => Verdict::Pass(detail),
+            Err(detail) => Verdict::Fail(detail),
+        }
+    }
+}
+
+impl ConformanceRunner {
+    /// Create a runner with the default configuration.
+    pub fn new(target: ConformanceTarget) -> Self {
+        Self::with_config(target, ConformanceConfig::default())
+    }
+
+    /// Create a runner with a custom configuration.
+    pub fn with_config(target: ConformanceTarget, config: ConformanceConfig) -> Self {
+        Self { target, config }
+    }
+
+    /// Run the configured scenarios and collect the report.
+    ///
+    /// Failures are recorded in the report rather than returned, so a run
+    /// always produces a complete report.
+    pub async fn run(&self) -> ConformanceReport {
+        let mut report = ConformanceReport::new(&self.target);
+
+        match &self.target {
+            ConformanceTarget::Uds(path) => {
+                let endpoint = FrameEndpoint::Uds(path.clone());
+                self.run_framed(&endpoint, &mut report).await;
+            }
+            ConformanceTarget::Reverse(path) => {
+                let started = Instant::now();
+                match bind_listener(path) {
+                    Ok(listener) => {
+                        let endpoint = FrameEndpoint::Reverse(listener);
+                        self.run_framed(&endpoint, &mut report).await;
+                        let _ = std::fs::remove_file(path);
+                    }
+                    Err(e) => {
+                        report.record(
+                            Scenario::Handshake,
+                            Verdict::Fail(format!("failed to bind listener: {}", e)),
+                            started,
+                        );
+                        self.skip_remaining(report);
+                    }
+                }
+            }
+            ConformanceTarget::Grpc(endpoint) => self.run_grpc(endpoint, &mut report).await,
+        }
+
+        report
+    }
+
+    fn skip_remaining(&self, report: &mut ConformanceReport) {
+        for &scenario in &self.config.scenarios {
+            if scenario != Scenario::Handshake {
+                report.record(
+                    scenario,
+                    Verdict::Skip("handshake failed".to_string()),
+                    Instant::now(),
+                );
+            }
+        }
+    }
+
+    // =========================================================================
+    // UDS and reverse connections
+    // =========================================================================
+
+    async fn run_framed(&self, endpoint: &FrameEndpoint, report: &mut ConformanceReport) {
+        let started = Instant::now();
+        let handshake = endpoint.open(&self.config).await;
+        let verdict = match &handshake {
+            Ok(session) if session.capabilities.agent_id.is_empty() => {
+                Verdict::Fail("capabilities.agent_id is empty".to_string())
+            }
+            Ok(session) => {
+                report.agent_id = Some(session.capabilities.agent_id.clone());
+                report.protocol_version = Some(session.protocol_version);
+                report.encoding = Some(session.encoding);
+                Verdict::Pass(format!(
+                    "agent '{}' negotiated protocol v{} with {:?} encoding",
+                    session.capabilities.agent_id, session.protocol_version, session.encoding
+                ))
+            }
+            Err(e) => Verdict::Fail(e.clone()),
+        };
+        report.record(Scenario::Handshake, verdict, started);
+
+        let Ok(first_session) = handshake else {
+            self.skip_remaining(report);
+            return;
+        };
+
+        // Scenarios share a connection until one of them closes or fails it
+        let mut spare = Some(first_session);
+        for &scenario in &self.config.scenarios {
+            if scenario == Scenario::Handshake {
+                continue;
+            }
+            let started = Instant::now();
+            let session = match spare.take() {
+                Some(session) => Ok(session),
+                None => endpoint.open(&self.config).await,
+            };
+            let verdict = match session {
+                Ok(mut session) => {
+                    let verdict = self.run_framed_scenario(scenario, &mut session).await;
+                    // A failed scenario may leave a frame half read
+                    if session.reusable && !matches!(verdict, Verdict::Fail(_)) {
+                        spare = Some(session);
+                    }
+                    verdict
+                }
+                Err(e) => Verdict::Fail(format!("failed to open a new connection: {}", e)),
+            };
+            report.record(scenario, verdict, started);
+        }
+    }
+
+    async fn run_framed_scenario(&self, scenario: Scenario, session: &mut FrameSession) -> Verdict {
+        let timeout = self.config.timeout;
+        let features = session.capabilities.features.clone();
+        match scenario {
+            Scenario::Handshake => unreachable!("handshake runs when the session opens"),
+            Scenario::RequestHeaders => framed_request_headers(session, timeout).await.into(),
+            Scenario::BodyStreaming if !features.streaming_body => {
+                Verdict::Skip("agent does not advertise streaming_body".to_string())
+            }
+            Scenario::BodyStreaming => framed_body_streaming(session, timeout).await.into(),
+            Scenario::CancelMidBody if !features.cancellation => {
+                Verdict::Skip("agent does not advertise cancellation".to_string())
+            }
+            Scenario::CancelMidBody => framed_cancel_mid_body(session, timeout).await.into(),
+            Scenario::FlowControlPause if !features.flow_control => {
+                Verdict::Skip("agent does not advertise flow_control".to_string())
+            }
+            Scenario::FlowControlPause => {
+                framed_flow_control(session, self.config.burst_size, timeout)
+                    .await
+                    .into()
+            }
+            Scenario::Drain => framed_drain(session, timeout).await.into(),
+            Scenario::OversizedFrame => framed_oversized_frame(session, timeout).await.into(),
+            Scenario::UnknownMessageType => {
+                framed_unknown_message_type(session, timeout).await.into()
+            }
+            Scenario::Ping => framed_ping(session, timeout).await.into(),
+        }
+    }
+
+    // =========================================================================
+    // gRPC
+    // =========================================================================
+
+    async fn run_grpc(&self, endpoint: &str, report: &mut ConformanceReport) {
+        let timeout = self.config.timeout;
+        let started = Instant::now();
+        let client = match connect_grpc(&self.config.client_id, endpoint, timeout).await {
+            Ok(client) => client,
+            Err(e) => {
+                report.record(Scenario::Handshake, Verdict::Fail(e), started);
+                self.skip_remaining(report);
+                return;
+            }
+        };
+
+        let capabilities = client.capabilities().await;
+        let verdict = match &capabilities {
+            _ if client.protocol_version() != PROTOCOL_VERSION_2 => Verdict::Fail(format!(
+                "negotiated protocol v{}, expected v{}",
+                client.protocol_version(),
+                PROTOCOL_VERSION_2
+            )),
+            None => Verdict::Fail("handshake returned no capabilities".to_string()),
+            Some(c) => {
+                report.agent_id = Some(c.agent_id.clone());
+                report.protocol_version = Some(client.protocol_version());
+                Verdict::Pass(format!(
+                    "agent '{}' negotiated protocol v{}",
+                    c.agent_id,
+                    client.protocol_version()
+                ))
+            }
+        };
+        report.record(Scenario::Handshake, verdict, started);
+        let features = capabilities.map(|c| c.features).unwrap_or_default();
+
+        for &scenario in &self.config.scenarios {
+            let started = Instant::now();
+            let verdict = match scenario {
+                Scenario::Handshake => continue,
+                Scenario::RequestHeaders => grpc_request_headers(&client).await.into(),
+                Scenario::BodyStreaming if !features.streaming_body => {
+                    Verdict::Skip("agent does not advertise streaming_body".to_string())
+                }
+                Scenario::BodyStreaming => grpc_body_streaming(&client).await.into(),
+                Scenario::CancelMidBody if !features.cancellation => {
+                    Verdict::Skip("agent does not advertise cancellation".to_string())
+                }
+                Scenario::CancelMidBody => grpc_cancel_mid_body(&client).await.into(),
+                Scenario::FlowControlPause if !features.flow_control => {
+                    Verdict::Skip("agent does not advertise flow_control".to_string())
+                }
+                Scenario::FlowControlPause => {
+                    grpc_flow_control(&client, self.config.burst_size).await.into()
+                }
+                Scenario::Drain => Verdict::Skip(
+                    "drain is signalled on the gRPC control stream, which the client does not drive"
+                        .to_string(),
+                ),
+                Scenario::OversizedFrame | Scenario::UnknownMessageType => Verdict::Skip(
+                    "frame-level checks apply to UDS and reverse connections".to_string(),
+                ),
+                Scenario::Ping => {
+                    Verdict::Skip("the gRPC client does not surface pong replies".to_string())
+                }
+            };
+            report.record(scenario, verdict, started);
+        }
+
+        let _ = client.close().await;
+    }
+}
+
+// =============================================================================
+// Framed sessions
+// =============================================================================
+
+enum FrameEndpoint {
+    Uds(PathBuf),
+    Reverse(UnixListener),
+}
+
+/// What arrived on a framed connection.
+enum Incoming {
+    Frame(MessageType, Vec<u8>),
+    Closed,
+}
+
+struct FrameSession {
+    reader: BufReader<OwnedReadHalf>,
+    writer: BufWriter<OwnedWriteHalf>,
+    encoding: UdsEncoding,
+    capabilities: UdsCapabilities,
+    protocol_version: u32,
+    /// Flow control actions received so far (1 = pause, 2 = resume)
+    flow_actions: Vec<i64>,
+    /// Whether the connection can serve another scenario
+    reusable: bool,
+}
+
+fn bind_listener(path: &std::path::Path) -> std::io::Result<UnixListener> {
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    UnixListener::bind(path)
+}
+
+impl FrameEndpoint {
+    /// Open a connection and complete the handshake or registration.
+    ///
+    /// Returns a description of the first protocol violation on failure.
+    async fn open(&self, config: &ConformanceConfig) -> Result<FrameSession, String> {
+        match self {
+            FrameEndpoint::Uds(path) => {
+                let stream = tokio::time::timeout(config.timeout, UnixStream::connect(path))
+                    .await
+                    .map_err(|_| format!("connect timed out after {:?}", config.timeout))?
+                    .map_err(|e| format!("failed to connect: {}", e))?;
+                uds_handshake(stream, config).await
+            }
+            FrameEndpoint::Reverse(listener) => {
+                let (stream, _) = tokio::time::timeout(config.timeout, listener.accept())
+                    .await
+                    .map_err(|_| format!("agent did not connect within {:?}", config.timeout))?
+                    .map_err(|e| format!("accept failed: {}", e))?;
+                reverse_registration(stream, config).await
+            }
+        }
+    }
+}
+
+async fn uds_handshake(
+    stream: UnixStream,
+    config: &ConformanceConfig,
+) -> Result<FrameSession, String> {
+    let (read_half, write_half) = stream.into_split();
+    let mut reader = BufReader::new(read_half);
+    let mut writer = BufWriter::new(write_half);
+
+    let request = UdsHandshakeRequest {
+        supported_versions: vec![PROTOCOL_VERSION_2],
+        proxy_id: config.client_id.clone(),
+        proxy_version: env!("CARGO_PKG_VERSION").to_string(),
+        config: None,
+        supported_encodings: config.encodings.clone(),
+    };
+    let payload = serde_json::to_vec(&request).map_err(|e| e.to_string())?;
+    write_message(&mut writer, MessageType::HandshakeRequest, &payload)
+        .await
+        .map_err(|e| format!("failed to send handshake: {}", e))?;
+
+    let payload = expect_frame(&mut reader, MessageType::HandshakeResponse, config.timeout).await?;
+    let response: UdsHandshakeResponse = serde_json::from_slice(&payload)
+        .map_err(|e| format!("handshake response is not valid JSON: {}", e))?;
+
+    if !response.success {
+        return Err(format!(
+            "agent rejected the handshake: {}",
+            response.error.unwrap_or_default()
+        ));
+    }
+    if response.protocol_version != PROTOCOL_VERSION_2 {
+        return Err(format!(
+            "agent answered with protocol v{}, expected v{}",
+            response.protocol_version, PROTOCOL_VERSION_2
+        ));
+    }
+    if !config.encodings.contains(&response.encoding) {
+        return Err(format!(
+            "agent chose {:?} encoding, which was not offered",
+            response.encoding
+        ));
+    }
+
+    Ok(FrameSession {
+        reader,
+        writer,
+        encoding: response.encoding,
+        capabilities: response.capabilities,
+        protocol_version: response.protocol_version,
+        flow_actions: Vec::new(),
+        reusable: true,
+    })
+}
+
+async fn reverse_registration(
+    stream: UnixStream,
+    config: &ConformanceConfig,
+) -> Result<FrameSession, String> {
+    let (read_half, write_half) = stream.into_split();
+    let mut reader = BufReader::new(read_half);
+    let mut writer = BufWriter::new(write_half);
+
+    // Registration reuses the handshake request type and is always JSON
+    let payload = expect_frame(&mut reader, MessageType::HandshakeRequest, config.timeout).await?;
+    let registration: RegistrationRequest = serde_json::from_slice(&payload)
+        .map_err(|e| format!("registration request is not valid JSON: {}", e))?;
+
+    if registration.protocol_version != PROTOCOL_VERSION_2 {
+        return Err(format!(
+            "agent registered with protocol v{}, expected v{}",
+            registration.protocol_version, PROTOCOL_VERSION_2
+        ));
+    }
+    if registration.agent_id.is_empty() {
+        return Err("registration has an empty agent_id".to_string());
+    }
+
+    let response = RegistrationResponse {
+        success: true,
+        error: None,
+        proxy_id: config.client_id.clone(),
+        proxy_version: env!("CARGO_PKG_VERSION").to_string(),
+        connection_id: format!("{}-conformance", registration.agent_id),
+    };
+    let payload = serde_json::to_vec(&response).map_err(|e| e.to_string())?;
+    write_message(&mut writer, MessageType::HandshakeResponse, &payload)
+        .await
+        .map_err(|e| format!("failed to send registration response: {}", e))?;
+
+    Ok(FrameSession {
+        reader,
+        writer,
+        encoding: UdsEncoding::Json,
+        capabilities: registration.capabilities,
+        protocol_version: registration.protocol_version,
+        flow_actions: Vec::new(),
+        reusable: true,
+    })
+}
+
+async fn expect_frame(
+    reader: &mut BufReader<OwnedReadHalf>,
+    expected: MessageType,
+    timeout: Duration,
+) -> Result<Vec<u8>, String> {
+    let (msg_type, payload) = tokio::time::timeout(timeout, read_message(reader))
+        .await
+        .map_err(|_| format!("no {:?} within {:?}", expected, timeout))?
+        .map_err(|e| format!("invalid frame while waiting for {:?}: {}", expected, e))?;
+    if msg_type != expected {
+        return Err(format!("expected {:?}, got {:?}", expected, msg_type));
+    }
+    Ok(payload)
+}
+
+impl FrameSession {
+    async fn send_event<T: Serialize>(
+        &mut self,
+        msg_type: MessageType,
+        correlation_id: &str,
+        event: &T,
+    ) -> Result<(), String> {
+        let payload =
+            encode_event(self.encoding, correlation_id, event).map_err(|e| e.to_string())?;
+        self.send_frame(msg_type, &payload).await
+    }
+
+    /// Send a control frame; the proxy always encodes these as JSON.
+    async fn send_json(
+        &mut self,
+        msg_type: MessageType,
+        value: serde_json::Value,
+    ) -> Result<(), String> {
+        let payload = serde_json::to_vec(&value).map_err(|e| e.to_string())?;
+        self.send_frame(msg_type, &payload).await
+    }
+
+    async fn send_frame(&mut self, msg_type: MessageType, payload: &[u8]) -> Result<(), String> {
+        write_message(&mut self.writer, msg_type, payload)
+            .await
+            .map_err(|e| format!("failed to send {:?}: {}", msg_type, e))
+    }
+
+    async fn send_cancel(
+        &mut self,
+        correlation_id: &str,
+        reason: CancelReason,
+    ) -> Result<(), String> {
+        self.send_json(
+            MessageType::Cancel,
+            serde_json::json!({
+                "correlation_id": correlation_id,
+                "reason": reason as i32,
+                "timestamp_ms": now_ms(),
+            }),
+        )
+        .await
+    }
+
+    /// Write a frame header by hand, bypassing the size and type checks.
+    async fn send_raw_header(&mut self, declared_len: u32, type_byte: u8) -> Result<(), String> {
+        let mut header = declared_len.to_be_bytes().to_vec();
+        header.push(type_byte);
+        self.writer
+            .write_all(&header)
+            .await
+            .map_err(|e| format!("failed to write frame header: {}", e))?;
+        self.writer
+            .flush()
+            .await
+            .map_err(|e| format!("failed to write frame header: {}", e))
+    }
+
+    async fn recv(&mut self, timeout: Duration) -> Result<Incoming, String> {
+        match tokio::time::timeout(timeout, read_message(&mut self.reader)).await {
+            Err(_) => Err(format!("no frame from the agent within {:?}", timeout)),
+            Ok(Ok((msg_type, payload))) => Ok(Incoming::Frame(msg_type, payload)),
+            Ok(Err(AgentProtocolError::ConnectionClosed)) => Ok(Incoming::Closed),
+            Ok(Err(AgentProtocolError::Io(e)))
+                if e.kind() == std::io::ErrorKind::ConnectionReset =>
+            {
+                Ok(Incoming::Closed)
+            }
+            Ok(Err(e)) => Err(format!("agent sent an invalid frame: {}", e)),
+        }
+    }
+
+    /// Wait for the next agent response or pong, handling control frames on the way.
+    ///
+    /// Returns the frame type and, for responses, the correlation ID.
+    async fn recv_reply(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Option<(MessageType, Option<(String, AgentResponse)>)>, String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let (msg_type, payload) = match self.recv(remaining).await? {
+                Incoming::Closed => return Ok(None),
+                Incoming::Frame(msg_type, payload) => (msg_type, payload),
+            };
+            match msg_type {
+                MessageType::AgentResponse => {
+                    let response: AgentResponse = self
+                        .encoding
+                        .deserialize(&payload)
+                        .map_err(|e| format!("agent response does not decode: {}", e))?;
+                    let correlation_id = response
+                        .audit
+                        .custom
+                        .get("correlation_id")
+                        .and_then(|v| v.as_str())
+                        .ok_or_else(|| {
+                            "agent response has no audit.custom.correlation_id".to_string()
+                        })?
+                        .to_string();
+                    return Ok(Some((msg_type, Some((correlation_id, response)))));
+                }
+                MessageType::Pong => return Ok(Some((msg_type, None))),
+                MessageType::FlowControl => {
+                    self.record_flow_action(&payload)?;
+                }
+                MessageType::HealthStatus
+                | MessageType::MetricsReport
+                | MessageType::ConfigUpdateRequest => {}
+                other => return Err(format!("agent sent proxy-bound message {:?}", other)),
+            }
+        }
+    }
+
+    fn record_flow_action(&mut self, payload: &[u8]) -> Result<i64, String> {
+        #[derive(Deserialize)]
+        struct FlowControlMsg {
+            action: Option<i64>,
+        }
+        let action = self
+            .encoding
+            .deserialize::<FlowControlMsg>(payload)
+            .map_err(|e| format!("flow control signal does not decode: {}", e))?
+            .action;
+        match action {
+            Some(action @ (1 | 2)) => {
+                self.flow_actions.push(action);
+                Ok(action)
+            }
+            other => Err(format!(
+                "flow control signal has invalid action {:?}",
+                other
+            )),
+        }
+    }
+
+    /// Wait for the response to `correlation_id`.
+    async fn expect_response(
+        &mut self,
+        correlation_id: &str,
+        timeout: Duration,
+    ) -> Result<AgentResponse, String> {
+        match self.recv_reply(timeout).await {
+            Ok(Some((_, Some((id, response))))) if id == correlation_id => Ok(response),
+            Ok(Some((_, Some((id, _))))) => Err(format!(
+                "expected a response for '{}', got one for '{}'",
+                correlation_id, id
+            )),
+            Ok(Some((msg_type, None))) => Err(format!(
+                "expected a response for '{}', got {:?}",
+                correlation_id, msg_type
+            )),
+            Ok(None) => Err(format!(
+                "agent closed the connection instead of answering '{}'",
+                correlation_id
+            )),
+            Err(e) => Err(format!("while waiting for '{}': {}", correlation_id, e)),
+        }
+    }
+
+    async fn request(
+        &mut self,
+        msg_type: MessageType,
+        correlation_id: &str,
+        event: &impl Serialize,
+        timeout: Duration,
+    ) -> Result<AgentResponse, String> {
+        self.send_event(msg_type, correlation_id, event).await?;
+        self.expect_response(correlation_id, timeout).await
+    }
+}
+
+async fn framed_request_headers(
+    session: &mut FrameSession,
+    timeout: Duration,
+) -> Result<String, String> {
+    let id = "conformance-headers";
+    let response = session
+        .request(MessageType::RequestHeaders, id, &headers_event(id), timeout)
+        .await?;
+    Ok(format!("answered with decision {:?}", response.decision))
+}
+
+async fn framed_body_streaming(
+    session: &mut FrameSession,
+    timeout: Duration,
+) -> Result<String, String> {
+    const CHUNKS: u32 = 3;
+    let id = "conformance-body";
+    session
+        .request(MessageType::RequestHeaders, id, &headers_event(id), timeout)
+        .await?;
+    for index in 0..CHUNKS {
+        session
+            .request(
+                MessageType::RequestBodyChunk,
+                id,
+                &body_chunk_event(id, index, index + 1 == CHUNKS),
+                timeout,
+            )
+            .await
+            .map_err(|e| format!("chunk {}: {}", index, e))?;
+    }
+    Ok(format!(
+        "answered headers and {} body chunks in order",
+        CHUNKS
+    ))
+}
+
+async fn framed_cancel_mid_body(
+    session: &mut FrameSession,
+    timeout: Duration,
+) -> Result<String, String> {
+    let cancelled = "conformance-cancelled";
+    let next = "conformance-after-cancel";
+    session
+        .request(
+            MessageType::RequestHeaders,
+            cancelled,
+            &headers_event(cancelled),
+            timeout,
+        )
+        .await?;
+    session
+        .request(
+            MessageType::RequestBodyChunk,
+            cancelled,
+            &body_chunk_event(cancelled, 0, false),
+            timeout,
+        )
+        .await?;
+
+    // The second chunk is still in flight when the cancel goes out, so one
+    // late reply for it is tolerated; anything more is not.
+    session
+        .send_event(
+            MessageType::RequestBodyChunk,
+            cancelled,
+            &body_chunk_event(cancelled, 1, false),
+        )
+        .await?;
+    session
+        .send_cancel(cancelled, CancelReason::ClientDisconnect)
+        .await?;
+    session
+        .send_event(MessageType::RequestHeaders, next, &headers_event(next))
+        .await?;
+
+    let mut late_replies = 0;
+    loop {
+        match session.recv_reply(timeout).await? {
+            Some((_, Some((id, _)))) if id == next => break,
+            Some((_, Some((id, _)))) if id == cancelled && late_replies == 0 => late_replies += 1,
+            Some((_, Some((id, _)))) if id == cancelled => {
+                return Err("agent kept answering a cancelled request".to_string())
+            }
+            Some((_, Some((id, _)))) => {
+                return Err(format!("agent answered unknown correlation ID '{}'", id))
+            }
+            Some((msg_type, None)) => return Err(format!("unexpected {:?}", msg_type)),
+            None => return Err("agent closed the connection after a cancel".to_string()),
+        }
+    }
+    Ok(format!(
+        "stopped answering the cancelled request ({} late reply) and served the next one",
+        late_replies
+    ))
+}
+
+async fn framed_flow_control(
+    session: &mut FrameSession,
+    burst_size: usize,
+    timeout: Duration,
+) -> Result<String, String> {
+    let ids: Vec<String> = (0..burst_size)
+        .map(|i| format!("conformance-burst-{}", i))
+        .collect();
+    for id in &ids {
+        session
+            .send_event(MessageType::RequestHeaders, id, &headers_event(id))
+            .await?;
+    }
+
+    // Requests already sent when the agent pauses are still its to answer
+    let mut outstanding: HashSet<&str> = ids.iter().map(String::as_str).collect();
+    while !outstanding.is_empty() {
+        match session.recv_reply(timeout).await? {
+            Some((_, Some((id, _)))) => {
+                if !outstanding.remove(id.as_str()) {
+                    return Err(format!("unexpected or duplicate response for '{}'", id));
+                }
+            }
+            Some((msg_type, None)) => return Err(format!("unexpected {:?}", msg_type)),
+            None => {
+                return Err(format!(
+                    "agent closed the connection with {} requests unanswered",
+                    outstanding.len()
+                ))
+            }
+        }
+    }
+
+    // A pause must eventually be lifted
+    if session.flow_actions.last() == Some(&1) {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let Ok(Incoming::Frame(msg_type, payload)) = session.recv(remaining).await else {
+                return Err(format!(
+                    "agent paused and did not resume within {:?}",
+                    timeout
+                ));
+            };
+            if msg_type == MessageType::FlowControl && session.record_flow_action(&payload)? == 2 {
+                break;
+            }
+        }
+    }
+
+    let pauses = session.flow_actions.iter().filter(|&&a| a == 1).count();
+    Ok(format!(
+        "answered a burst of {} requests with {} pause/resume cycles",
+        burst_size, pauses
+    ))
+}
+
+async fn framed_drain(session: &mut FrameSession, timeout: Duration) -> Result<String, String> {
+    // UDS has no drain frame: replay what the pool does when it drains, which
+    // is cancel everything in flight and close the connection.
+    session.reusable = false;
+    let ids: Vec<String> = (0..3).map(|i| format!("conformance-drain-{}", i)).collect();
+    for id in &ids {
+        session
+            .send_event(MessageType::RequestHeaders, id, &headers_event(id))
+            .await?;
+    }
+    for id in &ids {
+        session.send_cancel(id, CancelReason::ProxyShutdown).await?;
+    }
+    session
+        .writer
+        .shutdown()
+        .await
+        .map_err(|e| format!("failed to half-close the connection: {}", e))?;
+
+    let mut replies = 0;
+    loop {
+        match session.recv_reply(timeout).await {
+            Ok(None) => break,
+            Ok(Some((_, Some((id, _))))) if ids.contains(&id) => replies += 1,
+            Ok(Some((_, Some((id, _))))) => {
+                return Err(format!("agent answered unknown correlation ID '{}'", id))
+            }
+            Ok(Some(_)) => {}
+            Err(_) => {
+                return Err(format!(
+                    "agent kept the connection open {:?} after the proxy drained it",
+                    timeout
+                ))
+            }
+        }
+    }
+    Ok(format!(
+        "closed the connection after draining ({} late replies)",
+        replies
+    ))
+}
+
+async fn framed_oversized_frame(
+    session: &mut FrameSession,
+    timeout: Duration,
+) -> Result<String, String> {
+    session.reusable = false;
+    let declared = MAX_UDS_MESSAGE_SIZE as u32 + 1;
+    session
+        .send_raw_header(declared, MessageType::RequestBodyChunk as u8)
+        .await?;
+
+    match session.recv_reply(timeout).await {
+        Ok(None) => Ok(format!(
+            "closed the connection on a frame declaring {} bytes",
+            declared
+        )),
+        Ok(Some(_)) => Err("agent answered instead of rejecting an oversized frame".to_string()),
+        Err(_) => Err(format!(
+            "agent did not close the connection within {:?} after a frame declaring {} bytes (limit {})",
+            timeout, declared, MAX_UDS_MESSAGE_SIZE
+        )),
+    }
+}
+
+async fn framed_unknown_message_type(
+    session: &mut FrameSession,
+    timeout: Duration,
+) -> Result<String, String> {
+    let payload = b"{}";
+    session
+        .send_raw_header(payload.len() as u32 + 1, UNASSIGNED_MESSAGE_TYPE)
+        .await?;
+    session
+        .writer
+        .write_all(payload)
+        .await
+        .map_err(|e| format!("failed to write payload: {}", e))?;
+
+    // The agent may drop the frame or the connection; a closed connection
+    // fails the follow-up write or read, so a follow-up answer means ignored.
+    let id = "conformance-after-unknown";
+    if session
+        .send_event(MessageType::RequestHeaders, id, &headers_event(id))
+        .await
+        .is_err()
+    {
+        session.reusable = false;
+        return Ok("closed the connection on an unknown message type".to_string());
+    }
+    match session.recv_reply(timeout).await? {
+        None => {
+            session.reusable = false;
+            Ok("closed the connection on an unknown message type".to_string())
+        }
+        Some((_, Some((reply_id, _)))) if reply_id == id => {
+            Ok("ignored the unknown message type and kept serving".to_string())
+        }
+        Some((_, Some((reply_id, _)))) => Err(format!(
+            "agent answered an unknown message type (correlation ID '{}')",
+            reply_id
+        )),
+        Some((msg_type, None)) => Err(format!("unexpected {:?}", msg_type)),
+    }
+}
+
+async fn framed_ping(session: &mut FrameSession, timeout: Duration) -> Result<String, String> {
+    let started = Instant::now();
+    session
+        .send_json(
+            MessageType::Ping,
+            serde_json::json!({ "sequence": 0, "timestamp_ms": now_ms() }),
+        )
+        .await?;
+    match session.recv_reply(timeout).await? {
+        Some((MessageType::Pong, _)) => Ok(format!("pong after {:?}", started.elapsed())),
+        Some((msg_type, _)) => Err(format!("expected Pong, got {:?}", msg_type)),
+        None => Err("agent closed the connection instead of answering a ping".to_string()),
+    }
+}
+
+// =============================================================================
+// gRPC checks
+// =============================================================================
+
+async fn connect_grpc(
+    client_id: &str,
+    endpoint: &str,
+    timeout: Duration,
+) -> Result<AgentClientV2, String> {
+    let client = AgentClientV2::new(client_id, endpoint, timeout)
+        .await
+        .map_err(|e| e.to_string())?;
+    client.connect().await.map_err(|e| e.to_string())?;
+    Ok(client)
+}
+
+async fn grpc_request_headers(client: &AgentClientV2) -> Result<String, String> {
+    let id = "conformance-headers";
+    let response = client
+        .send_request_headers(id, &headers_event(id))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(format!("answered with decision {:?}", response.decision))
+}
+
+async fn grpc_body_streaming(client: &AgentClientV2) -> Result<String, String> {
+    const CHUNKS: u32 = 3;
+    let id = "conformance-body";
+    client
+        .send_request_headers(id, &headers_event(id))
+        .await
+        .map_err(|e| e.to_string())?;
+    for index in 0..CHUNKS {
+        client
+            .send_request_body_chunk(id, &body_chunk_event(id, index, index + 1 == CHUNKS))
+            .await
+            .map_err(|e| format!("chunk {}: {}", index, e))?;
+    }
+    Ok(format!(
+        "answered headers and {} body chunks in order",
+        CHUNKS
+    ))
+}
+
+async fn grpc_cancel_mid_body(client: &AgentClientV2) -> Result<String, String> {
+    let cancelled = "conformance-cancelled";
+    let next = "conformance-after-cancel";
+    client
+        .send_request_headers(cancelled, &headers_event(cancelled))
+        .await
+        .map_err(|e| e.to_string())?;
+    client
+        .send_request_body_chunk(cancelled, &body_chunk_event(cancelled, 0, false))
+        .await
+        .map_err(|e| e.to_string())?;
+    client
+        .cancel_request(cancelled, CancelReason::ClientDisconnect)
+        .await
+        .map_err(|e| format!("failed to send cancel: {}", e))?;
+    client
+        .send_request_headers(next, &headers_event(next))
+        .await
+        .map_err(|e| format!("request after cancel: {}", e))?;
+    Ok("served the next request after a cancel".to_string())
+}
+
+async fn grpc_flow_control(client: &AgentClientV2, burst_size: usize) -> Result<String, String> {
+    let ids: Vec<String> = (0..burst_size)
+        .map(|i| format!("conformance-burst-{}", i))
+        .collect();
+    let results = futures::future::join_all(
+        ids.iter()
+            .map(|id| async move { client.send_request_headers(id, &headers_event(id)).await }),
+    )
+    .await;
+    let failures = results.iter().filter(|r| r.is_err()).count();
+    if failures > 0 {
+        return Err(format!(
+            "{} of {} burst requests were not answered",
+            failures, burst_size
+        ));
+    }
+    Ok(format!("answered a burst of {} requests", burst_size))
+}
+
+// =============================================================================
+// Events
+// =============================================================================
+
+fn headers_event(correlation_id: &str) -> RequestHeadersEvent {
+    RequestHeadersEvent {
+        metadata: RequestMetadata {
+            correlation_id: correlation_id.to_string(),
+            request_id: correlation_id.to_string(),
+            client_ip: "127.0.0.1".to_string(),
+            client_port: 40000,
+            server_name: Some("conformance.test".to_string()),
+            protocol: "HTTP/1.1".to_string(),
+            tls_version: None,
+            tls_cipher: None,
+            route_id: Some("conformance".to_string()),
+            upstream_id: Some("conformance".to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            traceparent: None,
+            client_cert: None,
+        },
+        method: "POST".to_string(),
+        uri: "/conformance".to_string(),
+        headers: [
+            ("host".to_string(), vec!["conformance.test".to_string()]),
+            ("content-type".to_string(), vec!["text/plain".to_string()]),
+        ]
+        .into_iter()
+        .collect(),
+    }
+}
+
+fn body_chunk_event(
+    correlation_id: &str,
+    chunk_index: u32,
+    is_last: bool,
+) -> RequestBodyChunkEvent {
+    let data = format!("conformance chunk {}\n", chunk_index);
+    RequestBodyChunkEvent {
+        correlation_id: correlation_id.to_string(),
+        bytes_received: data.len() * (chunk_index as usize + 1),
+        data: STANDARD.encode(data),
+        is_last,
+        total_size: None,
+        chunk_index,
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_counts_and_json() {
+        let mut report = ConformanceReport::new(&ConformanceTarget::Uds("/tmp/agent.sock".into()));
+        report.record(
+            Scenario::Handshake,
+            Verdict::Pass("ok".to_string()),
+            Instant::now(),
+        );
+        report.record(
+            Scenario::Ping,
+            Verdict::Skip("n/a".to_string()),
+            Instant::now(),
+        );
+        assert!(report.is_conformant());
+
+        report.record(
+            Scenario::Drain,
+            Verdict::Fail("kept open".to_string()),
+            Instant::now(),
+        );
+        assert!(!report.is_conformant());
+        assert_eq!((report.passed, report.failed, report.skipped), (1, 1, 1));
+        assert_eq!(
+            report.check(Scenario::Drain).unwrap().status,
+            CheckStatus::Fail
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["transport"], "uds");
+        assert_eq!(json["checks"][2]["scenario"], "drain");
+        assert_eq!(json["checks"][2]["status"], "fail");
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_agent_fails_handshake_only() {
+        let report = ConformanceRunner::new(ConformanceTarget::Uds(
+            "/tmp/sentinel-conformance-missing.sock".into(),
+        ))
+        .run()
+        .await;
+
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.skipped, Scenario::ALL.len() - 1);
+        assert_eq!(
+            report.check(Scenario::Handshake).unwrap().status,
+            CheckStatus::Fail
+        );
+    }
+}
@@ -53,6 +53,21 @@ pub enum ConfigUpdateType {
     ListUpdate { list_id: String, add: Vec<String>, remove: Vec<String> },
     RestartRequired { reason: String, grace_period_ms: u64 },
     ConfigError { error: String, field: Option<String> },
+    /// Drop all decisions the proxy has cached for this agent
+    InvalidateCache,
+}
+
+impl ConfigUpdateType {
+    /// Whether this update can change the decisions an agent returns,
+    /// making its cached decisions stale.
+    pub fn invalidates_cache(&self) -> bool {
+        matches!(
+            self,
+            ConfigUpdateType::InvalidateCache
+                | ConfigUpdateType::RuleUpdate { .. }
+                | ConfigUpdateType::ListUpdate { .. }
+        )
+    }
 }
 
 /// A rule definition.
@@ -0,0 +1,829 @@
+//! HTTP transport for Agent Protocol v2.
+//!
+//! This binding lets agents built on ordinary web stacks (serverless
+//! functions, PHP, Python/WSGI, ...) speak v2 without holding a long-lived
+//! stream open. Every event is a single HTTP request; health and metrics are
+//! polled instead of pushed.
+//!
+//! # Endpoints
+//!
+//! All paths are relative to the agent's base URL:
+//!
+//! | Method | Path | Body | Response |
+//! |--------|------|------|----------|
+//! | `POST` | `/v2/handshake` | [`HttpHandshakeRequest`] (JSON) | [`HttpHandshakeResponse`] (JSON) |
+//! | `POST` | `/v2/events/request-headers` | event | `AgentResponse` |
+//! | `POST` | `/v2/events/request-body-chunk` | event | `AgentResponse` |
+//! | `POST` | `/v2/events/response-headers` | event | `AgentResponse` |
+//! | `POST` | `/v2/events/response-body-chunk` | event | `AgentResponse` |
+//! | `POST` | `/v2/cancel` | `{correlation_id, reason, timestamp_ms}` (JSON) | ignored |
+//! | `GET`  | `/v2/health` | - | `HealthStatus` (JSON) |
+//! | `GET`  | `/v2/metrics` | - | `MetricsReport` (JSON), or `204` |
+//!
+//! Event bodies use the encoding negotiated during the handshake: JSON
+//! carries the v2 event with an added `correlation_id` field (as over UDS),
+//! while protobuf carries a `ProxyToAgent` message and expects an
+//! `AgentResponse` message back (as over gRPC). The correlation ID is also
+//! sent in the `x-sentinel-correlation-id` header.
+//!
+//! # HTTP Versions
+//!
+//! HTTPS endpoints negotiate HTTP/2 through ALPN and fall back to HTTP/1.1.
+//! Plain HTTP endpoints use HTTP/1.1 with keep-alive, since most web stacks
+//! do not accept cleartext HTTP/2 (h2c).
+//!
+//! # Flow Control
+//!
+//! A `429` or `503` answer to an event pauses the connection until the next
+//! health poll reports the agent healthy again. A `Draining` health state
+//! stops new requests in the same way.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use prost::Message;
+use reqwest::StatusCode;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, trace, warn};
+
+use crate::grpc_v2::{self, ProxyToAgent};
+use crate::v2::{AgentCapabilities, HealthState, HealthStatus, MetricsReport, PROTOCOL_VERSION_2};
+use crate::{AgentProtocolError, AgentResponse};
+
+use super::client::{
+    convert_body_chunk_to_grpc, convert_request_headers_to_grpc,
+    convert_response_body_chunk_to_grpc, convert_response_from_grpc,
+    convert_response_headers_to_grpc, CancelReason, FlowState, MetricsCallback,
+};
+use super::uds::UdsCapabilities;
+
+/// Prefix that marks an [`AgentPool`](super::AgentPool) endpoint as an HTTP agent.
+///
+/// Plain `http://` and `https://` endpoints are gRPC, so HTTP agents are
+/// addressed as `sentinel+http://host:port/base` or `sentinel+https://...`.
+pub const HTTP_ENDPOINT_PREFIX: &str = "sentinel+";
+
+/// Header carrying the correlation ID of an event.
+pub const CORRELATION_ID_HEADER: &str = "x-sentinel-correlation-id";
+
+/// Default interval between health and metrics polls.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Body encoding for HTTP transport events.
+///
+/// Negotiated during handshake. The proxy sends its supported encodings,
+/// and the agent responds with the chosen encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpEncoding {
+    /// JSON bodies (default, always supported)
+    #[default]
+    Json,
+    /// Protobuf bodies using the v2 gRPC message types
+    Protobuf,
+}
+
+impl HttpEncoding {
+    /// Content type used for event bodies.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            HttpEncoding::Json => "application/json",
+            HttpEncoding::Protobuf => "application/x-protobuf",
+        }
+    }
+}
+
+/// Handshake request sent from proxy to agent over HTTP.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HttpHandshakeRequest {
+    pub supported_versions: Vec<u32>,
+    pub proxy_id: String,
+    pub proxy_version: String,
+    pub config: Option<serde_json::Value>,
+    /// Supported body encodings (in order of preference).
+    pub supported_encodings: Vec<HttpEncoding>,
+}
+
+/// Handshake response from agent to proxy over HTTP.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HttpHandshakeResponse {
+    pub protocol_version: u32,
+    pub capabilities: UdsCapabilities,
+    pub success: bool,
+    pub error: Option<String>,
+    /// Negotiated encoding for event bodies. Defaults to JSON if missing.
+    #[serde(default)]
+    pub encoding: HttpEncoding,
+}
+
+/// Extract the base URL from an `sentinel+http(s)://` pool endpoint.
+///
+/// Returns `None` if the endpoint is not an HTTP agent endpoint.
+pub fn http_base_url(endpoint: &str) -> Option<&str> {
+    let url = endpoint.strip_prefix(HTTP_ENDPOINT_PREFIX)?;
+    if url.starts_with("http://") || url.starts_with("https://") {
+        Some(url.trim_end_matches('/'))
+    } else {
+        None
+    }
+}
+
+/// Flow state implied by a polled health status.
+fn flow_state_for_health(state: &HealthState) -> FlowState {
+    match state {
+        HealthState::Healthy | HealthState::Degraded { .. } => FlowState::Normal,
+        HealthState::Draining { .. } => FlowState::Draining,
+        HealthState::Unhealthy { .. } => FlowState::Paused,
+    }
+}
+
+/// State shared between the client and its polling task.
+struct Shared {
+    agent_id: String,
+    base_url: String,
+    http: reqwest::Client,
+    connected: RwLock<bool>,
+    flow_state: RwLock<FlowState>,
+}
+
+impl Shared {
+    fn url(&self, path: &str) -> String {
+        format!("{}/v2/{}", self.base_url, path)
+    }
+
+    /// Poll `/v2/health` and update connection and flow state.
+    async fn poll_health(&self) {
+        let result = async {
+            let response = self.http.get(self.url("health")).send().await?;
+            response.error_for_status()?.json::<HealthStatus>().await
+        }
+        .await;
+
+        match result {
+            Ok(status) => {
+                let flow = flow_state_for_health(&status.state);
+                trace!(agent_id = %self.agent_id, state = ?status.state, "Polled agent health");
+                *self.connected.write().await = true;
+                *self.flow_state.write().await = flow;
+            }
+            Err(e) => {
+                warn!(agent_id = %self.agent_id, error = %e, "HTTP agent health poll failed");
+                *self.connected.write().await = false;
+            }
+        }
+    }
+
+    /// Poll `/v2/metrics` and forward the report.
+    async fn poll_metrics(&self, callback: &MetricsCallback) {
+        let result = async {
+            let response = self
+                .http
+                .get(self.url("metrics"))
+                .send()
+                .await?
+                .error_for_status()?;
+            if response.status() == StatusCode::NO_CONTENT {
+                return Ok(None);
+            }
+            response.json::<MetricsReport>().await.map(Some)
+        }
+        .await;
+
+        match result {
+            Ok(Some(mut report)) => {
+                if report.agent_id.is_empty() {
+                    report.agent_id = self.agent_id.clone();
+                }
+                callback(report);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                debug!(agent_id = %self.agent_id, error = %e, "HTTP agent metrics poll failed");
+            }
+        }
+    }
+}
+
+/// v2 agent client over HTTP.
+///
+/// Requests are independent HTTP exchanges, so concurrency is bounded only
+/// by the underlying connection pool. Health and metrics are polled by a
+/// background task started on [`connect`](Self::connect).
+pub struct AgentClientV2Http {
+    /// State shared with the polling task
+    shared: Arc<Shared>,
+    /// Request timeout
+    timeout: Duration,
+    /// Interval between health and metrics polls
+    poll_interval: Duration,
+    /// Negotiated capabilities
+    capabilities: RwLock<Option<AgentCapabilities>>,
+    /// Negotiated body encoding
+    encoding: RwLock<HttpEncoding>,
+    /// Correlation IDs of in-flight requests
+    pending: Mutex<HashSet<String>>,
+    /// In-flight request count
+    in_flight: AtomicU64,
+    /// Callback for polled metrics reports
+    metrics_callback: Option<MetricsCallback>,
+    /// Health and metrics polling task
+    poll_task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+}
+
+impl AgentClientV2Http {
+    /// Create a new HTTP v2 client.
+    ///
+    /// `base_url` is the agent's `http://` or `https://` base URL.
+    pub async fn new(
+        agent_id: impl Into<String>,
+        base_url: impl Into<String>,
+        timeout: Duration,
+    ) -> Result<Self, AgentProtocolError> {
+        let agent_id = agent_id.into();
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+
+        debug!(
+            agent_id = %agent_id,
+            base_url = %base_url,
+            timeout_ms = timeout.as_millis(),
+            "Creating HTTP v2 client"
+        );
+
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .connect_timeout(timeout)
+            .build()
+            .map_err(|e| AgentProtocolError::ConnectionFailed(e.to_string()))?;
+
+        Ok(Self {
+            shared: Arc::new(Shared {
+                agent_id,
+                base_url,
+                http,
+                connected: RwLock::new(false),
+                flow_state: RwLock::new(FlowState::Normal),
+            }),
+            timeout,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            capabilities: RwLock::new(None),
+            encoding: RwLock::new(HttpEncoding::Json),
+            pending: Mutex::new(HashSet::new()),
+            in_flight: AtomicU64::new(0),
+            metrics_callback: None,
+            poll_task: parking_lot::Mutex::new(None),
+        })
+    }
+
+    /// Set the interval between health and metrics polls.
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = interval;
+    }
+
+    /// Set the metrics callback.
+    ///
+    /// Metrics are only polled if the agent advertises `metrics_export`.
+    pub fn set_metrics_callback(&mut self, callback: MetricsCallback) {
+        self.metrics_callback = Some(callback);
+    }
+
+    /// Get the current negotiated encoding.
+    pub async fn encoding(&self) -> HttpEncoding {
+        *self.encoding.read().await
+    }
+
+    /// Perform the handshake and start polling health and metrics.
+    pub async fn connect(&self) -> Result<(), AgentProtocolError> {
+        info!(
+            agent_id = %self.shared.agent_id,
+            base_url = %self.shared.base_url,
+            "Connecting to agent via HTTP v2"
+        );
+
+        let handshake_req = HttpHandshakeRequest {
+            supported_versions: vec![PROTOCOL_VERSION_2],
+            proxy_id: "sentinel-proxy".to_string(),
+            proxy_version: env!("CARGO_PKG_VERSION").to_string(),
+            config: None,
+            supported_encodings: vec![HttpEncoding::Protobuf, HttpEncoding::Json],
+        };
+
+        let response = self
+            .shared
+            .http
+            .post(self.shared.url("handshake"))
+            .json(&handshake_req)
+            .send()
+            .await
+            .map_err(|e| {
+                error!(
+                    agent_id = %self.shared.agent_id,
+                    base_url = %self.shared.base_url,
+                    error = %e,
+                    "Failed to connect to agent via HTTP"
+                );
+                self.request_error(e)
+            })?;
+
+        if !response.status().is_success() {
+            return Err(AgentProtocolError::ConnectionFailed(format!(
+                "Handshake failed with HTTP {}",
+                response.status()
+            )));
+        }
+
+        let response: HttpHandshakeResponse = response
+            .json()
+            .await
+            .map_err(|e| AgentProtocolError::InvalidMessage(e.to_string()))?;
+
+        if !response.success {
+            return Err(AgentProtocolError::ConnectionFailed(
+                response
+                    .error
+                    .unwrap_or_else(|| "Unknown handshake error".to_string()),
+            ));
+        }
+
+        let capabilities: AgentCapabilities = response.capabilities.into();
+        let poll_metrics = capabilities.features.metrics_export;
+        *self.capabilities.write().await = Some(capabilities);
+        *self.encoding.write().await = response.encoding;
+        *self.shared.connected.write().await = true;
+
+        info!(
+            agent_id = %self.shared.agent_id,
+            protocol_version = response.protocol_version,
+            encoding = ?response.encoding,
+            "HTTP v2 handshake successful"
+        );
+
+        // Spawn health and metrics polling task
+        let shared = Arc::clone(&self.shared);
+        let metrics_callback = self.metrics_callback.clone().filter(|_| poll_metrics);
+        let interval = self.poll_interval;
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes immediately and the handshake just succeeded
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                shared.poll_health().await;
+                if let Some(ref callback) = metrics_callback {
+                    shared.poll_metrics(callback).await;
+                }
+            }
+        });
+
+        if let Some(previous) = self.poll_task.lock().replace(task) {
+            previous.abort();
+        }
+
+        Ok(())
+    }
+
+    /// Get negotiated capabilities.
+    pub async fn capabilities(&self) -> Option<AgentCapabilities> {
+        self.capabilities.read().await.clone()
+    }
+
+    /// Check if connected.
+    ///
+    /// Reflects the outcome of the handshake and the most recent health poll.
+    pub async fn is_connected(&self) -> bool {
+        *self.shared.connected.read().await
+    }
+
+    /// Send a request headers event.
+    pub async fn send_request_headers(
+        &self,
+        correlation_id: &str,
+        event: &crate::RequestHeadersEvent,
+    ) -> Result<AgentResponse, AgentProtocolError> {
+        self.send_event("request-headers", correlation_id, event, || ProxyToAgent {
+            message: Some(grpc_v2::proxy_to_agent::Message::RequestHeaders(
+                convert_request_headers_to_grpc(event),
+            )),
+        })
+        .await
+    }
+
+    /// Send a request body chunk event.
+    pub async fn send_request_body_chunk(
+        &self,
+        correlation_id: &str,
+        event: &crate::RequestBodyChunkEvent,
+    ) -> Result<AgentResponse, AgentProtocolError> {
+        self.send_event("request-body-chunk", correlation_id, event, || {
+            ProxyToAgent {
+                message: Some(grpc_v2::proxy_to_agent::Message::RequestBodyChunk(
+                    convert_body_chunk_to_grpc(event),
+                )),
+            }
+        })
+        .await
+    }
+
+    /// Send a response headers event.
+    pub async fn send_response_headers(
+        &self,
+        correlation_id: &str,
+        event: &crate::ResponseHeadersEvent,
+    ) -> Result<AgentResponse, AgentProtocolError> {
+        self.send_event("response-headers", correlation_id, event, || ProxyToAgent {
+            message: Some(grpc_v2::proxy_to_agent::Message::ResponseHeaders(
+                convert_response_headers_to_grpc(event),
+            )),
+        })
+        .await
+    }
+
+    /// Send a response body chunk event.
+    pub async fn send_response_body_chunk(
+        &self,
+        correlation_id: &str,
+        event: &crate::ResponseBodyChunkEvent,
+    ) -> Result<AgentResponse, AgentProtocolError> {
+        self.send_event("response-body-chunk", correlation_id, event, || {
+            ProxyToAgent {
+                message: Some(grpc_v2::proxy_to_agent::Message::ResponseBodyChunk(
+                    convert_response_body_chunk_to_grpc(event),
+                )),
+            }
+        })
+        .await
+    }
+
+    /// Send an event and wait for the agent's response.
+    async fn send_event<T: serde::Serialize>(
+        &self,
+        event_path: &str,
+        correlation_id: &str,
+        event: &T,
+        to_protobuf: impl FnOnce() -> ProxyToAgent,
+    ) -> Result<AgentResponse, AgentProtocolError> {
+        let encoding = *self.encoding.read().await;
+
+        let body = match encoding {
+            HttpEncoding::Json => {
+                let mut payload = serde_json::to_value(event)
+                    .map_err(|e| AgentProtocolError::Serialization(e.to_string()))?;
+                if let Some(obj) = payload.as_object_mut() {
+                    obj.insert(
+                        "correlation_id".to_string(),
+                        serde_json::Value::String(correlation_id.to_string()),
+                    );
+                }
+                serde_json::to_vec(&payload)
+                    .map_err(|e| AgentProtocolError::Serialization(e.to_string()))?
+            }
+            HttpEncoding::Protobuf => to_protobuf().encode_to_vec(),
+        };
+
+        self.pending.lock().await.insert(correlation_id.to_string());
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+
+        let result = self
+            .shared
+            .http
+            .post(self.shared.url(&format!("events/{}", event_path)))
+            .header(reqwest::header::CONTENT_TYPE, encoding.content_type())
+            .header(reqwest::header::ACCEPT, encoding.content_type())
+            .header(CORRELATION_ID_HEADER, correlation_id)
+            .body(body)
+            .send()
+            .await;
+
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+        self.pending.lock().await.remove(correlation_id);
+
+        let response = result.map_err(|e| self.request_error(e))?;
+        let status = response.status();
+
+        if status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE {
+            debug!(
+                agent_id = %self.shared.agent_id,
+                status = %status,
+                "HTTP agent requested backpressure"
+            );
+            *self.shared.flow_state.write().await = FlowState::Paused;
+            return Err(AgentProtocolError::FlowControlPaused {
+                agent_id: self.shared.agent_id.clone(),
+            });
+        }
+        if !status.is_success() {
+            return Err(AgentProtocolError::InvalidMessage(format!(
+                "Agent returned HTTP {} for {}",
+                status, event_path
+            )));
+        }
+
+        let bytes = response.bytes().await.map_err(|e| self.request_error(e))?;
+        decode_response(encoding, &bytes)
+    }
+
+    /// Map a transport error, preserving timeouts.
+    fn request_error(&self, e: reqwest::Error) -> AgentProtocolError {
+        if e.is_timeout() {
+            AgentProtocolError::Timeout(self.timeout)
+        } else {
+            AgentProtocolError::ConnectionFailed(e.to_string())
+        }
+    }
+
+    /// Send a cancel request for a specific correlation ID.
+    ///
+    /// Only forwarded to agents that advertise cancellation support.
+    pub async fn cancel_request(
+        &self,
+        correlation_id: &str,
+        reason: CancelReason,
+    ) -> Result<(), AgentProtocolError> {
+        self.pending.lock().await.remove(correlation_id);
+
+        let supports_cancellation = self
+            .capabilities
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|caps| caps.features.cancellation);
+        if !supports_cancellation {
+            return Ok(());
+        }
+
+        let cancel = serde_json::json!({
+            "correlation_id": correlation_id,
+            "reason": reason as i32,
+            "timestamp_ms": now_ms(),
+        });
+
+        self.shared
+            .http
+            .post(self.shared.url("cancel"))
+            .json(&cancel)
+            .send()
+            .await
+            .map_err(|e| self.request_error(e))?;
+
+        Ok(())
+    }
+
+    /// Cancel all in-flight requests.
+    pub async fn cancel_all(&self, reason: CancelReason) -> Result<usize, AgentProtocolError> {
+        let pending_ids: Vec<String> = self.pending.lock().await.iter().cloned().collect();
+        let count = pending_ids.len();
+
+        for correlation_id in pending_ids {
+            let _ = self.cancel_request(&correlation_id, reason).await;
+        }
+
+        Ok(count)
+    }
+
+    /// Stop polling and mark the client disconnected.
+    pub async fn close(&self) -> Result<(), AgentProtocolError> {
+        if let Some(task) = self.poll_task.lock().take() {
+            task.abort();
+        }
+        *self.shared.connected.write().await = false;
+        Ok(())
+    }
+
+    /// Get in-flight request count.
+    pub fn in_flight(&self) -> u64 {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Get agent ID.
+    pub fn agent_id(&self) -> &str {
+        &self.shared.agent_id
+    }
+
+    /// Check if the transport can accept new requests.
+    ///
+    /// Returns false while the agent is paused (backpressure or unhealthy)
+    /// or draining.
+    pub async fn can_accept_requests(&self) -> bool {
+        matches!(*self.shared.flow_state.read().await, FlowState::Normal)
+    }
+}
+
+impl Drop for AgentClientV2Http {
+    fn drop(&mut self) {
+        if let Some(task) = self.poll_task.get_mut().take() {
+            task.abort();
+        }
+    }
+}
+
+/// Decode an event response body.
+fn decode_response(
+    encoding: HttpEncoding,
+    bytes: &[u8],
+) -> Result<AgentResponse, AgentProtocolError> {
+    match encoding {
+        HttpEncoding::Json => serde_json::from_slice(bytes)
+            .map_err(|e| AgentProtocolError::InvalidMessage(e.to_string())),
+        HttpEncoding::Protobuf => grpc_v2::AgentResponse::decode(bytes)
+            .map(convert_response_from_grpc)
+            .map_err(|e| AgentProtocolError::InvalidMessage(e.to_string())),
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Decision;
+
+    #[test]
+    fn test_http_base_url() {
+        assert_eq!(
+            http_base_url("sentinel+https://waf.example.com/agent/"),
+            Some("https://waf.example.com/agent")
+        );
+        assert_eq!(
+            http_base_url("sentinel+http://127.0.0.1:8080"),
+            Some("http://127.0.0.1:8080")
+        );
+        assert_eq!(http_base_url("http://127.0.0.1:50051"), None);
+        assert_eq!(http_base_url("sentinel+unix:/tmp/agent.sock"), None);
+    }
+
+    #[test]
+    fn test_handshake_response_defaults_to_json() {
+        let response: HttpHandshakeResponse = serde_json::from_value(serde_json::json!({
+            "protocol_version": 2,
+            "capabilities": {
+                "agent_id": "php-waf",
+                "name": "PHP WAF",
+                "version": "1.0.0",
+                "supported_events": [1],
+                "features": {
+                    "streaming_body": false,
+                    "websocket": false,
+                    "guardrails": false,
+                    "config_push": false,
+                    "metrics_export": true,
+                    "concurrent_requests": 100,
+                    "cancellation": false,
+                    "flow_control": false,
+                    "health_reporting": true
+                },
+                "limits": {
+                    "max_body_size": 1048576,
+                    "max_concurrency": 100,
+                    "preferred_chunk_size": 65536
+                }
+            },
+            "success": true,
+            "error": null
+        }))
+        .unwrap();
+        assert_eq!(response.encoding, HttpEncoding::Json);
+
+        let encodings = serde_json::to_value([HttpEncoding::Protobuf, HttpEncoding::Json]).unwrap();
+        assert_eq!(encodings, serde_json::json!(["protobuf", "json"]));
+    }
+
+    #[test]
+    fn test_flow_state_for_health() {
+        assert_eq!(
+            flow_state_for_health(&HealthState::Healthy),
+            FlowState::Normal
+        );
+        assert_eq!(
+            flow_state_for_health(&HealthState::Draining { eta_ms: None }),
+            FlowState::Draining
+        );
+        assert_eq!(
+            flow_state_for_health(&HealthState::Unhealthy {
+                reason: "OOM".to_string(),
+                recoverable: true,
+            }),
+            FlowState::Paused
+        );
+    }
+
+    #[test]
+    fn test_decode_protobuf_response() {
+        let response = grpc_v2::AgentResponse {
+            decision: Some(grpc_v2::agent_response::Decision::Block(
+                grpc_v2::BlockDecision {
+                    status: 403,
+                    ..Default::default()
+                },
+            )),
+            ..Default::default()
+        };
+        let decoded = decode_response(HttpEncoding::Protobuf, &response.encode_to_vec()).unwrap();
+        assert!(matches!(
+            decoded.decision,
+            Decision::Block { status: 403, .. }
+        ));
+
+        assert!(decode_response(HttpEncoding::Json, b"not json").is_err());
+    }
+
+    /// Serve canned HTTP/1.1 responses, one per accepted connection.
+    async fn serve(responses: Vec<(u16, String)>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 64 * 1024];
+                let _ = stream.read(&mut buf).await.unwrap();
+                let reply = format!(
+                    "HTTP/1.1 {} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_http_client_round_trip() {
+        let handshake = serde_json::json!({
+            "protocol_version": 2,
+            "capabilities": {
+                "agent_id": "php-waf",
+                "name": "PHP WAF",
+                "version": "1.0.0",
+                "supported_events": [3],
+                "features": {
+                    "streaming_body": false,
+                    "websocket": false,
+                    "guardrails": false,
+                    "config_push": false,
+                    "metrics_export": false,
+                    "concurrent_requests": 10,
+                    "cancellation": false,
+                    "flow_control": true,
+                    "health_reporting": true
+                },
+                "limits": {
+                    "max_body_size": 0,
+                    "max_concurrency": 10,
+                    "preferred_chunk_size": 0
+                }
+            },
+            "success": true,
+            "error": null,
+            "encoding": "json"
+        });
+        let base_url = serve(vec![
+            (200, handshake.to_string()),
+            (
+                200,
+                serde_json::to_string(&AgentResponse::default_allow()).unwrap(),
+            ),
+            (503, String::new()),
+        ])
+        .await;
+
+        let client = AgentClientV2Http::new("php-waf", base_url, Duration::from_secs(5))
+            .await
+            .unwrap();
+        client.connect().await.unwrap();
+        assert!(client.is_connected().await);
+        assert_eq!(client.encoding().await, HttpEncoding::Json);
+        assert_eq!(client.capabilities().await.unwrap().name, "PHP WAF");
+
+        let event = crate::ResponseHeadersEvent {
+            correlation_id: "req-1".to_string(),
+            status: 200,
+            headers: Default::default(),
+        };
+        let response = client.send_response_headers("req-1", &event).await.unwrap();
+        assert!(matches!(response.decision, Decision::Allow));
+        assert!(client.can_accept_requests().await);
+
+        // 503 pauses the connection until the next health poll
+        let err = client
+            .send_response_headers("req-2", &event)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AgentProtocolError::FlowControlPaused { .. }));
+        assert!(!client.can_accept_requests().await);
+        assert_eq!(client.in_flight(), 0);
+
+        client.close().await.unwrap();
+        assert!(!client.is_connected().await);
+    }
+}
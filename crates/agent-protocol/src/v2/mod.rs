@@ -7,11 +7,14 @@
 //! - Metrics export
 //! - Bidirectional streaming
 //! - v2 server and client implementations
+//! - Protocol conformance kit for agent authors
 
 mod capabilities;
 pub mod client;
+pub mod conformance;
 mod control;
 mod health;
+pub mod http;
 mod metrics;
 pub mod observability;
 pub mod pool;
@@ -23,6 +26,7 @@ pub mod uds;
 
 pub use capabilities::*;
 pub use client::{AgentClientV2, CancelReason, ConfigUpdateCallback, FlowState, MetricsCallback};
+pub use self::http::{AgentClientV2Http, HttpEncoding, HttpHandshakeRequest, HttpHandshakeResponse, CORRELATION_ID_HEADER, HTTP_ENDPOINT_PREFIX};
 pub use uds::{AgentClientV2Uds, MessageType, UdsCapabilities, UdsEncoding, UdsFeatures, UdsHandshakeRequest, UdsHandshakeResponse, UdsLimits, MAX_UDS_MESSAGE_SIZE};
 pub use reverse::{RegistrationRequest, RegistrationResponse, ReverseConnectionClient, ReverseConnectionConfig, ReverseConnectionListener};
 pub use control::*;
@@ -32,7 +36,10 @@ pub use observability::{
 };
 pub use health::*;
 pub use metrics::*;
-pub use pool::{AgentPool, AgentPoolConfig, AgentPoolStats, LoadBalanceStrategy, V2Transport};
+pub use pool::{
+    AgentPool, AgentPoolConfig, AgentPoolStats, FlowControlMode, LoadBalanceStrategy,
+    RequestPriority, V2Transport,
+};
 pub use protocol_metrics::{ProtocolMetrics, ProtocolMetricsSnapshot, HistogramMetric, HistogramSnapshot};
 pub use server::{AgentHandlerV2, DrainReason, GrpcAgentHandlerV2, GrpcAgentServerV2, ShutdownReason};
 pub use streaming::*;
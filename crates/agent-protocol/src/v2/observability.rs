@@ -794,6 +794,10 @@ impl ConfigUpdateHandler {
                 );
                 ConfigUpdateResponse::success(request_id)
             }
+            ConfigUpdateType::InvalidateCache => {
+                // The pool bumps the agent's cache epoch before calling the handler
+                ConfigUpdateResponse::success(request_id)
+            }
         }
     }
 
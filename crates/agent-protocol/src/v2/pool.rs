@@ -9,16 +9,18 @@
 //! - **Automatic reconnection**: Reconnect failed connections
 //! - **Graceful shutdown**: Drain connections before closing
 
+use std::hash::{Hash, Hasher};
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use dashmap::DashMap;
-use tokio::sync::{RwLock, Semaphore};
+use tokio::sync::{RwLock, Semaphore, SemaphorePermit};
 use tracing::{debug, info, trace, warn};
 
 use crate::v2::client::{AgentClientV2, CancelReason, ConfigUpdateCallback, MetricsCallback};
 use crate::v2::control::ConfigUpdateType;
+use crate::v2::http::{http_base_url, AgentClientV2Http};
 use crate::v2::observability::{ConfigPusher, ConfigUpdateHandler, MetricsCollector};
 use crate::v2::protocol_metrics::ProtocolMetrics;
 use crate::v2::reverse::ReverseConnectionClient;
@@ -82,6 +84,23 @@ pub enum FlowControlMode {
     WaitAndRetry,
 }
 
+/// Priority of requests sent through the pool.
+///
+/// Low-priority requests never wait for an agent: when every permit of the
+/// selected connection is taken, or the agent has paused the flow, the
+/// request is skipped with `AgentProtocolError::Skipped` instead of queuing.
+/// Use this for agents whose decisions are observed but not enforced, so a
+/// slow agent cannot add latency to live traffic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RequestPriority {
+    /// Wait for a concurrency permit and apply `flow_control_mode` (default).
+    #[default]
+    Normal,
+
+    /// Skip the request when the agent is saturated or paused.
+    Low,
+}
+
 /// A sticky session entry tracking connection affinity for long-lived streams.
 ///
 /// Used for WebSocket connections, Server-Sent Events, long-polling, and other
@@ -155,6 +174,18 @@ pub struct AgentPoolConfig {
     ///
     /// Default: 5 minutes
     pub sticky_session_timeout: Option<Duration>,
+    /// Priority of requests sent to agents in this pool.
+    ///
+    /// Default: `RequestPriority::Normal`
+    pub priority: RequestPriority,
+    /// Fraction of requests (0.0-1.0) sent to agents in this pool.
+    ///
+    /// Sampling is keyed on the correlation ID, so all events of a sampled
+    /// request reach the agent. Unsampled requests return
+    /// `AgentProtocolError::Skipped`.
+    ///
+    /// Default: 1.0
+    pub sample_rate: f64,
 }
 
 impl Default for AgentPoolConfig {
@@ -173,6 +204,8 @@ impl Default for AgentPoolConfig {
             flow_control_mode: FlowControlMode::FailClosed,
             flow_control_wait_timeout: Duration::from_millis(100),
             sticky_session_timeout: Some(Duration::from_secs(5 * 60)), // 5 minutes
+            priority: RequestPriority::Normal,
+            sample_rate: 1.0,
         }
     }
 }
@@ -204,12 +237,14 @@ impl StickySession {
 
 /// Transport layer for v2 agent connections.
 ///
-/// Supports gRPC, Unix Domain Socket, and reverse connections.
+/// Supports gRPC, Unix Domain Socket, HTTP, and reverse connections.
 pub enum V2Transport {
     /// gRPC over HTTP/2
     Grpc(AgentClientV2),
     /// Binary protocol over Unix Domain Socket
     Uds(AgentClientV2Uds),
+    /// One HTTP request per event, with polled health and metrics
+    Http(AgentClientV2Http),
     /// Reverse connection (agent connected to proxy)
     Reverse(ReverseConnectionClient),
 }
@@ -220,6 +255,7 @@ impl V2Transport {
         match self {
             V2Transport::Grpc(client) => client.is_connected().await,
             V2Transport::Uds(client) => client.is_connected().await,
+            V2Transport::Http(client) => client.is_connected().await,
             V2Transport::Reverse(client) => client.is_connected().await,
         }
     }
@@ -231,6 +267,7 @@ impl V2Transport {
         match self {
             V2Transport::Grpc(client) => client.can_accept_requests().await,
             V2Transport::Uds(client) => client.can_accept_requests().await,
+            V2Transport::Http(client) => client.can_accept_requests().await,
             V2Transport::Reverse(client) => client.can_accept_requests().await,
         }
     }
@@ -240,6 +277,7 @@ impl V2Transport {
         match self {
             V2Transport::Grpc(client) => client.capabilities().await,
             V2Transport::Uds(client) => client.capabilities().await,
+            V2Transport::Http(client) => client.capabilities().await,
             V2Transport::Reverse(client) => client.capabilities().await,
         }
     }
@@ -253,6 +291,7 @@ impl V2Transport {
         match self {
             V2Transport::Grpc(client) => client.send_request_headers(correlation_id, event).await,
             V2Transport::Uds(client) => client.send_request_headers(correlation_id, event).await,
+            V2Transport::Http(client) => client.send_request_headers(correlation_id, event).await,
             V2Transport::Reverse(client) => client.send_request_headers(correlation_id, event).await,
         }
     }
@@ -266,6 +305,7 @@ impl V2Transport {
         match self {
             V2Transport::Grpc(client) => client.send_request_body_chunk(correlation_id, event).await,
             V2Transport::Uds(client) => client.send_request_body_chunk(correlation_id, event).await,
+            V2Transport::Http(client) => client.send_request_body_chunk(correlation_id, event).await,
             V2Transport::Reverse(client) => client.send_request_body_chunk(correlation_id, event).await,
         }
     }
@@ -279,6 +319,7 @@ impl V2Transport {
         match self {
             V2Transport::Grpc(client) => client.send_response_headers(correlation_id, event).await,
             V2Transport::Uds(client) => client.send_response_headers(correlation_id, event).await,
+            V2Transport::Http(client) => client.send_response_headers(correlation_id, event).await,
             V2Transport::Reverse(client) => client.send_response_headers(correlation_id, event).await,
         }
     }
@@ -292,6 +333,7 @@ impl V2Transport {
         match self {
             V2Transport::Grpc(client) => client.send_response_body_chunk(correlation_id, event).await,
             V2Transport::Uds(client) => client.send_response_body_chunk(correlation_id, event).await,
+            V2Transport::Http(client) => client.send_response_body_chunk(correlation_id, event).await,
             V2Transport::Reverse(client) => client.send_response_body_chunk(correlation_id, event).await,
         }
     }
@@ -305,6 +347,7 @@ impl V2Transport {
         match self {
             V2Transport::Grpc(client) => client.cancel_request(correlation_id, reason).await,
             V2Transport::Uds(client) => client.cancel_request(correlation_id, reason).await,
+            V2Transport::Http(client) => client.cancel_request(correlation_id, reason).await,
             V2Transport::Reverse(client) => client.cancel_request(correlation_id, reason).await,
         }
     }
@@ -314,6 +357,7 @@ impl V2Transport {
         match self {
             V2Transport::Grpc(client) => client.cancel_all(reason).await,
             V2Transport::Uds(client) => client.cancel_all(reason).await,
+            V2Transport::Http(client) => client.cancel_all(reason).await,
             V2Transport::Reverse(client) => client.cancel_all(reason).await,
         }
     }
@@ -323,6 +367,7 @@ impl V2Transport {
         match self {
             V2Transport::Grpc(client) => client.close().await,
             V2Transport::Uds(client) => client.close().await,
+            V2Transport::Http(client) => client.close().await,
             V2Transport::Reverse(client) => client.close().await,
         }
     }
@@ -332,6 +377,7 @@ impl V2Transport {
         match self {
             V2Transport::Grpc(client) => client.agent_id(),
             V2Transport::Uds(client) => client.agent_id(),
+            V2Transport::Http(client) => client.agent_id(),
             V2Transport::Reverse(client) => client.agent_id(),
         }
     }
@@ -514,6 +560,8 @@ pub struct AgentPool {
     config_update_handler: Arc<ConfigUpdateHandler>,
     /// Callback used to handle config updates from clients
     config_update_callback: ConfigUpdateCallback,
+    /// Per-agent cache epoch, bumped when an agent invalidates its cached decisions
+    cache_epochs: Arc<DashMap<String, u64>>,
     /// Protocol-level metrics (proxy-side instrumentation)
     protocol_metrics: Arc<ProtocolMetrics>,
     /// Connection affinity: correlation_id → connection used for headers.
@@ -544,6 +592,8 @@ impl AgentPool {
         let config_pusher = Arc::new(ConfigPusher::new());
         let config_update_handler = Arc::new(ConfigUpdateHandler::new());
         let handler_clone = Arc::clone(&config_update_handler);
+        let cache_epochs: Arc<DashMap<String, u64>> = Arc::new(DashMap::new());
+        let epochs_clone = Arc::clone(&cache_epochs);
 
         // Create a callback that handles config update requests from agents
         let config_update_callback: ConfigUpdateCallback = Arc::new(move |agent_id, request| {
@@ -552,6 +602,10 @@ impl AgentPool {
                 request_id = %request.request_id,
                 "Processing config update request from agent"
             );
+            if request.update_type.invalidates_cache() {
+                *epochs_clone.entry(agent_id.clone()).or_insert(0) += 1;
+                debug!(agent_id = %agent_id, "Invalidated cached decisions for agent");
+            }
             handler_clone.handle(request)
         });
 
@@ -565,6 +619,7 @@ impl AgentPool {
             config_pusher,
             config_update_handler,
             config_update_callback,
+            cache_epochs,
             protocol_metrics: Arc::new(ProtocolMetrics::new()),
             correlation_affinity: DashMap::new(),
             sticky_sessions: DashMap::new(),
@@ -741,6 +796,8 @@ impl AgentPool {
         correlation_id: &str,
         event: &RequestHeadersEvent,
     ) -> Result<(AgentResponse, bool), AgentProtocolError> {
+        self.check_sampled(agent_id, correlation_id)?;
+
         let start = Instant::now();
         self.total_requests.fetch_add(1, Ordering::Relaxed);
         self.protocol_metrics.inc_requests();
@@ -767,15 +824,13 @@ impl AgentPool {
         }
 
         // Acquire concurrency permit
-        let _permit = conn
-            .concurrency_limiter
-            .acquire()
-            .await
-            .map_err(|_| {
-                self.protocol_metrics.dec_in_flight();
+        let _permit = self.acquire_permit(&conn, agent_id).await.map_err(|e| {
+            self.protocol_metrics.dec_in_flight();
+            if !matches!(e, AgentProtocolError::Skipped { .. }) {
                 self.protocol_metrics.inc_connection_errors();
-                AgentProtocolError::ConnectionFailed("Concurrency limit reached".to_string())
-            })?;
+            }
+            e
+        })?;
 
         conn.in_flight.fetch_add(1, Ordering::Relaxed);
         conn.touch();
@@ -857,6 +912,14 @@ impl AgentPool {
         &self.config_update_handler
     }
 
+    /// Get the cache epoch for an agent.
+    ///
+    /// The epoch increases whenever the agent sends a config update that
+    /// invalidates cached decisions, so callers can include it in cache keys.
+    pub fn cache_epoch(&self, agent_id: &str) -> u64 {
+        self.cache_epochs.get(agent_id).map(|e| *e).unwrap_or(0)
+    }
+
     /// Push a configuration update to a specific agent.
     ///
     /// Returns the push ID if the agent supports config push, None otherwise.
@@ -1075,6 +1138,14 @@ impl AgentPool {
             return Ok(true);
         }
 
+        if self.config.priority == RequestPriority::Low {
+            self.protocol_metrics.record_skipped();
+            return Err(AgentProtocolError::Skipped {
+                agent_id: agent_id.to_string(),
+                reason: "paused".to_string(),
+            });
+        }
+
         match self.config.flow_control_mode {
             FlowControlMode::FailClosed => {
                 self.protocol_metrics.record_flow_rejection();
@@ -1107,6 +1178,41 @@ impl AgentPool {
         }
     }
 
+    /// Check whether a request is in the configured sample.
+    ///
+    /// Returns `AgentProtocolError::Skipped` for requests outside the sample.
+    fn check_sampled(&self, agent_id: &str, correlation_id: &str) -> Result<(), AgentProtocolError> {
+        if is_sampled(correlation_id, self.config.sample_rate) {
+            return Ok(());
+        }
+        self.protocol_metrics.record_skipped();
+        Err(AgentProtocolError::Skipped {
+            agent_id: agent_id.to_string(),
+            reason: "unsampled".to_string(),
+        })
+    }
+
+    /// Acquire a concurrency permit on a connection according to the
+    /// configured request priority.
+    async fn acquire_permit<'a>(
+        &self,
+        conn: &'a PooledConnection,
+        agent_id: &str,
+    ) -> Result<SemaphorePermit<'a>, AgentProtocolError> {
+        match self.config.priority {
+            RequestPriority::Normal => conn.concurrency_limiter.acquire().await.map_err(|_| {
+                AgentProtocolError::ConnectionFailed("Concurrency limit reached".to_string())
+            }),
+            RequestPriority::Low => conn.concurrency_limiter.try_acquire().map_err(|_| {
+                self.protocol_metrics.record_skipped();
+                AgentProtocolError::Skipped {
+                    agent_id: agent_id.to_string(),
+                    reason: "saturated".to_string(),
+                }
+            }),
+        }
+    }
+
     /// Send a request headers event to an agent.
     ///
     /// The pool selects the best connection based on the load balancing strategy.
@@ -1211,6 +1317,7 @@ impl AgentPool {
         correlation_id: &str,
         event: &RequestBodyChunkEvent,
     ) -> Result<AgentResponse, AgentProtocolError> {
+        self.check_sampled(agent_id, correlation_id)?;
         self.total_requests.fetch_add(1, Ordering::Relaxed);
 
         // Try to use affinity (same connection as headers), fall back to selection
@@ -1232,11 +1339,7 @@ impl AgentPool {
             Err(e) => return Err(e),
         }
 
-        let _permit = conn
-            .concurrency_limiter
-            .acquire()
-            .await
-            .map_err(|_| AgentProtocolError::ConnectionFailed("Concurrency limit reached".to_string()))?;
+        let _permit = self.acquire_permit(&conn, agent_id).await?;
 
         conn.in_flight.fetch_add(1, Ordering::Relaxed);
         conn.touch();
@@ -1273,15 +1376,12 @@ impl AgentPool {
         correlation_id: &str,
         event: &ResponseHeadersEvent,
     ) -> Result<AgentResponse, AgentProtocolError> {
+        self.check_sampled(agent_id, correlation_id)?;
         self.total_requests.fetch_add(1, Ordering::Relaxed);
 
         let conn = self.select_connection(agent_id)?;
 
-        let _permit = conn
-            .concurrency_limiter
-            .acquire()
-            .await
-            .map_err(|_| AgentProtocolError::ConnectionFailed("Concurrency limit reached".to_string()))?;
+        let _permit = self.acquire_permit(&conn, agent_id).await?;
 
         conn.in_flight.fetch_add(1, Ordering::Relaxed);
         conn.touch();
@@ -1318,6 +1418,7 @@ impl AgentPool {
         correlation_id: &str,
         event: &ResponseBodyChunkEvent,
     ) -> Result<AgentResponse, AgentProtocolError> {
+        self.check_sampled(agent_id, correlation_id)?;
         self.total_requests.fetch_add(1, Ordering::Relaxed);
 
         let conn = self.select_connection(agent_id)?;
@@ -1332,11 +1433,7 @@ impl AgentPool {
             Err(e) => return Err(e),
         }
 
-        let _permit = conn
-            .concurrency_limiter
-            .acquire()
-            .await
-            .map_err(|_| AgentProtocolError::ConnectionFailed("Concurrency limit reached".to_string()))?;
+        let _permit = self.acquire_permit(&conn, agent_id).await?;
 
         conn.in_flight.fetch_add(1, Ordering::Relaxed);
         conn.touch();
@@ -1601,6 +1698,16 @@ impl AgentPool {
 
             client.connect().await?;
             V2Transport::Uds(client)
+        } else if let Some(base_url) = http_base_url(endpoint) {
+            // HTTP transport
+            let mut client =
+                AgentClientV2Http::new(agent_id, base_url, self.config.request_timeout).await?;
+
+            client.set_poll_interval(self.config.health_check_interval);
+            client.set_metrics_callback(Arc::clone(&self.metrics_callback));
+
+            client.connect().await?;
+            V2Transport::Http(client)
         } else {
             // gRPC transport (default)
             let mut client =
@@ -1771,6 +1878,22 @@ fn is_uds_endpoint(endpoint: &str) -> bool {
         || endpoint.ends_with(".sock")
 }
 
+/// Check if a request falls within a sample rate.
+///
+/// The decision is a hash of the correlation ID, so every event of a
+/// request gets the same answer.
+fn is_sampled(correlation_id: &str, sample_rate: f64) -> bool {
+    if sample_rate >= 1.0 {
+        return true;
+    }
+    if sample_rate <= 0.0 {
+        return false;
+    }
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    correlation_id.hash(&mut hasher);
+    (hasher.finish() as f64 / u64::MAX as f64) < sample_rate
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1842,6 +1965,32 @@ mod tests {
         assert!(!is_uds_endpoint("127.0.0.1:8080"));
     }
 
+    #[test]
+    fn test_cache_epoch_bumped_by_invalidating_updates() {
+        use crate::v2::control::ConfigUpdateRequest;
+
+        let pool = AgentPool::new();
+        assert_eq!(pool.cache_epoch("auth"), 0);
+
+        let request = |update_type| ConfigUpdateRequest {
+            update_type,
+            request_id: "req-1".to_string(),
+            timestamp_ms: 0,
+        };
+
+        let response =
+            (pool.config_update_callback)("auth".to_string(), request(ConfigUpdateType::InvalidateCache));
+        assert!(response.accepted);
+        assert_eq!(pool.cache_epoch("auth"), 1);
+
+        (pool.config_update_callback)(
+            "auth".to_string(),
+            request(ConfigUpdateType::ConfigError { error: "x".to_string(), field: None }),
+        );
+        assert_eq!(pool.cache_epoch("auth"), 1);
+        assert_eq!(pool.cache_epoch("other"), 0);
+    }
+
     #[test]
     fn test_flow_control_mode_default() {
         assert_eq!(FlowControlMode::default(), FlowControlMode::FailClosed);
@@ -1906,6 +2055,51 @@ mod tests {
         assert!(config.sticky_session_timeout.is_none());
     }
 
+    #[test]
+    fn test_pool_config_priority_defaults() {
+        let config = AgentPoolConfig::default();
+        assert_eq!(config.priority, RequestPriority::Normal);
+        assert_eq!(config.sample_rate, 1.0);
+    }
+
+    #[test]
+    fn test_is_sampled() {
+        assert!(is_sampled("req-1", 1.0));
+        assert!(!is_sampled("req-1", 0.0));
+
+        // Stable per correlation ID
+        assert_eq!(is_sampled("req-1", 0.5), is_sampled("req-1", 0.5));
+
+        let sampled = (0..1000)
+            .filter(|i| is_sampled(&format!("req-{}", i), 0.25))
+            .count();
+        assert!((150..350).contains(&sampled), "sampled {}", sampled);
+    }
+
+    #[tokio::test]
+    async fn test_unsampled_request_skipped() {
+        let pool = AgentPool::with_config(AgentPoolConfig {
+            sample_rate: 0.0,
+            ..Default::default()
+        });
+        let event = RequestBodyChunkEvent {
+            correlation_id: "req-1".to_string(),
+            data: String::new(),
+            is_last: true,
+            total_size: None,
+            chunk_index: 0,
+            bytes_received: 0,
+        };
+
+        let err = pool
+            .send_request_body_chunk("waf", "req-1", &event)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AgentProtocolError::Skipped { .. }));
+        assert_eq!(pool.protocol_metrics.snapshot().requests_skipped_total, 1);
+        assert_eq!(pool.total_requests.load(Ordering::Relaxed), 0);
+    }
+
     #[test]
     fn test_sticky_session_count_empty() {
         let pool = AgentPool::new();
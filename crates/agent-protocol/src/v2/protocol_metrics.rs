@@ -31,6 +31,8 @@ pub struct ProtocolMetrics {
     pub flow_control_resumes_total: AtomicU64,
     /// Requests rejected due to flow control
     pub flow_control_rejections_total: AtomicU64,
+    /// Low-priority or unsampled requests the pool did not send
+    pub requests_skipped_total: AtomicU64,
 
     // Gauges
     /// Current in-flight requests
@@ -103,6 +105,12 @@ impl ProtocolMetrics {
         self.flow_control_rejections_total.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Record a request the pool skipped instead of sending.
+    #[inline]
+    pub fn record_skipped(&self) {
+        self.requests_skipped_total.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Set in-flight requests gauge.
     #[inline]
     pub fn set_in_flight(&self, count: u64) {
@@ -162,6 +170,7 @@ impl ProtocolMetrics {
             flow_control_pauses_total: self.flow_control_pauses_total.load(Ordering::Relaxed),
             flow_control_resumes_total: self.flow_control_resumes_total.load(Ordering::Relaxed),
             flow_control_rejections_total: self.flow_control_rejections_total.load(Ordering::Relaxed),
+            requests_skipped_total: self.requests_skipped_total.load(Ordering::Relaxed),
             in_flight_requests: self.in_flight_requests.load(Ordering::Relaxed),
             buffer_utilization_percent: self.buffer_utilization_percent.load(Ordering::Relaxed),
             healthy_connections: self.healthy_connections.load(Ordering::Relaxed),
@@ -219,6 +228,13 @@ impl ProtocolMetrics {
             snap.flow_control_rejections_total
         ));
 
+        output.push_str(&format!(
+            "# HELP {prefix}_requests_skipped_total Low-priority or unsampled requests not sent\n\
+             # TYPE {prefix}_requests_skipped_total counter\n\
+             {prefix}_requests_skipped_total {}\n\n",
+            snap.requests_skipped_total
+        ));
+
         // Gauges
         output.push_str(&format!(
             "# HELP {prefix}_in_flight_requests Current in-flight requests\n\
@@ -414,6 +430,7 @@ pub struct ProtocolMetricsSnapshot {
     pub flow_control_pauses_total: u64,
     pub flow_control_resumes_total: u64,
     pub flow_control_rejections_total: u64,
+    pub requests_skipped_total: u64,
 
     // Gauges
     pub in_flight_requests: u64,
@@ -603,6 +603,13 @@ fn convert_request_headers_from_grpc(e: grpc_v2::RequestHeadersEvent) -> Request
             upstream_id: m.upstream_id,
             timestamp: format!("{}", m.timestamp_ms),
             traceparent: m.traceparent,
+            client_cert: m.client_cert.map(|cert| crate::ClientCertInfo {
+                subject: cert.subject,
+                issuer: cert.issuer,
+                sans: cert.sans,
+                serial: cert.serial,
+                fingerprint_sha256: cert.fingerprint_sha256,
+            }),
         },
         None => RequestMetadata {
             correlation_id: String::new(),
@@ -617,6 +624,7 @@ fn convert_request_headers_from_grpc(e: grpc_v2::RequestHeadersEvent) -> Request
             upstream_id: None,
             timestamp: String::new(),
             traceparent: None,
+            client_cert: None,
         },
     };
 
@@ -765,6 +773,31 @@ fn create_agent_response(
             .collect(),
     });
 
+    let cache = resp.cache.map(|c| {
+        let (scope, header_name) = match c.scope {
+            crate::CacheScope::ClientIp => (grpc_v2::CacheScope::ClientIp, String::new()),
+            crate::CacheScope::Header { name } => (grpc_v2::CacheScope::Header, name),
+            crate::CacheScope::Route => (grpc_v2::CacheScope::Route, String::new()),
+        };
+        grpc_v2::CacheDirective {
+            ttl_ms: c.ttl_ms,
+            scope: scope as i32,
+            header_name,
+        }
+    });
+
+    let routing = resp.routing.map(|r| grpc_v2::RoutingOverride {
+        upstream: r.upstream,
+        target: r.target,
+        hash_key: r.hash_key,
+    });
+
+    let convert_body_mutation = |m: crate::BodyMutation| grpc_v2::BodyMutation {
+        data: m.data.map(String::into_bytes),
+        chunk_index: m.chunk_index,
+        insert: m.insert.map(String::into_bytes),
+    };
+
     AgentToProxy {
         message: Some(grpc_v2::agent_to_proxy::Message::Response(
             grpc_v2::AgentResponse {
@@ -775,6 +808,10 @@ fn create_agent_response(
                 audit,
                 processing_time_ms: Some(processing_time_ms),
                 needs_more: resp.needs_more,
+                cache,
+                routing,
+                request_body_mutation: resp.request_body_mutation.map(convert_body_mutation),
+                response_body_mutation: resp.response_body_mutation.map(convert_body_mutation),
             },
         )),
     }
@@ -808,35 +808,7 @@ impl AgentClientV2Uds {
         let encoding = *self.encoding.read().await;
 
         // Serialize event using negotiated encoding
-        let payload_bytes = match encoding {
-            UdsEncoding::Json => {
-                // JSON path: use Value mutation for backwards compatibility
-                let mut payload = serde_json::to_value(event)
-                    .map_err(|e| AgentProtocolError::Serialization(e.to_string()))?;
-                if let Some(obj) = payload.as_object_mut() {
-                    obj.insert(
-                        "correlation_id".to_string(),
-                        serde_json::Value::String(correlation_id.to_string()),
-                    );
-                }
-                serde_json::to_vec(&payload)
-                    .map_err(|e| AgentProtocolError::Serialization(e.to_string()))?
-            }
-            UdsEncoding::MessagePack => {
-                // MessagePack path: use wrapper struct for efficient serialization
-                #[derive(serde::Serialize)]
-                struct EventWithCorrelation<'a, T: serde::Serialize> {
-                    correlation_id: &'a str,
-                    #[serde(flatten)]
-                    event: &'a T,
-                }
-                let wrapped = EventWithCorrelation {
-                    correlation_id,
-                    event,
-                };
-                encoding.serialize(&wrapped)?
-            }
-        };
+        let payload_bytes = encode_event(encoding, correlation_id, event)?;
 
         // Send message
         {
@@ -963,6 +935,43 @@ impl AgentClientV2Uds {
     }
 }
 
+/// Serialize an event payload with its correlation ID attached.
+pub(crate) fn encode_event<T: serde::Serialize>(
+    encoding: UdsEncoding,
+    correlation_id: &str,
+    event: &T,
+) -> Result<Vec<u8>, AgentProtocolError> {
+    match encoding {
+        UdsEncoding::Json => {
+            // JSON path: use Value mutation for backwards compatibility
+            let mut payload = serde_json::to_value(event)
+                .map_err(|e| AgentProtocolError::Serialization(e.to_string()))?;
+            if let Some(obj) = payload.as_object_mut() {
+                obj.insert(
+                    "correlation_id".to_string(),
+                    serde_json::Value::String(correlation_id.to_string()),
+                );
+            }
+            serde_json::to_vec(&payload)
+                .map_err(|e| AgentProtocolError::Serialization(e.to_string()))
+        }
+        UdsEncoding::MessagePack => {
+            // MessagePack path: use wrapper struct for efficient serialization
+            #[derive(serde::Serialize)]
+            struct EventWithCorrelation<'a, T: serde::Serialize> {
+                correlation_id: &'a str,
+                #[serde(flatten)]
+                event: &'a T,
+            }
+            let wrapped = EventWithCorrelation {
+                correlation_id,
+                event,
+            };
+            encoding.serialize(&wrapped)
+        }
+    }
+}
+
 /// Write a message to the stream.
 pub async fn write_message<W: AsyncWriteExt + Unpin>(
     writer: &mut W,
@@ -1202,4 +1211,17 @@ mod tests {
         let parsed: serde_json::Value = rmp_serde::from_slice(&serialized).unwrap();
         assert_eq!(parsed, value);
     }
+
+    #[test]
+    #[cfg(feature = "binary-uds")]
+    fn test_agent_response_msgpack_roundtrip() {
+        let encoding = UdsEncoding::MessagePack;
+        let response = AgentResponse::default_allow()
+            .with_upstream("canary")
+            .with_target("10.0.0.5:8080");
+        let serialized = encoding.serialize(&response).unwrap();
+        let parsed: AgentResponse = encoding.deserialize(&serialized).unwrap();
+        assert_eq!(parsed.cache, None);
+        assert_eq!(parsed.routing, response.routing);
+    }
 }
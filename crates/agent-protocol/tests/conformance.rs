@@ -0,0 +1,263 @@
+//! Conformance kit tests against in-process reference agents.
+//!
+//! The agents below speak the v2 UDS wire format directly, the way an agent
+//! written in another language would.
+
+use sentinel_agent_protocol::v2::conformance::{
+    CheckStatus, ConformanceConfig, ConformanceRunner, ConformanceTarget, Scenario,
+};
+use sentinel_agent_protocol::v2::reverse::RegistrationRequest;
+use sentinel_agent_protocol::v2::uds::{read_message, write_message};
+use sentinel_agent_protocol::v2::{
+    MessageType, UdsCapabilities, UdsEncoding, UdsFeatures, UdsHandshakeResponse, UdsLimits,
+};
+use sentinel_agent_protocol::AgentResponse;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tempfile::tempdir;
+use tokio::io::{BufReader, BufWriter};
+use tokio::net::{UnixListener, UnixStream};
+
+/// Request count at which the reference agent pauses once.
+const PAUSE_AT: usize = 10;
+
+#[derive(Clone, Copy)]
+struct Behaviour {
+    /// Echo the correlation ID in `audit.custom`, as the proxy requires
+    tag_responses: bool,
+    /// Answer pings with pongs
+    answer_pings: bool,
+}
+
+const CONFORMING: Behaviour = Behaviour {
+    tag_responses: true,
+    answer_pings: true,
+};
+
+fn capabilities() -> UdsCapabilities {
+    UdsCapabilities {
+        agent_id: "reference-agent".to_string(),
+        name: "Reference Agent".to_string(),
+        version: "1.0.0".to_string(),
+        supported_events: vec![1, 2],
+        features: UdsFeatures {
+            streaming_body: true,
+            cancellation: true,
+            flow_control: true,
+            ..Default::default()
+        },
+        limits: UdsLimits {
+            max_body_size: 1024 * 1024,
+            max_concurrency: 100,
+            preferred_chunk_size: 64 * 1024,
+        },
+    }
+}
+
+async fn send_json(
+    writer: &mut BufWriter<tokio::net::unix::OwnedWriteHalf>,
+    msg_type: MessageType,
+    value: &impl serde::Serialize,
+) {
+    let payload = serde_json::to_vec(value).unwrap();
+    // The kit hangs up on purpose in several scenarios
+    let _ = write_message(writer, msg_type, &payload).await;
+}
+
+/// Serve events on an established connection until the proxy hangs up or
+/// sends a frame the agent cannot read.
+async fn serve(stream: UnixStream, handshake: bool, behaviour: Behaviour) {
+    let (read_half, write_half) = stream.into_split();
+    let mut reader = BufReader::new(read_half);
+    let mut writer = BufWriter::new(write_half);
+
+    if handshake {
+        let Ok((MessageType::HandshakeRequest, _)) = read_message(&mut reader).await else {
+            return;
+        };
+        let response = UdsHandshakeResponse {
+            protocol_version: 2,
+            capabilities: capabilities(),
+            success: true,
+            error: None,
+            encoding: UdsEncoding::Json,
+        };
+        send_json(&mut writer, MessageType::HandshakeResponse, &response).await;
+    }
+
+    let mut cancelled = HashSet::new();
+    let mut answered = 0;
+    while let Ok((msg_type, payload)) = read_message(&mut reader).await {
+        let event: serde_json::Value = serde_json::from_slice(&payload).unwrap_or_default();
+        let correlation_id = event["correlation_id"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        match msg_type {
+            MessageType::RequestHeaders | MessageType::RequestBodyChunk => {
+                if cancelled.contains(&correlation_id) {
+                    continue;
+                }
+                answered += 1;
+                let pause = answered == PAUSE_AT;
+                if pause {
+                    send_json(
+                        &mut writer,
+                        MessageType::FlowControl,
+                        &serde_json::json!({ "action": 1 }),
+                    )
+                    .await;
+                }
+
+                let mut response = AgentResponse::default_allow();
+                if behaviour.tag_responses {
+                    response
+                        .audit
+                        .custom
+                        .insert("correlation_id".to_string(), correlation_id.into());
+                }
+                send_json(&mut writer, MessageType::AgentResponse, &response).await;
+
+                if pause {
+                    send_json(
+                        &mut writer,
+                        MessageType::FlowControl,
+                        &serde_json::json!({ "action": 2 }),
+                    )
+                    .await;
+                }
+            }
+            MessageType::Cancel => {
+                cancelled.insert(correlation_id);
+            }
+            MessageType::Ping if behaviour.answer_pings => {
+                send_json(&mut writer, MessageType::Pong, &event).await;
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn spawn_uds_agent(path: &Path, behaviour: Behaviour) {
+    let listener = UnixListener::bind(path).unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve(stream, true, behaviour));
+        }
+    });
+}
+
+/// Keep registering with the proxy at `path`, reconnecting after each disconnect.
+fn spawn_reverse_agent(path: PathBuf) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let Ok(stream) = UnixStream::connect(&path).await else {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                continue;
+            };
+            let (read_half, write_half) = stream.into_split();
+            let mut reader = BufReader::new(read_half);
+            let mut writer = BufWriter::new(write_half);
+
+            let registration = RegistrationRequest {
+                protocol_version: 2,
+                agent_id: "reference-agent".to_string(),
+                capabilities: capabilities(),
+                auth_token: None,
+                metadata: None,
+            };
+            send_json(&mut writer, MessageType::HandshakeRequest, &registration).await;
+            let Ok((MessageType::HandshakeResponse, _)) = read_message(&mut reader).await else {
+                continue;
+            };
+
+            serve(
+                reader.into_inner().reunite(writer.into_inner()).unwrap(),
+                false,
+                CONFORMING,
+            )
+            .await;
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_conforming_uds_agent_passes() {
+    let dir = tempdir().unwrap();
+    let socket = dir.path().join("agent.sock");
+    spawn_uds_agent(&socket, CONFORMING).await;
+
+    let report = ConformanceRunner::new(ConformanceTarget::Uds(socket))
+        .run()
+        .await;
+
+    assert!(report.is_conformant(), "{}", report.to_json().unwrap());
+    assert_eq!(report.passed, Scenario::ALL.len());
+    assert_eq!(report.agent_id.as_deref(), Some("reference-agent"));
+    assert_eq!(report.encoding, Some(UdsEncoding::Json));
+    assert!(report
+        .check(Scenario::FlowControlPause)
+        .unwrap()
+        .detail
+        .contains("1 pause/resume cycles"));
+}
+
+#[tokio::test]
+async fn test_nonconforming_uds_agent_is_reported() {
+    let dir = tempdir().unwrap();
+    let socket = dir.path().join("agent.sock");
+    spawn_uds_agent(
+        &socket,
+        Behaviour {
+            tag_responses: false,
+            answer_pings: false,
+        },
+    )
+    .await;
+
+    let config = ConformanceConfig {
+        timeout: Duration::from_millis(300),
+        scenarios: vec![
+            Scenario::Handshake,
+            Scenario::RequestHeaders,
+            Scenario::Ping,
+        ],
+        ..Default::default()
+    };
+    let report = ConformanceRunner::with_config(ConformanceTarget::Uds(socket), config)
+        .run()
+        .await;
+
+    assert!(!report.is_conformant());
+    assert_eq!(
+        report.check(Scenario::Handshake).unwrap().status,
+        CheckStatus::Pass
+    );
+    let headers = report.check(Scenario::RequestHeaders).unwrap();
+    assert_eq!(headers.status, CheckStatus::Fail);
+    assert!(headers.detail.contains("correlation_id"));
+    assert_eq!(
+        report.check(Scenario::Ping).unwrap().status,
+        CheckStatus::Fail
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    assert_eq!(json["failed"], 2);
+}
+
+#[tokio::test]
+async fn test_conforming_reverse_agent_passes() {
+    let dir = tempdir().unwrap();
+    let socket = dir.path().join("proxy.sock");
+    let agent = spawn_reverse_agent(socket.clone());
+
+    let report = ConformanceRunner::new(ConformanceTarget::Reverse(socket))
+        .run()
+        .await;
+    agent.abort();
+
+    assert!(report.is_conformant(), "{}", report.to_json().unwrap());
+    assert_eq!(report.transport, "reverse");
+    assert_eq!(report.passed, Scenario::ALL.len());
+}
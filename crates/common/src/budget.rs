@@ -27,7 +27,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// Budgets track cumulative token usage over a configurable period,
 /// with optional alerts and enforcement.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TokenBudgetConfig {
     /// Budget period (when the budget resets)
     #[serde(default)]
@@ -53,6 +53,10 @@ pub struct TokenBudgetConfig {
     /// E.g., 0.10 allows 10% burst above the limit
     #[serde(default)]
     pub burst_allowance: Option<f64>,
+
+    /// Where usage is kept (default: process memory)
+    #[serde(default)]
+    pub storage: BudgetStorage,
 }
 
 fn default_alert_thresholds() -> Vec<f64> {
@@ -72,10 +76,54 @@ impl Default for TokenBudgetConfig {
             enforce: true,
             rollover: false,
             burst_allowance: None,
+            storage: BudgetStorage::Memory,
         }
     }
 }
 
+/// Storage backend for token budget usage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BudgetStorage {
+    /// In process memory; usage resets on restart and is per instance
+    #[default]
+    Memory,
+    /// In process memory, snapshotted to a local file so usage survives restarts
+    File {
+        /// Snapshot file path
+        path: std::path::PathBuf,
+        /// Minimum seconds between snapshot writes
+        #[serde(default = "default_flush_interval_secs")]
+        flush_interval_secs: u64,
+    },
+    /// Shared in Redis with atomic increments, so all instances enforce one budget
+    Redis {
+        /// Redis connection URL (e.g., "redis://127.0.0.1:6379")
+        url: String,
+        /// Key prefix for budget keys
+        #[serde(default = "default_budget_key_prefix")]
+        key_prefix: String,
+        /// Operation timeout in milliseconds
+        #[serde(default = "default_budget_timeout_ms")]
+        timeout_ms: u64,
+        /// Fall back to per-instance tracking if Redis is unavailable
+        #[serde(default = "default_true")]
+        fallback_local: bool,
+    },
+}
+
+fn default_flush_interval_secs() -> u64 {
+    5
+}
+
+fn default_budget_key_prefix() -> String {
+    "sentinel:budget:".to_string()
+}
+
+fn default_budget_timeout_ms() -> u64 {
+    50
+}
+
 /// Budget period defining when the budget resets.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
@@ -357,6 +405,8 @@ pub struct CostResult {
     pub input_tokens: u64,
     /// Number of output tokens
     pub output_tokens: u64,
+    /// Tenant the cost is attributed to
+    pub tenant: Option<String>,
 }
 
 impl CostResult {
@@ -377,6 +427,7 @@ impl CostResult {
             model: model.into(),
             input_tokens,
             output_tokens,
+            tenant: None,
         }
     }
 }
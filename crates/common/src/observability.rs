@@ -110,6 +110,8 @@ pub struct RequestMetrics {
     shadow_latency_seconds: HistogramVec,
     /// Guardrail PII detection metrics
     pii_detected_total: IntCounterVec,
+    /// Tool policy metrics
+    tool_calls_total: IntCounterVec,
 }
 
 /// Return a static string for common HTTP status codes to avoid
@@ -381,6 +383,13 @@ impl RequestMetrics {
         )
         .context("Failed to register pii_detected_total metric")?;
 
+        let tool_calls_total = register_int_counter_vec!(
+            "sentinel_inference_tool_calls_total",
+            "Tool definitions and tool calls checked by the tool policy",
+            &["route", "tool", "direction", "result"]
+        )
+        .context("Failed to register tool_calls_total metric")?;
+
         Ok(Self {
             request_duration,
             request_count,
@@ -410,6 +419,7 @@ impl RequestMetrics {
             shadow_errors_total,
             shadow_latency_seconds,
             pii_detected_total,
+            tool_calls_total,
         })
     }
 
@@ -480,6 +490,17 @@ impl RequestMetrics {
             .inc();
     }
 
+    /// Record a tool checked by the tool policy.
+    ///
+    /// `direction` is "request" for tool definitions and "response" for tool
+    /// calls; `result` is "allowed" or "denied", or "uninspected" (tool "*")
+    /// for a response too large to check.
+    pub fn record_tool_call(&self, route: &str, tool: &str, direction: &str, result: &str) {
+        self.tool_calls_total
+            .with_label_values(&[route, tool, direction, result])
+            .inc();
+    }
+
     /// Record request body size
     pub fn record_request_body_size(&self, route: &str, size_bytes: usize) {
         self.request_body_size
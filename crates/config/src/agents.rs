@@ -105,6 +105,27 @@ pub struct AgentPoolConfig {
     /// Health check interval in milliseconds (default: 10000)
     #[serde(default = "default_health_check_interval_ms")]
     pub health_check_interval_ms: u64,
+
+    /// Request priority (default: normal). Only applies to agents with
+    /// `enforce: false`; low-priority calls are skipped rather than queued
+    /// when the agent is busy.
+    #[serde(default)]
+    pub priority: AgentRequestPriority,
+
+    /// Fraction of requests sent to the agent, 0.0-1.0 (default: 1.0).
+    /// Only applies to agents with `enforce: false`.
+    #[serde(default = "default_pool_sample_rate")]
+    pub sample_rate: f64,
+
+    /// What to do when the agent pauses the flow (default: fail_closed).
+    /// Streamed body chunks wait for the agent, so this also bounds how long
+    /// a paused agent can hold them.
+    #[serde(default)]
+    pub flow_control_mode: AgentFlowControlMode,
+
+    /// How long `wait_and_retry` waits for a paused agent in milliseconds (default: 100)
+    #[serde(default = "default_flow_control_wait_timeout_ms")]
+    pub flow_control_wait_timeout_ms: u64,
 }
 
 impl Default for AgentPoolConfig {
@@ -118,6 +139,10 @@ impl Default for AgentPoolConfig {
             drain_timeout_ms: default_drain_timeout_ms(),
             max_concurrent_per_connection: default_max_concurrent_per_connection(),
             health_check_interval_ms: default_health_check_interval_ms(),
+            priority: AgentRequestPriority::default(),
+            sample_rate: default_pool_sample_rate(),
+            flow_control_mode: AgentFlowControlMode::default(),
+            flow_control_wait_timeout_ms: default_flow_control_wait_timeout_ms(),
         }
     }
 }
@@ -143,6 +168,12 @@ fn default_max_concurrent_per_connection() -> usize {
 fn default_health_check_interval_ms() -> u64 {
     10000
 }
+fn default_pool_sample_rate() -> f64 {
+    1.0
+}
+fn default_flow_control_wait_timeout_ms() -> u64 {
+    100
+}
 
 /// Load balancing strategy for v2 agent pool
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -159,6 +190,30 @@ pub enum LoadBalanceStrategy {
     Random,
 }
 
+/// Request priority for v2 agent pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentRequestPriority {
+    /// Wait for a free connection slot
+    #[default]
+    Normal,
+    /// Skip the call when the agent is saturated or paused
+    Low,
+}
+
+/// Flow control behavior for v2 agent pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentFlowControlMode {
+    /// Fail the call while the agent is paused
+    #[default]
+    FailClosed,
+    /// Skip the agent while it is paused
+    FailOpen,
+    /// Wait briefly for the agent to resume, then fail
+    WaitAndRetry,
+}
+
 /// Agent configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct AgentConfig {
@@ -244,6 +299,18 @@ pub struct AgentConfig {
     /// Default: 100 concurrent calls per agent
     #[serde(default = "default_max_concurrent_calls")]
     pub max_concurrent_calls: usize,
+
+    /// Traffic recording for offline replay (disabled by default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recording: Option<AgentRecordingConfig>,
+
+    /// Whether the agent's decisions are enforced (default: true).
+    ///
+    /// When false the agent runs in shadow mode: it is called off the
+    /// request path and the decisions it would have made (blocks, redirects,
+    /// header operations) are only logged and metered.
+    #[serde(default = "default_enforce")]
+    pub enforce: bool,
 }
 
 fn default_chunk_timeout() -> u64 {
@@ -254,6 +321,68 @@ fn default_max_concurrent_calls() -> usize {
     100 // Per-agent concurrency limit
 }
 
+fn default_enforce() -> bool {
+    true
+}
+
+// ============================================================================
+// Agent Recording
+// ============================================================================
+
+/// Sampled recording of the events sent to an agent and its responses
+///
+/// Recordings are written per request, one file per slot in a fixed-size
+/// ring under `directory`, and can be replayed with `sentinel agent replay`
+/// or loaded as simulator fixtures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentRecordingConfig {
+    /// Directory holding the recording ring
+    pub directory: PathBuf,
+
+    /// Fraction of requests to record, 0.0 to 1.0 (default: 0.01)
+    #[serde(default = "default_recording_sample_rate")]
+    pub sample_rate: f64,
+
+    /// Number of requests kept before the oldest is overwritten (default: 1000)
+    #[serde(default = "default_max_recordings")]
+    pub max_recordings: usize,
+
+    /// Maximum size of a single request's recording in bytes (default: 1MB)
+    ///
+    /// Exchanges past this limit are dropped from the recording.
+    #[serde(default = "default_max_recording_bytes")]
+    pub max_recording_bytes: u64,
+
+    /// Record body chunk contents instead of redacting them (default: false)
+    #[serde(default)]
+    pub include_bodies: bool,
+}
+
+impl AgentRecordingConfig {
+    /// Create a recording config with default limits
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            sample_rate: default_recording_sample_rate(),
+            max_recordings: default_max_recordings(),
+            max_recording_bytes: default_max_recording_bytes(),
+            include_bodies: false,
+        }
+    }
+}
+
+fn default_recording_sample_rate() -> f64 {
+    0.01
+}
+
+fn default_max_recordings() -> usize {
+    1000
+}
+
+fn default_max_recording_bytes() -> u64 {
+    1024 * 1024 // 1MB
+}
+
 // ============================================================================
 // Agent Type
 // ============================================================================
@@ -290,6 +419,54 @@ pub enum AgentTransport {
         url: String,
         tls: Option<AgentTlsConfig>,
     },
+
+    /// In-process WebAssembly component implementing the `sentinel:agent` world
+    Wasm {
+        module: PathBuf,
+        #[serde(default)]
+        config: WasmAgentTransportConfig,
+    },
+}
+
+/// Resource limits for an in-process WASM agent
+///
+/// Limits apply to every call into the component. Unset limits fall back to
+/// the WASM runtime defaults; `max_execution_ms` falls back to the agent's
+/// `timeout_ms`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmAgentTransportConfig {
+    /// Maximum linear memory per instance in bytes
+    #[serde(default)]
+    pub max_memory_bytes: Option<usize>,
+
+    /// Maximum fuel (roughly, WASM instructions) per call
+    #[serde(default)]
+    pub max_fuel: Option<u64>,
+
+    /// Maximum wall-clock time per call in milliseconds
+    #[serde(default)]
+    pub max_execution_ms: Option<u64>,
+
+    /// Number of pre-instantiated component instances (default: 4)
+    ///
+    /// Each instance handles one call at a time.
+    #[serde(default = "default_wasm_instances")]
+    pub instances: usize,
+}
+
+impl Default for WasmAgentTransportConfig {
+    fn default() -> Self {
+        Self {
+            max_memory_bytes: None,
+            max_fuel: None,
+            max_execution_ms: None,
+            instances: default_wasm_instances(),
+        }
+    }
+}
+
+fn default_wasm_instances() -> usize {
+    4
 }
 
 /// Agent TLS configuration
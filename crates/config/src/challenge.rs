@@ -0,0 +1,240 @@
+//! Challenge configuration types
+//!
+//! This module contains configuration for the interstitials the proxy serves
+//! when an agent returns a `Challenge` decision, and for the signed clearance
+//! cookie that lets a client skip further challenges once it has passed one.
+
+use serde::{Deserialize, Serialize};
+
+use crate::upstreams::SameSitePolicy;
+
+// ============================================================================
+// Challenge Configuration
+// ============================================================================
+
+/// Challenge subsystem configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChallengeConfig {
+    /// Secret used to sign clearance cookies and challenge state.
+    ///
+    /// When unset a random key is generated at startup, so clearances do not
+    /// survive restarts and are not shared between instances.
+    #[serde(default)]
+    pub secret: Option<String>,
+
+    /// Name of the clearance cookie
+    #[serde(default = "default_cookie_name")]
+    pub cookie_name: String,
+
+    /// How long a clearance stays valid, in seconds
+    #[serde(default = "default_clearance_ttl_secs")]
+    pub clearance_ttl_secs: u64,
+
+    /// What the clearance is bound to
+    #[serde(default)]
+    pub bind: ClearanceBinding,
+
+    /// Whether to set Secure and HttpOnly flags on challenge cookies
+    #[serde(default = "default_cookie_secure")]
+    pub cookie_secure: bool,
+
+    /// SameSite policy for challenge cookies
+    #[serde(default)]
+    pub cookie_same_site: SameSitePolicy,
+
+    /// How long a client has to solve an issued challenge, in seconds
+    #[serde(default = "default_solve_timeout_secs")]
+    pub solve_timeout_secs: u64,
+
+    /// Path prefix for challenge verification callbacks
+    #[serde(default = "default_path_prefix")]
+    pub path_prefix: String,
+
+    /// Challenge served when an agent requests an unknown challenge type
+    #[serde(default)]
+    pub default_type: ChallengeType,
+
+    /// Proof-of-work difficulty in leading zero bits
+    #[serde(default = "default_pow_difficulty")]
+    pub pow_difficulty: u8,
+
+    /// External CAPTCHA provider
+    #[serde(default)]
+    pub captcha: Option<CaptchaConfig>,
+}
+
+impl Default for ChallengeConfig {
+    fn default() -> Self {
+        Self {
+            secret: None,
+            cookie_name: default_cookie_name(),
+            clearance_ttl_secs: default_clearance_ttl_secs(),
+            bind: ClearanceBinding::default(),
+            cookie_secure: default_cookie_secure(),
+            cookie_same_site: SameSitePolicy::default(),
+            solve_timeout_secs: default_solve_timeout_secs(),
+            path_prefix: default_path_prefix(),
+            default_type: ChallengeType::default(),
+            pow_difficulty: default_pow_difficulty(),
+            captcha: None,
+        }
+    }
+}
+
+fn default_cookie_name() -> String {
+    "sentinel_clearance".to_string()
+}
+
+fn default_clearance_ttl_secs() -> u64 {
+    3600
+}
+
+fn default_cookie_secure() -> bool {
+    true
+}
+
+fn default_solve_timeout_secs() -> u64 {
+    300
+}
+
+fn default_path_prefix() -> String {
+    "/.sentinel/challenge".to_string()
+}
+
+fn default_pow_difficulty() -> u8 {
+    16
+}
+
+/// Maximum proof-of-work difficulty (leading zero bits)
+pub const MAX_POW_DIFFICULTY: u8 = 32;
+
+// ============================================================================
+// Clearance Binding
+// ============================================================================
+
+/// Client attributes a clearance cookie is bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClearanceBinding {
+    /// Bound to the client IP address
+    #[default]
+    Ip,
+    /// Bound to the User-Agent header
+    UserAgent,
+    /// Bound to both the client IP and the User-Agent header
+    IpAndUserAgent,
+}
+
+// ============================================================================
+// Challenge Type
+// ============================================================================
+
+/// Kind of challenge served to the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChallengeType {
+    /// JavaScript proof-of-work interstitial
+    #[default]
+    ProofOfWork,
+    /// Cookie round-trip via redirect
+    Cookie,
+    /// External CAPTCHA widget
+    Captcha,
+}
+
+impl ChallengeType {
+    /// Parse a challenge type as sent by agents or written in config.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "pow" | "proof-of-work" | "proof_of_work" | "js" | "javascript" => {
+                Some(Self::ProofOfWork)
+            }
+            "cookie" | "redirect" => Some(Self::Cookie),
+            "captcha" => Some(Self::Captcha),
+            _ => None,
+        }
+    }
+
+    /// Stable label for metrics and logs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ProofOfWork => "pow",
+            Self::Cookie => "cookie",
+            Self::Captcha => "captcha",
+        }
+    }
+}
+
+// ============================================================================
+// CAPTCHA Provider
+// ============================================================================
+
+/// External CAPTCHA provider configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptchaConfig {
+    /// CAPTCHA provider
+    pub provider: CaptchaProvider,
+
+    /// Public site key rendered into the widget
+    pub site_key: String,
+
+    /// Secret key used for server-side token verification
+    pub secret: String,
+
+    /// Override for the provider's verification endpoint
+    #[serde(default)]
+    pub verify_url: Option<String>,
+}
+
+/// Supported CAPTCHA providers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptchaProvider {
+    Hcaptcha,
+    Turnstile,
+    Recaptcha,
+}
+
+impl CaptchaProvider {
+    /// Parse a provider name.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "hcaptcha" => Some(Self::Hcaptcha),
+            "turnstile" => Some(Self::Turnstile),
+            "recaptcha" => Some(Self::Recaptcha),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge_type_parse() {
+        assert_eq!(ChallengeType::parse("js"), Some(ChallengeType::ProofOfWork));
+        assert_eq!(
+            ChallengeType::parse("PoW"),
+            Some(ChallengeType::ProofOfWork)
+        );
+        assert_eq!(
+            ChallengeType::parse("redirect"),
+            Some(ChallengeType::Cookie)
+        );
+        assert_eq!(
+            ChallengeType::parse("captcha"),
+            Some(ChallengeType::Captcha)
+        );
+        assert_eq!(ChallengeType::parse("unknown"), None);
+    }
+
+    #[test]
+    fn test_challenge_config_defaults() {
+        let config = ChallengeConfig::default();
+        assert_eq!(config.cookie_name, "sentinel_clearance");
+        assert_eq!(config.bind, ClearanceBinding::Ip);
+        assert_eq!(config.default_type, ChallengeType::ProofOfWork);
+        assert!(config.pow_difficulty <= MAX_POW_DIFFICULTY);
+    }
+}
@@ -354,6 +354,7 @@ pub fn create_default_config() -> Config {
         observability: ObservabilityConfig::default(),
         rate_limits: GlobalRateLimitConfig::default(),
         cache: None,
+        challenge: None,
         default_upstream: None,
     }
 }
@@ -59,6 +59,7 @@ pub fn parse_kdl_document(doc: kdl::KdlDocument) -> Result<Config> {
     let mut observability = None;
     let mut rate_limits = None;
     let mut cache = None;
+    let mut challenge = None;
 
     for node in doc.nodes() {
         let node_name = node.name().value();
@@ -127,11 +128,16 @@ pub fn parse_kdl_document(doc: kdl::KdlDocument) -> Result<Config> {
                 cache = Some(parse_cache_config(node)?);
                 trace!("Parsed cache configuration");
             }
+            "challenge" => {
+                challenge = Some(parse_challenge_config(node)?);
+                trace!("Parsed challenge configuration");
+            }
             other => {
                 return Err(anyhow::anyhow!(
                     "Unknown top-level configuration block: '{}'\n\
                      Valid blocks are: schema-version, system, listeners, routes, upstreams, \
-                     filters, agents, waf, namespace, limits, observability, rate-limits, cache",
+                     filters, agents, waf, namespace, limits, observability, rate-limits, cache, \
+                     challenge",
                     other
                 ));
             }
@@ -188,6 +194,7 @@ pub fn parse_kdl_document(doc: kdl::KdlDocument) -> Result<Config> {
         observability: observability.unwrap_or_default(),
         rate_limits: rate_limits.unwrap_or_default(),
         cache,
+        challenge,
         default_upstream: None,
     })
 }
@@ -196,8 +203,12 @@ pub fn parse_kdl_document(doc: kdl::KdlDocument) -> Result<Config> {
 // Agent Parsing
 // ============================================================================
 
-use crate::agents::{AgentEvent, AgentTlsConfig, AgentTransport, AgentType, BodyStreamingMode};
+use crate::agents::{
+    AgentEvent, AgentRecordingConfig, AgentTlsConfig, AgentTransport, AgentType,
+    BodyStreamingMode, WasmAgentTransportConfig,
+};
 use crate::routes::FailureMode;
+use helpers::get_float_entry;
 use sentinel_common::types::CircuitBreakerConfig;
 use std::path::PathBuf;
 
@@ -217,6 +228,12 @@ use std::path::PathBuf;
 ///         timeout-ms 100
 ///         events "request_headers"
 ///     }
+///     agent "allowlist" {
+///         wasm "/etc/sentinel/agents/allowlist.wasm" {
+///             max-fuel 5000000
+///         }
+///         events "request_headers"
+///     }
 /// }
 /// ```
 pub fn parse_agents(node: &kdl::KdlNode) -> Result<Vec<AgentConfig>> {
@@ -357,6 +374,8 @@ fn parse_single_agent(node: &kdl::KdlNode) -> Result<AgentConfig> {
     let mut chunk_timeout_ms = 5000u64;
     let mut config: Option<serde_json::Value> = None;
     let mut max_concurrent_calls = 100usize; // Per-agent concurrency limit
+    let mut recording = None;
+    let enforce = get_bool_entry(node, "enforce").unwrap_or(true);
 
     for child in children.nodes() {
         match child.name().value() {
@@ -386,6 +405,17 @@ fn parse_single_agent(node: &kdl::KdlNode) -> Result<AgentConfig> {
                 let tls = parse_agent_tls(child)?;
                 transport = Some(AgentTransport::Http { url, tls });
             }
+            "wasm" => {
+                let module = get_string_entry(child, "module")
+                    .or_else(|| get_first_arg_string(child))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("wasm requires 'module' attribute or argument")
+                    })?;
+                transport = Some(AgentTransport::Wasm {
+                    module: PathBuf::from(module),
+                    config: parse_wasm_transport(child),
+                });
+            }
             "timeout-ms" => {
                 if let Some(entry) = child.entries().first() {
                     if let Some(v) = entry.value().as_integer() {
@@ -469,13 +499,16 @@ fn parse_single_agent(node: &kdl::KdlNode) -> Result<AgentConfig> {
                     }
                 }
             }
+            "record" => {
+                recording = Some(parse_agent_recording(child, &id)?);
+            }
             _ => {}
         }
     }
 
     let transport = transport.ok_or_else(|| {
         anyhow::anyhow!(
-            "Agent '{}' requires a transport (unix-socket, grpc, or http)",
+            "Agent '{}' requires a transport (unix-socket, grpc, http, or wasm)",
             id
         )
     })?;
@@ -502,6 +535,8 @@ fn parse_single_agent(node: &kdl::KdlNode) -> Result<AgentConfig> {
         chunk_timeout_ms,
         config,
         max_concurrent_calls,
+        recording,
+        enforce,
     })
 }
 
@@ -594,7 +629,86 @@ fn parse_agent_tls(node: &kdl::KdlNode) -> Result<Option<AgentTlsConfig>> {
     }
 }
 
+/// Parse WASM agent resource limits
+///
+/// KDL format:
+/// ```kdl
+/// wasm "/etc/sentinel/agents/allowlist.wasm" {
+///     max-memory-mb 32
+///     max-fuel 5000000
+///     max-execution-ms 20
+///     instances 8
+/// }
+/// ```
+fn parse_wasm_transport(node: &kdl::KdlNode) -> WasmAgentTransportConfig {
+    let mut config = WasmAgentTransportConfig::default();
+
+    let Some(children) = node.children() else {
+        return config;
+    };
+
+    for child in children.nodes() {
+        let value = child
+            .entries()
+            .first()
+            .and_then(|e| e.value().as_integer());
+        match (child.name().value(), value) {
+            ("max-memory-mb", Some(v)) => config.max_memory_bytes = Some(v as usize * 1024 * 1024),
+            ("max-memory-bytes", Some(v)) => config.max_memory_bytes = Some(v as usize),
+            ("max-fuel", Some(v)) => config.max_fuel = Some(v as u64),
+            ("max-execution-ms", Some(v)) => config.max_execution_ms = Some(v as u64),
+            ("instances", Some(v)) => config.instances = v as usize,
+            _ => {}
+        }
+    }
+
+    config
+}
+
 /// Parse circuit breaker configuration
+/// Parse an agent `record` block
+///
+/// KDL format:
+/// ```kdl
+/// record {
+///     directory "/var/lib/sentinel/recordings/waf"
+///     sample-rate 0.01
+///     max-recordings 1000
+///     max-recording-bytes 1048576
+///     include-bodies #false
+/// }
+/// ```
+fn parse_agent_recording(node: &kdl::KdlNode, agent_id: &str) -> Result<AgentRecordingConfig> {
+    let directory = get_string_entry(node, "directory")
+        .or_else(|| get_first_arg_string(node))
+        .ok_or_else(|| {
+            anyhow::anyhow!("Agent '{}' record block requires a 'directory'", agent_id)
+        })?;
+    let mut config = AgentRecordingConfig::new(directory);
+
+    if let Some(v) = get_float_entry(node, "sample-rate") {
+        if !(0.0..=1.0).contains(&v) {
+            return Err(anyhow::anyhow!(
+                "Agent '{}' record sample-rate must be between 0.0 and 1.0, got {}",
+                agent_id,
+                v
+            ));
+        }
+        config.sample_rate = v;
+    }
+    if let Some(v) = get_int_entry(node, "max-recordings") {
+        config.max_recordings = v as usize;
+    }
+    if let Some(v) = get_int_entry(node, "max-recording-bytes") {
+        config.max_recording_bytes = v as u64;
+    }
+    if let Some(v) = get_bool_entry(node, "include-bodies") {
+        config.include_bodies = v;
+    }
+
+    Ok(config)
+}
+
 fn parse_circuit_breaker(node: &kdl::KdlNode) -> Result<CircuitBreakerConfig> {
     let mut config = CircuitBreakerConfig::default();
 
@@ -985,6 +1099,115 @@ pub fn parse_cache_config(node: &kdl::KdlNode) -> Result<CacheStorageConfig> {
     Ok(config)
 }
 
+// ============================================================================
+// Challenge Parsing
+// ============================================================================
+
+use crate::challenge::{
+    CaptchaConfig, CaptchaProvider, ChallengeConfig, ChallengeType, ClearanceBinding,
+};
+use crate::upstreams::SameSitePolicy;
+
+/// Parse challenge configuration block
+///
+/// KDL format:
+/// ```kdl
+/// challenge {
+///     secret "change-me-to-a-long-random-value"
+///     cookie-name "sentinel_clearance"
+///     clearance-ttl 3600         // Seconds a clearance stays valid
+///     bind "ip"                  // "ip", "user-agent", or "ip-and-user-agent"
+///     cookie-secure #true
+///     cookie-same-site "lax"
+///     solve-timeout 300          // Seconds to solve an issued challenge
+///     path-prefix "/.sentinel/challenge"
+///     default-type "pow"         // "pow", "cookie", or "captcha"
+///     pow-difficulty 16          // Leading zero bits
+///
+///     captcha "turnstile" {      // "hcaptcha", "turnstile", or "recaptcha"
+///         site-key "0x4AAAAAAA"
+///         secret "0x4AAAAAAA-secret"
+///     }
+/// }
+/// ```
+pub fn parse_challenge_config(node: &kdl::KdlNode) -> Result<ChallengeConfig> {
+    let mut config = ChallengeConfig {
+        secret: get_string_entry(node, "secret"),
+        ..Default::default()
+    };
+
+    if let Some(name) = get_string_entry(node, "cookie-name") {
+        config.cookie_name = name;
+    }
+    if let Some(v) = get_int_entry(node, "clearance-ttl") {
+        config.clearance_ttl_secs = v as u64;
+    }
+    if let Some(bind) = get_string_entry(node, "bind") {
+        config.bind = match bind.to_lowercase().as_str() {
+            "ip" => ClearanceBinding::Ip,
+            "user-agent" => ClearanceBinding::UserAgent,
+            "ip-and-user-agent" => ClearanceBinding::IpAndUserAgent,
+            other => {
+                return Err(anyhow::anyhow!(
+                    "Invalid challenge bind '{}'. Valid options: ip, user-agent, ip-and-user-agent",
+                    other
+                ));
+            }
+        };
+    }
+    if let Some(v) = get_bool_entry(node, "cookie-secure") {
+        config.cookie_secure = v;
+    }
+    if let Some(same_site) = get_string_entry(node, "cookie-same-site") {
+        config.cookie_same_site = match same_site.to_lowercase().as_str() {
+            "strict" => SameSitePolicy::Strict,
+            "none" => SameSitePolicy::None,
+            _ => SameSitePolicy::Lax,
+        };
+    }
+    if let Some(v) = get_int_entry(node, "solve-timeout") {
+        config.solve_timeout_secs = v as u64;
+    }
+    if let Some(prefix) = get_string_entry(node, "path-prefix") {
+        config.path_prefix = prefix.trim_end_matches('/').to_string();
+    }
+    if let Some(kind) = get_string_entry(node, "default-type") {
+        config.default_type = ChallengeType::parse(&kind).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid challenge default-type '{}'. Valid options: pow, cookie, captcha",
+                kind
+            )
+        })?;
+    }
+    if let Some(v) = get_int_entry(node, "pow-difficulty") {
+        config.pow_difficulty = u8::try_from(v)
+            .map_err(|_| anyhow::anyhow!("Invalid challenge pow-difficulty {}", v))?;
+    }
+
+    if let Some(captcha) = node.children().and_then(|c| c.get("captcha")) {
+        let provider = get_first_arg_string(captcha)
+            .or_else(|| get_string_entry(captcha, "provider"))
+            .ok_or_else(|| anyhow::anyhow!("Challenge captcha requires a provider"))?;
+        let provider = CaptchaProvider::parse(&provider).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid captcha provider '{}'. Valid options: hcaptcha, turnstile, recaptcha",
+                provider
+            )
+        })?;
+
+        config.captcha = Some(CaptchaConfig {
+            provider,
+            site_key: get_string_entry(captcha, "site-key")
+                .ok_or_else(|| anyhow::anyhow!("Challenge captcha requires 'site-key'"))?,
+            secret: get_string_entry(captcha, "secret")
+                .ok_or_else(|| anyhow::anyhow!("Challenge captcha requires 'secret'"))?,
+            verify_url: get_string_entry(captcha, "verify-url"),
+        });
+    }
+
+    Ok(config)
+}
+
 // ============================================================================
 // Rate Limits Parsing
 // ============================================================================
@@ -1102,6 +1325,11 @@ fn parse_rate_limit_key(key: &str) -> Result<RateLimitKey> {
 ///             log-agent-decisions true
 ///             log-waf-events true
 ///         }
+///         inference-log {
+///             file "/var/log/sentinel/inference.log"
+///             max-size-bytes 104857600
+///             max-files 5
+///         }
 ///     }
 /// }
 /// ```
@@ -1159,6 +1387,9 @@ fn parse_logging_config(node: &kdl::KdlNode) -> Result<crate::observability::Log
                 "audit-log" => {
                     config.audit_log = Some(parse_audit_log_config(child)?);
                 }
+                "inference-log" => {
+                    config.inference_log = Some(parse_inference_log_config(child)?);
+                }
                 _ => {
                     trace!(name = %name, "Unknown logging config block, ignoring");
                 }
@@ -1244,6 +1475,34 @@ fn parse_audit_log_config(node: &kdl::KdlNode) -> Result<crate::observability::A
     Ok(config)
 }
 
+/// Parse inference audit log configuration
+fn parse_inference_log_config(
+    node: &kdl::KdlNode,
+) -> Result<crate::observability::InferenceLogConfig> {
+    use crate::observability::InferenceLogConfig;
+    use std::path::PathBuf;
+
+    let mut config = InferenceLogConfig::default();
+
+    if let Some(enabled) = get_bool_entry(node, "enabled") {
+        config.enabled = enabled;
+    }
+    if let Some(file) = get_string_entry(node, "file") {
+        config.file = PathBuf::from(file);
+    }
+    if let Some(buffer_size) = get_int_entry(node, "buffer-size") {
+        config.buffer_size = buffer_size as usize;
+    }
+    if let Some(max_size) = get_int_entry(node, "max-size-bytes") {
+        config.max_size_bytes = max_size as u64;
+    }
+    if let Some(max_files) = get_int_entry(node, "max-files") {
+        config.max_files = max_files as usize;
+    }
+
+    Ok(config)
+}
+
 /// Parse metrics configuration block
 fn parse_metrics_config(node: &kdl::KdlNode) -> Result<crate::observability::MetricsConfig> {
     use crate::observability::MetricsConfig;
@@ -1953,6 +2212,60 @@ mod tests {
         assert_eq!(cache.lock_timeout_secs, 15);
     }
 
+    // =========================================================================
+    // Challenge Configuration Tests
+    // =========================================================================
+
+    #[test]
+    fn test_parse_challenge_config() {
+        let kdl = r#"
+            challenge {
+                secret "0123456789abcdef0123"
+                cookie-name "clearance"
+                clearance-ttl 600
+                bind "ip-and-user-agent"
+                cookie-same-site "strict"
+                path-prefix "/__challenge/"
+                default-type "cookie"
+                pow-difficulty 20
+
+                captcha "turnstile" {
+                    site-key "site"
+                    secret "captcha-secret"
+                }
+            }
+        "#;
+        let doc: kdl::KdlDocument = kdl.parse().unwrap();
+        let node = doc.nodes().first().unwrap();
+
+        let config = parse_challenge_config(node).unwrap();
+        assert_eq!(config.secret.as_deref(), Some("0123456789abcdef0123"));
+        assert_eq!(config.cookie_name, "clearance");
+        assert_eq!(config.clearance_ttl_secs, 600);
+        assert_eq!(config.bind, ClearanceBinding::IpAndUserAgent);
+        assert_eq!(config.cookie_same_site, SameSitePolicy::Strict);
+        assert!(config.cookie_secure);
+        assert_eq!(config.path_prefix, "/__challenge");
+        assert_eq!(config.default_type, ChallengeType::Cookie);
+        assert_eq!(config.pow_difficulty, 20);
+
+        let captcha = config.captcha.unwrap();
+        assert_eq!(captcha.provider, CaptchaProvider::Turnstile);
+        assert_eq!(captcha.site_key, "site");
+        assert_eq!(captcha.secret, "captcha-secret");
+        assert!(captcha.verify_url.is_none());
+    }
+
+    #[test]
+    fn test_parse_challenge_config_invalid_bind() {
+        let kdl = r#"challenge { bind "cookie" }"#;
+        let doc: kdl::KdlDocument = kdl.parse().unwrap();
+        let node = doc.nodes().first().unwrap();
+
+        let err = parse_challenge_config(node).unwrap_err();
+        assert!(err.to_string().contains("Invalid challenge bind"));
+    }
+
     #[test]
     fn test_parse_agent_max_concurrent_calls() {
         let kdl = r#"
@@ -2002,6 +2315,128 @@ mod tests {
         assert_eq!(auth_agent.max_concurrent_calls, 100);
     }
 
+    #[test]
+    fn test_parse_agent_recording() {
+        let kdl = r#"
+            server {
+                worker-threads 4
+            }
+
+            listeners {
+                listener "http" {
+                    address "0.0.0.0:8080"
+                    protocol "http"
+                }
+            }
+
+            agents {
+                agent "waf" type="waf" {
+                    unix-socket path="/tmp/waf.sock"
+                    record {
+                        directory "/var/lib/sentinel/recordings/waf"
+                        sample-rate 0.25
+                        max-recordings 50
+                    }
+                    enforce #false
+                }
+                agent "auth" type="auth" {
+                    unix-socket path="/tmp/auth.sock"
+                }
+            }
+
+            routes {
+                route "default" {
+                    match {
+                        path-prefix "/"
+                    }
+                    builtin "status"
+                }
+            }
+        "#;
+
+        let config = Config::from_kdl(kdl).unwrap();
+
+        let waf_agent = config.agents.iter().find(|a| a.id == "waf").unwrap();
+        let recording = waf_agent.recording.as_ref().unwrap();
+        assert_eq!(
+            recording.directory,
+            PathBuf::from("/var/lib/sentinel/recordings/waf")
+        );
+        assert_eq!(recording.sample_rate, 0.25);
+        assert_eq!(recording.max_recordings, 50);
+        assert_eq!(recording.max_recording_bytes, 1024 * 1024);
+        assert!(!recording.include_bodies);
+
+        let auth_agent = config.agents.iter().find(|a| a.id == "auth").unwrap();
+        assert!(auth_agent.recording.is_none());
+        assert!(!waf_agent.enforce);
+        assert!(auth_agent.enforce);
+    }
+
+    #[test]
+    fn test_parse_wasm_agent() {
+        let kdl = r#"
+            server {
+                worker-threads 4
+            }
+
+            listeners {
+                listener "http" {
+                    address "0.0.0.0:8080"
+                    protocol "http"
+                }
+            }
+
+            agents {
+                agent "allowlist" {
+                    wasm "/tmp/allowlist.wasm" {
+                        max-memory-mb 32
+                        max-fuel 5000000
+                        max-execution-ms 20
+                        instances 8
+                    }
+                    events "request_headers"
+                }
+                agent "defaults" {
+                    wasm module="/tmp/defaults.wasm"
+                    events "request_headers"
+                }
+            }
+
+            routes {
+                route "default" {
+                    match {
+                        path-prefix "/"
+                    }
+                    builtin "status"
+                }
+            }
+        "#;
+
+        let config = Config::from_kdl(kdl).unwrap();
+
+        let agent = config.agents.iter().find(|a| a.id == "allowlist").unwrap();
+        match &agent.transport {
+            AgentTransport::Wasm { module, config } => {
+                assert_eq!(module, &PathBuf::from("/tmp/allowlist.wasm"));
+                assert_eq!(config.max_memory_bytes, Some(32 * 1024 * 1024));
+                assert_eq!(config.max_fuel, Some(5_000_000));
+                assert_eq!(config.max_execution_ms, Some(20));
+                assert_eq!(config.instances, 8);
+            }
+            other => panic!("expected wasm transport, got {:?}", other),
+        }
+
+        let agent = config.agents.iter().find(|a| a.id == "defaults").unwrap();
+        match &agent.transport {
+            AgentTransport::Wasm { module, config } => {
+                assert_eq!(module, &PathBuf::from("/tmp/defaults.wasm"));
+                assert_eq!(config, &WasmAgentTransportConfig::default());
+            }
+            other => panic!("expected wasm transport, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_parse_api_schema_with_file() {
         let kdl = r#"
@@ -2268,6 +2703,9 @@ mod tests {
 
                     inference {
                         provider "openai"
+                        routing {
+                            strategy "least-time-to-first-token"
+                        }
                     }
 
                     fallback {
@@ -2277,6 +2715,7 @@ mod tests {
                             on-health-failure #true
                             on-budget-exhausted #true
                             on-latency-threshold-ms 5000
+                            on-ttft-threshold-ms 2000
                             on-error-codes 429 500 502 503 504
                             on-connection-error #true
                         }
@@ -2325,9 +2764,17 @@ mod tests {
         assert!(fallback.triggers.on_health_failure);
         assert!(fallback.triggers.on_budget_exhausted);
         assert_eq!(fallback.triggers.on_latency_threshold_ms, Some(5000));
+        assert_eq!(fallback.triggers.on_ttft_threshold_ms, Some(2000));
         assert_eq!(fallback.triggers.on_error_codes, vec![429, 500, 502, 503, 504]);
         assert!(fallback.triggers.on_connection_error);
 
+        // TTFT-aware routing
+        let routing = route.inference.as_ref().unwrap().routing.as_ref().unwrap();
+        assert_eq!(
+            routing.strategy,
+            crate::InferenceRoutingStrategy::LeastTimeToFirstToken
+        );
+
         // Check fallback upstreams
         assert_eq!(fallback.upstreams.len(), 2);
 
@@ -2419,6 +2866,63 @@ mod tests {
         assert!(fb_upstream.model_mapping.is_empty()); // default empty
     }
 
+    #[test]
+    fn test_parse_agent_execution_mode() {
+        let kdl = r#"
+            server {
+                worker-threads 4
+            }
+
+            listeners {
+                listener "http" {
+                    address "0.0.0.0:8080"
+                    protocol "http"
+                }
+            }
+
+            upstreams {
+                upstream "backend" {
+                    target "127.0.0.1:8001" weight=1
+                }
+            }
+
+            routes {
+                route "sequential" {
+                    matches {
+                        path-prefix "/api"
+                    }
+                    upstream "backend"
+                    agent-execution "sequential"
+                }
+                route "default" {
+                    matches {
+                        path-prefix "/"
+                    }
+                    upstream "backend"
+                }
+            }
+        "#;
+
+        let config = Config::from_kdl(kdl).expect("Failed to parse agent-execution KDL");
+        let mode = |id: &str| {
+            config
+                .routes
+                .iter()
+                .find(|r| r.id == id)
+                .expect("Route not found")
+                .policies
+                .agent_execution
+        };
+        assert_eq!(mode("sequential"), crate::AgentExecutionMode::Sequential);
+        assert_eq!(mode("default"), crate::AgentExecutionMode::Parallel);
+
+        let invalid = kdl.replace(
+            r#"agent-execution "sequential""#,
+            r#"agent-execution "racing""#,
+        );
+        assert!(Config::from_kdl(&invalid).is_err());
+    }
+
     #[test]
     fn test_parse_model_routing_config() {
         // Test model-based routing configuration
@@ -2527,6 +3031,541 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_budget_storage() {
+        let kdl = r#"
+            server {
+                worker-threads 4
+            }
+
+            listeners {
+                listener "http" {
+                    address "0.0.0.0:8080"
+                    protocol "http"
+                }
+            }
+
+            upstreams {
+                upstream "openai-primary" {
+                    target "api.openai.com:443"
+                }
+            }
+
+            routes {
+                route "inference-api" {
+                    matches {
+                        path-prefix "/v1/chat/completions"
+                    }
+                    upstream "openai-primary"
+
+                    inference {
+                        provider "openai"
+                        budget {
+                            period "monthly"
+                            limit 1000000
+                            storage "redis" {
+                                url "redis://redis.internal:6379"
+                                fallback-local #false
+                            }
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let config = Config::from_kdl(kdl).expect("Failed to parse budget storage KDL");
+        let budget = config.routes[0]
+            .inference
+            .as_ref()
+            .and_then(|i| i.budget.as_ref())
+            .expect("Budget config not found");
+
+        assert_eq!(
+            budget.storage,
+            sentinel_common::budget::BudgetStorage::Redis {
+                url: "redis://redis.internal:6379".to_string(),
+                key_prefix: "sentinel:budget:".to_string(),
+                timeout_ms: 50,
+                fallback_local: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_virtual_keys_and_upstream_secret() {
+        let kdl = r#"
+            server {
+                worker-threads 4
+            }
+
+            listeners {
+                listener "http" {
+                    address "0.0.0.0:8080"
+                    protocol "http"
+                }
+            }
+
+            upstreams {
+                upstream "anthropic" {
+                    target "api.anthropic.com:443"
+                    secret {
+                        file "/run/secrets/anthropic"
+                        header "x-api-key"
+                    }
+                }
+            }
+
+            routes {
+                route "inference-api" {
+                    matches {
+                        path-prefix "/v1/messages"
+                    }
+                    upstream "anthropic"
+
+                    inference {
+                        provider "anthropic"
+                        virtual-keys {
+                            file "/etc/sentinel/virtual-keys.json"
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let config = Config::from_kdl(kdl).expect("Failed to parse virtual keys KDL");
+        let virtual_keys = config.routes[0]
+            .inference
+            .as_ref()
+            .and_then(|i| i.virtual_keys.as_ref())
+            .expect("Virtual keys config not found");
+        assert_eq!(
+            virtual_keys.file,
+            std::path::PathBuf::from("/etc/sentinel/virtual-keys.json")
+        );
+
+        let secret = config.upstreams["anthropic"]
+            .secret
+            .as_ref()
+            .expect("Upstream secret not found");
+        assert_eq!(secret.env, None);
+        assert_eq!(
+            secret.file.as_deref(),
+            Some(std::path::Path::new("/run/secrets/anthropic"))
+        );
+        assert_eq!(secret.header.as_deref(), Some("x-api-key"));
+
+        // A secret needs exactly one source
+        let invalid = kdl.replace(
+            r#"file "/run/secrets/anthropic""#,
+            r#"file "/run/secrets/anthropic"
+                        env "ANTHROPIC_API_KEY""#,
+        );
+        assert!(Config::from_kdl(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_inference_cache() {
+        let kdl = r#"
+            server {
+                worker-threads 4
+            }
+
+            listeners {
+                listener "http" {
+                    address "0.0.0.0:8080"
+                    protocol "http"
+                }
+            }
+
+            upstreams {
+                upstream "openai" {
+                    target "api.openai.com:443"
+                }
+            }
+
+            routes {
+                route "inference-api" {
+                    matches {
+                        path-prefix "/v1/chat/completions"
+                    }
+                    upstream "openai"
+
+                    inference {
+                        provider "openai"
+                        cache {
+                            ttl-secs 600
+                            streaming #false
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let config = Config::from_kdl(kdl).expect("Failed to parse inference cache KDL");
+        let cache = config.routes[0]
+            .inference
+            .as_ref()
+            .and_then(|i| i.cache.as_ref())
+            .expect("Inference cache config not found");
+        assert_eq!(cache.ttl_secs, 600);
+        assert_eq!(cache.max_entries, 10_000);
+        assert!(!cache.streaming);
+
+        let semantic = kdl.replace(
+            "streaming #false",
+            "streaming #false\n semantic { agent \"embedder\" }",
+        );
+        assert!(Config::from_kdl(&semantic).is_err());
+    }
+
+    #[test]
+    fn test_parse_inference_providers() {
+        let kdl = r#"
+            server {
+                worker-threads 4
+            }
+
+            listeners {
+                listener "http" {
+                    address "0.0.0.0:8080"
+                    protocol "http"
+                }
+            }
+
+            upstreams {
+                upstream "local" {
+                    target "127.0.0.1:11434"
+                }
+            }
+
+            routes {
+                route "inference-api" {
+                    matches {
+                        path-prefix "/api/chat"
+                    }
+                    upstream "local"
+
+                    inference {
+                        provider "ollama"
+                    }
+                }
+            }
+        "#;
+
+        let providers = [
+            ("ollama", crate::InferenceProvider::Ollama),
+            ("gemini", crate::InferenceProvider::Gemini),
+            ("bedrock", crate::InferenceProvider::Bedrock),
+            ("vllm", crate::InferenceProvider::Vllm),
+            ("tgi", crate::InferenceProvider::Tgi),
+        ];
+        for (name, expected) in providers {
+            let source = kdl.replace(r#"provider "ollama""#, &format!("provider \"{}\"", name));
+            let config = Config::from_kdl(&source).expect("Failed to parse inference provider");
+            let inference = config.routes[0].inference.as_ref().unwrap();
+            assert_eq!(inference.provider, expected);
+            assert_eq!(inference.provider.as_str(), name);
+            assert_eq!(inference.max_usage_body_bytes, None);
+        }
+
+        let limited = kdl.replace(
+            r#"provider "ollama""#,
+            "provider \"ollama\"\nmax-usage-body-bytes 4096",
+        );
+        let config = Config::from_kdl(&limited).expect("Failed to parse usage body limit");
+        let inference = config.routes[0].inference.as_ref().unwrap();
+        assert_eq!(inference.max_usage_body_bytes, Some(4096));
+
+        let invalid = kdl.replace(r#"provider "ollama""#, r#"provider "llamafile""#);
+        assert!(Config::from_kdl(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_streaming_output_guardrail() {
+        let kdl = r#"
+            server {
+                worker-threads 4
+            }
+
+            listeners {
+                listener "http" {
+                    address "0.0.0.0:8080"
+                    protocol "http"
+                }
+            }
+
+            upstreams {
+                upstream "openai" {
+                    target "api.openai.com:443"
+                }
+            }
+
+            routes {
+                route "inference-api" {
+                    matches {
+                        path-prefix "/v1/chat/completions"
+                    }
+                    upstream "openai"
+
+                    inference {
+                        provider "openai"
+                        guardrails {
+                            streaming-output {
+                                enabled #true
+                                agent "output-guard"
+                                action "redact"
+                                categories "ssn" "credit_card"
+                                window-chars 128
+                                failure-mode "closed"
+                            }
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let config = Config::from_kdl(kdl).expect("Failed to parse streaming guardrail KDL");
+        let streaming = config.routes[0]
+            .inference
+            .as_ref()
+            .and_then(|i| i.guardrails.as_ref())
+            .and_then(|g| g.streaming_output.as_ref())
+            .expect("Streaming output guardrail not found");
+        assert!(streaming.enabled);
+        assert_eq!(streaming.agent, "output-guard");
+        assert_eq!(streaming.action, crate::PiiAction::Redact);
+        assert_eq!(streaming.categories, vec!["ssn", "credit_card"]);
+        assert_eq!(streaming.window_chars, 128);
+        assert_eq!(streaming.timeout_ms, 500);
+        assert_eq!(streaming.failure_mode, crate::GuardrailFailureMode::Closed);
+        assert!(streaming.block_message.is_none());
+
+        let invalid = kdl.replace("window-chars 128", "window-chars 0");
+        assert!(Config::from_kdl(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_tool_policy_guardrail() {
+        let kdl = r#"
+            server {
+                worker-threads 4
+            }
+
+            listeners {
+                listener "http" {
+                    address "0.0.0.0:8080"
+                    protocol "http"
+                }
+            }
+
+            upstreams {
+                upstream "openai" {
+                    target "api.openai.com:443"
+                }
+            }
+
+            routes {
+                route "inference-api" {
+                    matches {
+                        path-prefix "/v1/chat/completions"
+                    }
+                    upstream "openai"
+
+                    inference {
+                        provider "openai"
+                        guardrails {
+                            tool-policy {
+                                enabled #true
+                                action "strip"
+                                allow "search_*" "get_weather"
+                                deny "run_shell"
+                                tenant "ops" {
+                                    allow "*"
+                                    deny "drop_table"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let config = Config::from_kdl(kdl).expect("Failed to parse tool policy KDL");
+        let policy = config.routes[0]
+            .inference
+            .as_ref()
+            .and_then(|i| i.guardrails.as_ref())
+            .and_then(|g| g.tool_policy.as_ref())
+            .expect("Tool policy not found");
+        assert!(policy.enabled);
+        assert_eq!(policy.action, crate::ToolPolicyAction::Strip);
+        assert_eq!(policy.allow, vec!["search_*", "get_weather"]);
+        assert_eq!(policy.deny, vec!["run_shell"]);
+        assert_eq!(policy.block_status, 403);
+        let ops = policy.tenants.get("ops").expect("Tenant lists not found");
+        assert_eq!(ops.allow, vec!["*"]);
+        assert_eq!(ops.deny, vec!["drop_table"]);
+
+        let invalid = kdl.replace(r#"action "strip""#, r#"action "redact""#);
+        assert!(Config::from_kdl(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_inference_audit() {
+        let kdl = r#"
+            server {
+                worker-threads 4
+            }
+
+            listeners {
+                listener "http" {
+                    address "0.0.0.0:8080"
+                    protocol "http"
+                }
+            }
+
+            observability {
+                logging {
+                    inference-log {
+                        file "/tmp/sentinel/inference.log"
+                        max-size-bytes 1048576
+                        max-files 3
+                    }
+                }
+            }
+
+            upstreams {
+                upstream "openai" {
+                    target "api.openai.com:443"
+                }
+            }
+
+            routes {
+                route "inference-api" {
+                    matches {
+                        path-prefix "/v1/chat/completions"
+                    }
+                    upstream "openai"
+
+                    inference {
+                        provider "openai"
+                        audit {
+                            sample-rate 0.25
+                            include-completion #false
+                            content-mode "hash"
+                            redact-patterns "sk-[A-Za-z0-9]+"
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let config = Config::from_kdl(kdl).expect("Failed to parse inference audit KDL");
+        let log = config
+            .observability
+            .logging
+            .inference_log
+            .as_ref()
+            .expect("Inference log config not found");
+        assert!(log.enabled);
+        assert_eq!(
+            log.file,
+            std::path::PathBuf::from("/tmp/sentinel/inference.log")
+        );
+        assert_eq!(log.max_size_bytes, 1_048_576);
+        assert_eq!(log.max_files, 3);
+
+        let audit = config.routes[0]
+            .inference
+            .as_ref()
+            .and_then(|i| i.audit.as_ref())
+            .expect("Inference audit config not found");
+        assert_eq!(audit.sample_rate, 0.25);
+        assert!(audit.include_prompt);
+        assert!(!audit.include_completion);
+        assert_eq!(audit.max_content_chars, 4096);
+        assert_eq!(audit.content_mode, crate::AuditContentMode::Hash);
+        assert_eq!(audit.redact_patterns, vec!["sk-[A-Za-z0-9]+"]);
+
+        let invalid = kdl.replace("sample-rate 0.25", "sample-rate 2.0");
+        assert!(Config::from_kdl(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_inference_priority() {
+        let kdl = r#"
+            server {
+                worker-threads 4
+            }
+
+            listeners {
+                listener "http" {
+                    address "0.0.0.0:8080"
+                    protocol "http"
+                }
+            }
+
+            upstreams {
+                upstream "vllm" {
+                    target "10.0.0.1:8000"
+                }
+            }
+
+            routes {
+                route "inference-api" {
+                    matches {
+                        path-prefix "/v1/chat/completions"
+                    }
+                    upstream "vllm"
+
+                    inference {
+                        provider "vllm"
+                        routing {
+                            queue-depth-header "x-queue-depth"
+                        }
+                        priority {
+                            max-in-flight-tokens 200000
+                            max-upstream-queue-depth 32
+                            max-queued 500
+                            class "interactive" weight=8 queue-timeout-ms=2000
+                            class "batch" weight=1 queue-timeout-ms=60000
+                            tenant "search" class="interactive"
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let config = Config::from_kdl(kdl).expect("Failed to parse inference priority KDL");
+        let priority = config.routes[0]
+            .inference
+            .as_ref()
+            .and_then(|i| i.priority.as_ref())
+            .expect("Inference priority config not found");
+        assert_eq!(priority.classes.len(), 2);
+        assert_eq!(priority.classes[0].name, "interactive");
+        assert_eq!(priority.classes[0].weight, 8);
+        assert_eq!(priority.classes[1].queue_timeout_ms, 60_000);
+        // Defaults to the lowest-weight class
+        assert_eq!(priority.default_class, "batch");
+        assert_eq!(
+            priority.tenants.get("search").map(String::as_str),
+            Some("interactive")
+        );
+        assert_eq!(priority.max_in_flight_tokens, Some(200_000));
+        assert_eq!(priority.max_upstream_queue_depth, Some(32));
+        assert_eq!(priority.max_queued, 500);
+        assert_eq!(priority.retry_after_secs, 5);
+
+        // Tenants must map to a defined class
+        let invalid = kdl.replace(r#"class="interactive""#, r#"class="realtime""#);
+        assert!(Config::from_kdl(&invalid).is_err());
+
+        // Upstream queue depth can only be observed with a queue depth header
+        let invalid = kdl.replace(r#"queue-depth-header "x-queue-depth""#, "");
+        assert!(Config::from_kdl(&invalid).is_err());
+    }
+
     #[test]
     fn test_parse_model_routing_minimal() {
         // Test minimal model routing without provider override
@@ -5,7 +5,9 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use tracing::trace;
 
-use sentinel_common::budget::{BudgetPeriod, CostAttributionConfig, ModelPricing, TokenBudgetConfig};
+use sentinel_common::budget::{
+    BudgetPeriod, BudgetStorage, CostAttributionConfig, ModelPricing, TokenBudgetConfig,
+};
 
 use crate::routes::*;
 
@@ -91,6 +93,8 @@ pub fn parse_routes(node: &kdl::KdlNode) -> Result<Vec<RouteConfig>> {
                 // Build route policies with optional cache config
                 let policies = RoutePolicies {
                     cache: cache_config,
+                    client_cert_forwarding: parse_client_cert_forwarding_opt(child)?,
+                    agent_execution: parse_agent_execution(child)?,
                     ..RoutePolicies::default()
                 };
 
@@ -147,6 +151,21 @@ fn parse_match_conditions(node: &kdl::KdlNode) -> Result<Vec<MatchCondition>> {
                                 matches.push(MatchCondition::Host(host));
                             }
                         }
+                        "client-cert-subject" => {
+                            if let Some(subject) = get_first_arg_string(match_node) {
+                                matches.push(MatchCondition::ClientCertSubject(subject));
+                            }
+                        }
+                        "client-cert-san" => {
+                            if let Some(san) = get_first_arg_string(match_node) {
+                                matches.push(MatchCondition::ClientCertSan(san));
+                            }
+                        }
+                        "client-cert-fingerprint" => {
+                            if let Some(fingerprint) = get_first_arg_string(match_node) {
+                                matches.push(MatchCondition::ClientCertFingerprint(fingerprint));
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -157,6 +176,98 @@ fn parse_match_conditions(node: &kdl::KdlNode) -> Result<Vec<MatchCondition>> {
     Ok(matches)
 }
 
+/// Parse the route's agent execution mode
+///
+/// Example KDL:
+/// ```kdl
+/// agent-execution "sequential"    // "parallel" (default) or "sequential"
+/// ```
+fn parse_agent_execution(node: &kdl::KdlNode) -> Result<AgentExecutionMode> {
+    match get_string_entry(node, "agent-execution").as_deref() {
+        None | Some("parallel") => Ok(AgentExecutionMode::Parallel),
+        Some("sequential") => Ok(AgentExecutionMode::Sequential),
+        Some(other) => Err(anyhow::anyhow!(
+            "Invalid agent-execution '{}'. Valid modes: sequential, parallel",
+            other
+        )),
+    }
+}
+
+/// Parse client certificate forwarding configuration
+///
+/// Example KDL:
+/// ```kdl
+/// client-cert-forwarding {
+///     format "headers"            // "headers", "rfc9440" or "both"
+///     header-prefix "X-Client-Cert-"
+///     fields "subject" "san" "fingerprint"
+/// }
+/// ```
+fn parse_client_cert_forwarding_opt(
+    node: &kdl::KdlNode,
+) -> Result<Option<ClientCertForwardingConfig>> {
+    let Some(forwarding_node) = node
+        .children()
+        .and_then(|children| children.get("client-cert-forwarding"))
+    else {
+        return Ok(None);
+    };
+
+    let mut config = ClientCertForwardingConfig::default();
+
+    if let Some(format) = get_string_entry(forwarding_node, "format") {
+        config.format = match format.as_str() {
+            "headers" => ClientCertHeaderFormat::Headers,
+            "rfc9440" => ClientCertHeaderFormat::Rfc9440,
+            "both" => ClientCertHeaderFormat::Both,
+            other => {
+                return Err(anyhow::anyhow!(
+                    "Invalid client-cert-forwarding format '{}'. Valid formats: headers, rfc9440, both",
+                    other
+                ))
+            }
+        };
+    }
+
+    if let Some(prefix) = get_string_entry(forwarding_node, "header-prefix") {
+        config.header_prefix = prefix;
+    }
+
+    if let Some(fields_node) = forwarding_node
+        .children()
+        .and_then(|children| children.get("fields"))
+    {
+        for entry in fields_node.entries() {
+            let Some(name) = entry.value().as_string() else {
+                continue;
+            };
+            let field = match name {
+                "subject" => ClientCertField::Subject,
+                "issuer" => ClientCertField::Issuer,
+                "san" => ClientCertField::San,
+                "serial" => ClientCertField::Serial,
+                "fingerprint" => ClientCertField::Fingerprint,
+                other => {
+                    return Err(anyhow::anyhow!(
+                        "Invalid client-cert-forwarding field '{}'. Valid fields: subject, issuer, san, serial, fingerprint",
+                        other
+                    ))
+                }
+            };
+            config.fields.push(field);
+        }
+    }
+
+    trace!(
+        format = ?config.format,
+        header_prefix = %config.header_prefix,
+        fields = ?config.fields,
+        "Parsed client certificate forwarding"
+    );
+
+    Ok(Some(config))
+}
+
 fn parse_priority(node: &kdl::KdlNode) -> sentinel_common::types::Priority {
     match get_string_entry(node, "priority").as_deref() {
         Some("high") => sentinel_common::types::Priority::High,
@@ -617,6 +728,7 @@ fn parse_fallback_triggers(node: &kdl::KdlNode) -> Result<FallbackTriggers> {
     let on_health_failure = get_bool_entry(node, "on-health-failure").unwrap_or(true);
     let on_budget_exhausted = get_bool_entry(node, "on-budget-exhausted").unwrap_or(false);
     let on_latency_threshold_ms = get_int_entry(node, "on-latency-threshold-ms").map(|v| v as u64);
+    let on_ttft_threshold_ms = get_int_entry(node, "on-ttft-threshold-ms").map(|v| v as u64);
     let on_connection_error = get_bool_entry(node, "on-connection-error").unwrap_or(true);
 
     // Parse error codes (integer arguments)
@@ -647,6 +759,7 @@ fn parse_fallback_triggers(node: &kdl::KdlNode) -> Result<FallbackTriggers> {
         on_health_failure,
         on_budget_exhausted,
         on_latency_threshold_ms,
+        on_ttft_threshold_ms,
         on_error_codes,
         on_connection_error,
     })
@@ -743,11 +856,10 @@ fn parse_model_mapping(node: &kdl::KdlNode) -> Result<HashMap<String, String>> {
 
 /// Parse inference provider from node
 fn parse_inference_provider(node: &kdl::KdlNode) -> InferenceProvider {
-    match get_string_entry(node, "provider").as_deref() {
-        Some("openai") => InferenceProvider::OpenAi,
-        Some("anthropic") => InferenceProvider::Anthropic,
-        _ => InferenceProvider::Generic,
-    }
+    get_string_entry(node, "provider")
+        .as_deref()
+        .and_then(InferenceProvider::from_name)
+        .unwrap_or_default()
 }
 
 /// Parse optional model routing configuration from an inference block.
@@ -850,12 +962,7 @@ fn parse_model_upstream_mapping(node: &kdl::KdlNode) -> Result<Option<ModelUpstr
         .find(|e| e.name().map(|n| n.value()) == Some("provider"))
         .and_then(|e| e.value().as_string());
 
-    let provider = match provider_str {
-        Some("openai") => Some(InferenceProvider::OpenAi),
-        Some("anthropic") => Some(InferenceProvider::Anthropic),
-        Some("generic") => Some(InferenceProvider::Generic),
-        Some(_) | None => None,
-    };
+    let provider = provider_str.and_then(InferenceProvider::from_name);
 
     tracing::trace!(
         model_pattern = %model_pattern,
@@ -905,15 +1012,14 @@ fn parse_inference_config_opt(node: &kdl::KdlNode) -> Result<Option<InferenceCon
 fn parse_inference_config(node: &kdl::KdlNode) -> Result<InferenceConfig> {
     // Parse provider
     let provider = match get_string_entry(node, "provider").as_deref() {
-        Some("openai") | Some("open-ai") | Some("open_ai") => InferenceProvider::OpenAi,
-        Some("anthropic") => InferenceProvider::Anthropic,
-        Some("generic") | None => InferenceProvider::Generic,
-        Some(other) => {
-            return Err(anyhow::anyhow!(
-                "Unknown inference provider '{}'. Valid providers: openai, anthropic, generic",
-                other
-            ));
-        }
+        None => InferenceProvider::Generic,
+        Some(name) => InferenceProvider::from_name(name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown inference provider '{}'. Valid providers: openai, anthropic, gemini, \
+                 bedrock, ollama, vllm, tgi, generic",
+                name
+            )
+        })?,
     };
 
     let model_header = get_string_entry(node, "model-header");
@@ -977,6 +1083,46 @@ fn parse_inference_config(node: &kdl::KdlNode) -> Result<InferenceConfig> {
     // Parse guardrails block if present
     let guardrails = parse_guardrails_config_opt(node)?;
 
+    // Parse virtual-keys block if present
+    let virtual_keys = match node.children().and_then(|c| c.get("virtual-keys")) {
+        Some(keys_node) => Some(VirtualKeysConfig {
+            file: get_string_entry(keys_node, "file")
+                .map(PathBuf::from)
+                .ok_or_else(|| anyhow::anyhow!("Inference virtual-keys requires 'file'"))?,
+        }),
+        None => None,
+    };
+
+    // Parse cache block if present
+    let cache = match node.children().and_then(|c| c.get("cache")) {
+        Some(cache_node) => Some(parse_inference_cache_config(cache_node)?),
+        None => None,
+    };
+
+    // Parse audit block if present
+    let audit = match node.children().and_then(|c| c.get("audit")) {
+        Some(audit_node) => Some(parse_inference_audit_config(audit_node)?),
+        None => None,
+    };
+
+    // Parse priority block if present
+    let priority = match node.children().and_then(|c| c.get("priority")) {
+        Some(priority_node) => Some(parse_inference_priority_config(priority_node)?),
+        None => None,
+    };
+    if let Some(max_depth) = priority.as_ref().and_then(|p| p.max_upstream_queue_depth) {
+        if routing
+            .as_ref()
+            .and_then(|r| r.queue_depth_header.as_ref())
+            .is_none()
+        {
+            return Err(anyhow::anyhow!(
+                "Inference priority max-upstream-queue-depth {} requires routing queue-depth-header",
+                max_depth
+            ));
+        }
+    }
+
     Ok(InferenceConfig {
         provider,
         model_header,
@@ -986,9 +1132,276 @@ fn parse_inference_config(node: &kdl::KdlNode) -> Result<InferenceConfig> {
         routing,
         model_routing,
         guardrails,
+        virtual_keys,
+        cache,
+        audit,
+        priority,
+        max_usage_body_bytes: get_int_entry(node, "max-usage-body-bytes").map(|v| v as usize),
     })
 }
 
+/// Parse inference response cache configuration
+///
+/// Example KDL:
+/// ```kdl
+/// cache {
+///     ttl-secs 600
+///     max-entries 5000
+///     streaming #true
+/// }
+/// ```
+fn parse_inference_cache_config(node: &kdl::KdlNode) -> Result<InferenceCacheConfig> {
+    let defaults = InferenceCacheConfig::default();
+
+    // Only identical requests are cached; don't let a similarity block from
+    // an older configuration pass unnoticed
+    if node.children().and_then(|c| c.get("semantic")).is_some() {
+        return Err(anyhow::anyhow!(
+            "Inference cache 'semantic' block is not supported; remove it to cache identical requests"
+        ));
+    }
+
+    let config = InferenceCacheConfig {
+        ttl_secs: get_int_entry(node, "ttl-secs")
+            .map(|v| v as u64)
+            .unwrap_or(defaults.ttl_secs),
+        max_entries: get_int_entry(node, "max-entries")
+            .map(|v| v as usize)
+            .unwrap_or(defaults.max_entries),
+        max_entry_bytes: get_int_entry(node, "max-entry-bytes")
+            .map(|v| v as usize)
+            .unwrap_or(defaults.max_entry_bytes),
+        streaming: get_bool_entry(node, "streaming").unwrap_or(defaults.streaming),
+    };
+
+    trace!(
+        ttl_secs = config.ttl_secs,
+        max_entries = config.max_entries,
+        streaming = config.streaming,
+        "Parsed inference cache configuration"
+    );
+
+    Ok(config)
+}
+
+/// Parse inference audit configuration
+///
+/// KDL format:
+/// ```kdl
+/// audit {
+///     sample-rate 0.1
+///     include-prompt #true
+///     include-completion #true
+///     max-content-chars 2048
+///     content-mode "redact"  // plain, redact, hash
+///     redact-patterns "sk-[A-Za-z0-9]{20,}"
+/// }
+/// ```
+fn parse_inference_audit_config(node: &kdl::KdlNode) -> Result<InferenceAuditConfig> {
+    let defaults = InferenceAuditConfig::default();
+
+    let sample_rate = get_float_entry(node, "sample-rate").unwrap_or(defaults.sample_rate);
+    if !(0.0..=1.0).contains(&sample_rate) {
+        return Err(anyhow::anyhow!(
+            "Inference audit sample-rate must be in [0, 1], got {}",
+            sample_rate
+        ));
+    }
+
+    let content_mode = match get_string_entry(node, "content-mode").as_deref() {
+        Some("plain") => AuditContentMode::Plain,
+        Some("redact") | None => AuditContentMode::Redact,
+        Some("hash") => AuditContentMode::Hash,
+        Some(other) => {
+            return Err(anyhow::anyhow!(
+                "Unknown inference audit content-mode '{}'. Valid modes: plain, redact, hash",
+                other
+            ));
+        }
+    };
+
+    let redact_patterns = node
+        .children()
+        .and_then(|c| c.get("redact-patterns"))
+        .map(|patterns_node| {
+            patterns_node
+                .entries()
+                .iter()
+                .filter_map(|e| e.value().as_string().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let config = InferenceAuditConfig {
+        sample_rate,
+        include_prompt: get_bool_entry(node, "include-prompt").unwrap_or(defaults.include_prompt),
+        include_completion: get_bool_entry(node, "include-completion")
+            .unwrap_or(defaults.include_completion),
+        max_content_chars: get_int_entry(node, "max-content-chars")
+            .map(|v| v as usize)
+            .unwrap_or(defaults.max_content_chars),
+        content_mode,
+        redact_patterns,
+    };
+
+    trace!(
+        sample_rate = config.sample_rate,
+        include_prompt = config.include_prompt,
+        include_completion = config.include_completion,
+        content_mode = ?config.content_mode,
+        redact_patterns = config.redact_patterns.len(),
+        "Parsed inference audit configuration"
+    );
+
+    Ok(config)
+}
+
+/// Parse inference priority classes and admission queue configuration
+///
+/// Example KDL:
+/// ```kdl
+/// priority {
+///     default-class "standard"
+///     max-in-flight-tokens 200000
+///     class "interactive" weight=8 queue-timeout-ms=2000
+///     class "standard" weight=4 queue-timeout-ms=10000
+///     class "batch" weight=1 queue-timeout-ms=60000
+///     tenant "search" class="interactive"
+/// }
+/// ```
+fn parse_inference_priority_config(node: &kdl::KdlNode) -> Result<InferencePriorityConfig> {
+    let property = |node: &kdl::KdlNode, name: &str| {
+        node.entries()
+            .iter()
+            .find(|e| e.name().map(|n| n.value()) == Some(name))
+            .map(|e| e.value().clone())
+    };
+
+    let mut classes = Vec::new();
+    let mut tenants = HashMap::new();
+    for child in node.children().map(|c| c.nodes()).unwrap_or_default() {
+        match child.name().value() {
+            "class" => {
+                let name = get_first_arg_string(child)
+                    .ok_or_else(|| anyhow::anyhow!("Inference priority class requires a name"))?;
+                let weight = property(child, "weight")
+                    .and_then(|v| v.as_integer())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Priority class '{}' requires 'weight'", name)
+                    })?;
+                let queue_timeout_ms = property(child, "queue-timeout-ms")
+                    .and_then(|v| v.as_integer())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Priority class '{}' requires 'queue-timeout-ms'", name)
+                    })?;
+                if !(1..=1000).contains(&weight) {
+                    return Err(anyhow::anyhow!(
+                        "Priority class '{}' weight must be in [1, 1000], got {}",
+                        name,
+                        weight
+                    ));
+                }
+                if !(1..=300_000).contains(&queue_timeout_ms) {
+                    return Err(anyhow::anyhow!(
+                        "Priority class '{}' queue-timeout-ms must be in [1, 300000], got {}",
+                        name,
+                        queue_timeout_ms
+                    ));
+                }
+                if classes.iter().any(|c: &PriorityClassConfig| c.name == name) {
+                    return Err(anyhow::anyhow!("Duplicate priority class '{}'", name));
+                }
+                classes.push(PriorityClassConfig {
+                    name,
+                    weight: weight as u32,
+                    queue_timeout_ms: queue_timeout_ms as u64,
+                });
+            }
+            "tenant" => {
+                let tenant = get_first_arg_string(child)
+                    .ok_or_else(|| anyhow::anyhow!("Inference priority tenant requires a name"))?;
+                let class = property(child, "class")
+                    .and_then(|v| v.as_string().map(str::to_string))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Priority tenant '{}' requires 'class'", tenant)
+                    })?;
+                tenants.insert(tenant, class);
+            }
+            _ => {}
+        }
+    }
+
+    if classes.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Inference priority requires at least one class, e.g., class \"interactive\" weight=8 queue-timeout-ms=2000"
+        ));
+    }
+
+    // Without a default, unassigned requests go to the lowest-weight class
+    let default_class = get_string_entry(node, "default-class").unwrap_or_else(|| {
+        classes
+            .iter()
+            .min_by_key(|c| c.weight)
+            .map(|c| c.name.clone())
+            .unwrap_or_default()
+    });
+    let known = |class: &str| classes.iter().any(|c| c.name == class);
+    if !known(&default_class) {
+        return Err(anyhow::anyhow!(
+            "Inference priority default-class '{}' is not a defined class",
+            default_class
+        ));
+    }
+    if let Some((tenant, class)) = tenants.iter().find(|(_, class)| !known(class)) {
+        return Err(anyhow::anyhow!(
+            "Priority class '{}' of tenant '{}' is not a defined class",
+            class,
+            tenant
+        ));
+    }
+
+    let max_in_flight_tokens = get_int_entry(node, "max-in-flight-tokens").map(|v| v as u64);
+    let max_upstream_queue_depth =
+        get_int_entry(node, "max-upstream-queue-depth").map(|v| v as u64);
+    if max_in_flight_tokens.is_none() && max_upstream_queue_depth.is_none() {
+        return Err(anyhow::anyhow!(
+            "Inference priority requires max-in-flight-tokens or max-upstream-queue-depth"
+        ));
+    }
+
+    let max_queued = get_int_entry(node, "max-queued").unwrap_or(1000);
+    if !(1..=100_000).contains(&max_queued) {
+        return Err(anyhow::anyhow!(
+            "Inference priority max-queued must be in [1, 100000], got {}",
+            max_queued
+        ));
+    }
+
+    let config = InferencePriorityConfig {
+        classes,
+        default_class,
+        tenants,
+        max_in_flight_tokens,
+        max_upstream_queue_depth,
+        max_queued: max_queued as usize,
+        retry_after_secs: get_int_entry(node, "retry-after-secs")
+            .map(|v| v as u64)
+            .unwrap_or(5),
+    };
+
+    trace!(
+        classes = config.classes.len(),
+        default_class = %config.default_class,
+        tenants = config.tenants.len(),
+        max_in_flight_tokens = ?config.max_in_flight_tokens,
+        max_upstream_queue_depth = ?config.max_upstream_queue_depth,
+        max_queued = config.max_queued,
+        "Parsed inference priority configuration"
+    );
+
+    Ok(config)
+}
+
 /// Parse token rate limit configuration
 fn parse_token_rate_limit(node: &kdl::KdlNode) -> Result<TokenRateLimit> {
     let tokens_per_minute = get_int_entry(node, "tokens-per-minute")
@@ -1027,9 +1440,12 @@ fn parse_inference_routing(node: &kdl::KdlNode) -> Result<InferenceRouting> {
         }
         Some("round-robin") | Some("round_robin") => InferenceRoutingStrategy::RoundRobin,
         Some("least-latency") | Some("least_latency") => InferenceRoutingStrategy::LeastLatency,
+        Some("least-time-to-first-token") | Some("least_time_to_first_token") => {
+            InferenceRoutingStrategy::LeastTimeToFirstToken
+        }
         Some(other) => {
             return Err(anyhow::anyhow!(
-                "Unknown inference routing strategy '{}'. Valid strategies: least-tokens-queued, round-robin, least-latency",
+                "Unknown inference routing strategy '{}'. Valid strategies: least-tokens-queued, round-robin, least-latency, least-time-to-first-token",
                 other
             ));
         }
@@ -1054,6 +1470,9 @@ fn parse_inference_routing(node: &kdl::KdlNode) -> Result<InferenceRouting> {
 ///     enforce true
 ///     rollover false
 ///     burst-allowance 0.10
+///     storage "file" {
+///         path "/var/lib/sentinel/budgets.json"
+///     }
 /// }
 /// ```
 fn parse_token_budget(node: &kdl::KdlNode) -> Result<TokenBudgetConfig> {
@@ -1099,6 +1518,10 @@ fn parse_token_budget(node: &kdl::KdlNode) -> Result<TokenBudgetConfig> {
     let enforce = get_bool_entry(node, "enforce").unwrap_or(true);
     let rollover = get_bool_entry(node, "rollover").unwrap_or(false);
     let burst_allowance = get_float_entry(node, "burst-allowance");
+    let storage = match node.children().and_then(|c| c.get("storage")) {
+        Some(storage_node) => parse_budget_storage(storage_node)?,
+        None => BudgetStorage::Memory,
+    };
 
     trace!(
         period = ?period,
@@ -1107,6 +1530,7 @@ fn parse_token_budget(node: &kdl::KdlNode) -> Result<TokenBudgetConfig> {
         enforce = enforce,
         rollover = rollover,
         burst_allowance = ?burst_allowance,
+        storage = ?storage,
         "Parsed token budget configuration"
     );
 
@@ -1117,9 +1541,58 @@ fn parse_token_budget(node: &kdl::KdlNode) -> Result<TokenBudgetConfig> {
         enforce,
         rollover,
         burst_allowance,
+        storage,
     })
 }
 
+/// Parse token budget storage
+///
+/// KDL format:
+/// ```kdl
+/// storage "memory"
+/// storage "file" {
+///     path "/var/lib/sentinel/budgets.json"
+///     flush-interval-secs 5
+/// }
+/// storage "redis" {
+///     url "redis://127.0.0.1:6379"
+///     key-prefix "sentinel:budget:"
+///     timeout-ms 50
+///     fallback-local #true
+/// }
+/// ```
+fn parse_budget_storage(node: &kdl::KdlNode) -> Result<BudgetStorage> {
+    let storage_type = get_first_arg_string(node).unwrap_or_else(|| "memory".to_string());
+
+    match storage_type.as_str() {
+        "memory" => Ok(BudgetStorage::Memory),
+        "file" => {
+            let path = get_string_entry(node, "path")
+                .ok_or_else(|| anyhow::anyhow!("File budget storage requires 'path'"))?;
+            Ok(BudgetStorage::File {
+                path: PathBuf::from(path),
+                flush_interval_secs: get_int_entry(node, "flush-interval-secs")
+                    .map(|v| v as u64)
+                    .unwrap_or(5),
+            })
+        }
+        "redis" => Ok(BudgetStorage::Redis {
+            url: get_string_entry(node, "url")
+                .unwrap_or_else(|| "redis://127.0.0.1:6379".to_string()),
+            key_prefix: get_string_entry(node, "key-prefix")
+                .unwrap_or_else(|| "sentinel:budget:".to_string()),
+            timeout_ms: get_int_entry(node, "timeout-ms")
+                .map(|v| v as u64)
+                .unwrap_or(50),
+            fallback_local: get_bool_entry(node, "fallback-local").unwrap_or(true),
+        }),
+        other => Err(anyhow::anyhow!(
+            "Unknown budget storage: '{}'. Valid storage types: memory, file, redis",
+            other
+        )),
+    }
+}
+
 /// Parse cost attribution configuration
 ///
 /// KDL format:
@@ -1235,6 +1708,25 @@ fn parse_model_pricing_list(node: &kdl::KdlNode) -> Result<Vec<ModelPricing>> {
 ///         timeout-ms 1000
 ///         failure-mode "open"
 ///     }
+///
+///     streaming-output {
+///         enabled #true
+///         agent "output-guard"
+///         action "redact"
+///         window-chars 256
+///         timeout-ms 500
+///         failure-mode "closed"
+///     }
+///
+///     tool-policy {
+///         enabled #true
+///         action "strip"
+///         allow "search_*" "get_weather"
+///         deny "run_shell"
+///         tenant "ops" {
+///             allow "*"
+///         }
+///     }
 /// }
 /// ```
 fn parse_guardrails_config_opt(node: &kdl::KdlNode) -> Result<Option<GuardrailsConfig>> {
@@ -1270,15 +1762,31 @@ fn parse_guardrails_config(node: &kdl::KdlNode) -> Result<GuardrailsConfig> {
         None
     };
 
+    // Parse streaming-output sub-block
+    let streaming_output = match node.children().and_then(|c| c.get("streaming-output")) {
+        Some(so_node) => Some(parse_streaming_guardrail_config(so_node)?),
+        None => None,
+    };
+
+    // Parse tool-policy sub-block
+    let tool_policy = match node.children().and_then(|c| c.get("tool-policy")) {
+        Some(tp_node) => Some(parse_tool_policy_config(tp_node)?),
+        None => None,
+    };
+
     trace!(
         has_prompt_injection = prompt_injection.is_some(),
         has_pii_detection = pii_detection.is_some(),
+        has_streaming_output = streaming_output.is_some(),
+        has_tool_policy = tool_policy.is_some(),
         "Parsed guardrails configuration"
     );
 
     Ok(GuardrailsConfig {
         prompt_injection,
         pii_detection,
+        streaming_output,
+        tool_policy,
     })
 }
 
@@ -1403,3 +1911,153 @@ fn parse_pii_detection_config(node: &kdl::KdlNode) -> Result<PiiDetectionConfig>
         failure_mode,
     })
 }
+
+/// Parse tool/function-call policy configuration.
+fn parse_tool_policy_config(node: &kdl::KdlNode) -> Result<ToolPolicyConfig> {
+    // String arguments of a child node, e.g. `allow "search_*" "get_weather"`
+    fn patterns(node: &kdl::KdlNode, name: &str) -> Vec<String> {
+        node.children()
+            .map(|c| c.nodes())
+            .unwrap_or_default()
+            .iter()
+            .filter(|child| child.name().value() == name)
+            .flat_map(|child| child.entries())
+            .filter(|e| e.name().is_none())
+            .filter_map(|e| e.value().as_string().map(|s| s.to_string()))
+            .collect()
+    }
+
+    let enabled = get_bool_entry(node, "enabled").unwrap_or(false);
+
+    let action = match get_string_entry(node, "action").as_deref() {
+        Some("log") => ToolPolicyAction::Log,
+        Some("strip") => ToolPolicyAction::Strip,
+        Some("block") | None => ToolPolicyAction::Block,
+        Some(other) => {
+            return Err(anyhow::anyhow!(
+                "Unknown tool policy action '{}'. Valid actions: log, strip, block",
+                other
+            ));
+        }
+    };
+
+    let mut tenants = HashMap::new();
+    for child in node.children().map(|c| c.nodes()).unwrap_or_default() {
+        if child.name().value() != "tenant" {
+            continue;
+        }
+        let tenant = get_first_arg_string(child)
+            .ok_or_else(|| anyhow::anyhow!("Tool policy tenant requires a name"))?;
+        tenants.insert(
+            tenant,
+            ToolListConfig {
+                allow: patterns(child, "allow"),
+                deny: patterns(child, "deny"),
+            },
+        );
+    }
+
+    let block_status = get_int_entry(node, "block-status").unwrap_or(403);
+    if !(400..=599).contains(&block_status) {
+        return Err(anyhow::anyhow!(
+            "Tool policy 'block-status' must be an error status (400-599), got {}",
+            block_status
+        ));
+    }
+
+    let config = ToolPolicyConfig {
+        enabled,
+        action,
+        allow: patterns(node, "allow"),
+        deny: patterns(node, "deny"),
+        tenants,
+        block_status: block_status as u16,
+        block_message: get_string_entry(node, "block-message"),
+    };
+
+    trace!(
+        enabled = config.enabled,
+        action = ?config.action,
+        allow = ?config.allow,
+        deny = ?config.deny,
+        tenants = config.tenants.len(),
+        "Parsed tool policy configuration"
+    );
+
+    Ok(config)
+}
+
+/// Parse streaming output guardrail configuration.
+fn parse_streaming_guardrail_config(node: &kdl::KdlNode) -> Result<StreamingGuardrailConfig> {
+    let enabled = get_bool_entry(node, "enabled").unwrap_or(false);
+
+    let agent = get_string_entry(node, "agent")
+        .ok_or_else(|| anyhow::anyhow!("Streaming output guardrail requires 'agent' field"))?;
+
+    let action = match get_string_entry(node, "action").as_deref() {
+        Some("log") | None => PiiAction::Log,
+        Some("redact") => PiiAction::Redact,
+        Some("block") => PiiAction::Block,
+        Some(other) => {
+            return Err(anyhow::anyhow!(
+                "Unknown streaming output action '{}'. Valid actions: log, redact, block",
+                other
+            ));
+        }
+    };
+
+    let categories = node
+        .children()
+        .and_then(|c| c.get("categories"))
+        .map(|cat_node| {
+            cat_node
+                .entries()
+                .iter()
+                .filter_map(|e| e.value().as_string().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let window_chars = get_int_entry(node, "window-chars").unwrap_or(256);
+    if window_chars <= 0 {
+        return Err(anyhow::anyhow!(
+            "Streaming output 'window-chars' must be positive, got {}",
+            window_chars
+        ));
+    }
+
+    let timeout_ms = get_int_entry(node, "timeout-ms").unwrap_or(500) as u64;
+    let block_message = get_string_entry(node, "block-message");
+
+    let failure_mode = match get_string_entry(node, "failure-mode").as_deref() {
+        Some("open") | None => GuardrailFailureMode::Open,
+        Some("closed") => GuardrailFailureMode::Closed,
+        Some(other) => {
+            return Err(anyhow::anyhow!(
+                "Unknown failure mode '{}'. Valid modes: open, closed",
+                other
+            ));
+        }
+    };
+
+    trace!(
+        enabled = enabled,
+        agent = %agent,
+        action = ?action,
+        window_chars = window_chars,
+        timeout_ms = timeout_ms,
+        failure_mode = ?failure_mode,
+        "Parsed streaming output guardrail configuration"
+    );
+
+    Ok(StreamingGuardrailConfig {
+        enabled,
+        agent,
+        action,
+        categories,
+        window_chars: window_chars as usize,
+        timeout_ms,
+        failure_mode,
+        block_message,
+    })
+}
@@ -159,6 +159,15 @@ pub fn parse_listeners(node: &kdl::KdlNode) -> Result<Vec<ListenerConfig>> {
 ///         key-file "/etc/certs/example.key"
 ///     }
 ///
+///     // SNI entries can override the listener policy per hostname
+///     sni {
+///         hostnames "partners.example.com"
+///         cert-file "/etc/certs/partners.crt"
+///         key-file "/etc/certs/partners.key"
+///         client-auth true
+///         min-version "1.3"
+///     }
+///
 ///     // Option B: ACME automatic certificates
 ///     acme {
 ///         email "admin@example.com"
@@ -237,6 +246,19 @@ pub fn parse_tls_config(node: &kdl::KdlNode, listener_id: &str) -> Result<TlsCon
         Vec::new()
     };
 
+    // An SNI entry that requires client certificates needs a CA to verify
+    // them against, either its own or the listener's.
+    if let Some(sni) = additional_certs
+        .iter()
+        .find(|sni| sni.client_auth == Some(true) && sni.ca_file.is_none() && ca_file.is_none())
+    {
+        return Err(anyhow::anyhow!(
+            "SNI certificate {:?} for listener '{}' sets 'client-auth true' but no 'ca-file' is configured",
+            sni.hostnames,
+            listener_id
+        ));
+    }
+
     debug!(
         listener_id = %listener_id,
         has_cert_file = cert_file.is_some(),
@@ -545,6 +567,13 @@ fn parse_propagation_config(node: &kdl::KdlNode) -> PropagationCheckConfig {
 ///     hostnames "example.com" "www.example.com"
 ///     cert-file "/etc/certs/example.crt"
 ///     key-file "/etc/certs/example.key"
+///
+///     // Optional per-hostname policy overrides
+///     ca-file "/etc/certs/partners-ca.crt"
+///     client-auth true
+///     min-version "1.3"
+///     cipher-suite "TLS13_AES_256_GCM_SHA384"
+///     alpn "h2"
 /// }
 /// ```
 fn parse_sni_certificate(node: &kdl::KdlNode, listener_id: &str) -> Result<SniCertificate> {
@@ -589,10 +618,51 @@ fn parse_sni_certificate(node: &kdl::KdlNode, listener_id: &str) -> Result<SniCe
             )
         })?;
 
+    // Optional policy overrides (inherit from the listener when unset)
+    let ca_file = get_string_entry(node, "ca-file").map(PathBuf::from);
+    let client_auth = get_bool_entry(node, "client-auth");
+    let min_version = get_string_entry(node, "min-version").map(|s| parse_tls_version(&s));
+    let max_version = get_string_entry(node, "max-version").map(|s| parse_tls_version(&s));
+
+    let (cipher_suites, alpn_protocols) = if let Some(children) = node.children() {
+        let cipher_suites = children
+            .nodes()
+            .iter()
+            .filter(|n| n.name().value() == "cipher-suite")
+            .filter_map(get_first_arg_string)
+            .collect();
+        let alpn_protocols = children
+            .nodes()
+            .iter()
+            .filter(|n| n.name().value() == "alpn")
+            .flat_map(|n| {
+                n.entries()
+                    .iter()
+                    .filter_map(|e| e.value().as_string().map(|s| s.to_string()))
+            })
+            .collect();
+        (cipher_suites, alpn_protocols)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    if let (Some(min), Some(max)) = (min_version, max_version) {
+        if min == TlsVersion::Tls13 && max == TlsVersion::Tls12 {
+            return Err(anyhow::anyhow!(
+                "SNI certificate {:?} for listener '{}' has min-version greater than max-version",
+                hostnames,
+                listener_id
+            ));
+        }
+    }
+
     debug!(
         listener_id = %listener_id,
         hostnames = ?hostnames,
         cert_file = %cert_file.display(),
+        client_auth = ?client_auth,
+        min_version = ?min_version,
+        alpn = ?alpn_protocols,
         "Parsed SNI certificate"
     );
 
@@ -600,6 +670,12 @@ fn parse_sni_certificate(node: &kdl::KdlNode, listener_id: &str) -> Result<SniCe
         hostnames,
         cert_file,
         key_file,
+        ca_file,
+        client_auth,
+        min_version,
+        max_version,
+        cipher_suites,
+        alpn_protocols,
     })
 }
 
@@ -162,6 +162,20 @@ pub fn parse_upstreams(node: &kdl::KdlNode) -> Result<HashMap<String, UpstreamCo
                     );
                 }
 
+                // Parse secret (credential injected into forwarded requests)
+                let secret = child
+                    .children()
+                    .and_then(|c| c.nodes().iter().find(|n| n.name().value() == "secret"))
+                    .map(|n| parse_upstream_secret(&id, n))
+                    .transpose()?;
+
+                // Parse AWS SigV4 request signing
+                let aws_sigv4 = child
+                    .children()
+                    .and_then(|c| c.nodes().iter().find(|n| n.name().value() == "aws-sigv4"))
+                    .map(|n| parse_aws_sigv4(&id, n))
+                    .transpose()?;
+
                 trace!(
                     upstream_id = %id,
                     target_count = targets.len(),
@@ -186,6 +200,8 @@ pub fn parse_upstreams(node: &kdl::KdlNode) -> Result<HashMap<String, UpstreamCo
                         timeouts,
                         tls,
                         http_version,
+                        secret,
+                        aws_sigv4,
                     },
                 );
             }
@@ -429,6 +445,17 @@ fn parse_upstream_timeouts(node: &kdl::KdlNode) -> UpstreamTimeouts {
 ///     ca-cert "/path/to/ca.crt"
 /// }
 /// ```
+///
+/// Or with a SPIFFE workload identity from the Workload API:
+/// ```kdl
+/// tls {
+///     spiffe {
+///         socket "/run/spire/sockets/agent.sock"
+///         allowed-id "spiffe://example.org/ns/prod/sa/payments"
+///         fetch-timeout-secs 10
+///     }
+/// }
+/// ```
 fn parse_upstream_tls(node: &kdl::KdlNode) -> UpstreamTlsConfig {
     let sni = find_string_entry_from_node(node, "sni");
 
@@ -440,13 +467,92 @@ fn parse_upstream_tls(node: &kdl::KdlNode) -> UpstreamTlsConfig {
 
     let ca_cert = find_string_entry_from_node(node, "ca-cert").map(PathBuf::from);
 
+    let spiffe = node
+        .children()
+        .and_then(|c| c.nodes().iter().find(|n| n.name().value() == "spiffe"))
+        .map(parse_spiffe);
+
     UpstreamTlsConfig {
         sni,
         insecure_skip_verify,
         client_cert,
         client_key,
         ca_cert,
+        spiffe,
+    }
+}
+
+/// Parse upstream secret configuration
+///
+/// Example KDL:
+/// ```kdl
+/// secret {
+///     env "OPENAI_API_KEY"
+///     header "authorization"
+///     prefix "Bearer "
+/// }
+/// ```
+fn parse_upstream_secret(upstream_id: &str, node: &kdl::KdlNode) -> Result<UpstreamSecretConfig> {
+    let secret = UpstreamSecretConfig {
+        env: find_string_entry_from_node(node, "env"),
+        file: find_string_entry_from_node(node, "file").map(PathBuf::from),
+        header: find_string_entry_from_node(node, "header"),
+        prefix: find_string_entry_from_node(node, "prefix"),
+    };
+
+    if secret.env.is_some() == secret.file.is_some() {
+        return Err(anyhow::anyhow!(
+            "Upstream '{}' secret requires exactly one of 'env' or 'file'",
+            upstream_id
+        ));
+    }
+
+    Ok(secret)
+}
+
+/// Parse AWS SigV4 signing configuration
+///
+/// Example KDL:
+/// ```kdl
+/// aws-sigv4 {
+///     region "us-east-1"
+///     service "bedrock"
+/// }
+/// ```
+fn parse_aws_sigv4(upstream_id: &str, node: &kdl::KdlNode) -> Result<AwsSigV4Config> {
+    let region = find_string_entry_from_node(node, "region")
+        .ok_or_else(|| anyhow::anyhow!("Upstream '{}' aws-sigv4 requires 'region'", upstream_id))?;
+
+    Ok(AwsSigV4Config {
+        region,
+        service: find_string_entry_from_node(node, "service")
+            .unwrap_or_else(default_aws_sigv4_service),
+    })
+}
+
+/// Parse SPIFFE workload identity configuration
+fn parse_spiffe(node: &kdl::KdlNode) -> SpiffeConfig {
+    let mut config = SpiffeConfig::default();
+
+    if let Some(socket) = find_string_entry_from_node(node, "socket") {
+        config.socket = PathBuf::from(socket.strip_prefix("unix://").unwrap_or(&socket));
+    }
+
+    if let Some(children) = node.children() {
+        config.allowed_ids = children
+            .nodes()
+            .iter()
+            .filter(|n| n.name().value() == "allowed-id")
+            .flat_map(|n| n.entries().iter())
+            .filter_map(|e| e.value().as_string().map(|s| s.to_string()))
+            .collect();
     }
+
+    if let Some(timeout) = get_int_entry(node, "fetch-timeout-secs") {
+        config.fetch_timeout_secs = timeout as u64;
+    }
+
+    config
 }
 
 /// Find a string entry in a node's children by name
@@ -851,4 +957,60 @@ mod tests {
         assert!(tls.sni.is_none());
         assert!(tls.ca_cert.is_none());
     }
+
+    #[test]
+    fn test_parse_upstream_tls_spiffe() {
+        let kdl = r#"
+        upstreams {
+            upstream "payments" {
+                target "10.0.0.1:8443"
+                tls {
+                    spiffe {
+                        socket "unix:///run/spire/sockets/agent.sock"
+                        allowed-id "spiffe://example.org/ns/prod/sa/payments"
+                        allowed-id "spiffe://example.org/ns/prod/sa/ledger-*"
+                        fetch-timeout-secs 3
+                    }
+                }
+            }
+        }
+        "#;
+
+        let upstreams = parse_kdl_upstreams(kdl).unwrap();
+        let tls = upstreams.get("payments").unwrap().tls.as_ref().unwrap();
+        let spiffe = tls.spiffe.as_ref().unwrap();
+
+        assert_eq!(spiffe.socket, PathBuf::from("/run/spire/sockets/agent.sock"));
+        assert_eq!(
+            spiffe.allowed_ids,
+            vec![
+                "spiffe://example.org/ns/prod/sa/payments".to_string(),
+                "spiffe://example.org/ns/prod/sa/ledger-*".to_string(),
+            ]
+        );
+        assert_eq!(spiffe.fetch_timeout_secs, 3);
+        assert!(tls.client_cert.is_none());
+    }
+
+    #[test]
+    fn test_parse_upstream_aws_sigv4() {
+        let kdl = r#"
+        upstreams {
+            upstream "bedrock" {
+                target "bedrock-runtime.us-east-1.amazonaws.com:443"
+                aws-sigv4 {
+                    region "us-east-1"
+                }
+            }
+        }
+        "#;
+
+        let upstreams = parse_kdl_upstreams(kdl).unwrap();
+        let sigv4 = upstreams["bedrock"].aws_sigv4.as_ref().unwrap();
+        assert_eq!(sigv4.region, "us-east-1");
+        assert_eq!(sigv4.service, "bedrock");
+
+        let missing_region = kdl.replace(r#"region "us-east-1""#, r#"service "bedrock""#);
+        assert!(parse_kdl_upstreams(&missing_region).is_err());
+    }
 }
@@ -35,6 +35,7 @@ use sentinel_common::{
 // ============================================================================
 
 pub mod agents;
+pub mod challenge;
 mod defaults;
 pub mod filters;
 pub mod flatten;
@@ -58,8 +59,14 @@ pub mod waf;
 
 // Agents
 pub use agents::{
-    AgentConfig, AgentEvent, AgentPoolConfig, AgentProtocolVersion, AgentTlsConfig,
-    AgentTransport, AgentType, BodyStreamingMode, LoadBalanceStrategy,
+    AgentConfig, AgentEvent, AgentFlowControlMode, AgentPoolConfig, AgentProtocolVersion,
+    AgentRecordingConfig, AgentRequestPriority, AgentTlsConfig, AgentTransport, AgentType,
+    BodyStreamingMode, LoadBalanceStrategy, WasmAgentTransportConfig,
+};
+
+// Challenges
+pub use challenge::{
+    CaptchaConfig, CaptchaProvider, ChallengeConfig, ChallengeType, ClearanceBinding,
 };
 
 // Defaults
@@ -83,19 +90,23 @@ pub use resolution::ResourceResolver;
 
 // Observability
 pub use observability::{
-    AccessLogConfig, AccessLogFields, AuditLogConfig, ErrorLogConfig, LoggingConfig,
-    MetricsConfig, ObservabilityConfig, TracingBackend, TracingConfig,
+    AccessLogConfig, AccessLogFields, AuditLogConfig, ErrorLogConfig, InferenceLogConfig,
+    LoggingConfig, MetricsConfig, ObservabilityConfig, TracingBackend, TracingConfig,
 };
 
 // Routes
 pub use routes::{
-    ApiSchemaConfig, BuiltinHandler, CacheBackend, CacheStorageConfig, ErrorFormat, ErrorPage,
+    AgentExecutionMode, ApiSchemaConfig, AuditContentMode, BuiltinHandler, CacheBackend,
+    CacheStorageConfig, ClientCertField,
+    ClientCertForwardingConfig, ClientCertHeaderFormat, ErrorFormat, ErrorPage,
     ErrorPageConfig, FallbackConfig, FallbackTriggers, FallbackUpstream, FailureMode,
-    GuardrailAction, GuardrailFailureMode, GuardrailsConfig, HeaderModifications, InferenceConfig,
+    GuardrailAction, GuardrailFailureMode, GuardrailsConfig, HeaderModifications,
+    InferenceAuditConfig, InferenceCacheConfig, InferenceConfig, InferencePriorityConfig,
     InferenceProvider, InferenceRouting, InferenceRoutingStrategy, MatchCondition,
     ModelRoutingConfig, ModelUpstreamMapping, PiiAction, PiiDetectionConfig,
-    PromptInjectionConfig, RateLimitPolicy, RouteCacheConfig, RouteConfig, RoutePolicies,
-    ServiceType, StaticFileConfig, TokenEstimation, TokenRateLimit,
+    PriorityClassConfig, PromptInjectionConfig, RateLimitPolicy, RouteCacheConfig, RouteConfig, RoutePolicies,
+    ServiceType, StaticFileConfig, StreamingGuardrailConfig, TokenEstimation,
+    TokenRateLimit, ToolListConfig, ToolPolicyAction, ToolPolicyConfig, VirtualKeysConfig,
 };
 
 // Server
@@ -111,8 +122,9 @@ pub use sentinel_common::budget::{
 
 // Upstreams
 pub use upstreams::{
-    ConnectionPoolConfig, HealthCheck, HttpVersionConfig, UpstreamConfig, UpstreamPeer,
-    UpstreamTarget, UpstreamTimeouts, UpstreamTlsConfig,
+    AwsSigV4Config, ConnectionPoolConfig, HealthCheck, HttpVersionConfig, SpiffeConfig,
+    UpstreamConfig, UpstreamPeer, UpstreamSecretConfig, UpstreamTarget, UpstreamTimeouts,
+    UpstreamTlsConfig,
 };
 
 // Validation
@@ -196,6 +208,10 @@ pub struct Config {
     #[serde(default)]
     pub cache: Option<CacheStorageConfig>,
 
+    /// Challenge interstitial and clearance cookie configuration
+    #[serde(default)]
+    pub challenge: Option<ChallengeConfig>,
+
     /// Default upstream for Phase 0 testing
     #[serde(skip)]
     pub default_upstream: Option<UpstreamPeer>,
@@ -504,6 +520,9 @@ impl Config {
         self.limits.validate()?;
         trace!("Limits validation passed");
 
+        self.validate_challenge()?;
+        trace!("Challenge validation passed");
+
         debug!(
             routes = self.routes.len(),
             upstreams = self.upstreams.len(),
@@ -590,6 +609,71 @@ impl Config {
                     });
                 }
             }
+
+            if let AgentTransport::Wasm { config, .. } = &agent.transport {
+                if config.instances == 0 {
+                    return Err(SentinelError::Config {
+                        message: format!(
+                            "Agent '{}' WASM transport requires at least one instance",
+                            agent.id
+                        ),
+                        source: None,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_challenge(&self) -> SentinelResult<()> {
+        let Some(challenge) = &self.challenge else {
+            return Ok(());
+        };
+
+        let error = |message: String| SentinelError::Config {
+            message,
+            source: None,
+        };
+
+        if challenge.clearance_ttl_secs == 0 || challenge.solve_timeout_secs == 0 {
+            return Err(error(
+                "Challenge clearance-ttl and solve-timeout must be greater than zero".to_string(),
+            ));
+        }
+        if challenge.pow_difficulty > challenge::MAX_POW_DIFFICULTY {
+            return Err(error(format!(
+                "Challenge pow-difficulty {} exceeds the maximum of {}",
+                challenge.pow_difficulty,
+                challenge::MAX_POW_DIFFICULTY
+            )));
+        }
+        if !challenge.path_prefix.starts_with('/') || challenge.path_prefix.len() < 2 {
+            return Err(error(format!(
+                "Challenge path-prefix '{}' must be an absolute path other than '/'",
+                challenge.path_prefix
+            )));
+        }
+        if challenge.cookie_name.is_empty()
+            || challenge
+                .cookie_name
+                .contains(|c: char| c.is_whitespace() || ";=,".contains(c))
+        {
+            return Err(error(format!(
+                "Challenge cookie-name '{}' is not a valid cookie name",
+                challenge.cookie_name
+            )));
+        }
+        if challenge.secret.as_ref().is_some_and(|s| s.len() < 16) {
+            return Err(error(
+                "Challenge secret must be at least 16 bytes".to_string(),
+            ));
+        }
+        if let Some(captcha) = &challenge.captcha {
+            if captcha.site_key.is_empty() || captcha.secret.is_empty() {
+                return Err(error(
+                    "Challenge captcha requires both site-key and secret".to_string(),
+                ));
+            }
         }
         Ok(())
     }
@@ -616,6 +700,8 @@ impl Config {
                 timeouts: UpstreamTimeouts::default(),
                 tls: None,
                 http_version: HttpVersionConfig::default(),
+                secret: None,
+                aws_sigv4: None,
             },
         );
 
@@ -673,6 +759,7 @@ impl Config {
             observability: ObservabilityConfig::default(),
             rate_limits: GlobalRateLimitConfig::default(),
             cache: None,
+            challenge: None,
             default_upstream: Some(UpstreamPeer {
                 address: "127.0.0.1:8081".to_string(),
                 tls: false,
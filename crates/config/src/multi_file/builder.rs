@@ -263,6 +263,7 @@ impl ConfigBuilder {
             observability: self.observability.unwrap_or_default(),
             rate_limits: GlobalRateLimitConfig::default(),
             cache: None,
+            challenge: None,
             default_upstream: None,
         })
     }
@@ -239,6 +239,8 @@ pub(super) fn parse_upstream(node: &KdlNode) -> Result<(String, UpstreamConfig)>
             timeouts: crate::UpstreamTimeouts::default(),
             tls: None,
             http_version: crate::HttpVersionConfig::default(),
+            secret: None,
+            aws_sigv4: None,
         },
     ))
 }
@@ -285,6 +287,8 @@ pub(super) fn parse_agent(node: &KdlNode) -> Result<AgentConfig> {
         max_concurrent_calls: get_int_entry(node, "max-concurrent-calls")
             .map(|v| v as usize)
             .unwrap_or(100),
+        recording: None,
+        enforce: get_bool_entry(node, "enforce").unwrap_or(true),
     })
 }
 
@@ -94,6 +94,10 @@ pub struct LoggingConfig {
     /// Audit log configuration (security events)
     #[serde(default)]
     pub audit_log: Option<AuditLogConfig>,
+
+    /// Inference audit log (prompts, completions, usage and cost)
+    #[serde(default)]
+    pub inference_log: Option<InferenceLogConfig>,
 }
 
 impl Default for LoggingConfig {
@@ -106,6 +110,7 @@ impl Default for LoggingConfig {
             access_log: None,
             error_log: None,
             audit_log: None,
+            inference_log: None,
         }
     }
 }
@@ -290,6 +295,45 @@ impl Default for AuditLogConfig {
     }
 }
 
+/// Inference audit log configuration
+///
+/// Records are written by routes with an `audit` block in their inference
+/// configuration. The file is rotated by size.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceLogConfig {
+    /// Enable inference audit logging
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Inference audit log file path
+    #[serde(default = "default_inference_log_file")]
+    pub file: PathBuf,
+
+    /// Buffer size for writes
+    #[serde(default = "default_buffer_size")]
+    pub buffer_size: usize,
+
+    /// Rotate the file once it would exceed this size
+    #[serde(default = "default_inference_log_max_size_bytes")]
+    pub max_size_bytes: u64,
+
+    /// Rotated files to keep (`<file>.1` .. `<file>.N`)
+    #[serde(default = "default_inference_log_max_files")]
+    pub max_files: usize,
+}
+
+impl Default for InferenceLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            file: default_inference_log_file(),
+            buffer_size: default_buffer_size(),
+            max_size_bytes: default_inference_log_max_size_bytes(),
+            max_files: default_inference_log_max_files(),
+        }
+    }
+}
+
 // ============================================================================
 // Tracing Configuration
 // ============================================================================
@@ -366,6 +410,18 @@ fn default_audit_log_file() -> PathBuf {
     PathBuf::from("/var/log/sentinel/audit.log")
 }
 
+fn default_inference_log_file() -> PathBuf {
+    PathBuf::from("/var/log/sentinel/inference.log")
+}
+
+fn default_inference_log_max_size_bytes() -> u64 {
+    100 * 1024 * 1024
+}
+
+fn default_inference_log_max_files() -> usize {
+    5
+}
+
 fn default_sampling_rate() -> f64 {
     0.01
 }
@@ -131,6 +131,22 @@ pub enum MatchCondition {
 
     /// Match by query parameter
     QueryParam { name: String, value: Option<String> },
+
+    /// Match by client certificate subject DN (mTLS only)
+    ///
+    /// Supports `*` wildcards, e.g. `"CN=*,O=Partners"`.
+    ClientCertSubject(String),
+
+    /// Match by client certificate subject alternative name (mTLS only)
+    ///
+    /// Matches against any SAN value (DNS name, URI, email or IP) and
+    /// supports `*` wildcards, e.g. `"spiffe://example.org/ns/prod/*"`.
+    ClientCertSan(String),
+
+    /// Match by client certificate SHA-256 fingerprint (mTLS only)
+    ///
+    /// Hex encoded; case and `:` separators are ignored.
+    ClientCertFingerprint(String),
 }
 
 // ============================================================================
@@ -205,6 +221,10 @@ pub struct RoutePolicies {
     #[serde(default = "default_failure_mode")]
     pub failure_mode: FailureMode,
 
+    /// How agents for this route process request headers
+    #[serde(default)]
+    pub agent_execution: AgentExecutionMode,
+
     /// Enable request buffering
     #[serde(default)]
     pub buffer_requests: bool,
@@ -216,6 +236,98 @@ pub struct RoutePolicies {
     /// HTTP caching configuration
     #[serde(default)]
     pub cache: Option<RouteCacheConfig>,
+
+    /// Forward the verified client certificate identity to the upstream
+    #[serde(default)]
+    pub client_cert_forwarding: Option<ClientCertForwardingConfig>,
+}
+
+// ============================================================================
+// Client Certificate Forwarding
+// ============================================================================
+
+/// Forwarding of the verified client certificate to upstreams
+///
+/// Any client-supplied headers with the same names are always removed
+/// before forwarding, so upstreams can trust them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientCertForwardingConfig {
+    /// Header format
+    #[serde(default)]
+    pub format: ClientCertHeaderFormat,
+
+    /// Prefix for individual headers (`headers` format)
+    #[serde(default = "default_client_cert_header_prefix")]
+    pub header_prefix: String,
+
+    /// Fields to forward (`headers` format, empty = all)
+    #[serde(default)]
+    pub fields: Vec<ClientCertField>,
+}
+
+impl Default for ClientCertForwardingConfig {
+    fn default() -> Self {
+        Self {
+            format: ClientCertHeaderFormat::default(),
+            header_prefix: default_client_cert_header_prefix(),
+            fields: Vec::new(),
+        }
+    }
+}
+
+/// Header format for forwarded client certificates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientCertHeaderFormat {
+    /// Individual `X-Client-Cert-*` headers with parsed fields
+    #[default]
+    Headers,
+    /// RFC 9440 `Client-Cert` header with the DER certificate
+    Rfc9440,
+    /// Both individual headers and the RFC 9440 header
+    Both,
+}
+
+/// Client certificate field forwarded as an individual header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientCertField {
+    /// `<prefix>Subject`
+    Subject,
+    /// `<prefix>Issuer`
+    Issuer,
+    /// `<prefix>San` (comma-separated)
+    San,
+    /// `<prefix>Serial`
+    Serial,
+    /// `<prefix>Fingerprint` (SHA-256, hex)
+    Fingerprint,
+}
+
+impl ClientCertField {
+    /// All forwardable fields
+    pub const ALL: [ClientCertField; 5] = [
+        ClientCertField::Subject,
+        ClientCertField::Issuer,
+        ClientCertField::San,
+        ClientCertField::Serial,
+        ClientCertField::Fingerprint,
+    ];
+
+    /// Header name suffix for this field
+    pub fn header_suffix(&self) -> &'static str {
+        match self {
+            ClientCertField::Subject => "Subject",
+            ClientCertField::Issuer => "Issuer",
+            ClientCertField::San => "San",
+            ClientCertField::Serial => "Serial",
+            ClientCertField::Fingerprint => "Fingerprint",
+        }
+    }
+}
+
+fn default_client_cert_header_prefix() -> String {
+    "X-Client-Cert-".to_string()
 }
 
 // ============================================================================
@@ -432,6 +544,17 @@ pub(crate) fn default_failure_mode() -> FailureMode {
     FailureMode::Closed
 }
 
+/// How a route's agents process request headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentExecutionMode {
+    /// Call agents one after another in filter order, stopping at the first block
+    Sequential,
+    /// Call all agents concurrently; the first block wins and cancels the rest
+    #[default]
+    Parallel,
+}
+
 // ============================================================================
 // Static File Configuration
 // ============================================================================
@@ -650,6 +773,211 @@ pub struct InferenceConfig {
 
     /// Semantic guardrails configuration (prompt injection, PII detection)
     pub guardrails: Option<GuardrailsConfig>,
+
+    /// Sentinel-issued virtual API keys (client authentication per tenant)
+    #[serde(default)]
+    pub virtual_keys: Option<VirtualKeysConfig>,
+
+    /// Response cache for repeated prompts
+    #[serde(default)]
+    pub cache: Option<InferenceCacheConfig>,
+
+    /// Records written to the inference audit log
+    #[serde(default)]
+    pub audit: Option<InferenceAuditConfig>,
+
+    /// Priority classes and fair queuing in front of the upstream
+    #[serde(default)]
+    pub priority: Option<InferencePriorityConfig>,
+
+    /// Largest non-streaming response body read for token usage (default: 1 MiB)
+    #[serde(default)]
+    pub max_usage_body_bytes: Option<usize>,
+}
+
+/// Virtual API key store for an inference route
+///
+/// Clients authenticate with Sentinel-issued keys instead of provider keys.
+/// Each key maps to a tenant with its own model allowlist, token rate limit,
+/// budget, and expiry. The file is reloaded when it changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VirtualKeysConfig {
+    /// JSON file holding the keys
+    pub file: PathBuf,
+}
+
+/// Inference response cache configuration
+///
+/// Responses are keyed on the normalised request: model, messages, tool
+/// definitions, and sampling parameters. Streamed responses are recorded and
+/// replayed as the same SSE stream. Cache hits count no tokens against rate
+/// limits or budgets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceCacheConfig {
+    /// How long a response is served from cache (default: 3600)
+    #[serde(default = "default_inference_cache_ttl_secs")]
+    pub ttl_secs: u64,
+
+    /// Maximum number of cached responses (default: 10000)
+    #[serde(default = "default_inference_cache_max_entries")]
+    pub max_entries: usize,
+
+    /// Largest response body that is cached, in bytes (default: 1 MiB)
+    #[serde(default = "default_inference_cache_max_entry_bytes")]
+    pub max_entry_bytes: usize,
+
+    /// Also cache streamed (SSE) responses (default: true)
+    #[serde(default = "default_true")]
+    pub streaming: bool,
+}
+
+impl Default for InferenceCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: default_inference_cache_ttl_secs(),
+            max_entries: default_inference_cache_max_entries(),
+            max_entry_bytes: default_inference_cache_max_entry_bytes(),
+            streaming: true,
+        }
+    }
+}
+
+/// Inference audit logging for a route
+///
+/// Sampled requests are written to the inference audit log (see
+/// `observability.logging.inference-log`) with tenant, model, token usage,
+/// cost, guardrail detections and latency. Prompts and completions are
+/// truncated and, by default, have PII redacted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceAuditConfig {
+    /// Fraction of requests recorded (0.0-1.0, default: 1.0)
+    #[serde(default = "default_inference_audit_sample_rate")]
+    pub sample_rate: f64,
+
+    /// Record the prompt (default: true)
+    #[serde(default = "default_true")]
+    pub include_prompt: bool,
+
+    /// Record the completion (default: true)
+    #[serde(default = "default_true")]
+    pub include_completion: bool,
+
+    /// Prompts and completions are truncated to this many characters (default: 4096)
+    #[serde(default = "default_inference_audit_max_content_chars")]
+    pub max_content_chars: usize,
+
+    /// How prompts and completions are written
+    #[serde(default)]
+    pub content_mode: AuditContentMode,
+
+    /// Extra regular expressions redacted in addition to the built-in patterns
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+}
+
+impl Default for InferenceAuditConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: default_inference_audit_sample_rate(),
+            include_prompt: true,
+            include_completion: true,
+            max_content_chars: default_inference_audit_max_content_chars(),
+            content_mode: AuditContentMode::default(),
+            redact_patterns: Vec::new(),
+        }
+    }
+}
+
+/// How prompt and completion text appears in the inference audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditContentMode {
+    /// Text as sent (truncated)
+    Plain,
+    /// Credit card numbers, SSNs, emails, phone numbers and custom patterns
+    /// replaced by placeholders (truncated)
+    #[default]
+    Redact,
+    /// SHA-256 of the full text only
+    Hash,
+}
+
+/// Priority classes and admission queue for an inference route
+///
+/// While the upstream is saturated (estimated tokens in flight, or the queue
+/// depth it reports in `routing.queue-depth-header`), requests wait in a
+/// bounded queue and are admitted by weighted fair queuing across classes.
+/// When the queue is full, the lowest-weight class is shed first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferencePriorityConfig {
+    /// Priority classes (at least one)
+    pub classes: Vec<PriorityClassConfig>,
+
+    /// Class of requests whose tenant or key has none assigned
+    pub default_class: String,
+
+    /// Class per tenant (the virtual key's tenant, otherwise the client IP)
+    #[serde(default)]
+    pub tenants: HashMap<String, String>,
+
+    /// Queue requests while the estimated tokens of in-flight requests would
+    /// exceed this
+    #[serde(default)]
+    pub max_in_flight_tokens: Option<u64>,
+
+    /// Queue requests while the upstream reports at least this queue depth
+    #[serde(default)]
+    pub max_upstream_queue_depth: Option<u64>,
+
+    /// Most requests waiting across all classes (default: 1000)
+    #[serde(default = "default_priority_max_queued")]
+    pub max_queued: usize,
+
+    /// `Retry-After` sent with shed requests, in seconds (default: 5)
+    #[serde(default = "default_priority_retry_after_secs")]
+    pub retry_after_secs: u64,
+}
+
+/// A priority class of inference requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriorityClassConfig {
+    /// Class name
+    pub name: String,
+
+    /// Share of admissions while requests are queued; classes with the
+    /// lowest weight are shed first
+    pub weight: u32,
+
+    /// Longest time a request of this class waits for admission
+    pub queue_timeout_ms: u64,
+}
+
+fn default_priority_max_queued() -> usize {
+    1000
+}
+
+fn default_priority_retry_after_secs() -> u64 {
+    5
+}
+
+fn default_inference_audit_sample_rate() -> f64 {
+    1.0
+}
+
+fn default_inference_audit_max_content_chars() -> usize {
+    4096
+}
+
+fn default_inference_cache_ttl_secs() -> u64 {
+    3600
+}
+
+fn default_inference_cache_max_entries() -> usize {
+    10_000
+}
+
+fn default_inference_cache_max_entry_bytes() -> usize {
+    1024 * 1024
 }
 
 
@@ -664,6 +992,16 @@ pub enum InferenceProvider {
     OpenAi,
     /// Anthropic API (uses anthropic-ratelimit-tokens-remaining header)
     Anthropic,
+    /// Google Gemini `generateContent` API (uses `usageMetadata` in the body)
+    Gemini,
+    /// Amazon Bedrock Converse/InvokeModel API (uses x-amzn-bedrock-*-token-count headers)
+    Bedrock,
+    /// Ollama native API (uses `prompt_eval_count` and `eval_count` in the body)
+    Ollama,
+    /// vLLM OpenAI-compatible server (uses `usage` in bodies and streams)
+    Vllm,
+    /// Hugging Face Text Generation Inference (uses x-generated-tokens header or `details`)
+    Tgi,
 }
 
 impl InferenceProvider {
@@ -673,12 +1011,32 @@ impl InferenceProvider {
             Self::Generic => "generic",
             Self::OpenAi => "openai",
             Self::Anthropic => "anthropic",
+            Self::Gemini => "gemini",
+            Self::Bedrock => "bedrock",
+            Self::Ollama => "ollama",
+            Self::Vllm => "vllm",
+            Self::Tgi => "tgi",
+        }
+    }
+
+    /// Parses a provider name as written in configuration.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "generic" => Some(Self::Generic),
+            "openai" | "open-ai" | "open_ai" => Some(Self::OpenAi),
+            "anthropic" => Some(Self::Anthropic),
+            "gemini" => Some(Self::Gemini),
+            "bedrock" => Some(Self::Bedrock),
+            "ollama" => Some(Self::Ollama),
+            "vllm" => Some(Self::Vllm),
+            "tgi" => Some(Self::Tgi),
+            _ => None,
         }
     }
 }
 
 /// Token-based rate limiting configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TokenRateLimit {
     /// Maximum tokens per minute
     pub tokens_per_minute: u64,
@@ -734,6 +1092,9 @@ pub enum InferenceRoutingStrategy {
     RoundRobin,
     /// Route to upstream with lowest observed latency
     LeastLatency,
+    /// Route to the target with the lowest moving average of time to first
+    /// token on streamed responses
+    LeastTimeToFirstToken,
 }
 
 // ============================================================================
@@ -845,6 +1206,11 @@ pub struct FallbackTriggers {
     #[serde(default)]
     pub on_latency_threshold_ms: Option<u64>,
 
+    /// Trigger when the primary upstream's time to first token (moving
+    /// average of its fastest target) exceeds threshold (milliseconds)
+    #[serde(default)]
+    pub on_ttft_threshold_ms: Option<u64>,
+
     /// Trigger on specific HTTP error codes from upstream
     #[serde(default)]
     pub on_error_codes: Vec<u16>,
@@ -860,6 +1226,7 @@ impl Default for FallbackTriggers {
             on_health_failure: true,
             on_budget_exhausted: false,
             on_latency_threshold_ms: None,
+            on_ttft_threshold_ms: None,
             on_error_codes: Vec::new(),
             on_connection_error: true,
         }
@@ -879,6 +1246,8 @@ fn default_max_fallback_attempts() -> u32 {
 /// Enables content inspection via external agents for security:
 /// - Prompt injection detection on requests
 /// - PII detection on responses
+/// - Incremental inspection of streamed responses
+/// - Tool/function-call allow and deny lists
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GuardrailsConfig {
     /// Prompt injection detection configuration
@@ -886,6 +1255,14 @@ pub struct GuardrailsConfig {
 
     /// PII detection configuration
     pub pii_detection: Option<PiiDetectionConfig>,
+
+    /// Streaming output inspection configuration
+    #[serde(default)]
+    pub streaming_output: Option<StreamingGuardrailConfig>,
+
+    /// Tool/function-call policy configuration
+    #[serde(default)]
+    pub tool_policy: Option<ToolPolicyConfig>,
 }
 
 /// Prompt injection detection configuration.
@@ -951,6 +1328,105 @@ pub struct PiiDetectionConfig {
     pub failure_mode: GuardrailFailureMode,
 }
 
+/// Streaming output guardrail configuration.
+///
+/// Holds back streamed (SSE/NDJSON) completion deltas and sends them to an
+/// external agent one window at a time, so content can be redacted or the
+/// stream terminated before it reaches the client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamingGuardrailConfig {
+    /// Enable streaming output inspection
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Name of the agent to use for inspection
+    pub agent: String,
+
+    /// Action to take when the agent detects an issue
+    #[serde(default)]
+    pub action: PiiAction,
+
+    /// Categories to detect, passed to the agent
+    #[serde(default)]
+    pub categories: Vec<String>,
+
+    /// Characters of delta text held back per inspection (default: 256)
+    #[serde(default = "default_streaming_guardrail_window_chars")]
+    pub window_chars: usize,
+
+    /// Agent timeout in milliseconds (default: 500)
+    #[serde(default = "default_streaming_guardrail_timeout_ms")]
+    pub timeout_ms: u64,
+
+    /// Behavior when agent times out or fails
+    #[serde(default)]
+    pub failure_mode: GuardrailFailureMode,
+
+    /// Custom message for the error event that terminates a blocked stream
+    pub block_message: Option<String>,
+}
+
+/// Tool/function-call policy configuration.
+///
+/// Checks the tools a request offers the model and the tool calls the model
+/// makes against allow and deny lists. Unlike the other guardrails, the
+/// policy is enforced by the proxy itself, without an agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolPolicyConfig {
+    /// Enable tool policy enforcement
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Action to take on a disallowed tool
+    #[serde(default)]
+    pub action: ToolPolicyAction,
+
+    /// Allowed tool name patterns (`*` wildcards); empty allows every tool
+    #[serde(default)]
+    pub allow: Vec<String>,
+
+    /// Denied tool name patterns (`*` wildcards), checked before `allow`
+    #[serde(default)]
+    pub deny: Vec<String>,
+
+    /// Per-tenant lists. A tenant's `allow` replaces the route's when set;
+    /// its `deny` adds to the route's.
+    #[serde(default)]
+    pub tenants: HashMap<String, ToolListConfig>,
+
+    /// HTTP status code when blocking a request (default: 403)
+    #[serde(default = "default_tool_policy_block_status")]
+    pub block_status: u16,
+
+    /// Custom message when blocking
+    pub block_message: Option<String>,
+}
+
+/// Tool allow and deny lists of a tenant.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolListConfig {
+    /// Allowed tool name patterns
+    #[serde(default)]
+    pub allow: Vec<String>,
+
+    /// Denied tool name patterns
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// Action to take on a disallowed tool definition or tool call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolPolicyAction {
+    /// Log the violation only
+    Log,
+    /// Remove disallowed tools from requests and tool calls from responses
+    Strip,
+    /// Reject the request, or end the response with an error (default)
+    #[default]
+    Block,
+}
+
 /// Action to take when a guardrail detects an issue
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -999,3 +1475,15 @@ fn default_prompt_injection_timeout_ms() -> u64 {
 fn default_pii_detection_timeout_ms() -> u64 {
     1000
 }
+
+fn default_streaming_guardrail_window_chars() -> usize {
+    256
+}
+
+fn default_streaming_guardrail_timeout_ms() -> u64 {
+    500
+}
+
+fn default_tool_policy_block_status() -> u16 {
+    403
+}
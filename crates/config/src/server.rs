@@ -326,7 +326,12 @@ impl Default for PropagationCheckConfig {
 }
 
 /// SNI certificate configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Besides the certificate itself, each SNI entry may override the
+/// listener-level TLS policy (client auth, protocol versions, cipher
+/// suites and ALPN) for the hostnames it matches. Unset fields inherit
+/// the value from the enclosing [`TlsConfig`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SniCertificate {
     /// Hostname patterns to match (e.g., "example.com", "*.example.com")
     pub hostnames: Vec<String>,
@@ -336,6 +341,42 @@ pub struct SniCertificate {
 
     /// Private key file path
     pub key_file: PathBuf,
+
+    /// CA certificate file for client verification on these hostnames
+    #[serde(default)]
+    pub ca_file: Option<PathBuf>,
+
+    /// Require client certificates (mTLS) on these hostnames
+    #[serde(default)]
+    pub client_auth: Option<bool>,
+
+    /// Minimum TLS version for these hostnames
+    #[serde(default)]
+    pub min_version: Option<TlsVersion>,
+
+    /// Maximum TLS version for these hostnames
+    #[serde(default)]
+    pub max_version: Option<TlsVersion>,
+
+    /// Cipher suites for these hostnames (empty = inherit from listener)
+    #[serde(default)]
+    pub cipher_suites: Vec<String>,
+
+    /// ALPN protocols offered for these hostnames (empty = "h2", "http/1.1")
+    #[serde(default)]
+    pub alpn_protocols: Vec<String>,
+}
+
+impl SniCertificate {
+    /// Whether this entry overrides any part of the listener TLS policy
+    pub fn has_policy_overrides(&self) -> bool {
+        self.ca_file.is_some()
+            || self.client_auth.is_some()
+            || self.min_version.is_some()
+            || self.max_version.is_some()
+            || !self.cipher_suites.is_empty()
+            || !self.alpn_protocols.is_empty()
+    }
 }
 
 // ============================================================================
@@ -116,6 +116,14 @@ pub struct UpstreamConfig {
     /// HTTP version configuration
     #[serde(default)]
     pub http_version: HttpVersionConfig,
+
+    /// Credential injected into forwarded requests (e.g. a provider API key)
+    #[serde(default)]
+    pub secret: Option<UpstreamSecretConfig>,
+
+    /// AWS Signature Version 4 signing of forwarded requests (e.g. Bedrock)
+    #[serde(default)]
+    pub aws_sigv4: Option<AwsSigV4Config>,
 }
 
 /// HTTP version configuration for upstream connections
@@ -298,6 +306,117 @@ pub struct UpstreamTlsConfig {
 
     /// CA certificates
     pub ca_cert: Option<PathBuf>,
+
+    /// SPIFFE workload identity (replaces client_cert/client_key/ca_cert)
+    #[serde(default)]
+    pub spiffe: Option<SpiffeConfig>,
+}
+
+/// SPIFFE Workload API source for upstream mTLS
+///
+/// The X.509 SVID and trust bundles are streamed from the Workload API
+/// (e.g. a SPIRE agent) and rotated without a config reload. Upstream
+/// peers are verified by SPIFFE ID instead of hostname.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpiffeConfig {
+    /// Workload API Unix socket path
+    #[serde(default = "default_spiffe_socket")]
+    pub socket: PathBuf,
+
+    /// Allowed upstream SPIFFE IDs (`*` wildcards supported).
+    /// When empty, any ID in the workload's own trust domain is accepted.
+    #[serde(default)]
+    pub allowed_ids: Vec<String>,
+
+    /// Seconds to wait for the first SVID at startup
+    #[serde(default = "default_spiffe_fetch_timeout")]
+    pub fetch_timeout_secs: u64,
+}
+
+impl Default for SpiffeConfig {
+    fn default() -> Self {
+        Self {
+            socket: default_spiffe_socket(),
+            allowed_ids: Vec::new(),
+            fetch_timeout_secs: default_spiffe_fetch_timeout(),
+        }
+    }
+}
+
+/// Default Workload API socket (`SPIFFE_ENDPOINT_SOCKET`, else the SPIRE agent default)
+fn default_spiffe_socket() -> PathBuf {
+    std::env::var("SPIFFE_ENDPOINT_SOCKET")
+        .ok()
+        .map(|s| PathBuf::from(s.strip_prefix("unix://").unwrap_or(&s)))
+        .unwrap_or_else(|| PathBuf::from("/tmp/spire-agent/public/api.sock"))
+}
+
+fn default_spiffe_fetch_timeout() -> u64 {
+    10
+}
+
+// ============================================================================
+// Upstream Secret Configuration
+// ============================================================================
+
+/// Credential Sentinel sends to the upstream on behalf of clients
+///
+/// Any credential the client sent is stripped and replaced, so provider API
+/// keys stay inside the gateway. Exactly one of `env` or `file` must be set;
+/// the secret is read when the upstream is (re)loaded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpstreamSecretConfig {
+    /// Environment variable holding the secret
+    pub env: Option<String>,
+
+    /// File holding the secret (surrounding whitespace is trimmed)
+    pub file: Option<PathBuf>,
+
+    /// Header carrying the secret. Defaults to `x-api-key` for Anthropic
+    /// upstreams and `authorization` (with a `Bearer ` prefix) otherwise.
+    pub header: Option<String>,
+
+    /// Prefix prepended to the secret (overrides the `Bearer ` default)
+    pub prefix: Option<String>,
+}
+
+impl UpstreamSecretConfig {
+    /// Read the secret value from its source.
+    pub fn resolve(&self) -> Result<String, String> {
+        let value = match (&self.env, &self.file) {
+            (Some(var), None) => std::env::var(var)
+                .map_err(|_| format!("environment variable '{}' is not set", var))?,
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .map_err(|e| format!("failed to read '{}': {}", path.display(), e))?,
+            _ => return Err("exactly one of 'env' or 'file' must be set".to_string()),
+        };
+
+        let value = value.trim();
+        if value.is_empty() {
+            return Err("secret is empty".to_string());
+        }
+        Ok(value.to_string())
+    }
+}
+
+/// AWS Signature Version 4 signing for an upstream such as Amazon Bedrock
+///
+/// Forwarded requests are signed with the credentials in the standard
+/// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and (optionally)
+/// `AWS_SESSION_TOKEN` environment variables, read when the upstream is
+/// (re)loaded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AwsSigV4Config {
+    /// AWS region of the endpoint (e.g. `us-east-1`)
+    pub region: String,
+
+    /// Service name in the credential scope
+    #[serde(default = "default_aws_sigv4_service")]
+    pub service: String,
+}
+
+pub(crate) fn default_aws_sigv4_service() -> String {
+    "bedrock".to_string()
 }
 
 // ============================================================================
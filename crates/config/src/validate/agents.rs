@@ -58,6 +58,9 @@ pub async fn validate_agents(config: &Config) -> ValidationResult {
                 AgentTransport::Http { url, .. } => {
                     validate_http_url(&mut result, agent_id, url);
                 }
+                AgentTransport::Wasm { module, .. } => {
+                    validate_wasm_module(&mut result, agent_id, module);
+                }
             }
         }
     }
@@ -102,6 +105,15 @@ pub async fn validate_agents(config: &Config) -> ValidationResult {
                     )));
                 }
             }
+            AgentTransport::Wasm { module, .. } => {
+                if !module.is_file() {
+                    result.add_warning(ValidationWarning::new(format!(
+                        "Agent '{}' WASM module '{}' does not exist",
+                        agent_config.id,
+                        module.display()
+                    )));
+                }
+            }
         }
     }
 
@@ -149,6 +161,20 @@ fn is_socket_file(path: &Path) -> bool {
     }
 }
 
+/// Validate that a WASM agent's component file exists
+fn validate_wasm_module(result: &mut ValidationResult, agent_id: &str, module: &Path) {
+    if !module.is_file() {
+        result.add_error(ValidationError::new(
+            ErrorCategory::Agent,
+            format!(
+                "Agent '{}' WASM module '{}' does not exist",
+                agent_id,
+                module.display()
+            ),
+        ));
+    }
+}
+
 /// Validate gRPC address format
 fn validate_grpc_address(result: &mut ValidationResult, agent_id: &str, address: &str) {
     if !is_valid_grpc_address(address) {
@@ -245,6 +271,8 @@ mod tests {
             chunk_timeout_ms: 5000,
             config: None,
             max_concurrent_calls: 100,
+            recording: None,
+            enforce: true,
         }
     }
 
@@ -305,6 +333,30 @@ mod tests {
             .any(|e| e.message.contains("does not exist")));
     }
 
+    #[tokio::test]
+    async fn test_validate_wasm_module_missing() {
+        let mut config = Config::default_for_testing();
+        config.routes = vec![test_route_with_filter("wasm-filter")];
+        config.filters.insert(
+            "wasm-filter".to_string(),
+            FilterConfig::new("wasm-filter", Filter::Agent(AgentFilter::new("wasm-agent"))),
+        );
+        config.agents.push(test_agent_config(
+            "wasm-agent",
+            AgentTransport::Wasm {
+                module: PathBuf::from("/nonexistent/agent.wasm"),
+                config: Default::default(),
+            },
+        ));
+
+        let result = validate_agents(&config).await;
+
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| e.message.contains("WASM module") && e.message.contains("does not exist")));
+    }
+
     #[tokio::test]
     async fn test_validate_grpc_invalid_address() {
         let mut config = Config::default_for_testing();
@@ -72,6 +72,8 @@ mod tests {
                 timeouts: UpstreamTimeouts::default(),
                 tls: None,
                 http_version: HttpVersionConfig::default(),
+                secret: None,
+                aws_sigv4: None,
             },
         );
 
@@ -530,6 +530,43 @@ fn validate_routes(
             }
         }
     }
+
+    // An upstream secret or AWS signature is only added for clients
+    // authenticated with a virtual key; without a key store the route could
+    // not use it
+    for route in &config.routes {
+        let inference = route.inference.as_ref();
+        if inference.is_some_and(|i| i.virtual_keys.is_some()) {
+            continue;
+        }
+        let fallbacks = route.fallback.iter().flat_map(|f| &f.upstreams);
+        let model_routing = inference.and_then(|i| i.model_routing.as_ref());
+        let upstreams = route
+            .upstream
+            .iter()
+            .chain(fallbacks.map(|fallback| &fallback.upstream))
+            .chain(model_routing.iter().flat_map(|m| {
+                m.mappings
+                    .iter()
+                    .map(|mapping| &mapping.upstream)
+                    .chain(m.default_upstream.iter())
+            }));
+        for upstream_id in upstreams {
+            if config
+                .upstreams
+                .get(upstream_id)
+                .is_some_and(|upstream| upstream.secret.is_some() || upstream.aws_sigv4.is_some())
+            {
+                errors.push(format!(
+                    "Route '{}' uses upstream '{}', which has a secret or aws-sigv4 credentials, \
+                     but has no virtual keys.\n\
+                     Add 'inference {{ virtual-keys {{ ... }} }}' so only authenticated clients \
+                     can use the upstream's credential.",
+                    route.id, upstream_id
+                ));
+            }
+        }
+    }
 }
 
 fn validate_listeners(config: &Config, route_ids: &HashSet<&str>, errors: &mut Vec<String>) {
@@ -628,6 +665,14 @@ fn validate_upstreams(config: &Config, errors: &mut Vec<String>) {
                 }
             }
         }
+
+        if upstream.secret.is_some() && upstream.aws_sigv4.is_some() {
+            errors.push(format!(
+                "Upstream '{}' has both 'secret' and 'aws-sigv4' configured.\n\
+                 Both supply the upstream's credential; use one of them.",
+                upstream_id
+            ));
+        }
     }
 }
 
@@ -920,8 +965,9 @@ mod tests {
     use super::*;
     use crate::namespace::{ExportConfig, NamespaceConfig, ServiceConfig};
     use crate::{
-        ConnectionPoolConfig, HttpVersionConfig, MatchCondition, RoutePolicies, RouteConfig,
-        UpstreamConfig, UpstreamTarget, UpstreamTimeouts,
+        AwsSigV4Config, ConnectionPoolConfig, FallbackConfig, FallbackUpstream, HttpVersionConfig,
+        InferenceConfig, MatchCondition, RouteConfig, RoutePolicies, UpstreamConfig,
+        UpstreamSecretConfig, UpstreamTarget, UpstreamTimeouts, VirtualKeysConfig,
     };
     use sentinel_common::types::LoadBalancingAlgorithm;
 
@@ -941,6 +987,8 @@ mod tests {
             timeouts: UpstreamTimeouts::default(),
             tls: None,
             http_version: HttpVersionConfig::default(),
+            secret: None,
+            aws_sigv4: None,
         }
     }
 
@@ -968,6 +1016,87 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_upstream_secret_requires_virtual_keys() {
+        let mut config = Config::default_for_testing();
+        let mut upstream = test_upstream("openai");
+        upstream.secret = Some(UpstreamSecretConfig {
+            env: Some("OPENAI_API_KEY".to_string()),
+            file: None,
+            header: None,
+            prefix: None,
+        });
+        config.upstreams.insert("openai".to_string(), upstream);
+        let mut route = test_route("llm", Some("default"));
+        route.fallback = Some(FallbackConfig {
+            upstreams: vec![FallbackUpstream {
+                upstream: "openai".to_string(),
+                provider: Default::default(),
+                model_mapping: HashMap::new(),
+                skip_if_unhealthy: false,
+            }],
+            ..Default::default()
+        });
+        config.routes = vec![route];
+
+        let route_ids = HashSet::from(["llm"]);
+        let upstream_ids = HashSet::from(["default", "openai"]);
+        let mut errors = Vec::new();
+        validate_routes(
+            &config,
+            &route_ids,
+            &upstream_ids,
+            &HashSet::new(),
+            &mut errors,
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("has no virtual keys"));
+
+        config.routes[0].inference = Some(InferenceConfig {
+            virtual_keys: Some(VirtualKeysConfig {
+                file: "/etc/sentinel/virtual-keys.json".into(),
+            }),
+            ..Default::default()
+        });
+        errors.clear();
+        validate_routes(
+            &config,
+            &route_ids,
+            &upstream_ids,
+            &HashSet::new(),
+            &mut errors,
+        );
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_upstream_secret_conflicts_with_aws_sigv4() {
+        let mut config = Config::default_for_testing();
+        let mut upstream = test_upstream("bedrock");
+        upstream.aws_sigv4 = Some(AwsSigV4Config {
+            region: "us-east-1".to_string(),
+            service: "bedrock".to_string(),
+        });
+        config
+            .upstreams
+            .insert("bedrock".to_string(), upstream.clone());
+
+        let mut errors = Vec::new();
+        validate_upstreams(&config, &mut errors);
+        assert!(errors.is_empty());
+
+        upstream.secret = Some(UpstreamSecretConfig {
+            env: Some("BEDROCK_API_KEY".to_string()),
+            file: None,
+            header: None,
+            prefix: None,
+        });
+        config.upstreams.insert("bedrock".to_string(), upstream);
+        validate_upstreams(&config, &mut errors);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("both 'secret' and 'aws-sigv4'"));
+    }
+
     #[test]
     fn test_validation_context_from_config() {
         let mut config = Config::default_for_testing();
@@ -1,10 +1,18 @@
 //! Build script for sentinel-proxy
 //!
-//! Captures version information from git for display in --version output.
+//! Captures version information from git for display in --version output
+//! and compiles the SPIFFE Workload API definition (proto/workload.proto).
 
 use std::process::Command;
 
-fn main() {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Compile SPIFFE Workload API (server is used by the stub in tests)
+    tonic_prost_build::configure()
+        .build_server(true)
+        .build_client(true)
+        .compile_protos(&["proto/workload.proto"], &["proto/"])?;
+    println!("cargo:rerun-if-changed=proto/workload.proto");
+
     // Get the latest git tag (CalVer release version)
     let calver = Command::new("git")
         .args(["describe", "--tags", "--abbrev=0"])
@@ -52,4 +60,6 @@ fn main() {
     // Rebuild if git state changes
     println!("cargo:rerun-if-changed=.git/HEAD");
     println!("cargo:rerun-if-changed=.git/refs/tags");
+
+    Ok(())
 }
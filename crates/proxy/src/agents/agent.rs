@@ -394,6 +394,7 @@ impl Agent {
 
                 Ok(())
             }
+            AgentTransport::Wasm { .. } => Err(self.wasm_transport_error("initialize")),
         }
     }
 
@@ -541,6 +542,17 @@ impl Agent {
                     }
                 }
             }
+            AgentTransport::Wasm { .. } => Err(self.wasm_transport_error("create_client")),
+        }
+    }
+
+    /// WASM agents run in-process and are handled by `WasmAgent`.
+    fn wasm_transport_error(&self, event: &str) -> SentinelError {
+        SentinelError::Agent {
+            agent: self.config.id.clone(),
+            message: "WASM transport is handled in-process, not by a v1 client".to_string(),
+            event: event.to_string(),
+            source: None,
         }
     }
 
@@ -9,18 +9,22 @@ use std::time::{Duration, Instant};
 
 use sentinel_agent_protocol::v2::{
     AgentCapabilities, AgentPool, AgentPoolConfig as ProtocolPoolConfig,
-    AgentPoolStats, CancelReason, ConfigPusher, ConfigUpdateType,
-    LoadBalanceStrategy as ProtocolLBStrategy, MetricsCollector,
+    AgentPoolStats, CancelReason, ConfigPusher, ConfigUpdateType, FlowControlMode,
+    HTTP_ENDPOINT_PREFIX, LoadBalanceStrategy as ProtocolLBStrategy, MetricsCollector,
+    RequestPriority,
 };
 use sentinel_agent_protocol::{
-    AgentResponse, EventType, RequestBodyChunkEvent, RequestHeadersEvent,
+    AgentProtocolError, AgentResponse, EventType, RequestBodyChunkEvent, RequestHeadersEvent,
     ResponseBodyChunkEvent, ResponseHeadersEvent,
 };
 use sentinel_common::{
     errors::{SentinelError, SentinelResult},
     CircuitBreaker,
 };
-use sentinel_config::{AgentConfig, AgentEvent, FailureMode, LoadBalanceStrategy};
+use sentinel_config::{
+    AgentConfig, AgentEvent, AgentFlowControlMode, AgentRequestPriority, FailureMode,
+    LoadBalanceStrategy,
+};
 use tracing::{debug, error, info, trace, warn};
 
 use super::metrics::AgentMetrics;
@@ -60,6 +64,17 @@ impl AgentV2 {
             "Creating v2 agent instance"
         );
 
+        // Priority and sampling only make sense for agents whose decisions
+        // are not enforced; a skipped call would otherwise bypass the agent.
+        if let Some(p) = config.pool.as_ref().filter(|_| config.enforce) {
+            if p.priority != AgentRequestPriority::Normal || p.sample_rate < 1.0 {
+                warn!(
+                    agent_id = %config.id,
+                    "Pool priority and sample-rate only apply to agents with enforce disabled, ignoring"
+                );
+            }
+        }
+
         // Convert config pool settings to protocol pool config
         let pool_config = config.pool.as_ref().map(|p| ProtocolPoolConfig {
             connections_per_agent: p.connections_per_agent,
@@ -71,6 +86,14 @@ impl AgentV2 {
             drain_timeout: Duration::from_millis(p.drain_timeout_ms),
             max_concurrent_per_connection: p.max_concurrent_per_connection,
             health_check_interval: Duration::from_millis(p.health_check_interval_ms),
+            priority: if config.enforce {
+                RequestPriority::Normal
+            } else {
+                convert_priority(p.priority)
+            },
+            sample_rate: if config.enforce { 1.0 } else { p.sample_rate },
+            flow_control_mode: convert_flow_control_mode(p.flow_control_mode),
+            flow_control_wait_timeout: Duration::from_millis(p.flow_control_wait_timeout_ms),
             ..Default::default()
         }).unwrap_or_default();
 
@@ -107,6 +130,14 @@ impl AgentV2 {
         self.config.timeout_ms
     }
 
+    /// Get the agent's decision cache epoch.
+    ///
+    /// Bumped whenever the agent sends a config update that invalidates
+    /// previously cached decisions.
+    pub fn cache_epoch(&self) -> u64 {
+        self.pool.cache_epoch(&self.config.id)
+    }
+
     /// Get the agent's metrics.
     pub fn metrics(&self) -> &AgentMetrics {
         &self.metrics
@@ -179,10 +210,14 @@ impl AgentV2 {
                 Ok(format!("unix:{}", path.display()))
             }
             AgentTransport::Http { url, .. } => {
-                // V2 doesn't support HTTP transport
+                // The pool recognizes HTTP agents by the sentinel+ scheme prefix
+                Ok(format!("{}{}", HTTP_ENDPOINT_PREFIX, url))
+            }
+            AgentTransport::Wasm { .. } => {
+                // WASM agents run in-process via WasmAgent
                 Err(SentinelError::Agent {
                     agent: self.config.id.clone(),
-                    message: "HTTP transport not supported for v2 protocol".to_string(),
+                    message: "WASM transport has no v2 endpoint".to_string(),
                     event: "initialize".to_string(),
                     source: None,
                 })
@@ -232,20 +267,7 @@ impl AgentV2 {
         self.pool
             .send_request_headers(&self.config.id, correlation_id, event)
             .await
-            .map_err(|e| {
-                error!(
-                    agent_id = %self.config.id,
-                    correlation_id = %correlation_id,
-                    error = %e,
-                    "V2 agent request headers call failed"
-                );
-                SentinelError::Agent {
-                    agent: self.config.id.clone(),
-                    message: e.to_string(),
-                    event: "request_headers".to_string(),
-                    source: None,
-                }
-            })
+            .map_err(|e| self.call_error(e, correlation_id, "request_headers"))
     }
 
     /// Call agent with request body chunk event.
@@ -269,20 +291,7 @@ impl AgentV2 {
         self.pool
             .send_request_body_chunk(&self.config.id, correlation_id, event)
             .await
-            .map_err(|e| {
-                error!(
-                    agent_id = %self.config.id,
-                    correlation_id = %correlation_id,
-                    error = %e,
-                    "V2 agent request body chunk call failed"
-                );
-                SentinelError::Agent {
-                    agent: self.config.id.clone(),
-                    message: e.to_string(),
-                    event: "request_body_chunk".to_string(),
-                    source: None,
-                }
-            })
+            .map_err(|e| self.call_error(e, correlation_id, "request_body_chunk"))
     }
 
     /// Call agent with response headers event.
@@ -305,20 +314,7 @@ impl AgentV2 {
         self.pool
             .send_response_headers(&self.config.id, correlation_id, event)
             .await
-            .map_err(|e| {
-                error!(
-                    agent_id = %self.config.id,
-                    correlation_id = %correlation_id,
-                    error = %e,
-                    "V2 agent response headers call failed"
-                );
-                SentinelError::Agent {
-                    agent: self.config.id.clone(),
-                    message: e.to_string(),
-                    event: "response_headers".to_string(),
-                    source: None,
-                }
-            })
+            .map_err(|e| self.call_error(e, correlation_id, "response_headers"))
     }
 
     /// Call agent with response body chunk event.
@@ -342,20 +338,42 @@ impl AgentV2 {
         self.pool
             .send_response_body_chunk(&self.config.id, correlation_id, event)
             .await
-            .map_err(|e| {
-                error!(
-                    agent_id = %self.config.id,
-                    correlation_id = %correlation_id,
-                    error = %e,
-                    "V2 agent response body chunk call failed"
-                );
-                SentinelError::Agent {
-                    agent: self.config.id.clone(),
-                    message: e.to_string(),
-                    event: "response_body_chunk".to_string(),
-                    source: None,
-                }
-            })
+            .map_err(|e| self.call_error(e, correlation_id, "response_body_chunk"))
+    }
+
+    /// Convert a pool error into an agent error.
+    ///
+    /// Calls the pool skipped (low priority or outside the sample rate) are
+    /// expected and only traced.
+    fn call_error(
+        &self,
+        e: AgentProtocolError,
+        correlation_id: &str,
+        event: &str,
+    ) -> SentinelError {
+        if matches!(e, AgentProtocolError::Skipped { .. }) {
+            trace!(
+                agent_id = %self.config.id,
+                correlation_id = %correlation_id,
+                event = event,
+                reason = %e,
+                "V2 agent call skipped"
+            );
+        } else {
+            error!(
+                agent_id = %self.config.id,
+                correlation_id = %correlation_id,
+                event = event,
+                error = %e,
+                "V2 agent call failed"
+            );
+        }
+        SentinelError::Agent {
+            agent: self.config.id.clone(),
+            message: e.to_string(),
+            event: event.to_string(),
+            source: Some(Box::new(e)),
+        }
     }
 
     /// Cancel an in-flight request.
@@ -581,6 +599,23 @@ fn convert_lb_strategy(strategy: LoadBalanceStrategy) -> ProtocolLBStrategy {
     }
 }
 
+/// Convert config request priority to protocol request priority.
+fn convert_priority(priority: AgentRequestPriority) -> RequestPriority {
+    match priority {
+        AgentRequestPriority::Normal => RequestPriority::Normal,
+        AgentRequestPriority::Low => RequestPriority::Low,
+    }
+}
+
+/// Convert config flow control mode to protocol flow control mode.
+fn convert_flow_control_mode(mode: AgentFlowControlMode) -> FlowControlMode {
+    match mode {
+        AgentFlowControlMode::FailClosed => FlowControlMode::FailClosed,
+        AgentFlowControlMode::FailOpen => FlowControlMode::FailOpen,
+        AgentFlowControlMode::WaitAndRetry => FlowControlMode::WaitAndRetry,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,769 @@
+//! In-process WASM agent implementation.
+//!
+//! WASM agents run a `sentinel:agent` component inside the proxy using
+//! `sentinel-wasm-runtime` instead of calling out over a socket. Each agent
+//! keeps a small pool of pre-instantiated instances so concurrent requests
+//! don't serialize on a single store. Fuel, memory and wall-clock limits are
+//! enforced by the runtime on every call; an instance that traps is discarded
+//! and replaced, and the failure is reported to the manager like any other
+//! agent error so the filter's failure mode applies.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use arc_swap::{ArcSwap, ArcSwapOption};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use parking_lot::Mutex;
+use sentinel_agent_protocol::{
+    AgentResponse, EventType, RequestBodyChunkEvent, RequestHeadersEvent, ResponseBodyChunkEvent,
+    ResponseHeadersEvent,
+};
+use sentinel_common::{
+    errors::{SentinelError, SentinelResult},
+    CircuitBreaker,
+};
+use sentinel_config::{
+    AgentConfig, AgentEvent, AgentTransport, FailureMode, WasmAgentTransportConfig,
+};
+use sentinel_wasm_runtime::{
+    WasmAgentConfig, WasmAgentInstance, WasmAgentRuntime, WasmResourceLimits, WasmRuntimeError,
+};
+use tokio::sync::Semaphore;
+use tracing::{debug, error, info, trace, warn};
+
+use super::metrics::AgentMetrics;
+use super::wasm_metrics::{get_wasm_agent_metrics, init_wasm_agent_metrics};
+
+/// In-process agent backed by a WebAssembly component.
+pub struct WasmAgent {
+    /// Agent identifier (stable across reloads)
+    id: String,
+    /// Agent configuration, replaced on reload
+    config: ArcSwap<AgentConfig>,
+    /// Circuit breaker
+    circuit_breaker: Arc<CircuitBreaker>,
+    /// Agent-specific metrics
+    metrics: Arc<AgentMetrics>,
+    /// Loaded component (None until initialized)
+    component: ArcSwapOption<LoadedComponent>,
+    /// Consecutive failures
+    consecutive_failures: AtomicU32,
+}
+
+impl WasmAgent {
+    /// Create a new WASM agent.
+    ///
+    /// The component is compiled and instantiated by [`WasmAgent::initialize`].
+    pub fn new(config: AgentConfig, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        trace!(
+            agent_id = %config.id,
+            transport = ?config.transport,
+            timeout_ms = config.timeout_ms,
+            events = ?config.events,
+            "Creating WASM agent instance"
+        );
+
+        if let Err(e) = init_wasm_agent_metrics() {
+            warn!(error = %e, "Failed to initialize WASM agent metrics");
+        }
+
+        Self {
+            id: config.id.clone(),
+            config: ArcSwap::from_pointee(config),
+            circuit_breaker,
+            metrics: Arc::new(AgentMetrics::default()),
+            component: ArcSwapOption::empty(),
+            consecutive_failures: AtomicU32::new(0),
+        }
+    }
+
+    /// Get the agent ID.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Get the agent's circuit breaker.
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.circuit_breaker
+    }
+
+    /// Get the agent's failure mode.
+    pub fn failure_mode(&self) -> FailureMode {
+        self.config.load().failure_mode
+    }
+
+    /// Get the agent's timeout in milliseconds.
+    pub fn timeout_ms(&self) -> u64 {
+        self.config.load().timeout_ms
+    }
+
+    /// Get the agent's metrics.
+    pub fn metrics(&self) -> &AgentMetrics {
+        &self.metrics
+    }
+
+    /// Check if agent handles a specific event type.
+    ///
+    /// Only the events exported by the `sentinel:agent` world are supported.
+    pub fn handles_event(&self, event_type: EventType) -> bool {
+        self.config.load().events.iter().any(|e| {
+            matches!(
+                (e, event_type),
+                (AgentEvent::RequestHeaders, EventType::RequestHeaders)
+                    | (AgentEvent::RequestBody, EventType::RequestBodyChunk)
+                    | (AgentEvent::ResponseHeaders, EventType::ResponseHeaders)
+                    | (AgentEvent::ResponseBody, EventType::ResponseBodyChunk)
+            )
+        })
+    }
+
+    /// Compile the component and instantiate the instance pool.
+    pub async fn initialize(&self) -> SentinelResult<()> {
+        let source = ComponentSource::from_config(&self.config.load())?;
+        let start = Instant::now();
+
+        let loaded = self.load(source).await?;
+        info!(
+            agent_id = %self.id,
+            module = %loaded.source.module.display(),
+            instances = loaded.source.limits.instances,
+            load_time_ms = start.elapsed().as_millis(),
+            "WASM agent loaded"
+        );
+
+        if let Some(old) = self.component.swap(Some(loaded)) {
+            tokio::task::spawn_blocking(move || old.shutdown());
+        }
+        Ok(())
+    }
+
+    /// Apply a reloaded configuration.
+    ///
+    /// The component is recompiled and swapped in when the module path, its
+    /// modification time, the resource limits or the agent config changed.
+    /// In-flight calls finish on the old component. If the new component
+    /// fails to load, the old component and config stay active. Returns
+    /// whether a swap happened.
+    pub async fn reload(&self, config: &AgentConfig) -> SentinelResult<bool> {
+        let source = ComponentSource::from_config(config)?;
+
+        let unchanged = self
+            .component
+            .load_full()
+            .is_some_and(|current| current.source == source);
+        if unchanged {
+            trace!(agent_id = %self.id, "WASM agent unchanged on reload");
+            self.config.store(Arc::new(config.clone()));
+            return Ok(false);
+        }
+
+        // Keep the old config with the old component if loading fails
+        let loaded = self.load(source).await?;
+        self.config.store(Arc::new(config.clone()));
+        info!(
+            agent_id = %self.id,
+            module = %loaded.source.module.display(),
+            "WASM agent component hot-swapped"
+        );
+
+        if let Some(old) = self.component.swap(Some(loaded)) {
+            tokio::task::spawn_blocking(move || old.shutdown());
+        }
+        if let Some(metrics) = get_wasm_agent_metrics() {
+            metrics.record_reload(&self.id);
+        }
+        Ok(true)
+    }
+
+    /// Compile and instantiate a component off the async runtime.
+    async fn load(&self, source: ComponentSource) -> SentinelResult<Arc<LoadedComponent>> {
+        let agent_id = self.id.clone();
+        let module = source.module.clone();
+
+        let loaded = tokio::task::spawn_blocking(move || LoadedComponent::load(&agent_id, source))
+            .await
+            .map_err(|e| {
+                agent_error(
+                    &self.id,
+                    "initialize",
+                    format!("WASM load task failed: {}", e),
+                )
+            })?
+            .map_err(|e| {
+                error!(
+                    agent_id = %self.id,
+                    module = %module.display(),
+                    error = %e,
+                    "Failed to load WASM agent"
+                );
+                agent_error(
+                    &self.id,
+                    "initialize",
+                    format!("Failed to load WASM agent: {}", e),
+                )
+            })?;
+
+        Ok(Arc::new(loaded))
+    }
+
+    /// Call agent with an event, converting it to the component's signature.
+    pub async fn call_event<T: serde::Serialize>(
+        &self,
+        event_type: EventType,
+        event: &T,
+    ) -> SentinelResult<AgentResponse> {
+        let event_name = format!("{:?}", event_type);
+        let json = serde_json::to_value(event).map_err(|e| {
+            agent_error(
+                &self.id,
+                &event_name,
+                format!("Failed to serialize event: {}", e),
+            )
+        })?;
+        let decode_error = |e: serde_json::Error| {
+            agent_error(&self.id, &event_name, format!("Invalid event: {}", e))
+        };
+
+        let call = match event_type {
+            EventType::RequestHeaders => {
+                WasmCall::RequestHeaders(serde_json::from_value(json).map_err(decode_error)?)
+            }
+            EventType::RequestBodyChunk => {
+                let event: RequestBodyChunkEvent =
+                    serde_json::from_value(json).map_err(decode_error)?;
+                self.request_body_call(&event)?
+            }
+            EventType::ResponseHeaders => {
+                WasmCall::ResponseHeaders(serde_json::from_value(json).map_err(decode_error)?)
+            }
+            EventType::ResponseBodyChunk => {
+                let event: ResponseBodyChunkEvent =
+                    serde_json::from_value(json).map_err(decode_error)?;
+                self.response_body_call(&event)?
+            }
+            _ => {
+                return Err(agent_error(
+                    &self.id,
+                    &event_name,
+                    format!("WASM agents do not support event type {:?}", event_type),
+                ))
+            }
+        };
+
+        self.call(call).await
+    }
+
+    /// Call agent with request headers event.
+    pub async fn call_request_headers(
+        &self,
+        event: &RequestHeadersEvent,
+    ) -> SentinelResult<AgentResponse> {
+        self.call(WasmCall::RequestHeaders(event.clone())).await
+    }
+
+    /// Call agent with request body chunk event.
+    pub async fn call_request_body_chunk(
+        &self,
+        event: &RequestBodyChunkEvent,
+    ) -> SentinelResult<AgentResponse> {
+        let call = self.request_body_call(event)?;
+        self.call(call).await
+    }
+
+    /// Call agent with response headers event.
+    pub async fn call_response_headers(
+        &self,
+        event: &ResponseHeadersEvent,
+    ) -> SentinelResult<AgentResponse> {
+        self.call(WasmCall::ResponseHeaders(event.clone())).await
+    }
+
+    /// Call agent with response body chunk event.
+    pub async fn call_response_body_chunk(
+        &self,
+        event: &ResponseBodyChunkEvent,
+    ) -> SentinelResult<AgentResponse> {
+        let call = self.response_body_call(event)?;
+        self.call(call).await
+    }
+
+    fn request_body_call(&self, event: &RequestBodyChunkEvent) -> SentinelResult<WasmCall> {
+        Ok(WasmCall::RequestBody {
+            correlation_id: event.correlation_id.clone(),
+            data: self.decode_body(&event.data, "request_body_chunk")?,
+            chunk_index: event.chunk_index,
+            is_last: event.is_last,
+        })
+    }
+
+    fn response_body_call(&self, event: &ResponseBodyChunkEvent) -> SentinelResult<WasmCall> {
+        Ok(WasmCall::ResponseBody {
+            correlation_id: event.correlation_id.clone(),
+            data: self.decode_body(&event.data, "response_body_chunk")?,
+            chunk_index: event.chunk_index,
+            is_last: event.is_last,
+        })
+    }
+
+    fn decode_body(&self, data: &str, event: &str) -> SentinelResult<Vec<u8>> {
+        STANDARD
+            .decode(data)
+            .map_err(|e| agent_error(&self.id, event, format!("Invalid body encoding: {}", e)))
+    }
+
+    /// Run a call on a pooled instance.
+    ///
+    /// The guest runs on the blocking thread pool. If the caller times out and
+    /// drops this future, the call still completes in the background and the
+    /// instance is returned to the pool.
+    async fn call(&self, call: WasmCall) -> SentinelResult<AgentResponse> {
+        let event = call.event_name();
+        let call_num = self.metrics.calls_total.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let component = self
+            .component
+            .load_full()
+            .ok_or_else(|| agent_error(&self.id, event, "WASM component not loaded"))?;
+
+        trace!(
+            agent_id = %self.id,
+            call_num = call_num,
+            event = event,
+            "Calling WASM agent"
+        );
+
+        let permit = Arc::clone(&component.permits)
+            .acquire_owned()
+            .await
+            .map_err(|_| agent_error(&self.id, event, "WASM instance pool closed"))?;
+
+        let agent_id = self.id.clone();
+        let start = Instant::now();
+        let outcome = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            component.with_instance(&agent_id, |instance| call.invoke(instance))
+        })
+        .await
+        .map_err(|e| agent_error(&self.id, event, format!("WASM call task failed: {}", e)))?;
+        let duration = start.elapsed();
+
+        let (result, fuel) = outcome.map_err(|e| self.runtime_error(event, e))?;
+        if let Some(metrics) = get_wasm_agent_metrics() {
+            metrics.record_call(&self.id, event, duration, fuel);
+        }
+
+        trace!(
+            agent_id = %self.id,
+            event = event,
+            duration_us = duration.as_micros() as u64,
+            fuel = fuel,
+            "WASM agent call finished"
+        );
+
+        result.map_err(|e| self.runtime_error(event, e))
+    }
+
+    /// Convert a runtime error, counting traps.
+    fn runtime_error(&self, event: &str, err: WasmRuntimeError) -> SentinelError {
+        if let Some(reason) = trap_reason(&err) {
+            if let Some(metrics) = get_wasm_agent_metrics() {
+                metrics.record_trap(&self.id, reason);
+            }
+        }
+        agent_error(&self.id, event, err.to_string())
+    }
+
+    /// Record successful call.
+    pub fn record_success(&self, duration: Duration) {
+        self.metrics.calls_success.fetch_add(1, Ordering::Relaxed);
+        self.metrics
+            .duration_total_us
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        self.circuit_breaker.record_success();
+    }
+
+    /// Record failed call.
+    pub fn record_failure(&self) {
+        let fail_count = self.metrics.calls_failed.fetch_add(1, Ordering::Relaxed) + 1;
+        let consecutive = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+
+        debug!(
+            agent_id = %self.id,
+            total_failures = fail_count,
+            consecutive_failures = consecutive,
+            "Recorded WASM agent call failure"
+        );
+
+        self.circuit_breaker.record_failure();
+    }
+
+    /// Record timeout.
+    pub fn record_timeout(&self) {
+        let timeout_count = self.metrics.calls_timeout.fetch_add(1, Ordering::Relaxed) + 1;
+        let consecutive = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+
+        debug!(
+            agent_id = %self.id,
+            total_timeouts = timeout_count,
+            consecutive_failures = consecutive,
+            "Recorded WASM agent call timeout"
+        );
+
+        self.circuit_breaker.record_failure();
+    }
+
+    /// Shutdown the agent, running the component's shutdown hook.
+    pub async fn shutdown(&self) {
+        debug!(agent_id = %self.id, "Shutting down WASM agent");
+
+        if let Some(component) = self.component.swap(None) {
+            let _ = tokio::task::spawn_blocking(move || component.shutdown()).await;
+        }
+
+        info!(
+            agent_id = %self.id,
+            total_calls = self.metrics.calls_total.load(Ordering::Relaxed),
+            successes = self.metrics.calls_success.load(Ordering::Relaxed),
+            failures = self.metrics.calls_failed.load(Ordering::Relaxed),
+            timeouts = self.metrics.calls_timeout.load(Ordering::Relaxed),
+            "WASM agent shutdown complete"
+        );
+    }
+}
+
+/// Where a loaded component came from; any change triggers a hot-swap.
+#[derive(Debug, Clone, PartialEq)]
+struct ComponentSource {
+    module: PathBuf,
+    modified: Option<SystemTime>,
+    limits: WasmAgentTransportConfig,
+    timeout_ms: u64,
+    config_json: String,
+}
+
+impl ComponentSource {
+    fn from_config(config: &AgentConfig) -> SentinelResult<Self> {
+        let AgentTransport::Wasm {
+            module,
+            config: limits,
+        } = &config.transport
+        else {
+            return Err(agent_error(
+                &config.id,
+                "initialize",
+                "Agent transport is not wasm",
+            ));
+        };
+
+        Ok(Self {
+            module: module.clone(),
+            modified: std::fs::metadata(module).and_then(|m| m.modified()).ok(),
+            limits: limits.clone(),
+            timeout_ms: config.timeout_ms,
+            config_json: config
+                .config
+                .as_ref()
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| "{}".to_string()),
+        })
+    }
+
+    /// Runtime configuration; unset limits use runtime defaults, except
+    /// execution time which defaults to the agent timeout.
+    fn runtime_config(&self) -> WasmAgentConfig {
+        let defaults = WasmResourceLimits::default();
+        let limits = WasmResourceLimits {
+            max_memory: self.limits.max_memory_bytes.unwrap_or(defaults.max_memory),
+            max_fuel: self.limits.max_fuel.unwrap_or(defaults.max_fuel),
+            max_execution_time: Duration::from_millis(
+                self.limits.max_execution_ms.unwrap_or(self.timeout_ms),
+            ),
+            ..defaults
+        };
+
+        WasmAgentConfig {
+            limits,
+            max_instances: self.limits.instances as u32,
+            ..WasmAgentConfig::default()
+        }
+    }
+}
+
+/// A compiled component with its pool of idle instances.
+struct LoadedComponent {
+    source: ComponentSource,
+    runtime: WasmAgentRuntime,
+    idle: Mutex<Vec<WasmAgentInstance>>,
+    /// One permit per instance; a permit guarantees an idle instance
+    permits: Arc<Semaphore>,
+}
+
+impl LoadedComponent {
+    /// Compile the module and instantiate the pool (blocking).
+    fn load(agent_id: &str, source: ComponentSource) -> Result<Self, WasmRuntimeError> {
+        let runtime = WasmAgentRuntime::new(source.runtime_config())?;
+        runtime.compile_component_file(agent_id, &source.module)?;
+
+        let idle = (0..source.limits.instances)
+            .map(|_| runtime.instantiate(agent_id, agent_id, &source.config_json))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            permits: Arc::new(Semaphore::new(idle.len())),
+            idle: Mutex::new(idle),
+            runtime,
+            source,
+        })
+    }
+
+    /// Run `f` on an idle instance (blocking).
+    ///
+    /// Returns the call result and the fuel it consumed. Instances poisoned
+    /// by a trap are replaced with a fresh instantiation.
+    fn with_instance<R>(
+        &self,
+        agent_id: &str,
+        f: impl FnOnce(&WasmAgentInstance) -> R,
+    ) -> Result<(R, u64), WasmRuntimeError> {
+        let popped = self.idle.lock().pop();
+        let instance = match popped {
+            Some(instance) => instance,
+            // A previous replacement failed; try again
+            None => self
+                .runtime
+                .instantiate(agent_id, agent_id, &self.source.config_json)?,
+        };
+
+        let result = f(&instance);
+        let fuel = instance.last_fuel_consumed();
+
+        if instance.is_poisoned() {
+            match self
+                .runtime
+                .instantiate(agent_id, agent_id, &self.source.config_json)
+            {
+                Ok(fresh) => self.idle.lock().push(fresh),
+                Err(e) => warn!(
+                    agent_id = %agent_id,
+                    error = %e,
+                    "Failed to replace trapped WASM instance"
+                ),
+            }
+        } else {
+            self.idle.lock().push(instance);
+        }
+
+        Ok((result, fuel))
+    }
+
+    /// Run shutdown hooks on idle instances and stop the runtime (blocking).
+    fn shutdown(&self) {
+        self.permits.close();
+        for instance in self.idle.lock().drain(..) {
+            instance.shutdown();
+        }
+        self.runtime.shutdown();
+    }
+}
+
+/// An event converted to the component's call signature.
+enum WasmCall {
+    RequestHeaders(RequestHeadersEvent),
+    RequestBody {
+        correlation_id: String,
+        data: Vec<u8>,
+        chunk_index: u32,
+        is_last: bool,
+    },
+    ResponseHeaders(ResponseHeadersEvent),
+    ResponseBody {
+        correlation_id: String,
+        data: Vec<u8>,
+        chunk_index: u32,
+        is_last: bool,
+    },
+}
+
+impl WasmCall {
+    fn event_name(&self) -> &'static str {
+        match self {
+            WasmCall::RequestHeaders(_) => "request_headers",
+            WasmCall::RequestBody { .. } => "request_body_chunk",
+            WasmCall::ResponseHeaders(_) => "response_headers",
+            WasmCall::ResponseBody { .. } => "response_body_chunk",
+        }
+    }
+
+    fn invoke(&self, instance: &WasmAgentInstance) -> Result<AgentResponse, WasmRuntimeError> {
+        match self {
+            WasmCall::RequestHeaders(event) => instance.on_request_headers(
+                &event.metadata,
+                &event.method,
+                &event.uri,
+                &event.headers,
+            ),
+            WasmCall::RequestBody {
+                correlation_id,
+                data,
+                chunk_index,
+                is_last,
+            } => instance.on_request_body(correlation_id, data, *chunk_index, *is_last),
+            WasmCall::ResponseHeaders(event) => {
+                instance.on_response_headers(&event.correlation_id, event.status, &event.headers)
+            }
+            WasmCall::ResponseBody {
+                correlation_id,
+                data,
+                chunk_index,
+                is_last,
+            } => instance.on_response_body(correlation_id, data, *chunk_index, *is_last),
+        }
+    }
+}
+
+/// Metric label for errors caused by resource limits or traps.
+fn trap_reason(err: &WasmRuntimeError) -> Option<&'static str> {
+    match err {
+        WasmRuntimeError::Timeout(_) => Some("timeout"),
+        WasmRuntimeError::ResourceLimit(msg) if msg.contains("fuel") => Some("fuel"),
+        WasmRuntimeError::ResourceLimit(msg) if msg.contains("memory") => Some("memory"),
+        WasmRuntimeError::ResourceLimit(_) | WasmRuntimeError::Trap(_) => Some("trap"),
+        _ => None,
+    }
+}
+
+fn agent_error(agent: &str, event: &str, message: impl Into<String>) -> SentinelError {
+    SentinelError::Agent {
+        agent: agent.to_string(),
+        message: message.into(),
+        event: event.to_string(),
+        source: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sentinel_config::{AgentProtocolVersion, AgentType};
+
+    fn wasm_agent_config(module: &str, limits: WasmAgentTransportConfig) -> AgentConfig {
+        AgentConfig {
+            id: "wasm-agent".to_string(),
+            agent_type: AgentType::Custom("wasm".to_string()),
+            transport: AgentTransport::Wasm {
+                module: PathBuf::from(module),
+                config: limits,
+            },
+            events: vec![AgentEvent::RequestHeaders, AgentEvent::Log],
+            protocol_version: AgentProtocolVersion::V1,
+            pool: None,
+            timeout_ms: 50,
+            failure_mode: FailureMode::Closed,
+            circuit_breaker: None,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            request_body_mode: Default::default(),
+            response_body_mode: Default::default(),
+            chunk_timeout_ms: 5000,
+            config: Some(serde_json::json!({"allow": ["10.0.0.0/8"]})),
+            max_concurrent_calls: 100,
+            recording: None,
+            enforce: true,
+        }
+    }
+
+    #[test]
+    fn test_runtime_config_from_transport() {
+        let config = wasm_agent_config(
+            "/nonexistent/agent.wasm",
+            WasmAgentTransportConfig {
+                max_memory_bytes: Some(8 * 1024 * 1024),
+                max_fuel: Some(1_000),
+                max_execution_ms: None,
+                instances: 2,
+            },
+        );
+        let source = ComponentSource::from_config(&config).unwrap();
+        assert_eq!(source.modified, None);
+        assert_eq!(source.config_json, r#"{"allow":["10.0.0.0/8"]}"#);
+
+        let runtime = source.runtime_config();
+        assert_eq!(runtime.limits.max_memory, 8 * 1024 * 1024);
+        assert_eq!(runtime.limits.max_fuel, 1_000);
+        // Execution time falls back to the agent timeout
+        assert_eq!(runtime.limits.max_execution_time, Duration::from_millis(50));
+        assert_eq!(runtime.max_instances, 2);
+    }
+
+    #[test]
+    fn test_source_change_detection() {
+        let config = wasm_agent_config("/nonexistent/agent.wasm", Default::default());
+        let source = ComponentSource::from_config(&config).unwrap();
+        assert_eq!(source, ComponentSource::from_config(&config).unwrap());
+
+        let mut changed = config.clone();
+        changed.config = None;
+        assert_ne!(source, ComponentSource::from_config(&changed).unwrap());
+    }
+
+    #[test]
+    fn test_trap_reason() {
+        assert_eq!(
+            trap_reason(&WasmRuntimeError::Timeout(Duration::from_millis(5))),
+            Some("timeout")
+        );
+        assert_eq!(
+            trap_reason(&WasmRuntimeError::ResourceLimit(
+                "fuel exhausted".to_string()
+            )),
+            Some("fuel")
+        );
+        assert_eq!(
+            trap_reason(&WasmRuntimeError::ResourceLimit("memory limit".to_string())),
+            Some("memory")
+        );
+        assert_eq!(
+            trap_reason(&WasmRuntimeError::Trap("unreachable".to_string())),
+            Some("trap")
+        );
+        assert_eq!(
+            trap_reason(&WasmRuntimeError::Configuration("bad".to_string())),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_module_fails_closed() {
+        let config = wasm_agent_config("/nonexistent/agent.wasm", Default::default());
+        let agent = WasmAgent::new(config, Arc::new(CircuitBreaker::new(Default::default())));
+
+        assert!(agent.handles_event(EventType::RequestHeaders));
+        assert!(!agent.handles_event(EventType::RequestComplete));
+        assert!(agent.initialize().await.is_err());
+
+        let event = ResponseHeadersEvent {
+            correlation_id: "req-1".to_string(),
+            status: 200,
+            headers: Default::default(),
+        };
+        let err = agent.call_response_headers(&event).await.unwrap_err();
+        assert!(err.to_string().contains("not loaded"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_reload_keeps_config() {
+        let config = wasm_agent_config("/nonexistent/agent.wasm", Default::default());
+        let agent = WasmAgent::new(config, Arc::new(CircuitBreaker::new(Default::default())));
+
+        let mut reloaded = wasm_agent_config("/nonexistent/other.wasm", Default::default());
+        reloaded.timeout_ms = 500;
+        reloaded.failure_mode = FailureMode::Open;
+        assert!(agent.reload(&reloaded).await.is_err());
+
+        assert_eq!(agent.timeout_ms(), 50);
+        assert_eq!(agent.failure_mode(), FailureMode::Closed);
+    }
+}
@@ -2,7 +2,9 @@
 
 use std::collections::HashMap;
 
-use sentinel_agent_protocol::{AgentResponse, AuditMetadata, BodyMutation, Decision, HeaderOp};
+use sentinel_agent_protocol::{
+    AgentResponse, AuditMetadata, BodyMutation, Decision, HeaderOp, RoutingOverride,
+};
 
 /// Agent decision combining all agent responses.
 #[derive(Debug, Clone)]
@@ -23,6 +25,8 @@ pub struct AgentDecision {
     pub request_body_mutation: Option<BodyMutation>,
     /// Mutation for response body chunk (streaming mode)
     pub response_body_mutation: Option<BodyMutation>,
+    /// Upstream selection override requested by agents
+    pub routing: Option<RoutingOverride>,
 }
 
 /// Agent action types.
@@ -57,6 +61,7 @@ impl AgentDecision {
             needs_more: false,
             request_body_mutation: None,
             response_body_mutation: None,
+            routing: None,
         }
     }
 
@@ -75,6 +80,7 @@ impl AgentDecision {
             needs_more: false,
             request_body_mutation: None,
             response_body_mutation: None,
+            routing: None,
         }
     }
 
@@ -115,6 +121,20 @@ impl AgentDecision {
         if other.response_body_mutation.is_some() {
             self.response_body_mutation = other.response_body_mutation;
         }
+
+        // Routing overrides: last one wins per field
+        if let Some(other_routing) = other.routing {
+            let routing = self.routing.get_or_insert_with(Default::default);
+            if other_routing.upstream.is_some() {
+                routing.upstream = other_routing.upstream;
+            }
+            if other_routing.target.is_some() {
+                routing.target = other_routing.target;
+            }
+            if other_routing.hash_key.is_some() {
+                routing.hash_key = other_routing.hash_key;
+            }
+        }
     }
 }
 
@@ -150,6 +170,7 @@ impl From<AgentResponse> for AgentDecision {
             needs_more: response.needs_more,
             request_body_mutation: response.request_body_mutation,
             response_body_mutation: response.response_body_mutation,
+            routing: response.routing,
         }
     }
 }
@@ -171,4 +192,21 @@ mod tests {
         decision1.merge(decision2);
         assert!(!decision1.is_allow());
     }
+
+    #[test]
+    fn test_agent_decision_merge_routing() {
+        let mut decision =
+            AgentDecision::from(AgentResponse::default_allow().with_upstream("canary"));
+        decision.merge(AgentDecision::default_allow());
+        decision.merge(AgentDecision::from(
+            AgentResponse::default_allow()
+                .with_upstream("stable")
+                .with_hash_key("tenant-7"),
+        ));
+
+        let routing = decision.routing.unwrap();
+        assert_eq!(routing.upstream.as_deref(), Some("stable"));
+        assert_eq!(routing.target, None);
+        assert_eq!(routing.hash_key.as_deref(), Some("tenant-7"));
+    }
 }
@@ -0,0 +1,352 @@
+//! Cache for agent decisions marked reusable by the agent.
+//!
+//! Agents attach a [`CacheDirective`](sentinel_agent_protocol::CacheDirective)
+//! to a request-headers response to let the proxy reuse it for a while. The
+//! directive's scope decides what the decision is keyed on (client IP, a
+//! header value or just the route), and the key always includes the agent,
+//! the route and the agent's cache epoch, which v2 agents bump through a
+//! `ConfigUpdateRequest` to invalidate everything they cached.
+//!
+//! Since the scope is only known from a response, the cache remembers which
+//! scopes each agent has used and tries each of them on lookup.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use once_cell::sync::OnceCell;
+use prometheus::{register_int_counter_vec, IntCounterVec};
+use sentinel_agent_protocol::{AgentResponse, CacheScope};
+use sha2::{Digest, Sha256};
+use tracing::{trace, warn};
+
+use crate::memory_cache::TypedCache;
+
+/// Maximum number of cached decisions across all agents. The cache weighs
+/// every entry as one, so this is its capacity.
+const MAX_ENTRIES: usize = 100_000;
+
+/// Upper bound on the TTL an agent can request.
+const MAX_TTL: Duration = Duration::from_secs(3600);
+
+/// Maximum number of distinct scopes tracked per agent.
+const MAX_SCOPES_PER_AGENT: usize = 4;
+
+/// Request attributes a cached decision can be keyed on.
+#[derive(Clone, Copy)]
+pub(crate) struct CacheKeyInputs<'a> {
+    pub client_ip: &'a str,
+    pub route_id: Option<&'a str>,
+    pub headers: &'a HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct DecisionCacheKey {
+    agent_id: String,
+    epoch: u64,
+    route_id: Option<String>,
+    scope: CacheScope,
+    /// SHA-256 of the scoped value, so tokens are not kept in memory
+    value: [u8; 32],
+}
+
+/// Bounded cache of agent responses.
+pub(crate) struct DecisionCache {
+    entries: TypedCache<DecisionCacheKey, AgentResponse>,
+    /// Scopes each agent has cached decisions under
+    scopes: DashMap<String, Vec<CacheScope>>,
+}
+
+impl DecisionCache {
+    pub(crate) fn new() -> Self {
+        if let Err(e) = init_decision_cache_metrics() {
+            warn!(error = %e, "Failed to initialize agent decision cache metrics");
+        }
+
+        Self {
+            entries: TypedCache::new(MAX_ENTRIES, MAX_TTL),
+            scopes: DashMap::new(),
+        }
+    }
+
+    /// Look up a cached decision for an agent.
+    ///
+    /// Agents that never returned a cache directive are skipped without
+    /// touching the cache or the metrics.
+    pub(crate) fn lookup(
+        &self,
+        agent_id: &str,
+        epoch: u64,
+        inputs: CacheKeyInputs<'_>,
+    ) -> Option<AgentResponse> {
+        let scopes = self.scopes.get(agent_id)?.clone();
+
+        let hit = scopes.into_iter().find_map(|scope| {
+            let key = cache_key(agent_id, epoch, inputs, scope)?;
+            self.entries.get(&key)
+        });
+
+        if let Some(metrics) = get_decision_cache_metrics() {
+            if hit.is_some() {
+                metrics.record_hit(agent_id);
+            } else {
+                metrics.record_miss(agent_id);
+            }
+        }
+        hit
+    }
+
+    /// Store a response if it carries a usable cache directive.
+    pub(crate) fn store(
+        &self,
+        agent_id: &str,
+        epoch: u64,
+        inputs: CacheKeyInputs<'_>,
+        response: &AgentResponse,
+    ) {
+        let Some(directive) = &response.cache else {
+            return;
+        };
+        // Provisional decisions and body mutations are tied to one request
+        if directive.ttl_ms == 0
+            || response.needs_more
+            || response.request_body_mutation.is_some()
+            || response.response_body_mutation.is_some()
+        {
+            return;
+        }
+
+        let scope = directive.scope.clone();
+        let ttl = Duration::from_millis(directive.ttl_ms).min(MAX_TTL);
+        let Some(key) = cache_key(agent_id, epoch, inputs, scope.clone()) else {
+            return;
+        };
+
+        {
+            let mut scopes = self.scopes.entry(agent_id.to_string()).or_default();
+            if !scopes.contains(&scope) {
+                if scopes.len() >= MAX_SCOPES_PER_AGENT {
+                    warn!(
+                        agent_id = %agent_id,
+                        scope = ?scope,
+                        "Agent uses too many cache scopes, not caching decision"
+                    );
+                    return;
+                }
+                scopes.push(scope);
+            }
+        }
+
+        trace!(
+            agent_id = %agent_id,
+            ttl_ms = ttl.as_millis(),
+            "Caching agent decision"
+        );
+
+        let mut cached = response.clone();
+        cached.cache = None;
+        self.entries.put_with_ttl(&key, cached, ttl);
+    }
+}
+
+/// Build the cache key for a scope, or `None` if the request lacks the
+/// scoped attribute.
+fn cache_key(
+    agent_id: &str,
+    epoch: u64,
+    inputs: CacheKeyInputs<'_>,
+    scope: CacheScope,
+) -> Option<DecisionCacheKey> {
+    let value = match &scope {
+        CacheScope::ClientIp if !inputs.client_ip.is_empty() => inputs.client_ip.to_string(),
+        CacheScope::ClientIp => return None,
+        CacheScope::Header { name } => {
+            let (_, values) = inputs
+                .headers
+                .iter()
+                .find(|(header, _)| header.eq_ignore_ascii_case(name))?;
+            if values.is_empty() {
+                return None;
+            }
+            values.join(", ")
+        }
+        CacheScope::Route => String::new(),
+    };
+
+    Some(DecisionCacheKey {
+        agent_id: agent_id.to_string(),
+        epoch,
+        route_id: inputs.route_id.map(str::to_string),
+        scope,
+        value: Sha256::digest(value.as_bytes()).into(),
+    })
+}
+
+/// Global decision cache metrics instance.
+static DECISION_CACHE_METRICS: OnceCell<Arc<DecisionCacheMetrics>> = OnceCell::new();
+
+/// Get the global decision cache metrics, if initialized.
+pub fn get_decision_cache_metrics() -> Option<Arc<DecisionCacheMetrics>> {
+    DECISION_CACHE_METRICS.get().cloned()
+}
+
+/// Initialize the global decision cache metrics.
+/// Returns Ok if already initialized or initialization succeeds.
+pub fn init_decision_cache_metrics() -> Result<Arc<DecisionCacheMetrics>> {
+    if let Some(metrics) = DECISION_CACHE_METRICS.get() {
+        return Ok(metrics.clone());
+    }
+
+    let metrics = Arc::new(DecisionCacheMetrics::new()?);
+    let _ = DECISION_CACHE_METRICS.set(metrics.clone());
+    Ok(metrics)
+}
+
+/// Agent decision cache metrics.
+pub struct DecisionCacheMetrics {
+    /// Agent calls answered from the cache
+    /// Labels: agent
+    hits: IntCounterVec,
+
+    /// Lookups that found no cached decision
+    /// Labels: agent
+    misses: IntCounterVec,
+}
+
+impl DecisionCacheMetrics {
+    /// Create new decision cache metrics and register with Prometheus.
+    pub fn new() -> Result<Self> {
+        let hits = register_int_counter_vec!(
+            "sentinel_agent_decision_cache_hits_total",
+            "Agent calls skipped because a cached decision was still valid",
+            &["agent"]
+        )
+        .context("Failed to register agent_decision_cache_hits metric")?;
+
+        let misses = register_int_counter_vec!(
+            "sentinel_agent_decision_cache_misses_total",
+            "Agent decision cache lookups that found no valid entry",
+            &["agent"]
+        )
+        .context("Failed to register agent_decision_cache_misses metric")?;
+
+        Ok(Self { hits, misses })
+    }
+
+    /// Record a cache hit.
+    pub fn record_hit(&self, agent: &str) {
+        self.hits.with_label_values(&[agent]).inc();
+    }
+
+    /// Record a cache miss.
+    pub fn record_miss(&self, agent: &str) {
+        self.misses.with_label_values(&[agent]).inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(token: &str) -> HashMap<String, Vec<String>> {
+        HashMap::from([("Authorization".to_string(), vec![token.to_string()])])
+    }
+
+    fn inputs<'a>(
+        client_ip: &'a str,
+        route_id: &'a str,
+        headers: &'a HashMap<String, Vec<String>>,
+    ) -> CacheKeyInputs<'a> {
+        CacheKeyInputs {
+            client_ip,
+            route_id: Some(route_id),
+            headers,
+        }
+    }
+
+    #[test]
+    fn test_header_scoped_decision() {
+        let cache = DecisionCache::new();
+        let alice = headers("Bearer alice");
+        let bob = headers("Bearer bob");
+
+        let response = AgentResponse::block(401, None).with_cache(
+            60_000,
+            CacheScope::Header {
+                name: "authorization".to_string(),
+            },
+        );
+        assert!(cache
+            .lookup("auth", 0, inputs("10.0.0.1", "api", &alice))
+            .is_none());
+        cache.store("auth", 0, inputs("10.0.0.1", "api", &alice), &response);
+
+        // Same token from another client hits; another token or route misses
+        let hit = cache
+            .lookup("auth", 0, inputs("10.0.0.2", "api", &alice))
+            .unwrap();
+        assert!(hit.cache.is_none());
+        assert!(cache
+            .lookup("auth", 0, inputs("10.0.0.1", "api", &bob))
+            .is_none());
+        assert!(cache
+            .lookup("auth", 0, inputs("10.0.0.1", "admin", &alice))
+            .is_none());
+        assert!(cache
+            .lookup("other", 0, inputs("10.0.0.1", "api", &alice))
+            .is_none());
+
+        // A new epoch invalidates the entry
+        assert!(cache
+            .lookup("auth", 1, inputs("10.0.0.1", "api", &alice))
+            .is_none());
+    }
+
+    #[test]
+    fn test_uncacheable_responses() {
+        let cache = DecisionCache::new();
+        let empty = HashMap::new();
+
+        cache.store(
+            "rep",
+            0,
+            inputs("10.0.0.1", "api", &empty),
+            &AgentResponse::default_allow(),
+        );
+        cache.store(
+            "rep",
+            0,
+            inputs("10.0.0.1", "api", &empty),
+            &AgentResponse::default_allow()
+                .set_needs_more(true)
+                .with_cache(1_000, CacheScope::ClientIp),
+        );
+        // The scoped header is missing from the request
+        cache.store(
+            "rep",
+            0,
+            inputs("10.0.0.1", "api", &empty),
+            &AgentResponse::default_allow().with_cache(
+                1_000,
+                CacheScope::Header {
+                    name: "x-api-key".to_string(),
+                },
+            ),
+        );
+        assert!(cache
+            .lookup("rep", 0, inputs("10.0.0.1", "api", &empty))
+            .is_none());
+
+        cache.store(
+            "rep",
+            0,
+            inputs("10.0.0.1", "api", &empty),
+            &AgentResponse::default_allow().with_cache(1_000, CacheScope::ClientIp),
+        );
+        assert!(cache
+            .lookup("rep", 0, inputs("10.0.0.1", "api", &empty))
+            .is_some());
+    }
+}
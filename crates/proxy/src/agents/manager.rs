@@ -1,14 +1,14 @@
 //! Agent manager for coordinating external processing agents.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use base64::{engine::general_purpose::STANDARD, Engine as _};
 use pingora_timeout::timeout;
 use sentinel_agent_protocol::{
-    v2::MetricsCollector,
-    AgentResponse, EventType, RequestBodyChunkEvent, RequestHeadersEvent, ResponseBodyChunkEvent,
+    v2::{CancelReason, MetricsCollector},
+    AgentResponse, Decision, EventType, RequestBodyChunkEvent, RequestHeadersEvent, ResponseBodyChunkEvent,
     ResponseHeadersEvent, WebSocketFrameEvent,
 };
 use sentinel_common::{
@@ -16,22 +16,30 @@ use sentinel_common::{
     types::CircuitBreakerConfig,
     CircuitBreaker,
 };
-use futures::future::join_all;
-use sentinel_config::{AgentConfig, AgentProtocolVersion, FailureMode};
+use futures::stream::{FuturesUnordered, StreamExt};
+use futures::FutureExt;
+use sentinel_config::{
+    AgentConfig, AgentExecutionMode, AgentProtocolVersion, AgentTransport, FailureMode,
+};
 use tokio::sync::{RwLock, Semaphore};
 use tracing::{debug, error, info, trace, warn};
 
 use super::agent::Agent;
 use super::agent_v2::AgentV2;
+use super::agent_wasm::WasmAgent;
 use super::context::AgentCallContext;
 use super::decision::AgentDecision;
+use super::decision_cache::{CacheKeyInputs, DecisionCache};
 use super::metrics::AgentMetrics;
 use super::pool::AgentConnectionPool;
+use super::recorder::AgentRecorder;
+use super::shadow::{self, ShadowCall};
 
-/// Unified agent wrapper supporting both v1 and v2 protocols.
+/// Unified agent wrapper supporting v1, v2 and in-process WASM agents.
 pub enum UnifiedAgent {
     V1(Arc<Agent>),
     V2(Arc<AgentV2>),
+    Wasm(Arc<WasmAgent>),
 }
 
 impl UnifiedAgent {
@@ -40,6 +48,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.id(),
             UnifiedAgent::V2(agent) => agent.id(),
+            UnifiedAgent::Wasm(agent) => agent.id(),
         }
     }
 
@@ -48,6 +57,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.circuit_breaker(),
             UnifiedAgent::V2(agent) => agent.circuit_breaker(),
+            UnifiedAgent::Wasm(agent) => agent.circuit_breaker(),
         }
     }
 
@@ -56,6 +66,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.failure_mode(),
             UnifiedAgent::V2(agent) => agent.failure_mode(),
+            UnifiedAgent::Wasm(agent) => agent.failure_mode(),
         }
     }
 
@@ -64,6 +75,15 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.timeout_ms(),
             UnifiedAgent::V2(agent) => agent.timeout_ms(),
+            UnifiedAgent::Wasm(agent) => agent.timeout_ms(),
+        }
+    }
+
+    /// Get the agent's decision cache epoch (only v2 agents invalidate).
+    pub fn cache_epoch(&self) -> u64 {
+        match self {
+            UnifiedAgent::V2(agent) => agent.cache_epoch(),
+            UnifiedAgent::V1(_) | UnifiedAgent::Wasm(_) => 0,
         }
     }
 
@@ -72,6 +92,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.handles_event(event_type),
             UnifiedAgent::V2(agent) => agent.handles_event(event_type),
+            UnifiedAgent::Wasm(agent) => agent.handles_event(event_type),
         }
     }
 
@@ -80,6 +101,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.initialize().await,
             UnifiedAgent::V2(agent) => agent.initialize().await,
+            UnifiedAgent::Wasm(agent) => agent.initialize().await,
         }
     }
 
@@ -156,6 +178,7 @@ impl UnifiedAgent {
                     }
                 }
             }
+            UnifiedAgent::Wasm(agent) => agent.call_event(event_type, event).await,
         }
     }
 
@@ -167,6 +190,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.call_event(EventType::RequestHeaders, event).await,
             UnifiedAgent::V2(agent) => agent.call_request_headers(event).await,
+            UnifiedAgent::Wasm(agent) => agent.call_request_headers(event).await,
         }
     }
 
@@ -178,6 +202,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.call_event(EventType::RequestBodyChunk, event).await,
             UnifiedAgent::V2(agent) => agent.call_request_body_chunk(event).await,
+            UnifiedAgent::Wasm(agent) => agent.call_request_body_chunk(event).await,
         }
     }
 
@@ -189,6 +214,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.call_event(EventType::ResponseHeaders, event).await,
             UnifiedAgent::V2(agent) => agent.call_response_headers(event).await,
+            UnifiedAgent::Wasm(agent) => agent.call_response_headers(event).await,
         }
     }
 
@@ -200,6 +226,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.call_event(EventType::ResponseBodyChunk, event).await,
             UnifiedAgent::V2(agent) => agent.call_response_body_chunk(event).await,
+            UnifiedAgent::Wasm(agent) => agent.call_response_body_chunk(event).await,
         }
     }
 
@@ -208,6 +235,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.record_success(duration).await,
             UnifiedAgent::V2(agent) => agent.record_success(duration),
+            UnifiedAgent::Wasm(agent) => agent.record_success(duration),
         }
     }
 
@@ -216,6 +244,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.record_failure().await,
             UnifiedAgent::V2(agent) => agent.record_failure(),
+            UnifiedAgent::Wasm(agent) => agent.record_failure(),
         }
     }
 
@@ -224,6 +253,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.record_timeout().await,
             UnifiedAgent::V2(agent) => agent.record_timeout(),
+            UnifiedAgent::Wasm(agent) => agent.record_timeout(),
         }
     }
 
@@ -232,6 +262,7 @@ impl UnifiedAgent {
         match self {
             UnifiedAgent::V1(agent) => agent.shutdown().await,
             UnifiedAgent::V2(agent) => agent.shutdown().await,
+            UnifiedAgent::Wasm(agent) => agent.shutdown().await,
         }
     }
 
@@ -239,13 +270,19 @@ impl UnifiedAgent {
     pub fn is_v2(&self) -> bool {
         matches!(self, UnifiedAgent::V2(_))
     }
+
+    /// Check if this is an in-process WASM agent.
+    pub fn is_wasm(&self) -> bool {
+        matches!(self, UnifiedAgent::Wasm(_))
+    }
 }
 
 /// Agent manager handling all external agents.
 ///
 /// Supports both v1 and v2 protocol agents. V1 agents use simple request/response,
 /// while v2 agents support bidirectional streaming with capabilities, health
-/// reporting, metrics export, and flow control.
+/// reporting, metrics export, and flow control. Agents with a `wasm` transport
+/// run in-process regardless of protocol version.
 pub struct AgentManager {
     /// Configured agents (unified wrapper for v1 and v2)
     agents: Arc<RwLock<HashMap<String, Arc<UnifiedAgent>>>>,
@@ -257,6 +294,12 @@ pub struct AgentManager {
     metrics: Arc<AgentMetrics>,
     /// Per-agent semaphores for queue isolation (prevents noisy neighbor problem)
     agent_semaphores: Arc<RwLock<HashMap<String, Arc<Semaphore>>>>,
+    /// Request-header decisions agents marked as cacheable
+    decision_cache: Arc<DecisionCache>,
+    /// Traffic recorders for agents with a `record` block
+    recorders: Arc<HashMap<String, Arc<AgentRecorder>>>,
+    /// Agents with `enforce false`, called off the request path
+    shadow_agents: Arc<HashSet<String>>,
 }
 
 impl AgentManager {
@@ -274,9 +317,12 @@ impl AgentManager {
         let mut pools = HashMap::new();
         let mut breakers = HashMap::new();
         let mut semaphores = HashMap::new();
+        let mut recorders = HashMap::new();
+        let mut shadow_agents = HashSet::new();
 
         let mut v1_count = 0;
         let mut v2_count = 0;
+        let mut wasm_count = 0;
 
         for config in agents {
             debug!(
@@ -292,7 +338,52 @@ impl AgentManager {
             // Create per-agent semaphore for queue isolation
             let semaphore = Arc::new(Semaphore::new(config.max_concurrent_calls));
 
+            if let Some(recording) = &config.recording {
+                match AgentRecorder::new(&config.id, recording) {
+                    Ok(Some(recorder)) => {
+                        info!(
+                            agent_id = %config.id,
+                            directory = %recording.directory.display(),
+                            sample_rate = recording.sample_rate,
+                            "Recording agent traffic"
+                        );
+                        recorders.insert(config.id.clone(), Arc::new(recorder));
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!(
+                        agent_id = %config.id,
+                        error = %e,
+                        "Failed to set up agent recording, continuing without it"
+                    ),
+                }
+            }
+
+            if !config.enforce {
+                shadow::log_shadow_agent(&config.id);
+                shadow_agents.insert(config.id.clone());
+            }
+
             let unified_agent = match config.protocol_version {
+                // WASM: in-process, no connection pool
+                _ if matches!(config.transport, AgentTransport::Wasm { .. }) => {
+                    let circuit_breaker = Arc::new(CircuitBreaker::new(
+                        config
+                            .circuit_breaker
+                            .clone()
+                            .unwrap_or_else(CircuitBreakerConfig::default),
+                    ));
+
+                    trace!(
+                        agent_id = %config.id,
+                        max_concurrent_calls = config.max_concurrent_calls,
+                        "Creating in-process WASM agent instance"
+                    );
+
+                    let agent = Arc::new(WasmAgent::new(config.clone(), circuit_breaker));
+                    wasm_count += 1;
+
+                    Arc::new(UnifiedAgent::Wasm(agent))
+                }
                 AgentProtocolVersion::V1 => {
                     // V1: Create pool and circuit breaker externally
                     let pool = Arc::new(AgentConnectionPool::new(
@@ -368,6 +459,7 @@ impl AgentManager {
             configured_agents = agent_map.len(),
             v1_agents = v1_count,
             v2_agents = v2_count,
+            wasm_agents = wasm_count,
             "Agent manager created successfully with per-agent queue isolation"
         );
 
@@ -377,6 +469,9 @@ impl AgentManager {
             circuit_breakers: Arc::new(RwLock::new(breakers)),
             metrics: Arc::new(AgentMetrics::default()),
             agent_semaphores: Arc::new(RwLock::new(semaphores)),
+            decision_cache: Arc::new(DecisionCache::new()),
+            recorders: Arc::new(recorders),
+            shadow_agents: Arc::new(shadow_agents),
         })
     }
 
@@ -386,11 +481,13 @@ impl AgentManager {
     /// * `ctx` - Agent call context with correlation ID and metadata
     /// * `headers` - Request headers to send to agents
     /// * `route_agents` - List of (agent_id, failure_mode) tuples from filter chain
+    /// * `execution` - Whether agents are called in filter order or concurrently
     pub async fn process_request_headers(
         &self,
         ctx: &AgentCallContext,
         mut headers: HashMap<String, Vec<String>>,
         route_agents: &[(String, FailureMode)],
+        execution: AgentExecutionMode,
     ) -> SentinelResult<AgentDecision> {
         let method = headers
             .remove(":method")
@@ -407,9 +504,35 @@ impl AgentManager {
             headers,
         };
 
-        // Use parallel processing for better latency with multiple agents
-        self.process_event_parallel(EventType::RequestHeaders, &event, route_agents, ctx)
-            .await
+        // Agents may mark header decisions as cacheable
+        let cache_inputs = CacheKeyInputs {
+            client_ip: &event.metadata.client_ip,
+            route_id: ctx.route_id.as_deref(),
+            headers: &event.headers,
+        };
+
+        match execution {
+            AgentExecutionMode::Sequential => {
+                self.process_event_with_failure_modes(
+                    EventType::RequestHeaders,
+                    &event,
+                    route_agents,
+                    ctx,
+                    Some(cache_inputs),
+                )
+                .await
+            }
+            AgentExecutionMode::Parallel => {
+                self.process_event_parallel(
+                    EventType::RequestHeaders,
+                    &event,
+                    route_agents,
+                    ctx,
+                    Some(cache_inputs),
+                )
+                .await
+            }
+        }
     }
 
     /// Process request body chunk through agents.
@@ -549,11 +672,21 @@ impl AgentManager {
             "Processing WebSocket frame through agents"
         );
 
+        // Shadow agents see the frame without holding it up
+        self.start_shadow_calls(
+            EventType::WebSocketFrame,
+            &event,
+            self.shadow_agents.iter().map(String::as_str),
+            &event.correlation_id,
+        )
+        .await;
+
         // Get relevant agents for this route that handle WebSocket frames
         let agents = self.agents.read().await;
         let relevant_agents: Vec<_> = agents
             .values()
             .filter(|agent| agent.handles_event(EventType::WebSocketFrame))
+            .filter(|agent| !self.shadow_agents.contains(agent.id()))
             .collect();
 
         if relevant_agents.is_empty() {
@@ -690,10 +823,20 @@ impl AgentManager {
             "Starting agent event processing"
         );
 
+        // Shadow agents see the event without holding up the request
+        self.start_shadow_calls(
+            event_type,
+            event,
+            route_agents.iter().map(String::as_str),
+            ctx.correlation_id.as_str(),
+        )
+        .await;
+
         // Get relevant agents for this route and event type
         let agents = self.agents.read().await;
         let relevant_agents: Vec<_> = route_agents
             .iter()
+            .filter(|id| !self.shadow_agents.contains(*id))
             .filter_map(|id| agents.get(id))
             .filter(|agent| agent.handles_event(event_type))
             .collect();
@@ -795,7 +938,18 @@ impl AgentManager {
                 "Calling agent"
             );
 
-            match timeout(timeout_duration, agent.call_event(event_type, event)).await {
+            let result = timeout(timeout_duration, agent.call_event(event_type, event)).await;
+            if let Some(recorder) = self.recorders.get(agent.id()) {
+                recorder.record(
+                    ctx.correlation_id.as_str(),
+                    event_type,
+                    event,
+                    &result,
+                    start.elapsed(),
+                );
+            }
+
+            match result {
                 Ok(Ok(response)) => {
                     let duration = start.elapsed();
                     agent.record_success(duration).await;
@@ -879,6 +1033,7 @@ impl AgentManager {
         event: &T,
         route_agents: &[(String, FailureMode)],
         ctx: &AgentCallContext,
+        cache_inputs: Option<CacheKeyInputs<'_>>,
     ) -> SentinelResult<AgentDecision> {
         trace!(
             correlation_id = %ctx.correlation_id,
@@ -887,10 +1042,20 @@ impl AgentManager {
             "Starting agent event processing with failure modes"
         );
 
+        // Shadow agents see the event without holding up the request
+        self.start_shadow_calls(
+            event_type,
+            event,
+            route_agents.iter().map(|(id, _)| id.as_str()),
+            ctx.correlation_id.as_str(),
+        )
+        .await;
+
         // Get relevant agents for this route and event type, preserving failure modes
         let agents = self.agents.read().await;
         let relevant_agents: Vec<_> = route_agents
             .iter()
+            .filter(|(id, _)| !self.shadow_agents.contains(id))
             .filter_map(|(id, failure_mode)| {
                 agents.get(id).map(|agent| (agent, *failure_mode))
             })
@@ -927,6 +1092,23 @@ impl AgentManager {
                 "Processing event through agent with filter failure mode"
             );
 
+            // Reuse a cached decision without calling the agent
+            let cache_epoch = agent.cache_epoch();
+            if let Some(response) = cache_inputs
+                .and_then(|inputs| self.decision_cache.lookup(agent.id(), cache_epoch, inputs))
+            {
+                trace!(
+                    correlation_id = %ctx.correlation_id,
+                    agent_id = %agent.id(),
+                    "Using cached agent decision"
+                );
+                combined_decision.merge(response.into());
+                if !combined_decision.is_allow() {
+                    break;
+                }
+                continue;
+            }
+
             // Acquire per-agent semaphore permit (queue isolation)
             let semaphores = self.agent_semaphores.read().await;
             let agent_semaphore = semaphores.get(agent.id()).cloned();
@@ -993,7 +1175,18 @@ impl AgentManager {
                 "Calling agent"
             );
 
-            match timeout(timeout_duration, agent.call_event(event_type, event)).await {
+            let result = timeout(timeout_duration, agent.call_event(event_type, event)).await;
+            if let Some(recorder) = self.recorders.get(agent.id()) {
+                recorder.record(
+                    ctx.correlation_id.as_str(),
+                    event_type,
+                    event,
+                    &result,
+                    start.elapsed(),
+                );
+            }
+
+            match result {
                 Ok(Ok(response)) => {
                     let duration = start.elapsed();
                     agent.record_success(duration).await;
@@ -1006,6 +1199,11 @@ impl AgentManager {
                         "Agent call succeeded"
                     );
 
+                    if let Some(inputs) = cache_inputs {
+                        self.decision_cache
+                            .store(agent.id(), cache_epoch, inputs, &response);
+                    }
+
                     // Merge response into combined decision
                     combined_decision.merge(response.into());
 
@@ -1088,10 +1286,13 @@ impl AgentManager {
 
     /// Process an event through relevant agents in parallel.
     ///
-    /// This method executes all agent calls concurrently using `join_all`, which
-    /// significantly improves latency when multiple agents are configured. The
-    /// tradeoff is that if one agent blocks, other agents may still complete
-    /// their work (slight resource waste in blocking scenarios).
+    /// This method executes all agent calls concurrently, which significantly
+    /// improves latency when multiple agents are configured. The first agent to
+    /// return a blocking decision wins: agents still running are cancelled (v2
+    /// agents receive a `CancelRequest`), and the returned decision carries the
+    /// audit metadata of every agent that answered. If no agent blocks, header
+    /// operations are merged in filter order so the result does not depend on
+    /// which agent answered first.
     ///
     /// # Performance
     ///
@@ -1106,6 +1307,7 @@ impl AgentManager {
         event: &T,
         route_agents: &[(String, FailureMode)],
         ctx: &AgentCallContext,
+        cache_inputs: Option<CacheKeyInputs<'_>>,
     ) -> SentinelResult<AgentDecision> {
         trace!(
             correlation_id = %ctx.correlation_id,
@@ -1114,6 +1316,15 @@ impl AgentManager {
             "Starting parallel agent event processing"
         );
 
+        // Shadow agents see the event without holding up the request
+        self.start_shadow_calls(
+            event_type,
+            event,
+            route_agents.iter().map(|(id, _)| id.as_str()),
+            ctx.correlation_id.as_str(),
+        )
+        .await;
+
         // Get relevant agents for this route and event type
         let agents = self.agents.read().await;
         let semaphores = self.agent_semaphores.read().await;
@@ -1121,6 +1332,7 @@ impl AgentManager {
         // Collect agent info upfront to minimize lock duration
         let agent_info: Vec<_> = route_agents
             .iter()
+            .filter(|(id, _)| !self.shadow_agents.contains(id))
             .filter_map(|(id, failure_mode)| {
                 let agent = agents.get(id)?;
                 if !agent.handles_event(event_type) {
@@ -1152,16 +1364,33 @@ impl AgentManager {
             "Processing event through agents in parallel"
         );
 
-        // Spawn all agent calls concurrently
-        let futures: Vec<_> = agent_info
+        // Spawn all agent calls concurrently, tagged with their filter position
+        let mut pending: FuturesUnordered<_> = agent_info
             .iter()
             .map(|(agent, filter_failure_mode, semaphore)| {
                 let agent = Arc::clone(agent);
                 let filter_failure_mode = *filter_failure_mode;
                 let semaphore = semaphore.clone();
                 let correlation_id = ctx.correlation_id.clone();
+                let decision_cache = &self.decision_cache;
+                let recorder = self.recorders.get(agent.id());
 
                 async move {
+                    // Reuse a cached decision without calling the agent
+                    let cache_epoch = agent.cache_epoch();
+                    if let Some(inputs) = cache_inputs {
+                        if let Some(response) =
+                            decision_cache.lookup(agent.id(), cache_epoch, inputs)
+                        {
+                            trace!(
+                                correlation_id = %correlation_id,
+                                agent_id = %agent.id(),
+                                "Using cached agent decision"
+                            );
+                            return Ok((agent.id().to_string(), response));
+                        }
+                    }
+
                     // Acquire per-agent semaphore permit (queue isolation)
                     let _permit = if let Some(sem) = semaphore {
                         match sem.acquire_owned().await {
@@ -1202,7 +1431,19 @@ impl AgentManager {
                     let start = Instant::now();
                     let timeout_duration = Duration::from_millis(agent.timeout_ms());
 
-                    match timeout(timeout_duration, agent.call_event(event_type, event)).await {
+                    let result =
+                        timeout(timeout_duration, agent.call_event(event_type, event)).await;
+                    if let Some(recorder) = recorder {
+                        recorder.record(
+                            correlation_id.as_str(),
+                            event_type,
+                            event,
+                            &result,
+                            start.elapsed(),
+                        );
+                    }
+
+                    match result {
                         Ok(Ok(response)) => {
                             let duration = start.elapsed();
                             agent.record_success(duration).await;
@@ -1212,6 +1453,9 @@ impl AgentManager {
                                 duration_ms = duration.as_millis(),
                                 "Parallel agent call succeeded"
                             );
+                            if let Some(inputs) = cache_inputs {
+                                decision_cache.store(agent.id(), cache_epoch, inputs, &response);
+                            }
                             Ok((agent.id().to_string(), response))
                         }
                         Ok(Err(e)) => {
@@ -1248,33 +1492,75 @@ impl AgentManager {
                     }
                 }
             })
+            .enumerate()
+            .map(|(index, call)| call.map(move |result| (index, result)))
             .collect();
 
-        // Execute all agent calls in parallel
-        let results = join_all(futures).await;
+        // Collect results as they complete, stopping at the first blocking decision
+        let mut results: Vec<Option<_>> = (0..agent_info.len()).map(|_| None).collect();
+        let mut blocked_by = None;
+        while let Some((index, result)) = pending.next().await {
+            if let Ok((agent_id, response)) = &result {
+                if !matches!(response.decision, Decision::Allow) {
+                    debug!(
+                        correlation_id = %ctx.correlation_id,
+                        agent_id = %agent_id,
+                        decision = ?response.decision,
+                        "Agent returned blocking decision, cancelling remaining agents"
+                    );
+                    blocked_by = Some(index);
+                }
+            }
+            results[index] = Some(result);
+            if blocked_by.is_some() {
+                break;
+            }
+        }
+        // Dropping the remaining calls releases their permits
+        drop(pending);
+
+        if let Some(blocker) = blocked_by {
+            for ((agent, _, _), result) in agent_info.iter().zip(&results) {
+                if result.is_some() {
+                    continue;
+                }
+                if let UnifiedAgent::V2(agent) = agent.as_ref() {
+                    let agent = Arc::clone(agent);
+                    let correlation_id = ctx.metadata.correlation_id.clone();
+                    tokio::spawn(async move {
+                        // Failures are logged by the agent
+                        let _ = agent
+                            .cancel_request(&correlation_id, CancelReason::BlockedByAgent)
+                            .await;
+                    });
+                }
+            }
 
-        // Process results and merge decisions
+            // Keep audit metadata from every agent that answered, in filter order
+            let mut decision = AgentDecision::default_allow();
+            let mut audit = Vec::new();
+            for (index, result) in results.into_iter().enumerate() {
+                let Some(Ok((_, response))) = result else {
+                    continue;
+                };
+                let mut agent_decision: AgentDecision = response.into();
+                audit.append(&mut agent_decision.audit);
+                if index == blocker {
+                    decision = agent_decision;
+                }
+            }
+            decision.audit = audit;
+            return Ok(decision);
+        }
+
+        // Process results in filter order and merge decisions
         let mut combined_decision = AgentDecision::default_allow();
         let mut blocking_error: Option<AgentDecision> = None;
 
-        for result in results {
+        for result in results.into_iter().flatten() {
             match result {
-                Ok((agent_id, response)) => {
-                    let decision: AgentDecision = response.into();
-
-                    // Check for blocking decision
-                    if !decision.is_allow() {
-                        debug!(
-                            correlation_id = %ctx.correlation_id,
-                            agent_id = %agent_id,
-                            decision = ?decision,
-                            "Agent returned blocking decision"
-                        );
-                        // Return first blocking decision immediately
-                        return Ok(decision);
-                    }
-
-                    combined_decision.merge(decision);
+                Ok((_, response)) => {
+                    combined_decision.merge(response.into());
                 }
                 Err((agent_id, failure_mode, reason)) => {
                     // Handle failure based on filter's failure mode
@@ -1285,8 +1571,6 @@ impl AgentManager {
                             reason = %reason,
                             "Agent failure in fail-closed mode"
                         );
-                        // Store blocking error but continue processing other results
-                        // in case another agent returned a more specific block
                         let status = if reason.contains("Timeout") { 504 } else { 503 };
                         let message = if reason.contains("Timeout") {
                             "Gateway timeout"
@@ -1322,6 +1606,56 @@ impl AgentManager {
         Ok(combined_decision)
     }
 
+    /// Start background calls to the shadow agents among `agent_ids`.
+    ///
+    /// Their decisions are only logged and metered (see [`shadow`]); the
+    /// caller continues with the enforcing agents without waiting.
+    async fn start_shadow_calls<'a, T: serde::Serialize>(
+        &self,
+        event_type: EventType,
+        event: &T,
+        agent_ids: impl Iterator<Item = &'a str>,
+        correlation_id: &str,
+    ) {
+        if self.shadow_agents.is_empty() {
+            return;
+        }
+
+        let agents = self.agents.read().await;
+        let semaphores = self.agent_semaphores.read().await;
+        let mut event_json = None;
+
+        for id in agent_ids.filter(|id| self.shadow_agents.contains(*id)) {
+            let Some(agent) = agents.get(id).filter(|a| a.handles_event(event_type)) else {
+                continue;
+            };
+
+            // Serialize once; the background task outlives the borrowed event
+            if event_json.is_none() {
+                match serde_json::to_value(event) {
+                    Ok(value) => event_json = Some(value),
+                    Err(e) => {
+                        warn!(
+                            correlation_id = %correlation_id,
+                            error = %e,
+                            "Failed to serialize event for shadow agents"
+                        );
+                        return;
+                    }
+                }
+            }
+
+            shadow::spawn(ShadowCall {
+                agent: Arc::clone(agent),
+                semaphore: semaphores.get(id).cloned(),
+                recorder: self.recorders.get(id).cloned(),
+                event_type,
+                event: event_json.clone().unwrap_or_default(),
+                correlation_id: correlation_id.to_string(),
+            });
+        }
+    }
+
     /// Initialize agent connections.
     pub async fn initialize(&self) -> SentinelResult<()> {
         let agents = self.agents.read().await;
@@ -1372,6 +1706,40 @@ impl AgentManager {
         info!("Agent manager shutdown complete");
     }
 
+    /// Apply reloaded configuration to WASM agents.
+    ///
+    /// Each existing WASM agent hot-swaps its component if the module or its
+    /// limits changed. Agents added or removed by the reload are not picked
+    /// up until restart, the same as socket-based agents.
+    pub async fn reload_wasm_agents(&self, configs: &[AgentConfig]) {
+        let agents = self.agents.read().await;
+
+        for config in configs {
+            let Some(agent) = agents.get(&config.id) else {
+                if matches!(config.transport, AgentTransport::Wasm { .. }) {
+                    warn!(
+                        agent_id = %config.id,
+                        "New WASM agent requires a restart to take effect"
+                    );
+                }
+                continue;
+            };
+            let UnifiedAgent::Wasm(wasm_agent) = agent.as_ref() else {
+                continue;
+            };
+
+            match wasm_agent.reload(config).await {
+                Ok(true) => info!(agent_id = %config.id, "WASM agent reloaded"),
+                Ok(false) => {}
+                Err(e) => error!(
+                    agent_id = %config.id,
+                    error = %e,
+                    "Failed to reload WASM agent, keeping previous component"
+                ),
+            }
+        }
+    }
+
     /// Get agent metrics.
     pub fn metrics(&self) -> &AgentMetrics {
         &self.metrics
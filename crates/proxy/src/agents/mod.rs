@@ -9,6 +9,7 @@
 //! - [`AgentManager`]: Coordinates all agents, handles routing to appropriate agents
 //! - [`Agent`]: Protocol v1 agent with connection, circuit breaker, and metrics
 //! - [`AgentV2`]: Protocol v2 agent with bidirectional streaming and pooling
+//! - [`WasmAgent`]: In-process WebAssembly component with resource limits
 //! - [`AgentConnectionPool`]: Connection pooling for efficient connection reuse (v1)
 //! - [`AgentDecision`]: Combined result from processing through agents
 //! - [`AgentCallContext`]: Request context passed to agents
@@ -19,6 +20,29 @@
 //! - **V2**: Bidirectional streaming with capabilities, health reporting,
 //!           metrics export, and flow control
 //!
+//! Agents using the `wasm` transport run in-process under fuel, memory and
+//! wall-clock limits and are hot-swapped on config reload.
+//!
+//! Request-header decisions carrying a cache directive are reused until their
+//! TTL expires or a v2 agent invalidates them with a config update.
+//!
+//! Agents with a `record` block write a sample of the events they are sent and
+//! their responses to an on-disk ring. `sentinel agent replay` sends a
+//! recording to a local agent and diffs its decisions against the recorded
+//! ones.
+//!
+//! Agents with `enforce false` run in shadow mode: they are called in the
+//! background with the same events, and the decisions they would have made
+//! are only logged and counted in `sentinel_agent_shadow_*` metrics.
+//!
+//! # Execution Modes
+//!
+//! Request headers go to all of a route's agents concurrently. The first
+//! blocking decision wins and cancels the remaining calls; otherwise header
+//! operations are merged in filter order. Routes that set
+//! `agent-execution "sequential"` call their agents one after another in
+//! filter order instead, stopping at the first block.
+//!
 //! # Queue Isolation
 //!
 //! Each agent has its own semaphore for queue isolation, preventing a slow agent
@@ -34,7 +58,9 @@
 //! let manager = AgentManager::new(agent_configs).await?;
 //! manager.initialize().await?;
 //!
-//! let decision = manager.process_request_headers(&ctx, headers, &["waf", "auth"]).await?;
+//! let decision = manager
+//!     .process_request_headers(&ctx, headers, &route_agents, AgentExecutionMode::Parallel)
+//!     .await?;
 //! if !decision.is_allow() {
 //!     // Handle block/redirect/challenge
 //! }
@@ -42,19 +68,31 @@
 
 mod agent;
 mod agent_v2;
+mod agent_wasm;
 mod context;
 mod decision;
+mod decision_cache;
 mod manager;
 mod metrics;
 mod pool;
+mod recorder;
+mod replay;
+mod shadow;
+mod wasm_metrics;
 
 pub use agent::Agent;
 pub use agent_v2::AgentV2;
+pub use agent_wasm::WasmAgent;
 pub use context::AgentCallContext;
 pub use decision::{AgentAction, AgentDecision};
+pub use decision_cache::{get_decision_cache_metrics, DecisionCacheMetrics};
 pub use manager::AgentManager;
 pub use metrics::AgentMetrics;
 pub use pool::AgentConnectionPool;
+pub use recorder::{AgentRecorder, RecordedExchange};
+pub use replay::{replay_recording, run_agent_command, AgentArgs, AgentCommand, ReplayReport};
+pub use shadow::{get_shadow_agent_metrics, ShadowAgentMetrics};
+pub use wasm_metrics::{get_wasm_agent_metrics, WasmAgentMetrics};
 
 #[cfg(test)]
 mod tests {
@@ -126,6 +164,8 @@ mod tests {
             chunk_timeout_ms: 5000,
             config: None,
             max_concurrent_calls: 50, // Custom limit
+            recording: None,
+            enforce: true,
         };
 
         assert_eq!(config.max_concurrent_calls, 50);
@@ -150,6 +190,8 @@ mod tests {
             chunk_timeout_ms: 5000,
             config: None,
             max_concurrent_calls: 100, // Default value
+            recording: None,
+            enforce: true,
         };
 
         assert_eq!(default_config.max_concurrent_calls, 100);
@@ -180,6 +222,10 @@ mod tests {
                 drain_timeout_ms: 60000,
                 max_concurrent_per_connection: 200,
                 health_check_interval_ms: 5000,
+                priority: Default::default(),
+                sample_rate: 1.0,
+                flow_control_mode: Default::default(),
+                flow_control_wait_timeout_ms: 100,
             }),
             timeout_ms: 2000,
             failure_mode: Default::default(),
@@ -191,6 +237,8 @@ mod tests {
             chunk_timeout_ms: 5000,
             config: None,
             max_concurrent_calls: 100,
+            recording: None,
+            enforce: true,
         };
 
         assert_eq!(config.protocol_version, AgentProtocolVersion::V2);
@@ -0,0 +1,664 @@
+//! Sampled recording of agent traffic.
+//!
+//! When an agent has a `record` block, a sample of requests is chosen by
+//! hashing the correlation ID, so every event of a sampled request is kept
+//! and the rest cost a single hash. Each sampled request gets one slot in a
+//! ring of `max_recordings` files under the recording directory; once the ring
+//! is full the oldest request's slot is truncated and reused.
+//!
+//! A slot file holds one JSON [`RecordedExchange`] per line, in the order the
+//! events were sent. Body chunk and WebSocket frame contents are redacted
+//! unless `include_bodies` is set. Credentials are always redacted: the values
+//! of `Authorization`, `Cookie`, `Set-Cookie` and API key headers, in events
+//! and in agent header operations, and query parameter values in request URIs.
+//!
+//! Files are written by a dedicated thread per recorder, so recording never
+//! blocks the request path on disk I/O. When the writer falls behind, new
+//! exchanges are dropped rather than queued without bound.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Sender, SyncSender, TrySendError};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use sentinel_agent_protocol::{AgentResponse, BodyMutation, EventType, HeaderOp};
+use sentinel_common::errors::SentinelResult;
+use sentinel_config::AgentRecordingConfig;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Replacement for redacted body mutation contents and credentials.
+const REDACTED: &str = "[redacted]";
+
+/// Exchanges queued for the writer thread before new ones are dropped.
+const WRITE_QUEUE_EXCHANGES: usize = 1024;
+
+/// One event sent to an agent and what came back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedExchange {
+    /// Agent the event was sent to
+    pub agent_id: String,
+    /// Request correlation ID
+    pub correlation_id: String,
+    /// When the call completed (RFC 3339)
+    pub timestamp: String,
+    /// Event type
+    pub event_type: EventType,
+    /// Event payload as sent, with bodies redacted unless configured otherwise
+    pub event: serde_json::Value,
+    /// Whether body contents were removed from `event` and `response`
+    /// (credentials are always removed)
+    #[serde(default)]
+    pub redacted: bool,
+    /// Agent response, if the call succeeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<AgentResponse>,
+    /// Error or timeout, if the call failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Call duration in milliseconds
+    pub duration_ms: u64,
+}
+
+impl RecordedExchange {
+    /// Read all exchanges from a recording file.
+    pub fn read_all(path: &Path) -> Result<Vec<Self>> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open recording: {}", path.display()))?;
+
+        let mut exchanges = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let exchange = serde_json::from_str(&line).with_context(|| {
+                format!(
+                    "Invalid exchange on line {} of {}",
+                    index + 1,
+                    path.display()
+                )
+            })?;
+            exchanges.push(exchange);
+        }
+        Ok(exchanges)
+    }
+}
+
+/// Message for a recorder's writer thread.
+enum WriterMessage {
+    Exchange(Box<RecordedExchange>),
+    /// Acknowledge once everything queued before it is written
+    Flush(Sender<()>),
+}
+
+/// Slot ring, owned by the writer thread.
+struct RecordingWriter {
+    agent_id: String,
+    directory: PathBuf,
+    max_recordings: usize,
+    max_recording_bytes: u64,
+    /// Slot and bytes written for each request being recorded
+    active: HashMap<String, (usize, u64)>,
+    /// Requests in slot assignment order, oldest first
+    order: VecDeque<String>,
+    /// Next slot to hand out
+    next_slot: usize,
+}
+
+impl RecordingWriter {
+    fn run(mut self, queue: std::sync::mpsc::Receiver<WriterMessage>) {
+        for message in queue {
+            match message {
+                WriterMessage::Exchange(exchange) => {
+                    if let Err(e) = self.write(&exchange) {
+                        warn!(
+                            agent_id = %self.agent_id,
+                            correlation_id = %exchange.correlation_id,
+                            error = %e,
+                            "Failed to write agent recording"
+                        );
+                    }
+                }
+                WriterMessage::Flush(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+
+    fn write(&mut self, exchange: &RecordedExchange) -> Result<()> {
+        let mut line = serde_json::to_vec(exchange)?;
+        line.push(b'\n');
+
+        let (slot, written) = match self.active.get(&exchange.correlation_id) {
+            Some(&entry) => entry,
+            None => {
+                // The oldest request holds the slot being reused
+                if self.order.len() >= self.max_recordings {
+                    if let Some(evicted) = self.order.pop_front() {
+                        self.active.remove(&evicted);
+                    }
+                }
+                let slot = self.next_slot;
+                self.next_slot = (slot + 1) % self.max_recordings;
+                File::create(slot_path(&self.directory, slot))?;
+                self.active
+                    .insert(exchange.correlation_id.clone(), (slot, 0));
+                self.order.push_back(exchange.correlation_id.clone());
+                (slot, 0)
+            }
+        };
+
+        let size = line.len() as u64;
+        if written + size > self.max_recording_bytes {
+            debug!(
+                agent_id = %self.agent_id,
+                correlation_id = %exchange.correlation_id,
+                "Recording size limit reached, dropping exchange"
+            );
+            return Ok(());
+        }
+
+        OpenOptions::new()
+            .append(true)
+            .open(slot_path(&self.directory, slot))?
+            .write_all(&line)?;
+        self.active
+            .insert(exchange.correlation_id.clone(), (slot, written + size));
+        Ok(())
+    }
+}
+
+fn slot_path(directory: &Path, slot: usize) -> PathBuf {
+    directory.join(format!("{:06}.jsonl", slot))
+}
+
+/// Per-agent traffic recorder.
+pub struct AgentRecorder {
+    agent_id: String,
+    config: AgentRecordingConfig,
+    /// Requests whose correlation ID hashes below this are recorded
+    threshold: u64,
+    /// Queue feeding the writer thread, which exits when the recorder is dropped
+    queue: SyncSender<WriterMessage>,
+    /// Exchanges dropped because the writer was behind
+    dropped: AtomicU64,
+}
+
+impl AgentRecorder {
+    /// Create a recorder, creating the recording directory and starting its
+    /// writer thread.
+    ///
+    /// Returns `None` if the configuration records nothing.
+    pub fn new(agent_id: &str, config: &AgentRecordingConfig) -> Result<Option<Self>> {
+        if config.sample_rate <= 0.0 || config.max_recordings == 0 {
+            return Ok(None);
+        }
+
+        std::fs::create_dir_all(&config.directory).with_context(|| {
+            format!(
+                "Failed to create recording directory: {}",
+                config.directory.display()
+            )
+        })?;
+
+        let threshold = if config.sample_rate >= 1.0 {
+            u64::MAX
+        } else {
+            (config.sample_rate * u64::MAX as f64) as u64
+        };
+
+        let writer = RecordingWriter {
+            agent_id: agent_id.to_string(),
+            directory: config.directory.clone(),
+            max_recordings: config.max_recordings,
+            max_recording_bytes: config.max_recording_bytes,
+            active: HashMap::new(),
+            order: VecDeque::new(),
+            next_slot: 0,
+        };
+        let (queue, receiver) = sync_channel(WRITE_QUEUE_EXCHANGES);
+        std::thread::Builder::new()
+            .name(format!("record-{}", agent_id))
+            .spawn(move || writer.run(receiver))
+            .context("Failed to start agent recording writer")?;
+
+        Ok(Some(Self {
+            agent_id: agent_id.to_string(),
+            config: config.clone(),
+            threshold,
+            queue,
+            dropped: AtomicU64::new(0),
+        }))
+    }
+
+    /// Whether events for this request are recorded.
+    pub fn is_sampled(&self, correlation_id: &str) -> bool {
+        self.threshold == u64::MAX || xxh3_64(correlation_id.as_bytes()) < self.threshold
+    }
+
+    /// Path of a ring slot.
+    pub fn slot_path(&self, slot: usize) -> PathBuf {
+        slot_path(&self.config.directory, slot)
+    }
+
+    /// Record an agent call if its request is sampled.
+    ///
+    /// `result` is the outcome of the timed call; the outer error is the
+    /// timeout. The exchange is queued for the writer thread without
+    /// blocking; write failures are logged and never affect the request.
+    pub fn record<T: Serialize, E>(
+        &self,
+        correlation_id: &str,
+        event_type: EventType,
+        event: &T,
+        result: &std::result::Result<SentinelResult<AgentResponse>, E>,
+        duration: Duration,
+    ) {
+        if !self.is_sampled(correlation_id) {
+            return;
+        }
+
+        let mut event = match serde_json::to_value(event) {
+            Ok(event) => event,
+            Err(e) => {
+                warn!(
+                    agent_id = %self.agent_id,
+                    error = %e,
+                    "Failed to serialize recorded event"
+                );
+                return;
+            }
+        };
+        let (mut response, error) = match result {
+            Ok(Ok(response)) => (Some(response.clone()), None),
+            Ok(Err(e)) => (None, Some(e.to_string())),
+            Err(_) => (None, Some("timeout".to_string())),
+        };
+
+        redact_credentials(&mut event, &mut response);
+        let redacted = !self.config.include_bodies && redact(event_type, &mut event, &mut response);
+
+        let exchange = RecordedExchange {
+            agent_id: self.agent_id.clone(),
+            correlation_id: correlation_id.to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            event_type,
+            event,
+            redacted,
+            response,
+            error,
+            duration_ms: duration.as_millis() as u64,
+        };
+
+        match self
+            .queue
+            .try_send(WriterMessage::Exchange(Box::new(exchange)))
+        {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped.is_power_of_two() {
+                    warn!(
+                        agent_id = %self.agent_id,
+                        dropped,
+                        "Agent recording writer is behind, dropping exchanges"
+                    );
+                }
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                debug!(agent_id = %self.agent_id, "Agent recording writer stopped");
+            }
+        }
+    }
+
+    /// Block until every exchange recorded so far has been written.
+    ///
+    /// Not for use on the request path.
+    pub fn flush(&self) {
+        let (done, wait) = std::sync::mpsc::channel();
+        if self.queue.send(WriterMessage::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+}
+
+/// Remove body contents from an event and its response.
+///
+/// Returns `true` if anything was removed. Sizes and chunk indices are kept.
+fn redact(
+    event_type: EventType,
+    event: &mut serde_json::Value,
+    response: &mut Option<AgentResponse>,
+) -> bool {
+    let mut redacted = false;
+
+    if matches!(
+        event_type,
+        EventType::RequestBodyChunk | EventType::ResponseBodyChunk | EventType::WebSocketFrame
+    ) {
+        if let Some(data) = event.get_mut("data") {
+            *data = serde_json::Value::String(String::new());
+            redacted = true;
+        }
+    }
+
+    if let Some(response) = response {
+        for mutation in [
+            &mut response.request_body_mutation,
+            &mut response.response_body_mutation,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            redacted |= redact_mutation(mutation);
+        }
+    }
+
+    redacted
+}
+
+/// Remove credentials from an event and its response.
+///
+/// Header values are replaced one for one, so header counts are kept.
+fn redact_credentials(event: &mut serde_json::Value, response: &mut Option<AgentResponse>) {
+    if let Some(headers) = event
+        .get_mut("headers")
+        .and_then(|headers| headers.as_object_mut())
+    {
+        for (name, values) in headers.iter_mut() {
+            if !is_credential_header(name) {
+                continue;
+            }
+            if let Some(values) = values.as_array_mut() {
+                for value in values {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                }
+            }
+        }
+    }
+
+    if let Some(uri) = event.get_mut("uri") {
+        if let Some(redacted) = uri.as_str().and_then(redact_query) {
+            *uri = serde_json::Value::String(redacted);
+        }
+    }
+
+    if let Some(response) = response {
+        for op in response
+            .request_headers
+            .iter_mut()
+            .chain(response.response_headers.iter_mut())
+        {
+            if let HeaderOp::Set { name, value } | HeaderOp::Add { name, value } = op {
+                if is_credential_header(name) {
+                    *value = REDACTED.to_string();
+                }
+            }
+        }
+    }
+}
+
+/// Whether a header carries credentials.
+fn is_credential_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    matches!(
+        name.as_str(),
+        "authorization" | "proxy-authorization" | "cookie" | "set-cookie"
+    ) || ["api-key", "api_key", "apikey"]
+        .iter()
+        .any(|key| name.contains(key))
+}
+
+/// Replace the query parameter values of a URI, keeping their names.
+///
+/// Returns `None` if the URI has no query.
+fn redact_query(uri: &str) -> Option<String> {
+    let (path, query) = uri.split_once('?')?;
+    let params: Vec<String> = query
+        .split('&')
+        .map(|param| match param.split_once('=') {
+            Some((name, _)) => format!("{}={}", name, REDACTED),
+            None => param.to_string(),
+        })
+        .collect();
+    Some(format!("{}?{}", path, params.join("&")))
+}
+
+fn redact_mutation(mutation: &mut BodyMutation) -> bool {
+    let mut redacted = false;
+    for content in [&mut mutation.data, &mut mutation.insert] {
+        if let Some(content) = content.as_mut().filter(|c| !c.is_empty()) {
+            *content = REDACTED.to_string();
+            redacted = true;
+        }
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sentinel_agent_protocol::Decision;
+
+    fn config(dir: &Path, max_recordings: usize) -> AgentRecordingConfig {
+        AgentRecordingConfig {
+            sample_rate: 1.0,
+            max_recordings,
+            ..AgentRecordingConfig::new(dir)
+        }
+    }
+
+    fn allow() -> std::result::Result<SentinelResult<AgentResponse>, ()> {
+        Ok(Ok(AgentResponse::default_allow()))
+    }
+
+    #[test]
+    fn test_records_exchanges_per_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = AgentRecorder::new("waf", &config(dir.path(), 10))
+            .unwrap()
+            .unwrap();
+
+        let event = serde_json::json!({ "correlation_id": "req-1" });
+        recorder.record(
+            "req-1",
+            EventType::RequestHeaders,
+            &event,
+            &allow(),
+            Duration::ZERO,
+        );
+        let blocked: std::result::Result<SentinelResult<AgentResponse>, ()> =
+            Ok(Ok(AgentResponse::block(403, None)));
+        recorder.record(
+            "req-1",
+            EventType::RequestBodyChunk,
+            &event,
+            &blocked,
+            Duration::ZERO,
+        );
+        recorder.record(
+            "req-2",
+            EventType::RequestHeaders,
+            &event,
+            &Err(()),
+            Duration::ZERO,
+        );
+
+        recorder.flush();
+        let first = RecordedExchange::read_all(&recorder.slot_path(0)).unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].event_type, EventType::RequestHeaders);
+        assert!(matches!(
+            first[1].response.as_ref().unwrap().decision,
+            Decision::Block { status: 403, .. }
+        ));
+
+        let second = RecordedExchange::read_all(&recorder.slot_path(1)).unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].error.as_deref(), Some("timeout"));
+    }
+
+    #[test]
+    fn test_ring_reuses_oldest_slot() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = AgentRecorder::new("waf", &config(dir.path(), 2))
+            .unwrap()
+            .unwrap();
+
+        let event = serde_json::json!({});
+        for id in ["req-1", "req-2", "req-3"] {
+            recorder.record(
+                id,
+                EventType::RequestHeaders,
+                &event,
+                &allow(),
+                Duration::ZERO,
+            );
+        }
+
+        recorder.flush();
+        let reused = RecordedExchange::read_all(&recorder.slot_path(0)).unwrap();
+        assert_eq!(reused.len(), 1);
+        assert_eq!(reused[0].correlation_id, "req-3");
+        assert!(!recorder.slot_path(2).exists());
+    }
+
+    #[test]
+    fn test_bodies_redacted_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = AgentRecorder::new("dlp", &config(dir.path(), 10))
+            .unwrap()
+            .unwrap();
+
+        let event = serde_json::json!({ "data": "c2VjcmV0", "total_size": 6 });
+        let mut response = AgentResponse::default_allow();
+        response.request_body_mutation = Some(BodyMutation::replace(0, "masked".to_string()));
+        recorder.record(
+            "req-1",
+            EventType::RequestBodyChunk,
+            &event,
+            &Ok::<_, ()>(Ok(response)),
+            Duration::ZERO,
+        );
+
+        recorder.flush();
+        let exchange = &RecordedExchange::read_all(&recorder.slot_path(0)).unwrap()[0];
+        assert!(exchange.redacted);
+        assert_eq!(exchange.event["data"], "");
+        assert_eq!(exchange.event["total_size"], 6);
+        let mutation = exchange
+            .response
+            .as_ref()
+            .unwrap()
+            .request_body_mutation
+            .as_ref();
+        assert_eq!(mutation.unwrap().data.as_deref(), Some(REDACTED));
+    }
+
+    #[test]
+    fn test_credentials_always_redacted() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = AgentRecorder::new(
+            "auth",
+            &AgentRecordingConfig {
+                include_bodies: true,
+                ..config(dir.path(), 10)
+            },
+        )
+        .unwrap()
+        .unwrap();
+
+        let event = serde_json::json!({
+            "uri": "/v1/chat?key=secret&debug&model=gpt-4",
+            "headers": {
+                "Authorization": ["Bearer secret"],
+                "cookie": ["session=secret", "theme=dark"],
+                "x-api-key": ["secret"],
+                "content-type": ["application/json"],
+            },
+        });
+        let response = AgentResponse::default_allow()
+            .add_request_header(HeaderOp::Set {
+                name: "authorization".to_string(),
+                value: "Bearer upstream-secret".to_string(),
+            })
+            .add_response_header(HeaderOp::Add {
+                name: "Set-Cookie".to_string(),
+                value: "session=secret".to_string(),
+            })
+            .add_response_header(HeaderOp::Set {
+                name: "x-agent".to_string(),
+                value: "auth".to_string(),
+            });
+        recorder.record(
+            "req-1",
+            EventType::RequestHeaders,
+            &event,
+            &Ok::<_, ()>(Ok(response)),
+            Duration::ZERO,
+        );
+        recorder.flush();
+
+        let exchange = &RecordedExchange::read_all(&recorder.slot_path(0)).unwrap()[0];
+        assert!(!exchange.redacted);
+        assert_eq!(
+            exchange.event["uri"],
+            "/v1/chat?key=[redacted]&debug&model=[redacted]"
+        );
+        let headers = &exchange.event["headers"];
+        assert_eq!(headers["Authorization"], serde_json::json!([REDACTED]));
+        assert_eq!(headers["cookie"], serde_json::json!([REDACTED, REDACTED]));
+        assert_eq!(headers["x-api-key"], serde_json::json!([REDACTED]));
+        assert_eq!(
+            headers["content-type"],
+            serde_json::json!(["application/json"])
+        );
+
+        let response = exchange.response.as_ref().unwrap();
+        assert!(matches!(
+            &response.request_headers[0],
+            HeaderOp::Set { value, .. } if value == REDACTED
+        ));
+        assert!(matches!(
+            &response.response_headers[0],
+            HeaderOp::Add { value, .. } if value == REDACTED
+        ));
+        assert!(matches!(
+            &response.response_headers[1],
+            HeaderOp::Set { value, .. } if value == "auth"
+        ));
+    }
+
+    #[test]
+    fn test_sampling_is_per_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = AgentRecorder::new(
+            "waf",
+            &AgentRecordingConfig {
+                sample_rate: 0.5,
+                ..AgentRecordingConfig::new(dir.path())
+            },
+        )
+        .unwrap()
+        .unwrap();
+
+        let sampled = (0..1000)
+            .filter(|i| recorder.is_sampled(&format!("req-{}", i)))
+            .count();
+        assert!((350..650).contains(&sampled), "sampled {}", sampled);
+        assert_eq!(recorder.is_sampled("req-7"), recorder.is_sampled("req-7"));
+
+        let disabled = AgentRecordingConfig {
+            sample_rate: 0.0,
+            ..AgentRecordingConfig::new(dir.path())
+        };
+        assert!(AgentRecorder::new("waf", &disabled).unwrap().is_none());
+    }
+}
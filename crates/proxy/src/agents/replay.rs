@@ -0,0 +1,386 @@
+//! Replay of recorded agent traffic.
+//!
+//! Implements the `sentinel agent replay` subcommand, which sends the events
+//! of a recording to a locally running agent in their original order and
+//! compares its decisions and header operations with the recorded ones.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use pingora_timeout::timeout;
+use sentinel_agent_protocol::{AgentResponse, Decision, EventType, HeaderOp};
+use sentinel_common::types::CircuitBreakerConfig;
+use sentinel_common::CircuitBreaker;
+use sentinel_config::{
+    AgentConfig, AgentEvent, AgentProtocolVersion, AgentTransport, AgentType, BodyStreamingMode,
+    FailureMode,
+};
+use serde::Serialize;
+
+use super::agent::Agent;
+use super::agent_v2::AgentV2;
+use super::agent_wasm::WasmAgent;
+use super::manager::UnifiedAgent;
+use super::pool::AgentConnectionPool;
+use super::recorder::RecordedExchange;
+
+/// Agent command arguments
+#[derive(Args, Debug)]
+pub struct AgentArgs {
+    #[command(subcommand)]
+    pub command: AgentCommand,
+}
+
+/// Agent subcommands
+#[derive(Subcommand, Debug)]
+pub enum AgentCommand {
+    /// Replay a recording against a local agent and diff its decisions
+    Replay {
+        /// Recording file (one slot of an agent's recording directory)
+        recording: PathBuf,
+
+        /// Unix socket of the agent
+        #[arg(long, conflicts_with_all = ["grpc", "wasm"])]
+        socket: Option<PathBuf>,
+
+        /// gRPC address of the agent
+        #[arg(long, conflicts_with = "wasm")]
+        grpc: Option<String>,
+
+        /// WASM component to run in-process
+        #[arg(long)]
+        wasm: Option<PathBuf>,
+
+        /// Agent protocol version
+        #[arg(long, value_enum, default_value = "v2")]
+        protocol: ReplayProtocol,
+
+        /// Timeout per event in milliseconds
+        #[arg(long, default_value = "5000")]
+        timeout_ms: u64,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Protocol used to talk to the replay target
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ReplayProtocol {
+    V1,
+    V2,
+}
+
+/// Decision-relevant parts of an agent call outcome.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayOutcome {
+    /// The agent answered
+    Response {
+        decision: Decision,
+        request_headers: Vec<HeaderOp>,
+        response_headers: Vec<HeaderOp>,
+    },
+    /// The call failed or timed out
+    Error { message: String },
+}
+
+impl ReplayOutcome {
+    fn from_response(response: &AgentResponse) -> Self {
+        Self::Response {
+            decision: response.decision.clone(),
+            request_headers: response.request_headers.clone(),
+            response_headers: response.response_headers.clone(),
+        }
+    }
+
+    /// Whether two outcomes count as the same decision.
+    ///
+    /// Errors match any other error, since messages include timings and
+    /// connection details that differ between runs.
+    fn matches(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Error { .. }, Self::Error { .. }) => true,
+            _ => self == other,
+        }
+    }
+
+    fn summary(&self) -> String {
+        match self {
+            Self::Response {
+                decision,
+                request_headers,
+                response_headers,
+            } => {
+                let decision = match decision {
+                    Decision::Allow => "allow".to_string(),
+                    Decision::Block { status, .. } => format!("block {}", status),
+                    Decision::Redirect { url, status } => format!("redirect {} {}", status, url),
+                    Decision::Challenge { challenge_type, .. } => {
+                        format!("challenge {}", challenge_type)
+                    }
+                };
+                let header_ops = request_headers.len() + response_headers.len();
+                if header_ops > 0 {
+                    format!("{} (+{} header ops)", decision, header_ops)
+                } else {
+                    decision
+                }
+            }
+            Self::Error { message } => format!("error: {}", message),
+        }
+    }
+}
+
+/// Result of replaying one recorded exchange.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayedExchange {
+    pub correlation_id: String,
+    pub event_type: EventType,
+    /// Body contents were redacted in the recording
+    pub redacted: bool,
+    pub recorded: ReplayOutcome,
+    pub replayed: ReplayOutcome,
+    pub matches: bool,
+}
+
+/// Result of replaying a recording.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayReport {
+    pub exchanges: Vec<ReplayedExchange>,
+    pub matched: usize,
+    pub differed: usize,
+}
+
+/// Replay recorded exchanges against an agent, in order.
+///
+/// The agent is called directly, without circuit breaking or failure-mode
+/// handling, so every recorded event reaches it.
+pub async fn replay_recording(
+    config: AgentConfig,
+    exchanges: &[RecordedExchange],
+) -> Result<ReplayReport> {
+    let timeout_duration = Duration::from_millis(config.timeout_ms);
+    let agent = build_agent(config);
+    agent
+        .initialize()
+        .await
+        .context("Failed to connect to agent")?;
+
+    let mut report = ReplayReport::default();
+    for exchange in exchanges {
+        let recorded = match (&exchange.response, &exchange.error) {
+            (Some(response), _) => ReplayOutcome::from_response(response),
+            (None, error) => ReplayOutcome::Error {
+                message: error.clone().unwrap_or_default(),
+            },
+        };
+        let replayed = match timeout(
+            timeout_duration,
+            agent.call_event(exchange.event_type, &exchange.event),
+        )
+        .await
+        {
+            Ok(Ok(response)) => ReplayOutcome::from_response(&response),
+            Ok(Err(e)) => ReplayOutcome::Error {
+                message: e.to_string(),
+            },
+            Err(_) => ReplayOutcome::Error {
+                message: "timeout".to_string(),
+            },
+        };
+
+        let matches = recorded.matches(&replayed);
+        if matches {
+            report.matched += 1;
+        } else {
+            report.differed += 1;
+        }
+        report.exchanges.push(ReplayedExchange {
+            correlation_id: exchange.correlation_id.clone(),
+            event_type: exchange.event_type,
+            redacted: exchange.redacted,
+            recorded,
+            replayed,
+            matches,
+        });
+    }
+
+    agent.shutdown().await;
+    Ok(report)
+}
+
+fn build_agent(config: AgentConfig) -> UnifiedAgent {
+    let circuit_breaker = Arc::new(CircuitBreaker::new(CircuitBreakerConfig::default()));
+    match config.protocol_version {
+        _ if matches!(config.transport, AgentTransport::Wasm { .. }) => {
+            UnifiedAgent::Wasm(Arc::new(WasmAgent::new(config, circuit_breaker)))
+        }
+        AgentProtocolVersion::V1 => {
+            let pool = Arc::new(AgentConnectionPool::new(1, 0, 1, Duration::from_secs(60)));
+            UnifiedAgent::V1(Arc::new(Agent::new(config, pool, circuit_breaker)))
+        }
+        AgentProtocolVersion::V2 => {
+            UnifiedAgent::V2(Arc::new(AgentV2::new(config, circuit_breaker)))
+        }
+    }
+}
+
+/// Run the agent command
+pub fn run_agent_command(args: AgentArgs) -> Result<()> {
+    match args.command {
+        AgentCommand::Replay {
+            recording,
+            socket,
+            grpc,
+            wasm,
+            protocol,
+            timeout_ms,
+            json,
+        } => {
+            let transport = match (socket, grpc, wasm) {
+                (Some(path), _, _) => AgentTransport::UnixSocket { path },
+                (_, Some(address), _) => AgentTransport::Grpc { address, tls: None },
+                (_, _, Some(module)) => AgentTransport::Wasm {
+                    module,
+                    config: Default::default(),
+                },
+                _ => anyhow::bail!("Specify the agent with --socket, --grpc or --wasm"),
+            };
+            cmd_replay(&recording, transport, protocol, timeout_ms, json)
+        }
+    }
+}
+
+/// Replay command implementation
+fn cmd_replay(
+    recording: &Path,
+    transport: AgentTransport,
+    protocol: ReplayProtocol,
+    timeout_ms: u64,
+    json: bool,
+) -> Result<()> {
+    let exchanges = RecordedExchange::read_all(recording)?;
+    let agent_id = exchanges
+        .first()
+        .map(|e| e.agent_id.clone())
+        .ok_or_else(|| anyhow::anyhow!("Recording is empty: {}", recording.display()))?;
+
+    let config = replay_agent_config(agent_id, transport, protocol, timeout_ms);
+    let rt = tokio::runtime::Runtime::new()?;
+    let report = rt.block_on(replay_recording(config, &exchanges))?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "Replaying {} ({} events)",
+            recording.display(),
+            exchanges.len()
+        );
+        println!();
+        for exchange in &report.exchanges {
+            let event = serde_json::to_value(exchange.event_type)?;
+            let event = event.as_str().unwrap_or_default();
+            if exchange.matches {
+                println!("  ✓ {:<20} {}", event, exchange.replayed.summary());
+            } else {
+                println!(
+                    "  ✗ {:<20} recorded: {}",
+                    event,
+                    exchange.recorded.summary()
+                );
+                println!("    {:<20} replayed: {}", "", exchange.replayed.summary());
+            }
+        }
+        println!();
+        println!("{} matched, {} differed", report.matched, report.differed);
+        if report.exchanges.iter().any(|e| e.redacted) {
+            println!("Note: bodies were redacted in this recording; body decisions may differ");
+        }
+    }
+
+    if report.differed > 0 {
+        anyhow::bail!(
+            "{} of {} decisions differ from the recording",
+            report.differed,
+            report.exchanges.len()
+        );
+    }
+    Ok(())
+}
+
+/// Config for the local agent a recording is replayed against.
+fn replay_agent_config(
+    id: String,
+    transport: AgentTransport,
+    protocol: ReplayProtocol,
+    timeout_ms: u64,
+) -> AgentConfig {
+    AgentConfig {
+        id: id.clone(),
+        agent_type: AgentType::Custom(id),
+        transport,
+        events: vec![
+            AgentEvent::RequestHeaders,
+            AgentEvent::RequestBody,
+            AgentEvent::ResponseHeaders,
+            AgentEvent::ResponseBody,
+            AgentEvent::Log,
+            AgentEvent::WebSocketFrame,
+        ],
+        protocol_version: match protocol {
+            ReplayProtocol::V1 => AgentProtocolVersion::V1,
+            ReplayProtocol::V2 => AgentProtocolVersion::V2,
+        },
+        pool: None,
+        timeout_ms,
+        failure_mode: FailureMode::Open,
+        circuit_breaker: None,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        request_body_mode: BodyStreamingMode::Buffer,
+        response_body_mode: BodyStreamingMode::Buffer,
+        chunk_timeout_ms: timeout_ms,
+        config: None,
+        max_concurrent_calls: 1,
+        recording: None,
+        enforce: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcomes_compare_decisions_and_header_ops() {
+        let allow = ReplayOutcome::from_response(&AgentResponse::default_allow());
+        let block = ReplayOutcome::from_response(&AgentResponse::block(403, None));
+        assert!(allow.matches(&allow.clone()));
+        assert!(!allow.matches(&block));
+        assert_eq!(block.summary(), "block 403");
+
+        let mut tagged = AgentResponse::default_allow();
+        tagged.request_headers.push(HeaderOp::Set {
+            name: "X-Score".to_string(),
+            value: "10".to_string(),
+        });
+        let tagged = ReplayOutcome::from_response(&tagged);
+        assert!(!allow.matches(&tagged));
+        assert_eq!(tagged.summary(), "allow (+1 header ops)");
+
+        let timeout = ReplayOutcome::Error {
+            message: "timeout".to_string(),
+        };
+        let refused = ReplayOutcome::Error {
+            message: "connection refused".to_string(),
+        };
+        assert!(timeout.matches(&refused));
+        assert!(!timeout.matches(&allow));
+    }
+}
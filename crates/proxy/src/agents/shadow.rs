@@ -0,0 +1,355 @@
+//! Shadow execution for agents with `enforce false`.
+//!
+//! A shadow agent sees the same events as an enforcing one, but its call runs
+//! in a background task after the request has moved on. The decision it would
+//! have made is logged and counted in metrics, and never applied to the
+//! request. Calls are skipped rather than queued when the agent is saturated
+//! or its circuit breaker is open, so a slow shadow agent cannot add latency
+//! to live traffic.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use pingora_timeout::timeout;
+use prometheus::{register_int_counter_vec, IntCounterVec};
+use sentinel_agent_protocol::{AgentProtocolError, AgentResponse, Decision, EventType};
+use sentinel_common::errors::SentinelError;
+use tokio::sync::Semaphore;
+use tracing::{debug, info, trace, warn};
+
+use super::manager::UnifiedAgent;
+use super::recorder::AgentRecorder;
+
+/// One event to send to a shadow agent.
+pub(super) struct ShadowCall {
+    pub agent: Arc<UnifiedAgent>,
+    pub semaphore: Option<Arc<Semaphore>>,
+    pub recorder: Option<Arc<AgentRecorder>>,
+    pub event_type: EventType,
+    pub event: serde_json::Value,
+    pub correlation_id: String,
+}
+
+/// Run a shadow agent call in the background.
+pub(super) fn spawn(call: ShadowCall) {
+    let agent_id = call.agent.id().to_string();
+    let metrics = get_shadow_agent_metrics();
+
+    // Never wait for a permit: a busy shadow agent just misses this event
+    let permit = match call.semaphore.map(Semaphore::try_acquire_owned) {
+        Some(Err(_)) => {
+            trace!(
+                agent_id = %agent_id,
+                correlation_id = %call.correlation_id,
+                "Shadow agent saturated, skipping call"
+            );
+            if let Some(metrics) = &metrics {
+                metrics.record_skipped(&agent_id, "saturated");
+            }
+            return;
+        }
+        Some(Ok(permit)) => Some(permit),
+        None => None,
+    };
+
+    if !call.agent.circuit_breaker().is_closed() {
+        trace!(
+            agent_id = %agent_id,
+            correlation_id = %call.correlation_id,
+            "Shadow agent circuit breaker open, skipping call"
+        );
+        if let Some(metrics) = &metrics {
+            metrics.record_skipped(&agent_id, "circuit_open");
+        }
+        return;
+    }
+
+    tokio::spawn(async move {
+        let _permit = permit;
+        let agent = call.agent;
+        let start = Instant::now();
+        let timeout_duration = Duration::from_millis(agent.timeout_ms());
+
+        let result = timeout(
+            timeout_duration,
+            agent.call_event(call.event_type, &call.event),
+        )
+        .await;
+        let duration = start.elapsed();
+
+        if let Some(recorder) = &call.recorder {
+            recorder.record(
+                &call.correlation_id,
+                call.event_type,
+                &call.event,
+                &result,
+                duration,
+            );
+        }
+
+        match result {
+            Ok(Ok(response)) => {
+                agent.record_success(duration).await;
+                report_decision(
+                    &agent_id,
+                    &call.correlation_id,
+                    call.event_type,
+                    &response,
+                    metrics.as_deref(),
+                );
+            }
+            Ok(Err(e)) if is_skipped(&e) => {
+                if let Some(metrics) = &metrics {
+                    metrics.record_skipped(&agent_id, "pool");
+                }
+            }
+            Ok(Err(e)) => {
+                agent.record_failure().await;
+                debug!(
+                    agent_id = %agent_id,
+                    correlation_id = %call.correlation_id,
+                    error = %e,
+                    duration_ms = duration.as_millis(),
+                    "Shadow agent call failed"
+                );
+                if let Some(metrics) = &metrics {
+                    metrics.record_decision(&agent_id, call.event_type, "error");
+                }
+            }
+            Err(_) => {
+                agent.record_timeout().await;
+                debug!(
+                    agent_id = %agent_id,
+                    correlation_id = %call.correlation_id,
+                    timeout_ms = agent.timeout_ms(),
+                    "Shadow agent call timed out"
+                );
+                if let Some(metrics) = &metrics {
+                    metrics.record_decision(&agent_id, call.event_type, "timeout");
+                }
+            }
+        }
+    });
+}
+
+/// Log and meter the decision a shadow agent would have made.
+fn report_decision(
+    agent_id: &str,
+    correlation_id: &str,
+    event_type: EventType,
+    response: &AgentResponse,
+    metrics: Option<&ShadowAgentMetrics>,
+) {
+    let decision = decision_label(&response.decision);
+    let header_ops = response.request_headers.len() + response.response_headers.len();
+
+    if let Some(metrics) = metrics {
+        metrics.record_decision(agent_id, event_type, decision);
+        metrics.record_header_ops(agent_id, header_ops);
+    }
+
+    if !matches!(response.decision, Decision::Allow) {
+        info!(
+            agent_id = %agent_id,
+            correlation_id = %correlation_id,
+            event_type = ?event_type,
+            decision = ?response.decision,
+            header_ops = header_ops,
+            rule_ids = ?response.audit.rule_ids,
+            "Shadow agent would have {} the request",
+            match response.decision {
+                Decision::Block { .. } => "blocked",
+                Decision::Redirect { .. } => "redirected",
+                _ => "challenged",
+            }
+        );
+    } else if header_ops > 0 {
+        debug!(
+            agent_id = %agent_id,
+            correlation_id = %correlation_id,
+            event_type = ?event_type,
+            header_ops = header_ops,
+            "Shadow agent would have modified headers"
+        );
+    }
+}
+
+/// Metric label for a decision.
+fn decision_label(decision: &Decision) -> &'static str {
+    match decision {
+        Decision::Allow => "allow",
+        Decision::Block { .. } => "block",
+        Decision::Redirect { .. } => "redirect",
+        Decision::Challenge { .. } => "challenge",
+    }
+}
+
+/// Metric label for an event type.
+fn event_label(event_type: EventType) -> &'static str {
+    match event_type {
+        EventType::RequestHeaders => "request_headers",
+        EventType::RequestBodyChunk => "request_body_chunk",
+        EventType::ResponseHeaders => "response_headers",
+        EventType::ResponseBodyChunk => "response_body_chunk",
+        EventType::WebSocketFrame => "websocket_frame",
+        EventType::Configure => "configure",
+        EventType::RequestComplete => "request_complete",
+        EventType::GuardrailInspect => "guardrail_inspect",
+    }
+}
+
+/// Whether a call failed only because the v2 pool skipped it (low priority
+/// or outside the sample rate).
+fn is_skipped(error: &SentinelError) -> bool {
+    match error {
+        SentinelError::Agent {
+            source: Some(source),
+            ..
+        } => matches!(
+            source.downcast_ref::<AgentProtocolError>(),
+            Some(AgentProtocolError::Skipped { .. })
+        ),
+        _ => false,
+    }
+}
+
+// ============================================================================
+// Metrics
+// ============================================================================
+
+static SHADOW_AGENT_METRICS: OnceCell<Arc<ShadowAgentMetrics>> = OnceCell::new();
+
+/// Get the global shadow agent metrics, if initialized.
+pub fn get_shadow_agent_metrics() -> Option<Arc<ShadowAgentMetrics>> {
+    SHADOW_AGENT_METRICS.get().cloned()
+}
+
+/// Initialize the global shadow agent metrics.
+/// Returns Ok if already initialized or initialization succeeds.
+pub fn init_shadow_agent_metrics() -> Result<Arc<ShadowAgentMetrics>> {
+    if let Some(metrics) = SHADOW_AGENT_METRICS.get() {
+        return Ok(metrics.clone());
+    }
+
+    let metrics = Arc::new(ShadowAgentMetrics::new()?);
+    let _ = SHADOW_AGENT_METRICS.set(metrics.clone());
+    Ok(metrics)
+}
+
+/// Metrics for agents running with `enforce false`.
+pub struct ShadowAgentMetrics {
+    /// Decisions shadow agents would have made
+    /// Labels: agent, event, decision
+    decisions: IntCounterVec,
+
+    /// Header operations shadow agents would have applied
+    /// Labels: agent
+    header_ops: IntCounterVec,
+
+    /// Shadow calls not made to protect live traffic
+    /// Labels: agent, reason
+    skipped: IntCounterVec,
+}
+
+impl ShadowAgentMetrics {
+    /// Create new shadow agent metrics and register with Prometheus.
+    pub fn new() -> Result<Self> {
+        let decisions = register_int_counter_vec!(
+            "sentinel_agent_shadow_decisions_total",
+            "Decisions non-enforcing agents would have made",
+            &["agent", "event", "decision"]
+        )
+        .context("Failed to register agent_shadow_decisions metric")?;
+
+        let header_ops = register_int_counter_vec!(
+            "sentinel_agent_shadow_header_ops_total",
+            "Header operations non-enforcing agents would have applied",
+            &["agent"]
+        )
+        .context("Failed to register agent_shadow_header_ops metric")?;
+
+        let skipped = register_int_counter_vec!(
+            "sentinel_agent_shadow_skipped_total",
+            "Calls to non-enforcing agents skipped to protect live traffic",
+            &["agent", "reason"]
+        )
+        .context("Failed to register agent_shadow_skipped metric")?;
+
+        Ok(Self {
+            decisions,
+            header_ops,
+            skipped,
+        })
+    }
+
+    /// Record a decision (or `error` / `timeout` outcome).
+    pub fn record_decision(&self, agent: &str, event_type: EventType, decision: &str) {
+        self.decisions
+            .with_label_values(&[agent, event_label(event_type), decision])
+            .inc();
+    }
+
+    /// Record header operations.
+    pub fn record_header_ops(&self, agent: &str, count: usize) {
+        if count > 0 {
+            self.header_ops
+                .with_label_values(&[agent])
+                .inc_by(count as u64);
+        }
+    }
+
+    /// Record a skipped call.
+    pub fn record_skipped(&self, agent: &str, reason: &str) {
+        self.skipped.with_label_values(&[agent, reason]).inc();
+    }
+}
+
+/// Log that an agent runs in shadow mode.
+pub(super) fn log_shadow_agent(agent_id: &str) {
+    if let Err(e) = init_shadow_agent_metrics() {
+        warn!(error = %e, "Failed to initialize shadow agent metrics");
+    }
+    info!(
+        agent_id = %agent_id,
+        "Agent decisions are not enforced (shadow mode)"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_skipped() {
+        let skipped = SentinelError::Agent {
+            agent: "waf".to_string(),
+            message: "skipped".to_string(),
+            event: "request_headers".to_string(),
+            source: Some(Box::new(AgentProtocolError::Skipped {
+                agent_id: "waf".to_string(),
+                reason: "unsampled".to_string(),
+            })),
+        };
+        assert!(is_skipped(&skipped));
+
+        let failed = SentinelError::Agent {
+            agent: "waf".to_string(),
+            message: "closed".to_string(),
+            event: "request_headers".to_string(),
+            source: Some(Box::new(AgentProtocolError::ConnectionClosed)),
+        };
+        assert!(!is_skipped(&failed));
+    }
+
+    #[test]
+    fn test_decision_labels() {
+        assert_eq!(decision_label(&Decision::Allow), "allow");
+        assert_eq!(
+            decision_label(&AgentResponse::block(403, None).decision),
+            "block"
+        );
+        assert_eq!(event_label(EventType::RequestHeaders), "request_headers");
+    }
+}
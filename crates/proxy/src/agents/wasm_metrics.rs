@@ -0,0 +1,119 @@
+//! WASM agent metrics for observability.
+//!
+//! Provides Prometheus metrics for in-process WASM agents:
+//! - Fuel consumed per agent
+//! - Call latency per agent and event
+//! - Calls aborted by resource limits or traps
+//! - Component hot-swaps
+
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use prometheus::{register_histogram_vec, register_int_counter_vec, HistogramVec, IntCounterVec};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Global WASM agent metrics instance.
+static WASM_AGENT_METRICS: OnceCell<Arc<WasmAgentMetrics>> = OnceCell::new();
+
+/// Get the global WASM agent metrics, if initialized.
+pub fn get_wasm_agent_metrics() -> Option<Arc<WasmAgentMetrics>> {
+    WASM_AGENT_METRICS.get().cloned()
+}
+
+/// Initialize the global WASM agent metrics.
+/// Returns Ok if already initialized or initialization succeeds.
+pub fn init_wasm_agent_metrics() -> Result<Arc<WasmAgentMetrics>> {
+    if let Some(metrics) = WASM_AGENT_METRICS.get() {
+        return Ok(metrics.clone());
+    }
+
+    let metrics = Arc::new(WasmAgentMetrics::new()?);
+    let _ = WASM_AGENT_METRICS.set(metrics.clone());
+    Ok(metrics)
+}
+
+/// WASM agent metrics collector.
+pub struct WasmAgentMetrics {
+    /// Fuel consumed by guest code
+    /// Labels: agent
+    fuel_consumed: IntCounterVec,
+
+    /// Time spent inside the component per call
+    /// Labels: agent, event
+    call_duration: HistogramVec,
+
+    /// Calls aborted by a trap
+    /// Labels: agent, reason (fuel, timeout, memory, trap)
+    traps: IntCounterVec,
+
+    /// Component hot-swaps after a config reload
+    /// Labels: agent
+    reloads: IntCounterVec,
+}
+
+impl WasmAgentMetrics {
+    /// Create new WASM agent metrics and register with Prometheus.
+    pub fn new() -> Result<Self> {
+        // In-process calls are expected to finish in microseconds
+        let latency_buckets = vec![
+            0.00001, 0.000025, 0.00005, 0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025,
+            0.05, 0.1,
+        ];
+
+        let fuel_consumed = register_int_counter_vec!(
+            "sentinel_wasm_agent_fuel_consumed_total",
+            "Fuel consumed by WASM agent calls",
+            &["agent"]
+        )
+        .context("Failed to register wasm_agent_fuel_consumed metric")?;
+
+        let call_duration = register_histogram_vec!(
+            "sentinel_wasm_agent_call_duration_seconds",
+            "Time spent executing WASM agent calls",
+            &["agent", "event"],
+            latency_buckets
+        )
+        .context("Failed to register wasm_agent_call_duration metric")?;
+
+        let traps = register_int_counter_vec!(
+            "sentinel_wasm_agent_traps_total",
+            "WASM agent calls aborted by a trap or resource limit",
+            &["agent", "reason"]
+        )
+        .context("Failed to register wasm_agent_traps metric")?;
+
+        let reloads = register_int_counter_vec!(
+            "sentinel_wasm_agent_reloads_total",
+            "WASM agent components hot-swapped after a config reload",
+            &["agent"]
+        )
+        .context("Failed to register wasm_agent_reloads metric")?;
+
+        Ok(Self {
+            fuel_consumed,
+            call_duration,
+            traps,
+            reloads,
+        })
+    }
+
+    /// Record a completed call into the component.
+    pub fn record_call(&self, agent: &str, event: &str, duration: Duration, fuel: u64) {
+        self.call_duration
+            .with_label_values(&[agent, event])
+            .observe(duration.as_secs_f64());
+        if fuel > 0 {
+            self.fuel_consumed.with_label_values(&[agent]).inc_by(fuel);
+        }
+    }
+
+    /// Record a call aborted by a trap.
+    pub fn record_trap(&self, agent: &str, reason: &str) {
+        self.traps.with_label_values(&[agent, reason]).inc();
+    }
+
+    /// Record a component hot-swap.
+    pub fn record_reload(&self, agent: &str) {
+        self.reloads.with_label_values(&[agent]).inc();
+    }
+}
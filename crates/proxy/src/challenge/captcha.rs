@@ -0,0 +1,177 @@
+//! External CAPTCHA provider handoff.
+//!
+//! hCaptcha, Cloudflare Turnstile and reCAPTCHA share the same shape: a
+//! script renders a widget into a form, the widget adds a response token
+//! field, and the server posts that token with its secret to a `siteverify`
+//! endpoint that answers `{"success": true|false}`.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tracing::warn;
+
+use sentinel_config::{CaptchaConfig, CaptchaProvider};
+
+/// Timeout for the server-side verification call.
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Provider name as carried in signed challenge state.
+pub(crate) fn provider_name(provider: CaptchaProvider) -> &'static str {
+    match provider {
+        CaptchaProvider::Hcaptcha => "hcaptcha",
+        CaptchaProvider::Turnstile => "turnstile",
+        CaptchaProvider::Recaptcha => "recaptcha",
+    }
+}
+
+/// Widget script URL.
+pub(crate) fn script_url(provider: CaptchaProvider) -> &'static str {
+    match provider {
+        CaptchaProvider::Hcaptcha => "https://js.hcaptcha.com/1/api.js",
+        CaptchaProvider::Turnstile => "https://challenges.cloudflare.com/turnstile/v0/api.js",
+        CaptchaProvider::Recaptcha => "https://www.google.com/recaptcha/api.js",
+    }
+}
+
+/// CSS class the widget script looks for.
+pub(crate) fn widget_class(provider: CaptchaProvider) -> &'static str {
+    match provider {
+        CaptchaProvider::Hcaptcha => "h-captcha",
+        CaptchaProvider::Turnstile => "cf-turnstile",
+        CaptchaProvider::Recaptcha => "g-recaptcha",
+    }
+}
+
+/// Form field the widget fills with the response token.
+pub(crate) fn response_field(provider: CaptchaProvider) -> &'static str {
+    match provider {
+        CaptchaProvider::Hcaptcha => "h-captcha-response",
+        CaptchaProvider::Turnstile => "cf-turnstile-response",
+        CaptchaProvider::Recaptcha => "g-recaptcha-response",
+    }
+}
+
+/// Default server-side verification endpoint.
+pub(crate) fn default_verify_url(provider: CaptchaProvider) -> &'static str {
+    match provider {
+        CaptchaProvider::Hcaptcha => "https://api.hcaptcha.com/siteverify",
+        CaptchaProvider::Turnstile => "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+        CaptchaProvider::Recaptcha => "https://www.google.com/recaptcha/api/siteverify",
+    }
+}
+
+/// Resolve the CAPTCHA widget for a challenge.
+///
+/// Agents may pick the `provider` and `site_key` through decision params;
+/// the verification secret always comes from configuration.
+pub(crate) fn resolve_widget(
+    config: Option<&CaptchaConfig>,
+    params: &HashMap<String, String>,
+) -> Option<(CaptchaProvider, String)> {
+    let provider = params
+        .get("provider")
+        .and_then(|p| CaptchaProvider::parse(p))
+        .or(config.map(|c| c.provider))?;
+    let site_key = params
+        .get("site_key")
+        .cloned()
+        .or_else(|| config.map(|c| c.site_key.clone()))?;
+    Some((provider, site_key))
+}
+
+#[derive(Debug, Deserialize)]
+struct SiteVerifyResponse {
+    success: bool,
+    #[serde(default, rename = "error-codes")]
+    error_codes: Vec<String>,
+}
+
+/// Verify a response token with the provider.
+pub(crate) async fn verify_token(
+    client: &reqwest::Client,
+    config: &CaptchaConfig,
+    provider: CaptchaProvider,
+    token: &str,
+    remote_ip: &str,
+) -> bool {
+    // A custom endpoint only applies to the configured provider
+    let url = match &config.verify_url {
+        Some(url) if provider == config.provider => url.as_str(),
+        _ => default_verify_url(provider),
+    };
+
+    let form = [
+        ("secret", config.secret.as_str()),
+        ("response", token),
+        ("remoteip", remote_ip),
+    ];
+
+    let response = match client
+        .post(url)
+        .timeout(VERIFY_TIMEOUT)
+        .form(&form)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            warn!(provider = ?provider, error = %e, "CAPTCHA verification request failed");
+            return false;
+        }
+    };
+
+    match response.json::<SiteVerifyResponse>().await {
+        Ok(result) => {
+            if !result.success {
+                warn!(
+                    provider = ?provider,
+                    error_codes = ?result.error_codes,
+                    "CAPTCHA token rejected by provider"
+                );
+            }
+            result.success
+        }
+        Err(e) => {
+            warn!(provider = ?provider, error = %e, "Invalid CAPTCHA verification response");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CaptchaConfig {
+        CaptchaConfig {
+            provider: CaptchaProvider::Hcaptcha,
+            site_key: "configured".to_string(),
+            secret: "secret".to_string(),
+            verify_url: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_widget_from_config() {
+        let config = config();
+        assert_eq!(
+            resolve_widget(Some(&config), &HashMap::new()),
+            Some((CaptchaProvider::Hcaptcha, "configured".to_string()))
+        );
+        assert_eq!(resolve_widget(None, &HashMap::new()), None);
+    }
+
+    #[test]
+    fn test_resolve_widget_params_override() {
+        let config = config();
+        let params = HashMap::from([
+            ("provider".to_string(), "turnstile".to_string()),
+            ("site_key".to_string(), "from-agent".to_string()),
+        ]);
+        assert_eq!(
+            resolve_widget(Some(&config), &params),
+            Some((CaptchaProvider::Turnstile, "from-agent".to_string()))
+        );
+    }
+}
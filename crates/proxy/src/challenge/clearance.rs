@@ -0,0 +1,208 @@
+//! HMAC signing for clearance cookies and challenge state.
+//!
+//! Everything the challenge subsystem hands to a client is stateless: the
+//! proxy signs what it issues and verifies the signature when the client
+//! comes back. Signatures always cover the client binding (IP and/or
+//! User-Agent), so a solved challenge or clearance cannot be replayed from
+//! another client.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use sentinel_config::ClearanceBinding;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signs and verifies values with the challenge key.
+pub(crate) struct Signer<'a> {
+    key: &'a [u8],
+}
+
+impl<'a> Signer<'a> {
+    pub(crate) fn new(key: &'a [u8]) -> Self {
+        Self { key }
+    }
+
+    fn mac(&self, parts: &[&str]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(self.key).expect("HMAC accepts any key length");
+        for part in parts {
+            mac.update(part.as_bytes());
+            // NUL cannot appear in header values, so it unambiguously separates parts
+            mac.update(&[0]);
+        }
+        mac
+    }
+
+    /// Sign the given parts, returning a URL-safe signature.
+    pub(crate) fn sign(&self, parts: &[&str]) -> String {
+        URL_SAFE_NO_PAD.encode(self.mac(parts).finalize().into_bytes())
+    }
+
+    /// Verify a signature produced by [`Signer::sign`] in constant time.
+    pub(crate) fn verify(&self, parts: &[&str], signature: &str) -> bool {
+        match URL_SAFE_NO_PAD.decode(signature) {
+            Ok(bytes) => self.mac(parts).verify_slice(&bytes).is_ok(),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Why a clearance cookie was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ClearanceError {
+    /// Token is not in the expected format
+    Malformed,
+    /// Token has expired
+    Expired,
+    /// Signature does not match (tampered, other client, or rotated secret)
+    InvalidSignature,
+}
+
+impl ClearanceError {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            ClearanceError::Malformed => "malformed",
+            ClearanceError::Expired => "expired",
+            ClearanceError::InvalidSignature => "invalid_signature",
+        }
+    }
+}
+
+/// Build the value a clearance is bound to.
+///
+/// `client_addr` may include a port, which is ignored.
+pub(crate) fn binding_value(
+    binding: ClearanceBinding,
+    client_addr: &str,
+    user_agent: Option<&str>,
+) -> String {
+    let ip = client_addr
+        .parse::<std::net::SocketAddr>()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|_| client_addr.to_string());
+    let user_agent = user_agent.unwrap_or("");
+
+    match binding {
+        ClearanceBinding::Ip => ip,
+        ClearanceBinding::UserAgent => user_agent.to_string(),
+        ClearanceBinding::IpAndUserAgent => format!("{}\n{}", ip, user_agent),
+    }
+}
+
+/// Issue a clearance token of the form `{expires}.{signature}`.
+pub(crate) fn issue_clearance(signer: &Signer<'_>, binding: &str, expires: u64) -> String {
+    let expires = expires.to_string();
+    let signature = signer.sign(&["clearance", &expires, binding]);
+    format!("{}.{}", expires, signature)
+}
+
+/// Check a clearance token against the current client binding.
+pub(crate) fn check_clearance(
+    signer: &Signer<'_>,
+    token: &str,
+    binding: &str,
+    now: u64,
+) -> Result<(), ClearanceError> {
+    let (expires, signature) = token.split_once('.').ok_or(ClearanceError::Malformed)?;
+    let expires_at: u64 = expires.parse().map_err(|_| ClearanceError::Malformed)?;
+
+    if !signer.verify(&["clearance", expires, binding], signature) {
+        return Err(ClearanceError::InvalidSignature);
+    }
+    if expires_at <= now {
+        return Err(ClearanceError::Expired);
+    }
+    Ok(())
+}
+
+/// Find a cookie value in a `Cookie` header.
+pub(crate) fn find_cookie<'h>(cookie_header: &'h str, name: &str) -> Option<&'h str> {
+    cookie_header.split(';').find_map(|cookie| {
+        let (key, value) = cookie.trim().split_once('=')?;
+        (key == name).then_some(value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn test_clearance_round_trip() {
+        let signer = Signer::new(KEY);
+        let token = issue_clearance(&signer, "203.0.113.7", 1_000);
+
+        assert_eq!(check_clearance(&signer, &token, "203.0.113.7", 999), Ok(()));
+        assert_eq!(
+            check_clearance(&signer, &token, "203.0.113.7", 1_000),
+            Err(ClearanceError::Expired)
+        );
+    }
+
+    #[test]
+    fn test_clearance_bound_to_client() {
+        let signer = Signer::new(KEY);
+        let token = issue_clearance(&signer, "203.0.113.7", 1_000);
+
+        assert_eq!(
+            check_clearance(&signer, &token, "198.51.100.1", 0),
+            Err(ClearanceError::InvalidSignature)
+        );
+        assert_eq!(
+            check_clearance(&Signer::new(b"another key"), &token, "203.0.113.7", 0),
+            Err(ClearanceError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_clearance_tampered() {
+        let signer = Signer::new(KEY);
+        let token = issue_clearance(&signer, "203.0.113.7", 1_000);
+        let (_, signature) = token.split_once('.').unwrap();
+
+        assert_eq!(
+            check_clearance(&signer, &format!("99999.{}", signature), "203.0.113.7", 0),
+            Err(ClearanceError::InvalidSignature)
+        );
+        assert_eq!(
+            check_clearance(&signer, "garbage", "203.0.113.7", 0),
+            Err(ClearanceError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_binding_value() {
+        assert_eq!(
+            binding_value(ClearanceBinding::Ip, "203.0.113.7:51234", Some("curl")),
+            "203.0.113.7"
+        );
+        assert_eq!(
+            binding_value(ClearanceBinding::Ip, "[2001:db8::1]:443", None),
+            "2001:db8::1"
+        );
+        assert_eq!(
+            binding_value(ClearanceBinding::UserAgent, "203.0.113.7:1", Some("curl")),
+            "curl"
+        );
+        assert_eq!(
+            binding_value(
+                ClearanceBinding::IpAndUserAgent,
+                "203.0.113.7",
+                Some("curl")
+            ),
+            "203.0.113.7\ncurl"
+        );
+    }
+
+    #[test]
+    fn test_find_cookie() {
+        let header = "session=abc; sentinel_clearance=123.sig; other=x=y";
+        assert_eq!(find_cookie(header, "sentinel_clearance"), Some("123.sig"));
+        assert_eq!(find_cookie(header, "other"), Some("x=y"));
+        assert_eq!(find_cookie(header, "missing"), None);
+    }
+}
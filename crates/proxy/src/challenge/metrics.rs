@@ -0,0 +1,110 @@
+//! Challenge metrics for observability.
+//!
+//! Provides Prometheus metrics for agent-requested challenges:
+//! - Challenges issued by type
+//! - Challenges passed and failed by type
+//! - Challenges skipped because the client already holds a clearance
+
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use prometheus::{register_int_counter, register_int_counter_vec, IntCounter, IntCounterVec};
+use std::sync::Arc;
+
+/// Global challenge metrics instance.
+static CHALLENGE_METRICS: OnceCell<Arc<ChallengeMetrics>> = OnceCell::new();
+
+/// Get the global challenge metrics, if initialized.
+pub fn get_challenge_metrics() -> Option<Arc<ChallengeMetrics>> {
+    CHALLENGE_METRICS.get().cloned()
+}
+
+/// Initialize the global challenge metrics.
+/// Returns Ok if already initialized or initialization succeeds.
+pub fn init_challenge_metrics() -> Result<Arc<ChallengeMetrics>> {
+    if let Some(metrics) = CHALLENGE_METRICS.get() {
+        return Ok(metrics.clone());
+    }
+
+    let metrics = Arc::new(ChallengeMetrics::new()?);
+    let _ = CHALLENGE_METRICS.set(metrics.clone());
+    Ok(metrics)
+}
+
+/// Challenge metrics collector.
+pub struct ChallengeMetrics {
+    /// Challenges served to clients
+    /// Labels: type (pow, cookie, captcha)
+    issued: IntCounterVec,
+
+    /// Challenges solved, resulting in a clearance cookie
+    /// Labels: type
+    passed: IntCounterVec,
+
+    /// Challenge submissions rejected
+    /// Labels: type, reason
+    failed: IntCounterVec,
+
+    /// Challenge decisions skipped because of a valid clearance
+    bypassed: IntCounter,
+}
+
+impl ChallengeMetrics {
+    /// Create new challenge metrics and register with Prometheus.
+    pub fn new() -> Result<Self> {
+        let issued = register_int_counter_vec!(
+            "sentinel_challenges_issued_total",
+            "Challenges served to clients in response to agent decisions",
+            &["type"]
+        )
+        .context("Failed to register challenges_issued metric")?;
+
+        let passed = register_int_counter_vec!(
+            "sentinel_challenges_passed_total",
+            "Challenges solved by clients",
+            &["type"]
+        )
+        .context("Failed to register challenges_passed metric")?;
+
+        let failed = register_int_counter_vec!(
+            "sentinel_challenges_failed_total",
+            "Challenge submissions rejected",
+            &["type", "reason"]
+        )
+        .context("Failed to register challenges_failed metric")?;
+
+        let bypassed = register_int_counter!(
+            "sentinel_challenge_clearance_bypass_total",
+            "Challenge decisions skipped because the client held a valid clearance"
+        )
+        .context("Failed to register challenge_clearance_bypass metric")?;
+
+        Ok(Self {
+            issued,
+            passed,
+            failed,
+            bypassed,
+        })
+    }
+
+    /// Record a challenge served to a client.
+    pub fn record_issued(&self, challenge_type: &str) {
+        self.issued.with_label_values(&[challenge_type]).inc();
+    }
+
+    /// Record a solved challenge.
+    pub fn record_passed(&self, challenge_type: &str) {
+        self.passed.with_label_values(&[challenge_type]).inc();
+    }
+
+    /// Record a rejected challenge submission.
+    pub fn record_failed(&self, challenge_type: &str, reason: &str) {
+        self.failed
+            .with_label_values(&[challenge_type, reason])
+            .inc();
+    }
+
+    /// Record a challenge skipped due to a valid clearance.
+    pub fn record_bypass(&self) {
+        self.bypassed.inc();
+    }
+}
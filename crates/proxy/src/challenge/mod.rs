@@ -0,0 +1,660 @@
+//! Challenge subsystem for agent `Challenge` decisions.
+//!
+//! When an agent answers with `Decision::Challenge`, the proxy serves one of:
+//!
+//! - **pow**: a JavaScript proof-of-work interstitial
+//! - **cookie**: a cookie round-trip through a redirect
+//! - **captcha**: an external CAPTCHA widget, verified server-side
+//!
+//! Passing a challenge earns an HMAC-signed clearance cookie bound to the
+//! client IP and/or User-Agent. While the clearance is valid, further
+//! `Challenge` decisions for that client are skipped. Challenge state is
+//! signed rather than stored, so any instance sharing the configured secret
+//! can verify a submission.
+//!
+//! Callbacks are served under `challenge.path-prefix`:
+//! `{prefix}/pow`, `{prefix}/cookie` and `{prefix}/captcha`.
+
+mod captcha;
+mod clearance;
+mod metrics;
+mod pages;
+mod pow;
+
+pub use metrics::{get_challenge_metrics, init_challenge_metrics, ChallengeMetrics};
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+use rand::RngCore;
+use tracing::{debug, warn};
+
+use sentinel_config::challenge::MAX_POW_DIFFICULTY;
+use sentinel_config::{CaptchaProvider, ChallengeConfig, ChallengeType, Config};
+
+use clearance::{binding_value, check_clearance, find_cookie, issue_clearance, Signer};
+
+/// Settings used when the config has no `challenge` block.
+static DEFAULT_CHALLENGE_CONFIG: Lazy<ChallengeConfig> = Lazy::new(ChallengeConfig::default);
+
+/// Client attributes a challenge is issued for or verified against.
+#[derive(Debug, Clone, Copy)]
+pub struct ChallengeClient<'a> {
+    /// Client address, with or without port
+    pub client_addr: &'a str,
+    /// User-Agent header
+    pub user_agent: Option<&'a str>,
+    /// Cookie header
+    pub cookie_header: Option<&'a str>,
+}
+
+/// Response to write for a challenge or challenge callback.
+#[derive(Debug, Clone)]
+pub struct ChallengeResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl ChallengeResponse {
+    fn html(body: String) -> Self {
+        Self {
+            status: 403,
+            headers: vec![
+                (
+                    "Content-Type".to_string(),
+                    "text/html; charset=utf-8".to_string(),
+                ),
+                ("Cache-Control".to_string(), "no-store".to_string()),
+            ],
+            body,
+        }
+    }
+
+    fn redirect(location: String) -> Self {
+        Self {
+            status: 302,
+            headers: vec![
+                ("Location".to_string(), location),
+                ("Cache-Control".to_string(), "no-store".to_string()),
+            ],
+            body: String::new(),
+        }
+    }
+
+    fn rejected(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            headers: vec![
+                (
+                    "Content-Type".to_string(),
+                    "text/plain; charset=utf-8".to_string(),
+                ),
+                ("Cache-Control".to_string(), "no-store".to_string()),
+            ],
+            body: message.to_string(),
+        }
+    }
+
+    fn with_cookie(mut self, cookie: String) -> Self {
+        self.headers.push(("Set-Cookie".to_string(), cookie));
+        self
+    }
+}
+
+/// Issues challenges and verifies clearances and challenge submissions.
+pub struct ChallengeManager {
+    /// Signing key used when no secret is configured
+    generated_key: [u8; 32],
+    /// Client for CAPTCHA verification calls
+    http_client: reqwest::Client,
+}
+
+impl ChallengeManager {
+    /// Create a challenge manager with a freshly generated fallback key.
+    pub fn new() -> Self {
+        let mut generated_key = [0u8; 32];
+        rand::rng().fill_bytes(&mut generated_key);
+
+        Self {
+            generated_key,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    /// Effective challenge settings for a configuration.
+    pub fn settings(config: &Config) -> &ChallengeConfig {
+        config
+            .challenge
+            .as_ref()
+            .unwrap_or(&DEFAULT_CHALLENGE_CONFIG)
+    }
+
+    /// Whether a path is a challenge callback.
+    pub fn is_callback(settings: &ChallengeConfig, path: &str) -> bool {
+        path.strip_prefix(settings.path_prefix.as_str())
+            .is_some_and(|rest| rest.starts_with('/'))
+    }
+
+    fn signer<'a>(&'a self, settings: &'a ChallengeConfig) -> Signer<'a> {
+        match &settings.secret {
+            Some(secret) => Signer::new(secret.as_bytes()),
+            None => Signer::new(&self.generated_key),
+        }
+    }
+
+    /// Whether the client holds a valid clearance cookie.
+    pub fn has_clearance(&self, settings: &ChallengeConfig, client: &ChallengeClient<'_>) -> bool {
+        let Some(token) = client
+            .cookie_header
+            .and_then(|header| find_cookie(header, &settings.cookie_name))
+        else {
+            return false;
+        };
+
+        let binding = binding_value(settings.bind, client.client_addr, client.user_agent);
+        match check_clearance(&self.signer(settings), token, &binding, unix_now()) {
+            Ok(()) => true,
+            Err(e) => {
+                debug!(reason = e.as_str(), "Ignoring invalid clearance cookie");
+                false
+            }
+        }
+    }
+
+    /// Build the challenge for an agent `Challenge` decision.
+    ///
+    /// `return_to` is where the client is sent once it passes.
+    pub fn issue(
+        &self,
+        settings: &ChallengeConfig,
+        challenge_type: &str,
+        params: &HashMap<String, String>,
+        client: &ChallengeClient<'_>,
+        return_to: &str,
+    ) -> ChallengeResponse {
+        let mut kind = ChallengeType::parse(challenge_type).unwrap_or_else(|| {
+            warn!(
+                challenge_type = %challenge_type,
+                fallback = settings.default_type.as_str(),
+                "Unknown challenge type requested by agent"
+            );
+            settings.default_type
+        });
+
+        let widget = captcha::resolve_widget(settings.captcha.as_ref(), params);
+        if kind == ChallengeType::Captcha && (widget.is_none() || settings.captcha.is_none()) {
+            warn!(
+                "CAPTCHA challenge requested but no captcha provider is configured, \
+                 using proof-of-work"
+            );
+            kind = ChallengeType::ProofOfWork;
+        }
+
+        let signer = self.signer(settings);
+        let binding = binding_value(settings.bind, client.client_addr, client.user_agent);
+        let expires = unix_now() + settings.solve_timeout_secs;
+        let nonce = nonce();
+        let return_to = sanitize_return(return_to);
+        let action = format!("{}/{}", settings.path_prefix, kind.as_str());
+
+        if let Some(metrics) = get_challenge_metrics() {
+            metrics.record_issued(kind.as_str());
+        }
+
+        match kind {
+            ChallengeType::ProofOfWork => {
+                let difficulty = params
+                    .get("difficulty")
+                    .and_then(|d| d.parse::<u8>().ok())
+                    .unwrap_or(settings.pow_difficulty)
+                    .min(MAX_POW_DIFFICULTY);
+                let seed = format!("{}.{}.{}", expires, nonce, difficulty);
+                let signature = signer.sign(&["pow", &seed, &binding]);
+
+                ChallengeResponse::html(pages::proof_of_work(
+                    &action, &seed, &signature, return_to, difficulty,
+                ))
+            }
+            ChallengeType::Cookie => {
+                let state = format!("{}.{}", expires, nonce);
+                let signature = signer.sign(&["cookie", &state, &binding]);
+                let location = format!(
+                    "{}?state={}&sig={}&return={}",
+                    action,
+                    state,
+                    signature,
+                    urlencoding::encode(return_to)
+                );
+                let probe = cookie(
+                    settings,
+                    &probe_cookie_name(settings),
+                    &signature,
+                    &settings.path_prefix,
+                    settings.solve_timeout_secs,
+                );
+
+                ChallengeResponse::redirect(location).with_cookie(probe)
+            }
+            ChallengeType::Captcha => {
+                let (provider, site_key) = widget.expect("checked above");
+                let state = format!("{}.{}.{}", expires, nonce, captcha::provider_name(provider));
+                let signature = signer.sign(&["captcha", &state, &binding]);
+
+                ChallengeResponse::html(pages::captcha(
+                    &action,
+                    &state,
+                    &signature,
+                    return_to,
+                    captcha::script_url(provider),
+                    captcha::widget_class(provider),
+                    &site_key,
+                ))
+            }
+        }
+    }
+
+    /// Verify a challenge submission and grant a clearance on success.
+    pub async fn handle_callback(
+        &self,
+        settings: &ChallengeConfig,
+        path: &str,
+        query: Option<&str>,
+        client: &ChallengeClient<'_>,
+    ) -> ChallengeResponse {
+        let kind = path
+            .strip_prefix(settings.path_prefix.as_str())
+            .and_then(|rest| rest.strip_prefix('/'))
+            .and_then(ChallengeType::parse);
+        let Some(kind) = kind else {
+            return ChallengeResponse::rejected(404, "Not Found");
+        };
+
+        let query: HashMap<String, String> =
+            url::form_urlencoded::parse(query.unwrap_or("").as_bytes())
+                .into_owned()
+                .collect();
+        let return_to = sanitize_return(query.get("return").map(String::as_str).unwrap_or("/"));
+
+        let result = match kind {
+            ChallengeType::ProofOfWork => self.verify_pow(settings, &query, client),
+            ChallengeType::Cookie => self.verify_cookie(settings, &query, client),
+            ChallengeType::Captcha => self.verify_captcha(settings, &query, client).await,
+        };
+
+        match result {
+            Ok(()) => {
+                if let Some(metrics) = get_challenge_metrics() {
+                    metrics.record_passed(kind.as_str());
+                }
+                debug!(
+                    challenge_type = kind.as_str(),
+                    "Challenge passed, issuing clearance"
+                );
+
+                let binding = binding_value(settings.bind, client.client_addr, client.user_agent);
+                let token = issue_clearance(
+                    &self.signer(settings),
+                    &binding,
+                    unix_now() + settings.clearance_ttl_secs,
+                );
+                let mut response =
+                    ChallengeResponse::redirect(return_to.to_string()).with_cookie(cookie(
+                        settings,
+                        &settings.cookie_name,
+                        &token,
+                        "/",
+                        settings.clearance_ttl_secs,
+                    ));
+                if kind == ChallengeType::Cookie {
+                    response = response.with_cookie(cookie(
+                        settings,
+                        &probe_cookie_name(settings),
+                        "",
+                        &settings.path_prefix,
+                        0,
+                    ));
+                }
+                response
+            }
+            Err(reason) => {
+                if let Some(metrics) = get_challenge_metrics() {
+                    metrics.record_failed(kind.as_str(), reason);
+                }
+                debug!(
+                    challenge_type = kind.as_str(),
+                    reason = reason,
+                    "Challenge verification failed"
+                );
+
+                // An expired challenge is retried by sending the client back for a new one
+                if reason == "expired" {
+                    ChallengeResponse::redirect(return_to.to_string())
+                } else {
+                    ChallengeResponse::rejected(403, "Challenge verification failed")
+                }
+            }
+        }
+    }
+
+    fn verify_pow(
+        &self,
+        settings: &ChallengeConfig,
+        query: &HashMap<String, String>,
+        client: &ChallengeClient<'_>,
+    ) -> Result<(), &'static str> {
+        let seed = query.get("seed").ok_or("malformed")?;
+        let fields = self.check_state(settings, "pow", seed, query, client)?;
+        let difficulty: u8 = fields
+            .get(2)
+            .and_then(|d| d.parse().ok())
+            .ok_or("malformed")?;
+        let answer = query.get("answer").ok_or("malformed")?;
+
+        if pow::verify_solution(seed, answer, difficulty) {
+            Ok(())
+        } else {
+            Err("wrong_answer")
+        }
+    }
+
+    fn verify_cookie(
+        &self,
+        settings: &ChallengeConfig,
+        query: &HashMap<String, String>,
+        client: &ChallengeClient<'_>,
+    ) -> Result<(), &'static str> {
+        let state = query.get("state").ok_or("malformed")?;
+        self.check_state(settings, "cookie", state, query, client)?;
+
+        let probe = client
+            .cookie_header
+            .and_then(|header| find_cookie(header, &probe_cookie_name(settings)));
+        match (probe, query.get("sig")) {
+            (Some(probe), Some(signature)) if probe == signature => Ok(()),
+            _ => Err("cookie_missing"),
+        }
+    }
+
+    async fn verify_captcha(
+        &self,
+        settings: &ChallengeConfig,
+        query: &HashMap<String, String>,
+        client: &ChallengeClient<'_>,
+    ) -> Result<(), &'static str> {
+        let state = query.get("state").ok_or("malformed")?;
+        let fields = self.check_state(settings, "captcha", state, query, client)?;
+        let provider = fields
+            .get(2)
+            .and_then(|p| CaptchaProvider::parse(p))
+            .ok_or("malformed")?;
+        let config = settings.captcha.as_ref().ok_or("not_configured")?;
+        let token = query
+            .get(captcha::response_field(provider))
+            .filter(|t| !t.is_empty())
+            .ok_or("missing_token")?;
+
+        let remote_ip = binding_value(
+            sentinel_config::ClearanceBinding::Ip,
+            client.client_addr,
+            None,
+        );
+        if captcha::verify_token(&self.http_client, config, provider, token, &remote_ip).await {
+            Ok(())
+        } else {
+            Err("captcha_rejected")
+        }
+    }
+
+    /// Verify the signature and expiry of challenge state, returning its fields.
+    fn check_state<'q>(
+        &self,
+        settings: &ChallengeConfig,
+        kind: &str,
+        state: &'q str,
+        query: &HashMap<String, String>,
+        client: &ChallengeClient<'_>,
+    ) -> Result<Vec<&'q str>, &'static str> {
+        let signature = query.get("sig").ok_or("malformed")?;
+        let binding = binding_value(settings.bind, client.client_addr, client.user_agent);
+        if !self
+            .signer(settings)
+            .verify(&[kind, state, &binding], signature)
+        {
+            return Err("invalid_signature");
+        }
+
+        let fields: Vec<&str> = state.split('.').collect();
+        let expires: u64 = fields[0].parse().map_err(|_| "malformed")?;
+        if expires <= unix_now() {
+            return Err("expired");
+        }
+        Ok(fields)
+    }
+}
+
+impl Default for ChallengeManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn nonce() -> String {
+    let mut bytes = [0u8; 16];
+    rand::rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn probe_cookie_name(settings: &ChallengeConfig) -> String {
+    format!("{}_probe", settings.cookie_name)
+}
+
+/// Build a Set-Cookie header value.
+fn cookie(settings: &ChallengeConfig, name: &str, value: &str, path: &str, max_age: u64) -> String {
+    let mut header = format!(
+        "{}={}; Path={}; Max-Age={}; HttpOnly",
+        name, value, path, max_age
+    );
+    if settings.cookie_secure {
+        header.push_str("; Secure");
+    }
+    header.push_str(&format!("; SameSite={}", settings.cookie_same_site));
+    header
+}
+
+/// Only allow same-origin relative return targets.
+fn sanitize_return(target: &str) -> &str {
+    let safe = target.starts_with('/')
+        && !target.starts_with("//")
+        && !target.starts_with("/\\")
+        && !target.chars().any(|c| c.is_control());
+    if safe {
+        target
+    } else {
+        "/"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> ChallengeConfig {
+        ChallengeConfig {
+            secret: Some("0123456789abcdef0123".to_string()),
+            pow_difficulty: 4,
+            ..Default::default()
+        }
+    }
+
+    fn client(cookie_header: Option<&str>) -> ChallengeClient<'_> {
+        ChallengeClient {
+            client_addr: "203.0.113.7:51234",
+            user_agent: Some("test-agent"),
+            cookie_header,
+        }
+    }
+
+    fn header<'r>(response: &'r ChallengeResponse, name: &str) -> Option<&'r str> {
+        response
+            .headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Turn a Set-Cookie header into a Cookie header pair.
+    fn cookie_pair(set_cookie: &str) -> &str {
+        set_cookie.split(';').next().unwrap()
+    }
+
+    fn input_value<'p>(page: &'p str, name: &str) -> &'p str {
+        let marker = format!(r#"name="{}" value=""#, name);
+        let start = page.find(&marker).unwrap() + marker.len();
+        let end = start + page[start..].find('"').unwrap();
+        &page[start..end]
+    }
+
+    #[tokio::test]
+    async fn test_pow_challenge_round_trip() {
+        let manager = ChallengeManager::new();
+        let settings = settings();
+
+        let challenge = manager.issue(&settings, "js", &HashMap::new(), &client(None), "/shop?a=1");
+        assert_eq!(challenge.status, 403);
+
+        let seed = input_value(&challenge.body, "seed");
+        let sig = input_value(&challenge.body, "sig");
+        let answer = (0u64..)
+            .map(|n| n.to_string())
+            .find(|a| pow::verify_solution(seed, a, settings.pow_difficulty))
+            .unwrap();
+
+        let query = format!(
+            "seed={}&sig={}&answer={}&return={}",
+            seed,
+            sig,
+            answer,
+            urlencoding::encode("/shop?a=1")
+        );
+        let response = manager
+            .handle_callback(
+                &settings,
+                "/.sentinel/challenge/pow",
+                Some(&query),
+                &client(None),
+            )
+            .await;
+        assert_eq!(response.status, 302);
+        assert_eq!(header(&response, "location"), Some("/shop?a=1"));
+
+        let cleared = cookie_pair(header(&response, "set-cookie").unwrap());
+        assert!(manager.has_clearance(&settings, &client(Some(cleared))));
+
+        // The clearance is bound to the client IP
+        let other = ChallengeClient {
+            client_addr: "198.51.100.1:1",
+            ..client(Some(cleared))
+        };
+        assert!(!manager.has_clearance(&settings, &other));
+    }
+
+    #[tokio::test]
+    async fn test_pow_wrong_answer_rejected() {
+        let manager = ChallengeManager::new();
+        let settings = ChallengeConfig {
+            pow_difficulty: 32,
+            ..settings()
+        };
+
+        let challenge = manager.issue(&settings, "pow", &HashMap::new(), &client(None), "/");
+        let query = format!(
+            "seed={}&sig={}&answer=0",
+            input_value(&challenge.body, "seed"),
+            input_value(&challenge.body, "sig")
+        );
+        let response = manager
+            .handle_callback(
+                &settings,
+                "/.sentinel/challenge/pow",
+                Some(&query),
+                &client(None),
+            )
+            .await;
+        assert_eq!(response.status, 403);
+        assert!(header(&response, "set-cookie").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cookie_challenge_round_trip() {
+        let manager = ChallengeManager::new();
+        let settings = settings();
+
+        let challenge = manager.issue(&settings, "cookie", &HashMap::new(), &client(None), "/a");
+        assert_eq!(challenge.status, 302);
+        let location = header(&challenge, "location").unwrap();
+        let (path, query) = location.split_once('?').unwrap();
+        let probe = cookie_pair(header(&challenge, "set-cookie").unwrap());
+
+        // Without the probe cookie the round-trip fails
+        let response = manager
+            .handle_callback(&settings, path, Some(query), &client(None))
+            .await;
+        assert_eq!(response.status, 403);
+
+        let response = manager
+            .handle_callback(&settings, path, Some(query), &client(Some(probe)))
+            .await;
+        assert_eq!(response.status, 302);
+        assert_eq!(header(&response, "location"), Some("/a"));
+    }
+
+    #[test]
+    fn test_captcha_without_provider_falls_back_to_pow() {
+        let manager = ChallengeManager::new();
+        let challenge = manager.issue(&settings(), "captcha", &HashMap::new(), &client(None), "/");
+        assert!(challenge.body.contains("data-difficulty"));
+    }
+
+    #[test]
+    fn test_clearance_requires_configured_cookie() {
+        let manager = ChallengeManager::new();
+        let settings = settings();
+        assert!(!manager.has_clearance(&settings, &client(None)));
+        assert!(!manager.has_clearance(&settings, &client(Some("sentinel_clearance=1.abc"))));
+    }
+
+    #[test]
+    fn test_is_callback() {
+        let settings = settings();
+        assert!(ChallengeManager::is_callback(
+            &settings,
+            "/.sentinel/challenge/pow"
+        ));
+        assert!(!ChallengeManager::is_callback(
+            &settings,
+            "/.sentinel/challenge"
+        ));
+        assert!(!ChallengeManager::is_callback(
+            &settings,
+            "/.sentinel/challenger/pow"
+        ));
+    }
+
+    #[test]
+    fn test_sanitize_return() {
+        assert_eq!(sanitize_return("/path?q=1"), "/path?q=1");
+        assert_eq!(sanitize_return("//evil.example"), "/");
+        assert_eq!(sanitize_return("/\\evil.example"), "/");
+        assert_eq!(sanitize_return("https://evil.example"), "/");
+        assert_eq!(sanitize_return("/a\r\nSet-Cookie: x"), "/");
+    }
+}
@@ -0,0 +1,130 @@
+//! HTML interstitials served for proof-of-work and CAPTCHA challenges.
+
+use html_escape::encode_double_quoted_attribute as attr;
+
+/// Compact SHA-256 over ASCII strings, returning the eight state words.
+///
+/// Implemented in plain JavaScript because `crypto.subtle` is unavailable
+/// on plain-HTTP origins.
+const SHA256_JS: &str = r#"function sha256(s){function r(v,a){return(v>>>a)|(v<<(32-a))}var m=Math.pow,w=m(2,32),i,j,wd=[],bl=s.length*8,h=sha256.h=sha256.h||[],k=sha256.k=sha256.k||[],p=k.length,c={},n;for(n=2;p<64;n++){if(!c[n]){for(i=0;i<313;i+=n)c[i]=n;h[p]=(m(n,.5)*w)|0;k[p++]=(m(n,1/3)*w)|0}}s+="\x80";while(s.length%64-56)s+="\x00";for(i=0;i<s.length;i++){j=s.charCodeAt(i);if(j>>8)return;wd[i>>2]|=j<<((3-i)%4)*8}wd[wd.length]=(bl/w)|0;wd[wd.length]=bl;for(j=0;j<wd.length;){var x=wd.slice(j,j+=16),o=h;h=h.slice(0,8);for(i=0;i<64;i++){var a=x[i-15],b=x[i-2],e=h[4],t=h[7]+(r(e,6)^r(e,11)^r(e,25))+((e&h[5])^(~e&h[6]))+k[i]+(x[i]=i<16?x[i]:(x[i-16]+(r(a,7)^r(a,18)^(a>>>3))+x[i-7]+(r(b,17)^r(b,19)^(b>>>10)))|0),u=(r(h[0],2)^r(h[0],13)^r(h[0],22))+((h[0]&h[1])^(h[0]&h[2])^(h[1]&h[2]));h=[(t+u)|0].concat(h);h[4]=(h[4]+t)|0}for(i=0;i<8;i++)h[i]=(h[i]+o[i])|0}return h.slice(0,8)}"#;
+
+/// Searches for an answer in slices so the page stays responsive, then submits.
+const SOLVER_JS: &str = r#"(function(){var f=document.getElementById("challenge"),seed=f.elements.seed.value,limit=Math.pow(2,32-parseInt(f.getAttribute("data-difficulty"),10)),n=0;function step(){for(var end=n+5000;n<end;n++){if((sha256(seed+n)[0]>>>0)<limit){f.elements.answer.value=n;f.submit();return}}setTimeout(step,0)}step()})();"#;
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:32rem;margin:15vh auto;padding:0 1rem;color:#222}";
+
+/// Render the proof-of-work interstitial.
+pub(crate) fn proof_of_work(
+    action: &str,
+    seed: &str,
+    signature: &str,
+    return_to: &str,
+    difficulty: u8,
+) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="robots" content="noindex">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Checking your browser</title>
+<style>{style}</style>
+</head>
+<body>
+<h1>Checking your browser</h1>
+<p>This only takes a moment.</p>
+<noscript><p>Please enable JavaScript to continue.</p></noscript>
+<form id="challenge" method="get" action="{action}" data-difficulty="{difficulty}">
+<input type="hidden" name="seed" value="{seed}">
+<input type="hidden" name="sig" value="{signature}">
+<input type="hidden" name="return" value="{return_to}">
+<input type="hidden" name="answer" value="">
+</form>
+<script>{sha256}{solver}</script>
+</body>
+</html>
+"#,
+        style = STYLE,
+        action = attr(action),
+        difficulty = difficulty,
+        seed = attr(seed),
+        signature = attr(signature),
+        return_to = attr(return_to),
+        sha256 = SHA256_JS,
+        solver = SOLVER_JS,
+    )
+}
+
+/// Render the CAPTCHA interstitial.
+pub(crate) fn captcha(
+    action: &str,
+    state: &str,
+    signature: &str,
+    return_to: &str,
+    script_url: &str,
+    widget_class: &str,
+    site_key: &str,
+) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="robots" content="noindex">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Verify you are human</title>
+<style>{style}</style>
+<script src="{script_url}" async defer></script>
+</head>
+<body>
+<h1>Verify you are human</h1>
+<form method="get" action="{action}">
+<input type="hidden" name="state" value="{state}">
+<input type="hidden" name="sig" value="{signature}">
+<input type="hidden" name="return" value="{return_to}">
+<div class="{widget_class}" data-sitekey="{site_key}"></div>
+<p><button type="submit">Continue</button></p>
+</form>
+</body>
+</html>
+"#,
+        style = STYLE,
+        script_url = attr(script_url),
+        action = attr(action),
+        state = attr(state),
+        signature = attr(signature),
+        return_to = attr(return_to),
+        widget_class = attr(widget_class),
+        site_key = attr(site_key),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pages_escape_values() {
+        let page = proof_of_work(
+            "/.sentinel/challenge/pow",
+            "1.ab.16",
+            "sig",
+            "/a?b=\"c\"",
+            16,
+        );
+        assert!(page.contains(r#"data-difficulty="16""#));
+        assert!(page.contains("/a?b=&quot;c&quot;"));
+
+        let page = captcha(
+            "/.sentinel/challenge/captcha",
+            "1.ab",
+            "sig",
+            "/",
+            "https://js.hcaptcha.com/1/api.js",
+            "h-captcha",
+            "key\"><script>",
+        );
+        assert!(page.contains(r#"data-sitekey="key&quot;&gt;&lt;script&gt;""#));
+    }
+}
@@ -0,0 +1,78 @@
+//! Proof-of-work puzzle verification.
+//!
+//! The client is handed a signed seed and must find a decimal `answer` such
+//! that `SHA-256(seed + answer)` starts with `difficulty` zero bits. Solving
+//! costs the client roughly `2^difficulty` hashes; verifying costs one.
+
+use sha2::{Digest, Sha256};
+
+use sentinel_config::challenge::MAX_POW_DIFFICULTY;
+
+/// Longest answer accepted (`u64::MAX` has 20 digits).
+const MAX_ANSWER_LEN: usize = 20;
+
+/// Count leading zero bits of a digest.
+pub(crate) fn leading_zero_bits(digest: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in digest {
+        if *byte == 0 {
+            bits += 8;
+        } else {
+            bits += byte.leading_zeros();
+            break;
+        }
+    }
+    bits
+}
+
+/// Check an answer against a seed at the given difficulty.
+pub(crate) fn verify_solution(seed: &str, answer: &str, difficulty: u8) -> bool {
+    if answer.is_empty()
+        || answer.len() > MAX_ANSWER_LEN
+        || !answer.bytes().all(|b| b.is_ascii_digit())
+        || difficulty > MAX_POW_DIFFICULTY
+    {
+        return false;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(seed.as_bytes());
+    hasher.update(answer.as_bytes());
+    leading_zero_bits(&hasher.finalize()) >= difficulty as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(seed: &str, difficulty: u8) -> String {
+        (0u64..)
+            .map(|n| n.to_string())
+            .find(|answer| verify_solution(seed, answer, difficulty))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_leading_zero_bits() {
+        assert_eq!(leading_zero_bits(&[0xff]), 0);
+        assert_eq!(leading_zero_bits(&[0x0f]), 4);
+        assert_eq!(leading_zero_bits(&[0x00, 0x01]), 15);
+        assert_eq!(leading_zero_bits(&[0x00, 0x00]), 16);
+    }
+
+    #[test]
+    fn test_verify_solution() {
+        let answer = solve("1700000000.abcdef.8", 8);
+        assert!(verify_solution("1700000000.abcdef.8", &answer, 8));
+        assert!(verify_solution("1700000000.abcdef.8", "0", 0));
+        assert!(!verify_solution("1700000000.abcdef.8", "", 0));
+    }
+
+    #[test]
+    fn test_verify_solution_rejects_bad_answers() {
+        assert!(!verify_solution("seed", "12a", 0));
+        assert!(!verify_solution("seed", "-1", 0));
+        assert!(!verify_solution("seed", "123456789012345678901", 0));
+        assert!(!verify_solution("seed", "1", MAX_POW_DIFFICULTY + 1));
+    }
+}
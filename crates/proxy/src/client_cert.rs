@@ -0,0 +1,370 @@
+//! Client certificate identity for mTLS connections.
+//!
+//! Extracts the identity of a verified client certificate so it can be used
+//! for route matching (`client-cert-subject`, `client-cert-san`,
+//! `client-cert-fingerprint`), forwarded to upstreams as headers, and passed
+//! to agents in `RequestMetadata`.
+//!
+//! # Forwarded Headers
+//!
+//! With `format "headers"` each selected field becomes an individual header
+//! (default prefix `X-Client-Cert-`):
+//!
+//! ```text
+//! X-Client-Cert-Subject: CN=billing, O=Example
+//! X-Client-Cert-San: URI:spiffe://example.org/ns/prod/sa/billing
+//! X-Client-Cert-Fingerprint: 5f2c...
+//! ```
+//!
+//! With `format "rfc9440"` the DER certificate is sent as an RFC 9440
+//! structured field byte sequence:
+//!
+//! ```text
+//! Client-Cert: :MIIBqDCCAU6gAwIBAgIBBzAKBggqhkjOPQQDAjA6MRswGQYDVQQKDBJM...:
+//! ```
+
+use base64::Engine;
+use pingora::protocols::tls::SslDigest;
+use sha2::{Digest, Sha256};
+use tracing::trace;
+use x509_parser::prelude::*;
+
+use sentinel_agent_protocol::ClientCertInfo;
+use sentinel_config::{ClientCertField, ClientCertForwardingConfig, ClientCertHeaderFormat};
+
+/// RFC 9440 header carrying the client certificate
+pub const CLIENT_CERT_HEADER: &str = "Client-Cert";
+
+/// Verified client certificate identity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCertIdentity {
+    /// Parsed certificate fields (shared with the agent protocol)
+    info: ClientCertInfo,
+    /// DER-encoded certificate, when available
+    der: Option<Vec<u8>>,
+}
+
+impl ClientCertIdentity {
+    /// Create an identity from already-extracted certificate fields
+    pub fn new(info: ClientCertInfo) -> Self {
+        Self { info, der: None }
+    }
+
+    /// Parse the identity from a DER-encoded leaf certificate
+    pub fn from_der(der: &[u8]) -> Result<Self, String> {
+        let (_, cert) = X509Certificate::from_der(der)
+            .map_err(|e| format!("Failed to parse client certificate: {}", e))?;
+
+        let mut sans = Vec::new();
+        if let Ok(Some(san)) = cert.subject_alternative_name() {
+            for name in &san.value.general_names {
+                match name {
+                    GeneralName::DNSName(dns) => sans.push(format!("DNS:{}", dns)),
+                    GeneralName::URI(uri) => sans.push(format!("URI:{}", uri)),
+                    GeneralName::RFC822Name(email) => sans.push(format!("email:{}", email)),
+                    GeneralName::IPAddress(ip) => {
+                        if let Some(ip) = ip_from_bytes(ip) {
+                            sans.push(format!("IP:{}", ip));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let info = ClientCertInfo {
+            subject: Some(cert.subject().to_string()),
+            issuer: Some(cert.issuer().to_string()),
+            sans,
+            serial: Some(hex::encode(cert.raw_serial())),
+            fingerprint_sha256: hex::encode(Sha256::digest(der)),
+        };
+
+        trace!(
+            subject = ?info.subject,
+            san_count = info.sans.len(),
+            "Parsed client certificate"
+        );
+
+        Ok(Self {
+            info,
+            der: Some(der.to_vec()),
+        })
+    }
+
+    /// Identity of the client of a TLS connection
+    ///
+    /// The TLS listener parses the peer certificate during the handshake and
+    /// attaches the identity to the connection's digest, so it lives exactly
+    /// as long as the connection. Returns `Ok(None)` when the client did not
+    /// present a certificate, and an error when it did but no identity is
+    /// attached, so callers never act on a partial identity.
+    pub fn from_ssl_digest(digest: &SslDigest) -> Result<Option<Self>, String> {
+        if digest.cert_digest.is_empty() {
+            return Ok(None);
+        }
+        digest
+            .extension
+            .get::<ClientCertIdentity>()
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| {
+                format!(
+                    "No identity attached to client certificate {}",
+                    hex::encode(&digest.cert_digest)
+                )
+            })
+    }
+
+    /// Certificate fields as sent to agents
+    pub fn info(&self) -> &ClientCertInfo {
+        &self.info
+    }
+
+    /// Subject distinguished name
+    pub fn subject(&self) -> Option<&str> {
+        self.info.subject.as_deref()
+    }
+
+    /// Subject alternative names, prefixed by type
+    pub fn sans(&self) -> &[String] {
+        &self.info.sans
+    }
+
+    /// SHA-256 fingerprint (lowercase hex)
+    pub fn fingerprint(&self) -> &str {
+        &self.info.fingerprint_sha256
+    }
+
+    /// Whether the subject matches a glob pattern
+    pub fn matches_subject(&self, pattern: &str) -> bool {
+        self.subject()
+            .is_some_and(|subject| glob_matches(pattern, subject))
+    }
+
+    /// Whether any SAN matches a glob pattern
+    ///
+    /// The pattern is checked against both the bare value
+    /// (`spiffe://example.org/...`) and the typed form (`URI:spiffe://...`).
+    pub fn matches_san(&self, pattern: &str) -> bool {
+        self.sans().iter().any(|san| {
+            let bare = san.split_once(':').map(|(_, v)| v).unwrap_or(san);
+            glob_matches(pattern, bare) || glob_matches(pattern, san)
+        })
+    }
+
+    /// Whether the fingerprint equals the given hex string
+    pub fn matches_fingerprint(&self, fingerprint: &str) -> bool {
+        normalize_fingerprint(fingerprint) == self.fingerprint()
+    }
+
+    /// RFC 9440 `Client-Cert` header value (`:<base64 DER>:`)
+    pub fn rfc9440_value(&self) -> Option<String> {
+        self.der.as_ref().map(|der| {
+            format!(
+                ":{}:",
+                base64::engine::general_purpose::STANDARD.encode(der)
+            )
+        })
+    }
+
+    /// Value of an individual forwarded field
+    pub fn field_value(&self, field: ClientCertField) -> Option<String> {
+        match field {
+            ClientCertField::Subject => self.info.subject.clone(),
+            ClientCertField::Issuer => self.info.issuer.clone(),
+            ClientCertField::San => {
+                (!self.info.sans.is_empty()).then(|| self.info.sans.join(", "))
+            }
+            ClientCertField::Serial => self.info.serial.clone(),
+            ClientCertField::Fingerprint => Some(self.info.fingerprint_sha256.clone()),
+        }
+    }
+
+    /// Headers to forward to the upstream for a route's forwarding config
+    pub fn forwarding_headers(&self, config: &ClientCertForwardingConfig) -> Vec<(String, String)> {
+        let mut headers = Vec::new();
+
+        if config.format != ClientCertHeaderFormat::Rfc9440 {
+            for field in forwarded_fields(config) {
+                if let Some(value) = self.field_value(*field) {
+                    headers.push((
+                        format!("{}{}", config.header_prefix, field.header_suffix()),
+                        value,
+                    ));
+                }
+            }
+        }
+
+        if config.format != ClientCertHeaderFormat::Headers {
+            if let Some(value) = self.rfc9440_value() {
+                headers.push((CLIENT_CERT_HEADER.to_string(), value));
+            }
+        }
+
+        headers
+    }
+}
+
+/// Names of all headers a forwarding config may set
+///
+/// These are stripped from the client request before forwarding so that a
+/// client cannot spoof its certificate identity.
+pub fn forwarding_header_names(config: &ClientCertForwardingConfig) -> Vec<String> {
+    let mut names: Vec<String> = ClientCertField::ALL
+        .iter()
+        .map(|field| format!("{}{}", config.header_prefix, field.header_suffix()))
+        .collect();
+    names.push(CLIENT_CERT_HEADER.to_string());
+    names
+}
+
+fn forwarded_fields(config: &ClientCertForwardingConfig) -> &[ClientCertField] {
+    if config.fields.is_empty() {
+        &ClientCertField::ALL
+    } else {
+        &config.fields
+    }
+}
+
+/// Normalize a hex fingerprint (lowercase, no separators)
+pub fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .chars()
+        .filter(|c| *c != ':' && !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Match a value against a pattern where `*` matches any sequence
+pub fn glob_matches(pattern: &str, value: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == value;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    let Some(mut rest) = value
+        .strip_prefix(first)
+        .and_then(|rest| rest.strip_suffix(last))
+    else {
+        return false;
+    };
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn ip_from_bytes(bytes: &[u8]) -> Option<std::net::IpAddr> {
+    match bytes.len() {
+        4 => <[u8; 4]>::try_from(bytes).ok().map(std::net::IpAddr::from),
+        16 => <[u8; 16]>::try_from(bytes).ok().map(std::net::IpAddr::from),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_der(name: &str) -> Vec<u8> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../tests/fixtures/tls")
+            .join(name);
+        let pem = std::fs::read(path).unwrap();
+        rustls_pemfile::certs(&mut pem.as_slice())
+            .next()
+            .unwrap()
+            .unwrap()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("spiffe://example.org/*", "spiffe://example.org/ns/prod"));
+        assert!(glob_matches("*.example.com", "api.example.com"));
+        assert!(glob_matches("CN=*,O=Partners", "CN=billing,O=Partners"));
+        assert!(glob_matches("exact", "exact"));
+        assert!(!glob_matches("exact", "exactly"));
+        assert!(!glob_matches("a*b*c", "ac"));
+        assert!(glob_matches("a*b*c", "a-b-c"));
+
+        // Prefix and suffix may not overlap, and non-ASCII values must not
+        // be sliced inside a character
+        assert!(!glob_matches("ab*ba", "aba"));
+        assert!(!glob_matches("a*é", "aé-x"));
+        assert!(!glob_matches("gpt*x", "gpté"));
+        assert!(glob_matches("gpt-*-é", "gpt-ü-é"));
+    }
+
+    #[test]
+    fn test_normalize_fingerprint() {
+        assert_eq!(normalize_fingerprint("AB:cd:EF"), "abcdef");
+    }
+
+    #[test]
+    fn test_from_der_extracts_identity() {
+        let der = fixture_der("server-api.crt");
+        let identity = ClientCertIdentity::from_der(&der).unwrap();
+
+        assert!(identity.subject().unwrap().contains("CN=api.example.com"));
+        assert!(identity.sans().contains(&"DNS:api.example.com".to_string()));
+        assert!(identity.sans().contains(&"IP:127.0.0.1".to_string()));
+        assert_eq!(identity.fingerprint(), hex::encode(Sha256::digest(&der)));
+
+        assert!(identity.matches_san("api.example.com"));
+        assert!(identity.matches_san("DNS:*.example.com"));
+        assert!(identity.matches_subject("*CN=api.example.com"));
+        assert!(identity.matches_fingerprint(&identity.fingerprint().to_uppercase()));
+        assert!(!identity.matches_san("other.example.com"));
+    }
+
+    #[test]
+    fn test_identity_from_ssl_digest() {
+        let der = fixture_der("client.crt");
+        let identity = ClientCertIdentity::from_der(&der).unwrap();
+        let fingerprint = Sha256::digest(&der).to_vec();
+
+        let none = SslDigest::new("", "", None, None, Vec::new());
+        assert_eq!(ClientCertIdentity::from_ssl_digest(&none), Ok(None));
+
+        // A certificate without an attached identity is never reduced to the
+        // digest's own fields
+        let mut digest = SslDigest::new("", "", None, None, fingerprint);
+        assert!(ClientCertIdentity::from_ssl_digest(&digest).is_err());
+
+        digest.extension.set(identity.clone());
+        assert_eq!(
+            ClientCertIdentity::from_ssl_digest(&digest),
+            Ok(Some(identity))
+        );
+    }
+
+    #[test]
+    fn test_forwarding_headers() {
+        let identity = ClientCertIdentity::from_der(&fixture_der("client.crt")).unwrap();
+
+        let config = ClientCertForwardingConfig {
+            fields: vec![ClientCertField::Subject, ClientCertField::Fingerprint],
+            ..Default::default()
+        };
+        let headers = identity.forwarding_headers(&config);
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].0, "X-Client-Cert-Subject");
+        assert!(headers[0].1.contains("CN=test-client"));
+        assert_eq!(headers[1].0, "X-Client-Cert-Fingerprint");
+
+        let config = ClientCertForwardingConfig {
+            format: ClientCertHeaderFormat::Rfc9440,
+            ..Default::default()
+        };
+        let headers = identity.forwarding_headers(&config);
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].0, CLIENT_CERT_HEADER);
+        assert!(headers[0].1.starts_with(':') && headers[0].1.ends_with(':'));
+    }
+}
@@ -0,0 +1,307 @@
+//! Inference audit records.
+//!
+//! Routes with an `audit` block write a sample of their requests to the
+//! inference audit log. Prompt and completion text is pulled out of the
+//! request and response bodies, then truncated and redacted (or hashed)
+//! before it is logged.
+//!
+//! Redaction uses the data-masking agent's built-in patterns (credit card
+//! numbers passing a Luhn check, SSNs, email addresses, phone numbers) plus
+//! any patterns configured on the route.
+
+use regex::Regex;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use sentinel_config::{AuditContentMode, InferenceAuditConfig, InferenceProvider};
+
+use super::guardrails::extract_inference_content;
+use super::streaming::StreamingTokenCounter;
+
+/// Built-in redaction patterns, as used by the data-masking agent
+const BUILTIN_PATTERNS: &[(&str, &str)] = &[
+    (
+        "credit_card",
+        r"\b(?:4[0-9]{12}(?:[0-9]{3})?|5[1-5][0-9]{14}|3[47][0-9]{13}|6(?:011|5[0-9]{2})[0-9]{12})\b",
+    ),
+    ("ssn", r"\b\d{3}-\d{2}-\d{4}\b|\b\d{9}\b"),
+    (
+        "email",
+        r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Z|a-z]{2,}\b",
+    ),
+    (
+        "phone",
+        r"(?:\+1[-. ]?)?(?:\([0-9]{3}\)|[0-9]{3})[-. ]?[0-9]{3}[-. ]?[0-9]{4}",
+    ),
+];
+
+/// Per-route inference audit settings with compiled redaction patterns.
+#[derive(Debug)]
+pub struct InferenceAuditor {
+    config: InferenceAuditConfig,
+    /// Pattern name and expression, built-ins first
+    patterns: Vec<(String, Regex)>,
+}
+
+impl InferenceAuditor {
+    /// Create an auditor for a route. Custom patterns that fail to compile are
+    /// logged and skipped; the built-in patterns still apply.
+    pub fn new(route_id: &str, config: InferenceAuditConfig) -> Self {
+        let mut patterns: Vec<(String, Regex)> = BUILTIN_PATTERNS
+            .iter()
+            .map(|(name, pattern)| {
+                let regex = Regex::new(pattern).expect("built-in pattern should compile");
+                (name.to_string(), regex)
+            })
+            .collect();
+
+        for (index, pattern) in config.redact_patterns.iter().enumerate() {
+            match Regex::new(pattern) {
+                Ok(regex) => patterns.push((format!("custom_{}", index), regex)),
+                Err(e) => error!(
+                    route_id = route_id,
+                    pattern = %pattern,
+                    error = %e,
+                    "Invalid inference audit redact pattern, skipping"
+                ),
+            }
+        }
+
+        Self { config, patterns }
+    }
+
+    /// Audit settings of the route.
+    pub fn config(&self) -> &InferenceAuditConfig {
+        &self.config
+    }
+
+    /// Decide whether a request is recorded.
+    pub fn sample(&self) -> bool {
+        if self.config.sample_rate >= 1.0 {
+            return true;
+        }
+        use rand::Rng;
+        rand::rng().random::<f64>() < self.config.sample_rate
+    }
+
+    /// Prompt text of a request body, prepared for the log.
+    pub fn prompt(&self, body: &[u8]) -> Option<String> {
+        if !self.config.include_prompt {
+            return None;
+        }
+        extract_inference_content(body).map(|text| self.render(&text))
+    }
+
+    /// Completion text of a response body (as sent to the client), prepared
+    /// for the log. Streamed bodies are parsed in the provider's event format.
+    pub fn completion(
+        &self,
+        body: &[u8],
+        provider: InferenceProvider,
+        streaming: bool,
+    ) -> Option<String> {
+        if !self.config.include_completion || body.is_empty() {
+            return None;
+        }
+        let text = if streaming {
+            // The body may have been cut off mid-character at the size limit
+            let mut counter = StreamingTokenCounter::new(provider, None);
+            counter.process_chunk(String::from_utf8_lossy(body).as_bytes());
+            Some(counter.content().to_string()).filter(|text| !text.is_empty())
+        } else {
+            extract_completion_content(body)
+        };
+        text.map(|text| self.render(&text))
+    }
+
+    /// Truncate and redact, or hash, text as configured.
+    pub fn render(&self, text: &str) -> String {
+        match self.config.content_mode {
+            AuditContentMode::Hash => format!("sha256:{}", hex::encode(Sha256::digest(text))),
+            AuditContentMode::Plain => truncate(text, self.config.max_content_chars),
+            // Redact before truncating, so a cut can't split a match
+            AuditContentMode::Redact => truncate(&self.redact(text), self.config.max_content_chars),
+        }
+    }
+
+    /// Replace pattern matches with `[REDACTED:<name>]`.
+    fn redact(&self, text: &str) -> String {
+        let mut redacted = text.to_string();
+        for (name, regex) in &self.patterns {
+            let replaced = regex.replace_all(&redacted, |caps: &regex::Captures<'_>| {
+                let matched = &caps[0];
+                if name == "credit_card" && !luhn_check(matched) {
+                    matched.to_string()
+                } else {
+                    format!("[REDACTED:{}]", name)
+                }
+            });
+            if let std::borrow::Cow::Owned(replaced) = replaced {
+                redacted = replaced;
+            }
+        }
+        redacted
+    }
+}
+
+/// Truncate to at most `max_chars` characters, marking the cut.
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Luhn algorithm check for credit card validation.
+fn luhn_check(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    if digits.len() < 13 || digits.len() > 19 {
+        return false;
+    }
+
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+
+    sum % 10 == 0
+}
+
+/// Extract the generated text from a (non-streaming) inference response body.
+///
+/// Understands the OpenAI, Anthropic, Gemini, Bedrock Converse, Ollama and TGI
+/// response formats.
+pub fn extract_completion_content(body: &[u8]) -> Option<String> {
+    let json: Value = serde_json::from_slice(body).ok()?;
+    let json = match json {
+        // TGI `/generate` may answer with a one-element array
+        Value::Array(mut items) if !items.is_empty() => items.swap_remove(0),
+        other => other,
+    };
+
+    let texts = |items: Option<&Vec<Value>>, pointer: &str| -> Option<String> {
+        let parts: Vec<&str> = items?
+            .iter()
+            .filter_map(|item| item.pointer(pointer).and_then(Value::as_str))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(""))
+    };
+
+    // OpenAI: {"choices": [{"message": {"content": "..."}}]} (or legacy "text")
+    if let Some(choices) = json.get("choices").and_then(Value::as_array) {
+        return texts(Some(choices), "/message/content").or_else(|| texts(Some(choices), "/text"));
+    }
+
+    // Anthropic: {"content": [{"type": "text", "text": "..."}]}
+    if let Some(content) = json.get("content").and_then(Value::as_array) {
+        return texts(Some(content), "/text");
+    }
+
+    // Gemini: {"candidates": [{"content": {"parts": [{"text": "..."}]}}]}
+    if let Some(candidates) = json.get("candidates").and_then(Value::as_array) {
+        let parts: Vec<String> = candidates
+            .iter()
+            .filter_map(|c| texts(c.pointer("/content/parts")?.as_array(), "/text"))
+            .collect();
+        return (!parts.is_empty()).then(|| parts.join(""));
+    }
+
+    // Bedrock Converse: {"output": {"message": {"content": [{"text": "..."}]}}}
+    if let Some(content) = json.pointer("/output/message/content") {
+        return texts(content.as_array(), "/text");
+    }
+
+    // Ollama chat, Ollama generate, TGI
+    ["/message/content", "/response", "/generated_text"]
+        .iter()
+        .find_map(|pointer| json.pointer(pointer)?.as_str().map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auditor(content_mode: AuditContentMode) -> InferenceAuditor {
+        InferenceAuditor::new(
+            "test-route",
+            InferenceAuditConfig {
+                max_content_chars: 48,
+                content_mode,
+                redact_patterns: vec![r"sk-[A-Za-z0-9]{8,}".to_string(), "(".to_string()],
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_redacts_builtin_and_custom_patterns() {
+        let auditor = auditor(AuditContentMode::Redact);
+        let body = br#"{"messages":[{"role":"user","content":"Card 4111111111111111, key sk-abcdefgh12"}]}"#;
+
+        let prompt = auditor.prompt(body).unwrap();
+        assert_eq!(
+            prompt,
+            "Card [REDACTED:credit_card], key [REDACTED:custom_0]"
+        );
+
+        // Numbers failing the Luhn check are not cards
+        assert!(luhn_check("4111111111111111"));
+        assert!(!luhn_check("4111111111111112"));
+    }
+
+    #[test]
+    fn test_truncates_and_hashes() {
+        let long = "a".repeat(100);
+        let rendered = auditor(AuditContentMode::Plain).render(&long);
+        assert_eq!(rendered, format!("{}...", "a".repeat(48)));
+
+        let hashed = auditor(AuditContentMode::Hash).render("hello");
+        assert_eq!(
+            hashed,
+            "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+
+    #[test]
+    fn test_completion_formats() {
+        let cases: &[&[u8]] = &[
+            br#"{"choices":[{"message":{"role":"assistant","content":"Hi there"}}]}"#,
+            br#"{"content":[{"type":"text","text":"Hi there"}]}"#,
+            br#"{"candidates":[{"content":{"parts":[{"text":"Hi "},{"text":"there"}]}}]}"#,
+            br#"{"output":{"message":{"content":[{"text":"Hi there"}]}}}"#,
+            br#"{"message":{"role":"assistant","content":"Hi there"},"done":true}"#,
+            br#"[{"generated_text":"Hi there"}]"#,
+        ];
+        for body in cases {
+            assert_eq!(
+                extract_completion_content(body).as_deref(),
+                Some("Hi there")
+            );
+        }
+
+        let auditor = auditor(AuditContentMode::Plain);
+        let stream = b"data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\n\
+                       data: {\"choices\":[{\"delta\":{\"content\":\" there\"}}]}\n\n\
+                       data: [DONE]\n\n";
+        assert_eq!(
+            auditor
+                .completion(stream, InferenceProvider::OpenAi, true)
+                .as_deref(),
+            Some("Hi there")
+        );
+    }
+}
@@ -2,14 +2,24 @@
 //!
 //! Unlike rate limiting (tokens per minute), budgets track cumulative usage
 //! over longer periods (hourly, daily, monthly) with optional enforcement.
+//!
+//! Usage is kept in memory, optionally snapshotted to a file, or shared
+//! across instances through a [`BudgetStore`] (see [`BudgetStorage`]).
 
 use dashmap::DashMap;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tracing::{debug, info, trace, warn};
 
 use sentinel_common::budget::{
-    BudgetAlert, BudgetCheckResult, BudgetPeriod, TenantBudgetStatus, TokenBudgetConfig,
+    BudgetAlert, BudgetCheckResult, BudgetPeriod, BudgetStorage, TenantBudgetStatus,
+    TokenBudgetConfig,
+};
+
+use super::budget_store::{
+    create_redis_budget_store, BudgetSnapshotFile, BudgetStore, BudgetStoreError, TenantSnapshot,
 };
 
 /// Per-tenant budget state tracking
@@ -40,6 +50,27 @@ impl TenantBudgetState {
         }
     }
 
+    /// Restore state saved in a snapshot.
+    fn from_snapshot(snapshot: &TenantSnapshot) -> Self {
+        let age = unix_now().saturating_sub(snapshot.period_start);
+        Self {
+            period_start: Instant::now()
+                .checked_sub(Duration::from_secs(age))
+                .unwrap_or_else(Instant::now),
+            period_start_unix: snapshot.period_start,
+            tokens_used: AtomicU64::new(snapshot.tokens_used),
+            alerts_fired: AtomicU8::new(snapshot.alerts_fired),
+        }
+    }
+
+    fn snapshot(&self) -> TenantSnapshot {
+        TenantSnapshot {
+            period_start: self.period_start_unix,
+            tokens_used: self.tokens_used(),
+            alerts_fired: self.alerts_fired.load(Ordering::Acquire),
+        }
+    }
+
     fn tokens_used(&self) -> u64 {
         self.tokens_used.load(Ordering::Acquire)
     }
@@ -75,6 +106,35 @@ impl TenantBudgetState {
     }
 }
 
+/// Period of a shared budget, aligned to multiples of its length since the
+/// Unix epoch so every instance agrees on the boundaries.
+struct SharedPeriod {
+    start: u64,
+    secs: u64,
+    now: u64,
+}
+
+impl SharedPeriod {
+    fn current(period_secs: u64) -> Self {
+        let now = unix_now();
+        let secs = period_secs.max(1);
+        Self {
+            start: now - now % secs,
+            secs,
+            now,
+        }
+    }
+
+    fn remaining_secs(&self) -> u64 {
+        (self.start + self.secs).saturating_sub(self.now)
+    }
+
+    /// Counters outlive their period by one more, for rollover.
+    fn ttl_secs(&self) -> u64 {
+        self.secs * 2
+    }
+}
+
 /// Token budget tracker for per-tenant usage tracking.
 ///
 /// Tracks cumulative token usage over configurable periods (hourly, daily, monthly)
@@ -83,6 +143,7 @@ impl TenantBudgetState {
 /// - Hard or soft enforcement
 /// - Optional burst allowance
 /// - Period rollover
+/// - Persistent (file) or shared (Redis) storage
 pub struct TokenBudgetTracker {
     /// Budget configuration
     config: TokenBudgetConfig,
@@ -90,6 +151,12 @@ pub struct TokenBudgetTracker {
     tenants: DashMap<String, TenantBudgetState>,
     /// Route ID for logging
     route_id: String,
+    /// Snapshot file (`file` storage)
+    snapshot_file: Option<BudgetSnapshotFile>,
+    /// Shared store (`redis` storage)
+    store: Option<Arc<dyn BudgetStore>>,
+    /// Track locally when the shared store fails (otherwise fail open)
+    fallback_local: bool,
 }
 
 impl TokenBudgetTracker {
@@ -103,19 +170,75 @@ impl TokenBudgetTracker {
             limit = config.limit,
             enforce = config.enforce,
             rollover = config.rollover,
+            storage = ?config.storage,
             "Created token budget tracker"
         );
 
+        let tenants = DashMap::new();
+        let mut snapshot_file = None;
+        let mut store = None;
+        let mut fallback_local = true;
+
+        match &config.storage {
+            BudgetStorage::Memory => {}
+            BudgetStorage::File {
+                path,
+                flush_interval_secs,
+            } => {
+                let file = BudgetSnapshotFile::new(path, *flush_interval_secs);
+                for (tenant, snapshot) in file.load(&route_id) {
+                    tenants.insert(tenant, TenantBudgetState::from_snapshot(&snapshot));
+                }
+                info!(
+                    route_id = %route_id,
+                    path = %file.path().display(),
+                    tenants = tenants.len(),
+                    "Loaded token budget snapshot"
+                );
+                snapshot_file = Some(file);
+            }
+            BudgetStorage::Redis {
+                url,
+                key_prefix,
+                timeout_ms,
+                fallback_local: fallback,
+            } => {
+                store = create_redis_budget_store(url, key_prefix, *timeout_ms);
+                fallback_local = *fallback;
+            }
+        }
+
         Self {
             config,
-            tenants: DashMap::new(),
+            tenants,
             route_id,
+            snapshot_file,
+            store,
+            fallback_local,
         }
     }
 
+    /// Create a tracker that keeps usage in the given shared store.
+    pub fn with_store(
+        config: TokenBudgetConfig,
+        route_id: impl Into<String>,
+        store: Arc<dyn BudgetStore>,
+    ) -> Self {
+        let mut tracker = Self::new(config, route_id);
+        tracker.store = Some(store);
+        tracker
+    }
+
+    /// Whether usage is shared across instances.
+    pub fn is_shared(&self) -> bool {
+        self.store.is_some()
+    }
+
     /// Check if a request with the given token count is allowed.
     ///
     /// This does NOT consume tokens - call `record()` after the request completes.
+    /// Only local state is consulted; use [`check_async`](Self::check_async)
+    /// for shared storage.
     pub fn check(&self, tenant: &str, estimated_tokens: u64) -> BudgetCheckResult {
         let state = self.get_or_create_tenant(tenant);
         let period_secs = self.config.period.as_secs();
@@ -129,6 +252,57 @@ impl TokenBudgetTracker {
         }
 
         let current_used = state.tokens_used();
+        drop(state);
+        self.evaluate(
+            tenant,
+            current_used,
+            estimated_tokens,
+            period_secs.saturating_sub(elapsed.as_secs()),
+        )
+    }
+
+    /// Check a request against a shared budget (async, supports shared storage).
+    ///
+    /// Without a shared store this is the same as [`check`](Self::check).
+    pub async fn check_async(&self, tenant: &str, estimated_tokens: u64) -> BudgetCheckResult {
+        let Some(store) = &self.store else {
+            return self.check(tenant, estimated_tokens);
+        };
+
+        let period = SharedPeriod::current(self.config.period.as_secs());
+        match self.shared_usage(store.as_ref(), tenant, &period).await {
+            Ok(current_used) => self.evaluate(
+                tenant,
+                current_used,
+                estimated_tokens,
+                period.remaining_secs(),
+            ),
+            Err(e) => {
+                warn!(
+                    route_id = %self.route_id,
+                    tenant = tenant,
+                    error = %e,
+                    "Shared budget check failed"
+                );
+                if self.fallback_local {
+                    self.check(tenant, estimated_tokens)
+                } else {
+                    BudgetCheckResult::Allowed {
+                        remaining: self.config.limit,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Decide a check given the usage so far in the period.
+    fn evaluate(
+        &self,
+        tenant: &str,
+        current_used: u64,
+        estimated_tokens: u64,
+        retry_after: u64,
+    ) -> BudgetCheckResult {
         let would_use = current_used + estimated_tokens;
 
         // Check against limit
@@ -163,7 +337,6 @@ impl TokenBudgetTracker {
 
         // Budget exhausted
         if self.config.enforce {
-            let retry_after = period_secs.saturating_sub(elapsed.as_secs());
             debug!(
                 route_id = %self.route_id,
                 tenant = tenant,
@@ -224,53 +397,232 @@ impl TokenBudgetTracker {
         for (idx, &threshold) in self.config.alert_thresholds.iter().enumerate() {
             if usage_pct >= threshold && !state.has_fired_alert(idx as u8) {
                 state.mark_alert_fired(idx as u8);
+                alerts.push(self.alert(tenant, threshold, new_total, state.period_start_unix));
+            }
+        }
 
-                let alert = BudgetAlert {
-                    tenant: tenant.to_string(),
-                    threshold,
-                    tokens_used: new_total,
-                    tokens_limit: self.config.limit,
-                    period_start: state.period_start_unix,
-                };
+        drop(state);
+        self.persist_if_due();
 
-                info!(
+        alerts
+    }
+
+    /// Record usage against a shared budget (async, supports shared storage).
+    ///
+    /// The increment is atomic, so each alert threshold is crossed by exactly
+    /// one request across all instances. Without a shared store this is the
+    /// same as [`record`](Self::record).
+    pub async fn record_async(&self, tenant: &str, actual_tokens: u64) -> Vec<BudgetAlert> {
+        let Some(store) = &self.store else {
+            return self.record(tenant, actual_tokens);
+        };
+
+        let period = SharedPeriod::current(self.config.period.as_secs());
+        let result = async {
+            if self.config.rollover {
+                self.seed_rollover(store.as_ref(), tenant, &period).await?;
+            }
+            store
+                .increment(
+                    &self.shared_key(tenant, period.start),
+                    actual_tokens,
+                    period.ttl_secs(),
+                )
+                .await
+        }
+        .await;
+
+        let new_total = match result {
+            Ok(total) => total,
+            Err(e) => {
+                warn!(
                     route_id = %self.route_id,
                     tenant = tenant,
-                    threshold_pct = threshold * 100.0,
-                    tokens_used = new_total,
-                    tokens_limit = self.config.limit,
-                    "Budget alert threshold crossed"
+                    error = %e,
+                    "Shared budget update failed"
                 );
-
-                alerts.push(alert);
+                return if self.fallback_local {
+                    self.record(tenant, actual_tokens)
+                } else {
+                    Vec::new()
+                };
             }
-        }
+        };
 
-        alerts
+        trace!(
+            route_id = %self.route_id,
+            tenant = tenant,
+            tokens = actual_tokens,
+            total = new_total,
+            limit = self.config.limit,
+            "Recorded shared token usage"
+        );
+
+        let limit = self.config.limit as f64;
+        let previous_pct = new_total.saturating_sub(actual_tokens) as f64 / limit;
+        let usage_pct = new_total as f64 / limit;
+
+        self.config
+            .alert_thresholds
+            .iter()
+            .filter(|&&threshold| previous_pct < threshold && usage_pct >= threshold)
+            .map(|&threshold| self.alert(tenant, threshold, new_total, period.start))
+            .collect()
+    }
+
+    /// Build and log a budget alert.
+    fn alert(
+        &self,
+        tenant: &str,
+        threshold: f64,
+        tokens_used: u64,
+        period_start: u64,
+    ) -> BudgetAlert {
+        info!(
+            route_id = %self.route_id,
+            tenant = tenant,
+            threshold_pct = threshold * 100.0,
+            tokens_used = tokens_used,
+            tokens_limit = self.config.limit,
+            "Budget alert threshold crossed"
+        );
+
+        BudgetAlert {
+            tenant: tenant.to_string(),
+            threshold,
+            tokens_used,
+            tokens_limit: self.config.limit,
+            period_start,
+        }
     }
 
     /// Get the current budget status for a tenant.
     pub fn status(&self, tenant: &str) -> TenantBudgetStatus {
         let state = self.get_or_create_tenant(tenant);
-        let period_secs = self.config.period.as_secs();
-        let elapsed = state.elapsed();
-
         let tokens_used = state.tokens_used();
+        let period_start = state.period_start_unix;
+        drop(state);
+
+        self.status_for(tokens_used, period_start)
+    }
+
+    /// Get the current budget status for a tenant (async, supports shared storage).
+    pub async fn status_async(&self, tenant: &str) -> TenantBudgetStatus {
+        let Some(store) = &self.store else {
+            return self.status(tenant);
+        };
+
+        let period = SharedPeriod::current(self.config.period.as_secs());
+        match self.shared_usage(store.as_ref(), tenant, &period).await {
+            Ok(tokens_used) => self.status_for(tokens_used, period.start),
+            Err(e) => {
+                debug!(
+                    route_id = %self.route_id,
+                    tenant = tenant,
+                    error = %e,
+                    "Shared budget status unavailable, reporting local usage"
+                );
+                self.status(tenant)
+            }
+        }
+    }
+
+    fn status_for(&self, tokens_used: u64, period_start: u64) -> TenantBudgetStatus {
         let tokens_remaining = self.config.limit.saturating_sub(tokens_used);
         let usage_percent = (tokens_used as f64 / self.config.limit as f64) * 100.0;
-        let period_end = state.period_start_unix + period_secs;
 
         TenantBudgetStatus {
             tokens_used,
             tokens_limit: self.config.limit,
             tokens_remaining,
             usage_percent,
-            period_start: state.period_start_unix,
-            period_end,
+            period_start,
+            period_end: period_start + self.config.period.as_secs(),
             exhausted: tokens_used >= self.config.limit && self.config.enforce,
         }
     }
 
+    /// Store key for a tenant's usage in a period.
+    fn shared_key(&self, tenant: &str, period_start: u64) -> String {
+        format!("{}:{}:{}", self.route_id, tenant, period_start)
+    }
+
+    /// Usage so far in the current shared period.
+    async fn shared_usage(
+        &self,
+        store: &dyn BudgetStore,
+        tenant: &str,
+        period: &SharedPeriod,
+    ) -> Result<u64, BudgetStoreError> {
+        if self.config.rollover {
+            self.seed_rollover(store, tenant, period).await?;
+        }
+        Ok(store
+            .get(&self.shared_key(tenant, period.start))
+            .await?
+            .unwrap_or(0))
+    }
+
+    /// Start a shared period with the previous period's unused tokens, matching
+    /// local rollover. Only the first instance to get here creates the counter.
+    async fn seed_rollover(
+        &self,
+        store: &dyn BudgetStore,
+        tenant: &str,
+        period: &SharedPeriod,
+    ) -> Result<(), BudgetStoreError> {
+        let previous_key = self.shared_key(tenant, period.start.saturating_sub(period.secs));
+        if let Some(previous) = store.get(&previous_key).await? {
+            if previous < self.config.limit {
+                store
+                    .set_if_absent(
+                        &self.shared_key(tenant, period.start),
+                        self.config.limit - previous,
+                        period.ttl_secs(),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the snapshot file if one is configured and a write is due.
+    fn persist_if_due(&self) {
+        if let Some(file) = &self.snapshot_file {
+            file.mark_dirty();
+            if file.should_flush() {
+                self.flush();
+            }
+        }
+    }
+
+    /// Write the snapshot file now (no-op without `file` storage).
+    pub fn flush(&self) {
+        let Some(file) = &self.snapshot_file else {
+            return;
+        };
+
+        let tenants: HashMap<String, TenantSnapshot> = self
+            .tenants
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().snapshot()))
+            .collect();
+
+        match file.write(&self.route_id, tenants) {
+            Ok(()) => trace!(
+                route_id = %self.route_id,
+                path = %file.path().display(),
+                "Wrote token budget snapshot"
+            ),
+            Err(e) => warn!(
+                route_id = %self.route_id,
+                path = %file.path().display(),
+                error = %e,
+                "Failed to write token budget snapshot"
+            ),
+        }
+    }
+
     /// Reset the budget period for a tenant.
     pub fn reset_period(&self, tenant: &str) {
         if let Some(mut state) = self.tenants.get_mut(tenant) {
@@ -299,6 +651,9 @@ impl TokenBudgetTracker {
                 );
             }
         }
+        if let Some(file) = &self.snapshot_file {
+            file.mark_dirty();
+        }
     }
 
     /// Get the number of tracked tenants.
@@ -329,6 +684,26 @@ impl TokenBudgetTracker {
     }
 }
 
+impl Drop for TokenBudgetTracker {
+    fn drop(&mut self) {
+        // Keep usage recorded since the last write across restarts and reloads
+        if self
+            .snapshot_file
+            .as_ref()
+            .is_some_and(BudgetSnapshotFile::is_dirty)
+        {
+            self.flush();
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -345,6 +720,60 @@ mod tests {
             enforce: true,
             rollover: false,
             burst_allowance: None,
+            storage: BudgetStorage::Memory,
+        }
+    }
+
+    /// In-process stand-in for Redis with the same atomic counter semantics
+    #[derive(Default)]
+    struct LocalRedis {
+        counters: parking_lot::Mutex<HashMap<String, u64>>,
+        down: std::sync::atomic::AtomicBool,
+    }
+
+    impl LocalRedis {
+        fn check_up(&self) -> Result<(), BudgetStoreError> {
+            if self.down.load(Ordering::Relaxed) {
+                Err(BudgetStoreError::Backend("connection refused".to_string()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl BudgetStore for LocalRedis {
+        async fn get(&self, key: &str) -> Result<Option<u64>, BudgetStoreError> {
+            self.check_up()?;
+            Ok(self.counters.lock().get(key).copied())
+        }
+
+        async fn increment(
+            &self,
+            key: &str,
+            tokens: u64,
+            _ttl_secs: u64,
+        ) -> Result<u64, BudgetStoreError> {
+            self.check_up()?;
+            let mut counters = self.counters.lock();
+            let total = counters.entry(key.to_string()).or_insert(0);
+            *total += tokens;
+            Ok(*total)
+        }
+
+        async fn set_if_absent(
+            &self,
+            key: &str,
+            value: u64,
+            _ttl_secs: u64,
+        ) -> Result<bool, BudgetStoreError> {
+            self.check_up()?;
+            let mut counters = self.counters.lock();
+            if counters.contains_key(key) {
+                return Ok(false);
+            }
+            counters.insert(key.to_string(), value);
+            Ok(true)
         }
     }
 
@@ -494,4 +923,71 @@ mod tests {
         assert_eq!(tracker.status("tenant-2").tokens_used, 200);
         assert_eq!(tracker.tenant_count(), 2);
     }
+
+    #[test]
+    fn test_file_storage_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = test_config();
+        config.storage = BudgetStorage::File {
+            path: dir.path().join("budgets.json"),
+            flush_interval_secs: 3600,
+        };
+
+        let tracker = TokenBudgetTracker::new(config.clone(), "test-route");
+        tracker.record("tenant-1", 500);
+        tracker.record("tenant-2", 200);
+        // Dropping writes usage recorded since the last flush
+        drop(tracker);
+
+        let tracker = TokenBudgetTracker::new(config, "test-route");
+        assert_eq!(tracker.status("tenant-1").tokens_used, 500);
+        assert_eq!(tracker.status("tenant-2").tokens_used, 200);
+
+        // Fired alerts are restored too
+        let alerts = tracker.record("tenant-1", 10);
+        assert!(alerts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shared_budget_across_instances() {
+        let store = Arc::new(LocalRedis::default());
+        let replica_a = TokenBudgetTracker::with_store(test_config(), "test-route", store.clone());
+        let replica_b = TokenBudgetTracker::with_store(test_config(), "test-route", store.clone());
+        assert!(replica_a.is_shared());
+
+        let alerts_a = replica_a.record_async("tenant-1", 600).await;
+        let alerts_b = replica_b.record_async("tenant-1", 300).await;
+
+        // 50% crossed on replica A, 80% on replica B; each fires once
+        assert_eq!(alerts_a.len(), 1);
+        assert!((alerts_a[0].threshold - 0.50).abs() < 0.001);
+        assert_eq!(alerts_b.len(), 1);
+        assert!((alerts_b[0].threshold - 0.80).abs() < 0.001);
+        assert_eq!(alerts_b[0].tokens_used, 900);
+
+        // Both replicas see the combined usage
+        assert_eq!(replica_a.status_async("tenant-1").await.tokens_used, 900);
+        assert!(replica_a.check_async("tenant-1", 50).await.is_allowed());
+        assert!(!replica_b.check_async("tenant-1", 200).await.is_allowed());
+
+        // Local state is untouched
+        assert_eq!(replica_a.status("tenant-1").tokens_used, 0);
+    }
+
+    #[tokio::test]
+    async fn test_shared_budget_store_failure() {
+        let store = Arc::new(LocalRedis::default());
+        store.down.store(true, Ordering::Relaxed);
+
+        // Fallback: enforce per instance
+        let tracker = TokenBudgetTracker::with_store(test_config(), "test-route", store.clone());
+        tracker.record_async("tenant-1", 1000).await;
+        assert!(!tracker.check_async("tenant-1", 100).await.is_allowed());
+
+        // No fallback: fail open
+        let mut tracker = TokenBudgetTracker::with_store(test_config(), "test-route", store);
+        tracker.fallback_local = false;
+        assert!(tracker.record_async("tenant-1", 1000).await.is_empty());
+        assert!(tracker.check_async("tenant-1", 100).await.is_allowed());
+    }
 }
@@ -0,0 +1,380 @@
+//! Storage backends for token budgets.
+//!
+//! By default [`TokenBudgetTracker`](super::TokenBudgetTracker) keeps usage in
+//! process memory. Two other backends are provided:
+//!
+//! - [`BudgetSnapshotFile`]: per-tenant usage is snapshotted to a local file
+//!   and reloaded on start, so budgets survive restarts
+//! - [`BudgetStore`]: usage lives in shared counters updated with atomic
+//!   increments, so every instance enforces one budget. [`RedisBudgetStore`]
+//!   requires the `distributed-rate-limit` feature.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::warn;
+
+#[cfg(feature = "distributed-rate-limit")]
+use redis::aio::ConnectionManager;
+
+// ============================================================================
+// Shared store
+// ============================================================================
+
+/// Budget store operation failure.
+#[derive(Debug, Error)]
+pub enum BudgetStoreError {
+    #[error("Budget store operation timed out")]
+    Timeout,
+
+    #[error("Budget store error: {0}")]
+    Backend(String),
+}
+
+/// Shared counters for budgets enforced across instances.
+///
+/// Keys are scoped to one tenant and one period, so counters never need to be
+/// reset; they expire after `ttl_secs`.
+#[async_trait]
+pub trait BudgetStore: Send + Sync {
+    /// Current value of a counter, or `None` if it does not exist.
+    async fn get(&self, key: &str) -> Result<Option<u64>, BudgetStoreError>;
+
+    /// Atomically add to a counter (creating it at zero) and return the new value.
+    async fn increment(
+        &self,
+        key: &str,
+        tokens: u64,
+        ttl_secs: u64,
+    ) -> Result<u64, BudgetStoreError>;
+
+    /// Create a counter only if it does not exist. Returns `true` if it was created.
+    async fn set_if_absent(
+        &self,
+        key: &str,
+        value: u64,
+        ttl_secs: u64,
+    ) -> Result<bool, BudgetStoreError>;
+}
+
+/// Redis-backed budget store.
+///
+/// The connection is opened on first use, so trackers can be created while
+/// Redis is still unreachable.
+#[cfg(feature = "distributed-rate-limit")]
+pub struct RedisBudgetStore {
+    client: redis::Client,
+    connection: tokio::sync::OnceCell<ConnectionManager>,
+    key_prefix: String,
+    timeout: Duration,
+}
+
+#[cfg(feature = "distributed-rate-limit")]
+impl RedisBudgetStore {
+    /// Create a new Redis budget store.
+    pub fn new(url: &str, key_prefix: &str, timeout_ms: u64) -> Result<Self, redis::RedisError> {
+        Ok(Self {
+            client: redis::Client::open(url)?,
+            connection: tokio::sync::OnceCell::new(),
+            key_prefix: key_prefix.to_string(),
+            timeout: Duration::from_millis(timeout_ms),
+        })
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{}", self.key_prefix, key)
+    }
+
+    async fn connection(&self) -> redis::RedisResult<ConnectionManager> {
+        self.connection
+            .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+            .await
+            .cloned()
+    }
+
+    /// Run an operation under the configured timeout.
+    async fn with_timeout<T>(
+        &self,
+        operation: impl std::future::Future<Output = redis::RedisResult<T>>,
+    ) -> Result<T, BudgetStoreError> {
+        tokio::time::timeout(self.timeout, operation)
+            .await
+            .map_err(|_| BudgetStoreError::Timeout)?
+            .map_err(|e| BudgetStoreError::Backend(e.to_string()))
+    }
+}
+
+#[cfg(feature = "distributed-rate-limit")]
+#[async_trait]
+impl BudgetStore for RedisBudgetStore {
+    async fn get(&self, key: &str) -> Result<Option<u64>, BudgetStoreError> {
+        let key = self.key(key);
+        self.with_timeout(async {
+            let mut conn = self.connection().await?;
+            redis::cmd("GET").arg(&key).query_async(&mut conn).await
+        })
+        .await
+    }
+
+    async fn increment(
+        &self,
+        key: &str,
+        tokens: u64,
+        ttl_secs: u64,
+    ) -> Result<u64, BudgetStoreError> {
+        let key = self.key(key);
+        let (total,): (u64,) = self
+            .with_timeout(async {
+                let mut conn = self.connection().await?;
+                redis::pipe()
+                    .atomic()
+                    .incr(&key, tokens)
+                    .expire(&key, ttl_secs as i64)
+                    .ignore()
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?;
+        Ok(total)
+    }
+
+    async fn set_if_absent(
+        &self,
+        key: &str,
+        value: u64,
+        ttl_secs: u64,
+    ) -> Result<bool, BudgetStoreError> {
+        let key = self.key(key);
+        let created: Option<String> = self
+            .with_timeout(async {
+                let mut conn = self.connection().await?;
+                redis::cmd("SET")
+                    .arg(&key)
+                    .arg(value)
+                    .arg("NX")
+                    .arg("EX")
+                    .arg(ttl_secs)
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?;
+        Ok(created.is_some())
+    }
+}
+
+/// Create a Redis budget store from configuration.
+#[cfg(feature = "distributed-rate-limit")]
+pub fn create_redis_budget_store(
+    url: &str,
+    key_prefix: &str,
+    timeout_ms: u64,
+) -> Option<Arc<dyn BudgetStore>> {
+    match RedisBudgetStore::new(url, key_prefix, timeout_ms) {
+        Ok(store) => {
+            tracing::debug!(url = %url, prefix = %key_prefix, "Redis budget store created");
+            Some(Arc::new(store))
+        }
+        Err(e) => {
+            warn!(
+                error = %e,
+                url = %url,
+                "Failed to create Redis budget store, using local budget tracking"
+            );
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "distributed-rate-limit"))]
+pub fn create_redis_budget_store(
+    _url: &str,
+    _key_prefix: &str,
+    _timeout_ms: u64,
+) -> Option<Arc<dyn BudgetStore>> {
+    warn!(
+        "Redis budget storage requested but the 'distributed-rate-limit' feature is disabled. Using local budget tracking."
+    );
+    None
+}
+
+// ============================================================================
+// Snapshot file
+// ============================================================================
+
+/// Persisted usage of one tenant.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TenantSnapshot {
+    /// Period start (Unix seconds)
+    pub period_start: u64,
+    /// Tokens used in the period
+    pub tokens_used: u64,
+    /// Bitmask of alert thresholds already fired
+    pub alerts_fired: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotContents {
+    route_id: String,
+    tenants: HashMap<String, TenantSnapshot>,
+}
+
+/// Local file holding a route's budget usage.
+///
+/// Writes are rate limited to one per flush interval and go through a
+/// temporary file, so a crash never leaves a truncated snapshot behind.
+pub struct BudgetSnapshotFile {
+    path: PathBuf,
+    flush_interval: Duration,
+    last_flush: Mutex<Instant>,
+    dirty: AtomicBool,
+}
+
+impl BudgetSnapshotFile {
+    /// Create a snapshot file handle.
+    pub fn new(path: impl Into<PathBuf>, flush_interval_secs: u64) -> Self {
+        Self {
+            path: path.into(),
+            flush_interval: Duration::from_secs(flush_interval_secs),
+            last_flush: Mutex::new(Instant::now()),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Snapshot path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the usage saved for a route.
+    ///
+    /// A missing file starts empty; an unreadable one, or one written for a
+    /// different route, is logged and ignored.
+    pub fn load(&self, route_id: &str) -> HashMap<String, TenantSnapshot> {
+        let data = match std::fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return HashMap::new(),
+            Err(e) => {
+                warn!(
+                    route_id = route_id,
+                    path = %self.path.display(),
+                    error = %e,
+                    "Failed to read budget snapshot, starting empty"
+                );
+                return HashMap::new();
+            }
+        };
+
+        match serde_json::from_slice::<SnapshotContents>(&data) {
+            Ok(contents) if contents.route_id == route_id => contents.tenants,
+            Ok(contents) => {
+                warn!(
+                    route_id = route_id,
+                    snapshot_route_id = %contents.route_id,
+                    path = %self.path.display(),
+                    "Budget snapshot belongs to another route, starting empty"
+                );
+                HashMap::new()
+            }
+            Err(e) => {
+                warn!(
+                    route_id = route_id,
+                    path = %self.path.display(),
+                    error = %e,
+                    "Failed to parse budget snapshot, starting empty"
+                );
+                HashMap::new()
+            }
+        }
+    }
+
+    /// Note that usage changed since the last write.
+    pub fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Whether there are unwritten changes.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Acquire)
+    }
+
+    /// Whether a write is due. Claims the slot, so concurrent callers don't
+    /// both write.
+    pub fn should_flush(&self) -> bool {
+        if !self.is_dirty() {
+            return false;
+        }
+        let mut last_flush = self.last_flush.lock();
+        if last_flush.elapsed() < self.flush_interval {
+            return false;
+        }
+        *last_flush = Instant::now();
+        true
+    }
+
+    /// Write the usage of a route.
+    pub fn write(
+        &self,
+        route_id: &str,
+        tenants: HashMap<String, TenantSnapshot>,
+    ) -> std::io::Result<()> {
+        self.dirty.store(false, Ordering::Release);
+
+        let contents = SnapshotContents {
+            route_id: route_id.to_string(),
+            tenants,
+        };
+        let data = serde_json::to_vec(&contents)?;
+
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, data)?;
+        std::fs::rename(&tmp, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = BudgetSnapshotFile::new(dir.path().join("budgets.json"), 0);
+        assert!(file.load("route-a").is_empty());
+
+        let snapshot = TenantSnapshot {
+            period_start: 1_700_000_000,
+            tokens_used: 420,
+            alerts_fired: 0b01,
+        };
+        let tenants = HashMap::from([("tenant-1".to_string(), snapshot.clone())]);
+        file.write("route-a", tenants).unwrap();
+
+        assert_eq!(file.load("route-a").get("tenant-1"), Some(&snapshot));
+        // Snapshots of other routes are not picked up
+        assert!(file.load("route-b").is_empty());
+    }
+
+    #[test]
+    fn test_flush_interval() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = BudgetSnapshotFile::new(dir.path().join("budgets.json"), 3600);
+
+        file.mark_dirty();
+        assert!(!file.should_flush());
+
+        let file = BudgetSnapshotFile::new(dir.path().join("budgets.json"), 0);
+        assert!(!file.should_flush());
+        file.mark_dirty();
+        assert!(file.should_flush());
+    }
+}
@@ -0,0 +1,402 @@
+//! Response cache for inference routes.
+//!
+//! Responses are keyed on the normalised request, so the same prompt with the
+//! same model, tools, and sampling parameters is answered from cache whatever
+//! the JSON key order or whitespace. Streamed responses are recorded chunk by
+//! chunk and replayed as the same SSE stream.
+//!
+//! Entries are scoped to the client's tenant (or provider credential), so a
+//! response is only served to clients that could have made the request.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use http::{HeaderMap, Method, Uri};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::trace;
+
+use sentinel_config::InferenceCacheConfig;
+
+use crate::memory_cache::TypedCache;
+
+/// Request fields that don't change the model's output.
+const IGNORED_FIELDS: &[&str] = &["user", "metadata"];
+
+/// How a cached response was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheHitKind {
+    /// Identical normalised request
+    Exact,
+}
+
+impl CacheHitKind {
+    /// Label for metrics and the `X-Inference-Cache` header.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheHitKind::Exact => "exact",
+        }
+    }
+}
+
+// ============================================================================
+// Requests and responses
+// ============================================================================
+
+/// Normalised form of a cacheable request.
+#[derive(Debug, Clone)]
+pub struct InferenceCacheRequest {
+    /// Digest of the scope, method, target, and normalised body
+    key: String,
+}
+
+impl InferenceCacheRequest {
+    /// Normalise a request body. Returns `None` if it is not a JSON object.
+    ///
+    /// The method, path and query are part of the key: some providers name
+    /// the model or the streaming mode there (Gemini's
+    /// `models/{model}:streamGenerateContent`) rather than in the body.
+    pub fn new(scope: &str, method: &Method, uri: &Uri, body: &[u8]) -> Option<Self> {
+        let Value::Object(mut params) = serde_json::from_slice(body).ok()? else {
+            return None;
+        };
+        for field in IGNORED_FIELDS {
+            params.remove(*field);
+        }
+
+        // Objects serialise with sorted keys, so equal requests give equal digests
+        let target = uri
+            .path_and_query()
+            .map_or(uri.path(), |target| target.as_str());
+        let key = digest(&[
+            scope,
+            method.as_str(),
+            target,
+            &Value::Object(params).to_string(),
+        ]);
+
+        Some(Self { key })
+    }
+
+    /// Cache key.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+fn digest(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Who a cached response may be served to: the virtual key's tenant,
+/// otherwise clients presenting the same provider credential.
+pub fn cache_scope(tenant: Option<&str>, headers: &HeaderMap) -> String {
+    if let Some(tenant) = tenant {
+        return format!("tenant:{}", tenant);
+    }
+    let mut hasher = Sha256::new();
+    for name in ["authorization", "x-api-key"] {
+        if let Some(value) = headers.get(name) {
+            hasher.update(name.as_bytes());
+            hasher.update(value.as_bytes());
+        }
+    }
+    format!("credential:{}", hex::encode(hasher.finalize()))
+}
+
+/// Whether the client asked not to be served from (or stored in) the cache.
+pub fn client_bypasses_cache(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(http::header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|directive| matches!(directive.trim(), "no-cache" | "no-store"))
+}
+
+/// A cached response.
+#[derive(Debug)]
+pub struct CachedInferenceResponse {
+    /// Response status
+    pub status: u16,
+    /// Response content type
+    pub content_type: Option<String>,
+    /// Body chunks as they were sent (SSE events for streamed responses)
+    pub chunks: Vec<Bytes>,
+    /// Whether the response was streamed
+    pub streaming: bool,
+    /// Tokens the original request used
+    pub tokens: u64,
+    stored_at: Instant,
+}
+
+impl CachedInferenceResponse {
+    /// Time since the response was cached.
+    pub fn age(&self) -> Duration {
+        self.stored_at.elapsed()
+    }
+
+    /// Total body length.
+    pub fn body_len(&self) -> usize {
+        self.chunks.iter().map(Bytes::len).sum()
+    }
+}
+
+/// Copy of a response taken as it is sent, cached once it completes.
+#[derive(Debug)]
+pub struct InferenceCacheRecorder {
+    status: u16,
+    content_type: Option<String>,
+    streaming: bool,
+    chunks: Vec<Bytes>,
+    size: usize,
+    max_size: usize,
+    overflowed: bool,
+    complete: bool,
+}
+
+impl InferenceCacheRecorder {
+    /// Start recording a response.
+    pub fn new(
+        status: u16,
+        content_type: Option<String>,
+        streaming: bool,
+        max_size: usize,
+    ) -> Self {
+        Self {
+            status,
+            content_type,
+            streaming,
+            chunks: Vec::new(),
+            size: 0,
+            max_size,
+            overflowed: false,
+            complete: false,
+        }
+    }
+
+    /// Record a body chunk. Responses over the size limit are dropped.
+    pub fn record(&mut self, chunk: &Bytes) {
+        if self.overflowed || chunk.is_empty() {
+            return;
+        }
+        self.size += chunk.len();
+        if self.size > self.max_size {
+            self.overflowed = true;
+            self.chunks = Vec::new();
+            return;
+        }
+        self.chunks.push(chunk.clone());
+    }
+
+    /// Mark the response as fully sent.
+    pub fn finish(&mut self) {
+        self.complete = true;
+    }
+
+    /// The recorded response, if it was sent in full and within the size limit.
+    pub fn into_response(self, tokens: u64) -> Option<CachedInferenceResponse> {
+        (self.complete && !self.overflowed).then(|| CachedInferenceResponse {
+            status: self.status,
+            content_type: self.content_type,
+            chunks: self.chunks,
+            streaming: self.streaming,
+            tokens,
+            stored_at: Instant::now(),
+        })
+    }
+}
+
+// ============================================================================
+// Cache
+// ============================================================================
+
+/// Per-route inference response cache.
+pub struct InferenceCache {
+    route_id: String,
+    config: InferenceCacheConfig,
+    entries: TypedCache<String, Arc<CachedInferenceResponse>>,
+}
+
+impl InferenceCache {
+    /// Create a cache for a route.
+    pub fn new(route_id: &str, config: InferenceCacheConfig) -> Self {
+        let entries = TypedCache::new(config.max_entries, Duration::from_secs(config.ttl_secs));
+        Self {
+            route_id: route_id.to_string(),
+            config,
+            entries,
+        }
+    }
+
+    /// Cache configuration.
+    pub fn config(&self) -> &InferenceCacheConfig {
+        &self.config
+    }
+
+    /// Find a cached response for a request.
+    pub fn lookup(
+        &self,
+        request: &InferenceCacheRequest,
+    ) -> Option<(CacheHitKind, Arc<CachedInferenceResponse>)> {
+        let response = self.entries.get(&request.key)?;
+        Some((CacheHitKind::Exact, response))
+    }
+
+    /// Cache a response.
+    pub fn insert(&self, request: InferenceCacheRequest, response: CachedInferenceResponse) {
+        trace!(
+            route_id = %self.route_id,
+            streaming = response.streaming,
+            body_len = response.body_len(),
+            "Cached inference response"
+        );
+        self.entries.put(&request.key, Arc::new(response));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_request(scope: &str, body: &[u8]) -> Option<InferenceCacheRequest> {
+        let uri = Uri::from_static("/v1/chat/completions");
+        InferenceCacheRequest::new(scope, &Method::POST, &uri, body)
+    }
+
+    fn response(body: &str) -> CachedInferenceResponse {
+        let mut recorder =
+            InferenceCacheRecorder::new(200, Some("application/json".into()), false, 1024);
+        recorder.record(&Bytes::from(body.to_string()));
+        recorder.finish();
+        recorder.into_response(42).unwrap()
+    }
+
+    #[test]
+    fn test_request_normalisation() {
+        let a = cache_request(
+            "tenant:a",
+            br#"{"model":"gpt-4o","temperature":0,"messages":[{"role":"user","content":"hi"}]}"#,
+        )
+        .unwrap();
+        // Key order, whitespace, and the `user` field don't matter
+        let b = cache_request(
+            "tenant:a",
+            br#"{ "messages": [{"content": "hi", "role": "user"}], "user": "u1",
+                  "temperature": 0, "model": "gpt-4o" }"#,
+        )
+        .unwrap();
+        assert_eq!(a.key(), b.key());
+
+        // Sampling parameters and scope do
+        let c = cache_request(
+            "tenant:a",
+            br#"{"model":"gpt-4o","temperature":1,"messages":[{"role":"user","content":"hi"}]}"#,
+        )
+        .unwrap();
+        assert_ne!(a.key(), c.key());
+        let d = cache_request(
+            "tenant:b",
+            br#"{"model":"gpt-4o","temperature":0,"messages":[{"role":"user","content":"hi"}]}"#,
+        )
+        .unwrap();
+        assert_ne!(a.key(), d.key());
+
+        assert!(cache_request("tenant:a", b"not json").is_none());
+    }
+
+    #[test]
+    fn test_request_target_in_key() {
+        let body = br#"{"contents":[{"role":"user","parts":[{"text":"hi"}]}]}"#;
+        let key = |method: Method, uri: &'static str| {
+            InferenceCacheRequest::new("tenant:a", &method, &Uri::from_static(uri), body)
+                .unwrap()
+                .key()
+                .to_string()
+        };
+
+        let flash = key(
+            Method::POST,
+            "/v1beta/models/gemini-1.5-flash:generateContent",
+        );
+        assert_eq!(
+            flash,
+            key(
+                Method::POST,
+                "/v1beta/models/gemini-1.5-flash:generateContent"
+            )
+        );
+        // Model, streaming mode, query, and method all change the key
+        assert_ne!(
+            flash,
+            key(
+                Method::POST,
+                "/v1beta/models/gemini-1.5-pro:generateContent"
+            )
+        );
+        assert_ne!(
+            flash,
+            key(
+                Method::POST,
+                "/v1beta/models/gemini-1.5-flash:streamGenerateContent"
+            )
+        );
+        assert_ne!(
+            flash,
+            key(
+                Method::POST,
+                "/v1beta/models/gemini-1.5-flash:generateContent?alt=sse"
+            )
+        );
+        assert_ne!(
+            flash,
+            key(
+                Method::PUT,
+                "/v1beta/models/gemini-1.5-flash:generateContent"
+            )
+        );
+    }
+
+    #[test]
+    fn test_client_bypass() {
+        let mut headers = HeaderMap::new();
+        assert!(!client_bypasses_cache(&headers));
+        headers.insert("cache-control", "max-age=0, no-cache".parse().unwrap());
+        assert!(client_bypasses_cache(&headers));
+    }
+
+    #[test]
+    fn test_recorder_limits() {
+        let mut recorder = InferenceCacheRecorder::new(200, None, true, 8);
+        recorder.record(&Bytes::from_static(b"data: 1\n\n"));
+        recorder.finish();
+        assert!(recorder.into_response(0).is_none());
+
+        let mut recorder = InferenceCacheRecorder::new(200, None, true, 64);
+        recorder.record(&Bytes::from_static(b"data: 1\n\n"));
+        // Never finished, e.g. the client disconnected
+        assert!(recorder.into_response(0).is_none());
+    }
+
+    #[test]
+    fn test_exact_hit() {
+        let cache = InferenceCache::new("route", InferenceCacheConfig::default());
+        let body = br#"{"model":"gpt-4o","messages":[{"role":"user","content":"hi"}]}"#;
+
+        let request = cache_request("tenant:a", body).unwrap();
+        assert!(cache.lookup(&request).is_none());
+        cache.insert(request, response("hello"));
+
+        let request = cache_request("tenant:a", body).unwrap();
+        let (kind, cached) = cache.lookup(&request).unwrap();
+        assert_eq!(kind, CacheHitKind::Exact);
+        assert_eq!(cached.chunks, vec![Bytes::from_static(b"hello")]);
+        assert_eq!(cached.tokens, 42);
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Calculates costs based on per-model pricing for input and output tokens.
 
+use dashmap::DashMap;
 use tracing::{debug, trace};
 
 use sentinel_common::budget::{CostAttributionConfig, CostResult, ModelPricing};
@@ -9,12 +10,14 @@ use sentinel_common::budget::{CostAttributionConfig, CostResult, ModelPricing};
 /// Cost calculator for inference requests.
 ///
 /// Uses per-model pricing rules to calculate costs for inference requests
-/// based on input and output token counts.
+/// based on input and output token counts, and keeps a running spend per tenant.
 pub struct CostCalculator {
     /// Configuration
     config: CostAttributionConfig,
     /// Route ID for logging
     route_id: String,
+    /// Total cost attributed to each tenant
+    tenant_spend: DashMap<String, f64>,
 }
 
 impl CostCalculator {
@@ -32,7 +35,11 @@ impl CostCalculator {
             "Created cost calculator"
         );
 
-        Self { config, route_id }
+        Self {
+            config,
+            route_id,
+            tenant_spend: DashMap::new(),
+        }
     }
 
     /// Check if cost attribution is enabled.
@@ -94,6 +101,28 @@ impl CostCalculator {
         CostResult::new(model, input_tokens, output_tokens, input_cost, output_cost, currency)
     }
 
+    /// Calculate the cost for a tenant's request and add it to the tenant's spend.
+    pub fn calculate_for_tenant(
+        &self,
+        tenant: &str,
+        model: &str,
+        input_tokens: u64,
+        output_tokens: u64,
+    ) -> CostResult {
+        let mut result = self.calculate(model, input_tokens, output_tokens);
+        *self.tenant_spend.entry(tenant.to_string()).or_insert(0.0) += result.total_cost;
+        result.tenant = Some(tenant.to_string());
+        result
+    }
+
+    /// Total cost attributed to a tenant since startup.
+    pub fn tenant_spend(&self, tenant: &str) -> f64 {
+        self.tenant_spend
+            .get(tenant)
+            .map(|spend| *spend)
+            .unwrap_or(0.0)
+    }
+
     /// Find the pricing rule for a model.
     ///
     /// Returns the first matching rule, or None if no rules match.
@@ -219,6 +248,18 @@ mod tests {
         assert!((result.total_cost).abs() < 0.00001);
     }
 
+    #[test]
+    fn test_tenant_spend() {
+        let calc = CostCalculator::new(test_config(), "test-route");
+
+        let result = calc.calculate_for_tenant("team-a", "gpt-3.5-turbo", 1_000_000, 1_000_000);
+        assert_eq!(result.tenant.as_deref(), Some("team-a"));
+        calc.calculate_for_tenant("team-a", "gpt-3.5-turbo", 1_000_000, 0);
+
+        assert!((calc.tenant_spend("team-a") - 2.5).abs() < 0.001);
+        assert_eq!(calc.tenant_spend("team-b"), 0.0);
+    }
+
     #[test]
     fn test_find_pricing() {
         let calc = CostCalculator::new(test_config(), "test-route");
@@ -3,6 +3,7 @@
 //! Provides content inspection via external agents:
 //! - Prompt injection detection on requests
 //! - PII detection on responses
+//! - Incremental inspection of streamed responses (see [`super::stream_guard`])
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -14,7 +15,8 @@ use sentinel_agent_protocol::{
     GuardrailDetection, GuardrailInspectEvent, GuardrailInspectionType, GuardrailResponse,
 };
 use sentinel_config::{
-    GuardrailAction, GuardrailFailureMode, PiiDetectionConfig, PromptInjectionConfig,
+    GuardrailAction, GuardrailFailureMode, PiiAction, PiiDetectionConfig, PromptInjectionConfig,
+    StreamingGuardrailConfig,
 };
 use tracing::{debug, trace, warn};
 
@@ -53,6 +55,23 @@ pub enum PiiCheckResult {
     Error { message: String },
 }
 
+/// Result of inspecting one window of a streamed response
+#[derive(Debug)]
+pub enum StreamingOutputResult {
+    /// Window may be sent as is (detections, if any, are only logged)
+    Allow { detections: Vec<GuardrailDetection> },
+    /// Window text must be replaced before it is sent
+    Redact {
+        detections: Vec<GuardrailDetection>,
+        content: String,
+    },
+    /// Stream must be terminated with an error event
+    Block {
+        message: String,
+        detections: Vec<GuardrailDetection>,
+    },
+}
+
 /// Trait for calling guardrail agents.
 ///
 /// This trait allows for mocking agent calls in tests.
@@ -339,6 +358,132 @@ impl GuardrailProcessor {
             }
         }
     }
+
+    /// Check one window of a streamed response.
+    ///
+    /// # Arguments
+    /// * `config` - Streaming output guardrail configuration
+    /// * `content` - Delta text held back since the previous window
+    /// * `offset` - Characters of the response already inspected
+    /// * `is_final` - Whether this is the last window of the stream
+    /// * `model` - Model name if available
+    /// * `route_id` - Route ID for context
+    /// * `correlation_id` - Request correlation ID
+    #[allow(clippy::too_many_arguments)]
+    pub async fn check_stream_window(
+        &self,
+        config: &StreamingGuardrailConfig,
+        content: &str,
+        offset: usize,
+        is_final: bool,
+        model: Option<&str>,
+        route_id: Option<&str>,
+        correlation_id: &str,
+    ) -> StreamingOutputResult {
+        if !config.enabled {
+            return StreamingOutputResult::Allow { detections: vec![] };
+        }
+
+        trace!(
+            correlation_id = correlation_id,
+            agent = %config.agent,
+            content_len = content.len(),
+            offset = offset,
+            is_final = is_final,
+            "Checking streamed response window"
+        );
+
+        let mut metadata = HashMap::new();
+        metadata.insert("stream_offset".to_string(), offset.to_string());
+        metadata.insert("final".to_string(), is_final.to_string());
+
+        let event = GuardrailInspectEvent {
+            correlation_id: correlation_id.to_string(),
+            inspection_type: GuardrailInspectionType::ResponseStream,
+            content: content.to_string(),
+            model: model.map(String::from),
+            categories: config.categories.clone(),
+            route_id: route_id.map(String::from),
+            metadata,
+        };
+
+        let block_message = || {
+            config
+                .block_message
+                .clone()
+                .unwrap_or_else(|| "Response blocked by output guardrail".to_string())
+        };
+
+        let start = Instant::now();
+        let timeout_duration = Duration::from_millis(config.timeout_ms);
+
+        let failure = match timeout(
+            timeout_duration,
+            self.agent_caller.call_guardrail_agent(&config.agent, event),
+        )
+        .await
+        {
+            Ok(Ok(response)) => {
+                debug!(
+                    correlation_id = correlation_id,
+                    agent = %config.agent,
+                    detected = response.detected,
+                    detection_count = response.detections.len(),
+                    duration_ms = start.elapsed().as_millis(),
+                    "Streamed response window check completed"
+                );
+
+                if !response.detected {
+                    return StreamingOutputResult::Allow { detections: vec![] };
+                }
+
+                return match (config.action, response.redacted_content) {
+                    (PiiAction::Log, _) => StreamingOutputResult::Allow {
+                        detections: response.detections,
+                    },
+                    (PiiAction::Redact, Some(content)) => StreamingOutputResult::Redact {
+                        detections: response.detections,
+                        content,
+                    },
+                    // Without a redacted version the window cannot be sent
+                    (PiiAction::Redact, None) | (PiiAction::Block, _) => {
+                        StreamingOutputResult::Block {
+                            message: block_message(),
+                            detections: response.detections,
+                        }
+                    }
+                };
+            }
+            Ok(Err(e)) => {
+                warn!(
+                    correlation_id = correlation_id,
+                    agent = %config.agent,
+                    error = %e,
+                    failure_mode = ?config.failure_mode,
+                    "Streaming output guardrail agent call failed"
+                );
+                "Guardrail check unavailable"
+            }
+            Err(_) => {
+                warn!(
+                    correlation_id = correlation_id,
+                    agent = %config.agent,
+                    timeout_ms = config.timeout_ms,
+                    failure_mode = ?config.failure_mode,
+                    "Streaming output guardrail agent call timed out"
+                );
+                "Guardrail check timed out"
+            }
+        };
+
+        match config.failure_mode {
+            GuardrailFailureMode::Open => StreamingOutputResult::Allow { detections: vec![] },
+            GuardrailFailureMode::Closed => StreamingOutputResult::Block {
+                message: failure.to_string(),
+                detections: vec![],
+            },
+        }
+    }
 }
 
 /// Extract message content from an inference request body.
@@ -455,6 +600,19 @@ mod tests {
         }
     }
 
+    fn create_streaming_config(action: PiiAction) -> StreamingGuardrailConfig {
+        StreamingGuardrailConfig {
+            enabled: true,
+            agent: "output-guard".to_string(),
+            action,
+            categories: vec![],
+            window_chars: 64,
+            timeout_ms: 5000,
+            failure_mode: GuardrailFailureMode::Open,
+            block_message: None,
+        }
+    }
+
     fn create_detection(category: &str, description: &str) -> GuardrailDetection {
         GuardrailDetection {
             category: category.to_string(),
@@ -808,6 +966,82 @@ mod tests {
         }
     }
 
+    // ==================== Streaming Output Tests ====================
+
+    #[tokio::test]
+    async fn test_stream_window_redact() {
+        let mut response =
+            create_guardrail_response(true, vec![create_detection("email", "Email address")]);
+        response.redacted_content = Some("mail me at [EMAIL]".to_string());
+
+        let mock = Arc::new(MockAgentCaller::with_response(Ok(response)));
+        let processor = GuardrailProcessor::with_caller(mock.clone());
+        let config = create_streaming_config(PiiAction::Redact);
+
+        let result = processor
+            .check_stream_window(
+                &config,
+                "mail me at a@b.com",
+                0,
+                false,
+                None,
+                None,
+                "corr-123",
+            )
+            .await;
+
+        match result {
+            StreamingOutputResult::Redact {
+                content,
+                detections,
+            } => {
+                assert_eq!(content, "mail me at [EMAIL]");
+                assert_eq!(detections.len(), 1);
+            }
+            _ => panic!("Expected Redact result, got {:?}", result),
+        }
+        assert_eq!(mock.call_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stream_window_redact_without_content_blocks() {
+        let response = create_guardrail_response(true, vec![create_detection("ssn", "SSN")]);
+        let mock = Arc::new(MockAgentCaller::with_response(Ok(response)));
+        let processor = GuardrailProcessor::with_caller(mock);
+        let config = create_streaming_config(PiiAction::Redact);
+
+        let result = processor
+            .check_stream_window(&config, "123-45-6789", 0, true, None, None, "corr-123")
+            .await;
+
+        assert!(matches!(result, StreamingOutputResult::Block { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_stream_window_failure_modes() {
+        let mock = Arc::new(MockAgentCaller::with_response(
+            Err("agent down".to_string()),
+        ));
+        let processor = GuardrailProcessor::with_caller(mock);
+        let mut config = create_streaming_config(PiiAction::Block);
+
+        let result = processor
+            .check_stream_window(&config, "text", 0, false, None, None, "corr-123")
+            .await;
+        assert!(matches!(result, StreamingOutputResult::Allow { .. }));
+
+        config.failure_mode = GuardrailFailureMode::Closed;
+        let result = processor
+            .check_stream_window(&config, "text", 0, false, None, None, "corr-123")
+            .await;
+        match result {
+            StreamingOutputResult::Block { message, .. } => {
+                assert_eq!(message, "Guardrail check unavailable");
+            }
+            _ => panic!("Expected Block result, got {:?}", result),
+        }
+    }
+
     // ==================== Result Type Tests ====================
 
     #[test]
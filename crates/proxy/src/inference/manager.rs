@@ -6,16 +6,20 @@
 use dashmap::DashMap;
 use http::HeaderMap;
 use std::sync::Arc;
-use tracing::{debug, info, trace};
+use tracing::{debug, error, info, trace};
 
 use sentinel_common::budget::{BudgetAlert, BudgetCheckResult, CostResult};
 use sentinel_config::{InferenceConfig, TokenEstimation};
 
+use super::audit::InferenceAuditor;
 use super::budget::TokenBudgetTracker;
+use super::cache::InferenceCache;
 use super::cost::CostCalculator;
+use super::priority::AdmissionQueue;
 use super::providers::create_provider;
 use super::rate_limit::{TokenRateLimitResult, TokenRateLimiter};
 use super::tokens::{TokenCounter, TokenEstimate, TokenSource};
+use super::virtual_keys::VirtualKeyStore;
 
 /// Per-route inference state with rate limiter, budget, and cost tracking.
 struct RouteInferenceState {
@@ -25,6 +29,14 @@ struct RouteInferenceState {
     budget_tracker: Option<TokenBudgetTracker>,
     /// Cost calculator
     cost_calculator: Option<CostCalculator>,
+    /// Virtual API keys accepted on the route
+    virtual_keys: Option<Arc<VirtualKeyStore>>,
+    /// Response cache
+    cache: Option<Arc<InferenceCache>>,
+    /// Inference audit log sampling and redaction
+    auditor: Option<Arc<InferenceAuditor>>,
+    /// Priority classes and admission queue
+    admission: Option<Arc<AdmissionQueue>>,
     /// Token counter (for estimation and actual counting)
     token_counter: TokenCounter,
     /// Route ID for logging
@@ -99,12 +111,74 @@ impl InferenceRateLimitManager {
             CostCalculator::new(cost.clone(), route_id)
         });
 
+        // Load virtual keys if configured. A key file that fails to load leaves
+        // the store empty, so clients are rejected until the file is fixed.
+        let virtual_keys = config.virtual_keys.as_ref().map(|keys| {
+            let store = VirtualKeyStore::new(route_id, &keys.file);
+            if let Err(e) = store.reload() {
+                error!(
+                    route_id = route_id,
+                    error = %e,
+                    "Failed to load virtual API keys, rejecting all keys until the file is fixed"
+                );
+            }
+            Arc::new(store)
+        });
+
+        // Create response cache if configured
+        let cache = config.cache.as_ref().map(|cache| {
+            info!(
+                route_id = route_id,
+                ttl_secs = cache.ttl_secs,
+                max_entries = cache.max_entries,
+                streaming = cache.streaming,
+                "Registered inference response cache"
+            );
+            Arc::new(InferenceCache::new(route_id, cache.clone()))
+        });
+
+        // Create audit sampler if configured
+        let auditor = config.audit.as_ref().map(|audit| {
+            info!(
+                route_id = route_id,
+                sample_rate = audit.sample_rate,
+                content_mode = ?audit.content_mode,
+                "Registered inference audit logging"
+            );
+            Arc::new(InferenceAuditor::new(route_id, audit.clone()))
+        });
+
+        // Create admission queue if priority classes are configured
+        let admission = config.priority.as_ref().map(|priority| {
+            info!(
+                route_id = route_id,
+                classes = priority.classes.len(),
+                default_class = %priority.default_class,
+                max_in_flight_tokens = ?priority.max_in_flight_tokens,
+                max_upstream_queue_depth = ?priority.max_upstream_queue_depth,
+                max_queued = priority.max_queued,
+                "Registered inference admission queue"
+            );
+            Arc::new(AdmissionQueue::new(route_id, priority.clone()))
+        });
+
         // Only register if at least one feature is enabled
-        if rate_limiter.is_some() || budget_tracker.is_some() || cost_calculator.is_some() {
+        if rate_limiter.is_some()
+            || budget_tracker.is_some()
+            || cost_calculator.is_some()
+            || virtual_keys.is_some()
+            || cache.is_some()
+            || auditor.is_some()
+            || admission.is_some()
+        {
             let state = RouteInferenceState {
                 rate_limiter,
                 budget_tracker,
                 cost_calculator,
+                virtual_keys,
+                cache,
+                auditor,
+                admission,
                 token_counter,
                 route_id: route_id.to_string(),
             };
@@ -117,6 +191,10 @@ impl InferenceRateLimitManager {
                 has_rate_limit = config.rate_limit.is_some(),
                 has_budget = config.budget.is_some(),
                 has_cost = config.cost_attribution.is_some(),
+                has_virtual_keys = config.virtual_keys.is_some(),
+                has_cache = config.cache.is_some(),
+                has_audit = config.audit.is_some(),
+                has_priority = config.priority.is_some(),
                 "Registered inference route"
             );
         }
@@ -143,6 +221,35 @@ impl InferenceRateLimitManager {
             .unwrap_or(false)
     }
 
+    /// Virtual key store of a route, if the route authenticates clients with
+    /// virtual keys.
+    pub fn virtual_keys(&self, route_id: &str) -> Option<Arc<VirtualKeyStore>> {
+        self.routes.get(route_id)?.virtual_keys.clone()
+    }
+
+    /// All virtual key stores (for file watching).
+    pub fn virtual_key_stores(&self) -> Vec<Arc<VirtualKeyStore>> {
+        self.routes
+            .iter()
+            .filter_map(|entry| entry.virtual_keys.clone())
+            .collect()
+    }
+
+    /// Response cache of a route, if configured.
+    pub fn cache(&self, route_id: &str) -> Option<Arc<InferenceCache>> {
+        self.routes.get(route_id)?.cache.clone()
+    }
+
+    /// Get the inference audit settings of a route.
+    pub fn auditor(&self, route_id: &str) -> Option<Arc<InferenceAuditor>> {
+        self.routes.get(route_id)?.auditor.clone()
+    }
+
+    /// Admission queue of a route, if priority classes are configured.
+    pub fn admission(&self, route_id: &str) -> Option<Arc<AdmissionQueue>> {
+        self.routes.get(route_id)?.admission.clone()
+    }
+
     /// Check rate limit for a request.
     ///
     /// Returns the rate limit result and the estimated token count.
@@ -183,52 +290,59 @@ impl InferenceRateLimitManager {
     /// Check budget for a request.
     ///
     /// Returns the budget check result, or None if no budget is configured.
-    pub fn check_budget(
+    /// Shared (Redis) budgets are checked against the cluster-wide usage.
+    pub async fn check_budget(
         &self,
         route_id: &str,
         tenant: &str,
         estimated_tokens: u64,
     ) -> Option<BudgetCheckResult> {
-        let state = self.routes.get(route_id)?;
+        let state = Arc::clone(self.routes.get(route_id)?.value());
         let budget_tracker = state.budget_tracker.as_ref()?;
 
-        Some(budget_tracker.check(tenant, estimated_tokens))
+        Some(budget_tracker.check_async(tenant, estimated_tokens).await)
     }
 
     /// Record budget usage after a request completes.
     ///
     /// Returns any budget alerts that were triggered.
-    pub fn record_budget(
+    pub async fn record_budget(
         &self,
         route_id: &str,
         tenant: &str,
         actual_tokens: u64,
     ) -> Vec<BudgetAlert> {
-        if let Some(state) = self.routes.get(route_id) {
-            if let Some(ref budget_tracker) = state.budget_tracker {
-                return budget_tracker.record(tenant, actual_tokens);
-            }
+        let Some(state) = self
+            .routes
+            .get(route_id)
+            .map(|entry| Arc::clone(entry.value()))
+        else {
+            return Vec::new();
+        };
+        match state.budget_tracker {
+            Some(ref budget_tracker) => budget_tracker.record_async(tenant, actual_tokens).await,
+            None => Vec::new(),
         }
-        Vec::new()
     }
 
     /// Get budget status for a tenant.
-    pub fn budget_status(
+    pub async fn budget_status(
         &self,
         route_id: &str,
         tenant: &str,
     ) -> Option<sentinel_common::budget::TenantBudgetStatus> {
-        let state = self.routes.get(route_id)?;
+        let state = Arc::clone(self.routes.get(route_id)?.value());
         let budget_tracker = state.budget_tracker.as_ref()?;
-        Some(budget_tracker.status(tenant))
+        Some(budget_tracker.status_async(tenant).await)
     }
 
-    /// Calculate cost for a request.
+    /// Calculate cost for a request and attribute it to the tenant.
     ///
     /// Returns the cost result, or None if cost attribution is not configured.
     pub fn calculate_cost(
         &self,
         route_id: &str,
+        tenant: &str,
         model: &str,
         input_tokens: u64,
         output_tokens: u64,
@@ -240,7 +354,7 @@ impl InferenceRateLimitManager {
             return None;
         }
 
-        Some(cost_calculator.calculate(model, input_tokens, output_tokens))
+        Some(cost_calculator.calculate_for_tenant(tenant, model, input_tokens, output_tokens))
     }
 
     /// Record actual token usage from response.
@@ -279,6 +393,16 @@ impl InferenceRateLimitManager {
         Some(actual)
     }
 
+    /// Refund the tokens reserved for a request answered from cache.
+    pub fn record_cache_hit(&self, route_id: &str, key: &str, estimated_tokens: u64) {
+        let Some(state) = self.routes.get(route_id) else {
+            return;
+        };
+        if let Some(ref rate_limiter) = state.rate_limiter {
+            rate_limiter.record_actual(key, 0, estimated_tokens);
+        }
+    }
+
     /// Get the number of registered routes
     pub fn route_count(&self) -> usize {
         self.routes.len()
@@ -381,6 +505,11 @@ mod tests {
             routing: None,
             model_routing: None,
             guardrails: None,
+            virtual_keys: None,
+            cache: None,
+            audit: None,
+            priority: None,
+            max_usage_body_bytes: None,
         }
     }
 
@@ -423,6 +552,11 @@ mod tests {
             routing: None,
             model_routing: None,
             guardrails: None,
+            virtual_keys: None,
+            cache: None,
+            audit: None,
+            priority: None,
+            max_usage_body_bytes: None,
         };
         manager.register_route("no-limit-route", &config);
 
@@ -446,11 +580,17 @@ mod tests {
                 enforce: true,
                 rollover: false,
                 burst_allowance: None,
+                storage: Default::default(),
             }),
             cost_attribution: None,
             routing: None,
             model_routing: None,
             guardrails: None,
+            virtual_keys: None,
+            cache: None,
+            audit: None,
+            priority: None,
+            max_usage_body_bytes: None,
         };
         manager.register_route("budget-route", &config);
 
@@ -458,4 +598,50 @@ mod tests {
         assert!(manager.has_budget("budget-route"));
         assert!(!manager.has_cost_attribution("budget-route"));
     }
+
+    #[test]
+    fn test_virtual_keys_only_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.json");
+        std::fs::write(
+            &path,
+            serde_json::json!({
+                "keys": [{
+                    "id": "k",
+                    "key_hash": crate::inference::hash_key("sk-test"),
+                    "tenant": "team-a"
+                }]
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let manager = InferenceRateLimitManager::new();
+        let config = InferenceConfig {
+            virtual_keys: Some(sentinel_config::VirtualKeysConfig { file: path }),
+            ..Default::default()
+        };
+        manager.register_route("keys-route", &config);
+
+        assert!(manager.has_route("keys-route"));
+        let store = manager
+            .virtual_keys("keys-route")
+            .expect("store registered");
+        assert_eq!(store.len(), 1);
+        assert_eq!(manager.virtual_key_stores().len(), 1);
+    }
+
+    #[test]
+    fn test_cache_only_config() {
+        let manager = InferenceRateLimitManager::new();
+        let config = InferenceConfig {
+            cache: Some(sentinel_config::InferenceCacheConfig::default()),
+            ..Default::default()
+        };
+        manager.register_route("cache-route", &config);
+
+        assert!(manager.has_route("cache-route"));
+        assert!(manager.cache("cache-route").is_some());
+        assert!(!manager.has_budget("cache-route"));
+    }
 }
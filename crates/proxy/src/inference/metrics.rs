@@ -4,8 +4,14 @@
 //! - Token budget usage per tenant
 //! - Budget alerts and exhaustion events
 //! - Cost attribution per model and route
+//! - Response cache hits, misses, and tokens saved
+//! - Time to first token and inter-token latency of streamed responses
+
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
 use prometheus::{
     register_counter_vec, register_histogram_vec, register_int_counter_vec, register_int_gauge_vec,
     CounterVec, HistogramVec, IntCounterVec, IntGaugeVec,
@@ -14,6 +20,28 @@ use prometheus::{
 use sentinel_common::budget::{BudgetAlert, BudgetCheckResult, CostResult};
 use sentinel_common::ids::Scope;
 
+use super::cache::CacheHitKind;
+
+/// Global inference metrics instance.
+static INFERENCE_METRICS: OnceCell<Arc<InferenceMetrics>> = OnceCell::new();
+
+/// Get the global inference metrics, if initialized.
+pub fn get_inference_metrics() -> Option<Arc<InferenceMetrics>> {
+    INFERENCE_METRICS.get().cloned()
+}
+
+/// Initialize the global inference metrics.
+/// Returns Ok if already initialized or initialization succeeds.
+pub fn init_inference_metrics() -> Result<Arc<InferenceMetrics>> {
+    if let Some(metrics) = INFERENCE_METRICS.get() {
+        return Ok(metrics.clone());
+    }
+
+    let metrics = Arc::new(InferenceMetrics::new()?);
+    let _ = INFERENCE_METRICS.set(metrics.clone());
+    Ok(metrics)
+}
+
 /// Inference-specific metrics collector.
 ///
 /// Tracks token budgets, costs, and inference-specific metrics with
@@ -40,6 +68,24 @@ pub struct InferenceMetrics {
     output_tokens_total: IntCounterVec,
     /// Request cost histogram (histogram)
     cost_per_request: HistogramVec,
+
+    // Cache metrics
+    /// Cache lookups by result (counter)
+    cache_lookups: IntCounterVec,
+    /// Tokens served from cache instead of the model (counter)
+    cache_tokens_saved: IntCounterVec,
+
+    // Streaming latency metrics
+    /// Time to first token by model and upstream (histogram)
+    time_to_first_token: HistogramVec,
+    /// Mean inter-token latency per response by model and upstream (histogram)
+    inter_token_latency: HistogramVec,
+
+    // Admission queue metrics
+    /// Admission decisions by priority class and result (counter)
+    admissions: IntCounterVec,
+    /// Time spent in the admission queue by priority class (histogram)
+    admission_wait: HistogramVec,
 }
 
 impl InferenceMetrics {
@@ -50,6 +96,21 @@ impl InferenceMetrics {
             0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0,
         ];
 
+        // Time to first token buckets in seconds (from 50ms to 30s)
+        let ttft_buckets = vec![
+            0.05, 0.1, 0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0, 5.0, 10.0, 30.0,
+        ];
+
+        // Admission queue wait buckets in seconds (from 10ms to 60s)
+        let admission_wait_buckets = vec![
+            0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+        ];
+
+        // Inter-token latency buckets in seconds (from 5ms to 1s)
+        let itl_buckets = vec![
+            0.005, 0.01, 0.02, 0.03, 0.05, 0.075, 0.1, 0.15, 0.25, 0.5, 1.0,
+        ];
+
         let budget_limit = register_int_gauge_vec!(
             "sentinel_inference_budget_limit",
             "Token budget limit per tenant",
@@ -114,6 +175,51 @@ impl InferenceMetrics {
         )
         .context("Failed to register inference_cost_per_request metric")?;
 
+        let cache_lookups = register_int_counter_vec!(
+            "sentinel_inference_cache_lookups_total",
+            "Inference cache lookups by result (exact, miss)",
+            &["namespace", "service", "route", "result"]
+        )
+        .context("Failed to register inference_cache_lookups metric")?;
+
+        let cache_tokens_saved = register_int_counter_vec!(
+            "sentinel_inference_cache_tokens_saved_total",
+            "Tokens served from the inference cache instead of the model",
+            &["namespace", "service", "route"]
+        )
+        .context("Failed to register inference_cache_tokens_saved metric")?;
+
+        let time_to_first_token = register_histogram_vec!(
+            "sentinel_inference_time_to_first_token_seconds",
+            "Time from request start to the first streamed response chunk",
+            &["namespace", "service", "route", "model", "upstream"],
+            ttft_buckets
+        )
+        .context("Failed to register inference_time_to_first_token metric")?;
+
+        let inter_token_latency = register_histogram_vec!(
+            "sentinel_inference_inter_token_latency_seconds",
+            "Mean time between streamed tokens after the first, per response",
+            &["namespace", "service", "route", "model", "upstream"],
+            itl_buckets
+        )
+        .context("Failed to register inference_inter_token_latency metric")?;
+
+        let admissions = register_int_counter_vec!(
+            "sentinel_inference_admissions_total",
+            "Inference admission decisions by priority class and result (admitted, queued, shed, timed_out)",
+            &["namespace", "service", "route", "class", "result"]
+        )
+        .context("Failed to register inference_admissions metric")?;
+
+        let admission_wait = register_histogram_vec!(
+            "sentinel_inference_admission_wait_seconds",
+            "Time queued requests waited for admission",
+            &["namespace", "service", "route", "class"],
+            admission_wait_buckets
+        )
+        .context("Failed to register inference_admission_wait metric")?;
+
         Ok(Self {
             budget_limit,
             budget_used,
@@ -124,6 +230,12 @@ impl InferenceMetrics {
             input_tokens_total,
             output_tokens_total,
             cost_per_request,
+            cache_lookups,
+            cache_tokens_saved,
+            time_to_first_token,
+            inter_token_latency,
+            admissions,
+            admission_wait,
         })
     }
 
@@ -216,6 +328,76 @@ impl InferenceMetrics {
             .with_label_values(&[namespace, service, route, &cost.model])
             .observe(cost.total_cost);
     }
+
+    /// Record an inference cache lookup (`None` for a miss) and, for hits, the
+    /// tokens the cached response originally used.
+    pub fn record_cache_lookup(
+        &self,
+        route: &str,
+        hit: Option<(CacheHitKind, u64)>,
+        scope: &Scope,
+    ) {
+        let (namespace, service) = Self::scope_labels(scope);
+
+        let result = hit.map(|(kind, _)| kind.as_str()).unwrap_or("miss");
+        self.cache_lookups
+            .with_label_values(&[namespace, service, route, result])
+            .inc();
+
+        if let Some((_, tokens)) = hit {
+            self.cache_tokens_saved
+                .with_label_values(&[namespace, service, route])
+                .inc_by(tokens);
+        }
+    }
+
+    /// Record the time to first token and, when known, the inter-token
+    /// latency of a streamed response.
+    pub fn record_stream_latency(
+        &self,
+        route: &str,
+        model: &str,
+        upstream: &str,
+        ttft: Duration,
+        inter_token: Option<Duration>,
+        scope: &Scope,
+    ) {
+        let (namespace, service) = Self::scope_labels(scope);
+        let labels = [namespace, service, route, model, upstream];
+
+        self.time_to_first_token
+            .with_label_values(&labels)
+            .observe(ttft.as_secs_f64());
+
+        if let Some(inter_token) = inter_token {
+            self.inter_token_latency
+                .with_label_values(&labels)
+                .observe(inter_token.as_secs_f64());
+        }
+    }
+
+    /// Record an admission decision. `waited` is the time spent queued, or
+    /// zero for requests admitted straight away.
+    pub fn record_admission(
+        &self,
+        route: &str,
+        class: &str,
+        result: &str,
+        waited: Duration,
+        scope: &Scope,
+    ) {
+        let (namespace, service) = Self::scope_labels(scope);
+
+        self.admissions
+            .with_label_values(&[namespace, service, route, class, result])
+            .inc();
+
+        if !waited.is_zero() {
+            self.admission_wait
+                .with_label_values(&[namespace, service, route, class])
+                .observe(waited.as_secs_f64());
+        }
+    }
 }
 
 // ============================================================================
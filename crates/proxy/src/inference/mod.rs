@@ -4,8 +4,16 @@
 //! - Token-based rate limiting (tokens/minute instead of requests/second)
 //! - Token budget tracking (cumulative usage per period)
 //! - Cost attribution (per-model pricing)
-//! - Multi-provider token counting (OpenAI, Anthropic, generic)
+//! - Multi-provider token counting (OpenAI, Anthropic, Gemini, Bedrock, Ollama, vLLM, TGI, generic)
 //! - Model-aware load balancing (LeastTokensQueued strategy)
+//! - Time-to-first-token and inter-token latency tracking, and TTFT-aware routing
+//! - Request/response translation between provider APIs (OpenAI, Anthropic)
+//! - Sentinel-issued virtual API keys (per-tenant auth, limits, and budgets)
+//! - Response caching for identical requests
+//! - Streaming output guardrails (windowed inspection, redaction, termination)
+//! - Audit records of prompts and completions (sampled, redacted or hashed)
+//! - Priority classes with a bounded, weighted fair admission queue
+//! - Tool/function-call allow and deny lists (OpenAI and Anthropic formats)
 //!
 //! # Example Usage
 //!
@@ -38,29 +46,62 @@
 //! }
 //! ```
 
+mod audit;
 mod budget;
+mod budget_store;
+mod cache;
 mod cost;
 mod guardrails;
 mod manager;
 mod metrics;
+mod priority;
 mod providers;
 mod rate_limit;
+mod stream_guard;
 mod streaming;
 mod tiktoken;
 mod tokens;
+mod tool_policy;
+mod translation;
+mod virtual_keys;
 
+pub use audit::{extract_completion_content, InferenceAuditor};
 pub use budget::TokenBudgetTracker;
+pub use budget_store::{BudgetSnapshotFile, BudgetStore, BudgetStoreError, TenantSnapshot};
+#[cfg(feature = "distributed-rate-limit")]
+pub use budget_store::RedisBudgetStore;
+pub use cache::{
+    cache_scope, client_bypasses_cache, CacheHitKind, CachedInferenceResponse, InferenceCache,
+    InferenceCacheRecorder, InferenceCacheRequest,
+};
 pub use cost::CostCalculator;
 pub use guardrails::{
     extract_inference_content, GuardrailProcessor, PiiCheckResult, PromptInjectionResult,
+    StreamingOutputResult,
 };
 pub use manager::{InferenceCheckResult, InferenceRateLimitManager, InferenceRouteStats};
-pub use metrics::InferenceMetrics;
+pub use metrics::{get_inference_metrics, init_inference_metrics, InferenceMetrics};
+pub use priority::{AdmissionPermit, AdmissionQueue, AdmissionRejection};
 pub use providers::{create_provider, InferenceProviderAdapter};
 pub use rate_limit::{TokenRateLimitResult, TokenRateLimiter};
-pub use streaming::{is_sse_response, StreamingTokenCounter, StreamingTokenResult, TokenCountSource};
+pub use stream_guard::StreamGuard;
+pub use streaming::{
+    is_sse_response, StreamTiming, StreamingTokenCounter, StreamingTokenResult, TokenCountSource,
+    TtftTracker,
+};
 pub use tiktoken::{tiktoken_manager, TiktokenEncoding, TiktokenManager};
 pub use tokens::{TokenCounter, TokenEstimate, TokenSource};
+pub use tool_policy::{
+    ToolCallFilter, ToolPolicy, ToolPolicyOutcome, ToolUse, MAX_TOOL_POLICY_REQUEST_BYTES,
+    TOOL_POLICY_CATEGORY,
+};
+pub use translation::{
+    ApiFormat, InferenceTranslator, StreamTranslator, TranslationError, ANTHROPIC_VERSION,
+    MAX_TRANSLATION_BODY_BYTES,
+};
+pub use virtual_keys::{
+    hash_key, VirtualKey, VirtualKeyDefinition, VirtualKeyError, VirtualKeyStore, VirtualKeyWatcher,
+};
 
 use sentinel_config::{InferenceConfig, InferenceProvider};
 
@@ -0,0 +1,444 @@
+//! Priority classes and weighted fair admission for inference routes.
+//!
+//! Routes with a `priority` block put a bounded admission queue in front of
+//! the upstream. Requests are admitted while the route has capacity, measured
+//! as estimated tokens in flight and/or the queue depth the upstream reports
+//! in the routing `queue-depth-header`. Beyond that they wait in the queue,
+//! and are dispatched with self-clocked weighted fair queuing: each request
+//! gets a finish tag of `max(virtual time, class finish tag) + tokens / weight`,
+//! and the smallest tag goes first. Heavier classes therefore get a larger
+//! share of the upstream without starving lighter ones.
+//!
+//! The queue holds at most `max-queued` requests. When it is full, the newest
+//! request of the lowest-weight class is shed; a request waiting longer than
+//! its class's queue timeout gives up.
+
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tracing::{debug, trace};
+
+use sentinel_config::InferencePriorityConfig;
+
+/// Why a request was not admitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdmissionRejection {
+    /// The queue was full and the request had the lowest priority
+    Shed,
+    /// The request waited longer than its class's queue timeout
+    TimedOut,
+}
+
+impl AdmissionRejection {
+    /// Label for metrics and logs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdmissionRejection::Shed => "shed",
+            AdmissionRejection::TimedOut => "timed_out",
+        }
+    }
+}
+
+/// A request waiting for admission.
+struct Waiter {
+    seq: u64,
+    class: usize,
+    tokens: u64,
+    finish: f64,
+    grant: oneshot::Sender<AdmissionPermit>,
+}
+
+/// Mutable admission state, guarded by the queue's lock.
+struct AdmissionState {
+    in_flight_requests: u64,
+    in_flight_tokens: u64,
+    /// Last queue depth reported by the upstream
+    upstream_queue_depth: u64,
+    /// Finish tag of the most recently dispatched request
+    virtual_time: f64,
+    /// Finish tag of the last request queued per class
+    class_finish: Vec<f64>,
+    waiters: Vec<Waiter>,
+    next_seq: u64,
+}
+
+/// Bounded weighted fair admission queue of one inference route.
+pub struct AdmissionQueue {
+    route_id: String,
+    config: InferencePriorityConfig,
+    default_class: usize,
+    state: Mutex<AdmissionState>,
+}
+
+impl AdmissionQueue {
+    /// Create the admission queue of a route.
+    pub fn new(route_id: &str, config: InferencePriorityConfig) -> Self {
+        let default_class = config
+            .classes
+            .iter()
+            .position(|c| c.name == config.default_class)
+            .unwrap_or(0);
+        let state = AdmissionState {
+            in_flight_requests: 0,
+            in_flight_tokens: 0,
+            upstream_queue_depth: 0,
+            virtual_time: 0.0,
+            class_finish: vec![0.0; config.classes.len()],
+            waiters: Vec::new(),
+            next_seq: 0,
+        };
+        Self {
+            route_id: route_id.to_string(),
+            config,
+            default_class,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Priority settings of the route.
+    pub fn config(&self) -> &InferencePriorityConfig {
+        &self.config
+    }
+
+    /// Priority class of a request: the virtual key's class if it names one,
+    /// then the tenant's configured class, then the default class.
+    pub fn class_for(&self, tenant: &str, key_priority: Option<&str>) -> &str {
+        key_priority
+            .filter(|class| self.class_index(class).is_some())
+            .or_else(|| self.config.tenants.get(tenant).map(String::as_str))
+            .unwrap_or(&self.config.default_class)
+    }
+
+    /// Number of requests waiting for admission.
+    pub fn queued(&self) -> usize {
+        self.state.lock().waiters.len()
+    }
+
+    /// Wait for admission of a request with the given estimated tokens.
+    ///
+    /// The returned permit holds the request's share of the route's capacity
+    /// until it is dropped. Unknown classes are treated as the default class.
+    pub async fn admit(
+        self: &Arc<Self>,
+        class: &str,
+        tokens: u64,
+    ) -> Result<AdmissionPermit, AdmissionRejection> {
+        let class = self.class_index(class).unwrap_or(self.default_class);
+        let tokens = tokens.max(1);
+        let weight = self.config.classes[class].weight.max(1);
+        let start = Instant::now();
+
+        let (seq, mut grant) = {
+            let mut state = self.state.lock();
+
+            // Waiters are served first, so an idle queue is required to skip it
+            if state.waiters.is_empty() && self.has_capacity(&state, tokens) {
+                state.in_flight_requests += 1;
+                state.in_flight_tokens += tokens;
+                return Ok(self.permit(class, tokens));
+            }
+
+            if state.waiters.len() >= self.config.max_queued {
+                // Shed the newest request of the lowest-weight class
+                let victim = state
+                    .waiters
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, w)| (self.config.classes[w.class].weight, u64::MAX - w.seq))
+                    .map(|(index, w)| (index, self.config.classes[w.class].weight));
+                match victim {
+                    Some((index, victim_weight)) if victim_weight < weight => {
+                        // Dropping the sender wakes the waiter as shed
+                        let victim = state.waiters.swap_remove(index);
+                        debug!(
+                            route_id = %self.route_id,
+                            class = %self.config.classes[victim.class].name,
+                            "Shed queued inference request for higher priority request"
+                        );
+                    }
+                    _ => return Err(AdmissionRejection::Shed),
+                }
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            let start_tag = state.virtual_time.max(state.class_finish[class]);
+            let finish = start_tag + tokens as f64 / weight as f64;
+            state.class_finish[class] = finish;
+            state.waiters.push(Waiter {
+                seq,
+                class,
+                tokens,
+                finish,
+                grant: sender,
+            });
+
+            trace!(
+                route_id = %self.route_id,
+                class = %self.config.classes[class].name,
+                tokens = tokens,
+                queued = state.waiters.len(),
+                "Queued inference request for admission"
+            );
+            (seq, receiver)
+        };
+
+        // A request that goes away while queued (the client disconnected)
+        // gives up its place
+        let _waiting = WaitingGuard { queue: self, seq };
+
+        let timeout = Duration::from_millis(self.config.classes[class].queue_timeout_ms);
+        let mut permit = match tokio::time::timeout(timeout, &mut grant).await {
+            Ok(Ok(permit)) => permit,
+            Ok(Err(_)) => return Err(AdmissionRejection::Shed),
+            Err(_) => {
+                if self.remove_waiter(seq) {
+                    return Err(AdmissionRejection::TimedOut);
+                }
+                // Granted or shed between the timeout and taking the lock
+                grant.try_recv().map_err(|_| AdmissionRejection::Shed)?
+            }
+        };
+        permit.waited = start.elapsed();
+        Ok(permit)
+    }
+
+    /// Record the queue depth reported by the upstream.
+    pub fn record_upstream_queue_depth(self: &Arc<Self>, depth: u64) {
+        let mut state = self.state.lock();
+        state.upstream_queue_depth = depth;
+        self.dispatch(&mut state);
+    }
+
+    /// Take a waiter out of the queue, if it is still there.
+    fn remove_waiter(self: &Arc<Self>, seq: u64) -> bool {
+        let mut state = self.state.lock();
+        let Some(index) = state.waiters.iter().position(|w| w.seq == seq) else {
+            return false;
+        };
+        state.waiters.swap_remove(index);
+        // A large request at the head may have held back smaller ones
+        self.dispatch(&mut state);
+        true
+    }
+
+    fn class_index(&self, class: &str) -> Option<usize> {
+        self.config.classes.iter().position(|c| c.name == class)
+    }
+
+    fn permit(self: &Arc<Self>, class: usize, tokens: u64) -> AdmissionPermit {
+        AdmissionPermit {
+            queue: Arc::clone(self),
+            class,
+            tokens,
+            waited: Duration::ZERO,
+            released: false,
+        }
+    }
+
+    /// Whether a request of `tokens` fits. A route with nothing in flight
+    /// always admits, so oversized requests and stale upstream depths can't
+    /// stall the queue.
+    fn has_capacity(&self, state: &AdmissionState, tokens: u64) -> bool {
+        if state.in_flight_requests == 0 {
+            return true;
+        }
+        let tokens_fit = self
+            .config
+            .max_in_flight_tokens
+            .is_none_or(|max| state.in_flight_tokens + tokens <= max);
+        let depth_fits = self
+            .config
+            .max_upstream_queue_depth
+            .is_none_or(|max| state.upstream_queue_depth < max);
+        tokens_fit && depth_fits
+    }
+
+    /// Grant waiters in finish tag order while capacity allows.
+    fn dispatch(self: &Arc<Self>, state: &mut AdmissionState) {
+        while let Some(index) = state
+            .waiters
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.finish.total_cmp(&b.finish).then(a.seq.cmp(&b.seq)))
+            .map(|(index, _)| index)
+        {
+            if !self.has_capacity(state, state.waiters[index].tokens) {
+                break;
+            }
+            let waiter = state.waiters.swap_remove(index);
+            state.virtual_time = waiter.finish;
+            state.in_flight_requests += 1;
+            state.in_flight_tokens += waiter.tokens;
+
+            if let Err(mut permit) = waiter.grant.send(self.permit(waiter.class, waiter.tokens)) {
+                // The waiting request went away; give the capacity back here,
+                // as dropping the permit would take the lock again
+                permit.released = true;
+                state.in_flight_requests -= 1;
+                state.in_flight_tokens -= waiter.tokens;
+            }
+        }
+    }
+}
+
+/// Removes a queued request's waiter when its `admit` future is dropped.
+struct WaitingGuard<'a> {
+    queue: &'a Arc<AdmissionQueue>,
+    seq: u64,
+}
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.queue.remove_waiter(self.seq);
+    }
+}
+
+/// A request's share of a route's admission capacity, released on drop.
+pub struct AdmissionPermit {
+    queue: Arc<AdmissionQueue>,
+    class: usize,
+    tokens: u64,
+    waited: Duration,
+    released: bool,
+}
+
+impl AdmissionPermit {
+    /// Priority class the request was admitted in.
+    pub fn class(&self) -> &str {
+        &self.queue.config.classes[self.class].name
+    }
+
+    /// Time spent in the queue.
+    pub fn waited(&self) -> Duration {
+        self.waited
+    }
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+        let mut state = self.queue.state.lock();
+        state.in_flight_requests = state.in_flight_requests.saturating_sub(1);
+        state.in_flight_tokens = state.in_flight_tokens.saturating_sub(self.tokens);
+        self.queue.dispatch(&mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sentinel_config::PriorityClassConfig;
+    use std::collections::HashMap;
+
+    fn queue(max_in_flight_tokens: u64, max_queued: usize) -> Arc<AdmissionQueue> {
+        let class = |name: &str, weight: u32, queue_timeout_ms: u64| PriorityClassConfig {
+            name: name.to_string(),
+            weight,
+            queue_timeout_ms,
+        };
+        Arc::new(AdmissionQueue::new(
+            "test-route",
+            InferencePriorityConfig {
+                classes: vec![class("interactive", 4, 200), class("batch", 1, 50)],
+                default_class: "batch".to_string(),
+                tenants: HashMap::from([("search".to_string(), "interactive".to_string())]),
+                max_in_flight_tokens: Some(max_in_flight_tokens),
+                max_upstream_queue_depth: None,
+                max_queued,
+                retry_after_secs: 5,
+            },
+        ))
+    }
+
+    #[test]
+    fn test_class_for() {
+        let queue = queue(100, 10);
+        assert_eq!(queue.class_for("search", None), "interactive");
+        assert_eq!(queue.class_for("search", Some("batch")), "batch");
+        assert_eq!(queue.class_for("other", Some("unknown")), "batch");
+    }
+
+    #[tokio::test]
+    async fn test_weighted_fair_dispatch() {
+        let queue = queue(100, 10);
+        let running = queue.admit("batch", 100).await.unwrap();
+
+        // Queue two batch requests before two interactive ones
+        let mut waiting = Vec::new();
+        for class in ["batch", "batch", "interactive", "interactive"] {
+            let queue = Arc::clone(&queue);
+            waiting.push(tokio::spawn(async move {
+                let permit = queue.admit(class, 100).await;
+                let class = permit.as_ref().map(|p| p.class().to_string());
+                (class, permit)
+            }));
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(queue.queued(), 4);
+
+        // Interactive requests have the smaller finish tags and go first
+        drop(running);
+        let (class, permit) = waiting.remove(2).await.unwrap();
+        assert_eq!(class.as_deref(), Ok("interactive"));
+        drop(permit);
+        let (class, _permit) = waiting.remove(2).await.unwrap();
+        assert_eq!(class.as_deref(), Ok("interactive"));
+    }
+
+    #[tokio::test]
+    async fn test_sheds_lowest_priority_and_times_out() {
+        let queue = queue(100, 1);
+        let _running = queue.admit("interactive", 100).await.unwrap();
+
+        let batch = {
+            let queue = Arc::clone(&queue);
+            tokio::spawn(async move { queue.admit("batch", 10).await.map(|_| ()) })
+        };
+        tokio::task::yield_now().await;
+        assert_eq!(queue.queued(), 1);
+
+        // A full queue turns away requests that don't outrank a waiter...
+        assert_eq!(
+            queue.admit("batch", 10).await.err(),
+            Some(AdmissionRejection::Shed)
+        );
+
+        // ...and sheds the lowest-weight waiter for one that does
+        let interactive = {
+            let queue = Arc::clone(&queue);
+            tokio::spawn(async move { queue.admit("interactive", 10).await.map(|_| ()) })
+        };
+        assert_eq!(batch.await.unwrap(), Err(AdmissionRejection::Shed));
+        assert_eq!(
+            interactive.await.unwrap(),
+            Err(AdmissionRejection::TimedOut)
+        );
+        assert_eq!(queue.queued(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_waiter_leaves_queue() {
+        let queue = queue(100, 10);
+        let running = queue.admit("batch", 100).await.unwrap();
+
+        let abandoned = {
+            let queue = Arc::clone(&queue);
+            tokio::spawn(async move { queue.admit("interactive", 10).await.map(|_| ()) })
+        };
+        tokio::task::yield_now().await;
+        assert_eq!(queue.queued(), 1);
+
+        // The client went away, so its place in the queue is freed
+        abandoned.abort();
+        let _ = abandoned.await;
+        assert_eq!(queue.queued(), 0);
+
+        drop(running);
+        assert!(queue.admit("batch", 100).await.is_ok());
+    }
+}
@@ -3,6 +3,11 @@
 //! Each provider has specific headers and body formats for token information:
 //! - OpenAI: `x-ratelimit-remaining-tokens` header, `usage.total_tokens` in body
 //! - Anthropic: `anthropic-ratelimit-tokens-remaining` header, `usage.input_tokens + output_tokens`
+//! - Gemini: `usageMetadata.totalTokenCount` in body (model taken from the request path)
+//! - Bedrock: `x-amzn-bedrock-input-token-count`/`-output-token-count` headers, `usage.totalTokens`
+//! - Ollama: `prompt_eval_count + eval_count` in body
+//! - vLLM/TGI: `x-prompt-tokens`/`x-generated-tokens` headers (TGI), `usage.total_tokens` or
+//!   `details.generated_tokens` in body
 //! - Generic: `x-tokens-used` header, estimation fallback
 
 use http::HeaderMap;
@@ -11,6 +16,7 @@ use sentinel_config::{InferenceProvider, TokenEstimation};
 use tracing::trace;
 
 use super::tiktoken::tiktoken_manager;
+use super::translation::ApiFormat;
 
 /// Trait for provider-specific token extraction and estimation
 pub trait InferenceProviderAdapter: Send + Sync {
@@ -28,6 +34,16 @@ pub trait InferenceProviderAdapter: Send + Sync {
 
     /// Extract model name from request (header or body)
     fn extract_model(&self, headers: &HeaderMap, body: &[u8]) -> Option<String>;
+
+    /// Extract model name from the request path, for APIs that put it there
+    fn model_from_path(&self, _path: &str) -> Option<String> {
+        None
+    }
+
+    /// Request/response schema spoken by this provider, if translation supports it
+    fn api_format(&self) -> Option<ApiFormat> {
+        None
+    }
 }
 
 /// Create a provider adapter based on provider type
@@ -35,6 +51,11 @@ pub fn create_provider(provider: &InferenceProvider) -> Box<dyn InferenceProvide
     match provider {
         InferenceProvider::OpenAi => Box::new(OpenAiProvider),
         InferenceProvider::Anthropic => Box::new(AnthropicProvider),
+        InferenceProvider::Gemini => Box::new(GeminiProvider),
+        InferenceProvider::Bedrock => Box::new(BedrockProvider),
+        InferenceProvider::Ollama => Box::new(OllamaProvider),
+        InferenceProvider::Vllm => Box::new(OpenAiCompatibleProvider { name: "vllm" }),
+        InferenceProvider::Tgi => Box::new(OpenAiCompatibleProvider { name: "tgi" }),
         InferenceProvider::Generic => Box::new(GenericProvider),
     }
 }
@@ -50,6 +71,10 @@ impl InferenceProviderAdapter for OpenAiProvider {
         "openai"
     }
 
+    fn api_format(&self) -> Option<ApiFormat> {
+        Some(ApiFormat::OpenAiChat)
+    }
+
     fn tokens_from_headers(&self, headers: &HeaderMap) -> Option<u64> {
         // OpenAI uses several headers:
         // - x-ratelimit-remaining-tokens
@@ -122,6 +147,10 @@ impl InferenceProviderAdapter for AnthropicProvider {
         "anthropic"
     }
 
+    fn api_format(&self) -> Option<ApiFormat> {
+        Some(ApiFormat::AnthropicMessages)
+    }
+
     fn tokens_from_headers(&self, headers: &HeaderMap) -> Option<u64> {
         // Anthropic uses:
         // - anthropic-ratelimit-tokens-limit
@@ -176,6 +205,295 @@ impl InferenceProviderAdapter for AnthropicProvider {
     }
 }
 
+// ============================================================================
+// Gemini Provider
+// ============================================================================
+
+struct GeminiProvider;
+
+impl InferenceProviderAdapter for GeminiProvider {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn tokens_from_headers(&self, _headers: &HeaderMap) -> Option<u64> {
+        // Gemini reports usage only in the body
+        None
+    }
+
+    fn tokens_from_body(&self, body: &[u8]) -> Option<u64> {
+        // Gemini response format:
+        // { "usageMetadata": { "promptTokenCount": N, "candidatesTokenCount": M, "totalTokenCount": T } }
+        // A non-SSE streamGenerateContent response is a JSON array of these, with
+        // the final totals in the last element
+        let json: Value = serde_json::from_slice(body).ok()?;
+        let usage = match json {
+            Value::Array(items) => items
+                .into_iter()
+                .rev()
+                .find_map(|item| item.get("usageMetadata").cloned())?,
+            other => other.get("usageMetadata")?.clone(),
+        };
+
+        let total = usage
+            .get("totalTokenCount")
+            .and_then(|t| t.as_u64())
+            .or_else(|| {
+                let prompt = usage.get("promptTokenCount").and_then(|t| t.as_u64())?;
+                let candidates = usage
+                    .get("candidatesTokenCount")
+                    .and_then(|t| t.as_u64())
+                    .unwrap_or(0);
+                Some(prompt + candidates)
+            })?;
+
+        trace!(tokens = total, "Got token count from Gemini response body");
+        Some(total)
+    }
+
+    fn estimate_request_tokens(&self, body: &[u8], method: TokenEstimation) -> u64 {
+        estimate_tokens(body, method)
+    }
+
+    fn extract_model(&self, headers: &HeaderMap, body: &[u8]) -> Option<String> {
+        if let Some(model) = headers.get("x-model").and_then(|v| v.to_str().ok()) {
+            return Some(model.to_string());
+        }
+
+        // Gemini names the model in the path; some gateways also accept it in the body
+        let json: Value = serde_json::from_slice(body).ok()?;
+        json.get("model")?.as_str().map(|s| s.to_string())
+    }
+
+    fn model_from_path(&self, path: &str) -> Option<String> {
+        // /v1beta/models/gemini-1.5-pro:generateContent
+        // /v1/projects/p/locations/l/publishers/google/models/gemini-1.5-pro:streamGenerateContent
+        let (_, rest) = path.rsplit_once("/models/")?;
+        let model = rest.split([':', '/', '?']).next()?;
+        (!model.is_empty()).then(|| model.to_string())
+    }
+}
+
+// ============================================================================
+// Bedrock Provider
+// ============================================================================
+
+struct BedrockProvider;
+
+impl InferenceProviderAdapter for BedrockProvider {
+    fn name(&self) -> &'static str {
+        "bedrock"
+    }
+
+    fn tokens_from_headers(&self, headers: &HeaderMap) -> Option<u64> {
+        // InvokeModel and Converse both return:
+        // - x-amzn-bedrock-input-token-count
+        // - x-amzn-bedrock-output-token-count
+        let input = header_u64(headers, "x-amzn-bedrock-input-token-count")?;
+        let output = header_u64(headers, "x-amzn-bedrock-output-token-count").unwrap_or(0);
+
+        trace!(
+            input = input,
+            output = output,
+            "Got token count from Bedrock headers"
+        );
+        Some(input + output)
+    }
+
+    fn tokens_from_body(&self, body: &[u8]) -> Option<u64> {
+        let json: Value = serde_json::from_slice(body).ok()?;
+
+        // Converse format: { "usage": { "inputTokens": N, "outputTokens": M, "totalTokens": T } }
+        if let Some(usage) = json.get("usage") {
+            if let Some(total) = usage.get("totalTokens").and_then(|t| t.as_u64()) {
+                trace!(
+                    tokens = total,
+                    "Got token count from Bedrock Converse response body"
+                );
+                return Some(total);
+            }
+
+            // InvokeModel with Anthropic models returns the Anthropic body unchanged
+            let input = usage
+                .get("input_tokens")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(0);
+            let output = usage
+                .get("output_tokens")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(0);
+            if input > 0 || output > 0 {
+                return Some(input + output);
+            }
+        }
+
+        // Final chunk of InvokeModelWithResponseStream, once decoded
+        let metrics = json.get("amazon-bedrock-invocationMetrics")?;
+        let input = metrics
+            .get("inputTokenCount")
+            .and_then(|t| t.as_u64())
+            .unwrap_or(0);
+        let output = metrics
+            .get("outputTokenCount")
+            .and_then(|t| t.as_u64())
+            .unwrap_or(0);
+        (input > 0 || output > 0).then_some(input + output)
+    }
+
+    fn estimate_request_tokens(&self, body: &[u8], method: TokenEstimation) -> u64 {
+        estimate_tokens(body, method)
+    }
+
+    fn extract_model(&self, headers: &HeaderMap, body: &[u8]) -> Option<String> {
+        if let Some(model) = headers.get("x-model").and_then(|v| v.to_str().ok()) {
+            return Some(model.to_string());
+        }
+
+        let json: Value = serde_json::from_slice(body).ok()?;
+        json.get("model")?.as_str().map(|s| s.to_string())
+    }
+
+    fn model_from_path(&self, path: &str) -> Option<String> {
+        // /model/anthropic.claude-3-5-sonnet-20240620-v1%3A0/converse
+        let (_, rest) = path.split_once("/model/")?;
+        let model = rest.split(['/', '?']).next()?;
+        (!model.is_empty()).then(|| model.replace("%3A", ":").replace("%3a", ":"))
+    }
+}
+
+// ============================================================================
+// Ollama Provider
+// ============================================================================
+
+struct OllamaProvider;
+
+impl InferenceProviderAdapter for OllamaProvider {
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn tokens_from_headers(&self, _headers: &HeaderMap) -> Option<u64> {
+        // Ollama reports usage only in the body
+        None
+    }
+
+    fn tokens_from_body(&self, body: &[u8]) -> Option<u64> {
+        // Ollama /api/chat and /api/generate format:
+        // { "done": true, "prompt_eval_count": N, "eval_count": M }
+        let json: Value = serde_json::from_slice(body).ok()?;
+        let prompt = json.get("prompt_eval_count").and_then(|t| t.as_u64());
+        let eval = json.get("eval_count").and_then(|t| t.as_u64());
+        if prompt.is_none() && eval.is_none() {
+            return None;
+        }
+
+        let total = prompt.unwrap_or(0) + eval.unwrap_or(0);
+        trace!(tokens = total, "Got token count from Ollama response body");
+        Some(total)
+    }
+
+    fn estimate_request_tokens(&self, body: &[u8], method: TokenEstimation) -> u64 {
+        estimate_tokens(body, method)
+    }
+
+    fn extract_model(&self, headers: &HeaderMap, body: &[u8]) -> Option<String> {
+        if let Some(model) = headers.get("x-model").and_then(|v| v.to_str().ok()) {
+            return Some(model.to_string());
+        }
+
+        // Ollama puts model in body: { "model": "llama3.1:8b" }
+        let json: Value = serde_json::from_slice(body).ok()?;
+        json.get("model")?.as_str().map(|s| s.to_string())
+    }
+}
+
+// ============================================================================
+// OpenAI-Compatible Inference Servers (vLLM, TGI)
+// ============================================================================
+
+/// vLLM and TGI serve the OpenAI Chat Completions API, so they share the
+/// OpenAI schema plus the usage extensions of each server.
+struct OpenAiCompatibleProvider {
+    name: &'static str,
+}
+
+impl InferenceProviderAdapter for OpenAiCompatibleProvider {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn api_format(&self) -> Option<ApiFormat> {
+        Some(ApiFormat::OpenAiChat)
+    }
+
+    fn tokens_from_headers(&self, headers: &HeaderMap) -> Option<u64> {
+        // TGI sets x-prompt-tokens and x-generated-tokens on every response
+        let generated = header_u64(headers, "x-generated-tokens")?;
+        let prompt = header_u64(headers, "x-prompt-tokens").unwrap_or(0);
+
+        trace!(
+            prompt = prompt,
+            generated = generated,
+            "Got token count from TGI headers"
+        );
+        Some(prompt + generated)
+    }
+
+    fn tokens_from_body(&self, body: &[u8]) -> Option<u64> {
+        let json: Value = serde_json::from_slice(body).ok()?;
+
+        // OpenAI format, as served by vLLM and TGI's Messages API
+        if let Some(usage) = json.get("usage").filter(|u| !u.is_null()) {
+            let prompt = usage
+                .get("prompt_tokens")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(0);
+            let completion = usage
+                .get("completion_tokens")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(0);
+            let total = usage
+                .get("total_tokens")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(prompt + completion);
+            trace!(
+                tokens = total,
+                provider = self.name,
+                "Got token count from response body"
+            );
+            return Some(total);
+        }
+
+        // TGI native /generate format (an object, or a one-element array):
+        // { "generated_text": "...", "details": { "generated_tokens": M, "prefill": [...] } }
+        let response = match json {
+            Value::Array(mut items) if !items.is_empty() => items.swap_remove(0),
+            other => other,
+        };
+        let details = response.get("details")?;
+        let generated = details.get("generated_tokens")?.as_u64()?;
+        let prompt = details
+            .get("input_length")
+            .and_then(|t| t.as_u64())
+            .or_else(|| details.get("prefill")?.as_array().map(|p| p.len() as u64))
+            .unwrap_or(0);
+        Some(prompt + generated)
+    }
+
+    fn estimate_request_tokens(&self, body: &[u8], method: TokenEstimation) -> u64 {
+        estimate_tokens(body, method)
+    }
+
+    fn extract_model(&self, headers: &HeaderMap, body: &[u8]) -> Option<String> {
+        if let Some(model) = headers.get("x-model").and_then(|v| v.to_str().ok()) {
+            return Some(model.to_string());
+        }
+
+        let json: Value = serde_json::from_slice(body).ok()?;
+        json.get("model")?.as_str().map(|s| s.to_string())
+    }
+}
+
 // ============================================================================
 // Generic Provider
 // ============================================================================
@@ -249,6 +567,11 @@ impl InferenceProviderAdapter for GenericProvider {
     }
 }
 
+/// Parse a numeric response header
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
 // ============================================================================
 // Token Estimation Utilities
 // ============================================================================
@@ -319,6 +642,65 @@ mod tests {
         assert_eq!(provider.tokens_from_body(body), Some(150));
     }
 
+    #[test]
+    fn test_gemini_body_parsing() {
+        let body = br#"{"candidates": [], "usageMetadata": {"promptTokenCount": 12, "candidatesTokenCount": 30, "totalTokenCount": 42}}"#;
+        assert_eq!(GeminiProvider.tokens_from_body(body), Some(42));
+
+        // Non-SSE streamGenerateContent returns an array of chunks
+        let body = br#"[{"usageMetadata": {"promptTokenCount": 12}}, {"usageMetadata": {"promptTokenCount": 12, "candidatesTokenCount": 8}}]"#;
+        assert_eq!(GeminiProvider.tokens_from_body(body), Some(20));
+
+        assert_eq!(
+            GeminiProvider.model_from_path("/v1beta/models/gemini-1.5-pro:generateContent"),
+            Some("gemini-1.5-pro".to_string())
+        );
+        assert_eq!(GeminiProvider.model_from_path("/v1/chat/completions"), None);
+    }
+
+    #[test]
+    fn test_bedrock_usage() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-amzn-bedrock-input-token-count", "100".parse().unwrap());
+        headers.insert("x-amzn-bedrock-output-token-count", "25".parse().unwrap());
+        assert_eq!(BedrockProvider.tokens_from_headers(&headers), Some(125));
+
+        let body = br#"{"usage": {"inputTokens": 100, "outputTokens": 25, "totalTokens": 125}}"#;
+        assert_eq!(BedrockProvider.tokens_from_body(body), Some(125));
+
+        assert_eq!(
+            BedrockProvider
+                .model_from_path("/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse"),
+            Some("anthropic.claude-3-haiku-20240307-v1:0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_ollama_body_parsing() {
+        let body =
+            br#"{"model": "llama3.1", "done": true, "prompt_eval_count": 26, "eval_count": 290}"#;
+        assert_eq!(OllamaProvider.tokens_from_body(body), Some(316));
+        assert_eq!(OllamaProvider.tokens_from_body(br#"{"done": false}"#), None);
+    }
+
+    #[test]
+    fn test_openai_compatible_usage() {
+        let provider = create_provider(&InferenceProvider::Tgi);
+        assert_eq!(provider.name(), "tgi");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-prompt-tokens", "7".parse().unwrap());
+        headers.insert("x-generated-tokens", "20".parse().unwrap());
+        assert_eq!(provider.tokens_from_headers(&headers), Some(27));
+
+        let body =
+            br#"{"usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}}"#;
+        assert_eq!(provider.tokens_from_body(body), Some(15));
+
+        let body = br#"[{"generated_text": "hi", "details": {"generated_tokens": 20, "prefill": [{}, {}, {}]}}]"#;
+        assert_eq!(provider.tokens_from_body(body), Some(23));
+    }
+
     #[test]
     fn test_token_estimation_chars() {
         let body = b"Hello world, this is a test message for token counting!";
@@ -0,0 +1,370 @@
+//! Incremental guardrail inspection of streamed inference responses.
+//!
+//! Completion deltas are held back until a window of text has accumulated,
+//! then released (optionally with the text rewritten) or replaced by an error
+//! event once the guardrail agent has answered. Events that carry no text are
+//! passed through as soon as nothing is held in front of them.
+//!
+//! The stream is parsed in the client's API format: SSE events separated by a
+//! blank line, or NDJSON lines for Ollama.
+
+use sentinel_config::InferenceProvider;
+use serde_json::{json, Value};
+
+/// Holds back streamed completion deltas for guardrail inspection.
+#[derive(Debug)]
+pub struct StreamGuard {
+    provider: InferenceProvider,
+    window_chars: usize,
+    /// Bytes of an incomplete event
+    buffer: Vec<u8>,
+    /// Complete events not yet released
+    held: Vec<HeldEvent>,
+    /// Delta text of the held events
+    pending: String,
+    /// Characters in `pending`
+    pending_chars: usize,
+    /// Characters inspected in earlier windows
+    offset: usize,
+    terminated: bool,
+}
+
+#[derive(Debug)]
+struct HeldEvent {
+    /// Event as received
+    raw: String,
+    /// Parsed data and the JSON pointer of its delta text, for text events
+    text: Option<(Value, &'static str)>,
+}
+
+impl StreamGuard {
+    /// Create a guard for a stream in the given provider's format.
+    pub fn new(provider: InferenceProvider, window_chars: usize) -> Self {
+        Self {
+            provider,
+            window_chars: window_chars.max(1),
+            buffer: Vec::new(),
+            held: Vec::new(),
+            pending: String::new(),
+            pending_chars: 0,
+            offset: 0,
+            terminated: false,
+        }
+    }
+
+    /// Add a chunk from the stream, returning the bytes that can be sent now.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<u8> {
+        if self.terminated {
+            return Vec::new();
+        }
+        self.buffer.extend(chunk.iter().filter(|&&b| b != b'\r'));
+
+        let delimiter: &[u8] = match self.provider {
+            InferenceProvider::Ollama => b"\n",
+            _ => b"\n\n",
+        };
+        let mut out = Vec::new();
+        while let Some(end) = self
+            .buffer
+            .windows(delimiter.len())
+            .position(|w| w == delimiter)
+        {
+            let event: Vec<u8> = self.buffer.drain(..end + delimiter.len()).collect();
+            self.hold(String::from_utf8_lossy(&event).into_owned(), &mut out);
+        }
+        out
+    }
+
+    /// Treat any incomplete event as complete, at end of stream.
+    pub fn finish(&mut self) -> Vec<u8> {
+        let mut out = Vec::new();
+        if !self.buffer.is_empty() && !self.terminated {
+            let event = std::mem::take(&mut self.buffer);
+            self.hold(String::from_utf8_lossy(&event).into_owned(), &mut out);
+        }
+        out
+    }
+
+    fn hold(&mut self, raw: String, out: &mut Vec<u8>) {
+        let text = event_data(&raw)
+            .and_then(|data| serde_json::from_str::<Value>(&data).ok())
+            .and_then(|json| {
+                let pointer = text_pointer(self.provider, &json)?;
+                Some((json, pointer))
+            });
+
+        match text {
+            Some((json, pointer)) => {
+                let delta = json.pointer(pointer).and_then(Value::as_str).unwrap_or("");
+                self.pending.push_str(delta);
+                self.pending_chars += delta.chars().count();
+                self.held.push(HeldEvent {
+                    raw,
+                    text: Some((json, pointer)),
+                });
+            }
+            // Nothing held in front of it, so it can go straight out
+            None if self.held.is_empty() => out.extend_from_slice(raw.as_bytes()),
+            None => self.held.push(HeldEvent { raw, text: None }),
+        }
+    }
+
+    /// Whether enough text is held to inspect a window.
+    pub fn window_ready(&self) -> bool {
+        self.pending_chars >= self.window_chars
+    }
+
+    /// Whether any events are held.
+    pub fn has_held(&self) -> bool {
+        !self.held.is_empty()
+    }
+
+    /// Delta text held since the last release.
+    pub fn pending_text(&self) -> &str {
+        &self.pending
+    }
+
+    /// Characters inspected before the pending window.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Whether the stream was terminated.
+    pub fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    /// Release the held events, with their text replaced if `replacement` is given.
+    ///
+    /// The replacement goes into the first text event; the deltas of the other
+    /// text events are emptied, so fields such as `finish_reason` survive.
+    pub fn release(&mut self, replacement: Option<&str>) -> Vec<u8> {
+        let rewrite = replacement.is_some();
+        let mut replacement = replacement;
+        let mut out = Vec::new();
+        for event in self.held.drain(..) {
+            match event.text {
+                Some((mut json, pointer)) if rewrite => {
+                    let text = replacement.take().unwrap_or_default();
+                    if let Some(slot) = json.pointer_mut(pointer) {
+                        *slot = Value::String(text.to_string());
+                    }
+                    out.extend(rewrite_event(&event.raw, &json));
+                }
+                _ => out.extend_from_slice(event.raw.as_bytes()),
+            }
+        }
+        self.offset += self.pending_chars;
+        self.pending.clear();
+        self.pending_chars = 0;
+        out
+    }
+
+    /// Drop the held events and end the stream with an error event.
+    pub fn terminate(&mut self, message: &str) -> Vec<u8> {
+        self.held.clear();
+        self.buffer.clear();
+        self.pending.clear();
+        self.pending_chars = 0;
+        self.terminated = true;
+        error_event(self.provider, message)
+    }
+}
+
+/// Data of an SSE event, or the whole line for NDJSON.
+pub(super) fn event_data(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    if trimmed.starts_with('{') {
+        return Some(trimmed.to_string());
+    }
+    let data: Vec<&str> = raw
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim_start)
+        .collect();
+    (!data.is_empty()).then(|| data.join("\n"))
+}
+
+/// JSON pointer of the delta text in an event, if it carries any.
+fn text_pointer(provider: InferenceProvider, json: &Value) -> Option<&'static str> {
+    let candidates: &[&'static str] = match provider {
+        InferenceProvider::OpenAi | InferenceProvider::Vllm => &["/choices/0/delta/content"],
+        InferenceProvider::Tgi => &["/choices/0/delta/content", "/token/text"],
+        InferenceProvider::Anthropic => &["/delta/text"],
+        InferenceProvider::Gemini => &["/candidates/0/content/parts/0/text"],
+        InferenceProvider::Ollama => &["/message/content", "/response"],
+        InferenceProvider::Generic | InferenceProvider::Bedrock => {
+            &["/choices/0/delta/content", "/delta/text"]
+        }
+    };
+
+    candidates.iter().copied().find(|pointer| {
+        // TGI's special tokens (e.g. `</s>`) are not text
+        let special = *pointer == "/token/text"
+            && json.pointer("/token/special").and_then(Value::as_bool) == Some(true);
+        !special && json.pointer(pointer).is_some_and(Value::is_string)
+    })
+}
+
+/// Rebuild an event around new data, keeping its non-data lines.
+pub(super) fn rewrite_event(raw: &str, json: &Value) -> Vec<u8> {
+    if raw.trim_start().starts_with('{') {
+        return format!("{}\n", json).into_bytes();
+    }
+    let mut out = String::new();
+    for line in raw
+        .lines()
+        .filter(|l| !l.is_empty() && !l.starts_with("data:"))
+    {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.push_str(&format!("data: {}\n\n", json));
+    out.into_bytes()
+}
+
+/// Error event that ends a stream in the provider's format.
+pub(super) fn error_event(provider: InferenceProvider, message: &str) -> Vec<u8> {
+    match provider {
+        InferenceProvider::Anthropic => {
+            let error = json!({
+                "type": "error",
+                "error": {"type": "invalid_request_error", "message": message},
+            });
+            format!("event: error\ndata: {}\n\n", error)
+        }
+        InferenceProvider::Gemini => {
+            let error = json!({
+                "error": {"code": 400, "message": message, "status": "INVALID_ARGUMENT"},
+            });
+            format!("data: {}\n\n", error)
+        }
+        InferenceProvider::Ollama => format!("{}\n", json!({ "error": message })),
+        _ => {
+            let error = json!({
+                "error": {
+                    "message": message,
+                    "type": "invalid_request_error",
+                    "code": "content_filter",
+                },
+            });
+            format!("data: {}\n\ndata: [DONE]\n\n", error)
+        }
+    }
+    .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn openai_delta(text: &str) -> String {
+        format!(
+            "data: {}\n\n",
+            json!({"id": "c1", "choices": [{"index": 0, "delta": {"content": text}, "finish_reason": null}]})
+        )
+    }
+
+    fn data_events(bytes: &[u8]) -> Vec<Value> {
+        String::from_utf8_lossy(bytes)
+            .split("\n\n")
+            .filter_map(|event| event.strip_prefix("data: "))
+            .filter_map(|data| serde_json::from_str(data).ok())
+            .collect()
+    }
+
+    #[test]
+    fn test_holds_until_window() {
+        let mut guard = StreamGuard::new(InferenceProvider::OpenAi, 10);
+
+        assert!(guard.push(openai_delta("Hello").as_bytes()).is_empty());
+        assert!(!guard.window_ready());
+
+        // A split event is only held once complete
+        let second = openai_delta(" world");
+        let (head, tail) = second.split_at(12);
+        assert!(guard.push(head.as_bytes()).is_empty());
+        assert!(guard.push(tail.as_bytes()).is_empty());
+        assert!(guard.window_ready());
+        assert_eq!(guard.pending_text(), "Hello world");
+
+        let out = guard.release(None);
+        assert_eq!(
+            out,
+            [openai_delta("Hello"), openai_delta(" world")]
+                .concat()
+                .into_bytes()
+        );
+        assert_eq!(guard.offset(), 11);
+        assert!(!guard.has_held());
+
+        // With nothing held, events without text pass straight through
+        assert_eq!(guard.push(b"data: [DONE]\n\n"), b"data: [DONE]\n\n");
+    }
+
+    #[test]
+    fn test_release_with_redaction() {
+        let mut guard = StreamGuard::new(InferenceProvider::OpenAi, 100);
+        guard.push(openai_delta("mail a@").as_bytes());
+        guard.push(openai_delta("b.com now").as_bytes());
+        guard.push(b"data: [DONE]\n\n");
+
+        let out = guard.release(Some("mail [EMAIL] now"));
+        let events = data_events(&out);
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0]["choices"][0]["delta"]["content"],
+            "mail [EMAIL] now"
+        );
+        assert_eq!(events[0]["id"], "c1");
+        assert_eq!(events[1]["choices"][0]["delta"]["content"], "");
+        assert!(String::from_utf8_lossy(&out).ends_with("data: [DONE]\n\n"));
+    }
+
+    #[test]
+    fn test_terminate_anthropic() {
+        let mut guard = StreamGuard::new(InferenceProvider::Anthropic, 100);
+        let start = "event: message_start\ndata: {\"type\":\"message_start\"}\n\n";
+        assert_eq!(guard.push(start.as_bytes()), start.as_bytes());
+
+        let delta = "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"secret\"}}\n\n";
+        assert!(guard.push(delta.as_bytes()).is_empty());
+        assert_eq!(guard.pending_text(), "secret");
+
+        let out = String::from_utf8(guard.terminate("Blocked")).unwrap();
+        assert!(out.starts_with("event: error\ndata: "));
+        assert!(out.contains("\"message\":\"Blocked\""));
+        assert!(guard.is_terminated());
+
+        // Everything after termination is dropped
+        assert!(guard.push(delta.as_bytes()).is_empty());
+        assert!(!guard.has_held());
+    }
+
+    #[test]
+    fn test_ollama_ndjson() {
+        let mut guard = StreamGuard::new(InferenceProvider::Ollama, 4);
+        let line = b"{\"message\":{\"role\":\"assistant\",\"content\":\"Hello\"},\"done\":false}\n";
+        assert!(guard.push(line).is_empty());
+        assert!(guard.window_ready());
+
+        let out = guard.release(Some("Hi"));
+        let json: Value = serde_json::from_slice(out.strip_suffix(b"\n").unwrap()).unwrap();
+        assert_eq!(json["message"]["content"], "Hi");
+
+        let error = guard.terminate("Blocked");
+        assert_eq!(error, b"{\"error\":\"Blocked\"}\n");
+    }
+
+    #[test]
+    fn test_finish_flushes_partial_event() {
+        let mut guard = StreamGuard::new(InferenceProvider::Gemini, 100);
+        let chunk = b"data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"Hi\"}]}}]}";
+        assert!(guard.push(chunk).is_empty());
+        assert!(!guard.has_held());
+
+        assert!(guard.finish().is_empty());
+        assert_eq!(guard.pending_text(), "Hi");
+        assert!(guard.has_held());
+    }
+}
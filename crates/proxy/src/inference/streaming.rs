@@ -4,6 +4,8 @@
 //! - Parses SSE chunks to extract content deltas
 //! - Accumulates text content across chunks
 //! - Provides final token count using tiktoken
+//! - Measures time to first token and inter-token latency, and keeps a
+//!   per-target moving average of TTFT for latency-aware routing
 //!
 //! # SSE Formats
 //!
@@ -20,6 +22,25 @@
 //! data: {"type":"content_block_delta","delta":{"type":"text_delta","text":"Hello"}}
 //! ```
 //!
+//! ## Gemini (`streamGenerateContent?alt=sse`)
+//! ```text
+//! data: {"candidates":[{"content":{"parts":[{"text":"Hello"}]}}],"usageMetadata":{...}}
+//! ```
+//!
+//! ## Ollama (NDJSON, one object per line)
+//! ```text
+//! {"message":{"role":"assistant","content":"Hello"},"done":false}
+//! {"done":true,"prompt_eval_count":26,"eval_count":290}
+//! ```
+//!
+//! ## TGI (`/generate_stream`)
+//! ```text
+//! data:{"token":{"text":"Hello","special":false},"generated_text":null,"details":null}
+//! ```
+//!
+//! vLLM streams the OpenAI format, with `usage` in the final chunk (or every
+//! chunk with `continuous_usage_stats`).
+//!
 //! # Usage
 //!
 //! ```ignore
@@ -29,6 +50,9 @@
 //! let tokens = counter.finalize();
 //! ```
 
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
 use serde_json::Value;
 use tracing::{trace, warn};
 
@@ -163,6 +187,9 @@ impl StreamingTokenCounter {
             &line[6..]
         } else if line.starts_with("data:") {
             &line[5..]
+        } else if line.starts_with('{') {
+            // NDJSON streams (Ollama) send bare JSON objects
+            line
         } else {
             // Skip event lines, comments, etc.
             return ChunkResult {
@@ -199,7 +226,18 @@ impl StreamingTokenCounter {
         match self.provider {
             InferenceProvider::OpenAi => self.parse_openai_chunk(&json),
             InferenceProvider::Anthropic => self.parse_anthropic_chunk(&json),
-            InferenceProvider::Generic => {
+            InferenceProvider::Gemini => self.parse_gemini_chunk(&json),
+            InferenceProvider::Ollama => self.parse_ollama_chunk(&json),
+            InferenceProvider::Vllm | InferenceProvider::Tgi => {
+                // OpenAI-compatible endpoints first, then TGI's native stream
+                let result = self.parse_openai_chunk(&json);
+                if result.content.is_some() || result.is_done || result.usage.is_some() {
+                    result
+                } else {
+                    self.parse_tgi_chunk(&json)
+                }
+            }
+            InferenceProvider::Generic | InferenceProvider::Bedrock => {
                 // Try OpenAI format first, then Anthropic
                 let result = self.parse_openai_chunk(&json);
                 if result.content.is_some() || result.is_done || result.usage.is_some() {
@@ -333,6 +371,158 @@ impl StreamingTokenCounter {
         result
     }
 
+    /// Parse Gemini streaming chunk format.
+    ///
+    /// Format: {"candidates":[{"content":{"parts":[{"text":"..."}]},"finishReason":"STOP"}],
+    /// "usageMetadata":{"promptTokenCount":N,"candidatesTokenCount":M,"totalTokenCount":T}}
+    fn parse_gemini_chunk(&self, json: &Value) -> ChunkResult {
+        let mut result = ChunkResult {
+            content: None,
+            is_done: false,
+            usage: None,
+        };
+
+        if let Some(candidate) = json
+            .get("candidates")
+            .and_then(|c| c.as_array())
+            .and_then(|c| c.first())
+        {
+            if candidate.get("finishReason").is_some_and(|r| !r.is_null()) {
+                result.is_done = true;
+            }
+
+            if let Some(parts) = candidate
+                .get("content")
+                .and_then(|c| c.get("parts"))
+                .and_then(|p| p.as_array())
+            {
+                let text: String = parts
+                    .iter()
+                    .filter_map(|part| part.get("text").and_then(|t| t.as_str()))
+                    .collect();
+                if !text.is_empty() {
+                    result.content = Some(text);
+                }
+            }
+        }
+
+        // Every chunk carries the cumulative usage so far
+        if let Some(usage) = json.get("usageMetadata") {
+            let input_tokens = usage
+                .get("promptTokenCount")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(0);
+            let output_tokens = usage
+                .get("candidatesTokenCount")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(0);
+            let total_tokens = usage
+                .get("totalTokenCount")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(input_tokens + output_tokens);
+
+            if total_tokens > 0 {
+                result.usage = Some(ApiUsage {
+                    input_tokens,
+                    output_tokens,
+                    total_tokens,
+                });
+            }
+        }
+
+        result
+    }
+
+    /// Parse Ollama streaming chunk format (`/api/chat` and `/api/generate`).
+    ///
+    /// Format: {"message":{"content":"..."},"done":false} or {"response":"...","done":false},
+    /// with counts in the final {"done":true,"prompt_eval_count":N,"eval_count":M}
+    fn parse_ollama_chunk(&self, json: &Value) -> ChunkResult {
+        let mut result = ChunkResult {
+            content: None,
+            is_done: false,
+            usage: None,
+        };
+
+        let content = json
+            .get("message")
+            .and_then(|m| m.get("content"))
+            .or_else(|| json.get("response"))
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty());
+        result.content = content.map(|c| c.to_string());
+
+        if json.get("done").and_then(|d| d.as_bool()) == Some(true) {
+            result.is_done = true;
+
+            let input_tokens = json
+                .get("prompt_eval_count")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(0);
+            let output_tokens = json.get("eval_count").and_then(|t| t.as_u64()).unwrap_or(0);
+
+            if input_tokens + output_tokens > 0 {
+                result.usage = Some(ApiUsage {
+                    input_tokens,
+                    output_tokens,
+                    total_tokens: input_tokens + output_tokens,
+                });
+            }
+        }
+
+        result
+    }
+
+    /// Parse TGI `/generate_stream` chunk format.
+    ///
+    /// Format: {"token":{"text":"...","special":false},"generated_text":null,"details":null},
+    /// with {"details":{"generated_tokens":M,"input_length":N}} on the last event
+    fn parse_tgi_chunk(&self, json: &Value) -> ChunkResult {
+        let mut result = ChunkResult {
+            content: None,
+            is_done: false,
+            usage: None,
+        };
+
+        if let Some(token) = json.get("token") {
+            let special = token
+                .get("special")
+                .and_then(|s| s.as_bool())
+                .unwrap_or(false);
+            if !special {
+                result.content = token
+                    .get("text")
+                    .and_then(|t| t.as_str())
+                    .map(|t| t.to_string());
+            }
+        }
+
+        if json.get("generated_text").is_some_and(|t| !t.is_null()) {
+            result.is_done = true;
+        }
+
+        if let Some(details) = json.get("details").filter(|d| !d.is_null()) {
+            let output_tokens = details
+                .get("generated_tokens")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(0);
+            let input_tokens = details
+                .get("input_length")
+                .and_then(|t| t.as_u64())
+                .unwrap_or(0);
+
+            if output_tokens > 0 {
+                result.usage = Some(ApiUsage {
+                    input_tokens,
+                    output_tokens,
+                    total_tokens: input_tokens + output_tokens,
+                });
+            }
+        }
+
+        result
+    }
+
     /// Check if the stream has completed.
     pub fn is_completed(&self) -> bool {
         self.completed
@@ -429,6 +619,135 @@ pub struct StreamingTokenResult {
     pub content_length: usize,
 }
 
+// ============================================================================
+// Stream Latency
+// ============================================================================
+
+/// Timing of the response body chunks sent for a streamed inference response.
+///
+/// Time to first token is measured from the start of the request, so it is
+/// the latency the client sees. Inter-token latency is the mean gap between
+/// tokens after the first chunk.
+#[derive(Debug, Clone, Copy)]
+pub struct StreamTiming {
+    /// Request start
+    start: Instant,
+    /// When the first non-empty chunk was sent
+    first_chunk: Option<Instant>,
+    /// When the latest non-empty chunk was sent
+    last_chunk: Option<Instant>,
+    /// Non-empty chunks sent
+    chunks: u64,
+}
+
+impl StreamTiming {
+    /// Start timing a response for a request that began at `start`.
+    pub fn new(start: Instant) -> Self {
+        Self {
+            start,
+            first_chunk: None,
+            last_chunk: None,
+            chunks: 0,
+        }
+    }
+
+    /// Record a non-empty chunk sent at `at`.
+    pub fn record_chunk(&mut self, at: Instant) {
+        self.first_chunk.get_or_insert(at);
+        self.last_chunk = Some(at);
+        self.chunks += 1;
+    }
+
+    /// Time from the request start until the first chunk was sent.
+    pub fn time_to_first_token(&self) -> Option<Duration> {
+        self.first_chunk
+            .map(|first| first.duration_since(self.start))
+    }
+
+    /// Mean time between tokens after the first chunk.
+    ///
+    /// The generation time is spread over `output_tokens - 1` gaps when the
+    /// token count is known, otherwise over the gaps between chunks (most
+    /// providers send one token per event). `None` with fewer than two tokens.
+    pub fn inter_token_latency(&self, output_tokens: Option<u64>) -> Option<Duration> {
+        let generation = self.last_chunk?.duration_since(self.first_chunk?);
+        let gaps = match output_tokens {
+            Some(tokens) if tokens > 1 => tokens - 1,
+            _ => self.chunks.saturating_sub(1),
+        };
+        (gaps > 0).then(|| generation.div_f64(gaps as f64))
+    }
+}
+
+/// Smoothing factor for TTFT samples (weight of the newest sample)
+const TTFT_EWMA_ALPHA: f64 = 0.3;
+
+/// Samples older than this no longer describe a target; it is treated as
+/// unmeasured so that routing tries it again.
+const TTFT_SAMPLE_TTL: Duration = Duration::from_secs(60);
+
+/// Moving average (EWMA) of time to first token per upstream target.
+///
+/// Fed from streamed inference responses; used by the
+/// `least-time-to-first-token` routing strategy and the TTFT fallback trigger.
+#[derive(Debug, Default)]
+pub struct TtftTracker {
+    /// Upstream ID -> target address -> (EWMA in milliseconds, last sample)
+    upstreams: DashMap<String, DashMap<String, (f64, Instant)>>,
+}
+
+impl TtftTracker {
+    /// Create an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a TTFT sample for a target of an upstream.
+    pub fn record(&self, upstream: &str, target: &str, ttft: Duration) {
+        let sample_ms = ttft.as_secs_f64() * 1000.0;
+        let now = Instant::now();
+        let targets = self.upstreams.entry(upstream.to_string()).or_default();
+        let mut entry = targets
+            .entry(target.to_string())
+            .or_insert((sample_ms, now));
+        let (ewma, updated) = entry.value_mut();
+        if now.duration_since(*updated) > TTFT_SAMPLE_TTL {
+            *ewma = sample_ms;
+        } else {
+            *ewma = TTFT_EWMA_ALPHA * sample_ms + (1.0 - TTFT_EWMA_ALPHA) * *ewma;
+        }
+        *updated = now;
+    }
+
+    /// Current TTFT average of a target, if it has a recent sample.
+    pub fn ewma_ms(&self, upstream: &str, target: &str) -> Option<f64> {
+        let targets = self.upstreams.get(upstream)?;
+        let entry = targets.get(target)?;
+        let (ewma, updated) = *entry.value();
+        (updated.elapsed() <= TTFT_SAMPLE_TTL).then_some(ewma)
+    }
+
+    /// Lowest TTFT average among an upstream's recently measured targets.
+    pub fn best_ms(&self, upstream: &str) -> Option<f64> {
+        let targets = self.upstreams.get(upstream)?;
+        targets
+            .iter()
+            .filter(|entry| entry.value().1.elapsed() <= TTFT_SAMPLE_TTL)
+            .map(|entry| entry.value().0)
+            .min_by(f64::total_cmp)
+    }
+
+    /// Pick the candidate with the lowest TTFT average. Candidates without a
+    /// recent sample are picked first, so every target gets measured.
+    pub fn select<'a>(&self, upstream: &str, candidates: &'a [String]) -> Option<&'a String> {
+        candidates.iter().min_by(|a, b| {
+            let a = self.ewma_ms(upstream, a).unwrap_or(f64::NEG_INFINITY);
+            let b = self.ewma_ms(upstream, b).unwrap_or(f64::NEG_INFINITY);
+            a.total_cmp(&b)
+        })
+    }
+}
+
 /// Check if a response appears to be SSE based on content type.
 pub fn is_sse_response(content_type: Option<&str>) -> bool {
     content_type.map_or(false, |ct| {
@@ -565,4 +884,130 @@ mod tests {
         let result = counter.process_chunk(chunk);
         assert_eq!(result.content, Some("Test".to_string()));
     }
+
+    #[test]
+    fn test_gemini_streaming() {
+        let mut counter = StreamingTokenCounter::new(
+            InferenceProvider::Gemini,
+            Some("gemini-1.5-pro".to_string()),
+        );
+
+        let chunk1 = b"data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"Hello\"}],\"role\":\"model\"}}],\"usageMetadata\":{\"promptTokenCount\":9}}\r\n\r\n";
+        let chunk2 = b"data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\" world\"}],\"role\":\"model\"},\"finishReason\":\"STOP\"}],\"usageMetadata\":{\"promptTokenCount\":9,\"candidatesTokenCount\":2,\"totalTokenCount\":11}}\r\n\r\n";
+
+        assert_eq!(
+            counter.process_chunk(chunk1).content,
+            Some("Hello".to_string())
+        );
+        let r2 = counter.process_chunk(chunk2);
+        assert!(r2.is_done);
+        assert_eq!(counter.content(), "Hello world");
+
+        let result = counter.finalize();
+        assert_eq!(result.source, TokenCountSource::ApiProvided);
+        assert_eq!(result.input_tokens, Some(9));
+        assert_eq!(result.output_tokens, 2);
+        assert_eq!(result.total_tokens, Some(11));
+    }
+
+    #[test]
+    fn test_ollama_ndjson_streaming() {
+        let mut counter =
+            StreamingTokenCounter::new(InferenceProvider::Ollama, Some("llama3.1".to_string()));
+
+        let chunk1 = b"{\"model\":\"llama3.1\",\"message\":{\"role\":\"assistant\",\"content\":\"Hi\"},\"done\":false}\n";
+        // The final object arrives split across two chunks
+        let chunk2 = b"{\"model\":\"llama3.1\",\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,";
+        let chunk3 = b"\"prompt_eval_count\":26,\"eval_count\":290}\n";
+
+        assert_eq!(
+            counter.process_chunk(chunk1).content,
+            Some("Hi".to_string())
+        );
+        assert!(counter.process_chunk(chunk2).content.is_none());
+        assert!(counter.process_chunk(chunk3).is_done);
+
+        let result = counter.finalize();
+        assert_eq!(result.input_tokens, Some(26));
+        assert_eq!(result.output_tokens, 290);
+        assert_eq!(result.total_tokens, Some(316));
+    }
+
+    #[test]
+    fn test_tgi_streaming() {
+        let mut counter = StreamingTokenCounter::new(InferenceProvider::Tgi, None);
+
+        let chunk1 = b"data:{\"index\":1,\"token\":{\"id\":9906,\"text\":\"Hello\",\"logprob\":-0.1,\"special\":false},\"generated_text\":null,\"details\":null}\n\n";
+        let chunk2 = b"data:{\"index\":2,\"token\":{\"id\":2,\"text\":\"</s>\",\"logprob\":0.0,\"special\":true},\"generated_text\":\"Hello\",\"details\":{\"finish_reason\":\"eos_token\",\"generated_tokens\":2,\"input_length\":5}}\n\n";
+
+        assert_eq!(
+            counter.process_chunk(chunk1).content,
+            Some("Hello".to_string())
+        );
+        let r2 = counter.process_chunk(chunk2);
+        assert!(r2.is_done);
+        assert!(r2.content.is_none());
+
+        let result = counter.finalize();
+        assert_eq!(result.total_tokens, Some(7));
+    }
+
+    #[test]
+    fn test_vllm_streaming_usage() {
+        let mut counter = StreamingTokenCounter::new(InferenceProvider::Vllm, None);
+
+        // Continuous usage stats: every chunk carries the running totals
+        let chunk1 = b"data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"},\"finish_reason\":null}],\"usage\":{\"prompt_tokens\":4,\"completion_tokens\":1,\"total_tokens\":5}}\n\n";
+        let chunk2 = b"data: {\"choices\":[],\"usage\":{\"prompt_tokens\":4,\"completion_tokens\":3,\"total_tokens\":7}}\n\ndata: [DONE]\n\n";
+
+        counter.process_chunk(chunk1);
+        assert!(counter.process_chunk(chunk2).is_done);
+        assert_eq!(counter.finalize().total_tokens, Some(7));
+    }
+
+    #[test]
+    fn test_stream_timing() {
+        let start = Instant::now();
+        let mut timing = StreamTiming::new(start);
+        assert!(timing.time_to_first_token().is_none());
+
+        timing.record_chunk(start + Duration::from_millis(200));
+        timing.record_chunk(start + Duration::from_millis(250));
+        timing.record_chunk(start + Duration::from_millis(300));
+
+        assert_eq!(
+            timing.time_to_first_token(),
+            Some(Duration::from_millis(200))
+        );
+        // Two gaps between three chunks, or four gaps between five tokens
+        assert_eq!(
+            timing.inter_token_latency(None),
+            Some(Duration::from_millis(50))
+        );
+        assert_eq!(
+            timing.inter_token_latency(Some(5)),
+            Some(Duration::from_millis(25))
+        );
+    }
+
+    #[test]
+    fn test_ttft_tracker_selects_fastest() {
+        let tracker = TtftTracker::new();
+        let targets = vec!["10.0.0.1:8000".to_string(), "10.0.0.2:8000".to_string()];
+
+        // Unmeasured targets are tried first
+        tracker.record("llm", "10.0.0.1:8000", Duration::from_millis(400));
+        assert_eq!(tracker.select("llm", &targets), Some(&targets[1]));
+
+        tracker.record("llm", "10.0.0.2:8000", Duration::from_millis(900));
+        assert_eq!(tracker.select("llm", &targets), Some(&targets[0]));
+        assert_eq!(tracker.best_ms("llm"), Some(400.0));
+
+        // The average moves towards new samples
+        tracker.record("llm", "10.0.0.1:8000", Duration::from_millis(1400));
+        let ewma = tracker.ewma_ms("llm", "10.0.0.1:8000").unwrap();
+        assert!((ewma - 700.0).abs() < 1e-6);
+        assert_eq!(tracker.select("llm", &targets), Some(&targets[0]));
+        assert!(tracker.best_ms("other").is_none());
+    }
 }
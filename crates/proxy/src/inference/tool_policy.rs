@@ -0,0 +1,837 @@
+//! Tool/function-call policy for inference routes.
+//!
+//! Agentic clients offer the model tools (`tools`, or the legacy OpenAI
+//! `functions`) and the model answers with tool calls. The policy checks the
+//! tool names on both sides against the route's allow and deny lists, merged
+//! with the tenant's, and logs, strips or blocks the ones not allowed.
+//!
+//! Bodies are read in the OpenAI and Anthropic formats, streamed or not; the
+//! format of each response is recognised from its JSON, so OpenAI-compatible
+//! providers are covered too. Tools in other formats are not inspected.
+
+use std::collections::HashSet;
+
+use sentinel_agent_protocol::{DetectionSeverity, GuardrailDetection};
+use sentinel_config::{InferenceProvider, ToolPolicyAction, ToolPolicyConfig};
+use serde_json::{json, Map, Value};
+
+use super::stream_guard::{error_event, event_data, rewrite_event};
+use crate::client_cert::glob_matches;
+
+/// Detection category of tool policy violations
+pub const TOOL_POLICY_CATEGORY: &str = "tool_policy";
+
+/// Largest request body held back for inspection
+pub const MAX_TOOL_POLICY_REQUEST_BYTES: usize = 10 * 1024 * 1024;
+
+/// Largest non-streaming response held back for inspection
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// A tool definition or tool call checked against the policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolUse {
+    /// Tool (function) name
+    pub name: String,
+    /// Whether the policy allows the tool
+    pub allowed: bool,
+}
+
+impl ToolUse {
+    /// Guardrail detection for a disallowed tool.
+    pub fn detection(&self) -> GuardrailDetection {
+        GuardrailDetection::new(
+            TOOL_POLICY_CATEGORY,
+            format!("Tool '{}' is not allowed", self.name),
+        )
+        .with_severity(DetectionSeverity::High)
+    }
+}
+
+/// Tools found in a body, and the body with disallowed ones stripped.
+#[derive(Debug, Default)]
+pub struct ToolPolicyOutcome {
+    /// Tools in the order they were found
+    pub tools: Vec<ToolUse>,
+    /// Rewritten body, when the action is `strip` and tools were removed
+    pub body: Option<Vec<u8>>,
+}
+
+impl ToolPolicyOutcome {
+    /// Name of the first disallowed tool, if any.
+    pub fn first_violation(&self) -> Option<&str> {
+        self.tools
+            .iter()
+            .find(|tool| !tool.allowed)
+            .map(|tool| tool.name.as_str())
+    }
+}
+
+/// Tool policy of one request: the route's lists merged with the tenant's.
+#[derive(Debug, Clone)]
+pub struct ToolPolicy {
+    action: ToolPolicyAction,
+    allow: Vec<String>,
+    deny: Vec<String>,
+    block_status: u16,
+    block_message: Option<String>,
+}
+
+impl ToolPolicy {
+    /// Resolve the policy for a tenant. The tenant's allow list replaces the
+    /// route's when it has one; deny lists add up.
+    pub fn new(config: &ToolPolicyConfig, tenant: &str) -> Self {
+        let tenant_lists = config.tenants.get(tenant);
+        let allow = match tenant_lists {
+            Some(lists) if !lists.allow.is_empty() => lists.allow.clone(),
+            _ => config.allow.clone(),
+        };
+        let mut deny = config.deny.clone();
+        if let Some(lists) = tenant_lists {
+            deny.extend(lists.deny.iter().cloned());
+        }
+
+        Self {
+            action: config.action,
+            allow,
+            deny,
+            block_status: config.block_status,
+            block_message: config.block_message.clone(),
+        }
+    }
+
+    /// Action taken on disallowed tools.
+    pub fn action(&self) -> ToolPolicyAction {
+        self.action
+    }
+
+    /// Status of blocked requests.
+    pub fn block_status(&self) -> u16 {
+        self.block_status
+    }
+
+    /// Message for a blocked request or response.
+    pub fn block_message(&self, tool: &str) -> String {
+        self.block_message
+            .clone()
+            .unwrap_or_else(|| format!("Tool '{}' is not allowed", tool))
+    }
+
+    /// Whether a tool may be used.
+    pub fn allows(&self, tool: &str) -> bool {
+        !self.deny.iter().any(|pattern| glob_matches(pattern, tool))
+            && (self.allow.is_empty()
+                || self.allow.iter().any(|pattern| glob_matches(pattern, tool)))
+    }
+
+    /// Record a tool, returning whether it stays in the body.
+    fn keep(&self, name: Option<&Value>, tools: &mut Vec<ToolUse>) -> bool {
+        let Some(name) = name.and_then(Value::as_str) else {
+            return true;
+        };
+        let allowed = self.allows(name);
+        tools.push(ToolUse {
+            name: name.to_string(),
+            allowed,
+        });
+        allowed || self.action != ToolPolicyAction::Strip
+    }
+
+    /// Check the tools a request offers the model.
+    pub fn check_request(&self, body: &[u8]) -> ToolPolicyOutcome {
+        let Ok(mut json) = serde_json::from_slice::<Value>(body) else {
+            return ToolPolicyOutcome::default();
+        };
+        let mut tools = Vec::new();
+        let Some(request) = json.as_object_mut() else {
+            return ToolPolicyOutcome::default();
+        };
+
+        // OpenAI nests the name under `function`; Anthropic and legacy
+        // OpenAI `functions` have it at the top
+        for key in ["tools", "functions"] {
+            if let Some(definitions) = request.get_mut(key).and_then(Value::as_array_mut) {
+                definitions.retain(|definition| {
+                    let name = definition
+                        .pointer("/function/name")
+                        .or_else(|| definition.get("name"));
+                    self.keep(name, &mut tools)
+                });
+            }
+        }
+
+        let removed: Vec<&str> = tools
+            .iter()
+            .filter(|tool| !tool.allowed)
+            .map(|tool| tool.name.as_str())
+            .collect();
+        if self.action != ToolPolicyAction::Strip || removed.is_empty() {
+            return ToolPolicyOutcome { tools, body: None };
+        }
+
+        for key in ["tools", "functions"] {
+            if request
+                .get(key)
+                .and_then(Value::as_array)
+                .is_some_and(|definitions| definitions.is_empty())
+            {
+                request.remove(key);
+            }
+        }
+        // A forced choice of a removed tool, or any choice without tools left,
+        // would be rejected by the provider
+        let no_tools = !request.contains_key("tools") && !request.contains_key("functions");
+        for key in ["tool_choice", "function_call"] {
+            let forced = request.get(key).and_then(|choice| {
+                choice
+                    .pointer("/function/name")
+                    .or_else(|| choice.get("name"))
+                    .and_then(Value::as_str)
+            });
+            if request.contains_key(key)
+                && (no_tools || forced.is_some_and(|name| removed.contains(&name)))
+            {
+                request.remove(key);
+            }
+        }
+
+        let body = serde_json::to_vec(&json).ok();
+        ToolPolicyOutcome { tools, body }
+    }
+
+    /// Check the tool calls in a (non-streaming) response.
+    pub fn check_response(&self, body: &[u8]) -> ToolPolicyOutcome {
+        let Ok(mut json) = serde_json::from_slice::<Value>(body) else {
+            return ToolPolicyOutcome::default();
+        };
+        let mut tools = Vec::new();
+
+        // OpenAI: {"choices": [{"message": {"tool_calls": [{"function": {"name": ...}}]}}]}
+        if let Some(choices) = json.get_mut("choices").and_then(Value::as_array_mut) {
+            for choice in choices {
+                let Some(message) = choice.get_mut("message").and_then(Value::as_object_mut) else {
+                    continue;
+                };
+                if let Some(calls) = message.get_mut("tool_calls").and_then(Value::as_array_mut) {
+                    calls.retain(|call| self.keep(call.pointer("/function/name"), &mut tools));
+                }
+                let function_call = message.get("function_call").map(|call| call.get("name"));
+                if let Some(name) = function_call {
+                    if !self.keep(name, &mut tools) {
+                        message.remove("function_call");
+                    }
+                }
+                strip_openai_message(message);
+                let has_calls =
+                    message.contains_key("tool_calls") || message.contains_key("function_call");
+                finish_without_calls(choice, "/finish_reason", has_calls);
+            }
+        }
+
+        // Anthropic: {"content": [{"type": "tool_use", "name": ...}], "stop_reason": "tool_use"}
+        if let Some(content) = json.get_mut("content").and_then(Value::as_array_mut) {
+            content.retain(|block| !is_tool_use(block) || self.keep(block.get("name"), &mut tools));
+            let has_calls = content.iter().any(is_tool_use);
+            if !has_calls && json.get("stop_reason").and_then(Value::as_str) == Some("tool_use") {
+                json["stop_reason"] = json!("end_turn");
+            }
+        }
+
+        let stripped = tools.iter().any(|tool| !tool.allowed);
+        let body = (self.action == ToolPolicyAction::Strip && stripped)
+            .then(|| serde_json::to_vec(&json).ok())
+            .flatten();
+        ToolPolicyOutcome { tools, body }
+    }
+}
+
+/// Whether an Anthropic content block is a tool call.
+fn is_tool_use(block: &Value) -> bool {
+    matches!(
+        block.get("type").and_then(Value::as_str),
+        Some("tool_use") | Some("server_tool_use")
+    )
+}
+
+/// Drop an OpenAI message's emptied `tool_calls`, giving it text content.
+fn strip_openai_message(message: &mut Map<String, Value>) {
+    if message
+        .get("tool_calls")
+        .and_then(Value::as_array)
+        .is_some_and(|calls| calls.is_empty())
+    {
+        message.remove("tool_calls");
+        if message.get("content").is_some_and(Value::is_null) {
+            message.insert("content".to_string(), json!(""));
+        }
+    }
+}
+
+/// Turn a `tool_calls` finish reason into `stop` once no calls are left.
+fn finish_without_calls(choice: &mut Value, pointer: &str, has_calls: bool) -> bool {
+    let Some(reason) = choice.pointer_mut(pointer) else {
+        return false;
+    };
+    if has_calls || !matches!(reason.as_str(), Some("tool_calls") | Some("function_call")) {
+        return false;
+    }
+    *reason = json!("stop");
+    true
+}
+
+/// What happens to a streamed event.
+enum EventEdit {
+    Keep,
+    Rewrite,
+    Drop,
+}
+
+/// Applies the tool policy to a response as it passes through.
+///
+/// Streamed responses are filtered one event at a time: a tool call is
+/// decided when its name arrives, and the later deltas of a stripped call are
+/// removed with it. Non-streaming responses are held back and checked whole.
+/// A response, or an event, over 1 MiB ends with an error under `block` and
+/// passes through unchecked otherwise.
+#[derive(Debug)]
+pub struct ToolCallFilter {
+    policy: ToolPolicy,
+    provider: InferenceProvider,
+    streaming: bool,
+    /// Bytes of an incomplete event, or the held back response
+    buffer: Vec<u8>,
+    /// Streamed tool calls not allowed, by choice and call index
+    denied: HashSet<(u64, u64)>,
+    /// Whether a streamed tool call was allowed
+    allowed_calls: bool,
+    /// Tools checked since the last `take_tools`
+    tools: Vec<ToolUse>,
+    /// The response was too large to hold and is passed through
+    passthrough: bool,
+    terminated: bool,
+}
+
+impl ToolCallFilter {
+    /// Create a filter for a response in the given provider's format.
+    pub fn new(policy: ToolPolicy, provider: InferenceProvider, streaming: bool) -> Self {
+        Self {
+            policy,
+            provider,
+            streaming,
+            buffer: Vec::new(),
+            denied: HashSet::new(),
+            allowed_calls: false,
+            tools: Vec::new(),
+            passthrough: false,
+            terminated: false,
+        }
+    }
+
+    /// Add a chunk of the response, returning the bytes that can be sent now.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<u8> {
+        if self.terminated {
+            return Vec::new();
+        }
+        if self.passthrough {
+            return chunk.to_vec();
+        }
+        if !self.streaming {
+            self.buffer.extend_from_slice(chunk);
+            if self.buffer.len() > MAX_RESPONSE_BYTES {
+                return self.too_large();
+            }
+            return Vec::new();
+        }
+
+        self.buffer.extend(chunk.iter().filter(|&&b| b != b'\r'));
+        let delimiter: &[u8] = match self.provider {
+            InferenceProvider::Ollama => b"\n",
+            _ => b"\n\n",
+        };
+        let mut out = Vec::new();
+        while let Some(end) = self
+            .buffer
+            .windows(delimiter.len())
+            .position(|w| w == delimiter)
+        {
+            let event: Vec<u8> = self.buffer.drain(..end + delimiter.len()).collect();
+            out.extend(self.filter_event(String::from_utf8_lossy(&event).into_owned()));
+            if self.terminated {
+                break;
+            }
+        }
+        if !self.terminated && self.buffer.len() > MAX_RESPONSE_BYTES {
+            out.extend(self.too_large());
+        }
+        out
+    }
+
+    /// Release what is held back, at end of response.
+    pub fn finish(&mut self) -> Vec<u8> {
+        if self.terminated || self.buffer.is_empty() {
+            return Vec::new();
+        }
+        let body = std::mem::take(&mut self.buffer);
+        if self.streaming {
+            return self.filter_event(String::from_utf8_lossy(&body).into_owned());
+        }
+
+        let outcome = self.policy.check_response(&body);
+        let violation = outcome.first_violation().map(str::to_string);
+        self.tools.extend(outcome.tools);
+        match (self.policy.action, violation) {
+            (ToolPolicyAction::Block, Some(tool)) => {
+                self.terminated = true;
+                error_body(self.provider, &self.policy.block_message(&tool))
+            }
+            _ => outcome.body.unwrap_or(body),
+        }
+    }
+
+    /// Action taken on disallowed tool calls.
+    pub fn action(&self) -> ToolPolicyAction {
+        self.policy.action
+    }
+
+    /// Tools checked since the last call.
+    pub fn take_tools(&mut self) -> Vec<ToolUse> {
+        std::mem::take(&mut self.tools)
+    }
+
+    /// Whether the response was replaced by an error.
+    pub fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    /// Whether the response was too large to hold and passes through
+    /// unchecked.
+    pub fn is_uninspected(&self) -> bool {
+        self.passthrough
+    }
+
+    /// Give up on a response, or an event, over the size limit: `block` ends
+    /// it with an error, other actions let the rest through unchecked.
+    fn too_large(&mut self) -> Vec<u8> {
+        let held = std::mem::take(&mut self.buffer);
+        if self.policy.action == ToolPolicyAction::Block {
+            self.terminated = true;
+            let message = "Response too large for tool policy inspection";
+            return if self.streaming {
+                error_event(self.provider, message)
+            } else {
+                error_body(self.provider, message)
+            };
+        }
+        self.passthrough = true;
+        held
+    }
+
+    fn filter_event(&mut self, raw: String) -> Vec<u8> {
+        let Some(mut json) =
+            event_data(&raw).and_then(|data| serde_json::from_str::<Value>(&data).ok())
+        else {
+            return raw.into_bytes();
+        };
+
+        let checked = self.tools.len();
+        let edit = if json.get("choices").is_some() {
+            self.filter_openai_event(&mut json)
+        } else {
+            self.filter_anthropic_event(&mut json)
+        };
+        let violation = self.tools[checked..]
+            .iter()
+            .find(|tool| !tool.allowed)
+            .map(|tool| tool.name.clone());
+
+        match (self.policy.action, violation, edit) {
+            (ToolPolicyAction::Block, Some(tool), _) => {
+                self.terminated = true;
+                self.buffer.clear();
+                error_event(self.provider, &self.policy.block_message(&tool))
+            }
+            (ToolPolicyAction::Strip, _, EventEdit::Rewrite) => rewrite_event(&raw, &json),
+            (ToolPolicyAction::Strip, _, EventEdit::Drop) => Vec::new(),
+            _ => raw.into_bytes(),
+        }
+    }
+
+    /// Record a streamed tool call by its name, once it arrives.
+    fn record_call(&mut self, key: (u64, u64), name: Option<&str>) {
+        let Some(name) = name else {
+            return;
+        };
+        let allowed = self.policy.allows(name);
+        if allowed {
+            self.allowed_calls = true;
+        } else {
+            self.denied.insert(key);
+        }
+        self.tools.push(ToolUse {
+            name: name.to_string(),
+            allowed,
+        });
+    }
+
+    /// OpenAI: `choices[].delta.tool_calls[]`, the name in the first delta of
+    /// each call index; or the legacy `delta.function_call`.
+    fn filter_openai_event(&mut self, json: &mut Value) -> EventEdit {
+        let mut edit = EventEdit::Keep;
+        let Some(choices) = json.get_mut("choices").and_then(Value::as_array_mut) else {
+            return edit;
+        };
+
+        for choice in choices {
+            let choice_index = choice.get("index").and_then(Value::as_u64).unwrap_or(0);
+            if let Some(delta) = choice.get_mut("delta").and_then(Value::as_object_mut) {
+                if let Some(calls) = delta.get_mut("tool_calls").and_then(Value::as_array_mut) {
+                    let before = calls.len();
+                    calls.retain(|call| {
+                        let key = (
+                            choice_index,
+                            call.get("index").and_then(Value::as_u64).unwrap_or(0),
+                        );
+                        self.record_call(
+                            key,
+                            call.pointer("/function/name").and_then(Value::as_str),
+                        );
+                        !self.denied.contains(&key)
+                    });
+                    if calls.len() != before {
+                        edit = EventEdit::Rewrite;
+                        if calls.is_empty() {
+                            delta.remove("tool_calls");
+                        }
+                    }
+                }
+
+                let key = (choice_index, u64::MAX);
+                if let Some(call) = delta.get("function_call") {
+                    let name = call.get("name").and_then(Value::as_str).map(str::to_string);
+                    self.record_call(key, name.as_deref());
+                    if self.denied.contains(&key) {
+                        delta.remove("function_call");
+                        edit = EventEdit::Rewrite;
+                    }
+                }
+            }
+
+            if !self.denied.is_empty()
+                && finish_without_calls(choice, "/finish_reason", self.allowed_calls)
+            {
+                edit = EventEdit::Rewrite;
+            }
+        }
+        edit
+    }
+
+    /// Anthropic: a `content_block_start` names the tool of a content block,
+    /// whose `content_block_delta` and `content_block_stop` events follow.
+    fn filter_anthropic_event(&mut self, json: &mut Value) -> EventEdit {
+        let index = json.get("index").and_then(Value::as_u64);
+        match json.get("type").and_then(Value::as_str) {
+            Some("content_block_start") => {
+                let block = json.get("content_block").filter(|block| is_tool_use(block));
+                if let (Some(block), Some(index)) = (block, index) {
+                    let name = block
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .map(str::to_string);
+                    self.record_call((0, index), name.as_deref());
+                }
+            }
+            Some("message_delta") => {
+                let tool_use =
+                    json.pointer("/delta/stop_reason").and_then(Value::as_str) == Some("tool_use");
+                if tool_use && !self.denied.is_empty() && !self.allowed_calls {
+                    json["delta"]["stop_reason"] = json!("end_turn");
+                    return EventEdit::Rewrite;
+                }
+            }
+            _ => {}
+        }
+
+        match index {
+            Some(index) if self.denied.contains(&(0, index)) => EventEdit::Drop,
+            _ => EventEdit::Keep,
+        }
+    }
+}
+
+/// Error body replacing a blocked non-streaming response.
+fn error_body(provider: InferenceProvider, message: &str) -> Vec<u8> {
+    match provider {
+        InferenceProvider::Anthropic => json!({
+            "type": "error",
+            "error": {"type": "invalid_request_error", "message": message},
+        }),
+        _ => json!({
+            "error": {
+                "message": message,
+                "type": "invalid_request_error",
+                "code": "tool_not_allowed",
+            },
+        }),
+    }
+    .to_string()
+    .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sentinel_config::ToolListConfig;
+    use std::collections::HashMap;
+
+    fn policy(action: ToolPolicyAction) -> ToolPolicy {
+        let config = ToolPolicyConfig {
+            enabled: true,
+            action,
+            allow: vec!["search_*".to_string(), "get_weather".to_string()],
+            deny: vec!["search_private".to_string()],
+            tenants: HashMap::from([(
+                "ops".to_string(),
+                ToolListConfig {
+                    allow: vec!["*".to_string()],
+                    deny: vec!["drop_table".to_string()],
+                },
+            )]),
+            block_status: 403,
+            block_message: None,
+        };
+        ToolPolicy::new(&config, "team-a")
+    }
+
+    fn data_events(bytes: &[u8]) -> Vec<Value> {
+        String::from_utf8_lossy(bytes)
+            .split("\n\n")
+            .filter_map(|event| event.lines().find_map(|line| line.strip_prefix("data: ")))
+            .filter_map(|data| serde_json::from_str(data).ok())
+            .collect()
+    }
+
+    #[test]
+    fn test_allow_and_deny_lists() {
+        let policy = policy(ToolPolicyAction::Block);
+        assert!(policy.allows("search_web"));
+        assert!(policy.allows("get_weather"));
+        assert!(!policy.allows("search_private"));
+        assert!(!policy.allows("run_shell"));
+
+        // The tenant's allow list replaces the route's; deny lists add up
+        let config = ToolPolicyConfig {
+            enabled: true,
+            action: ToolPolicyAction::Block,
+            allow: vec!["get_weather".to_string()],
+            deny: vec!["run_shell".to_string()],
+            tenants: HashMap::from([(
+                "ops".to_string(),
+                ToolListConfig {
+                    allow: vec!["*".to_string()],
+                    deny: vec!["drop_table".to_string()],
+                },
+            )]),
+            block_status: 403,
+            block_message: None,
+        };
+        let ops = ToolPolicy::new(&config, "ops");
+        assert!(ops.allows("run_sql"));
+        assert!(!ops.allows("run_shell"));
+        assert!(!ops.allows("drop_table"));
+    }
+
+    #[test]
+    fn test_strip_request_tools() {
+        let body = json!({
+            "model": "gpt-4o",
+            "tools": [
+                {"type": "function", "function": {"name": "search_web"}},
+                {"type": "function", "function": {"name": "run_shell"}},
+            ],
+            "tool_choice": {"type": "function", "function": {"name": "run_shell"}},
+        });
+        let outcome = policy(ToolPolicyAction::Strip).check_request(body.to_string().as_bytes());
+        assert_eq!(outcome.first_violation(), Some("run_shell"));
+        assert_eq!(outcome.tools.len(), 2);
+
+        let stripped: Value = serde_json::from_slice(&outcome.body.unwrap()).unwrap();
+        assert_eq!(stripped["tools"].as_array().unwrap().len(), 1);
+        assert!(stripped.get("tool_choice").is_none());
+
+        // Anthropic definitions; nothing to strip keeps the body as is
+        let body = json!({"tools": [{"name": "get_weather", "input_schema": {}}]});
+        let outcome = policy(ToolPolicyAction::Strip).check_request(body.to_string().as_bytes());
+        assert!(outcome.first_violation().is_none());
+        assert!(outcome.body.is_none());
+    }
+
+    #[test]
+    fn test_check_response() {
+        let openai = json!({
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{"id": "c1", "type": "function", "function": {"name": "run_shell", "arguments": "{}"}}],
+                },
+                "finish_reason": "tool_calls",
+            }],
+        });
+        let outcome = policy(ToolPolicyAction::Strip).check_response(openai.to_string().as_bytes());
+        let stripped: Value = serde_json::from_slice(&outcome.body.unwrap()).unwrap();
+        assert!(stripped["choices"][0]["message"]
+            .get("tool_calls")
+            .is_none());
+        assert_eq!(stripped["choices"][0]["message"]["content"], "");
+        assert_eq!(stripped["choices"][0]["finish_reason"], "stop");
+
+        let anthropic = json!({
+            "content": [
+                {"type": "text", "text": "Let me check."},
+                {"type": "tool_use", "id": "t1", "name": "get_weather", "input": {}},
+            ],
+            "stop_reason": "tool_use",
+        });
+        let outcome =
+            policy(ToolPolicyAction::Strip).check_response(anthropic.to_string().as_bytes());
+        assert_eq!(
+            outcome.tools,
+            vec![ToolUse {
+                name: "get_weather".to_string(),
+                allowed: true
+            }]
+        );
+        assert!(outcome.body.is_none());
+
+        let mut filter = ToolCallFilter::new(
+            policy(ToolPolicyAction::Block),
+            InferenceProvider::OpenAi,
+            false,
+        );
+        assert!(filter.push(openai.to_string().as_bytes()).is_empty());
+        let error: Value = serde_json::from_slice(&filter.finish()).unwrap();
+        assert_eq!(error["error"]["code"], "tool_not_allowed");
+        assert!(filter.is_terminated());
+    }
+
+    #[test]
+    fn test_strip_streamed_openai_calls() {
+        let event = |calls: Value, finish: Value| {
+            format!(
+                "data: {}\n\n",
+                json!({"choices": [{"index": 0, "delta": {"tool_calls": calls}, "finish_reason": finish}]})
+            )
+        };
+        let mut filter = ToolCallFilter::new(
+            policy(ToolPolicyAction::Strip),
+            InferenceProvider::OpenAi,
+            true,
+        );
+
+        let stream = [
+            event(
+                json!([{"index": 0, "function": {"name": "run_shell", "arguments": ""}}]),
+                Value::Null,
+            ),
+            event(
+                json!([{"index": 0, "function": {"arguments": "{\"cmd\":"}}]),
+                Value::Null,
+            ),
+            event(json!([]), json!("tool_calls")),
+        ]
+        .concat();
+        let mut out = filter.push(stream.as_bytes());
+        out.extend(filter.push(b"data: [DONE]\n\n"));
+
+        let events = data_events(&out);
+        assert_eq!(events.len(), 3);
+        assert!(events[0]["choices"][0]["delta"].get("tool_calls").is_none());
+        assert!(events[1]["choices"][0]["delta"].get("tool_calls").is_none());
+        assert_eq!(events[2]["choices"][0]["finish_reason"], "stop");
+        assert!(String::from_utf8_lossy(&out).ends_with("data: [DONE]\n\n"));
+        assert_eq!(filter.take_tools().len(), 1);
+    }
+
+    #[test]
+    fn test_streamed_anthropic_calls() {
+        let events = [
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "tool_use", "id": "t1", "name": "run_shell", "input": {}}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "{}"}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}}),
+        ];
+        let stream: String = events
+            .iter()
+            .map(|event| {
+                format!(
+                    "event: {}\ndata: {}\n\n",
+                    event["type"].as_str().unwrap(),
+                    event
+                )
+            })
+            .collect();
+
+        let mut filter = ToolCallFilter::new(
+            policy(ToolPolicyAction::Strip),
+            InferenceProvider::Anthropic,
+            true,
+        );
+        let out = data_events(&filter.push(stream.as_bytes()));
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[0]["index"], 0);
+        assert_eq!(out[1]["delta"]["stop_reason"], "end_turn");
+
+        let mut filter = ToolCallFilter::new(
+            policy(ToolPolicyAction::Block),
+            InferenceProvider::Anthropic,
+            true,
+        );
+        let out = String::from_utf8(filter.push(stream.as_bytes())).unwrap();
+        assert!(out.contains("event: error\n"));
+        assert!(out.contains("Tool 'run_shell' is not allowed"));
+        assert!(filter.is_terminated());
+        assert_eq!(
+            filter.take_tools()[0].detection().category,
+            TOOL_POLICY_CATEGORY
+        );
+    }
+
+    #[test]
+    fn test_oversized_response() {
+        let large = vec![b'x'; MAX_RESPONSE_BYTES + 1];
+
+        let mut filter = ToolCallFilter::new(
+            policy(ToolPolicyAction::Block),
+            InferenceProvider::OpenAi,
+            false,
+        );
+        let error: Value = serde_json::from_slice(&filter.push(&large)).unwrap();
+        assert!(error["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("too large"));
+        assert!(filter.is_terminated());
+        assert!(filter.push(b"more").is_empty());
+
+        // A stream with no event delimiter is capped the same way
+        let mut filter = ToolCallFilter::new(
+            policy(ToolPolicyAction::Block),
+            InferenceProvider::Anthropic,
+            true,
+        );
+        let out = String::from_utf8(filter.push(&large)).unwrap();
+        assert!(out.contains("event: error\n"));
+        assert!(filter.is_terminated());
+
+        let mut filter = ToolCallFilter::new(
+            policy(ToolPolicyAction::Strip),
+            InferenceProvider::OpenAi,
+            true,
+        );
+        assert_eq!(filter.push(&large).len(), large.len());
+        assert!(filter.is_uninspected());
+        assert_eq!(filter.push(b"data: {}\n\n"), b"data: {}\n\n");
+        assert!(!filter.is_terminated());
+    }
+}
@@ -0,0 +1,1397 @@
+//! Request and response translation between inference provider APIs.
+//!
+//! Fallback and model-based routing can send a request to an upstream whose
+//! provider speaks a different API than the client. [`InferenceTranslator`]
+//! converts between OpenAI Chat Completions and Anthropic Messages so the
+//! upstream receives its own schema and the client gets back the one it sent:
+//!
+//! - Requests: system prompts, text and image content, tools, tool choice,
+//!   tool calls and tool results, sampling parameters
+//! - Responses: content, tool calls, stop reasons and usage
+//! - SSE streams, event by event ([`StreamTranslator`])
+//! - Error bodies
+//!
+//! A provider's format comes from [`InferenceProviderAdapter::api_format`];
+//! providers without one (generic) are never translated.
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use super::providers::InferenceProviderAdapter;
+
+/// Anthropic requires `max_tokens`; used when an OpenAI request has none
+const DEFAULT_ANTHROPIC_MAX_TOKENS: u64 = 4096;
+
+/// `anthropic-version` header sent with translated requests
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Largest non-streaming body held back for translation, in either direction
+pub const MAX_TRANSLATION_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// Inference API schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiFormat {
+    /// OpenAI Chat Completions
+    OpenAiChat,
+    /// Anthropic Messages
+    AnthropicMessages,
+}
+
+impl ApiFormat {
+    /// Request path of the API.
+    pub fn path(&self) -> &'static str {
+        match self {
+            Self::OpenAiChat => "/v1/chat/completions",
+            Self::AnthropicMessages => "/v1/messages",
+        }
+    }
+
+    /// Returns the string label for this format (for metrics and logging).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OpenAiChat => "openai",
+            Self::AnthropicMessages => "anthropic",
+        }
+    }
+}
+
+/// Translation failure.
+#[derive(Debug, Error)]
+pub enum TranslationError {
+    #[error("Body is not valid JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    #[error("Body is not a JSON object")]
+    NotAnObject,
+}
+
+/// Translates between the client's and the upstream's inference API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InferenceTranslator {
+    client: ApiFormat,
+    upstream: ApiFormat,
+}
+
+impl InferenceTranslator {
+    /// Create a translator, or `None` if both sides speak the same API or
+    /// either side has no supported format.
+    pub fn new(
+        client: &dyn InferenceProviderAdapter,
+        upstream: &dyn InferenceProviderAdapter,
+    ) -> Option<Self> {
+        let client = client.api_format()?;
+        let upstream = upstream.api_format()?;
+        (client != upstream).then_some(Self { client, upstream })
+    }
+
+    /// API the client speaks.
+    pub fn client_format(&self) -> ApiFormat {
+        self.client
+    }
+
+    /// API the upstream speaks.
+    pub fn upstream_format(&self) -> ApiFormat {
+        self.upstream
+    }
+
+    /// Path and query for the translated upstream request.
+    ///
+    /// Whatever precedes the client API's endpoint in `uri` (e.g. `/openai` in
+    /// `/openai/v1/chat/completions`) is kept as the base path, and the query
+    /// string is carried over unchanged.
+    pub fn upstream_path_and_query(&self, uri: &http::Uri) -> String {
+        let path = uri.path();
+        let base = path
+            .strip_suffix(self.client.path())
+            .unwrap_or_default()
+            .trim_end_matches('/');
+        match uri.query() {
+            Some(query) => format!("{}{}?{}", base, self.upstream.path(), query),
+            None => format!("{}{}", base, self.upstream.path()),
+        }
+    }
+
+    /// Translate a client request body into the upstream's API, optionally
+    /// replacing the model.
+    pub fn translate_request(
+        &self,
+        body: &[u8],
+        model: Option<&str>,
+    ) -> Result<Vec<u8>, TranslationError> {
+        let mut request = parse_object(body)?;
+        if let Some(model) = model {
+            request.insert("model".to_string(), Value::String(model.to_string()));
+        }
+
+        let translated = match self.upstream {
+            ApiFormat::AnthropicMessages => openai_to_anthropic_request(request),
+            ApiFormat::OpenAiChat => anthropic_to_openai_request(request),
+        };
+        Ok(serde_json::to_vec(&translated)?)
+    }
+
+    /// Translate a complete (non-streaming) upstream response body, including
+    /// error bodies, into the client's API.
+    pub fn translate_response(&self, body: &[u8]) -> Result<Vec<u8>, TranslationError> {
+        let response = parse_object(body)?;
+        let is_error = response.get("error").is_some_and(Value::is_object);
+
+        let translated = match (self.upstream, is_error) {
+            (ApiFormat::OpenAiChat, true) => openai_to_anthropic_error(&response),
+            (ApiFormat::OpenAiChat, false) => openai_to_anthropic_response(&response),
+            (ApiFormat::AnthropicMessages, true) => anthropic_to_openai_error(&response),
+            (ApiFormat::AnthropicMessages, false) => anthropic_to_openai_response(&response),
+        };
+        Ok(serde_json::to_vec(&translated)?)
+    }
+
+    /// Create a translator for a streaming (SSE) upstream response.
+    pub fn stream_translator(&self) -> StreamTranslator {
+        StreamTranslator::new(self.upstream)
+    }
+}
+
+// ============================================================================
+// Requests
+// ============================================================================
+
+fn openai_to_anthropic_request(mut request: Map<String, Value>) -> Value {
+    let mut out = Map::new();
+    insert_some(&mut out, "model", request.remove("model"));
+
+    let mut system = Vec::new();
+    let mut messages = Vec::new();
+    for message in take_array(&mut request, "messages") {
+        let role = message
+            .get("role")
+            .and_then(Value::as_str)
+            .unwrap_or("user");
+        match role {
+            "system" | "developer" => system.push(text_of(message.get("content"))),
+            "tool" => {
+                let block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": message.get("tool_call_id").cloned().unwrap_or(Value::Null),
+                    "content": text_of(message.get("content")),
+                });
+                push_anthropic_message(&mut messages, "user", vec![block]);
+            }
+            "assistant" => {
+                let mut blocks = anthropic_blocks(message.get("content"));
+                blocks.extend(tool_calls_of(&message).iter().map(tool_use_block));
+                push_anthropic_message(&mut messages, "assistant", blocks);
+            }
+            _ => {
+                let blocks = anthropic_blocks(message.get("content"));
+                push_anthropic_message(&mut messages, "user", blocks);
+            }
+        }
+    }
+
+    if !system.is_empty() {
+        out.insert("system".to_string(), Value::String(system.join("\n\n")));
+    }
+    out.insert("messages".to_string(), Value::Array(messages));
+
+    let max_tokens = request
+        .remove("max_completion_tokens")
+        .or_else(|| request.remove("max_tokens"))
+        .filter(|v| !v.is_null())
+        .unwrap_or_else(|| json!(DEFAULT_ANTHROPIC_MAX_TOKENS));
+    out.insert("max_tokens".to_string(), max_tokens);
+
+    insert_some(&mut out, "temperature", request.remove("temperature"));
+    insert_some(&mut out, "top_p", request.remove("top_p"));
+    insert_some(&mut out, "stream", request.remove("stream"));
+    match request.remove("stop") {
+        Some(Value::String(stop)) => {
+            out.insert("stop_sequences".to_string(), json!([stop]));
+        }
+        Some(stop @ Value::Array(_)) => {
+            out.insert("stop_sequences".to_string(), stop);
+        }
+        _ => {}
+    }
+
+    let tools: Vec<Value> = take_array(&mut request, "tools")
+        .iter()
+        .filter_map(|tool| tool.get("function"))
+        .map(|function| {
+            let mut tool = Map::new();
+            insert_some(&mut tool, "name", function.get("name").cloned());
+            insert_some(
+                &mut tool,
+                "description",
+                function.get("description").cloned(),
+            );
+            tool.insert(
+                "input_schema".to_string(),
+                function
+                    .get("parameters")
+                    .cloned()
+                    .unwrap_or_else(|| json!({"type": "object"})),
+            );
+            Value::Object(tool)
+        })
+        .collect();
+    if !tools.is_empty() {
+        out.insert("tools".to_string(), Value::Array(tools));
+    }
+
+    let mut tool_choice = match request.remove("tool_choice") {
+        Some(Value::String(choice)) => match choice.as_str() {
+            "required" => Some(json!({"type": "any"})),
+            "none" => Some(json!({"type": "none"})),
+            _ => Some(json!({"type": "auto"})),
+        },
+        Some(choice @ Value::Object(_)) => choice
+            .pointer("/function/name")
+            .map(|name| json!({"type": "tool", "name": name})),
+        _ => None,
+    };
+    if request.get("parallel_tool_calls") == Some(&Value::Bool(false)) {
+        let choice = tool_choice.get_or_insert_with(|| json!({"type": "auto"}));
+        choice["disable_parallel_tool_use"] = Value::Bool(true);
+    }
+    insert_some(&mut out, "tool_choice", tool_choice);
+
+    if let Some(user) = request.remove("user") {
+        out.insert("metadata".to_string(), json!({"user_id": user}));
+    }
+
+    Value::Object(out)
+}
+
+fn anthropic_to_openai_request(mut request: Map<String, Value>) -> Value {
+    let mut out = Map::new();
+    insert_some(&mut out, "model", request.remove("model"));
+
+    let mut messages = Vec::new();
+    if let Some(system) = request.remove("system") {
+        let system = text_of(Some(&system));
+        if !system.is_empty() {
+            messages.push(json!({"role": "system", "content": system}));
+        }
+    }
+
+    for message in take_array(&mut request, "messages") {
+        let role = message
+            .get("role")
+            .and_then(Value::as_str)
+            .unwrap_or("user");
+        let blocks = match message.get("content") {
+            Some(Value::String(text)) => {
+                messages.push(json!({"role": role, "content": text}));
+                continue;
+            }
+            Some(Value::Array(blocks)) => blocks,
+            _ => continue,
+        };
+
+        let mut parts = Vec::new();
+        let mut tool_calls = Vec::new();
+        for block in blocks {
+            match block.get("type").and_then(Value::as_str) {
+                Some("text") => parts.push(json!({
+                    "type": "text",
+                    "text": block.get("text").cloned().unwrap_or_default(),
+                })),
+                Some("image") => {
+                    if let Some(url) = image_url_of(block) {
+                        parts.push(json!({"type": "image_url", "image_url": {"url": url}}));
+                    }
+                }
+                Some("tool_use") => tool_calls.push(openai_tool_call(block)),
+                // Tool results lead Anthropic user turns, so emitting them as
+                // they come keeps them right after the assistant's tool calls
+                Some("tool_result") => messages.push(json!({
+                    "role": "tool",
+                    "tool_call_id": block.get("tool_use_id").cloned().unwrap_or_default(),
+                    "content": text_of(block.get("content")),
+                })),
+                _ => {}
+            }
+        }
+
+        if parts.is_empty() && tool_calls.is_empty() {
+            continue;
+        }
+        let content = if role == "assistant" {
+            let text = parts
+                .iter()
+                .filter_map(|part| part.get("text").and_then(Value::as_str))
+                .collect::<String>();
+            if text.is_empty() {
+                Value::Null
+            } else {
+                Value::String(text)
+            }
+        } else {
+            Value::Array(parts)
+        };
+        let mut translated = json!({"role": role, "content": content});
+        if !tool_calls.is_empty() {
+            translated["tool_calls"] = Value::Array(tool_calls);
+        }
+        messages.push(translated);
+    }
+    out.insert("messages".to_string(), Value::Array(messages));
+
+    insert_some(&mut out, "max_tokens", request.remove("max_tokens"));
+    insert_some(&mut out, "temperature", request.remove("temperature"));
+    insert_some(&mut out, "top_p", request.remove("top_p"));
+    insert_some(&mut out, "stop", request.remove("stop_sequences"));
+    if let Some(stream) = request.remove("stream") {
+        if stream == Value::Bool(true) {
+            // Usage only arrives in the stream when asked for
+            out.insert("stream_options".to_string(), json!({"include_usage": true}));
+        }
+        out.insert("stream".to_string(), stream);
+    }
+
+    let tools: Vec<Value> = take_array(&mut request, "tools")
+        .iter()
+        .map(|tool| {
+            let mut function = Map::new();
+            insert_some(&mut function, "name", tool.get("name").cloned());
+            insert_some(
+                &mut function,
+                "description",
+                tool.get("description").cloned(),
+            );
+            insert_some(
+                &mut function,
+                "parameters",
+                tool.get("input_schema").cloned(),
+            );
+            json!({"type": "function", "function": function})
+        })
+        .collect();
+    if !tools.is_empty() {
+        out.insert("tools".to_string(), Value::Array(tools));
+    }
+
+    if let Some(choice) = request.remove("tool_choice") {
+        let translated = match choice.get("type").and_then(Value::as_str) {
+            Some("any") => json!("required"),
+            Some("none") => json!("none"),
+            Some("tool") => json!({
+                "type": "function",
+                "function": {"name": choice.get("name").cloned().unwrap_or_default()},
+            }),
+            _ => json!("auto"),
+        };
+        out.insert("tool_choice".to_string(), translated);
+        if choice.get("disable_parallel_tool_use") == Some(&Value::Bool(true)) {
+            out.insert("parallel_tool_calls".to_string(), Value::Bool(false));
+        }
+    }
+
+    if let Some(user) = request
+        .get("metadata")
+        .and_then(|metadata| metadata.get("user_id"))
+    {
+        out.insert("user".to_string(), user.clone());
+    }
+
+    Value::Object(out)
+}
+
+// ============================================================================
+// Responses
+// ============================================================================
+
+fn openai_to_anthropic_response(response: &Map<String, Value>) -> Value {
+    let choice = response.get("choices").and_then(|choices| choices.get(0));
+    let message = choice.and_then(|choice| choice.get("message"));
+
+    let mut content = anthropic_blocks(message.and_then(|m| m.get("content")));
+    if let Some(message) = message {
+        content.extend(tool_calls_of(message).iter().map(tool_use_block));
+    }
+
+    let stop_reason = anthropic_stop_reason(
+        choice
+            .and_then(|choice| choice.get("finish_reason"))
+            .and_then(Value::as_str),
+    );
+    let usage = response.get("usage");
+
+    json!({
+        "id": response.get("id").cloned().unwrap_or_default(),
+        "type": "message",
+        "role": "assistant",
+        "model": response.get("model").cloned().unwrap_or_default(),
+        "content": content,
+        "stop_reason": stop_reason,
+        "stop_sequence": null,
+        "usage": {
+            "input_tokens": usage_field(usage, "prompt_tokens"),
+            "output_tokens": usage_field(usage, "completion_tokens"),
+        },
+    })
+}
+
+fn anthropic_to_openai_response(response: &Map<String, Value>) -> Value {
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    for block in response
+        .get("content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        match block.get("type").and_then(Value::as_str) {
+            Some("text") => text.push_str(block.get("text").and_then(Value::as_str).unwrap_or("")),
+            Some("tool_use") => tool_calls.push(openai_tool_call(block)),
+            _ => {}
+        }
+    }
+
+    let content = if text.is_empty() && !tool_calls.is_empty() {
+        Value::Null
+    } else {
+        Value::String(text)
+    };
+    let mut message = json!({"role": "assistant", "content": content});
+    if !tool_calls.is_empty() {
+        message["tool_calls"] = Value::Array(tool_calls);
+    }
+
+    let finish_reason = openai_finish_reason(response.get("stop_reason").and_then(Value::as_str));
+    let usage = response.get("usage");
+    let input_tokens = usage_field(usage, "input_tokens");
+    let output_tokens = usage_field(usage, "output_tokens");
+
+    json!({
+        "id": response.get("id").cloned().unwrap_or_default(),
+        "object": "chat.completion",
+        "created": unix_now(),
+        "model": response.get("model").cloned().unwrap_or_default(),
+        "choices": [{
+            "index": 0,
+            "message": message,
+            "finish_reason": finish_reason,
+        }],
+        "usage": {
+            "prompt_tokens": input_tokens,
+            "completion_tokens": output_tokens,
+            "total_tokens": input_tokens + output_tokens,
+        },
+    })
+}
+
+fn openai_to_anthropic_error(response: &Map<String, Value>) -> Value {
+    let error = response.get("error");
+    json!({
+        "type": "error",
+        "error": {
+            "type": error
+                .and_then(|e| e.get("type"))
+                .and_then(Value::as_str)
+                .unwrap_or("api_error"),
+            "message": error
+                .and_then(|e| e.get("message"))
+                .cloned()
+                .unwrap_or_default(),
+        },
+    })
+}
+
+fn anthropic_to_openai_error(response: &Map<String, Value>) -> Value {
+    let error = response.get("error");
+    json!({
+        "error": {
+            "message": error
+                .and_then(|e| e.get("message"))
+                .cloned()
+                .unwrap_or_default(),
+            "type": error
+                .and_then(|e| e.get("type"))
+                .and_then(Value::as_str)
+                .unwrap_or("api_error"),
+            "param": null,
+            "code": null,
+        },
+    })
+}
+
+/// OpenAI `finish_reason` to Anthropic `stop_reason`.
+fn anthropic_stop_reason(finish_reason: Option<&str>) -> &'static str {
+    match finish_reason {
+        Some("length") => "max_tokens",
+        Some("tool_calls") | Some("function_call") => "tool_use",
+        Some("content_filter") => "refusal",
+        _ => "end_turn",
+    }
+}
+
+/// Anthropic `stop_reason` to OpenAI `finish_reason`.
+fn openai_finish_reason(stop_reason: Option<&str>) -> &'static str {
+    match stop_reason {
+        Some("max_tokens") => "length",
+        Some("tool_use") => "tool_calls",
+        Some("refusal") => "content_filter",
+        _ => "stop",
+    }
+}
+
+// ============================================================================
+// Streaming
+// ============================================================================
+
+/// Content block currently open in a translated Anthropic stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OpenBlock {
+    Text,
+    /// Tool call with its OpenAI `index`
+    Tool(u64),
+}
+
+/// Translates an upstream SSE stream into the client's API, event by event.
+///
+/// Chunks may split events anywhere; incomplete events are buffered until the
+/// blank line that ends them arrives. Call [`finish`](Self::finish) at end of
+/// stream so the client always sees a well-formed ending.
+#[derive(Debug)]
+pub struct StreamTranslator {
+    /// Format of the incoming events
+    upstream: ApiFormat,
+    buffer: Vec<u8>,
+    id: String,
+    model: String,
+    created: u64,
+    started: bool,
+    finished: bool,
+    stop_reason: Option<String>,
+    input_tokens: u64,
+    output_tokens: u64,
+    // OpenAI → Anthropic
+    next_block: usize,
+    open_block: Option<OpenBlock>,
+    // Anthropic → OpenAI: content block index → tool call index
+    tool_indices: HashMap<u64, usize>,
+}
+
+impl StreamTranslator {
+    fn new(upstream: ApiFormat) -> Self {
+        Self {
+            upstream,
+            buffer: Vec::new(),
+            id: String::new(),
+            model: String::new(),
+            created: unix_now(),
+            started: false,
+            finished: false,
+            stop_reason: None,
+            input_tokens: 0,
+            output_tokens: 0,
+            next_block: 0,
+            open_block: None,
+            tool_indices: HashMap::new(),
+        }
+    }
+
+    /// Process an upstream chunk, returning the translated bytes to send.
+    pub fn process_chunk(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.buffer.extend(chunk.iter().filter(|&&b| b != b'\r'));
+
+        let mut out = String::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            self.process_event(&String::from_utf8_lossy(&event), &mut out);
+        }
+        out.into_bytes()
+    }
+
+    /// Flush any buffered event and close the translated stream.
+    pub fn finish(&mut self) -> Vec<u8> {
+        let mut out = String::new();
+        if !self.buffer.is_empty() {
+            let event = std::mem::take(&mut self.buffer);
+            self.process_event(&String::from_utf8_lossy(&event), &mut out);
+        }
+        if self.started {
+            match self.upstream {
+                ApiFormat::OpenAiChat => self.finish_anthropic(&mut out),
+                ApiFormat::AnthropicMessages => self.finish_openai(&mut out),
+            }
+        }
+        out.into_bytes()
+    }
+
+    fn process_event(&mut self, event: &str, out: &mut String) {
+        let data = event
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(str::trim_start)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if data.is_empty() || self.finished {
+            return;
+        }
+
+        match self.upstream {
+            ApiFormat::OpenAiChat => self.openai_event(&data, out),
+            ApiFormat::AnthropicMessages => self.anthropic_event(&data, out),
+        }
+    }
+
+    // --- OpenAI chunks → Anthropic events ---
+
+    fn openai_event(&mut self, data: &str, out: &mut String) {
+        if data == "[DONE]" {
+            self.finish_anthropic(out);
+            return;
+        }
+        let Ok(chunk) = serde_json::from_str::<Value>(data) else {
+            return;
+        };
+
+        if let Some(response) = chunk
+            .as_object()
+            .filter(|c| c.get("error").is_some_and(Value::is_object))
+        {
+            emit_anthropic(out, "error", openai_to_anthropic_error(response));
+            return;
+        }
+
+        if !self.started {
+            self.id = chunk
+                .get("id")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string();
+            self.model = chunk
+                .get("model")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string();
+            self.start_anthropic(out);
+        }
+
+        if let Some(usage) = chunk.get("usage").filter(|u| u.is_object()) {
+            self.input_tokens = usage_field(Some(usage), "prompt_tokens");
+            self.output_tokens = usage_field(Some(usage), "completion_tokens");
+        }
+
+        let Some(choice) = chunk.get("choices").and_then(|choices| choices.get(0)) else {
+            return;
+        };
+        let delta = choice.get("delta");
+
+        if let Some(text) = delta
+            .and_then(|d| d.get("content"))
+            .and_then(Value::as_str)
+            .filter(|text| !text.is_empty())
+        {
+            if self.open_block != Some(OpenBlock::Text) {
+                self.close_block(out);
+                self.open(out, OpenBlock::Text, json!({"type": "text", "text": ""}));
+            }
+            emit_anthropic(
+                out,
+                "content_block_delta",
+                json!({
+                    "type": "content_block_delta",
+                    "index": self.next_block - 1,
+                    "delta": {"type": "text_delta", "text": text},
+                }),
+            );
+        }
+
+        for call in delta
+            .and_then(|d| d.get("tool_calls"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let index = call.get("index").and_then(Value::as_u64).unwrap_or(0);
+            if self.open_block != Some(OpenBlock::Tool(index)) {
+                self.close_block(out);
+                self.open(
+                    out,
+                    OpenBlock::Tool(index),
+                    json!({
+                        "type": "tool_use",
+                        "id": call.get("id").cloned().unwrap_or_default(),
+                        "name": call.pointer("/function/name").cloned().unwrap_or_default(),
+                        "input": {},
+                    }),
+                );
+            }
+            if let Some(arguments) = call
+                .pointer("/function/arguments")
+                .and_then(Value::as_str)
+                .filter(|arguments| !arguments.is_empty())
+            {
+                emit_anthropic(
+                    out,
+                    "content_block_delta",
+                    json!({
+                        "type": "content_block_delta",
+                        "index": self.next_block - 1,
+                        "delta": {"type": "input_json_delta", "partial_json": arguments},
+                    }),
+                );
+            }
+        }
+
+        if let Some(reason) = choice.get("finish_reason").and_then(Value::as_str) {
+            self.stop_reason = Some(anthropic_stop_reason(Some(reason)).to_string());
+            self.close_block(out);
+        }
+    }
+
+    fn start_anthropic(&mut self, out: &mut String) {
+        self.started = true;
+        emit_anthropic(
+            out,
+            "message_start",
+            json!({
+                "type": "message_start",
+                "message": {
+                    "id": self.id,
+                    "type": "message",
+                    "role": "assistant",
+                    "model": self.model,
+                    "content": [],
+                    "stop_reason": null,
+                    "stop_sequence": null,
+                    "usage": {"input_tokens": self.input_tokens, "output_tokens": 0},
+                },
+            }),
+        );
+    }
+
+    fn open(&mut self, out: &mut String, block: OpenBlock, content_block: Value) {
+        emit_anthropic(
+            out,
+            "content_block_start",
+            json!({
+                "type": "content_block_start",
+                "index": self.next_block,
+                "content_block": content_block,
+            }),
+        );
+        self.open_block = Some(block);
+        self.next_block += 1;
+    }
+
+    fn close_block(&mut self, out: &mut String) {
+        if self.open_block.take().is_some() {
+            emit_anthropic(
+                out,
+                "content_block_stop",
+                json!({"type": "content_block_stop", "index": self.next_block - 1}),
+            );
+        }
+    }
+
+    fn finish_anthropic(&mut self, out: &mut String) {
+        if self.finished {
+            return;
+        }
+        if !self.started {
+            self.start_anthropic(out);
+        }
+        self.close_block(out);
+        emit_anthropic(
+            out,
+            "message_delta",
+            json!({
+                "type": "message_delta",
+                "delta": {
+                    "stop_reason": self.stop_reason.as_deref().unwrap_or("end_turn"),
+                    "stop_sequence": null,
+                },
+                "usage": {
+                    "input_tokens": self.input_tokens,
+                    "output_tokens": self.output_tokens,
+                },
+            }),
+        );
+        emit_anthropic(out, "message_stop", json!({"type": "message_stop"}));
+        self.finished = true;
+    }
+
+    // --- Anthropic events → OpenAI chunks ---
+
+    fn anthropic_event(&mut self, data: &str, out: &mut String) {
+        let Ok(event) = serde_json::from_str::<Value>(data) else {
+            return;
+        };
+
+        match event.get("type").and_then(Value::as_str) {
+            Some("message_start") => {
+                let message = event.get("message");
+                let field = |name: &str| {
+                    message
+                        .and_then(|m| m.get(name))
+                        .and_then(Value::as_str)
+                        .unwrap_or("")
+                        .to_string()
+                };
+                self.id = field("id");
+                self.model = field("model");
+                self.input_tokens =
+                    usage_field(message.and_then(|m| m.get("usage")), "input_tokens");
+                self.started = true;
+                self.emit_openai(out, json!({"role": "assistant", "content": ""}), None);
+            }
+            Some("content_block_start") => {
+                let block = event.get("content_block");
+                match block.and_then(|b| b.get("type")).and_then(Value::as_str) {
+                    Some("tool_use") => {
+                        let block_index = event.get("index").and_then(Value::as_u64).unwrap_or(0);
+                        let index = self.tool_indices.len();
+                        self.tool_indices.insert(block_index, index);
+                        let call = json!({
+                            "index": index,
+                            "id": block.and_then(|b| b.get("id")).cloned().unwrap_or_default(),
+                            "type": "function",
+                            "function": {
+                                "name": block.and_then(|b| b.get("name")).cloned().unwrap_or_default(),
+                                "arguments": "",
+                            },
+                        });
+                        self.emit_openai(out, json!({"tool_calls": [call]}), None);
+                    }
+                    Some("text") => {
+                        if let Some(text) = block
+                            .and_then(|b| b.get("text"))
+                            .and_then(Value::as_str)
+                            .filter(|text| !text.is_empty())
+                        {
+                            self.emit_openai(out, json!({"content": text}), None);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some("content_block_delta") => {
+                let delta = event.get("delta");
+                match delta.and_then(|d| d.get("type")).and_then(Value::as_str) {
+                    Some("text_delta") => {
+                        let text = delta
+                            .and_then(|d| d.get("text"))
+                            .cloned()
+                            .unwrap_or_default();
+                        self.emit_openai(out, json!({"content": text}), None);
+                    }
+                    Some("input_json_delta") => {
+                        let block_index = event.get("index").and_then(Value::as_u64).unwrap_or(0);
+                        if let Some(&index) = self.tool_indices.get(&block_index) {
+                            let arguments = delta
+                                .and_then(|d| d.get("partial_json"))
+                                .cloned()
+                                .unwrap_or_default();
+                            let call =
+                                json!({"index": index, "function": {"arguments": arguments}});
+                            self.emit_openai(out, json!({"tool_calls": [call]}), None);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some("message_delta") => {
+                if let Some(reason) = event.pointer("/delta/stop_reason").and_then(Value::as_str) {
+                    self.stop_reason = Some(reason.to_string());
+                }
+                if let Some(usage) = event.get("usage") {
+                    if let Some(output) = usage.get("output_tokens").and_then(Value::as_u64) {
+                        self.output_tokens = output;
+                    }
+                    if let Some(input) = usage.get("input_tokens").and_then(Value::as_u64) {
+                        if input > 0 {
+                            self.input_tokens = input;
+                        }
+                    }
+                }
+            }
+            Some("message_stop") => self.finish_openai(out),
+            Some("error") => {
+                if let Some(response) = event.as_object() {
+                    let translated = anthropic_to_openai_error(response);
+                    out.push_str(&format!("data: {}\n\n", translated));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn emit_openai(&self, out: &mut String, delta: Value, finish_reason: Option<&str>) {
+        let chunk = json!({
+            "id": self.id,
+            "object": "chat.completion.chunk",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{"index": 0, "delta": delta, "finish_reason": finish_reason}],
+        });
+        out.push_str(&format!("data: {}\n\n", chunk));
+    }
+
+    fn finish_openai(&mut self, out: &mut String) {
+        if self.finished {
+            return;
+        }
+        let finish_reason = openai_finish_reason(self.stop_reason.as_deref());
+        let mut chunk = json!({
+            "id": self.id,
+            "object": "chat.completion.chunk",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{"index": 0, "delta": {}, "finish_reason": finish_reason}],
+        });
+        chunk["usage"] = json!({
+            "prompt_tokens": self.input_tokens,
+            "completion_tokens": self.output_tokens,
+            "total_tokens": self.input_tokens + self.output_tokens,
+        });
+        out.push_str(&format!("data: {}\n\n", chunk));
+        out.push_str("data: [DONE]\n\n");
+        self.finished = true;
+    }
+}
+
+fn emit_anthropic(out: &mut String, event: &str, data: Value) {
+    out.push_str(&format!("event: {}\ndata: {}\n\n", event, data));
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn parse_object(body: &[u8]) -> Result<Map<String, Value>, TranslationError> {
+    match serde_json::from_slice::<Value>(body)? {
+        Value::Object(object) => Ok(object),
+        _ => Err(TranslationError::NotAnObject),
+    }
+}
+
+fn take_array(object: &mut Map<String, Value>, key: &str) -> Vec<Value> {
+    match object.remove(key) {
+        Some(Value::Array(items)) => items,
+        _ => Vec::new(),
+    }
+}
+
+fn insert_some(object: &mut Map<String, Value>, key: &str, value: Option<Value>) {
+    if let Some(value) = value.filter(|v| !v.is_null()) {
+        object.insert(key.to_string(), value);
+    }
+}
+
+fn usage_field(usage: Option<&Value>, key: &str) -> u64 {
+    usage
+        .and_then(|usage| usage.get(key))
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Plain text of a string or an array of text blocks/parts.
+fn text_of(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join(""),
+        _ => String::new(),
+    }
+}
+
+fn tool_calls_of(message: &Value) -> Vec<Value> {
+    message
+        .get("tool_calls")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// OpenAI message content to Anthropic content blocks.
+fn anthropic_blocks(content: Option<&Value>) -> Vec<Value> {
+    match content {
+        Some(Value::String(text)) if !text.is_empty() => {
+            vec![json!({"type": "text", "text": text})]
+        }
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| match part.get("type").and_then(Value::as_str) {
+                Some("text") => Some(json!({
+                    "type": "text",
+                    "text": part.get("text").cloned().unwrap_or_default(),
+                })),
+                Some("image_url") => {
+                    let url = part
+                        .pointer("/image_url/url")
+                        .or_else(|| part.get("image_url"))
+                        .and_then(Value::as_str)?;
+                    Some(anthropic_image(url))
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// OpenAI image URL (possibly a `data:` URL) to an Anthropic image block.
+fn anthropic_image(url: &str) -> Value {
+    if let Some((media_type, data)) = url
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+    {
+        json!({
+            "type": "image",
+            "source": {"type": "base64", "media_type": media_type, "data": data},
+        })
+    } else {
+        json!({"type": "image", "source": {"type": "url", "url": url}})
+    }
+}
+
+/// Anthropic image block to an OpenAI image URL.
+fn image_url_of(block: &Value) -> Option<String> {
+    let source = block.get("source")?;
+    match source.get("type").and_then(Value::as_str) {
+        Some("base64") => Some(format!(
+            "data:{};base64,{}",
+            source.get("media_type").and_then(Value::as_str)?,
+            source.get("data").and_then(Value::as_str)?
+        )),
+        Some("url") => source.get("url").and_then(Value::as_str).map(String::from),
+        _ => None,
+    }
+}
+
+/// OpenAI tool call to an Anthropic `tool_use` block.
+fn tool_use_block(call: &Value) -> Value {
+    let input = call
+        .pointer("/function/arguments")
+        .and_then(Value::as_str)
+        .and_then(|arguments| serde_json::from_str(arguments).ok())
+        .unwrap_or_else(|| json!({}));
+    json!({
+        "type": "tool_use",
+        "id": call.get("id").cloned().unwrap_or_default(),
+        "name": call.pointer("/function/name").cloned().unwrap_or_default(),
+        "input": input,
+    })
+}
+
+/// Anthropic `tool_use` block to an OpenAI tool call.
+fn openai_tool_call(block: &Value) -> Value {
+    json!({
+        "id": block.get("id").cloned().unwrap_or_default(),
+        "type": "function",
+        "function": {
+            "name": block.get("name").cloned().unwrap_or_default(),
+            "arguments": block.get("input").cloned().unwrap_or_else(|| json!({})).to_string(),
+        },
+    })
+}
+
+/// Append content blocks, merging consecutive same-role messages since
+/// Anthropic requires roles to alternate.
+fn push_anthropic_message(messages: &mut Vec<Value>, role: &str, blocks: Vec<Value>) {
+    if blocks.is_empty() {
+        return;
+    }
+    if let Some(last) = messages.last_mut() {
+        if last.get("role").and_then(Value::as_str) == Some(role) {
+            if let Some(content) = last.get_mut("content").and_then(Value::as_array_mut) {
+                content.extend(blocks);
+                return;
+            }
+        }
+    }
+    messages.push(json!({"role": role, "content": blocks}));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::create_provider;
+    use sentinel_config::InferenceProvider;
+
+    fn translator(client: InferenceProvider, upstream: InferenceProvider) -> InferenceTranslator {
+        InferenceTranslator::new(
+            create_provider(&client).as_ref(),
+            create_provider(&upstream).as_ref(),
+        )
+        .expect("formats differ")
+    }
+
+    fn to_json(bytes: &[u8]) -> Value {
+        serde_json::from_slice(bytes).unwrap()
+    }
+
+    /// Data payloads of an SSE stream
+    fn sse_data(bytes: &[u8]) -> Vec<String> {
+        String::from_utf8_lossy(bytes)
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_translator_only_between_known_formats() {
+        let openai = create_provider(&InferenceProvider::OpenAi);
+        let anthropic = create_provider(&InferenceProvider::Anthropic);
+        let generic = create_provider(&InferenceProvider::Generic);
+
+        assert!(InferenceTranslator::new(openai.as_ref(), openai.as_ref()).is_none());
+        assert!(InferenceTranslator::new(openai.as_ref(), generic.as_ref()).is_none());
+        let translator = InferenceTranslator::new(openai.as_ref(), anthropic.as_ref()).unwrap();
+        assert_eq!(translator.upstream_format().path(), "/v1/messages");
+    }
+
+    #[test]
+    fn test_upstream_path_keeps_base_and_query() {
+        let translator = translator(InferenceProvider::OpenAi, InferenceProvider::Anthropic);
+        let path = |uri: &str| translator.upstream_path_and_query(&uri.parse().unwrap());
+
+        assert_eq!(path("/v1/chat/completions"), "/v1/messages");
+        assert_eq!(
+            path("/openai/v1/chat/completions?api-version=2024-06-01"),
+            "/openai/v1/messages?api-version=2024-06-01"
+        );
+        // Not the client API's endpoint: no base path to keep
+        assert_eq!(path("/chat?stream=true"), "/v1/messages?stream=true");
+    }
+
+    #[test]
+    fn test_openai_request_to_anthropic() {
+        let t = translator(InferenceProvider::OpenAi, InferenceProvider::Anthropic);
+        let body = json!({
+            "model": "gpt-4",
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": "Weather in Paris?"},
+                {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1", "type": "function",
+                    "function": {"name": "weather", "arguments": "{\"city\":\"Paris\"}"}
+                }]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "Sunny"},
+                {"role": "user", "content": "Thanks"}
+            ],
+            "tools": [{"type": "function", "function": {
+                "name": "weather", "description": "Get weather",
+                "parameters": {"type": "object"}
+            }}],
+            "tool_choice": "required",
+            "stop": "END",
+            "stream": true
+        });
+
+        let out = to_json(
+            &t.translate_request(body.to_string().as_bytes(), Some("claude-3-opus"))
+                .unwrap(),
+        );
+        assert_eq!(out["model"], "claude-3-opus");
+        assert_eq!(out["system"], "Be brief.");
+        assert_eq!(out["max_tokens"], DEFAULT_ANTHROPIC_MAX_TOKENS);
+        assert_eq!(out["stop_sequences"], json!(["END"]));
+        assert_eq!(out["tool_choice"], json!({"type": "any"}));
+        assert_eq!(out["tools"][0]["input_schema"], json!({"type": "object"}));
+
+        let messages = out["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["content"][0]["type"], "tool_use");
+        assert_eq!(messages[1]["content"][0]["input"]["city"], "Paris");
+        // Tool result and the following user text merge into one user turn
+        assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], "call_1");
+        assert_eq!(messages[2]["content"][1]["text"], "Thanks");
+    }
+
+    #[test]
+    fn test_anthropic_request_to_openai() {
+        let t = translator(InferenceProvider::Anthropic, InferenceProvider::OpenAi);
+        let body = json!({
+            "model": "claude-3-opus",
+            "system": "Be brief.",
+            "max_tokens": 100,
+            "messages": [
+                {"role": "user", "content": "Weather in Paris?"},
+                {"role": "assistant", "content": [{
+                    "type": "tool_use", "id": "toolu_1", "name": "weather",
+                    "input": {"city": "Paris"}
+                }]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "Sunny"}
+                ]}
+            ],
+            "tool_choice": {"type": "tool", "name": "weather"},
+            "stream": true
+        });
+
+        let out = to_json(
+            &t.translate_request(body.to_string().as_bytes(), None)
+                .unwrap(),
+        );
+        assert_eq!(out["max_tokens"], 100);
+        assert_eq!(out["stream_options"]["include_usage"], true);
+        assert_eq!(out["tool_choice"]["function"]["name"], "weather");
+
+        let messages = out["messages"].as_array().unwrap();
+        assert_eq!(
+            messages[0],
+            json!({"role": "system", "content": "Be brief."})
+        );
+        assert_eq!(messages[2]["tool_calls"][0]["id"], "toolu_1");
+        assert_eq!(
+            messages[2]["tool_calls"][0]["function"]["arguments"],
+            "{\"city\":\"Paris\"}"
+        );
+        assert_eq!(messages[3]["role"], "tool");
+        assert_eq!(messages[3]["tool_call_id"], "toolu_1");
+    }
+
+    #[test]
+    fn test_responses_round_trip_usage_and_stop_reason() {
+        let t = translator(InferenceProvider::OpenAi, InferenceProvider::Anthropic);
+        let response = json!({
+            "id": "msg_1", "type": "message", "role": "assistant", "model": "claude-3-opus",
+            "content": [{"type": "text", "text": "Hi"}],
+            "stop_reason": "max_tokens",
+            "usage": {"input_tokens": 10, "output_tokens": 5}
+        });
+        let out = to_json(
+            &t.translate_response(response.to_string().as_bytes())
+                .unwrap(),
+        );
+        assert_eq!(out["choices"][0]["message"]["content"], "Hi");
+        assert_eq!(out["choices"][0]["finish_reason"], "length");
+        assert_eq!(out["usage"]["total_tokens"], 15);
+
+        let t = translator(InferenceProvider::Anthropic, InferenceProvider::OpenAi);
+        let response = json!({
+            "id": "chatcmpl-1", "object": "chat.completion", "model": "gpt-4",
+            "choices": [{"index": 0, "finish_reason": "tool_calls", "message": {
+                "role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1", "type": "function",
+                    "function": {"name": "weather", "arguments": "{}"}
+                }]
+            }}],
+            "usage": {"prompt_tokens": 7, "completion_tokens": 3, "total_tokens": 10}
+        });
+        let out = to_json(
+            &t.translate_response(response.to_string().as_bytes())
+                .unwrap(),
+        );
+        assert_eq!(out["stop_reason"], "tool_use");
+        assert_eq!(out["content"][0]["type"], "tool_use");
+        assert_eq!(out["usage"], json!({"input_tokens": 7, "output_tokens": 3}));
+    }
+
+    #[test]
+    fn test_error_translation() {
+        let t = translator(InferenceProvider::OpenAi, InferenceProvider::Anthropic);
+        let error = json!({
+            "type": "error",
+            "error": {"type": "overloaded_error", "message": "Overloaded"}
+        });
+        let out = to_json(&t.translate_response(error.to_string().as_bytes()).unwrap());
+        assert_eq!(out["error"]["type"], "overloaded_error");
+        assert_eq!(out["error"]["message"], "Overloaded");
+
+        assert!(matches!(
+            t.translate_response(b"not json"),
+            Err(TranslationError::InvalidJson(_))
+        ));
+    }
+
+    #[test]
+    fn test_anthropic_stream_to_openai() {
+        let t = translator(InferenceProvider::OpenAi, InferenceProvider::Anthropic);
+        let mut stream = t.stream_translator();
+        let upstream = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"model\":\"claude\",\"usage\":{\"input_tokens\":12,\"output_tokens\":1}}}\n\n",
+            "event: content_block_start\n",
+            "data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hello\"}}\n\n",
+            "event: message_delta\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":4}}\n\n",
+            "event: message_stop\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        );
+
+        // Split mid-event to exercise buffering
+        let (first, second) = upstream.as_bytes().split_at(150);
+        let mut out = stream.process_chunk(first);
+        out.extend(stream.process_chunk(second));
+        out.extend(stream.finish());
+
+        let data = sse_data(&out);
+        assert_eq!(data.last().unwrap(), "[DONE]");
+        let chunks: Vec<Value> = data[..data.len() - 1]
+            .iter()
+            .map(|d| serde_json::from_str(d).unwrap())
+            .collect();
+        assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+        assert_eq!(chunks[1]["choices"][0]["delta"]["content"], "Hello");
+        let last = chunks.last().unwrap();
+        assert_eq!(last["choices"][0]["finish_reason"], "stop");
+        assert_eq!(last["usage"]["prompt_tokens"], 12);
+        assert_eq!(last["usage"]["completion_tokens"], 4);
+    }
+
+    #[test]
+    fn test_openai_stream_to_anthropic() {
+        let t = translator(InferenceProvider::Anthropic, InferenceProvider::OpenAi);
+        let mut stream = t.stream_translator();
+        let upstream = concat!(
+            "data: {\"id\":\"c1\",\"model\":\"gpt-4\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"Hi\"}}]}\n\n",
+            "data: {\"id\":\"c1\",\"model\":\"gpt-4\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"function\":{\"name\":\"weather\",\"arguments\":\"{\\\"ci\"}}]}}]}\n\n",
+            "data: {\"id\":\"c1\",\"model\":\"gpt-4\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"ty\\\":1}\"}}]}}]}\n\n",
+            "data: {\"id\":\"c1\",\"model\":\"gpt-4\",\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"tool_calls\"}]}\n\n",
+            "data: {\"id\":\"c1\",\"model\":\"gpt-4\",\"choices\":[],\"usage\":{\"prompt_tokens\":9,\"completion_tokens\":6}}\n\n",
+            "data: [DONE]\n\n",
+        );
+
+        let mut out = stream.process_chunk(upstream.as_bytes());
+        out.extend(stream.finish());
+
+        let events: Vec<Value> = sse_data(&out)
+            .iter()
+            .map(|d| serde_json::from_str(d).unwrap())
+            .collect();
+        let types: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            types,
+            [
+                "message_start",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_stop",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "message_delta",
+                "message_stop",
+            ]
+        );
+        assert_eq!(events[4]["content_block"]["name"], "weather");
+        assert_eq!(events[4]["index"], 1);
+        assert_eq!(events[8]["delta"]["stop_reason"], "tool_use");
+        assert_eq!(events[8]["usage"]["output_tokens"], 6);
+        assert_eq!(events[8]["usage"]["input_tokens"], 9);
+    }
+}
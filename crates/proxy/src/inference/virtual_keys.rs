@@ -0,0 +1,643 @@
+//! Sentinel-issued virtual API keys for inference routes.
+//!
+//! Clients authenticate with a virtual key instead of a provider key. Each key
+//! maps to a tenant with its own model allowlist, token rate limit, budget,
+//! priority class, and expiry; the real provider credential comes from the
+//! upstream's `secret` config and never reaches the client.
+//!
+//! Keys are read from a JSON file that is reloaded when it changes:
+//!
+//! ```json
+//! {
+//!   "keys": [
+//!     {
+//!       "id": "search-prod",
+//!       "key_hash": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+//!       "tenant": "search",
+//!       "allowed_models": ["gpt-4o*"],
+//!       "rate_limit": { "tokens_per_minute": 50000 },
+//!       "budget": { "period": "monthly", "limit": 10000000 },
+//!       "priority": "interactive",
+//!       "expires_at": "2027-01-01T00:00:00Z"
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Only the SHA-256 of each key is stored, so the file holds no usable
+//! credentials.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use chrono::{DateTime, Utc};
+use http::HeaderMap;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use parking_lot::RwLock;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+
+use sentinel_common::budget::{BudgetAlert, BudgetCheckResult, TokenBudgetConfig};
+use sentinel_config::TokenRateLimit;
+
+use super::budget::TokenBudgetTracker;
+use super::rate_limit::{TokenRateLimitResult, TokenRateLimiter};
+use crate::client_cert::glob_matches;
+
+/// Virtual key authentication or loading failure.
+#[derive(Debug, Error)]
+pub enum VirtualKeyError {
+    #[error("Missing API key")]
+    Missing,
+
+    #[error("Invalid API key")]
+    Invalid,
+
+    #[error("API key '{0}' has expired")]
+    Expired(String),
+
+    #[error("Model '{model}' is not allowed for API key '{key_id}'")]
+    ModelNotAllowed { key_id: String, model: String },
+
+    #[error("Failed to load virtual keys from '{path}': {message}")]
+    Load { path: PathBuf, message: String },
+}
+
+impl VirtualKeyError {
+    /// HTTP status to answer the client with.
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Self::ModelNotAllowed { .. } => 403,
+            Self::Load { .. } => 500,
+            _ => 401,
+        }
+    }
+
+    /// Machine-readable error code for response bodies.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Missing => "missing_api_key",
+            Self::Invalid => "invalid_api_key",
+            Self::Expired(_) => "expired_api_key",
+            Self::ModelNotAllowed { .. } => "model_not_allowed",
+            Self::Load { .. } => "key_store_unavailable",
+        }
+    }
+}
+
+/// One key in the key file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct VirtualKeyDefinition {
+    /// Key identifier, used in logs (never the key itself)
+    pub id: String,
+    /// Hex-encoded SHA-256 of the key
+    pub key_hash: String,
+    /// Tenant the key's usage is attributed to
+    pub tenant: String,
+    /// Allowed model patterns (`*` wildcards); empty allows every model
+    #[serde(default)]
+    pub allowed_models: Vec<String>,
+    /// Token rate limit for this key
+    #[serde(default)]
+    pub rate_limit: Option<TokenRateLimit>,
+    /// Token budget for this key
+    #[serde(default)]
+    pub budget: Option<TokenBudgetConfig>,
+    /// Priority class of the key's requests, overriding the tenant's class
+    #[serde(default)]
+    pub priority: Option<String>,
+    /// When the key stops being accepted
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VirtualKeyFile {
+    keys: Vec<VirtualKeyDefinition>,
+}
+
+/// An issued key with its own limits.
+pub struct VirtualKey {
+    definition: VirtualKeyDefinition,
+    rate_limiter: Option<Arc<TokenRateLimiter>>,
+    budget_tracker: Option<Arc<TokenBudgetTracker>>,
+}
+
+impl VirtualKey {
+    /// Build a key, reusing the limiter state of its previous version when
+    /// the limits did not change.
+    fn new(
+        route_id: &str,
+        definition: VirtualKeyDefinition,
+        previous: Option<&VirtualKey>,
+    ) -> Self {
+        let rate_limiter = definition.rate_limit.as_ref().map(|config| {
+            previous
+                .filter(|p| p.definition.rate_limit.as_ref() == Some(config))
+                .and_then(|p| p.rate_limiter.clone())
+                .unwrap_or_else(|| Arc::new(TokenRateLimiter::new(config.clone())))
+        });
+
+        let budget_tracker = definition.budget.as_ref().map(|config| {
+            previous
+                .filter(|p| p.definition.budget.as_ref() == Some(config))
+                .and_then(|p| p.budget_tracker.clone())
+                .unwrap_or_else(|| {
+                    Arc::new(TokenBudgetTracker::new(
+                        config.clone(),
+                        format!("{}:key:{}", route_id, definition.id),
+                    ))
+                })
+        });
+
+        Self {
+            definition,
+            rate_limiter,
+            budget_tracker,
+        }
+    }
+
+    /// Key identifier.
+    pub fn id(&self) -> &str {
+        &self.definition.id
+    }
+
+    /// Tenant the key belongs to.
+    pub fn tenant(&self) -> &str {
+        &self.definition.tenant
+    }
+
+    /// Priority class of the key, if set.
+    pub fn priority(&self) -> Option<&str> {
+        self.definition.priority.as_deref()
+    }
+
+    /// Whether the key is limited to specific models.
+    pub fn restricts_models(&self) -> bool {
+        !self.definition.allowed_models.is_empty()
+    }
+
+    /// Check that the key may use a model.
+    pub fn check_model(&self, model: &str) -> Result<(), VirtualKeyError> {
+        let allowed = !self.restricts_models()
+            || self
+                .definition
+                .allowed_models
+                .iter()
+                .any(|pattern| glob_matches(pattern, model));
+        if allowed {
+            Ok(())
+        } else {
+            Err(VirtualKeyError::ModelNotAllowed {
+                key_id: self.definition.id.clone(),
+                model: model.to_string(),
+            })
+        }
+    }
+
+    /// Whether the key has expired at the given time.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.definition
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Check the key's token rate limit.
+    pub fn check_rate_limit(&self, estimated_tokens: u64) -> TokenRateLimitResult {
+        match self.rate_limiter {
+            Some(ref rate_limiter) => rate_limiter.check(&self.definition.id, estimated_tokens),
+            None => TokenRateLimitResult::Allowed,
+        }
+    }
+
+    /// Adjust the key's rate limiter with the actual token count.
+    pub fn record_actual(&self, actual_tokens: u64, estimated_tokens: u64) {
+        if let Some(ref rate_limiter) = self.rate_limiter {
+            rate_limiter.record_actual(&self.definition.id, actual_tokens, estimated_tokens);
+        }
+    }
+
+    /// Check the key's budget, or `None` if it has none.
+    pub async fn check_budget(&self, estimated_tokens: u64) -> Option<BudgetCheckResult> {
+        let budget_tracker = self.budget_tracker.as_ref()?;
+        Some(
+            budget_tracker
+                .check_async(&self.definition.id, estimated_tokens)
+                .await,
+        )
+    }
+
+    /// Record usage against the key's budget.
+    pub async fn record_budget(&self, actual_tokens: u64) -> Vec<BudgetAlert> {
+        match self.budget_tracker {
+            Some(ref budget_tracker) => {
+                budget_tracker
+                    .record_async(&self.definition.id, actual_tokens)
+                    .await
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for VirtualKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VirtualKey")
+            .field("id", &self.definition.id)
+            .field("tenant", &self.definition.tenant)
+            .finish()
+    }
+}
+
+/// Hex-encoded SHA-256 of a key, as stored in the key file.
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Extract the key a client presented: `Authorization: Bearer <key>` (OpenAI
+/// clients) or `x-api-key: <key>` (Anthropic clients).
+fn presented_key(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get(http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| {
+            v.strip_prefix("Bearer ")
+                .or_else(|| v.strip_prefix("bearer "))
+        });
+    bearer
+        .or_else(|| headers.get("x-api-key").and_then(|v| v.to_str().ok()))
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+/// File-backed set of virtual keys for one route.
+pub struct VirtualKeyStore {
+    route_id: String,
+    path: PathBuf,
+    /// Keys by hash
+    keys: ArcSwap<HashMap<String, Arc<VirtualKey>>>,
+}
+
+impl VirtualKeyStore {
+    /// Create a store for a key file. Call [`reload`](Self::reload) to load it;
+    /// until then every key is rejected.
+    pub fn new(route_id: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            route_id: route_id.into(),
+            path: path.into(),
+            keys: ArcSwap::from_pointee(HashMap::new()),
+        }
+    }
+
+    /// Key file path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Route the keys belong to.
+    pub fn route_id(&self) -> &str {
+        &self.route_id
+    }
+
+    /// Number of loaded keys.
+    pub fn len(&self) -> usize {
+        self.keys.load().len()
+    }
+
+    /// Whether no keys are loaded.
+    pub fn is_empty(&self) -> bool {
+        self.keys.load().is_empty()
+    }
+
+    /// Re-read the key file. On error the previous keys stay active.
+    ///
+    /// Returns the number of keys loaded.
+    pub fn reload(&self) -> Result<usize, VirtualKeyError> {
+        let load_error = |message: String| VirtualKeyError::Load {
+            path: self.path.clone(),
+            message,
+        };
+
+        let data = std::fs::read(&self.path).map_err(|e| load_error(e.to_string()))?;
+        let file: VirtualKeyFile =
+            serde_json::from_slice(&data).map_err(|e| load_error(e.to_string()))?;
+
+        let previous = self.keys.load();
+        let previous_by_id: HashMap<&str, &Arc<VirtualKey>> =
+            previous.values().map(|key| (key.id(), key)).collect();
+
+        let mut ids = HashSet::new();
+        let mut keys = HashMap::with_capacity(file.keys.len());
+        for definition in file.keys {
+            let hash = definition.key_hash.to_ascii_lowercase();
+            if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(load_error(format!(
+                    "key '{}' has an invalid key_hash (expected hex SHA-256)",
+                    definition.id
+                )));
+            }
+            if !ids.insert(definition.id.clone()) {
+                return Err(load_error(format!("duplicate key id '{}'", definition.id)));
+            }
+
+            let old = previous_by_id.get(definition.id.as_str()).copied();
+            let key = match old {
+                Some(old) if old.definition == definition => Arc::clone(old),
+                _ => Arc::new(VirtualKey::new(
+                    &self.route_id,
+                    definition,
+                    old.map(|k| &**k),
+                )),
+            };
+            if keys.insert(hash, key).is_some() {
+                return Err(load_error("two keys share the same key_hash".to_string()));
+            }
+        }
+
+        let count = keys.len();
+        self.keys.store(Arc::new(keys));
+
+        info!(
+            route_id = %self.route_id,
+            path = %self.path.display(),
+            key_count = count,
+            "Loaded virtual API keys"
+        );
+        Ok(count)
+    }
+
+    /// Authenticate the key presented in the request headers.
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Arc<VirtualKey>, VirtualKeyError> {
+        let presented = presented_key(headers).ok_or(VirtualKeyError::Missing)?;
+        let key = self
+            .keys
+            .load()
+            .get(&hash_key(presented))
+            .cloned()
+            .ok_or(VirtualKeyError::Invalid)?;
+
+        if key.is_expired(Utc::now()) {
+            return Err(VirtualKeyError::Expired(key.id().to_string()));
+        }
+
+        debug!(
+            route_id = %self.route_id,
+            key_id = key.id(),
+            tenant = key.tenant(),
+            "Authenticated virtual API key"
+        );
+        Ok(key)
+    }
+}
+
+// ============================================================================
+// File watching
+// ============================================================================
+
+/// Watches virtual key files and reloads stores when they change.
+pub struct VirtualKeyWatcher {
+    watcher: RwLock<Option<notify::RecommendedWatcher>>,
+    stores: Vec<Arc<VirtualKeyStore>>,
+}
+
+impl VirtualKeyWatcher {
+    /// Create a watcher for the given stores.
+    pub fn new(stores: Vec<Arc<VirtualKeyStore>>) -> Self {
+        Self {
+            watcher: RwLock::new(None),
+            stores,
+        }
+    }
+
+    /// Start watching the key files.
+    ///
+    /// Directories are watched rather than files, so keys replaced by an
+    /// atomic rename (as Kubernetes secrets are) are picked up too.
+    pub fn start_watching(&self) -> Result<mpsc::Receiver<PathBuf>, notify::Error> {
+        let (tx, rx) = mpsc::channel::<PathBuf>(10);
+
+        let dirs: HashSet<PathBuf> = self
+            .stores
+            .iter()
+            .map(|store| watch_dir(store.path()))
+            .collect();
+
+        if dirs.is_empty() {
+            debug!("No virtual key files to watch");
+            return Ok(rx);
+        }
+
+        let mut watcher =
+            notify::recommended_watcher(move |event: Result<Event, notify::Error>| {
+                if let Ok(event) = event {
+                    if matches!(
+                        event.kind,
+                        EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
+                    ) {
+                        for path in &event.paths {
+                            let _ = tx.blocking_send(path.clone());
+                        }
+                    }
+                }
+            })?;
+
+        for dir in &dirs {
+            if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+                warn!(
+                    path = %dir.display(),
+                    error = %e,
+                    "Failed to watch virtual key directory"
+                );
+            } else {
+                info!(path = %dir.display(), "Watching virtual key files for changes");
+            }
+        }
+
+        *self.watcher.write() = Some(watcher);
+        Ok(rx)
+    }
+
+    /// Reload every store whose key file lives in a directory that changed.
+    ///
+    /// Any entry in the directory counts: Kubernetes swaps a `..data` symlink
+    /// rather than touching the key file itself. Each store is reloaded at
+    /// most once per batch of changes.
+    pub fn handle_changes(&self, paths: &[PathBuf]) {
+        let changed: HashSet<PathBuf> = paths.iter().map(|path| watch_dir(path)).collect();
+
+        for store in &self.stores {
+            if !changed.contains(&watch_dir(store.path())) {
+                continue;
+            }
+            if let Err(e) = store.reload() {
+                error!(
+                    route_id = %store.route_id(),
+                    error = %e,
+                    "Failed to reload virtual API keys, keeping previous keys"
+                );
+            }
+        }
+    }
+}
+
+/// Directory watched for a key file (and holding a changed entry).
+fn watch_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    fn write_keys(path: &Path, keys: serde_json::Value) {
+        std::fs::write(path, serde_json::json!({ "keys": keys }).to_string()).unwrap();
+    }
+
+    fn bearer(key: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", key)).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.json");
+        write_keys(
+            &path,
+            serde_json::json!([
+                { "id": "search", "key_hash": hash_key("sk-search"), "tenant": "team-search" },
+                {
+                    "id": "old",
+                    "key_hash": hash_key("sk-old"),
+                    "tenant": "team-old",
+                    "expires_at": "2020-01-01T00:00:00Z"
+                }
+            ]),
+        );
+
+        let store = VirtualKeyStore::new("route", &path);
+        assert!(matches!(
+            store.authenticate(&bearer("sk-search")),
+            Err(VirtualKeyError::Invalid)
+        ));
+        assert_eq!(store.reload().unwrap(), 2);
+
+        let key = store.authenticate(&bearer("sk-search")).unwrap();
+        assert_eq!(key.tenant(), "team-search");
+        assert_eq!(key.priority(), None);
+
+        // Anthropic clients send the key in x-api-key
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", HeaderValue::from_static("sk-search"));
+        assert_eq!(store.authenticate(&headers).unwrap().id(), "search");
+
+        assert!(matches!(
+            store.authenticate(&HeaderMap::new()),
+            Err(VirtualKeyError::Missing)
+        ));
+        assert!(matches!(
+            store.authenticate(&bearer("sk-unknown")),
+            Err(VirtualKeyError::Invalid)
+        ));
+        assert!(matches!(
+            store.authenticate(&bearer("sk-old")),
+            Err(VirtualKeyError::Expired(_))
+        ));
+    }
+
+    #[test]
+    fn test_allowed_models() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.json");
+        write_keys(
+            &path,
+            serde_json::json!([{
+                "id": "k",
+                "key_hash": hash_key("sk"),
+                "tenant": "t",
+                "allowed_models": ["gpt-4o*", "claude-3-5-haiku-*"]
+            }]),
+        );
+        let store = VirtualKeyStore::new("route", &path);
+        store.reload().unwrap();
+        let key = store.authenticate(&bearer("sk")).unwrap();
+
+        assert!(key.restricts_models());
+        assert!(key.check_model("gpt-4o-mini").is_ok());
+        assert!(key.check_model("claude-3-5-haiku-20241022").is_ok());
+        let err = key.check_model("gpt-4-turbo").unwrap_err();
+        assert_eq!(err.status_code(), 403);
+    }
+
+    #[test]
+    fn test_reload_keeps_limiter_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.json");
+        let limited = |expires_at: &str| {
+            serde_json::json!([{
+                "id": "k",
+                "key_hash": hash_key("sk"),
+                "tenant": "t",
+                "rate_limit": { "tokens_per_minute": 100, "burst_tokens": 100 },
+                "expires_at": expires_at
+            }])
+        };
+        write_keys(&path, limited("2099-01-01T00:00:00Z"));
+        let store = VirtualKeyStore::new("route", &path);
+        store.reload().unwrap();
+
+        let key = store.authenticate(&bearer("sk")).unwrap();
+        assert!(key.check_rate_limit(100).is_allowed());
+        assert!(!key.check_rate_limit(100).is_allowed());
+
+        // Changing the expiry does not reset the key's rate limit
+        write_keys(&path, limited("2098-01-01T00:00:00Z"));
+        store.reload().unwrap();
+        let key = store.authenticate(&bearer("sk")).unwrap();
+        assert!(!key.check_rate_limit(100).is_allowed());
+
+        // A broken file keeps the previous keys
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(store.reload().is_err());
+        assert!(store.authenticate(&bearer("sk")).is_ok());
+    }
+
+    #[test]
+    fn test_directory_change_reloads_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.json");
+        write_keys(
+            &path,
+            serde_json::json!([{ "id": "k", "key_hash": hash_key("sk"), "tenant": "t" }]),
+        );
+        let store = Arc::new(VirtualKeyStore::new("route", &path));
+        let watcher = VirtualKeyWatcher::new(vec![store.clone()]);
+
+        // Kubernetes swaps the `..data` symlink, never the key file itself
+        watcher.handle_changes(&[dir.path().join("..data")]);
+        assert!(store.authenticate(&bearer("sk")).is_ok());
+
+        // Changes elsewhere leave the store alone
+        write_keys(&path, serde_json::json!([]));
+        let other = tempfile::tempdir().unwrap();
+        watcher.handle_changes(&[other.path().join("keys.json")]);
+        assert!(store.authenticate(&bearer("sk")).is_ok());
+    }
+}
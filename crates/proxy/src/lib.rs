@@ -47,6 +47,8 @@ pub mod agents;
 pub mod app;
 pub mod builtin_handlers;
 pub mod cache;
+pub mod challenge;
+pub mod client_cert;
 pub mod decompression;
 pub mod discovery;
 pub mod distributed_rate_limit;
@@ -73,8 +75,10 @@ pub mod scoped_rate_limit;
 pub mod routing;
 pub mod scoped_routing;
 pub mod shadow;
+pub mod spiffe;
 pub mod static_files;
 pub mod tls;
+pub mod tls_listener;
 pub mod trace_id;
 pub mod upstream;
 pub mod validation;
@@ -90,6 +94,9 @@ pub mod bundle;
 // Error handling
 pub use errors::ErrorHandler;
 
+// Client certificate identity
+pub use client_cert::ClientCertIdentity;
+
 // Static file serving
 pub use static_files::{CacheStats, CachedFile, FileCache, StaticFileServer};
 
@@ -103,7 +110,7 @@ pub use scoped_routing::{ScopedRouteMatch, ScopedRouteMatcher};
 // Upstream management
 pub use upstream::{
     LoadBalancer, PoolConfigSnapshot, PoolStats, RequestContext, ShadowTarget, TargetSelection,
-    UpstreamPool, UpstreamTarget,
+    UpstreamCredential, UpstreamPool, UpstreamTarget,
 };
 
 // Health checking
@@ -149,7 +156,10 @@ pub use otel::{
 pub use tls::{
     build_server_config, build_upstream_tls_config, load_client_ca, validate_tls_config,
     validate_upstream_tls_config, CertificateReloader, HotReloadableSniResolver, OcspCacheEntry,
-    OcspStapler, SniResolver, TlsError,
+    OcspStapler, SniResolver, TlsError, TlsPolicy,
+};
+pub use tls_listener::{
+    accept_tls, needs_sni_listener, SharedApp, TlsClientStream, TlsListenerService,
 };
 
 // Logging
@@ -172,6 +182,9 @@ pub use scoped_circuit_breaker::{ScopedBreakerStatus, ScopedCircuitBreakerManage
 // Traffic mirroring / shadowing
 pub use shadow::{buffer_request_body, clone_body_for_shadow, should_buffer_method, ShadowManager};
 
+// SPIFFE workload identity
+pub use spiffe::{SpiffeId, SpiffeSource, SpiffeVerifier, X509Context};
+
 // GeoIP filtering
 pub use geo_filter::{
     GeoDatabaseWatcher, GeoFilterManager, GeoFilterPool, GeoFilterResult, GeoLookupError,
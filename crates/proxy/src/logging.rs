@@ -4,6 +4,8 @@
 //! - Access logs (request/response data with trace_id)
 //! - Error logs (errors and warnings)
 //! - Audit logs (security events)
+//! - Inference audit logs (prompt, completion, usage and cost per request),
+//!   rotated by size
 //!
 //! Access log formats supported:
 //! - `json` (default): Structured JSON with all fields
@@ -14,11 +16,12 @@ use parking_lot::Mutex;
 use serde::Serialize;
 use std::fs::{File, OpenOptions};
 use std::io::{BufWriter, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::{error, warn};
 
-use sentinel_config::{AuditLogConfig, LoggingConfig};
+use sentinel_agent_protocol::RoutingOverride;
+use sentinel_config::{AuditLogConfig, InferenceLogConfig, LoggingConfig};
 
 /// Access log format
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -90,6 +93,9 @@ pub struct AccessLogEntry {
     /// GeoIP country code (ISO 3166-1 alpha-2)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub geo_country: Option<String>,
+    /// Agent routing override applied to this request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routing_override: Option<RoutingOverride>,
 }
 
 impl AccessLogEntry {
@@ -501,6 +507,63 @@ impl AuditLogEntry {
     }
 }
 
+/// Inference audit log entry, one per sampled inference request
+#[derive(Debug, Serialize)]
+pub struct InferenceAuditEntry {
+    /// Timestamp in RFC3339 format
+    pub timestamp: String,
+    /// Trace ID for correlation
+    pub trace_id: String,
+    /// Route ID
+    pub route_id: String,
+    /// Tenant the request was attributed to (virtual key tenant or client IP)
+    pub tenant: String,
+    /// Virtual key the client authenticated with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    /// Client IP
+    pub client_ip: String,
+    /// Model requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Upstream that served the request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+    /// Response status code
+    pub status: u16,
+    /// Whether the response was streamed
+    pub streaming: bool,
+    /// How the response was served from cache (exact)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_hit: Option<String>,
+    /// Input tokens, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u64>,
+    /// Output tokens, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<u64>,
+    /// Total tokens charged against rate limits and budgets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_tokens: Option<u64>,
+    /// Request cost from the route's pricing rules
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    /// Guardrail detection categories (prompt and response)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub guardrail_detections: Vec<String>,
+    /// Total request duration in milliseconds
+    pub latency_ms: u64,
+    /// Time until the first response body bytes were sent, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttft_ms: Option<u64>,
+    /// Prompt (truncated, redacted or hashed as configured)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Completion (truncated, redacted or hashed as configured)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion: Option<String>,
+}
+
 /// Buffered file writer for log files
 struct LogFileWriter {
     writer: BufWriter<File>,
@@ -536,6 +599,91 @@ impl LogFileWriter {
     }
 }
 
+/// Buffered log file writer with size-based rotation
+///
+/// When a line would take the file past `max_size_bytes`, `<path>.N-1` is
+/// renamed to `<path>.N` (and so on down to `<path>` -> `<path>.1`), the
+/// oldest file is dropped and a fresh `<path>` is opened.
+struct RotatingLogWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    buffer_size: usize,
+    size: u64,
+    max_size_bytes: u64,
+    max_files: usize,
+}
+
+impl RotatingLogWriter {
+    fn new(config: &InferenceLogConfig) -> Result<Self> {
+        let path = config.file.clone();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create log directory: {:?}", parent))?;
+        }
+
+        let file = Self::open(&path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        Ok(Self {
+            path,
+            writer: BufWriter::with_capacity(config.buffer_size, file),
+            buffer_size: config.buffer_size,
+            size,
+            max_size_bytes: config.max_size_bytes,
+            max_files: config.max_files,
+        })
+    }
+
+    fn open(path: &Path) -> Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log file: {:?}", path))
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        if self.max_files == 0 {
+            // No history kept: start over in place
+            File::create(&self.path)?;
+        } else {
+            let _ = std::fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        self.writer = BufWriter::with_capacity(self.buffer_size, Self::open(&self.path)?);
+        self.size = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_size_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.writer, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
 /// Log manager handling all log file writers
 pub struct LogManager {
     access_log: Option<Mutex<LogFileWriter>>,
@@ -544,6 +692,7 @@ pub struct LogManager {
     error_log: Option<Mutex<LogFileWriter>>,
     audit_log: Option<Mutex<LogFileWriter>>,
     audit_config: Option<AuditLogConfig>,
+    inference_log: Option<Mutex<RotatingLogWriter>>,
 }
 
 impl LogManager {
@@ -590,6 +739,13 @@ impl LogManager {
             None
         };
 
+        let inference_log = match config.inference_log {
+            Some(ref inference_config) if inference_config.enabled => {
+                Some(Mutex::new(RotatingLogWriter::new(inference_config)?))
+            }
+            _ => None,
+        };
+
         Ok(Self {
             access_log,
             access_log_format,
@@ -597,6 +753,7 @@ impl LogManager {
             error_log,
             audit_log,
             audit_config: config.audit_log.clone(),
+            inference_log,
         })
     }
 
@@ -609,6 +766,7 @@ impl LogManager {
             error_log: None,
             audit_log: None,
             audit_config: None,
+            inference_log: None,
         }
     }
 
@@ -698,6 +856,23 @@ impl LogManager {
         }
     }
 
+    /// Write an inference audit log entry
+    pub fn log_inference(&self, entry: &InferenceAuditEntry) {
+        if let Some(ref writer) = self.inference_log {
+            match serde_json::to_string(entry) {
+                Ok(json) => {
+                    let mut guard = writer.lock();
+                    if let Err(e) = guard.write_line(&json) {
+                        error!("Failed to write inference audit log: {}", e);
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to serialize inference audit log entry: {}", e);
+                }
+            }
+        }
+    }
+
     /// Flush all log buffers
     pub fn flush(&self) {
         if let Some(ref writer) = self.access_log {
@@ -715,6 +890,11 @@ impl LogManager {
                 warn!("Failed to flush audit log: {}", e);
             }
         }
+        if let Some(ref writer) = self.inference_log {
+            if let Err(e) = writer.lock().flush() {
+                warn!("Failed to flush inference audit log: {}", e);
+            }
+        }
     }
 
     /// Check if access logging is enabled
@@ -731,6 +911,11 @@ impl LogManager {
     pub fn audit_log_enabled(&self) -> bool {
         self.audit_log.is_some()
     }
+
+    /// Check if inference audit logging is enabled
+    pub fn inference_log_enabled(&self) -> bool {
+        self.inference_log.is_some()
+    }
 }
 
 /// Shared log manager that can be passed around
@@ -769,6 +954,7 @@ mod tests {
             connection_reused: true,
             rate_limit_hit: false,
             geo_country: None,
+            routing_override: None,
         };
 
         let json = serde_json::to_string(&entry).unwrap();
@@ -803,11 +989,13 @@ mod tests {
             connection_reused: false,
             rate_limit_hit: false,
             geo_country: Some("US".to_string()),
+            routing_override: None,
         };
 
         let json = serde_json::to_string(&entry).unwrap();
         assert!(json.contains("\"namespace\":\"api\""));
         assert!(json.contains("\"service\":\"payments\""));
+        assert!(!json.contains("routing_override"));
     }
 
     #[test]
@@ -846,12 +1034,45 @@ mod tests {
                 log_agent_decisions: true,
                 log_waf_events: true,
             }),
+            inference_log: None,
         };
 
         let manager = LogManager::new(&config).unwrap();
         assert!(manager.access_log_enabled());
         assert!(manager.error_log_enabled());
         assert!(manager.audit_log_enabled());
+        assert!(!manager.inference_log_enabled());
+    }
+
+    #[test]
+    fn test_inference_log_rotation() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("inference.log");
+        let config = LoggingConfig {
+            inference_log: Some(InferenceLogConfig {
+                file: path.clone(),
+                max_size_bytes: 20,
+                max_files: 2,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let manager = LogManager::new(&config).unwrap();
+        assert!(manager.inference_log_enabled());
+        {
+            let mut writer = manager.inference_log.as_ref().unwrap().lock();
+            for i in 0..4 {
+                writer.write_line(&format!("line-{:03}-xxxx", i)).unwrap();
+            }
+        }
+        manager.flush();
+
+        let read = |name: &str| std::fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("inference.log"), "line-003-xxxx\n");
+        assert_eq!(read("inference.log.1"), "line-002-xxxx\n");
+        assert_eq!(read("inference.log.2"), "line-001-xxxx\n");
+        assert!(!dir.path().join("inference.log.3").exists());
     }
 
     #[test]
@@ -881,6 +1102,7 @@ mod tests {
             connection_reused: true,
             rate_limit_hit: false,
             geo_country: Some("US".to_string()),
+            routing_override: None,
         };
 
         let combined = entry.format(AccessLogFormat::Combined);
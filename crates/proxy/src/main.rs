@@ -17,12 +17,17 @@ static GLOBAL: Jemalloc = Jemalloc;
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use pingora::prelude::*;
+use pingora::services::background::background_service;
 use std::sync::Arc;
 use tracing::{error, info, warn};
 
 use sentinel_config::Config;
+use sentinel_proxy::agents::{run_agent_command, AgentArgs};
 use sentinel_proxy::bundle::{run_bundle_command, BundleArgs};
-use sentinel_proxy::{ReloadTrigger, SentinelProxy, SignalManager, SignalType};
+use sentinel_proxy::{
+    needs_sni_listener, HotReloadableSniResolver, ReloadTrigger, SentinelProxy, SharedApp,
+    SignalManager, SignalType, TlsListenerService,
+};
 
 /// Version string combining Cargo semver and CalVer release tag
 const VERSION: &str = concat!(
@@ -105,6 +110,9 @@ enum Commands {
 
     /// Manage bundled agents (install, status, update)
     Bundle(BundleArgs),
+
+    /// Debug agents (replay recorded traffic)
+    Agent(AgentArgs),
 }
 
 fn main() -> Result<()> {
@@ -147,6 +155,14 @@ fn main() -> Result<()> {
                 .init();
             run_bundle_command(args)
         }
+        Some(Commands::Agent(args)) => {
+            // Initialize minimal logging for agent commands
+            tracing_subscriber::fmt()
+                .with_target(false)
+                .with_level(true)
+                .init();
+            run_agent_command(args)
+        }
         None => {
             // Default: run the server
             run_server(cli.config, cli.verbose, cli.daemon, cli.upgrade)
@@ -458,8 +474,13 @@ fn run_server(
     let mut server = Server::new_with_opt_and_conf(Some(pingora_opt), pingora_conf);
     server.bootstrap();
 
-    // Create proxy service
-    let mut proxy_service = http_proxy_service(&server.configuration, proxy);
+    // Create the HTTP application, shared by the plain HTTP listeners (served
+    // by Pingora) and the HTTPS listeners (which select TLS policy per SNI)
+    let app = Arc::new(pingora::proxy::http_proxy(&server.configuration, proxy));
+    let mut proxy_service = pingora::services::listening::Service::new(
+        "Sentinel HTTP".to_string(),
+        SharedApp(app.clone()),
+    );
 
     // Configure listening addresses from config
     for listener in &config.listeners {
@@ -532,16 +553,61 @@ fn run_server(
                             continue;
                         }
 
-                        match proxy_service.add_tls(&listener.address, &cert_path_str, &key_path_str) {
-                            Ok(()) => {
+                        // Pingora's listener takes part in `--upgrade` socket
+                        // handoff, so it serves every listener it can: one
+                        // certificate, no client authentication or protocol limits
+                        if !needs_sni_listener(tls_config) {
+                            match proxy_service.add_tls(&listener.address, &cert_path_str, &key_path_str) {
+                                Ok(()) => {
+                                    info!(
+                                        listener_id = %listener.id,
+                                        address = %listener.address,
+                                        cert_file = %cert_path_str,
+                                        acme_enabled = tls_config.acme.is_some(),
+                                        "HTTPS listening on: {}", listener.address
+                                    );
+                                }
+                                Err(e) => {
+                                    error!(
+                                        listener_id = %listener.id,
+                                        address = %listener.address,
+                                        error = %e,
+                                        "Failed to configure TLS listener"
+                                    );
+                                }
+                            }
+                            continue;
+                        }
+
+                        // The resolver carries the default certificate and the
+                        // per-SNI certificates and policies
+                        let mut listener_tls = tls_config.clone();
+                        listener_tls.cert_file = Some(cert_path.clone());
+                        listener_tls.key_file = Some(key_path.clone());
+                        match HotReloadableSniResolver::from_config(listener_tls) {
+                            Ok(resolver) => {
+                                let resolver = Arc::new(resolver);
+                                config_manager
+                                    .cert_reloader()
+                                    .register(&listener.id, resolver.clone());
+                                server.add_service(background_service(
+                                    &format!("TLS listener {}", listener.id),
+                                    TlsListenerService::new(
+                                        &listener.id,
+                                        &listener.address,
+                                        resolver,
+                                        app.clone(),
+                                    ),
+                                ));
                                 info!(
                                     listener_id = %listener.id,
                                     address = %listener.address,
                                     cert_file = %cert_path_str,
                                     min_tls_version = ?tls_config.min_version,
                                     client_auth = tls_config.client_auth,
+                                    sni_entries = tls_config.additional_certs.len(),
                                     acme_enabled = tls_config.acme.is_some(),
-                                    "HTTPS listening on: {}", listener.address
+                                    "HTTPS listener configured: {}", listener.address
                                 );
                             }
                             Err(e) => {
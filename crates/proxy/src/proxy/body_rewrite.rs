@@ -0,0 +1,246 @@
+//! Streaming body rewriting by agents.
+//!
+//! In `stream` and `hybrid` body modes each chunk is held only while the
+//! agents look at it, then forwarded as the [`BodyMutation`] they returned
+//! describes: unchanged, replaced, dropped or with data inserted before it.
+//! Because chunk sizes can change after the headers have gone out, messages
+//! that may be rewritten lose their `Content-Length` up front (see
+//! [`rewrite_framing`]).
+//!
+//! Memory stays bounded by holding at most one chunk per message: the filter
+//! waits for the agents before Pingora reads the next chunk, so a slow agent
+//! slows the transfer down rather than queueing data. A v2 agent that pauses
+//! the flow is handled by its pool's `flow_control_mode`: `fail_closed` fails
+//! the chunk (subject to the route's failure mode), `fail_open` forwards it
+//! unchanged, and `wait_and_retry` holds it for at most
+//! `flow_control_wait_timeout_ms`.
+
+use bytes::Bytes;
+use http::Version;
+use sentinel_agent_protocol::BodyMutation;
+use sentinel_config::{AgentConfig, AgentEvent, BodyStreamingMode};
+
+/// How a rewritable body is delimited once its `Content-Length` is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewriteFraming {
+    /// HTTP/1.1: switch to chunked transfer encoding
+    Chunked,
+    /// HTTP/2 and later: frames delimit the body, and `Transfer-Encoding`
+    /// is not allowed
+    Frames,
+    /// HTTP/1.0: no chunked encoding. A response body ends when the
+    /// connection closes, but a request body must keep its length and cannot
+    /// be resized.
+    Unframed,
+}
+
+/// Pick the framing for a rewritable body sent with `version`.
+pub fn rewrite_framing(version: Version) -> RewriteFraming {
+    match version {
+        Version::HTTP_11 => RewriteFraming::Chunked,
+        Version::HTTP_2 | Version::HTTP_3 => RewriteFraming::Frames,
+        _ => RewriteFraming::Unframed,
+    }
+}
+
+/// Route agents that see response body chunks.
+///
+/// Response bodies are only sent to agents subscribed to `response_body` in
+/// `stream` mode; other agents on the route never see them.
+pub fn response_stream_agents(agents: &[AgentConfig], agent_ids: &[String]) -> Vec<String> {
+    agent_ids
+        .iter()
+        .filter(|id| {
+            agents.iter().any(|agent| {
+                &agent.id == *id
+                    && agent.events.contains(&AgentEvent::ResponseBody)
+                    && agent.response_body_mode == BodyStreamingMode::Stream
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Pick the streaming mode for a set of agents.
+///
+/// `Stream` wins over `Hybrid`, which wins over `Buffer`. With several hybrid
+/// agents the smallest threshold applies, so none of them is held back longer
+/// than configured.
+pub fn streaming_mode_for_agents(
+    agents: &[AgentConfig],
+    agent_ids: &[String],
+    mode: impl Fn(&AgentConfig) -> BodyStreamingMode,
+) -> BodyStreamingMode {
+    agents
+        .iter()
+        .filter(|agent| agent_ids.contains(&agent.id))
+        .map(mode)
+        .fold(BodyStreamingMode::Buffer, |combined, mode| {
+            match (combined, mode) {
+                (BodyStreamingMode::Stream, _) | (_, BodyStreamingMode::Stream) => {
+                    BodyStreamingMode::Stream
+                }
+                (
+                    BodyStreamingMode::Hybrid {
+                        buffer_threshold: a,
+                    },
+                    BodyStreamingMode::Hybrid {
+                        buffer_threshold: b,
+                    },
+                ) => BodyStreamingMode::Hybrid {
+                    buffer_threshold: a.min(b),
+                },
+                (hybrid @ BodyStreamingMode::Hybrid { .. }, BodyStreamingMode::Buffer)
+                | (BodyStreamingMode::Buffer, hybrid @ BodyStreamingMode::Hybrid { .. }) => hybrid,
+                (BodyStreamingMode::Buffer, BodyStreamingMode::Buffer) => BodyStreamingMode::Buffer,
+            }
+        })
+}
+
+/// Whether agents can rewrite chunks in this mode.
+///
+/// Buffer mode only inspects a copy of the body, so the original is forwarded
+/// untouched and its length stays valid.
+pub fn can_rewrite(mode: BodyStreamingMode) -> bool {
+    !matches!(mode, BodyStreamingMode::Buffer)
+}
+
+/// Apply an agent's mutation to a streamed chunk.
+///
+/// A chunk that ends up empty is replaced by `None` rather than an empty
+/// buffer, which would terminate a chunked body early. Returns `true` if the
+/// chunk changed.
+pub fn apply_body_mutation(body: &mut Option<Bytes>, mutation: &BodyMutation) -> bool {
+    if mutation.is_pass_through() {
+        return false;
+    }
+
+    let mutated = mutation.apply(body.as_deref().unwrap_or_default());
+    *body = if mutated.is_empty() {
+        None
+    } else {
+        Some(Bytes::from(mutated))
+    };
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sentinel_config::{AgentProtocolVersion, AgentTransport, AgentType, FailureMode};
+    use std::path::PathBuf;
+
+    fn agent(id: &str, request_body_mode: BodyStreamingMode) -> AgentConfig {
+        AgentConfig {
+            id: id.to_string(),
+            agent_type: AgentType::Custom("test".to_string()),
+            transport: AgentTransport::UnixSocket {
+                path: PathBuf::from(format!("/tmp/{}.sock", id)),
+            },
+            events: vec![AgentEvent::RequestBody],
+            protocol_version: AgentProtocolVersion::V1,
+            pool: None,
+            timeout_ms: 1000,
+            failure_mode: FailureMode::Open,
+            circuit_breaker: None,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            request_body_mode,
+            response_body_mode: BodyStreamingMode::Buffer,
+            chunk_timeout_ms: 5000,
+            config: None,
+            max_concurrent_calls: 100,
+            recording: None,
+            enforce: true,
+        }
+    }
+
+    #[test]
+    fn test_streaming_mode_for_agents() {
+        let agents = vec![
+            agent("waf", BodyStreamingMode::Buffer),
+            agent(
+                "dlp",
+                BodyStreamingMode::Hybrid {
+                    buffer_threshold: 4096,
+                },
+            ),
+            agent(
+                "scan",
+                BodyStreamingMode::Hybrid {
+                    buffer_threshold: 1024,
+                },
+            ),
+            agent("rewrite", BodyStreamingMode::Stream),
+        ];
+        let mode = |ids: &[&str]| {
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            streaming_mode_for_agents(&agents, &ids, |a| a.request_body_mode)
+        };
+
+        assert_eq!(mode(&[]), BodyStreamingMode::Buffer);
+        assert_eq!(mode(&["waf"]), BodyStreamingMode::Buffer);
+        assert_eq!(
+            mode(&["waf", "dlp", "scan"]),
+            BodyStreamingMode::Hybrid {
+                buffer_threshold: 1024
+            }
+        );
+        assert_eq!(mode(&["dlp", "rewrite"]), BodyStreamingMode::Stream);
+    }
+
+    #[test]
+    fn test_response_stream_agents() {
+        let mut streaming = agent("rewrite", BodyStreamingMode::Buffer);
+        streaming.events = vec![AgentEvent::RequestHeaders, AgentEvent::ResponseBody];
+        streaming.response_body_mode = BodyStreamingMode::Stream;
+        let mut buffered = agent("dlp", BodyStreamingMode::Buffer);
+        buffered.events = vec![AgentEvent::ResponseBody];
+        let mut headers_only = agent("auth", BodyStreamingMode::Buffer);
+        headers_only.events = vec![AgentEvent::RequestHeaders];
+        headers_only.response_body_mode = BodyStreamingMode::Stream;
+        let agents = vec![streaming, buffered, headers_only];
+
+        let ids: Vec<String> = ["auth", "dlp", "rewrite", "missing"]
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        assert_eq!(
+            response_stream_agents(&agents, &ids),
+            vec!["rewrite".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_rewrite_framing() {
+        assert_eq!(rewrite_framing(Version::HTTP_11), RewriteFraming::Chunked);
+        assert_eq!(rewrite_framing(Version::HTTP_2), RewriteFraming::Frames);
+        assert_eq!(rewrite_framing(Version::HTTP_10), RewriteFraming::Unframed);
+    }
+
+    #[test]
+    fn test_apply_body_mutation() {
+        let mut body = Some(Bytes::from_static(b"hello"));
+        assert!(!apply_body_mutation(
+            &mut body,
+            &BodyMutation::pass_through(0)
+        ));
+        assert_eq!(body.as_deref(), Some(&b"hello"[..]));
+
+        assert!(apply_body_mutation(
+            &mut body,
+            &BodyMutation::replace(0, "HELLO".to_string())
+        ));
+        assert_eq!(body.as_deref(), Some(&b"HELLO"[..]));
+
+        assert!(apply_body_mutation(&mut body, &BodyMutation::drop_chunk(0)));
+        assert_eq!(body, None);
+
+        // Inserting on the empty final chunk appends to the body
+        assert!(apply_body_mutation(
+            &mut body,
+            &BodyMutation::insert(1, "\n".to_string())
+        ));
+        assert_eq!(body.as_deref(), Some(&b"\n"[..]));
+    }
+}
@@ -6,9 +6,17 @@
 use std::sync::Arc;
 use std::time::Instant;
 
+use bytes::Bytes;
+use sentinel_agent_protocol::RoutingOverride;
+use sentinel_common::ids::Scope;
 use sentinel_config::{BodyStreamingMode, Config, RouteConfig, ServiceType};
 
-use crate::inference::StreamingTokenCounter;
+use crate::client_cert::ClientCertIdentity;
+use crate::inference::{
+    AdmissionPermit, CacheHitKind, InferenceAuditor, InferenceCacheRecorder, InferenceCacheRequest,
+    InferenceTranslator, StreamGuard, StreamTiming, StreamTranslator, StreamingTokenCounter,
+    ToolCallFilter, ToolPolicy, VirtualKey,
+};
 use crate::websocket::WebSocketHandler;
 
 /// Reason why fallback routing was triggered
@@ -20,6 +28,8 @@ pub enum FallbackReason {
     BudgetExhausted,
     /// Response latency exceeded threshold
     LatencyThreshold { observed_ms: u64, threshold_ms: u64 },
+    /// Time to first token of the primary upstream exceeded threshold
+    TtftThreshold { observed_ms: u64, threshold_ms: u64 },
     /// Upstream returned an error code that triggers fallback
     ErrorCode(u16),
     /// Connection to upstream failed
@@ -35,6 +45,14 @@ impl std::fmt::Display for FallbackReason {
                 observed_ms,
                 threshold_ms,
             } => write!(f, "latency_threshold_{}ms_exceeded_{}ms", observed_ms, threshold_ms),
+            FallbackReason::TtftThreshold {
+                observed_ms,
+                threshold_ms,
+            } => write!(
+                f,
+                "ttft_threshold_{}ms_exceeded_{}ms",
+                observed_ms, threshold_ms
+            ),
             FallbackReason::ErrorCode(code) => write!(f, "error_code_{}", code),
             FallbackReason::ConnectionError(msg) => write!(f, "connection_error_{}", msg),
         }
@@ -104,10 +122,16 @@ pub struct RequestContext {
     pub(crate) referer: Option<String>,
     /// Host header
     pub(crate) host: Option<String>,
+    /// Verified client certificate (mTLS listeners only)
+    pub(crate) client_cert: Option<Arc<ClientCertIdentity>>,
 
     // === Body tracking ===
     /// Request body bytes received
     pub(crate) request_body_bytes: u64,
+    /// Whole request body read in request_filter, not yet sent upstream
+    pub(crate) prefetched_request_body: Option<Bytes>,
+    /// SHA-256 of the request body an AWS SigV4 signature covers
+    pub(crate) signed_payload_sha256: Option<String>,
     /// Response body bytes (set during response)
     pub(crate) response_bytes: u64,
 
@@ -170,6 +194,9 @@ pub struct RequestContext {
     pub(crate) request_body_chunk_index: u32,
     /// Whether agent needs more data (streaming mode)
     pub(crate) agent_needs_more: bool,
+    /// Whether the upstream request keeps its Content-Length (HTTP/1.0), so
+    /// agents cannot resize request body chunks
+    pub(crate) request_body_length_fixed: bool,
     /// Body streaming mode for response body inspection
     pub(crate) response_body_streaming_mode: BodyStreamingMode,
     /// Current chunk index for response body streaming
@@ -180,6 +207,8 @@ pub struct RequestContext {
     pub(crate) response_body_inspection_enabled: bool,
     /// Agent IDs for response body inspection
     pub(crate) response_body_inspection_agents: Vec<String>,
+    /// Whether a response agent needs more data (streaming mode)
+    pub(crate) response_agent_needs_more: bool,
 
     // === OpenTelemetry Tracing ===
     /// OpenTelemetry request span (if tracing enabled)
@@ -203,6 +232,20 @@ pub struct RequestContext {
     /// Actual tokens from response (filled in after response)
     pub(crate) inference_actual_tokens: Option<u64>,
 
+    // === Virtual API Keys ===
+    /// Virtual key the client authenticated with
+    pub(crate) virtual_key: Option<Arc<VirtualKey>>,
+
+    // === Inference Response Cache ===
+    /// Cache must be checked at the end of request_filter
+    pub(crate) inference_cache_pending: bool,
+    /// Normalised request, kept on a miss so the response can be stored
+    pub(crate) inference_cache_request: Option<InferenceCacheRequest>,
+    /// Response being recorded for the cache
+    pub(crate) inference_cache_recorder: Option<InferenceCacheRecorder>,
+    /// How the response was served from cache (if it was)
+    pub(crate) inference_cache_hit: Option<CacheHitKind>,
+
     // === Token Budget Tracking ===
     /// Whether budget tracking is enabled for this route
     pub(crate) inference_budget_enabled: bool,
@@ -228,6 +271,24 @@ pub struct RequestContext {
     pub(crate) inference_streaming_response: bool,
     /// Streaming token counter for SSE responses
     pub(crate) inference_streaming_counter: Option<StreamingTokenCounter>,
+    /// Non-streaming response body kept for usage extraction (None when not collected)
+    pub(crate) inference_usage_body: Option<Vec<u8>>,
+    /// Streamed deltas held back for the streaming output guardrail
+    pub(crate) inference_stream_guard: Option<StreamGuard>,
+    /// Time to first token and inter-token latency of the response
+    pub(crate) inference_timing: StreamTiming,
+
+    // === Inference Audit ===
+    /// Audit settings, when this request was sampled for the inference audit log
+    pub(crate) inference_audit: Option<Arc<InferenceAuditor>>,
+    /// Request body kept for the audit record (up to a size limit)
+    pub(crate) inference_audit_request: Vec<u8>,
+    /// Response body as sent to the client, kept for the audit record
+    pub(crate) inference_audit_response: Vec<u8>,
+
+    // === Priority Admission ===
+    /// Admission queue permit, held until the request completes
+    pub(crate) inference_admission: Option<AdmissionPermit>,
 
     // === Fallback Routing ===
     /// Current fallback attempt number (0 = primary, 1+ = fallback)
@@ -243,6 +304,16 @@ pub struct RequestContext {
     /// Whether fallback should be retried after response
     pub(crate) should_retry_with_fallback: bool,
 
+    // === Provider Translation ===
+    /// Translator when the upstream speaks a different inference API than the client
+    pub(crate) inference_translator: Option<InferenceTranslator>,
+    /// Request body held back until it can be translated as a whole
+    pub(crate) translation_request_buffer: Vec<u8>,
+    /// Response body held back until it can be translated (non-streaming)
+    pub(crate) translation_response_buffer: Vec<u8>,
+    /// Event translator for streaming (SSE) responses
+    pub(crate) translation_stream: Option<StreamTranslator>,
+
     // === Semantic Guardrails ===
     /// Whether guardrails are enabled for this route
     pub(crate) guardrails_enabled: bool,
@@ -253,6 +324,14 @@ pub struct RequestContext {
     /// PII categories detected in response (for logging)
     pub(crate) pii_detection_categories: Vec<String>,
 
+    // === Tool Policy ===
+    /// Tool definitions must be checked once the request body arrives
+    pub(crate) inference_tool_policy_pending: bool,
+    /// Request body held back until its tools have been checked
+    pub(crate) inference_tool_policy_buffer: Vec<u8>,
+    /// Tool calls in the response being checked against the policy
+    pub(crate) inference_tool_filter: Option<ToolCallFilter>,
+
     // === Shadow Traffic ===
     /// Pending shadow request info (stored for deferred execution after body buffering)
     pub(crate) shadow_pending: Option<ShadowPendingRequest>,
@@ -266,6 +345,12 @@ pub struct RequestContext {
     pub(crate) sticky_session_set_cookie: Option<String>,
     /// Target index for sticky session (for logging)
     pub(crate) sticky_target_index: Option<usize>,
+
+    // === Agent Routing Overrides ===
+    /// Upstream selection override requested by request-header agents
+    pub(crate) routing_override: Option<RoutingOverride>,
+    /// Parts of the override that passed validation (for logging)
+    pub(crate) routing_override_applied: Option<RoutingOverride>,
 }
 
 /// Pending shadow request information stored in context for deferred execution
@@ -284,8 +369,9 @@ pub struct ShadowPendingRequest {
 impl RequestContext {
     /// Create a new empty request context with the current timestamp.
     pub fn new() -> Self {
+        let start_time = Instant::now();
         Self {
-            start_time: Instant::now(),
+            start_time,
             trace_id: String::new(),
             config: None,
             route_id: None,
@@ -300,9 +386,12 @@ impl RequestContext {
             query: None,
             client_ip: String::new(),
             user_agent: None,
+            client_cert: None,
             referer: None,
             host: None,
             request_body_bytes: 0,
+            prefetched_request_body: None,
+            signed_payload_sha256: None,
             response_bytes: 0,
             connection_reused: false,
             is_websocket_upgrade: false,
@@ -326,11 +415,13 @@ impl RequestContext {
             request_body_streaming_mode: BodyStreamingMode::Buffer,
             request_body_chunk_index: 0,
             agent_needs_more: false,
+            request_body_length_fixed: false,
             response_body_streaming_mode: BodyStreamingMode::Buffer,
             response_body_chunk_index: 0,
             response_body_bytes_inspected: 0,
             response_body_inspection_enabled: false,
             response_body_inspection_agents: Vec::new(),
+            response_agent_needs_more: false,
             otel_span: None,
             trace_context: None,
             inference_rate_limit_enabled: false,
@@ -340,6 +431,11 @@ impl RequestContext {
             inference_provider_override: None,
             model_routing_used: false,
             inference_actual_tokens: None,
+            virtual_key: None,
+            inference_cache_pending: false,
+            inference_cache_request: None,
+            inference_cache_recorder: None,
+            inference_cache_hit: None,
             inference_budget_enabled: false,
             inference_budget_remaining: None,
             inference_budget_period_reset: None,
@@ -350,21 +446,37 @@ impl RequestContext {
             inference_output_tokens: 0,
             inference_streaming_response: false,
             inference_streaming_counter: None,
+            inference_usage_body: None,
+            inference_stream_guard: None,
+            inference_timing: StreamTiming::new(start_time),
+            inference_audit: None,
+            inference_audit_request: Vec::new(),
+            inference_audit_response: Vec::new(),
+            inference_admission: None,
             fallback_attempt: 0,
             tried_upstreams: Vec::new(),
             fallback_reason: None,
             original_upstream: None,
             model_mapping_applied: None,
             should_retry_with_fallback: false,
+            inference_translator: None,
+            translation_request_buffer: Vec::new(),
+            translation_response_buffer: Vec::new(),
+            translation_stream: None,
             guardrails_enabled: false,
             guardrail_warning: false,
             guardrail_detection_categories: Vec::new(),
             pii_detection_categories: Vec::new(),
+            inference_tool_policy_pending: false,
+            inference_tool_policy_buffer: Vec::new(),
+            inference_tool_filter: None,
             shadow_pending: None,
             shadow_sent: false,
             sticky_session_new_assignment: false,
             sticky_session_set_cookie: None,
             sticky_target_index: None,
+            routing_override: None,
+            routing_override_applied: None,
         }
     }
 
@@ -462,6 +574,12 @@ impl RequestContext {
         &self.client_ip
     }
 
+    /// Get the verified client certificate, if one was presented.
+    #[inline]
+    pub fn client_cert(&self) -> Option<&ClientCertIdentity> {
+        self.client_cert.as_deref()
+    }
+
     /// Get the User-Agent header, if present.
     #[inline]
     pub fn user_agent(&self) -> Option<&str> {
@@ -510,6 +628,18 @@ impl RequestContext {
         })
     }
 
+    /// Get the configuration scope the request was routed in.
+    pub fn scope(&self) -> Scope {
+        match (&self.namespace, &self.service) {
+            (Some(namespace), Some(service)) => Scope::Service {
+                namespace: namespace.clone(),
+                service: service.clone(),
+            },
+            (Some(namespace), None) => Scope::Namespace(namespace.clone()),
+            _ => Scope::Global,
+        }
+    }
+
     // === Mutation helpers ===
 
     /// Set the trace ID.
@@ -638,6 +768,91 @@ impl RequestContext {
         }
         self.inference_provider_override = provider_override;
     }
+
+    // === Tool policy ===
+
+    /// Tool policy of the route, resolved for the request's tenant (the
+    /// virtual key's tenant, otherwise the client IP).
+    pub(crate) fn tool_policy(&self) -> Option<ToolPolicy> {
+        let config = self
+            .route_config
+            .as_ref()?
+            .inference
+            .as_ref()?
+            .guardrails
+            .as_ref()?
+            .tool_policy
+            .as_ref()
+            .filter(|config| config.enabled)?;
+        let tenant = match self.virtual_key {
+            Some(ref key) => key.tenant(),
+            None => self.client_ip.as_str(),
+        };
+        Some(ToolPolicy::new(config, tenant))
+    }
+
+    // === Inference output ===
+
+    /// Record response body bytes sent to the client on an inference route:
+    /// each non-empty chunk is timed for the time to first token and
+    /// inter-token latency, and the body is kept (up to a size limit) when the
+    /// request is being audited.
+    pub(crate) fn record_inference_output(&mut self, chunk: &[u8]) {
+        if chunk.is_empty() {
+            return;
+        }
+        self.inference_timing.record_chunk(Instant::now());
+        if self
+            .inference_audit
+            .as_ref()
+            .is_some_and(|auditor| auditor.config().include_completion)
+        {
+            append_bounded(&mut self.inference_audit_response, chunk);
+        }
+    }
+
+    /// Keep a non-streaming inference response for reading token usage,
+    /// giving up on it once it grows past `limit` bytes.
+    pub(crate) fn record_inference_usage(&mut self, chunk: &[u8], limit: usize) {
+        if let Some(ref mut body) = self.inference_usage_body {
+            if body.len() + chunk.len() <= limit {
+                body.extend_from_slice(chunk);
+            } else {
+                self.inference_usage_body = None;
+            }
+        }
+    }
+
+    /// Hold back request body bytes for the tool policy check. Returns
+    /// `false`, dropping what was held, once the body grows past `limit` bytes.
+    pub(crate) fn buffer_tool_policy_request(&mut self, chunk: &[u8], limit: usize) -> bool {
+        if self.inference_tool_policy_buffer.len() + chunk.len() > limit {
+            self.inference_tool_policy_buffer = Vec::new();
+            return false;
+        }
+        self.inference_tool_policy_buffer.extend_from_slice(chunk);
+        true
+    }
+
+    /// Keep request body bytes for the inference audit record.
+    pub(crate) fn record_inference_input(&mut self, chunk: &[u8]) {
+        if self
+            .inference_audit
+            .as_ref()
+            .is_some_and(|auditor| auditor.config().include_prompt)
+        {
+            append_bounded(&mut self.inference_audit_request, chunk);
+        }
+    }
+}
+
+/// Largest request or response body kept for an inference audit record
+const MAX_INFERENCE_AUDIT_BODY_BYTES: usize = 1024 * 1024;
+
+/// Append to an audit buffer, dropping whatever exceeds the size limit.
+fn append_bounded(buffer: &mut Vec<u8>, chunk: &[u8]) {
+    let room = MAX_INFERENCE_AUDIT_BODY_BYTES.saturating_sub(buffer.len());
+    buffer.extend_from_slice(&chunk[..chunk.len().min(room)]);
 }
 
 impl Default for RequestContext {
@@ -789,6 +1004,37 @@ mod tests {
         assert_eq!(mapping.1, "claude-3-opus");
     }
 
+    #[test]
+    fn test_inference_usage_body_limit() {
+        let mut ctx = RequestContext::new();
+        ctx.record_inference_usage(b"ignored", 16);
+        assert!(ctx.inference_usage_body.is_none());
+
+        ctx.inference_usage_body = Some(Vec::new());
+        ctx.record_inference_usage(br#"{"usage":"#, 16);
+        ctx.record_inference_usage(b"{}}", 16);
+        assert_eq!(
+            ctx.inference_usage_body.as_deref(),
+            Some(&br#"{"usage":{}}"#[..])
+        );
+
+        // Past the limit the partial body is dropped, not read
+        ctx.record_inference_usage(b"trailing bytes", 16);
+        assert!(ctx.inference_usage_body.is_none());
+    }
+
+    #[test]
+    fn test_tool_policy_request_buffer_limit() {
+        let mut ctx = RequestContext::new();
+        assert!(ctx.buffer_tool_policy_request(br#"{"tools":"#, 16));
+        assert!(ctx.buffer_tool_policy_request(b"[]}", 16));
+        assert_eq!(ctx.inference_tool_policy_buffer, br#"{"tools":[]}"#);
+
+        // Past the limit the request can't be checked, so nothing is kept
+        assert!(!ctx.buffer_tool_policy_request(b"trailing bytes", 16));
+        assert!(ctx.inference_tool_policy_buffer.is_empty());
+    }
+
     #[test]
     fn test_fallback_reason_display() {
         assert_eq!(
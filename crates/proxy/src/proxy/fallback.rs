@@ -2,7 +2,7 @@
 //!
 //! This module provides the `FallbackEvaluator` which determines when to trigger
 //! fallback routing based on configurable conditions (health failures, budget
-//! exhaustion, latency thresholds, error codes).
+//! exhaustion, latency and time-to-first-token thresholds, error codes).
 //!
 //! It also handles cross-provider model mapping (e.g., `gpt-4` → `claude-3-opus`)
 //! with support for glob patterns in model names.
@@ -156,6 +156,41 @@ impl<'a> FallbackEvaluator<'a> {
         None
     }
 
+    /// Check if fallback should be triggered by the primary upstream's time to
+    /// first token (if `on_ttft_threshold_ms` is configured).
+    ///
+    /// # Arguments
+    /// * `upstream_id` - The upstream that would be used
+    /// * `observed_ttft_ms` - TTFT moving average of the upstream's fastest target
+    /// * `current_model` - The model being requested (for model mapping)
+    ///
+    /// # Returns
+    /// `Some(FallbackDecision)` if fallback should be used, `None` otherwise.
+    pub fn should_fallback_on_ttft(
+        &self,
+        upstream_id: &str,
+        observed_ttft_ms: u64,
+        current_model: Option<&str>,
+    ) -> Option<FallbackDecision> {
+        if !self.can_attempt_fallback() {
+            return None;
+        }
+
+        let threshold_ms = self.config.triggers.on_ttft_threshold_ms?;
+        if observed_ttft_ms > threshold_ms {
+            return self.create_fallback_decision(
+                FallbackReason::TtftThreshold {
+                    observed_ms: observed_ttft_ms,
+                    threshold_ms,
+                },
+                upstream_id,
+                current_model,
+            );
+        }
+
+        None
+    }
+
     /// Check if fallback should be triggered due to a connection error.
     ///
     /// # Arguments
@@ -332,6 +367,7 @@ mod tests {
                 on_health_failure: true,
                 on_budget_exhausted: true,
                 on_latency_threshold_ms: Some(5000),
+                on_ttft_threshold_ms: Some(2000),
                 on_error_codes: vec![429, 500, 502, 503, 504],
                 on_connection_error: true,
             },
@@ -556,6 +592,28 @@ mod tests {
         assert_eq!(evaluator.map_model(upstream, "gpt-3.5-turbo"), "llama-3-8b");
     }
 
+    #[test]
+    fn test_fallback_on_ttft_threshold() {
+        let config = create_test_config();
+        let evaluator = FallbackEvaluator::new(&config, &[], 0);
+
+        assert!(evaluator
+            .should_fallback_on_ttft("openai-primary", 1500, Some("gpt-4"))
+            .is_none());
+
+        let decision = evaluator
+            .should_fallback_on_ttft("openai-primary", 2500, Some("gpt-4"))
+            .unwrap();
+        assert_eq!(decision.next_upstream, "anthropic-fallback");
+        assert!(matches!(
+            decision.reason,
+            FallbackReason::TtftThreshold {
+                observed_ms: 2500,
+                threshold_ms: 2000
+            }
+        ));
+    }
+
     #[test]
     fn test_no_fallback_when_healthy_and_budget_ok() {
         let config = create_test_config();
@@ -138,6 +138,7 @@ impl FallbackMetrics {
             FallbackReason::HealthCheckFailed => "health_check_failed",
             FallbackReason::BudgetExhausted => "budget_exhausted",
             FallbackReason::LatencyThreshold { .. } => "latency_threshold",
+            FallbackReason::TtftThreshold { .. } => "ttft_threshold",
             FallbackReason::ErrorCode(_) => "error_code",
             FallbackReason::ConnectionError(_) => "connection_error",
         }
@@ -165,6 +166,13 @@ mod tests {
             }),
             "latency_threshold"
         );
+        assert_eq!(
+            FallbackMetrics::reason_label(&FallbackReason::TtftThreshold {
+                observed_ms: 2500,
+                threshold_ms: 2000
+            }),
+            "ttft_threshold"
+        );
         assert_eq!(
             FallbackMetrics::reason_label(&FallbackReason::ErrorCode(503)),
             "error_code"
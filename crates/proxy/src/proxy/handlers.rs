@@ -5,11 +5,17 @@
 //! - Builtin handlers (health, metrics, config, upstreams)
 //! - API validation
 //! - Agent processing
+//! - Agent challenges and challenge callbacks
+//! - Virtual API key rejections
+//! - Inference admission (priority classes)
+//! - Inference cache hits
+//! - Tool policy detections
 //! - Error responses
 
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use bytes::{Bytes, BytesMut};
 use pingora::http::ResponseHeader;
 use pingora::prelude::*;
 use pingora::proxy::Session;
@@ -17,10 +23,18 @@ use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::builtin_handlers;
-use crate::logging::{AuditEventType, AuditLogEntry};
+use crate::challenge::{
+    get_challenge_metrics, ChallengeClient, ChallengeManager, ChallengeResponse,
+};
+use crate::inference::{
+    cache_scope, create_provider, get_inference_metrics, AdmissionRejection, InferenceCacheRequest,
+    ToolUse, VirtualKey, VirtualKeyError, TOOL_POLICY_CATEGORY,
+};
+use crate::logging::{AuditEventType, AuditLogEntry, InferenceAuditEntry};
 use crate::routing::RouteMatch;
 use crate::validation::SchemaValidator;
 
+use super::body_rewrite;
 use super::context::RequestContext;
 use super::SentinelProxy;
 
@@ -310,27 +324,29 @@ impl SentinelProxy {
     }
 
     /// Process request through external agents
+    ///
+    /// Returns `Ok(true)` when a response (challenge) has already been written.
     pub(super) async fn process_agents(
         &self,
         session: &mut Session,
         ctx: &mut RequestContext,
         client_addr: &str,
         client_port: u16,
-    ) -> Result<(), Box<Error>> {
+    ) -> Result<bool, Box<Error>> {
         use crate::agents::AgentAction;
 
         // Use cached route config from context (already matched in upstream_peer)
         let Some(ref route_config) = ctx.route_config else {
-            return Ok(());
+            return Ok(false);
         };
 
         // Fast path: if route has no filters, skip agent processing entirely
         if route_config.filters.is_empty() {
-            return Ok(());
+            return Ok(false);
         }
 
         let Some(ref route_id) = ctx.route_id else {
-            return Ok(());
+            return Ok(false);
         };
 
         // Use cached config (or fetch if not yet cached)
@@ -358,7 +374,7 @@ impl SentinelProxy {
             .collect();
 
         if agent_filters.is_empty() {
-            return Ok(());
+            return Ok(false);
         }
 
         // Extract just the agent IDs for logging and body inspection
@@ -371,6 +387,14 @@ impl SentinelProxy {
             "Processing request through agents"
         );
 
+        // Shadow agents must not buffer or re-frame bodies whose decisions are
+        // never applied, so only enforcing agents get body events
+        let body_agent_ids: Vec<String> = agent_ids
+            .iter()
+            .filter(|id| config.agents.iter().any(|a| &a.id == *id && a.enforce))
+            .cloned()
+            .collect();
+
         // Set up body inspection if enabled
         let body_inspection_enabled = config
             .waf
@@ -378,7 +402,7 @@ impl SentinelProxy {
             .map(|w| w.body_inspection.inspect_request_body)
             .unwrap_or(false);
 
-        if body_inspection_enabled {
+        if body_inspection_enabled && !body_agent_ids.is_empty() {
             // Check content-type allowlist
             let content_type = session
                 .req_header()
@@ -400,7 +424,11 @@ impl SentinelProxy {
 
             if is_allowed_type || allowed_types.is_empty() {
                 ctx.body_inspection_enabled = true;
-                ctx.body_inspection_agents = agent_ids.clone();
+                ctx.request_body_streaming_mode =
+                    body_rewrite::streaming_mode_for_agents(&config.agents, &body_agent_ids, |a| {
+                        a.request_body_mode
+                    });
+                ctx.body_inspection_agents = body_agent_ids.clone();
 
                 // Set up decompression if enabled in WAF config
                 let decompress_enabled = config
@@ -448,8 +476,9 @@ impl SentinelProxy {
                 debug!(
                     correlation_id = %ctx.trace_id,
                     content_type = %content_type,
-                    agent_count = agent_ids.len(),
+                    agent_count = ctx.body_inspection_agents.len(),
                     decompression = ctx.decompression_enabled,
+                    streaming_mode = ?ctx.request_body_streaming_mode,
                     "Body inspection enabled for request"
                 );
             } else {
@@ -462,6 +491,28 @@ impl SentinelProxy {
             }
         }
 
+        // Response bodies are only sent chunk by chunk to agents that ask for them
+        let response_body_inspection_enabled = config
+            .waf
+            .as_ref()
+            .map(|w| w.body_inspection.inspect_response_body)
+            .unwrap_or(false);
+
+        if response_body_inspection_enabled {
+            let response_agents =
+                body_rewrite::response_stream_agents(&config.agents, &body_agent_ids);
+            if !response_agents.is_empty() {
+                debug!(
+                    correlation_id = %ctx.trace_id,
+                    agents = ?response_agents,
+                    "Body inspection enabled for response"
+                );
+                ctx.response_body_inspection_enabled = true;
+                ctx.response_body_inspection_agents = response_agents;
+                ctx.response_body_streaming_mode = sentinel_config::BodyStreamingMode::Stream;
+            }
+        }
+
         let req_header = session.req_header_mut();
 
         // Build headers map for agent processing
@@ -486,6 +537,8 @@ impl SentinelProxy {
             .path_and_query()
             .map(|pq| pq.as_str().to_string())
             .unwrap_or_else(|| req_header.uri.path().to_string());
+        // Where a challenged client returns once it passes
+        let return_to = full_path.clone();
         headers_map.insert(":path".to_string(), vec![full_path]);
 
         // Create agent call context
@@ -504,6 +557,7 @@ impl SentinelProxy {
                 upstream_id: ctx.upstream.clone(),
                 timestamp: chrono::Utc::now().to_rfc3339(),
                 traceparent: ctx.traceparent(),
+                client_cert: ctx.client_cert().map(|cert| cert.info().clone()),
             },
             route_id: Some(route_id.clone()),
             upstream_id: ctx.upstream.clone(),
@@ -514,7 +568,12 @@ impl SentinelProxy {
         // Process through agents (passing filter-specific failure modes)
         match self
             .agent_manager
-            .process_request_headers(&agent_ctx, headers_map, &agent_filters)
+            .process_request_headers(
+                &agent_ctx,
+                headers_map,
+                &agent_filters,
+                route_config.policies.agent_execution,
+            )
             .await
         {
             Ok(decision) => {
@@ -578,7 +637,65 @@ impl SentinelProxy {
                                 format!("Redirect to {}", url),
                             ));
                         }
-                        _ => {}
+                        AgentAction::Challenge {
+                            challenge_type,
+                            params,
+                        } => {
+                            let settings = ChallengeManager::settings(config);
+                            let header_value = |name: &str| {
+                                req_header.headers.get(name).and_then(|v| v.to_str().ok())
+                            };
+                            let client = ChallengeClient {
+                                client_addr,
+                                user_agent: header_value("user-agent"),
+                                cookie_header: header_value("cookie"),
+                            };
+
+                            if self.challenge_manager.has_clearance(settings, &client) {
+                                debug!(
+                                    correlation_id = %ctx.trace_id,
+                                    challenge_type = %challenge_type,
+                                    "Challenge skipped, client holds a valid clearance"
+                                );
+                                if let Some(metrics) = get_challenge_metrics() {
+                                    metrics.record_bypass();
+                                }
+                            } else {
+                                info!(
+                                    correlation_id = %ctx.trace_id,
+                                    challenge_type = %challenge_type,
+                                    "Request challenged by agent"
+                                );
+
+                                let response = self.challenge_manager.issue(
+                                    settings,
+                                    &challenge_type,
+                                    &params,
+                                    &client,
+                                    &return_to,
+                                );
+
+                                let audit_entry = AuditLogEntry::new(
+                                    &ctx.trace_id,
+                                    AuditEventType::AgentDecision,
+                                    &ctx.method,
+                                    &ctx.path,
+                                    &ctx.client_ip,
+                                )
+                                .with_route_id(ctx.route_id.as_deref().unwrap_or("unknown"))
+                                .with_action("challenge")
+                                .with_status_code(response.status)
+                                .with_reason(format!(
+                                    "{} challenge requested by agent",
+                                    challenge_type
+                                ));
+                                self.log_manager.log_audit(&audit_entry);
+
+                                self.write_challenge_response(session, response).await?;
+                                return Ok(true);
+                            }
+                        }
+                        AgentAction::Allow => {}
                     }
                 }
 
@@ -597,6 +714,9 @@ impl SentinelProxy {
                     }
                 }
 
+                // Applied in upstream_peer once the route's upstream is known
+                ctx.routing_override = decision.routing;
+
                 debug!(
                     correlation_id = %ctx.trace_id,
                     "Agent processing completed, request allowed"
@@ -619,7 +739,7 @@ impl SentinelProxy {
             }
         }
 
-        Ok(())
+        Ok(false)
     }
 
     /// Handle error responses with custom error pages
@@ -684,6 +804,615 @@ impl SentinelProxy {
         Ok(())
     }
 
+    /// Serve challenge callbacks (solved proof-of-work, cookie round-trip, CAPTCHA)
+    ///
+    /// Returns `Ok(true)` if the request was a challenge callback and has been answered.
+    pub(super) async fn handle_challenge_callback(
+        &self,
+        session: &mut Session,
+        ctx: &mut RequestContext,
+    ) -> Result<bool, Box<Error>> {
+        let config = ctx
+            .config
+            .get_or_insert_with(|| self.config_manager.current())
+            .clone();
+        let settings = ChallengeManager::settings(&config);
+
+        if !ChallengeManager::is_callback(settings, &ctx.path) {
+            return Ok(false);
+        }
+
+        let client_addr = session
+            .client_addr()
+            .map(|a| a.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let req_header = session.req_header();
+        let header_value = |name: &str| req_header.headers.get(name).and_then(|v| v.to_str().ok());
+        let client = ChallengeClient {
+            client_addr: &client_addr,
+            user_agent: header_value("user-agent"),
+            cookie_header: header_value("cookie"),
+        };
+
+        let response = self
+            .challenge_manager
+            .handle_callback(settings, &ctx.path, req_header.uri.query(), &client)
+            .await;
+
+        debug!(
+            correlation_id = %ctx.trace_id,
+            path = %ctx.path,
+            status = response.status,
+            "Served challenge callback"
+        );
+
+        self.write_challenge_response(session, response).await?;
+        Ok(true)
+    }
+
+    /// Write a challenge interstitial, redirect or rejection to the session
+    async fn write_challenge_response(
+        &self,
+        session: &mut Session,
+        response: ChallengeResponse,
+    ) -> Result<(), Box<Error>> {
+        let mut resp_header = ResponseHeader::build(response.status, None)?;
+        for (name, value) in response.headers {
+            // Set-Cookie may repeat, so append rather than insert
+            resp_header.append_header(name, &value)?;
+        }
+        resp_header.insert_header("Content-Length", response.body.len().to_string())?;
+
+        session.set_keepalive(None);
+        session
+            .write_response_header(Box::new(resp_header), false)
+            .await?;
+        session
+            .write_response_body(Some(bytes::Bytes::from(response.body)), true)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Log and audit a virtual API key that failed authentication or authorization
+    pub(super) fn audit_virtual_key_rejection(
+        &self,
+        ctx: &RequestContext,
+        route_id: &str,
+        error: &VirtualKeyError,
+    ) {
+        warn!(
+            correlation_id = %ctx.trace_id,
+            route_id = route_id,
+            client_ip = %ctx.client_ip,
+            error = %error,
+            "Virtual API key rejected"
+        );
+        self.metrics.record_blocked_request("virtual_key_rejected");
+
+        let audit_entry = AuditLogEntry::new(
+            &ctx.trace_id,
+            AuditEventType::AuthEvent,
+            &ctx.method,
+            &ctx.path,
+            &ctx.client_ip,
+        )
+        .with_route_id(route_id)
+        .with_status_code(error.status_code())
+        .with_reason(error.to_string());
+        self.log_manager.log_audit(&audit_entry);
+    }
+
+    /// Read the whole request body ahead of upstream selection.
+    ///
+    /// Checks that need the body before anything is sent upstream (the virtual
+    /// key's model allowlist, the inference cache) share one read. The body is
+    /// kept on the context and handed on by `request_body_filter`. Bodies over
+    /// `limits.max-body-size-bytes` are rejected with 413.
+    pub(super) async fn prefetch_request_body(
+        &self,
+        session: &mut Session,
+        ctx: &mut RequestContext,
+    ) -> Result<Bytes, Box<Error>> {
+        if let Some(ref body) = ctx.prefetched_request_body {
+            return Ok(body.clone());
+        }
+
+        let limit = ctx
+            .config
+            .get_or_insert_with(|| self.config_manager.current())
+            .limits
+            .max_body_size_bytes;
+        session.enable_retry_buffering();
+
+        let mut buffer = BytesMut::new();
+        while let Some(chunk) = session.read_request_body().await? {
+            if buffer.len() + chunk.len() > limit {
+                warn!(
+                    correlation_id = %ctx.trace_id,
+                    limit = limit,
+                    "Request body size limit exceeded"
+                );
+                self.metrics.record_blocked_request("body_size_exceeded");
+                return Err(Error::explain(
+                    ErrorType::HTTPStatus(413),
+                    "Request body too large",
+                ));
+            }
+            buffer.extend_from_slice(&chunk);
+        }
+
+        let body = buffer.freeze();
+        ctx.prefetched_request_body = Some(body.clone());
+        Ok(body)
+    }
+
+    /// Check the models named in the request body, the headers and the path
+    /// against the client's virtual key, before the upstream or its
+    /// credential see the request. A request naming no model is rejected.
+    ///
+    /// Returns `Ok(true)` if the model is not allowed and a response has been sent.
+    pub(super) async fn check_virtual_key_model(
+        &self,
+        session: &mut Session,
+        ctx: &mut RequestContext,
+        route_id: &str,
+        key: &VirtualKey,
+    ) -> Result<bool, Box<Error>> {
+        if !key.restricts_models() {
+            return Ok(false);
+        }
+
+        let body = self.prefetch_request_body(session, ctx).await?;
+        let body_model = serde_json::from_slice::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|json| json.get("model")?.as_str().map(str::to_string));
+        // Gemini and Bedrock name the model in the path
+        let path_model = ctx
+            .route_config
+            .as_ref()
+            .and_then(|route| route.inference.as_ref())
+            .and_then(|inference| create_provider(&inference.provider).model_from_path(&ctx.path));
+
+        let models: Vec<&str> = [&body_model, &ctx.inference_model, &path_model]
+            .into_iter()
+            .filter_map(|model| model.as_deref())
+            .collect();
+        let allowed = if models.is_empty() {
+            Err(VirtualKeyError::ModelNotAllowed {
+                key_id: key.id().to_string(),
+                model: "<unspecified>".to_string(),
+            })
+        } else {
+            models
+                .into_iter()
+                .try_for_each(|model| key.check_model(model))
+        };
+        match allowed {
+            Ok(()) => Ok(false),
+            Err(e) => {
+                self.reject_virtual_key(session, ctx, route_id, &e).await?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Reject a request whose virtual API key failed authentication or authorization
+    pub(super) async fn reject_virtual_key(
+        &self,
+        session: &mut Session,
+        ctx: &RequestContext,
+        route_id: &str,
+        error: &VirtualKeyError,
+    ) -> Result<(), Box<Error>> {
+        self.audit_virtual_key_rejection(ctx, route_id, error);
+
+        let body = serde_json::json!({
+            "error": error.code(),
+            "message": error.to_string(),
+        })
+        .to_string();
+        crate::http_helpers::write_error(session, error.status_code(), &body, "application/json")
+            .await
+    }
+
+    /// Apply a virtual key's own token rate limit and budget
+    ///
+    /// Returns `Ok(true)` if the key is over a limit and a 429 has been sent.
+    pub(super) async fn check_virtual_key_limits(
+        &self,
+        session: &mut Session,
+        ctx: &RequestContext,
+        route_id: &str,
+        key: &VirtualKey,
+        estimated_tokens: u64,
+    ) -> Result<bool, Box<Error>> {
+        let rate_limit = key.check_rate_limit(estimated_tokens);
+        let (reason, retry_after_secs) = if !rate_limit.is_allowed() {
+            (
+                "API key token rate limit exceeded",
+                rate_limit.retry_after_ms().div_ceil(1000),
+            )
+        } else {
+            match key.check_budget(estimated_tokens).await {
+                Some(budget) if !budget.is_allowed() => {
+                    ("API key token budget exhausted", budget.retry_after_secs())
+                }
+                _ => return Ok(false),
+            }
+        };
+
+        warn!(
+            correlation_id = %ctx.trace_id,
+            route_id = route_id,
+            key_id = key.id(),
+            tenant = key.tenant(),
+            estimated_tokens = estimated_tokens,
+            retry_after_secs = retry_after_secs,
+            reason = reason,
+            "Virtual API key limit exceeded"
+        );
+        self.metrics.record_blocked_request("virtual_key_limited");
+
+        let audit_entry = AuditLogEntry::new(
+            &ctx.trace_id,
+            AuditEventType::RateLimitExceeded,
+            &ctx.method,
+            &ctx.path,
+            &ctx.client_ip,
+        )
+        .with_route_id(route_id)
+        .with_status_code(429)
+        .with_reason(format!("{} (key '{}')", reason, key.id()));
+        self.log_manager.log_audit(&audit_entry);
+
+        let reset_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            + retry_after_secs;
+        crate::http_helpers::write_rate_limit_error(
+            session,
+            429,
+            reason,
+            0,
+            0,
+            reset_at,
+            retry_after_secs,
+        )
+        .await?;
+        Ok(true)
+    }
+
+    /// Wait for admission through the route's priority admission queue
+    ///
+    /// Returns `Ok(true)` if the request was shed or timed out in the queue
+    /// and a 503 with `Retry-After` has been sent.
+    pub(super) async fn admit_inference_request(
+        &self,
+        session: &mut Session,
+        ctx: &mut RequestContext,
+        route_id: &str,
+        tenant: &str,
+        estimated_tokens: u64,
+    ) -> Result<bool, Box<Error>> {
+        let Some(queue) = self.inference_rate_limit_manager.admission(route_id) else {
+            return Ok(false);
+        };
+
+        let key_priority = ctx.virtual_key.as_ref().and_then(|key| key.priority());
+        let class = queue.class_for(tenant, key_priority).to_string();
+        let start = std::time::Instant::now();
+        let admission = queue.admit(&class, estimated_tokens).await;
+
+        let (result, waited) = match admission {
+            Ok(ref permit) if permit.waited().is_zero() => ("admitted", permit.waited()),
+            Ok(ref permit) => ("queued", permit.waited()),
+            Err(rejection) => (rejection.as_str(), start.elapsed()),
+        };
+        if let Some(metrics) = get_inference_metrics() {
+            metrics.record_admission(route_id, &class, result, waited, &ctx.scope());
+        }
+
+        let rejection = match admission {
+            Ok(permit) => {
+                ctx.inference_admission = Some(permit);
+                return Ok(false);
+            }
+            Err(rejection) => rejection,
+        };
+
+        let retry_after_secs = queue.config().retry_after_secs;
+        warn!(
+            correlation_id = %ctx.trace_id,
+            route_id = route_id,
+            tenant = tenant,
+            class = %class,
+            estimated_tokens = estimated_tokens,
+            waited_ms = waited.as_millis() as u64,
+            result = result,
+            "Inference request not admitted"
+        );
+        self.metrics.record_blocked_request("inference_shed");
+
+        let reason = match rejection {
+            AdmissionRejection::Shed => "Inference admission queue full",
+            AdmissionRejection::TimedOut => "Inference admission queue timeout",
+        };
+        let audit_entry = AuditLogEntry::new(
+            &ctx.trace_id,
+            AuditEventType::RateLimitExceeded,
+            &ctx.method,
+            &ctx.path,
+            &ctx.client_ip,
+        )
+        .with_route_id(route_id)
+        .with_status_code(503)
+        .with_reason(format!("{} (class '{}')", reason, class));
+        self.log_manager.log_audit(&audit_entry);
+
+        let reset_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            + retry_after_secs;
+        crate::http_helpers::write_rate_limit_error(
+            session,
+            503,
+            reason,
+            0,
+            0,
+            reset_at,
+            retry_after_secs,
+        )
+        .await?;
+        Ok(true)
+    }
+
+    /// Look up the inference response cache before an upstream is selected
+    ///
+    /// The request body is read ahead to build the cache key. A hit goes
+    /// through request body inspection like any other request, is written
+    /// straight to the client and `Ok(true)` returned; on a miss the
+    /// normalised request is kept so the response can be stored.
+    pub(super) async fn serve_inference_cache(
+        &self,
+        session: &mut Session,
+        ctx: &mut RequestContext,
+    ) -> Result<bool, Box<Error>> {
+        let Some(route_id) = ctx.route_id.clone() else {
+            return Ok(false);
+        };
+        let Some(cache) = self.inference_rate_limit_manager.cache(&route_id) else {
+            return Ok(false);
+        };
+
+        // The key is built from the body the upstream would get, with
+        // disallowed tools stripped; requests the tool policy blocks are left
+        // to `request_body_filter` to reject
+        let mut body = self.prefetch_request_body(session, ctx).await?;
+        if let Some(policy) = ctx.tool_policy() {
+            let outcome = policy.check_request(&body);
+            if policy.action() == sentinel_config::ToolPolicyAction::Block
+                && outcome.first_violation().is_some()
+            {
+                return Ok(false);
+            }
+            if let Some(stripped) = outcome.body {
+                body = Bytes::from(stripped);
+            }
+        }
+
+        let req_header = session.req_header();
+        let scope = cache_scope(
+            ctx.virtual_key.as_ref().map(|key| key.tenant()),
+            &req_header.headers,
+        );
+        let Some(request) =
+            InferenceCacheRequest::new(&scope, &req_header.method, &req_header.uri, &body)
+        else {
+            return Ok(false);
+        };
+
+        let hit = cache.lookup(&request);
+        if let Some(metrics) = get_inference_metrics() {
+            metrics.record_cache_lookup(
+                &route_id,
+                hit.as_ref().map(|(kind, cached)| (*kind, cached.tokens)),
+                &ctx.scope(),
+            );
+        }
+        let Some((kind, cached)) = hit else {
+            ctx.inference_cache_request = Some(request);
+            return Ok(false);
+        };
+
+        // A hit never reaches `request_body_filter`, so its body is inspected here
+        self.inspect_request_body(&mut Some(body), true, ctx)
+            .await?;
+
+        debug!(
+            correlation_id = %ctx.trace_id,
+            route_id = %route_id,
+            kind = kind.as_str(),
+            streaming = cached.streaming,
+            tokens_saved = cached.tokens,
+            "Serving inference response from cache"
+        );
+        ctx.inference_cache_hit = Some(kind);
+        ctx.inference_streaming_response = cached.streaming;
+
+        let mut header = ResponseHeader::build(cached.status, Some(6))?;
+        if let Some(ref content_type) = cached.content_type {
+            header.insert_header("Content-Type", content_type)?;
+        }
+        if !cached.streaming {
+            header.insert_header("Content-Length", cached.body_len().to_string())?;
+        }
+        header.insert_header("Age", cached.age().as_secs().to_string())?;
+        header.insert_header("X-Inference-Cache", kind.as_str())?;
+        header.insert_header("X-Correlation-Id", ctx.trace_id.as_str())?;
+        session
+            .write_response_header(Box::new(header), false)
+            .await?;
+
+        // Replay the recorded chunks, so streamed responses arrive as the same
+        // sequence of SSE events
+        if cached.chunks.is_empty() {
+            session
+                .write_response_body(Some(bytes::Bytes::new()), true)
+                .await?;
+        }
+        let last = cached.chunks.len().saturating_sub(1);
+        for (i, chunk) in cached.chunks.iter().enumerate() {
+            session
+                .write_response_body(Some(chunk.clone()), i == last)
+                .await?;
+            ctx.response_bytes += chunk.len() as u64;
+            ctx.record_inference_output(chunk);
+        }
+        Ok(true)
+    }
+
+    /// Record the tools the tool policy checked, per tool name, and report
+    /// disallowed ones as guardrail detections. `direction` is "request" for
+    /// tool definitions and "response" for tool calls.
+    pub(super) fn record_tool_policy(
+        &self,
+        ctx: &mut RequestContext,
+        direction: &str,
+        tools: &[ToolUse],
+    ) {
+        let route_id = ctx.route_id.as_deref().unwrap_or("unknown");
+        for tool in tools {
+            let result = if tool.allowed { "allowed" } else { "denied" };
+            self.metrics
+                .record_tool_call(route_id, &tool.name, direction, result);
+        }
+
+        let detections: Vec<_> = tools
+            .iter()
+            .filter(|tool| !tool.allowed)
+            .map(ToolUse::detection)
+            .collect();
+        if detections.is_empty() {
+            return;
+        }
+        warn!(
+            correlation_id = %ctx.trace_id,
+            route_id = route_id,
+            direction = direction,
+            tools = ?detections.iter().map(|d| d.description.as_str()).collect::<Vec<_>>(),
+            "Tool policy violation"
+        );
+
+        // Keep the category for the inference audit log
+        let category = TOOL_POLICY_CATEGORY.to_string();
+        if !ctx.guardrail_detection_categories.contains(&category) {
+            ctx.guardrail_detection_categories.push(category);
+        }
+    }
+
+    /// Record the time to first token and inter-token latency of a streamed
+    /// inference response served by `target` of `upstream`.
+    pub(super) fn record_stream_latency(&self, ctx: &RequestContext, upstream: &str, target: &str) {
+        let Some(ttft) = ctx.inference_timing.time_to_first_token() else {
+            return;
+        };
+        self.ttft_tracker.record(upstream, target, ttft);
+
+        let output_tokens = ctx
+            .inference_streaming_counter
+            .as_ref()
+            .and_then(|counter| counter.api_usage())
+            .map(|usage| usage.output_tokens);
+        let inter_token = ctx.inference_timing.inter_token_latency(output_tokens);
+
+        if let Some(metrics) = get_inference_metrics() {
+            metrics.record_stream_latency(
+                ctx.route_id.as_deref().unwrap_or("unknown"),
+                ctx.inference_model.as_deref().unwrap_or("unknown"),
+                upstream,
+                ttft,
+                inter_token,
+                &ctx.scope(),
+            );
+        }
+
+        debug!(
+            correlation_id = %ctx.trace_id,
+            upstream = %upstream,
+            peer_address = %target,
+            ttft_ms = ttft.as_millis() as u64,
+            inter_token_ms = ?inter_token.map(|itl| itl.as_secs_f64() * 1000.0),
+            "Recorded streaming inference latency"
+        );
+    }
+
+    /// Write the inference audit log record of a sampled request
+    pub(super) fn log_inference_audit(
+        &self,
+        ctx: &mut RequestContext,
+        status: u16,
+        latency: std::time::Duration,
+    ) {
+        let Some(auditor) = ctx.inference_audit.take() else {
+            return;
+        };
+        let request = std::mem::take(&mut ctx.inference_audit_request);
+        let response = std::mem::take(&mut ctx.inference_audit_response);
+        let provider = ctx
+            .route_config
+            .as_ref()
+            .and_then(|r| r.inference.as_ref())
+            .map(|i| i.provider)
+            .unwrap_or_default();
+
+        // Input and output are only split once the cost has been calculated
+        let cost_tokens = ctx
+            .inference_request_cost
+            .map(|_| (ctx.inference_input_tokens, ctx.inference_output_tokens));
+
+        let mut guardrail_detections = ctx.guardrail_detection_categories.clone();
+        for category in &ctx.pii_detection_categories {
+            if !guardrail_detections.contains(category) {
+                guardrail_detections.push(category.clone());
+            }
+        }
+
+        let entry = InferenceAuditEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            trace_id: ctx.trace_id.clone(),
+            route_id: ctx.route_id.clone().unwrap_or_default(),
+            tenant: ctx
+                .inference_rate_limit_key
+                .clone()
+                .unwrap_or_else(|| ctx.client_ip.clone()),
+            key_id: ctx.virtual_key.as_ref().map(|key| key.id().to_string()),
+            client_ip: ctx.client_ip.clone(),
+            model: ctx.inference_model.clone(),
+            upstream: ctx.upstream.clone(),
+            status,
+            streaming: ctx.inference_streaming_response,
+            cache_hit: ctx
+                .inference_cache_hit
+                .map(|kind| kind.as_str().to_string()),
+            input_tokens: cost_tokens.map(|(input, _)| input),
+            output_tokens: cost_tokens.map(|(_, output)| output),
+            total_tokens: ctx.inference_actual_tokens,
+            cost: ctx.inference_request_cost,
+            guardrail_detections,
+            latency_ms: latency.as_millis() as u64,
+            ttft_ms: ctx
+                .inference_timing
+                .time_to_first_token()
+                .map(|ttft| ttft.as_millis() as u64),
+            prompt: auditor.prompt(&request),
+            completion: auditor.completion(&response, provider, ctx.inference_streaming_response),
+        };
+        self.log_manager.log_inference(&entry);
+    }
+
     /// Write HTTP response to session
     pub(super) async fn write_http_response(
         &self,
@@ -20,19 +20,28 @@ use tracing::{debug, error, info, trace, warn};
 
 use crate::cache::{get_cache_eviction, get_cache_lock, get_cache_storage};
 use crate::inference::{
-    extract_inference_content, is_sse_response, PromptInjectionResult, StreamingTokenCounter,
+    client_bypasses_cache, create_provider, extract_inference_content, is_sse_response, ApiFormat,
+    InferenceCacheRecorder, InferenceTranslator, PromptInjectionResult, StreamGuard,
+    StreamingOutputResult, StreamingTokenCounter, ToolCallFilter, ANTHROPIC_VERSION,
+    MAX_TOOL_POLICY_REQUEST_BYTES, MAX_TRANSLATION_BODY_BYTES,
 };
 use crate::logging::{AccessLogEntry, AuditEventType, AuditLogEntry};
 use crate::rate_limit::HeaderAccessor;
 use crate::routing::RequestInfo;
 
+use super::body_rewrite;
 use super::context::{FallbackReason, RequestContext};
 use super::fallback::FallbackEvaluator;
 use super::fallback_metrics::get_fallback_metrics;
 use super::model_routing;
 use super::model_routing_metrics::get_model_routing_metrics;
+use super::routing_override;
 use super::SentinelProxy;
 
+/// Largest non-streaming inference response kept for reading token usage,
+/// unless the route's `max-usage-body-bytes` says otherwise
+const DEFAULT_INFERENCE_USAGE_BODY_BYTES: usize = 1024 * 1024;
+
 /// Helper type for rate limiting when we don't need header access
 struct NoHeaderAccessor;
 impl HeaderAccessor for NoHeaderAccessor {
@@ -126,10 +135,31 @@ impl ProxyHttp for SentinelProxy {
         ctx.path = path.to_string();
         ctx.host = Some(host.to_string());
 
+        // Capture the verified client certificate on mTLS listeners. A
+        // certificate whose identity is unknown is rejected rather than
+        // matched on partial fields.
+        let ssl_digest = session
+            .digest()
+            .and_then(|digest| digest.ssl_digest.clone());
+        if let Some(ssl_digest) = ssl_digest {
+            match crate::client_cert::ClientCertIdentity::from_ssl_digest(&ssl_digest) {
+                Ok(identity) => ctx.client_cert = identity.map(std::sync::Arc::new),
+                Err(e) => {
+                    warn!(error = %e, "Rejecting request without client certificate identity");
+                    self.metrics.record_blocked_request("client_cert_identity");
+                    return Err(Error::explain(
+                        ErrorType::HTTPStatus(403),
+                        "Client certificate identity unavailable",
+                    ));
+                }
+            }
+        }
+
         // Match route to determine service type
         let route_match = {
             let route_matcher = self.route_matcher.read();
-            let request_info = RequestInfo::new(method, path, host);
+            let request_info =
+                RequestInfo::new(method, path, host).with_client_cert(ctx.client_cert.as_deref());
             match route_matcher.match_request(&request_info) {
                 Some(m) => m,
                 None => return Ok(()), // No matching route, let upstream_peer handle it
@@ -243,7 +273,8 @@ impl ProxyHttp for SentinelProxy {
                 let host = ctx.host.as_deref().unwrap_or("");
 
                 // Build request info (zero-copy for common case)
-                let mut request_info = RequestInfo::new(&ctx.method, &ctx.path, host);
+                let mut request_info = RequestInfo::new(&ctx.method, &ctx.path, host)
+                    .with_client_cert(ctx.client_cert.as_deref());
 
                 // Only build headers HashMap if any route needs header matching
                 if route_matcher.needs_headers() {
@@ -457,8 +488,37 @@ impl ProxyHttp for SentinelProxy {
             }
         }
 
+        // === Agent routing override ===
+        // Agents may only switch to upstreams the route could already use
+        if let Some(upstream) = ctx
+            .routing_override
+            .as_ref()
+            .and_then(|r| r.upstream.clone())
+        {
+            if routing_override::is_upstream_allowed(&route_match.config, &upstream) {
+                debug!(
+                    correlation_id = %ctx.trace_id,
+                    route_id = %route_match.route_id,
+                    from_upstream = ?ctx.upstream,
+                    to_upstream = %upstream,
+                    "Agent routing override selected upstream"
+                );
+                ctx.upstream = Some(upstream.clone());
+                ctx.routing_override_applied
+                    .get_or_insert_with(Default::default)
+                    .upstream = Some(upstream);
+            } else {
+                warn!(
+                    correlation_id = %ctx.trace_id,
+                    route_id = %route_match.route_id,
+                    upstream = %upstream,
+                    "Ignoring agent upstream override not allowed by route"
+                );
+            }
+        }
+
         // === Fallback routing evaluation (pre-request) ===
-        // Check if fallback should be triggered due to health or budget conditions
+        // Check if fallback should be triggered due to health, budget, or TTFT conditions
         if let Some(ref fallback_config) = route_match.config.fallback {
             let upstream_name = ctx.upstream.as_ref().unwrap();
 
@@ -483,12 +543,23 @@ impl ProxyHttp for SentinelProxy {
             );
 
             // Evaluate pre-request fallback conditions
-            if let Some(decision) = evaluator.should_fallback_before_request(
-                upstream_name,
-                is_healthy,
-                is_budget_exhausted,
-                current_model,
-            ) {
+            let decision = evaluator
+                .should_fallback_before_request(
+                    upstream_name,
+                    is_healthy,
+                    is_budget_exhausted,
+                    current_model,
+                )
+                .or_else(|| {
+                    // Even the primary's fastest target is slow to start streaming
+                    let observed_ttft_ms = self.ttft_tracker.best_ms(upstream_name)?;
+                    evaluator.should_fallback_on_ttft(
+                        upstream_name,
+                        observed_ttft_ms as u64,
+                        current_model,
+                    )
+                });
+            if let Some(decision) = decision {
                 info!(
                     correlation_id = %ctx.trace_id,
                     route_id = %route_match.route_id,
@@ -534,6 +605,45 @@ impl ProxyHttp for SentinelProxy {
             }
         }
 
+        // === Provider translation ===
+        // A fallback or model-routed upstream may speak a different inference API
+        // than the client; translate bodies so each side sees its own schema
+        ctx.inference_translator = None;
+        if let Some(ref inference) = route_match.config.inference {
+            let upstream_provider = ctx
+                .upstream
+                .as_deref()
+                .and_then(|upstream| {
+                    route_match
+                        .config
+                        .fallback
+                        .as_ref()?
+                        .upstreams
+                        .iter()
+                        .find(|fallback| fallback.upstream == upstream)
+                })
+                .map(|fallback| fallback.provider)
+                .or(ctx.inference_provider_override);
+
+            if let Some(upstream_provider) = upstream_provider {
+                ctx.inference_translator = InferenceTranslator::new(
+                    create_provider(&inference.provider).as_ref(),
+                    create_provider(&upstream_provider).as_ref(),
+                );
+            }
+
+            if let Some(translator) = ctx.inference_translator {
+                debug!(
+                    correlation_id = %ctx.trace_id,
+                    route_id = %route_match.route_id,
+                    upstream = ?ctx.upstream,
+                    client_api = translator.client_format().as_str(),
+                    upstream_api = translator.upstream_format().as_str(),
+                    "Translating inference request between provider APIs"
+                );
+            }
+        }
+
         debug!(
             correlation_id = %ctx.trace_id,
             route_id = %route_match.route_id,
@@ -603,6 +713,93 @@ impl ProxyHttp for SentinelProxy {
         let mut last_error = None;
         let selection_start = std::time::Instant::now();
 
+        // Agent target pin, honored only if the target belongs to this pool
+        if let Some(target) = ctx.routing_override.as_ref().and_then(|r| r.target.clone()) {
+            match pool.select_pinned_peer(&target).await {
+                Ok(peer) => {
+                    ctx.upstream_attempts = 1;
+                    ctx.selected_upstream_address = Some(peer.address().to_string());
+                    ctx.routing_override_applied
+                        .get_or_insert_with(Default::default)
+                        .target = Some(target);
+                    debug!(
+                        correlation_id = %ctx.trace_id,
+                        upstream = %upstream_name,
+                        peer_address = ?ctx.selected_upstream_address,
+                        "Selected upstream peer pinned by agent"
+                    );
+                    return Ok(Box::new(peer));
+                }
+                Err(e) => {
+                    warn!(
+                        correlation_id = %ctx.trace_id,
+                        upstream = %upstream_name,
+                        target = %target,
+                        error = %e,
+                        "Ignoring agent target override"
+                    );
+                }
+            }
+        }
+
+        // TTFT-aware routing: pick the target that starts streaming soonest.
+        // The configured address is kept as the selected address, so TTFT
+        // samples recorded in logging() match the pool's target list.
+        let least_ttft = route_match
+            .config
+            .inference
+            .as_ref()
+            .and_then(|inference| inference.routing.as_ref())
+            .is_some_and(|routing| {
+                routing.strategy == sentinel_config::InferenceRoutingStrategy::LeastTimeToFirstToken
+            });
+        if least_ttft {
+            let healthy = pool.healthy_targets().await;
+            if let Some(target) = self.ttft_tracker.select(upstream_name, &healthy) {
+                match pool.select_pinned_peer(target).await {
+                    Ok(peer) => {
+                        ctx.upstream_attempts = 1;
+                        ctx.selected_upstream_address = Some(target.clone());
+                        debug!(
+                            correlation_id = %ctx.trace_id,
+                            upstream = %upstream_name,
+                            peer_address = %target,
+                            ttft_ewma_ms = ?self.ttft_tracker.ewma_ms(upstream_name, target),
+                            "Selected upstream peer with least time to first token"
+                        );
+                        return Ok(Box::new(peer));
+                    }
+                    Err(e) => {
+                        debug!(
+                            correlation_id = %ctx.trace_id,
+                            upstream = %upstream_name,
+                            target = %target,
+                            error = %e,
+                            "TTFT-aware selection failed, using load balancer"
+                        );
+                    }
+                }
+            }
+        }
+
+        // Agent hash key for consistent-hash and Maglev balancers
+        let lb_context = ctx
+            .routing_override
+            .as_ref()
+            .and_then(|r| r.hash_key.clone())
+            .map(|hash_key| crate::upstream::RequestContext {
+                client_ip: ctx.client_ip.parse().ok(),
+                headers: std::collections::HashMap::new(),
+                path: ctx.path.clone(),
+                method: ctx.method.clone(),
+                hash_key: Some(hash_key),
+            });
+        if let Some(ref lb_context) = lb_context {
+            ctx.routing_override_applied
+                .get_or_insert_with(Default::default)
+                .hash_key = lb_context.hash_key.clone();
+        }
+
         for attempt in 1..=max_retries {
             ctx.upstream_attempts = attempt;
 
@@ -614,7 +811,7 @@ impl ProxyHttp for SentinelProxy {
                 "Attempting to select upstream peer"
             );
 
-            match pool.select_peer_with_metadata(None).await {
+            match pool.select_peer_with_metadata(lb_context.as_ref()).await {
                 Ok((peer, metadata)) => {
                     let selection_duration = selection_start.elapsed();
                     // Store selected peer address for feedback reporting in logging()
@@ -817,6 +1014,11 @@ impl ProxyHttp for SentinelProxy {
             }
         }
 
+        // Serve challenge callbacks before any route-specific processing
+        if self.handle_challenge_callback(session, ctx).await? {
+            return Ok(true); // Request complete, don't continue
+        }
+
         // Inference rate limiting (token-based, for LLM/AI routes)
         // This runs after regular rate limiting and checks service type
         if let Some(route_id) = ctx.route_id.as_deref() {
@@ -824,6 +1026,17 @@ impl ProxyHttp for SentinelProxy {
                 if route_config.service_type == sentinel_config::ServiceType::Inference
                     && self.inference_rate_limit_manager.has_route(route_id)
                 {
+                    // Authenticate the client's virtual API key, if the route issues them
+                    if let Some(store) = self.inference_rate_limit_manager.virtual_keys(route_id) {
+                        match store.authenticate(&session.req_header().headers) {
+                            Ok(key) => ctx.virtual_key = Some(key),
+                            Err(e) => {
+                                self.reject_virtual_key(session, ctx, route_id, &e).await?;
+                                return Ok(true);
+                            }
+                        }
+                    }
+
                     // For inference rate limiting, we need access to the request body
                     // to estimate tokens. We'll use buffered body if available.
                     let headers = &session.req_header().headers;
@@ -831,8 +1044,12 @@ impl ProxyHttp for SentinelProxy {
                     // Try to get buffered body, or use empty (will estimate from headers only)
                     let body = ctx.body_buffer.as_slice();
 
-                    // Use client IP as the rate limit key (could be enhanced to use API key header)
-                    let rate_limit_key = &ctx.client_ip;
+                    // Rate limit and budget per tenant: the virtual key's tenant,
+                    // otherwise the client IP
+                    let rate_limit_key = match ctx.virtual_key {
+                        Some(ref key) => key.tenant(),
+                        None => ctx.client_ip.as_str(),
+                    };
 
                     if let Some(check_result) = self.inference_rate_limit_manager.check(
                         route_id,
@@ -845,6 +1062,12 @@ impl ProxyHttp for SentinelProxy {
                         ctx.inference_estimated_tokens = check_result.estimated_tokens;
                         ctx.inference_rate_limit_key = Some(rate_limit_key.to_string());
                         ctx.inference_model = check_result.model.clone();
+                        if ctx.inference_model.is_none() {
+                            // Gemini and Bedrock name the model in the path
+                            ctx.inference_model = route_config.inference.as_ref().and_then(|i| {
+                                create_provider(&i.provider).model_from_path(&ctx.path)
+                            });
+                        }
 
                         if !check_result.is_allowed() {
                             let retry_after_ms = check_result.retry_after_ms();
@@ -907,15 +1130,44 @@ impl ProxyHttp for SentinelProxy {
                             "Inference rate limit check passed"
                         );
 
+                        // Enforce the virtual key's model allowlist and its own limits.
+                        // The models in the body and the path are checked too, so a
+                        // header can't stand in for a different model.
+                        if let Some(key) = ctx.virtual_key.clone() {
+                            if self
+                                .check_virtual_key_model(session, ctx, route_id, &key)
+                                .await?
+                            {
+                                return Ok(true);
+                            }
+
+                            if self
+                                .check_virtual_key_limits(
+                                    session,
+                                    ctx,
+                                    route_id,
+                                    &key,
+                                    check_result.estimated_tokens,
+                                )
+                                .await?
+                            {
+                                return Ok(true);
+                            }
+                        }
+
                         // Check budget tracking (cumulative per-period limits)
                         if self.inference_rate_limit_manager.has_budget(route_id) {
                             ctx.inference_budget_enabled = true;
 
-                            if let Some(budget_result) = self.inference_rate_limit_manager.check_budget(
-                                route_id,
-                                rate_limit_key,
-                                check_result.estimated_tokens,
-                            ) {
+                            if let Some(budget_result) = self
+                                .inference_rate_limit_manager
+                                .check_budget(
+                                    route_id,
+                                    rate_limit_key,
+                                    check_result.estimated_tokens,
+                                )
+                                .await
+                            {
                                 if !budget_result.is_allowed() {
                                     let retry_after_secs = budget_result.retry_after_secs();
 
@@ -974,10 +1226,11 @@ impl ProxyHttp for SentinelProxy {
                                 ctx.inference_budget_remaining = Some(remaining);
 
                                 // Get period reset time from budget status
-                                if let Some(status) = self.inference_rate_limit_manager.budget_status(
-                                    route_id,
-                                    rate_limit_key,
-                                ) {
+                                if let Some(status) = self
+                                    .inference_rate_limit_manager
+                                    .budget_status(route_id, rate_limit_key)
+                                    .await
+                                {
                                     ctx.inference_budget_period_reset = Some(status.period_end);
                                 }
 
@@ -994,11 +1247,28 @@ impl ProxyHttp for SentinelProxy {
                         if self.inference_rate_limit_manager.has_cost_attribution(route_id) {
                             ctx.inference_cost_enabled = true;
                         }
+
+                        // Sample the request for the inference audit log
+                        if self.log_manager.inference_log_enabled() {
+                            ctx.inference_audit = self
+                                .inference_rate_limit_manager
+                                .auditor(route_id)
+                                .filter(|auditor| auditor.sample());
+                        }
+
+                        // Look up the response cache once the request has been checked
+                        ctx.inference_cache_pending = ctx.method == "POST"
+                            && self.inference_rate_limit_manager.cache(route_id).is_some()
+                            && !client_bypasses_cache(&session.req_header().headers);
                     }
                 }
             }
         }
 
+        // Tool policy: check the tools offered to the model once the request
+        // body arrives
+        ctx.inference_tool_policy_pending = ctx.method == "POST" && ctx.tool_policy().is_some();
+
         // Prompt injection guardrail (for inference routes)
         if let Some(ref route_config) = ctx.route_config {
             if let Some(ref inference) = route_config.inference {
@@ -1384,31 +1654,57 @@ impl ProxyHttp for SentinelProxy {
             correlation_id = %ctx.trace_id,
             "Processing request through agents"
         );
-        if let Err(e) = self
+        match self
             .process_agents(session, ctx, &client_addr, client_port)
             .await
         {
-            // Check if this is an HTTPStatus error (e.g., agent block or fail-closed)
-            // In that case, we need to send a proper HTTP response instead of just closing the connection
-            if let ErrorType::HTTPStatus(status) = e.etype() {
-                // Extract the message from the error (the context part after "HTTPStatus context:")
-                let error_msg = e.to_string();
-                let body = error_msg
-                    .split("context:")
-                    .nth(1)
-                    .map(|s| s.trim())
-                    .unwrap_or("Request blocked");
-                debug!(
-                    correlation_id = %ctx.trace_id,
-                    status = status,
-                    body = %body,
-                    "Sending HTTP error response for agent block"
-                );
-                crate::http_helpers::write_error(session, *status, body, "text/plain").await?;
-                return Ok(true); // Request complete, don't continue to upstream
+            Ok(true) => return Ok(true), // Challenge served, request complete
+            Ok(false) => {}
+            Err(e) => {
+                // Check if this is an HTTPStatus error (e.g., agent block or fail-closed)
+                // In that case, we need to send a proper HTTP response instead of just closing the connection
+                if let ErrorType::HTTPStatus(status) = e.etype() {
+                    // Extract the message from the error (the context part after "HTTPStatus context:")
+                    let error_msg = e.to_string();
+                    let body = error_msg
+                        .split("context:")
+                        .nth(1)
+                        .map(|s| s.trim())
+                        .unwrap_or("Request blocked");
+                    debug!(
+                        correlation_id = %ctx.trace_id,
+                        status = status,
+                        body = %body,
+                        "Sending HTTP error response for agent block"
+                    );
+                    crate::http_helpers::write_error(session, *status, body, "text/plain").await?;
+                    return Ok(true); // Request complete, don't continue to upstream
+                }
+                // For other errors, propagate them
+                return Err(e);
+            }
+        }
+
+        // Serve inference cache hits before an upstream is selected
+        if ctx.inference_cache_pending {
+            ctx.inference_cache_pending = false;
+            if self.serve_inference_cache(session, ctx).await? {
+                return Ok(true);
+            }
+        }
+
+        // Wait for a slot in the priority admission queue; cache hits never
+        // take one
+        if let (Some(route_id), Some(tenant)) =
+            (ctx.route_id.clone(), ctx.inference_rate_limit_key.clone())
+        {
+            let estimated_tokens = ctx.inference_estimated_tokens;
+            if self
+                .admit_inference_request(session, ctx, &route_id, &tenant, estimated_tokens)
+                .await?
+            {
+                return Ok(true);
             }
-            // For other errors, propagate them
-            return Err(e);
         }
 
         trace!(
@@ -1427,13 +1723,11 @@ impl ProxyHttp for SentinelProxy {
     /// - **Stream mode**: Send each chunk immediately to agents as it arrives
     async fn request_body_filter(
         &self,
-        _session: &mut Session,
+        session: &mut Session,
         body: &mut Option<Bytes>,
         end_of_stream: bool,
         ctx: &mut Self::CTX,
     ) -> Result<(), Box<Error>> {
-        use sentinel_config::BodyStreamingMode;
-
         // Handle WebSocket frame inspection (client -> server)
         if ctx.is_websocket_upgrade {
             if let Some(ref handler) = ctx.websocket_handler {
@@ -1461,6 +1755,12 @@ impl ProxyHttp for SentinelProxy {
             return Ok(());
         }
 
+        // A body read ahead in request_filter goes upstream in place of
+        // whatever the session replays from its retry buffer
+        if let Some(prefetched) = ctx.prefetched_request_body.take() {
+            *body = (!prefetched.is_empty()).then_some(prefetched);
+        }
+
         // Track request body size
         let chunk_len = body.as_ref().map(|b| b.len()).unwrap_or(0);
         if chunk_len > 0 {
@@ -1494,101 +1794,103 @@ impl ProxyHttp for SentinelProxy {
             }
         }
 
-        // Body inspection for agents (WAF, etc.)
-        if ctx.body_inspection_enabled && !ctx.body_inspection_agents.is_empty() {
-            let config = ctx
-                .config
-                .get_or_insert_with(|| self.config_manager.current());
-            let max_inspection_bytes = config
-                .waf
-                .as_ref()
-                .map(|w| w.body_inspection.max_inspection_bytes as u64)
-                .unwrap_or(1024 * 1024);
+        // Keep the prompt, as sent by the client, for the inference audit log
+        if ctx.inference_audit.is_some() {
+            if let Some(ref chunk) = body {
+                ctx.record_inference_input(chunk);
+            }
+        }
 
-            match ctx.request_body_streaming_mode {
-                BodyStreamingMode::Stream => {
-                    // Stream mode: send each chunk immediately
-                    if body.is_some() {
-                        self.process_body_chunk_streaming(body, end_of_stream, ctx)
-                            .await?;
-                    } else if end_of_stream && ctx.agent_needs_more {
-                        // Send final empty chunk to signal end
-                        self.process_body_chunk_streaming(body, end_of_stream, ctx)
-                            .await?;
-                    }
+        // Hold the body back until the tools it offers the model have been
+        // checked against the tool policy; a stripped body goes on in place of
+        // the client's (and is what the cache key is built from)
+        if ctx.inference_tool_policy_pending {
+            if let Some(chunk) = body.take() {
+                if !ctx.buffer_tool_policy_request(&chunk, MAX_TOOL_POLICY_REQUEST_BYTES) {
+                    warn!(
+                        correlation_id = %ctx.trace_id,
+                        limit = MAX_TOOL_POLICY_REQUEST_BYTES,
+                        "Inference request too large for tool policy check"
+                    );
+                    self.metrics.record_blocked_request("tool_policy");
+                    return Err(Error::explain(
+                        ErrorType::HTTPStatus(413),
+                        "Inference request too large for tool policy check",
+                    ));
                 }
-                BodyStreamingMode::Hybrid { buffer_threshold } => {
-                    // Hybrid mode: buffer up to threshold, then stream
-                    if ctx.body_bytes_inspected < buffer_threshold as u64 {
-                        // Still in buffering phase
-                        if let Some(ref chunk) = body {
-                            let bytes_to_buffer = std::cmp::min(
-                                chunk.len(),
-                                (buffer_threshold as u64 - ctx.body_bytes_inspected) as usize,
-                            );
-                            ctx.body_buffer.extend_from_slice(&chunk[..bytes_to_buffer]);
-                            ctx.body_bytes_inspected += bytes_to_buffer as u64;
-
-                            // If we've reached threshold or end of stream, switch to streaming
-                            if ctx.body_bytes_inspected >= buffer_threshold as u64 || end_of_stream
-                            {
-                                // Send buffered content first
-                                self.send_buffered_body_to_agents(
-                                    end_of_stream && chunk.len() == bytes_to_buffer,
-                                    ctx,
-                                )
-                                .await?;
-                                ctx.body_buffer.clear();
-
-                                // If there's remaining data in this chunk, stream it
-                                if bytes_to_buffer < chunk.len() {
-                                    let remaining = chunk.slice(bytes_to_buffer..);
-                                    let mut remaining_body = Some(remaining);
-                                    self.process_body_chunk_streaming(
-                                        &mut remaining_body,
-                                        end_of_stream,
-                                        ctx,
-                                    )
-                                    .await?;
-                                }
-                            }
-                        }
-                    } else {
-                        // Past threshold, stream directly
-                        self.process_body_chunk_streaming(body, end_of_stream, ctx)
-                            .await?;
-                    }
+            }
+            if !end_of_stream {
+                return Ok(());
+            }
+            ctx.inference_tool_policy_pending = false;
+
+            let mut buffered = std::mem::take(&mut ctx.inference_tool_policy_buffer);
+            if let Some(policy) = ctx.tool_policy() {
+                let outcome = policy.check_request(&buffered);
+                self.record_tool_policy(ctx, "request", &outcome.tools);
+                if let (sentinel_config::ToolPolicyAction::Block, Some(tool)) =
+                    (policy.action(), outcome.first_violation())
+                {
+                    self.metrics.record_blocked_request("tool_policy");
+                    return Err(Error::explain(
+                        ErrorType::HTTPStatus(policy.block_status()),
+                        policy.block_message(tool),
+                    ));
                 }
-                BodyStreamingMode::Buffer => {
-                    // Buffer mode: collect chunks until ready to send
-                    if let Some(ref chunk) = body {
-                        if ctx.body_bytes_inspected < max_inspection_bytes {
-                            let bytes_to_inspect = std::cmp::min(
-                                chunk.len() as u64,
-                                max_inspection_bytes - ctx.body_bytes_inspected,
-                            ) as usize;
+                if let Some(stripped) = outcome.body {
+                    buffered = stripped;
+                }
+            }
+            if !buffered.is_empty() {
+                *body = Some(Bytes::from(buffered));
+            }
+        }
 
-                            ctx.body_buffer
-                                .extend_from_slice(&chunk[..bytes_to_inspect]);
-                            ctx.body_bytes_inspected += bytes_to_inspect as u64;
+        // Body inspection for agents (WAF, etc.)
+        self.inspect_request_body(body, end_of_stream, ctx).await?;
 
-                            trace!(
-                                correlation_id = %ctx.trace_id,
-                                bytes_inspected = ctx.body_bytes_inspected,
-                                max_inspection_bytes = max_inspection_bytes,
-                                buffer_size = ctx.body_buffer.len(),
-                                "Buffering body for agent inspection"
-                            );
-                        }
+        // Hold the body back until it can be translated for the upstream's API
+        if let Some(translator) = ctx.inference_translator {
+            if let Some(chunk) = body.take() {
+                ctx.translation_request_buffer.extend_from_slice(&chunk);
+            }
+            if ctx.translation_request_buffer.len() > MAX_TRANSLATION_BODY_BYTES {
+                warn!(
+                    correlation_id = %ctx.trace_id,
+                    limit = MAX_TRANSLATION_BODY_BYTES,
+                    "Inference request too large to translate"
+                );
+                return Err(Error::explain(
+                    ErrorType::HTTPStatus(413),
+                    "Inference request too large to translate",
+                ));
+            }
+            if end_of_stream && !ctx.translation_request_buffer.is_empty() {
+                let original = std::mem::take(&mut ctx.translation_request_buffer);
+                let model = ctx
+                    .model_mapping_applied
+                    .as_ref()
+                    .map(|(_, mapped)| mapped.as_str());
+                match translator.translate_request(&original, model) {
+                    Ok(translated) => {
+                        trace!(
+                            correlation_id = %ctx.trace_id,
+                            original_bytes = original.len(),
+                            translated_bytes = translated.len(),
+                            "Translated inference request body"
+                        );
+                        *body = Some(Bytes::from(translated));
                     }
-
-                    // Send when complete or limit reached
-                    let should_send =
-                        end_of_stream || ctx.body_bytes_inspected >= max_inspection_bytes;
-                    if should_send && !ctx.body_buffer.is_empty() {
-                        self.send_buffered_body_to_agents(end_of_stream, ctx)
-                            .await?;
-                        ctx.body_buffer.clear();
+                    Err(e) => {
+                        warn!(
+                            correlation_id = %ctx.trace_id,
+                            error = %e,
+                            "Failed to translate inference request"
+                        );
+                        return Err(Error::explain(
+                            ErrorType::HTTPStatus(400),
+                            "Inference request could not be translated",
+                        ));
                     }
                 }
             }
@@ -1608,7 +1910,7 @@ impl ProxyHttp for SentinelProxy {
 
     async fn response_filter(
         &self,
-        _session: &mut Session,
+        session: &mut Session,
         upstream_response: &mut ResponseHeader,
         ctx: &mut Self::CTX,
     ) -> Result<(), Box<Error>> {
@@ -1664,6 +1966,25 @@ impl ProxyHttp for SentinelProxy {
             }
         }
 
+        // Streaming agents may resize response chunks after the headers are sent
+        if ctx.response_body_inspection_enabled
+            && !ctx.response_body_inspection_agents.is_empty()
+            && ctx.response_body_streaming_mode == sentinel_config::BodyStreamingMode::Stream
+            && ctx.method != "HEAD"
+            && upstream_response.headers.contains_key("content-length")
+        {
+            let framing = body_rewrite::rewrite_framing(session.req_header().version);
+            upstream_response.remove_header("content-length");
+            if framing == body_rewrite::RewriteFraming::Chunked {
+                upstream_response.insert_header("Transfer-Encoding", "chunked")?;
+            }
+            trace!(
+                correlation_id = %ctx.trace_id,
+                framing = ?framing,
+                "Dropped response Content-Length for agent rewriting"
+            );
+        }
+
         // Apply security headers
         trace!(
             correlation_id = %ctx.trace_id,
@@ -1760,14 +2081,134 @@ impl ProxyHttp for SentinelProxy {
             }
         }
 
-        // Initialize streaming token counter for SSE responses on inference routes
-        if ctx.inference_rate_limit_enabled {
-            // Check if this is an SSE response
+        // Translated responses differ in length from what the upstream sent
+        if let Some(translator) = ctx.inference_translator {
             let content_type = upstream_response
                 .headers
                 .get("content-type")
                 .and_then(|ct| ct.to_str().ok());
+            if is_sse_response(content_type) {
+                ctx.translation_stream = Some(translator.stream_translator());
+            }
+            if upstream_response.headers.contains_key("content-length") {
+                upstream_response.remove_header("content-length");
+                upstream_response.insert_header("Transfer-Encoding", "chunked")?;
+            }
+        }
+
+        // Streaming output guardrail: hold back deltas until a window has been
+        // inspected. The body is checked after translation, so it is parsed in
+        // the client's format unless the upstream is passed through as is.
+        if let Some(inference) = ctx.route_config.as_ref().and_then(|r| r.inference.as_ref()) {
+            let streaming_output = inference
+                .guardrails
+                .as_ref()
+                .and_then(|g| g.streaming_output.as_ref())
+                .filter(|cfg| cfg.enabled);
+            let content_type = upstream_response
+                .headers
+                .get("content-type")
+                .and_then(|ct| ct.to_str().ok());
+
+            if let Some(cfg) = streaming_output {
+                if status == 200 && is_sse_response(content_type) {
+                    let provider = match ctx.inference_translator {
+                        Some(_) => inference.provider,
+                        None => ctx
+                            .inference_provider_override
+                            .unwrap_or(inference.provider),
+                    };
+                    ctx.inference_stream_guard = Some(StreamGuard::new(provider, cfg.window_chars));
+                    if upstream_response.headers.contains_key("content-length") {
+                        upstream_response.remove_header("content-length");
+                        upstream_response.insert_header("Transfer-Encoding", "chunked")?;
+                    }
+
+                    trace!(
+                        correlation_id = %ctx.trace_id,
+                        provider = ?provider,
+                        window_chars = cfg.window_chars,
+                        "Initialized streaming output guardrail"
+                    );
+                }
+            }
+        }
 
+        // Tool policy: check the tool calls of the response, in the client's
+        // format as for the streaming output guardrail. Encoded bodies are not
+        // inspected.
+        if status == 200 && !upstream_response.headers.contains_key("content-encoding") {
+            if let (Some(policy), Some(inference)) = (
+                ctx.tool_policy(),
+                ctx.route_config.as_ref().and_then(|r| r.inference.as_ref()),
+            ) {
+                let provider = match ctx.inference_translator {
+                    Some(_) => inference.provider,
+                    None => ctx
+                        .inference_provider_override
+                        .unwrap_or(inference.provider),
+                };
+                let content_type = upstream_response
+                    .headers
+                    .get("content-type")
+                    .and_then(|ct| ct.to_str().ok());
+                let streaming = is_sse_response(content_type);
+
+                // Stripping or blocking may change the body length
+                if policy.action() != sentinel_config::ToolPolicyAction::Log
+                    && upstream_response.headers.contains_key("content-length")
+                {
+                    upstream_response.remove_header("content-length");
+                    upstream_response.insert_header("Transfer-Encoding", "chunked")?;
+                }
+                ctx.inference_tool_filter = Some(ToolCallFilter::new(policy, provider, streaming));
+
+                trace!(
+                    correlation_id = %ctx.trace_id,
+                    provider = ?provider,
+                    streaming = streaming,
+                    "Initialized tool call filter"
+                );
+            }
+        }
+
+        // Feed the queue depth reported by the upstream to the admission queue
+        if ctx.inference_admission.is_some() {
+            let depth_header = ctx
+                .route_config
+                .as_ref()
+                .and_then(|r| r.inference.as_ref())
+                .and_then(|i| i.routing.as_ref())
+                .and_then(|r| r.queue_depth_header.as_deref());
+            let depth = depth_header
+                .and_then(|name| upstream_response.headers.get(name))
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok());
+            if let (Some(depth), Some(route_id)) = (depth, ctx.route_id.as_deref()) {
+                if let Some(queue) = self.inference_rate_limit_manager.admission(route_id) {
+                    queue.record_upstream_queue_depth(depth);
+                }
+            }
+        }
+
+        // Check if this is an SSE response
+        let content_type = upstream_response
+            .headers
+            .get("content-type")
+            .and_then(|ct| ct.to_str().ok());
+
+        // Streamed responses on any inference route are timed (TTFT, inter-token latency)
+        if is_sse_response(content_type)
+            && ctx
+                .route_config
+                .as_ref()
+                .is_some_and(|r| r.inference.is_some())
+        {
+            ctx.inference_streaming_response = true;
+        }
+
+        // Initialize streaming token counter for SSE responses on inference routes
+        if ctx.inference_rate_limit_enabled {
             if is_sse_response(content_type) {
                 // Get provider from route config
                 let provider = ctx
@@ -1777,7 +2218,6 @@ impl ProxyHttp for SentinelProxy {
                     .map(|i| i.provider.clone())
                     .unwrap_or_default();
 
-                ctx.inference_streaming_response = true;
                 ctx.inference_streaming_counter = Some(StreamingTokenCounter::new(
                     provider,
                     ctx.inference_model.clone(),
@@ -1789,6 +2229,38 @@ impl ProxyHttp for SentinelProxy {
                     model = ?ctx.inference_model,
                     "Initialized streaming token counter for SSE response"
                 );
+            } else if content_type.is_some_and(|ct| ct.contains("json"))
+                && !upstream_response.headers.contains_key("content-encoding")
+            {
+                // Keep JSON bodies so the provider adapter can read exact usage
+                ctx.inference_usage_body = Some(Vec::new());
+            }
+        }
+
+        // Record successful responses for the inference cache (the request missed
+        // it). Encoded bodies are skipped, as only the content type is replayed.
+        if ctx.inference_cache_request.is_some()
+            && status == 200
+            && !upstream_response.headers.contains_key("content-encoding")
+        {
+            if let Some(cache) = ctx
+                .route_id
+                .as_deref()
+                .and_then(|route_id| self.inference_rate_limit_manager.cache(route_id))
+            {
+                let content_type = upstream_response
+                    .headers
+                    .get("content-type")
+                    .and_then(|ct| ct.to_str().ok());
+                let streaming = is_sse_response(content_type);
+                if !streaming || cache.config().streaming {
+                    ctx.inference_cache_recorder = Some(InferenceCacheRecorder::new(
+                        status,
+                        content_type.map(str::to_string),
+                        streaming,
+                        cache.config().max_entry_bytes,
+                    ));
+                }
             }
         }
 
@@ -1902,7 +2374,7 @@ impl ProxyHttp for SentinelProxy {
     /// Used for header modifications, adding authentication, etc.
     async fn upstream_request_filter(
         &self,
-        _session: &mut Session,
+        session: &mut Session,
         upstream_request: &mut pingora::http::RequestHeader,
         ctx: &mut Self::CTX,
     ) -> Result<()>
@@ -1960,12 +2432,114 @@ impl ProxyHttp for SentinelProxy {
                 correlation_id = %ctx.trace_id,
                 "Applied request header modifications"
             );
+
+            // Forward client certificate identity, never trusting client-supplied values
+            if let Some(ref forwarding) = route_config.policies.client_cert_forwarding {
+                for name in crate::client_cert::forwarding_header_names(forwarding) {
+                    upstream_request.remove_header(&name);
+                }
+                if let Some(cert) = ctx.client_cert() {
+                    for (name, value) in cert.forwarding_headers(forwarding) {
+                        upstream_request.insert_header(name, value).ok();
+                    }
+                }
+            }
         }
 
         // Remove sensitive headers that shouldn't go to upstream
         upstream_request.remove_header("X-Internal-Token");
         upstream_request.remove_header("Authorization-Internal");
 
+        // Streaming agents may resize chunks, so the client's length no longer holds.
+        // Pingora has already set the version of the upstream connection here.
+        if ctx.body_inspection_enabled
+            && !ctx.body_inspection_agents.is_empty()
+            && body_rewrite::can_rewrite(ctx.request_body_streaming_mode)
+            && upstream_request.headers.contains_key("content-length")
+        {
+            let framing = body_rewrite::rewrite_framing(upstream_request.version);
+            match framing {
+                body_rewrite::RewriteFraming::Chunked => {
+                    upstream_request.remove_header("content-length");
+                    upstream_request
+                        .insert_header("Transfer-Encoding", "chunked")
+                        .ok();
+                }
+                body_rewrite::RewriteFraming::Frames => {
+                    upstream_request.remove_header("content-length");
+                }
+                body_rewrite::RewriteFraming::Unframed => {
+                    ctx.request_body_length_fixed = true;
+                }
+            }
+            trace!(
+                correlation_id = %ctx.trace_id,
+                framing = ?framing,
+                "Prepared upstream request body for agent rewriting"
+            );
+        }
+
+        // Stripping disallowed tools changes the request body length
+        if ctx.inference_tool_policy_pending
+            && ctx
+                .tool_policy()
+                .is_some_and(|policy| policy.action() == sentinel_config::ToolPolicyAction::Strip)
+            && upstream_request.headers.contains_key("content-length")
+        {
+            upstream_request.remove_header("content-length");
+            upstream_request
+                .insert_header("Transfer-Encoding", "chunked")
+                .ok();
+        }
+
+        // Translated requests go to the upstream API's endpoint, and their body
+        // length is only known once the whole body has been translated
+        if let Some(translator) = ctx.inference_translator {
+            let format = translator.upstream_format();
+            let path = translator.upstream_path_and_query(&upstream_request.uri);
+            match path.parse() {
+                Ok(uri) => upstream_request.set_uri(uri),
+                Err(e) => warn!(
+                    correlation_id = %ctx.trace_id,
+                    error = %e,
+                    "Failed to set translated inference path"
+                ),
+            }
+            if format == ApiFormat::AnthropicMessages
+                && !upstream_request.headers.contains_key("anthropic-version")
+            {
+                upstream_request
+                    .insert_header("anthropic-version", ANTHROPIC_VERSION)
+                    .ok();
+            }
+            if upstream_request.headers.contains_key("content-length") {
+                upstream_request.remove_header("content-length");
+                upstream_request
+                    .insert_header("Transfer-Encoding", "chunked")
+                    .ok();
+            }
+            trace!(
+                correlation_id = %ctx.trace_id,
+                path = %path,
+                "Rewrote upstream request for provider translation"
+            );
+        }
+
+        // === Upstream Credentials ===
+        // Virtual keys never leave the gateway, and an upstream with a secret
+        // or AWS signing gets its own credential instead. The credential is
+        // only ever sent for a client authenticated with a virtual key.
+        let pool = match (&ctx.virtual_key, ctx.upstream.as_deref()) {
+            (Some(_), Some(upstream)) => self.upstream_pools.get(upstream).await,
+            _ => None,
+        };
+        let credential = pool.as_ref().and_then(|pool| pool.credential().cloned());
+        let signer = pool.as_ref().and_then(|pool| pool.signer().cloned());
+        if ctx.virtual_key.is_some() {
+            upstream_request.remove_header("authorization");
+            upstream_request.remove_header("x-api-key");
+        }
+
         // === Traffic Mirroring / Shadowing ===
         // Check if this route has shadow configuration
         if let Some(ref route_config) = ctx.route_config {
@@ -2003,6 +2577,7 @@ impl ProxyHttp for SentinelProxy {
                         headers: std::collections::HashMap::new(), // Empty for now
                         path: ctx.path.clone(),
                         method: ctx.method.clone(),
+                        hash_key: None,
                     };
 
                     // Determine if we should buffer the body
@@ -2039,14 +2614,67 @@ impl ProxyHttp for SentinelProxy {
             }
         }
 
+        // Inject the upstream credential after mirroring, so shadow targets
+        // never receive it
+        if let Some(credential) = credential {
+            let anthropic = match ctx.inference_translator {
+                Some(translator) => translator.upstream_format() == ApiFormat::AnthropicMessages,
+                None => ctx
+                    .inference_provider_override
+                    .or_else(|| {
+                        ctx.route_config
+                            .as_ref()
+                            .and_then(|r| r.inference.as_ref())
+                            .map(|i| i.provider)
+                    })
+                    .is_some_and(|p| p == sentinel_config::InferenceProvider::Anthropic),
+            };
+            let (name, value) = credential.header(anthropic);
+            if let Err(e) = upstream_request.insert_header(name.to_string(), value) {
+                warn!(
+                    correlation_id = %ctx.trace_id,
+                    upstream = ?ctx.upstream,
+                    error = %e,
+                    "Failed to inject upstream credential"
+                );
+            }
+        }
+
+        // Sign for AWS last, once every other header is final. The signature
+        // covers the body as received, which is read ahead for it; its hash
+        // is kept so a retried request is signed the same way.
+        if let Some(signer) = signer {
+            let payload_sha256 = match ctx.signed_payload_sha256 {
+                Some(ref hash) => hash.clone(),
+                None => {
+                    let body = self.prefetch_request_body(session, ctx).await?;
+                    let hash = crate::upstream::sigv4::payload_sha256(&body);
+                    ctx.signed_payload_sha256 = Some(hash.clone());
+                    hash
+                }
+            };
+            if let Err(e) = signer.sign(upstream_request, &payload_sha256, chrono::Utc::now()) {
+                warn!(
+                    correlation_id = %ctx.trace_id,
+                    upstream = ?ctx.upstream,
+                    error = %e,
+                    "Failed to sign upstream request"
+                );
+                return Err(Error::explain(
+                    ErrorType::InternalError,
+                    "Failed to sign upstream request",
+                ));
+            }
+        }
+
         Ok(())
     }
 
     /// Process response body chunks from upstream.
     /// Used for response size tracking and WAF inspection.
     ///
-    /// Note: Response body inspection is currently buffered only (streaming mode not supported
-    /// for responses due to Pingora's synchronous filter design).
+    /// In stream mode each chunk is sent to agents, which may rewrite it before it is
+    /// forwarded to the client.
     fn response_body_filter(
         &self,
         _session: &mut Session,
@@ -2094,6 +2722,55 @@ impl ProxyHttp for SentinelProxy {
             return Ok(None);
         }
 
+        // Translate the upstream's response into the client's API before anything
+        // else reads it, so token counting and inspection see the client schema
+        if let Some(translator) = ctx.inference_translator {
+            if let Some(ref mut stream) = ctx.translation_stream {
+                let mut translated = body
+                    .take()
+                    .map(|chunk| stream.process_chunk(&chunk))
+                    .unwrap_or_default();
+                if end_of_stream {
+                    translated.extend(stream.finish());
+                }
+                *body = (!translated.is_empty()).then(|| Bytes::from(translated));
+            } else {
+                if let Some(chunk) = body.take() {
+                    ctx.translation_response_buffer.extend_from_slice(&chunk);
+                }
+                // The client must never see the upstream's schema, so a response
+                // that cannot be translated is dropped as a bad gateway
+                if ctx.translation_response_buffer.len() > MAX_TRANSLATION_BODY_BYTES {
+                    warn!(
+                        correlation_id = %ctx.trace_id,
+                        limit = MAX_TRANSLATION_BODY_BYTES,
+                        "Inference response too large to translate"
+                    );
+                    return Err(Error::explain(
+                        ErrorType::HTTPStatus(502),
+                        "Inference response too large to translate",
+                    ));
+                }
+                if end_of_stream && !ctx.translation_response_buffer.is_empty() {
+                    let original = std::mem::take(&mut ctx.translation_response_buffer);
+                    match translator.translate_response(&original) {
+                        Ok(translated) => *body = Some(Bytes::from(translated)),
+                        Err(e) => {
+                            warn!(
+                                correlation_id = %ctx.trace_id,
+                                error = %e,
+                                "Failed to translate inference response"
+                            );
+                            return Err(Error::explain(
+                                ErrorType::HTTPStatus(502),
+                                "Inference response could not be translated",
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
         // Track response body size
         if let Some(ref chunk) = body {
             ctx.response_bytes += chunk.len() as u64;
@@ -2106,6 +2783,17 @@ impl ProxyHttp for SentinelProxy {
                 "Processing response body chunk"
             );
 
+            // Collect non-streaming inference responses for usage extraction
+            if ctx.inference_usage_body.is_some() {
+                let limit = ctx
+                    .route_config
+                    .as_ref()
+                    .and_then(|route| route.inference.as_ref())
+                    .and_then(|inference| inference.max_usage_body_bytes)
+                    .unwrap_or(DEFAULT_INFERENCE_USAGE_BODY_BYTES);
+                ctx.record_inference_usage(chunk, limit);
+            }
+
             // Process SSE chunks for streaming token counting
             if let Some(ref mut counter) = ctx.inference_streaming_counter {
                 let result = counter.process_chunk(chunk);
@@ -2122,11 +2810,10 @@ impl ProxyHttp for SentinelProxy {
                 }
             }
 
-            // Response body inspection (buffered mode only)
-            // Note: Streaming mode for response bodies is not currently supported
-            // due to Pingora's synchronous response_body_filter design
+            // Response body inspection tracking (buffer and hybrid modes)
             if ctx.response_body_inspection_enabled
                 && !ctx.response_body_inspection_agents.is_empty()
+                && ctx.response_body_streaming_mode != sentinel_config::BodyStreamingMode::Stream
             {
                 let config = ctx
                     .config
@@ -2160,6 +2847,60 @@ impl ProxyHttp for SentinelProxy {
             }
         }
 
+        // Tool policy: also after token counting, and before the streaming
+        // output guardrail so stripped calls are never inspected or released
+        if ctx.inference_tool_filter.is_some() {
+            self.apply_tool_policy(body, end_of_stream, ctx);
+        }
+
+        // Streaming output guardrail: runs after token counting, so usage still
+        // reflects what the upstream generated
+        if ctx.inference_stream_guard.is_some() {
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current().block_on(self.apply_stream_guardrail(
+                    body,
+                    end_of_stream,
+                    ctx,
+                ))
+            });
+        }
+
+        // Stream mode: agents see (and may rewrite) each chunk before it is
+        // forwarded. The filter is synchronous, so calls go through
+        // block_in_place as for WebSocket frames; holding each chunk until the
+        // agents answer keeps at most one chunk in flight per response.
+        if ctx.response_body_inspection_enabled
+            && !ctx.response_body_inspection_agents.is_empty()
+            && ctx.response_body_streaming_mode == sentinel_config::BodyStreamingMode::Stream
+            && (body.is_some() || (end_of_stream && ctx.response_agent_needs_more))
+        {
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current()
+                    .block_on(self.process_response_body_chunk_streaming(body, end_of_stream, ctx))
+            })?;
+        }
+
+        // Time to first token and the completion for the inference audit log
+        if let Some(ref chunk) = body {
+            if ctx
+                .route_config
+                .as_ref()
+                .is_some_and(|r| r.inference.is_some())
+            {
+                ctx.record_inference_output(chunk);
+            }
+        }
+
+        // Copy the body as sent to the client for the inference cache
+        if let Some(ref mut recorder) = ctx.inference_cache_recorder {
+            if let Some(ref chunk) = body {
+                recorder.record(chunk);
+            }
+            if end_of_stream {
+                recorder.finish();
+            }
+        }
+
         if end_of_stream {
             trace!(
                 correlation_id = %ctx.trace_id,
@@ -2889,10 +3630,12 @@ impl ProxyHttp for SentinelProxy {
             .unwrap_or(0);
 
         // Report result to load balancer for adaptive LB feedback
-        // This enables latency-aware weight adjustment
-        if let (Some(ref peer_addr), Some(ref upstream_id)) =
-            (&ctx.selected_upstream_address, &ctx.upstream)
-        {
+        // This enables latency-aware weight adjustment (cache hits never reached the upstream)
+        if let (Some(ref peer_addr), Some(ref upstream_id), None) = (
+            &ctx.selected_upstream_address,
+            &ctx.upstream,
+            ctx.inference_cache_hit,
+        ) {
             // Success = status code < 500 (client errors are not upstream failures)
             let success = status > 0 && status < 500;
 
@@ -2923,11 +3666,33 @@ impl ProxyHttp for SentinelProxy {
                     );
                 }
             }
+
+            // Streaming latency for TTFT-aware routing, fallback, and metrics
+            if ctx.inference_streaming_response && success {
+                self.record_stream_latency(ctx, upstream_id, peer_addr);
+            }
+        }
+
+        // Cache hits consumed no upstream tokens, so refund the estimates
+        if ctx.inference_rate_limit_enabled && ctx.inference_cache_hit.is_some() {
+            if let (Some(route_id), Some(ref rate_limit_key)) =
+                (ctx.route_id.as_deref(), &ctx.inference_rate_limit_key)
+            {
+                self.inference_rate_limit_manager.record_cache_hit(
+                    route_id,
+                    rate_limit_key,
+                    ctx.inference_estimated_tokens,
+                );
+            }
+            if let Some(ref key) = ctx.virtual_key {
+                key.record_actual(0, ctx.inference_estimated_tokens);
+            }
+            ctx.inference_actual_tokens = Some(0);
         }
 
         // Record actual token usage for inference rate limiting
         // This adjusts the token bucket based on actual vs estimated tokens
-        if ctx.inference_rate_limit_enabled {
+        if ctx.inference_rate_limit_enabled && ctx.inference_cache_hit.is_none() {
             if let (Some(route_id), Some(ref rate_limit_key)) =
                 (ctx.route_id.as_deref(), &ctx.inference_rate_limit_key)
             {
@@ -3029,16 +3794,15 @@ impl ProxyHttp for SentinelProxy {
                     }
                 }
 
-                // Response body would require buffering, which is expensive
-                // For non-streaming, most LLM APIs provide token counts in headers
-                // For streaming, we use the accumulated SSE content
-                let empty_body: &[u8] = &[];
+                // Non-streaming JSON bodies are collected up to a size limit;
+                // for streaming, we use the accumulated SSE content
+                let usage_body = ctx.inference_usage_body.as_deref().unwrap_or_default();
 
                 if let Some(actual_estimate) = self.inference_rate_limit_manager.record_actual(
                     route_id,
                     rate_limit_key,
                     &response_headers,
-                    empty_body,
+                    usage_body,
                     ctx.inference_estimated_tokens,
                 ) {
                     // Use streaming result if available and header extraction failed
@@ -3073,11 +3837,10 @@ impl ProxyHttp for SentinelProxy {
 
                     // Record budget usage with actual tokens (if budget tracking enabled)
                     if ctx.inference_budget_enabled {
-                        let alerts = self.inference_rate_limit_manager.record_budget(
-                            route_id,
-                            rate_limit_key,
-                            actual_tokens,
-                        );
+                        let alerts = self
+                            .inference_rate_limit_manager
+                            .record_budget(route_id, rate_limit_key, actual_tokens)
+                            .await;
 
                         // Log any budget alerts that fired
                         for alert in alerts.iter() {
@@ -3093,14 +3856,32 @@ impl ProxyHttp for SentinelProxy {
                         }
 
                         // Update context with remaining budget
-                        if let Some(status) = self.inference_rate_limit_manager.budget_status(
-                            route_id,
-                            rate_limit_key,
-                        ) {
+                        if let Some(status) = self
+                            .inference_rate_limit_manager
+                            .budget_status(route_id, rate_limit_key)
+                            .await
+                        {
                             ctx.inference_budget_remaining = Some(status.tokens_remaining as i64);
                         }
                     }
 
+                    // Charge the virtual key's own rate limit and budget
+                    if let Some(ref key) = ctx.virtual_key {
+                        key.record_actual(actual_tokens, ctx.inference_estimated_tokens);
+                        for alert in key.record_budget(actual_tokens).await {
+                            warn!(
+                                correlation_id = %ctx.trace_id,
+                                route_id = route_id,
+                                key_id = key.id(),
+                                tenant = key.tenant(),
+                                threshold_pct = alert.threshold * 100.0,
+                                tokens_used = alert.tokens_used,
+                                tokens_limit = alert.tokens_limit,
+                                "API key budget alert threshold crossed"
+                            );
+                        }
+                    }
+
                     // Calculate cost if cost attribution is enabled
                     if ctx.inference_cost_enabled {
                         if let Some(model) = ctx.inference_model.as_deref() {
@@ -3116,10 +3897,12 @@ impl ProxyHttp for SentinelProxy {
                                 let output = actual_tokens.saturating_sub(input);
                                 (input, output)
                             };
+                            ctx.inference_input_tokens = input_tokens;
                             ctx.inference_output_tokens = output_tokens;
 
                             if let Some(cost_result) = self.inference_rate_limit_manager.calculate_cost(
                                 route_id,
+                                rate_limit_key,
                                 model,
                                 input_tokens,
                                 output_tokens,
@@ -3129,6 +3912,7 @@ impl ProxyHttp for SentinelProxy {
                                 trace!(
                                     correlation_id = %ctx.trace_id,
                                     route_id = route_id,
+                                    tenant = %rate_limit_key,
                                     model = model,
                                     input_tokens = input_tokens,
                                     output_tokens = output_tokens,
@@ -3143,6 +3927,30 @@ impl ProxyHttp for SentinelProxy {
             }
         }
 
+        // Store the completed response in the inference cache
+        if _error.is_none() {
+            if let (Some(recorder), Some(request)) = (
+                ctx.inference_cache_recorder.take(),
+                ctx.inference_cache_request.take(),
+            ) {
+                let cache = ctx
+                    .route_id
+                    .as_deref()
+                    .and_then(|route_id| self.inference_rate_limit_manager.cache(route_id));
+                if let (Some(cache), Some(response)) = (
+                    cache,
+                    recorder.into_response(ctx.inference_actual_tokens.unwrap_or(0)),
+                ) {
+                    cache.insert(request, response);
+                }
+            }
+        }
+
+        // Record the request in the inference audit log if it was sampled
+        if ctx.inference_audit.is_some() {
+            self.log_inference_audit(ctx, status, duration);
+        }
+
         // Write to access log file if configured (check sampling before allocating entry)
         if self.log_manager.should_log_access(status) {
             let access_entry = AccessLogEntry {
@@ -3171,6 +3979,7 @@ impl ProxyHttp for SentinelProxy {
                 connection_reused: ctx.connection_reused,
                 rate_limit_hit: status == 429,
                 geo_country: ctx.geo_country_code.clone(),
+                routing_override: ctx.routing_override_applied.clone(),
             };
             self.log_manager.log_access(&access_entry);
         }
@@ -3214,6 +4023,118 @@ impl ProxyHttp for SentinelProxy {
 // =============================================================================
 
 impl SentinelProxy {
+    /// Send a request body chunk to the body inspection agents (WAF, etc.)
+    /// according to the route's streaming mode.
+    pub(super) async fn inspect_request_body(
+        &self,
+        body: &mut Option<Bytes>,
+        end_of_stream: bool,
+        ctx: &mut RequestContext,
+    ) -> Result<(), Box<Error>> {
+        use sentinel_config::BodyStreamingMode;
+
+        if ctx.body_inspection_enabled && !ctx.body_inspection_agents.is_empty() {
+            let config = ctx
+                .config
+                .get_or_insert_with(|| self.config_manager.current());
+            let max_inspection_bytes = config
+                .waf
+                .as_ref()
+                .map(|w| w.body_inspection.max_inspection_bytes as u64)
+                .unwrap_or(1024 * 1024);
+
+            match ctx.request_body_streaming_mode {
+                BodyStreamingMode::Stream => {
+                    // Stream mode: send each chunk immediately
+                    if body.is_some() {
+                        self.process_body_chunk_streaming(body, end_of_stream, ctx)
+                            .await?;
+                    } else if end_of_stream && ctx.agent_needs_more {
+                        // Send final empty chunk to signal end
+                        self.process_body_chunk_streaming(body, end_of_stream, ctx)
+                            .await?;
+                    }
+                }
+                BodyStreamingMode::Hybrid { buffer_threshold } => {
+                    // Hybrid mode: buffer up to threshold, then stream
+                    if ctx.body_bytes_inspected < buffer_threshold as u64 {
+                        // Still in buffering phase
+                        if let Some(ref chunk) = body {
+                            let bytes_to_buffer = std::cmp::min(
+                                chunk.len(),
+                                (buffer_threshold as u64 - ctx.body_bytes_inspected) as usize,
+                            );
+                            ctx.body_buffer.extend_from_slice(&chunk[..bytes_to_buffer]);
+                            ctx.body_bytes_inspected += bytes_to_buffer as u64;
+
+                            // If we've reached threshold or end of stream, switch to streaming
+                            if ctx.body_bytes_inspected >= buffer_threshold as u64 || end_of_stream
+                            {
+                                // Send buffered content first
+                                self.send_buffered_body_to_agents(
+                                    end_of_stream && chunk.len() == bytes_to_buffer,
+                                    ctx,
+                                )
+                                .await?;
+                                ctx.body_buffer.clear();
+
+                                // If there's remaining data in this chunk, stream it
+                                if bytes_to_buffer < chunk.len() {
+                                    let remaining = chunk.slice(bytes_to_buffer..);
+                                    let mut remaining_body = Some(remaining);
+                                    self.process_body_chunk_streaming(
+                                        &mut remaining_body,
+                                        end_of_stream,
+                                        ctx,
+                                    )
+                                    .await?;
+                                }
+                            }
+                        }
+                    } else {
+                        // Past threshold, stream directly
+                        self.process_body_chunk_streaming(body, end_of_stream, ctx)
+                            .await?;
+                    }
+                }
+                BodyStreamingMode::Buffer => {
+                    // Buffer mode: collect chunks until ready to send
+                    if let Some(ref chunk) = body {
+                        if ctx.body_bytes_inspected < max_inspection_bytes {
+                            let bytes_to_inspect = std::cmp::min(
+                                chunk.len() as u64,
+                                max_inspection_bytes - ctx.body_bytes_inspected,
+                            ) as usize;
+
+                            ctx.body_buffer
+                                .extend_from_slice(&chunk[..bytes_to_inspect]);
+                            ctx.body_bytes_inspected += bytes_to_inspect as u64;
+
+                            trace!(
+                                correlation_id = %ctx.trace_id,
+                                bytes_inspected = ctx.body_bytes_inspected,
+                                max_inspection_bytes = max_inspection_bytes,
+                                buffer_size = ctx.body_buffer.len(),
+                                "Buffering body for agent inspection"
+                            );
+                        }
+                    }
+
+                    // Send when complete or limit reached
+                    let should_send =
+                        end_of_stream || ctx.body_bytes_inspected >= max_inspection_bytes;
+                    if should_send && !ctx.body_buffer.is_empty() {
+                        self.send_buffered_body_to_agents(end_of_stream, ctx)
+                            .await?;
+                        ctx.body_buffer.clear();
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Process a single body chunk in streaming mode.
     async fn process_body_chunk_streaming(
         &self,
@@ -3251,6 +4172,7 @@ impl SentinelProxy {
                 upstream_id: ctx.upstream.clone(),
                 timestamp: chrono::Utc::now().to_rfc3339(),
                 traceparent: ctx.traceparent(),
+                client_cert: ctx.client_cert().map(|cert| cert.info().clone()),
             },
             route_id: ctx.route_id.clone(),
             upstream_id: ctx.upstream.clone(),
@@ -3280,26 +4202,20 @@ impl SentinelProxy {
 
                 // Apply body mutation if present
                 if let Some(ref mutation) = decision.request_body_mutation {
-                    if !mutation.is_pass_through() {
-                        if mutation.is_drop() {
-                            // Drop the chunk
-                            *body = None;
-                            trace!(
-                                correlation_id = %ctx.trace_id,
-                                chunk_index = chunk_index,
-                                "Agent dropped body chunk"
-                            );
-                        } else if let Some(ref new_data) = mutation.data {
-                            // Replace chunk with mutated content
-                            *body = Some(Bytes::from(new_data.clone()));
-                            trace!(
-                                correlation_id = %ctx.trace_id,
-                                chunk_index = chunk_index,
-                                original_size = chunk_data.len(),
-                                new_size = new_data.len(),
-                                "Agent mutated body chunk"
-                            );
-                        }
+                    if ctx.request_body_length_fixed {
+                        debug!(
+                            correlation_id = %ctx.trace_id,
+                            chunk_index = chunk_index,
+                            "Ignoring body mutation, upstream request keeps its Content-Length"
+                        );
+                    } else if body_rewrite::apply_body_mutation(body, mutation) {
+                        trace!(
+                            correlation_id = %ctx.trace_id,
+                            chunk_index = chunk_index,
+                            original_size = chunk_data.len(),
+                            new_size = body.as_ref().map(|b| b.len()).unwrap_or(0),
+                            "Agent mutated body chunk"
+                        );
                     }
                 }
 
@@ -3359,6 +4275,297 @@ impl SentinelProxy {
         Ok(())
     }
 
+    /// Process a single response body chunk in streaming mode.
+    ///
+    /// The response status is already on its way to the client, so a blocking
+    /// decision aborts the response instead of replacing it.
+    async fn process_response_body_chunk_streaming(
+        &self,
+        body: &mut Option<Bytes>,
+        end_of_stream: bool,
+        ctx: &mut RequestContext,
+    ) -> Result<(), Box<Error>> {
+        let chunk_data: Vec<u8> = body.as_ref().map(|b| b.to_vec()).unwrap_or_default();
+        let chunk_index = ctx.response_body_chunk_index;
+        ctx.response_body_chunk_index += 1;
+        ctx.response_body_bytes_inspected += chunk_data.len() as u64;
+
+        debug!(
+            correlation_id = %ctx.trace_id,
+            chunk_index = chunk_index,
+            chunk_size = chunk_data.len(),
+            end_of_stream = end_of_stream,
+            "Streaming response body chunk to agents"
+        );
+
+        let agent_ctx = crate::agents::AgentCallContext {
+            correlation_id: sentinel_common::CorrelationId::from_string(&ctx.trace_id),
+            metadata: sentinel_agent_protocol::RequestMetadata {
+                correlation_id: ctx.trace_id.clone(),
+                request_id: ctx.trace_id.clone(),
+                client_ip: ctx.client_ip.clone(),
+                client_port: 0,
+                server_name: ctx.host.clone(),
+                protocol: "HTTP/1.1".to_string(),
+                tls_version: None,
+                tls_cipher: None,
+                route_id: ctx.route_id.clone(),
+                upstream_id: ctx.upstream.clone(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                traceparent: ctx.traceparent(),
+                client_cert: ctx.client_cert().map(|cert| cert.info().clone()),
+            },
+            route_id: ctx.route_id.clone(),
+            upstream_id: ctx.upstream.clone(),
+            request_body: None,
+            response_body: None,
+        };
+
+        let agent_ids = ctx.response_body_inspection_agents.clone();
+
+        match self
+            .agent_manager
+            .process_response_body_streaming(
+                &agent_ctx,
+                &chunk_data,
+                end_of_stream,
+                chunk_index,
+                ctx.response_body_bytes_inspected as usize,
+                None,
+                &agent_ids,
+            )
+            .await
+        {
+            Ok(decision) => {
+                ctx.response_agent_needs_more = decision.needs_more;
+
+                if let Some(ref mutation) = decision.response_body_mutation {
+                    if body_rewrite::apply_body_mutation(body, mutation) {
+                        trace!(
+                            correlation_id = %ctx.trace_id,
+                            chunk_index = chunk_index,
+                            original_size = chunk_data.len(),
+                            new_size = body.as_ref().map(|b| b.len()).unwrap_or(0),
+                            "Agent mutated response body chunk"
+                        );
+                    }
+                }
+
+                if !decision.needs_more && !decision.is_allow() {
+                    warn!(
+                        correlation_id = %ctx.trace_id,
+                        action = ?decision.action,
+                        chunk_index = chunk_index,
+                        "Agent blocked response body, aborting response"
+                    );
+                    self.metrics
+                        .record_blocked_request("agent_response_body_inspection");
+                    return Err(Error::explain(
+                        ErrorType::InternalError,
+                        "Response body blocked by agent",
+                    ));
+                }
+            }
+            Err(e) => {
+                let fail_closed = ctx
+                    .route_config
+                    .as_ref()
+                    .map(|r| r.policies.failure_mode == sentinel_config::FailureMode::Closed)
+                    .unwrap_or(false);
+
+                if fail_closed {
+                    error!(
+                        correlation_id = %ctx.trace_id,
+                        error = %e,
+                        "Agent streaming response body inspection failed, aborting (fail-closed)"
+                    );
+                    return Err(Error::explain(
+                        ErrorType::InternalError,
+                        "Response body inspection failed",
+                    ));
+                } else {
+                    warn!(
+                        correlation_id = %ctx.trace_id,
+                        error = %e,
+                        "Agent streaming response body inspection failed, allowing (fail-open)"
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Run an inference response through the tool call filter.
+    ///
+    /// Disallowed tool calls are logged, stripped, or end the response with a
+    /// provider error; anything the upstream sends after a block is dropped.
+    fn apply_tool_policy(
+        &self,
+        body: &mut Option<Bytes>,
+        end_of_stream: bool,
+        ctx: &mut RequestContext,
+    ) {
+        let Some(mut filter) = ctx.inference_tool_filter.take() else {
+            return;
+        };
+        let was_terminated = filter.is_terminated();
+        let was_uninspected = filter.is_uninspected();
+
+        let mut output = match body {
+            Some(chunk) => filter.push(chunk),
+            None => Vec::new(),
+        };
+        if end_of_stream {
+            output.extend(filter.finish());
+        }
+
+        let tools = filter.take_tools();
+        if !tools.is_empty() {
+            self.record_tool_policy(ctx, "response", &tools);
+            // Tool lists differ per tenant, so a response with tool calls is
+            // not replayed from the cache
+            if filter.action() != sentinel_config::ToolPolicyAction::Log {
+                ctx.inference_cache_recorder = None;
+            }
+        }
+        if filter.is_terminated() && !was_terminated {
+            warn!(
+                correlation_id = %ctx.trace_id,
+                route_id = ctx.route_id.as_deref().unwrap_or("unknown"),
+                "Tool policy terminated inference response"
+            );
+            self.metrics.record_blocked_request("inference_tool_policy");
+        }
+        if filter.is_uninspected() && !was_uninspected {
+            let route_id = ctx.route_id.as_deref().unwrap_or("unknown");
+            warn!(
+                correlation_id = %ctx.trace_id,
+                route_id = route_id,
+                "Inference response too large for tool policy inspection, passing through"
+            );
+            self.metrics
+                .record_tool_call(route_id, "*", "response", "uninspected");
+            if filter.action() != sentinel_config::ToolPolicyAction::Log {
+                ctx.inference_cache_recorder = None;
+            }
+        }
+
+        *body = (!output.is_empty()).then(|| Bytes::from(output));
+        ctx.inference_tool_filter = Some(filter);
+    }
+
+    /// Run a streamed inference response through the output guardrail.
+    ///
+    /// Deltas are held until a window is complete, then released (possibly
+    /// redacted) or, on a block, replaced by a provider error event that ends
+    /// the stream; anything the upstream sends afterwards is dropped.
+    async fn apply_stream_guardrail(
+        &self,
+        body: &mut Option<Bytes>,
+        end_of_stream: bool,
+        ctx: &mut RequestContext,
+    ) {
+        let Some(mut guard) = ctx.inference_stream_guard.take() else {
+            return;
+        };
+        let Some(config) = ctx
+            .route_config
+            .as_ref()
+            .and_then(|r| r.inference.as_ref())
+            .and_then(|i| i.guardrails.as_ref())
+            .and_then(|g| g.streaming_output.clone())
+        else {
+            return;
+        };
+
+        let mut output = match body {
+            Some(chunk) => guard.push(chunk),
+            None => Vec::new(),
+        };
+        if end_of_stream {
+            output.extend(guard.finish());
+        }
+
+        let inspect = guard.window_ready() || (end_of_stream && guard.has_held());
+        if !guard.is_terminated() && inspect {
+            if guard.pending_text().is_empty() {
+                output.extend(guard.release(None));
+            } else {
+                let result = self
+                    .guardrail_processor
+                    .check_stream_window(
+                        &config,
+                        guard.pending_text(),
+                        guard.offset(),
+                        end_of_stream,
+                        ctx.inference_model.as_deref(),
+                        ctx.route_id.as_deref(),
+                        &ctx.trace_id,
+                    )
+                    .await;
+
+                // Keep detection categories for the inference audit log
+                let (StreamingOutputResult::Allow { detections }
+                | StreamingOutputResult::Redact { detections, .. }
+                | StreamingOutputResult::Block { detections, .. }) = &result;
+                for detection in detections {
+                    if !ctx.pii_detection_categories.contains(&detection.category) {
+                        ctx.pii_detection_categories
+                            .push(detection.category.clone());
+                    }
+                }
+
+                match result {
+                    StreamingOutputResult::Allow { detections } => {
+                        if !detections.is_empty() {
+                            warn!(
+                                correlation_id = %ctx.trace_id,
+                                route_id = ctx.route_id.as_deref().unwrap_or("unknown"),
+                                detection_count = detections.len(),
+                                offset = guard.offset(),
+                                "Output guardrail detections in streamed response (logged only)"
+                            );
+                        }
+                        output.extend(guard.release(None));
+                    }
+                    StreamingOutputResult::Redact {
+                        detections,
+                        content,
+                    } => {
+                        debug!(
+                            correlation_id = %ctx.trace_id,
+                            detection_count = detections.len(),
+                            offset = guard.offset(),
+                            "Redacting streamed response window"
+                        );
+                        output.extend(guard.release(Some(&content)));
+                    }
+                    StreamingOutputResult::Block {
+                        message,
+                        detections,
+                    } => {
+                        warn!(
+                            correlation_id = %ctx.trace_id,
+                            route_id = ctx.route_id.as_deref().unwrap_or("unknown"),
+                            detection_count = detections.len(),
+                            offset = guard.offset(),
+                            "Output guardrail terminated streamed response"
+                        );
+                        self.metrics
+                            .record_blocked_request("inference_output_guardrail");
+                        // A truncated stream must not be replayed from the cache
+                        ctx.inference_cache_recorder = None;
+                        output.extend(guard.terminate(&message));
+                    }
+                }
+            }
+        }
+
+        *body = (!output.is_empty()).then(|| Bytes::from(output));
+        ctx.inference_stream_guard = Some(guard);
+    }
+
     /// Send buffered body to agents (buffer mode).
     async fn send_buffered_body_to_agents(
         &self,
@@ -3472,6 +4679,7 @@ impl SentinelProxy {
                 upstream_id: ctx.upstream.clone(),
                 timestamp: chrono::Utc::now().to_rfc3339(),
                 traceparent: ctx.traceparent(),
+                client_cert: ctx.client_cert().map(|cert| cert.info().clone()),
             },
             route_id: ctx.route_id.clone(),
             upstream_id: ctx.upstream.clone(),
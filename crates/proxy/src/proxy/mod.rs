@@ -7,6 +7,7 @@
 //! - `handlers`: Helper methods for handling different route types
 //! - `http_trait`: ProxyHttp trait implementation for Pingora
 
+mod body_rewrite;
 mod context;
 mod fallback;
 mod fallback_metrics;
@@ -14,6 +15,7 @@ mod handlers;
 mod http_trait;
 mod model_routing;
 mod model_routing_metrics;
+mod routing_override;
 
 pub use context::{FallbackReason, RequestContext};
 pub use fallback::{FallbackDecision, FallbackEvaluator};
@@ -40,11 +42,12 @@ use crate::agents::AgentManager;
 use crate::app::AppState;
 use crate::builtin_handlers::BuiltinHandlerState;
 use crate::cache::{CacheConfig, CacheManager};
+use crate::challenge::{init_challenge_metrics, ChallengeManager};
 use crate::errors::ErrorHandler;
 use crate::geo_filter::{GeoDatabaseWatcher, GeoFilterManager};
 use crate::health::PassiveHealthChecker;
 use crate::http_helpers;
-use crate::inference::InferenceRateLimitManager;
+use crate::inference::{InferenceRateLimitManager, VirtualKeyStore, VirtualKeyWatcher};
 use crate::logging::{LogManager, SharedLogManager};
 use crate::rate_limit::{RateLimitConfig, RateLimitManager};
 use crate::reload::{
@@ -107,8 +110,12 @@ pub struct SentinelProxy {
     pub(super) inference_rate_limit_manager: Arc<InferenceRateLimitManager>,
     /// Warmth tracker for cold model detection on inference routes
     pub(super) warmth_tracker: Arc<crate::health::WarmthTracker>,
+    /// Time-to-first-token averages per target, for TTFT-aware routing and fallback
+    pub(super) ttft_tracker: Arc<crate::inference::TtftTracker>,
     /// Guardrail processor for semantic inspection (prompt injection, PII detection)
     pub(super) guardrail_processor: Arc<crate::inference::GuardrailProcessor>,
+    /// Challenge manager for agent challenge decisions and clearance cookies
+    pub(super) challenge_manager: Arc<ChallengeManager>,
     /// ACME challenge manager for HTTP-01 challenge handling
     /// Present only when ACME is configured for at least one listener
     pub acme_challenges: Option<Arc<crate::acme::ChallengeManager>>,
@@ -231,6 +238,7 @@ impl SentinelProxy {
             upstream_pools.clone(),
             scoped_route_matcher.clone(),
             scoped_upstream_pools.clone(),
+            agent_manager.clone(),
         )
         .await;
 
@@ -297,6 +305,9 @@ impl SentinelProxy {
         // Initialize warmth tracker for cold model detection
         let warmth_tracker = Arc::new(crate::health::WarmthTracker::with_defaults());
 
+        // Initialize TTFT tracker for TTFT-aware inference routing
+        let ttft_tracker = Arc::new(crate::inference::TtftTracker::new());
+
         // Initialize guardrail processor for semantic inspection
         let guardrail_processor =
             Arc::new(crate::inference::GuardrailProcessor::new(agent_manager.clone()));
@@ -310,6 +321,9 @@ impl SentinelProxy {
         // Start geo database file watcher for hot reload
         Self::spawn_geo_database_watcher(geo_filter_manager.clone());
 
+        // Start virtual API key file watcher for hot reload
+        Self::spawn_virtual_key_watcher(inference_rate_limit_manager.virtual_key_stores());
+
         // Mark as ready
         app_state.set_ready(true);
 
@@ -329,6 +343,19 @@ impl SentinelProxy {
             warn!("Failed to initialize model routing metrics: {}", e);
         }
 
+        // Initialize inference metrics (best-effort, log warning if fails)
+        if let Err(e) = crate::inference::init_inference_metrics() {
+            warn!("Failed to initialize inference metrics: {}", e);
+        }
+
+        // Initialize challenge metrics (best-effort, log warning if fails)
+        if let Err(e) = init_challenge_metrics() {
+            warn!("Failed to initialize challenge metrics: {}", e);
+        }
+
+        // Create challenge manager (signing key survives config reloads)
+        let challenge_manager = Arc::new(ChallengeManager::new());
+
         Ok(Self {
             config_manager,
             route_matcher,
@@ -353,7 +380,9 @@ impl SentinelProxy {
             geo_filter_manager,
             inference_rate_limit_manager,
             warmth_tracker,
+            ttft_tracker,
             guardrail_processor,
+            challenge_manager,
             // ACME challenge manager - initialized later if ACME is configured
             acme_challenges: None,
             acme_client: None,
@@ -367,6 +396,7 @@ impl SentinelProxy {
         upstream_pools: Registry<UpstreamPool>,
         scoped_route_matcher: Arc<tokio::sync::RwLock<ScopedRouteMatcher>>,
         scoped_upstream_pools: ScopedRegistry<UpstreamPool>,
+        agent_manager: Arc<AgentManager>,
     ) {
         let mut reload_rx = config_manager.subscribe();
         let config_manager_clone = config_manager.clone();
@@ -427,6 +457,9 @@ impl SentinelProxy {
                         scoped_upstream_pools.len().await
                     );
 
+                    // Hot-swap WASM agent components
+                    agent_manager.reload_wasm_agents(&new_config.agents).await;
+
                     // Shutdown old pools after delay
                     tokio::spawn(async move {
                         tokio::time::sleep(Duration::from_secs(60)).await;
@@ -877,4 +910,44 @@ impl SentinelProxy {
             }
         }
     }
+
+    /// Spawn background task to reload virtual API key files when they change
+    fn spawn_virtual_key_watcher(stores: Vec<Arc<VirtualKeyStore>>) {
+        if stores.is_empty() {
+            return;
+        }
+
+        let watcher = Arc::new(VirtualKeyWatcher::new(stores));
+        match watcher.start_watching() {
+            Ok(mut rx) => {
+                let watcher_clone = watcher.clone();
+                tokio::spawn(async move {
+                    // Debounce interval
+                    const DEBOUNCE_MS: u64 = 500;
+
+                    while let Some(path) = rx.recv().await {
+                        // Debounce rapid changes (e.g., temp file then rename),
+                        // keeping every distinct file that changed meanwhile
+                        tokio::time::sleep(Duration::from_millis(DEBOUNCE_MS)).await;
+                        let mut changed = vec![path];
+                        while let Ok(path) = rx.try_recv() {
+                            if !changed.contains(&path) {
+                                changed.push(path);
+                            }
+                        }
+
+                        watcher_clone.handle_changes(&changed);
+                    }
+                });
+
+                info!("Started virtual API key file watcher");
+            }
+            Err(e) => {
+                warn!(
+                    error = %e,
+                    "Failed to start virtual API key file watcher, auto-reload disabled"
+                );
+            }
+        }
+    }
 }
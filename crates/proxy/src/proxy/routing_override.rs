@@ -0,0 +1,111 @@
+//! Validation of agent routing overrides.
+//!
+//! Request-header agents can ask for a different upstream, pin a target or
+//! supply a hash key through a [`RoutingOverride`](sentinel_agent_protocol::RoutingOverride).
+//! An agent must not be able to send traffic anywhere a route could not
+//! already go, so upstream overrides are limited to the route's own upstream,
+//! its fallback upstreams and its model routing upstreams.
+
+use sentinel_config::RouteConfig;
+
+/// Check whether a route may be switched to the given upstream.
+pub fn is_upstream_allowed(route: &RouteConfig, upstream: &str) -> bool {
+    if route.upstream.as_deref() == Some(upstream) {
+        return true;
+    }
+
+    if let Some(ref fallback) = route.fallback {
+        if fallback.upstreams.iter().any(|f| f.upstream == upstream) {
+            return true;
+        }
+    }
+
+    if let Some(model_routing) = route
+        .inference
+        .as_ref()
+        .and_then(|i| i.model_routing.as_ref())
+    {
+        if model_routing.default_upstream.as_deref() == Some(upstream)
+            || model_routing
+                .mappings
+                .iter()
+                .any(|m| m.upstream == upstream)
+        {
+            return true;
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sentinel_common::types::Priority;
+    use sentinel_config::{
+        FallbackConfig, FallbackUpstream, InferenceConfig, InferenceProvider, MatchCondition,
+        ModelRoutingConfig, ModelUpstreamMapping, RoutePolicies, ServiceType,
+    };
+    use std::collections::HashMap;
+
+    fn test_route() -> RouteConfig {
+        RouteConfig {
+            id: "api".to_string(),
+            priority: Priority::Normal,
+            matches: vec![MatchCondition::PathPrefix("/".to_string())],
+            upstream: Some("primary".to_string()),
+            service_type: ServiceType::Web,
+            policies: RoutePolicies::default(),
+            filters: vec![],
+            builtin_handler: None,
+            waf_enabled: false,
+            circuit_breaker: None,
+            retry_policy: None,
+            static_files: None,
+            api_schema: None,
+            error_pages: None,
+            websocket: false,
+            websocket_inspection: false,
+            inference: None,
+            shadow: None,
+            fallback: None,
+        }
+    }
+
+    #[test]
+    fn test_route_upstream_allowed() {
+        let route = test_route();
+        assert!(is_upstream_allowed(&route, "primary"));
+        assert!(!is_upstream_allowed(&route, "internal-admin"));
+    }
+
+    #[test]
+    fn test_fallback_and_model_upstreams_allowed() {
+        let mut route = test_route();
+        route.fallback = Some(FallbackConfig {
+            upstreams: vec![FallbackUpstream {
+                upstream: "backup".to_string(),
+                provider: InferenceProvider::Generic,
+                model_mapping: HashMap::new(),
+                skip_if_unhealthy: true,
+            }],
+            ..Default::default()
+        });
+        route.inference = Some(InferenceConfig {
+            model_routing: Some(ModelRoutingConfig {
+                mappings: vec![ModelUpstreamMapping {
+                    model_pattern: "gpt-4*".to_string(),
+                    upstream: "openai".to_string(),
+                    provider: None,
+                }],
+                default_upstream: Some("local-gpu".to_string()),
+            }),
+            ..Default::default()
+        });
+
+        assert!(is_upstream_allowed(&route, "backup"));
+        assert!(is_upstream_allowed(&route, "openai"));
+        assert!(is_upstream_allowed(&route, "local-gpu"));
+        assert!(!is_upstream_allowed(&route, "canary"));
+    }
+}
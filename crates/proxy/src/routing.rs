@@ -15,6 +15,8 @@ use sentinel_common::types::Priority;
 use sentinel_common::RouteId;
 use sentinel_config::{MatchCondition, RouteConfig, RoutePolicies};
 
+use crate::client_cert::{normalize_fingerprint, ClientCertIdentity};
+
 /// Route matcher for efficient route selection
 pub struct RouteMatcher {
     /// Routes sorted by priority (highest first)
@@ -27,6 +29,8 @@ pub struct RouteMatcher {
     needs_headers: bool,
     /// Whether any route requires query param matching (optimization flag)
     needs_query_params: bool,
+    /// Whether any route matches on the client certificate (optimization flag)
+    needs_client_cert: bool,
 }
 
 /// Compiled route with pre-processed match conditions
@@ -57,6 +61,12 @@ enum CompiledMatcher {
     Method(Vec<String>),
     /// Query parameter match
     QueryParam { name: String, value: Option<String> },
+    /// Client certificate subject match (glob)
+    ClientCertSubject(String),
+    /// Client certificate SAN match (glob)
+    ClientCertSan(String),
+    /// Client certificate SHA-256 fingerprint match (normalized hex)
+    ClientCertFingerprint(String),
 }
 
 /// Host matching logic
@@ -137,10 +147,23 @@ impl RouteMatcher {
                 .iter()
                 .any(|m| matches!(m, CompiledMatcher::QueryParam { .. }))
         });
+        let needs_client_cert = compiled_routes.iter().any(|r| {
+            r.matchers.iter().any(|m| {
+                matches!(
+                    m,
+                    CompiledMatcher::ClientCertSubject(_)
+                        | CompiledMatcher::ClientCertSan(_)
+                        | CompiledMatcher::ClientCertFingerprint(_)
+                )
+            })
+        });
 
         info!(
             compiled_routes = compiled_routes.len(),
-            needs_headers, needs_query_params, "Route matcher initialized"
+            needs_headers,
+            needs_query_params,
+            needs_client_cert,
+            "Route matcher initialized"
         );
 
         Ok(Self {
@@ -149,6 +172,7 @@ impl RouteMatcher {
             cache: Arc::new(RouteCache::new(1000)),
             needs_headers,
             needs_query_params,
+            needs_client_cert,
         })
     }
 
@@ -164,6 +188,12 @@ impl RouteMatcher {
         self.needs_query_params
     }
 
+    /// Check if any route matches on the client certificate
+    #[inline]
+    pub fn needs_client_cert(&self) -> bool {
+        self.needs_client_cert
+    }
+
     /// Match a request to a route
     pub fn match_request(&self, req: &RequestInfo<'_>) -> Option<RouteMatch> {
         trace!(
@@ -174,7 +204,7 @@ impl RouteMatcher {
         );
 
         // Check cache first (lock-free read, zero-allocation on hit)
-        let cached = req.with_cache_key(|key| {
+        let cached = req.with_cache_key(self.needs_client_cert, |key| {
             self.cache.get(key).map(|r| {
                 let route_id = r.clone();
                 drop(r);
@@ -231,7 +261,7 @@ impl RouteMatcher {
                 );
 
                 // Update cache — allocate key only on miss (rare after warmup)
-                req.with_cache_key(|key| {
+                req.with_cache_key(self.needs_client_cert, |key| {
                     self.cache.insert(key.to_string(), route.id.clone());
                 });
 
@@ -321,6 +351,15 @@ impl CompiledRoute {
                     name: name.clone(),
                     value: value.clone(),
                 },
+                MatchCondition::ClientCertSubject(pattern) => {
+                    CompiledMatcher::ClientCertSubject(pattern.clone())
+                }
+                MatchCondition::ClientCertSan(pattern) => {
+                    CompiledMatcher::ClientCertSan(pattern.clone())
+                }
+                MatchCondition::ClientCertFingerprint(fingerprint) => {
+                    CompiledMatcher::ClientCertFingerprint(normalize_fingerprint(fingerprint))
+                }
             };
             matchers.push(compiled);
         }
@@ -382,6 +421,8 @@ impl CompiledRoute {
                         15
                     }
                 }
+                CompiledMatcher::ClientCertFingerprint(_) => 60,
+                CompiledMatcher::ClientCertSubject(_) | CompiledMatcher::ClientCertSan(_) => 40,
             };
         }
         score
@@ -411,6 +452,15 @@ impl CompiledMatcher {
                     false
                 }
             }
+            Self::ClientCertSubject(pattern) => req
+                .client_cert
+                .is_some_and(|cert| cert.matches_subject(pattern)),
+            Self::ClientCertSan(pattern) => req
+                .client_cert
+                .is_some_and(|cert| cert.matches_san(pattern)),
+            Self::ClientCertFingerprint(fingerprint) => req
+                .client_cert
+                .is_some_and(|cert| cert.fingerprint() == fingerprint),
         }
     }
 }
@@ -551,6 +601,8 @@ pub struct RequestInfo<'a> {
     headers: Option<HashMap<String, String>>,
     /// Query parameters (lazy-initialized, only if needed)
     query_params: Option<HashMap<String, String>>,
+    /// Verified client certificate (mTLS listeners only)
+    client_cert: Option<&'a ClientCertIdentity>,
 }
 
 impl<'a> RequestInfo<'a> {
@@ -563,6 +615,7 @@ impl<'a> RequestInfo<'a> {
             host,
             headers: None,
             query_params: None,
+            client_cert: None,
         }
     }
 
@@ -580,6 +633,13 @@ impl<'a> RequestInfo<'a> {
         self
     }
 
+    /// Set the verified client certificate for certificate-based matching
+    #[inline]
+    pub fn with_client_cert(mut self, cert: Option<&'a ClientCertIdentity>) -> Self {
+        self.client_cert = cert;
+        self
+    }
+
     /// Get headers (returns empty map if not set)
     #[inline]
     pub fn headers(&self) -> &HashMap<String, String> {
@@ -600,7 +660,10 @@ impl<'a> RequestInfo<'a> {
 
     /// Generate a cache key for this request using a thread-local buffer
     /// to avoid per-request heap allocation.
-    fn with_cache_key<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+    ///
+    /// The client certificate fingerprint is part of the key only when
+    /// `include_cert` is set, i.e. when some route matches on the certificate.
+    fn with_cache_key<R>(&self, include_cert: bool, f: impl FnOnce(&str) -> R) -> R {
         use std::cell::RefCell;
         use std::fmt::Write;
 
@@ -612,6 +675,10 @@ impl<'a> RequestInfo<'a> {
             let mut buf = buf.borrow_mut();
             buf.clear();
             let _ = write!(buf, "{}:{}:{}", self.method, self.host, self.path);
+            // Certificate-matched routes differ per client identity
+            if let Some(cert) = self.client_cert.filter(|_| include_cert) {
+                let _ = write!(buf, ":{}", cert.fingerprint());
+            }
             f(&buf)
         })
     }
@@ -707,6 +774,9 @@ impl std::fmt::Debug for CompiledMatcher {
             Self::Header { name, .. } => write!(f, "Header({})", name),
             Self::Method(m) => write!(f, "Method({:?})", m),
             Self::QueryParam { name, .. } => write!(f, "QueryParam({})", name),
+            Self::ClientCertSubject(p) => write!(f, "ClientCertSubject({})", p),
+            Self::ClientCertSan(p) => write!(f, "ClientCertSan({})", p),
+            Self::ClientCertFingerprint(p) => write!(f, "ClientCertFingerprint({})", p),
         }
     }
 }
@@ -762,6 +832,7 @@ mod tests {
             host: "example.com",
             headers: None,
             query_params: None,
+            client_cert: None,
         };
 
         let result = matcher.match_request(&req).unwrap();
@@ -783,6 +854,7 @@ mod tests {
             host: "api.example.com",
             headers: None,
             query_params: None,
+            client_cert: None,
         };
 
         let result = matcher.match_request(&req).unwrap();
@@ -808,6 +880,7 @@ mod tests {
             host: "example.com",
             headers: None,
             query_params: None,
+            client_cert: None,
         };
 
         let result = matcher.match_request(&req).unwrap();
@@ -821,4 +894,74 @@ mod tests {
         assert_eq!(params.get("baz"), Some(&"qux".to_string()));
         assert_eq!(params.get("empty"), Some(&"".to_string()));
     }
+
+    #[test]
+    fn test_client_cert_matching() {
+        use sentinel_agent_protocol::ClientCertInfo;
+
+        let routes = vec![
+            create_test_route(
+                "partner",
+                vec![
+                    MatchCondition::PathPrefix("/api/".to_string()),
+                    MatchCondition::ClientCertSan("spiffe://example.org/partner/*".to_string()),
+                ],
+            ),
+            create_test_route("public", vec![MatchCondition::PathPrefix("/api/".to_string())]),
+        ];
+        let matcher = RouteMatcher::new(routes, None).unwrap();
+        assert!(matcher.needs_client_cert());
+
+        let partner = ClientCertIdentity::new(ClientCertInfo {
+            subject: Some("CN=acme".to_string()),
+            sans: vec!["URI:spiffe://example.org/partner/acme".to_string()],
+            fingerprint_sha256: "aa".to_string(),
+            ..Default::default()
+        });
+        let other = ClientCertIdentity::new(ClientCertInfo {
+            sans: vec!["URI:spiffe://example.org/internal/batch".to_string()],
+            fingerprint_sha256: "bb".to_string(),
+            ..Default::default()
+        });
+
+        let req = RequestInfo::new("GET", "/api/orders", "example.com");
+        assert_eq!(matcher.match_request(&req).unwrap().route_id.as_str(), "public");
+
+        // Same path, different identities must not share a cache entry
+        let req = RequestInfo::new("GET", "/api/orders", "example.com")
+            .with_client_cert(Some(&partner));
+        assert_eq!(matcher.match_request(&req).unwrap().route_id.as_str(), "partner");
+
+        let req = RequestInfo::new("GET", "/api/orders", "example.com")
+            .with_client_cert(Some(&other));
+        assert_eq!(matcher.match_request(&req).unwrap().route_id.as_str(), "public");
+    }
+
+    #[test]
+    fn test_client_cert_not_in_cache_key_without_cert_routes() {
+        use sentinel_agent_protocol::ClientCertInfo;
+
+        let routes = vec![create_test_route(
+            "api",
+            vec![MatchCondition::PathPrefix("/api/".to_string())],
+        )];
+        let matcher = RouteMatcher::new(routes, None).unwrap();
+        assert!(!matcher.needs_client_cert());
+
+        for fingerprint in ["aa", "bb", "cc"] {
+            let cert = ClientCertIdentity::new(ClientCertInfo {
+                fingerprint_sha256: fingerprint.to_string(),
+                ..Default::default()
+            });
+            let req =
+                RequestInfo::new("GET", "/api/orders", "example.com").with_client_cert(Some(&cert));
+            assert_eq!(
+                matcher.match_request(&req).unwrap().route_id.as_str(),
+                "api"
+            );
+        }
+
+        // One cache entry shared by all client identities
+        assert_eq!(matcher.cache_stats().entries, 1);
+    }
 }
@@ -0,0 +1,743 @@
+//! SPIFFE workload identity for upstream mTLS.
+//!
+//! Streams X.509 SVIDs and trust bundles from the SPIFFE Workload API (e.g. a
+//! SPIRE agent) over a Unix socket. Every update replaces the current
+//! [`X509Context`], so certificate rotations are picked up by new upstream
+//! connections without a config reload.
+//!
+//! Upstream peers are verified by SPIFFE ID rather than hostname: the peer's
+//! chain must validate against the bundle of its trust domain and its URI SAN
+//! must match one of the configured `allowed-id` patterns (or, when none are
+//! configured, belong to the workload's own trust domain).
+//!
+//! # Configuration
+//!
+//! ```kdl
+//! upstream "payments" {
+//!     target "payments.internal:8443"
+//!     tls {
+//!         spiffe {
+//!             socket "/run/spire/sockets/agent.sock"
+//!             allowed-id "spiffe://example.org/ns/prod/sa/payments"
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! # Pingora Integration
+//!
+//! Pingora's rustls connector only verifies hostnames and cannot take a custom
+//! certificate verifier. SPIFFE upstream peers are therefore plaintext peers
+//! whose custom L4 connector is a [`SpiffeConnector`]: it performs the TLS
+//! handshake itself, with [`SpiffeVerifier`] checking the chain and SPIFFE ID
+//! during the handshake, and bridges the established TLS stream to Pingora
+//! over a socket pair. A peer that fails verification never yields a
+//! connection.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use pingora_core::connectors::L4Connect;
+use pingora_core::protocols::l4::stream::Stream as L4Stream;
+use pingora_core::{Error, ErrorType};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::{verify_server_cert_signed_by_trust_anchor, ResolvesClientCert};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{
+    CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName, UnixTime,
+};
+use rustls::server::ParsedCertificate;
+use rustls::sign::CertifiedKey;
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use sha2::{Digest, Sha256};
+use tokio::net::{TcpStream, UnixStream};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tonic::transport::{Endpoint, Uri};
+use tracing::{debug, info, trace, warn};
+use x509_parser::prelude::*;
+
+use sentinel_config::SpiffeConfig;
+
+use crate::client_cert::glob_matches;
+use crate::tls::TlsError;
+
+/// SPIFFE Workload API definitions generated from proto/workload.proto
+pub mod proto {
+    tonic::include_proto!("_");
+}
+
+use proto::spiffe_workload_api_client::SpiffeWorkloadApiClient;
+
+/// Metadata header required by every Workload API call
+pub const WORKLOAD_API_HEADER: &str = "workload.spiffe.io";
+
+/// Maximum delay between Workload API reconnect attempts
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+// ============================================================================
+// SPIFFE IDs
+// ============================================================================
+
+/// Parsed SPIFFE ID (`spiffe://<trust-domain>/<path>`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpiffeId {
+    trust_domain: String,
+    path: String,
+}
+
+impl SpiffeId {
+    /// Parse a SPIFFE ID URI
+    pub fn parse(id: &str) -> Result<Self, TlsError> {
+        let rest = id
+            .strip_prefix("spiffe://")
+            .ok_or_else(|| TlsError::Spiffe(format!("'{}' is not a spiffe:// URI", id)))?;
+
+        let (trust_domain, path) = match rest.find('/') {
+            Some(pos) => (&rest[..pos], &rest[pos..]),
+            None => (rest, ""),
+        };
+
+        if trust_domain.is_empty() {
+            return Err(TlsError::Spiffe(format!(
+                "'{}' has an empty trust domain",
+                id
+            )));
+        }
+
+        Ok(Self {
+            trust_domain: trust_domain.to_lowercase(),
+            path: path.to_string(),
+        })
+    }
+
+    /// Trust domain name (e.g. `example.org`)
+    pub fn trust_domain(&self) -> &str {
+        &self.trust_domain
+    }
+
+    /// Path component (e.g. `/ns/prod/sa/payments`)
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Extract the SPIFFE ID from a certificate's URI SAN
+    ///
+    /// An SVID must carry exactly one `spiffe://` URI SAN.
+    pub fn from_certificate(der: &[u8]) -> Result<Self, TlsError> {
+        let (_, cert) = X509Certificate::from_der(der)
+            .map_err(|e| TlsError::InvalidCertificate(e.to_string()))?;
+
+        let mut ids = Vec::new();
+        if let Ok(Some(san)) = cert.subject_alternative_name() {
+            for name in &san.value.general_names {
+                if let GeneralName::URI(uri) = name {
+                    if uri.starts_with("spiffe://") {
+                        ids.push(*uri);
+                    }
+                }
+            }
+        }
+
+        match ids.as_slice() {
+            [id] => Self::parse(id),
+            [] => Err(TlsError::Spiffe(
+                "certificate has no SPIFFE ID URI SAN".to_string(),
+            )),
+            _ => Err(TlsError::Spiffe(
+                "certificate has more than one SPIFFE ID URI SAN".to_string(),
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for SpiffeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "spiffe://{}{}", self.trust_domain, self.path)
+    }
+}
+
+// ============================================================================
+// X.509 Context
+// ============================================================================
+
+/// X.509 SVID issued to this workload
+pub struct X509Svid {
+    /// SPIFFE ID of the SVID
+    pub spiffe_id: SpiffeId,
+    /// Certificate chain (leaf first)
+    pub cert_chain: Vec<CertificateDer<'static>>,
+}
+
+impl std::fmt::Debug for X509Svid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("X509Svid")
+            .field("spiffe_id", &self.spiffe_id)
+            .field("chain_len", &self.cert_chain.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Snapshot of the workload's SVID and trust bundles
+pub struct X509Context {
+    /// Default SVID (first returned by the Workload API)
+    pub svid: X509Svid,
+    /// Trust bundles keyed by trust domain name (own and federated)
+    pub bundles: HashMap<String, Vec<CertificateDer<'static>>>,
+    /// Signing key for the SVID
+    certified_key: Arc<CertifiedKey>,
+    /// SVID in Pingora's client certificate format
+    cert_key: Arc<pingora_core::utils::tls::CertKey>,
+}
+
+impl std::fmt::Debug for X509Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("X509Context")
+            .field("svid", &self.svid)
+            .field("trust_domains", &self.bundles.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl X509Context {
+    /// Build a context from a Workload API response
+    pub fn from_response(
+        response: proto::X509svidResponse,
+        provider: &CryptoProvider,
+    ) -> Result<Self, TlsError> {
+        let svid = response
+            .svids
+            .into_iter()
+            .next()
+            .ok_or_else(|| TlsError::Spiffe("Workload API returned no SVIDs".to_string()))?;
+
+        let spiffe_id = SpiffeId::parse(&svid.spiffe_id)?;
+        let cert_chain = split_der_certificates(&svid.x509_svid)?;
+        if cert_chain.is_empty() {
+            return Err(TlsError::Spiffe(format!(
+                "SVID for {} has an empty certificate chain",
+                spiffe_id
+            )));
+        }
+
+        let mut bundles = HashMap::new();
+        bundles.insert(
+            spiffe_id.trust_domain().to_string(),
+            split_der_certificates(&svid.bundle)?,
+        );
+        for (trust_domain, bundle) in &response.federated_bundles {
+            let name = trust_domain
+                .strip_prefix("spiffe://")
+                .unwrap_or(trust_domain)
+                .to_lowercase();
+            bundles.insert(name, split_der_certificates(bundle)?);
+        }
+
+        let signing_key = provider
+            .key_provider
+            .load_private_key(PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(
+                svid.x509_svid_key.clone(),
+            )))
+            .map_err(|e| TlsError::KeyLoad(format!("SVID private key: {}", e)))?;
+        let certified_key = Arc::new(CertifiedKey::new(cert_chain.clone(), signing_key));
+
+        let cert_key = Arc::new(pingora_core::utils::tls::CertKey::new(
+            cert_chain.iter().map(|c| c.to_vec()).collect(),
+            svid.x509_svid_key,
+        ));
+
+        Ok(Self {
+            svid: X509Svid {
+                spiffe_id,
+                cert_chain,
+            },
+            bundles,
+            certified_key,
+            cert_key,
+        })
+    }
+
+    /// Trust bundle for a trust domain
+    pub fn bundle(&self, trust_domain: &str) -> Option<&[CertificateDer<'static>]> {
+        self.bundles.get(trust_domain).map(|b| b.as_slice())
+    }
+
+    /// SHA-256 fingerprint of the SVID leaf certificate
+    pub fn svid_fingerprint(&self) -> String {
+        hex::encode(Sha256::digest(&self.svid.cert_chain[0]))
+    }
+}
+
+/// Split concatenated ASN.1 DER certificates
+fn split_der_certificates(mut bytes: &[u8]) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let mut certs = Vec::new();
+    while !bytes.is_empty() {
+        let (rest, _) = X509Certificate::from_der(bytes)
+            .map_err(|e| TlsError::InvalidCertificate(format!("SPIFFE DER bundle: {}", e)))?;
+        let len = bytes.len() - rest.len();
+        certs.push(CertificateDer::from(bytes[..len].to_vec()));
+        bytes = rest;
+    }
+    Ok(certs)
+}
+
+// ============================================================================
+// Workload API Source
+// ============================================================================
+
+/// Live SVID and trust bundle source backed by the SPIFFE Workload API
+///
+/// A background task keeps a `FetchX509SVID` stream open and reconnects with
+/// exponential backoff. The latest context is always available via
+/// [`current`](Self::current).
+pub struct SpiffeSource {
+    socket: PathBuf,
+    allowed_ids: Vec<String>,
+    provider: Arc<CryptoProvider>,
+    context: watch::Sender<Option<Arc<X509Context>>>,
+    /// Client config used by [`SpiffeConnector`] (HTTP/1.1 over the bridge)
+    upstream_config: OnceLock<Arc<ClientConfig>>,
+    task: parking_lot::Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SpiffeSource {
+    /// Connect to the Workload API and wait for the first SVID
+    pub async fn connect(config: &SpiffeConfig) -> Result<Arc<Self>, TlsError> {
+        let source = Arc::new(Self {
+            socket: config.socket.clone(),
+            allowed_ids: config.allowed_ids.clone(),
+            provider: crate::tls::default_crypto_provider(),
+            context: watch::channel(None).0,
+            upstream_config: OnceLock::new(),
+            task: parking_lot::Mutex::new(None),
+        });
+
+        let mut updates = source.subscribe();
+        let task = tokio::spawn(Self::run(
+            Arc::downgrade(&source),
+            source.socket.clone(),
+            source.provider.clone(),
+        ));
+        *source.task.lock() = Some(task);
+
+        let timeout = Duration::from_secs(config.fetch_timeout_secs);
+        tokio::time::timeout(timeout, updates.wait_for(|ctx| ctx.is_some()))
+            .await
+            .map_err(|_| {
+                TlsError::Spiffe(format!(
+                    "no SVID received from {} within {:?}",
+                    config.socket.display(),
+                    timeout
+                ))
+            })?
+            .map_err(|_| TlsError::Spiffe("Workload API source stopped".to_string()))?;
+
+        info!(
+            socket = %config.socket.display(),
+            spiffe_id = %source.current().map(|c| c.svid.spiffe_id.to_string()).unwrap_or_default(),
+            "SPIFFE workload identity initialized"
+        );
+
+        Ok(source)
+    }
+
+    /// Current SVID and bundles
+    pub fn current(&self) -> Option<Arc<X509Context>> {
+        self.context.borrow().clone()
+    }
+
+    /// Subscribe to context updates (rotations)
+    pub fn subscribe(&self) -> watch::Receiver<Option<Arc<X509Context>>> {
+        self.context.subscribe()
+    }
+
+    /// Current SVID as a Pingora client certificate
+    pub fn cert_key(&self) -> Option<Arc<pingora_core::utils::tls::CertKey>> {
+        self.current().map(|ctx| ctx.cert_key.clone())
+    }
+
+    /// Allowed upstream SPIFFE ID patterns
+    pub fn allowed_ids(&self) -> &[String] {
+        &self.allowed_ids
+    }
+
+    /// Background loop: stream updates, reconnect with backoff
+    ///
+    /// Only a weak reference is held so dropping the source stops the loop.
+    async fn run(source: Weak<Self>, socket: PathBuf, provider: Arc<CryptoProvider>) {
+        let mut backoff = Duration::from_millis(500);
+
+        loop {
+            match Self::stream_updates(&source, &socket, &provider).await {
+                Ok(()) => {
+                    debug!(socket = %socket.display(), "Workload API stream ended");
+                    backoff = Duration::from_millis(500);
+                }
+                Err(e) => {
+                    warn!(
+                        socket = %socket.display(),
+                        error = %e,
+                        retry_in_ms = backoff.as_millis() as u64,
+                        "Workload API stream failed"
+                    );
+                }
+            }
+
+            if source.strong_count() == 0 {
+                return;
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+        }
+    }
+
+    /// Open a `FetchX509SVID` stream and apply updates until it ends
+    async fn stream_updates(
+        source: &Weak<Self>,
+        socket: &Path,
+        provider: &CryptoProvider,
+    ) -> Result<(), TlsError> {
+        let mut client = connect_workload_api(socket).await?;
+
+        let mut request = tonic::Request::new(proto::X509svidRequest {});
+        request.metadata_mut().insert(
+            WORKLOAD_API_HEADER,
+            tonic::metadata::MetadataValue::from_static("true"),
+        );
+
+        let mut stream = client
+            .fetch_x509svid(request)
+            .await
+            .map_err(|e| TlsError::Spiffe(format!("FetchX509SVID: {}", e.message())))?
+            .into_inner();
+
+        while let Some(response) = stream
+            .message()
+            .await
+            .map_err(|e| TlsError::Spiffe(format!("FetchX509SVID stream: {}", e.message())))?
+        {
+            let Some(this) = source.upgrade() else {
+                return Ok(());
+            };
+            match X509Context::from_response(response, provider) {
+                Ok(ctx) => this.update(ctx),
+                Err(e) => warn!(error = %e, "Ignoring invalid Workload API response"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replace the current context
+    fn update(&self, ctx: X509Context) {
+        info!(
+            spiffe_id = %ctx.svid.spiffe_id,
+            fingerprint = %ctx.svid_fingerprint(),
+            trust_domains = ctx.bundles.len(),
+            "Received SPIFFE X.509 SVID"
+        );
+        self.context.send_replace(Some(Arc::new(ctx)));
+    }
+
+    /// rustls client config that presents the live SVID and verifies peers by SPIFFE ID
+    pub fn client_config(self: &Arc<Self>) -> ClientConfig {
+        ClientConfig::builder_with_provider(self.provider.clone())
+            .with_safe_default_protocol_versions()
+            .expect("default provider supports safe protocol versions")
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(SpiffeVerifier {
+                source: self.clone(),
+            }))
+            .with_client_cert_resolver(Arc::new(SpiffeCertResolver {
+                source: self.clone(),
+            }))
+    }
+
+    /// L4 connector for an upstream peer, sending `server_name` as SNI
+    pub fn connector(self: &Arc<Self>, server_name: &str) -> Result<SpiffeConnector, TlsError> {
+        let server_name = ServerName::try_from(server_name.to_string()).map_err(|e| {
+            TlsError::Spiffe(format!("invalid server name '{}': {}", server_name, e))
+        })?;
+        Ok(SpiffeConnector {
+            source: self.clone(),
+            server_name,
+        })
+    }
+
+    /// Client config for bridged upstream connections
+    ///
+    /// Pingora speaks plaintext HTTP/1.1 over the bridge, so only
+    /// `http/1.1` is offered via ALPN.
+    fn upstream_config(self: &Arc<Self>) -> Arc<ClientConfig> {
+        self.upstream_config
+            .get_or_init(|| {
+                let mut config = self.client_config();
+                config.alpn_protocols = vec![b"http/1.1".to_vec()];
+                Arc::new(config)
+            })
+            .clone()
+    }
+}
+
+impl Drop for SpiffeSource {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.lock().take() {
+            task.abort();
+        }
+    }
+}
+
+/// Open a gRPC channel to the Workload API over a Unix socket
+async fn connect_workload_api(
+    socket: &Path,
+) -> Result<SpiffeWorkloadApiClient<tonic::transport::Channel>, TlsError> {
+    let path = socket.to_path_buf();
+    // The URI is required by tonic but ignored by the connector
+    let channel = Endpoint::from_static("http://localhost")
+        .connect_with_connector(tower::service_fn(move |_: Uri| {
+            let path = path.clone();
+            async move {
+                let stream = tokio::net::UnixStream::connect(path).await?;
+                Ok::<_, std::io::Error>(hyper_util::rt::TokioIo::new(stream))
+            }
+        }))
+        .await
+        .map_err(|e| TlsError::Spiffe(format!("connect {}: {}", socket.display(), e)))?;
+
+    Ok(SpiffeWorkloadApiClient::new(channel))
+}
+
+// ============================================================================
+// Pingora Connector
+// ============================================================================
+
+/// Upstream L4 connector that verifies the peer by SPIFFE ID in the handshake
+///
+/// Set as the `custom_l4` connector of a plaintext [`HttpPeer`]. Each
+/// connection is a TCP connection plus a rustls handshake using
+/// [`SpiffeSource::client_config`]; Pingora receives one end of a Unix socket
+/// pair and a task copies bytes between the other end and the TLS stream.
+///
+/// [`HttpPeer`]: pingora_core::upstreams::peer::HttpPeer
+#[derive(Debug)]
+pub struct SpiffeConnector {
+    source: Arc<SpiffeSource>,
+    server_name: ServerName<'static>,
+}
+
+#[async_trait]
+impl L4Connect for SpiffeConnector {
+    async fn connect(&self, addr: &SocketAddr) -> pingora_core::Result<L4Stream> {
+        let tcp = TcpStream::connect(addr)
+            .await
+            .map_err(|e| Error::because(ErrorType::ConnectError, format!("connect {}", addr), e))?;
+        let mut tls = tokio_rustls::TlsConnector::from(self.source.upstream_config())
+            .connect(self.server_name.clone(), tcp)
+            .await
+            .map_err(|e| {
+                Error::because(
+                    ErrorType::TLSHandshakeFailure,
+                    format!("SPIFFE handshake with {}", addr),
+                    e,
+                )
+            })?;
+
+        let (local, mut bridged) = UnixStream::pair()
+            .map_err(|e| Error::because(ErrorType::SocketError, "SPIFFE bridge socket pair", e))?;
+        let addr = *addr;
+        tokio::spawn(async move {
+            if let Err(e) = tokio::io::copy_bidirectional(&mut bridged, &mut tls).await {
+                trace!(peer_address = %addr, error = %e, "SPIFFE upstream bridge closed");
+            }
+        });
+
+        trace!(peer_address = %addr, "SPIFFE upstream connection verified");
+        Ok(local.into())
+    }
+}
+
+// ============================================================================
+// rustls Integration
+// ============================================================================
+
+/// Server certificate verifier that checks SPIFFE IDs instead of hostnames
+#[derive(Debug)]
+pub struct SpiffeVerifier {
+    source: Arc<SpiffeSource>,
+}
+
+impl std::fmt::Debug for SpiffeSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpiffeSource")
+            .field("socket", &self.socket)
+            .field("allowed_ids", &self.allowed_ids)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SpiffeVerifier {
+    /// Whether a peer SPIFFE ID is acceptable for this workload
+    fn is_allowed(&self, peer: &SpiffeId, ctx: &X509Context) -> bool {
+        if self.source.allowed_ids.is_empty() {
+            return peer.trust_domain() == ctx.svid.spiffe_id.trust_domain();
+        }
+        let id = peer.to_string();
+        self.source
+            .allowed_ids
+            .iter()
+            .any(|pattern| glob_matches(pattern, &id))
+    }
+}
+
+impl ServerCertVerifier for SpiffeVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let ctx = self
+            .source
+            .current()
+            .ok_or_else(|| rustls::Error::General("no SPIFFE trust bundle available".into()))?;
+
+        let peer_id = SpiffeId::from_certificate(end_entity)
+            .map_err(|e| rustls::Error::General(e.to_string()))?;
+
+        let bundle = ctx.bundle(peer_id.trust_domain()).ok_or_else(|| {
+            rustls::Error::General(format!(
+                "no trust bundle for trust domain '{}'",
+                peer_id.trust_domain()
+            ))
+        })?;
+
+        let mut roots = RootCertStore::empty();
+        let (added, _) = roots.add_parsable_certificates(bundle.iter().cloned());
+        if added == 0 {
+            return Err(rustls::Error::General(format!(
+                "trust bundle for '{}' has no usable certificates",
+                peer_id.trust_domain()
+            )));
+        }
+
+        let cert = ParsedCertificate::try_from(end_entity)?;
+        verify_server_cert_signed_by_trust_anchor(
+            &cert,
+            &roots,
+            intermediates,
+            now,
+            self.source.provider.signature_verification_algorithms.all,
+        )?;
+
+        if !self.is_allowed(&peer_id, &ctx) {
+            debug!(peer_id = %peer_id, "Upstream SPIFFE ID not allowed");
+            return Err(rustls::Error::General(format!(
+                "upstream SPIFFE ID {} is not allowed",
+                peer_id
+            )));
+        }
+
+        trace!(peer_id = %peer_id, "Upstream SPIFFE ID verified");
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.source.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.source.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.source
+            .provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Client certificate resolver that always presents the latest SVID
+#[derive(Debug)]
+struct SpiffeCertResolver {
+    source: Arc<SpiffeSource>,
+}
+
+impl ResolvesClientCert for SpiffeCertResolver {
+    fn resolve(
+        &self,
+        _root_hint_subjects: &[&[u8]],
+        _sigschemes: &[SignatureScheme],
+    ) -> Option<Arc<CertifiedKey>> {
+        self.source.current().map(|ctx| ctx.certified_key.clone())
+    }
+
+    fn has_certs(&self) -> bool {
+        self.source.current().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spiffe_id() {
+        let id = SpiffeId::parse("spiffe://Example.org/ns/prod/sa/payments").unwrap();
+        assert_eq!(id.trust_domain(), "example.org");
+        assert_eq!(id.path(), "/ns/prod/sa/payments");
+        assert_eq!(id.to_string(), "spiffe://example.org/ns/prod/sa/payments");
+
+        assert!(SpiffeId::parse("https://example.org/x").is_err());
+        assert!(SpiffeId::parse("spiffe:///x").is_err());
+    }
+
+    #[test]
+    fn test_split_der_certificates() {
+        let pem = std::fs::read(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures/tls/ca.crt"),
+        )
+        .unwrap();
+        let ca = rustls_pemfile::certs(&mut pem.as_slice())
+            .next()
+            .unwrap()
+            .unwrap();
+
+        let mut concatenated = ca.to_vec();
+        concatenated.extend_from_slice(&ca);
+
+        let certs = split_der_certificates(&concatenated).unwrap();
+        assert_eq!(certs.len(), 2);
+        assert_eq!(certs[0].as_ref(), ca.as_ref());
+
+        assert!(split_der_certificates(&[]).unwrap().is_empty());
+        assert!(split_der_certificates(&[0x30, 0x03, 0x01]).is_err());
+    }
+}
@@ -8,6 +8,7 @@
 //! - SNI-based certificate selection
 //! - Wildcard certificate matching (e.g., `*.example.com`)
 //! - Default certificate fallback
+//! - Per-SNI TLS policy (mTLS, versions, cipher suites, ALPN)
 //! - Certificate validation at startup
 //! - mTLS client certificate verification
 //! - Certificate hot-reload on SIGHUP
@@ -41,6 +42,17 @@
 //!
 //!         // OCSP stapling
 //!         ocsp-stapling true
+//!
+//!         // Per-hostname policy: mTLS and TLS 1.3 only for partners
+//!         sni {
+//!             hostnames "partners.example.com"
+//!             cert-file "/etc/certs/partners.crt"
+//!             key-file "/etc/certs/partners.key"
+//!             ca-file "/etc/certs/partners-ca.crt"
+//!             client-auth true
+//!             min-version "1.3"
+//!             alpn "h2"
+//!         }
 //!     }
 //! }
 //! ```
@@ -48,19 +60,21 @@
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use parking_lot::RwLock;
 use rustls::client::ClientConfig;
 use rustls::pki_types::CertificateDer;
+use rustls::crypto::CryptoProvider;
 use rustls::server::{ClientHello, ResolvesServerCert};
 use rustls::sign::CertifiedKey;
-use rustls::{RootCertStore, ServerConfig};
+use rustls::{RootCertStore, ServerConfig, SupportedProtocolVersion};
 use tracing::{debug, error, info, trace, warn};
 
-use sentinel_config::{TlsConfig, UpstreamTlsConfig};
+use sentinel_common::types::TlsVersion;
+use sentinel_config::{SniCertificate, TlsConfig, UpstreamTlsConfig};
 
 /// Error type for TLS operations
 #[derive(Debug)]
@@ -77,6 +91,8 @@ pub enum TlsError {
     InvalidCertificate(String),
     /// OCSP fetch error
     OcspFetch(String),
+    /// SPIFFE Workload API or SVID verification error
+    Spiffe(String),
 }
 
 impl std::fmt::Display for TlsError {
@@ -88,28 +104,131 @@ impl std::fmt::Display for TlsError {
             TlsError::CertKeyMismatch(e) => write!(f, "Certificate/key mismatch: {}", e),
             TlsError::InvalidCertificate(e) => write!(f, "Invalid certificate: {}", e),
             TlsError::OcspFetch(e) => write!(f, "Failed to fetch OCSP response: {}", e),
+            TlsError::Spiffe(e) => write!(f, "SPIFFE workload identity error: {}", e),
         }
     }
 }
 
 impl std::error::Error for TlsError {}
 
-/// SNI-aware certificate resolver
+/// Effective TLS policy for a listener or a set of SNI hostnames
+///
+/// Built from the listener-level [`TlsConfig`] with any per-hostname
+/// overrides from an [`SniCertificate`] layered on top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsPolicy {
+    /// Require client certificates (mTLS)
+    pub client_auth: bool,
+    /// CA certificate file for client verification
+    pub ca_file: Option<PathBuf>,
+    /// Minimum TLS version
+    pub min_version: TlsVersion,
+    /// Maximum TLS version
+    pub max_version: Option<TlsVersion>,
+    /// Cipher suites (empty = provider defaults)
+    pub cipher_suites: Vec<String>,
+    /// ALPN protocols offered to clients, in preference order
+    pub alpn_protocols: Vec<Vec<u8>>,
+}
+
+impl TlsPolicy {
+    /// Policy applied to the listener as a whole
+    pub fn from_listener(config: &TlsConfig) -> Self {
+        Self {
+            client_auth: config.client_auth,
+            ca_file: config.ca_file.clone(),
+            min_version: config.min_version,
+            max_version: config.max_version,
+            cipher_suites: config.cipher_suites.clone(),
+            alpn_protocols: default_alpn_protocols(),
+        }
+    }
+
+    /// Policy for an SNI entry, inheriting unset fields from the listener
+    pub fn for_sni(config: &TlsConfig, sni: &SniCertificate) -> Self {
+        let listener = Self::from_listener(config);
+        Self {
+            client_auth: sni.client_auth.unwrap_or(listener.client_auth),
+            ca_file: sni.ca_file.clone().or(listener.ca_file),
+            min_version: sni.min_version.unwrap_or(listener.min_version),
+            max_version: sni.max_version.or(listener.max_version),
+            cipher_suites: if sni.cipher_suites.is_empty() {
+                listener.cipher_suites
+            } else {
+                sni.cipher_suites.clone()
+            },
+            alpn_protocols: if sni.alpn_protocols.is_empty() {
+                listener.alpn_protocols
+            } else {
+                sni.alpn_protocols
+                    .iter()
+                    .map(|p| p.as_bytes().to_vec())
+                    .collect()
+            },
+        }
+    }
+}
+
+/// Default ALPN protocols (HTTP/2 preferred, HTTP/1.1 fallback)
+fn default_alpn_protocols() -> Vec<Vec<u8>> {
+    vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+}
+
+/// Certificate and full server configuration for a set of hostnames
+#[derive(Debug, Clone)]
+struct SniEntry {
+    cert: Arc<CertifiedKey>,
+    server_config: Arc<ServerConfig>,
+}
+
+impl SniEntry {
+    fn new(cert: CertifiedKey, policy: &TlsPolicy) -> Result<Self, TlsError> {
+        let cert = Arc::new(cert);
+        let server_config =
+            build_policy_server_config(policy, Arc::new(FixedCertResolver(cert.clone())))?;
+        Ok(Self {
+            cert,
+            server_config: Arc::new(server_config),
+        })
+    }
+}
+
+/// Resolver that always presents the same certificate
+///
+/// Used for per-hostname server configurations, where the hostname has
+/// already been matched before the configuration was selected.
+#[derive(Debug)]
+struct FixedCertResolver(Arc<CertifiedKey>);
+
+impl ResolvesServerCert for FixedCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.0.clone())
+    }
+}
+
+/// SNI-aware certificate and policy resolver
 ///
 /// Resolves certificates based on the Server Name Indication (SNI) extension
 /// in the TLS handshake. Supports:
 /// - Exact hostname matches
 /// - Wildcard certificates (e.g., `*.example.com`)
 /// - Default certificate fallback
+///
+/// Each SNI entry also carries a complete rustls [`ServerConfig`] built from
+/// its effective [`TlsPolicy`], so client authentication, protocol versions,
+/// cipher suites and ALPN can differ per hostname on the same listener. Use
+/// [`SniResolver::resolve_server_config`] (or [`SniResolver::select_server_config`]
+/// with a rustls [`Acceptor`](rustls::server::Acceptor)) to pick the configuration
+/// once the ClientHello has been read.
 #[derive(Debug)]
 pub struct SniResolver {
-    /// Default certificate (used when no SNI match)
-    default_cert: Arc<CertifiedKey>,
-    /// SNI hostname to certificate mapping
-    /// Key is lowercase hostname, value is the certified key
-    sni_certs: HashMap<String, Arc<CertifiedKey>>,
-    /// Wildcard certificates (e.g., "*.example.com" -> cert)
-    wildcard_certs: HashMap<String, Arc<CertifiedKey>>,
+    /// Default certificate and config (used when no SNI match)
+    default: SniEntry,
+    /// SNI hostname to entry mapping
+    /// Key is lowercase hostname
+    sni_certs: HashMap<String, SniEntry>,
+    /// Wildcard entries (e.g., "*.example.com" -> entry)
+    wildcard_certs: HashMap<String, SniEntry>,
 }
 
 impl SniResolver {
@@ -127,6 +246,7 @@ impl SniResolver {
 
         // Load default certificate
         let default_cert = load_certified_key(cert_file, key_file)?;
+        let default = SniEntry::new(default_cert, &TlsPolicy::from_listener(config))?;
 
         info!(
             cert_file = %cert_file.display(),
@@ -139,7 +259,22 @@ impl SniResolver {
         // Load SNI certificates
         for sni_config in &config.additional_certs {
             let cert = load_certified_key(&sni_config.cert_file, &sni_config.key_file)?;
-            let cert = Arc::new(cert);
+            let policy = TlsPolicy::for_sni(config, sni_config);
+            if sni_config.client_auth == Some(true) && policy.ca_file.is_none() {
+                return Err(sni_client_auth_without_ca(sni_config));
+            }
+            let entry = SniEntry::new(cert, &policy)?;
+
+            if sni_config.has_policy_overrides() {
+                debug!(
+                    hostnames = ?sni_config.hostnames,
+                    client_auth = policy.client_auth,
+                    min_version = ?policy.min_version,
+                    max_version = ?policy.max_version,
+                    cipher_suites = policy.cipher_suites.len(),
+                    "Applied per-SNI TLS policy overrides"
+                );
+            }
 
             for hostname in &sni_config.hostnames {
                 let hostname_lower = hostname.to_lowercase();
@@ -147,7 +282,7 @@ impl SniResolver {
                 if hostname_lower.starts_with("*.") {
                     // Wildcard certificate
                     let domain = hostname_lower.strip_prefix("*.").unwrap().to_string();
-                    wildcard_certs.insert(domain.clone(), cert.clone());
+                    wildcard_certs.insert(domain.clone(), entry.clone());
                     debug!(
                         pattern = %hostname,
                         domain = %domain,
@@ -156,7 +291,7 @@ impl SniResolver {
                     );
                 } else {
                     // Exact hostname match
-                    sni_certs.insert(hostname_lower.clone(), cert.clone());
+                    sni_certs.insert(hostname_lower.clone(), entry.clone());
                     debug!(
                         hostname = %hostname_lower,
                         cert_file = %sni_config.cert_file.display(),
@@ -173,7 +308,7 @@ impl SniResolver {
         );
 
         Ok(Self {
-            default_cert: Arc::new(default_cert),
+            default,
             sni_certs,
             wildcard_certs,
         })
@@ -184,17 +319,34 @@ impl SniResolver {
     /// This is the core resolution logic. For the rustls trait implementation,
     /// see `ResolvesServerCert`.
     pub fn resolve(&self, server_name: Option<&str>) -> Arc<CertifiedKey> {
+        self.lookup(server_name).cert.clone()
+    }
+
+    /// Resolve the full server configuration for a given server name
+    ///
+    /// The returned configuration carries the certificate and the effective
+    /// TLS policy (client auth, versions, cipher suites, ALPN) for the name.
+    pub fn resolve_server_config(&self, server_name: Option<&str>) -> Arc<ServerConfig> {
+        self.lookup(server_name).server_config.clone()
+    }
+
+    /// Select the server configuration for a ClientHello read by a rustls `Acceptor`
+    pub fn select_server_config(&self, accepted: &rustls::server::Accepted) -> Arc<ServerConfig> {
+        self.resolve_server_config(accepted.client_hello().server_name())
+    }
+
+    fn lookup(&self, server_name: Option<&str>) -> &SniEntry {
         let Some(name) = server_name else {
             debug!("No SNI provided, using default certificate");
-            return self.default_cert.clone();
+            return &self.default;
         };
 
         let name_lower = name.to_lowercase();
 
         // Try exact match first
-        if let Some(cert) = self.sni_certs.get(&name_lower) {
+        if let Some(entry) = self.sni_certs.get(&name_lower) {
             debug!(hostname = %name_lower, "SNI exact match found");
-            return cert.clone();
+            return entry;
         }
 
         // Try wildcard match
@@ -202,13 +354,13 @@ impl SniResolver {
         let parts: Vec<&str> = name_lower.split('.').collect();
         for i in 1..parts.len() {
             let domain = parts[i..].join(".");
-            if let Some(cert) = self.wildcard_certs.get(&domain) {
+            if let Some(entry) = self.wildcard_certs.get(&domain) {
                 debug!(
                     hostname = %name_lower,
                     wildcard_domain = %domain,
                     "SNI wildcard match found"
                 );
-                return cert.clone();
+                return entry;
             }
         }
 
@@ -216,7 +368,7 @@ impl SniResolver {
             hostname = %name_lower,
             "No SNI match found, using default certificate"
         );
-        self.default_cert.clone()
+        &self.default
     }
 }
 
@@ -319,6 +471,11 @@ impl HotReloadableSniResolver {
     pub fn resolve(&self, server_name: Option<&str>) -> Arc<CertifiedKey> {
         self.inner.read().resolve(server_name)
     }
+
+    /// Resolve the full server configuration for a given server name
+    pub fn resolve_server_config(&self, server_name: Option<&str>) -> Arc<ServerConfig> {
+        self.inner.read().resolve_server_config(server_name)
+    }
 }
 
 impl ResolvesServerCert for HotReloadableSniResolver {
@@ -1056,6 +1213,28 @@ pub fn validate_upstream_tls_config(config: &UpstreamTlsConfig) -> Result<(), Tl
         ));
     }
 
+    // SPIFFE supplies both the client identity and the trust roots
+    if let Some(spiffe) = &config.spiffe {
+        if config.client_cert.is_some() || config.ca_cert.is_some() {
+            return Err(TlsError::ConfigBuild(
+                "spiffe cannot be combined with client_cert or ca_cert".to_string(),
+            ));
+        }
+        if config.insecure_skip_verify {
+            return Err(TlsError::ConfigBuild(
+                "spiffe cannot be combined with insecure_skip_verify".to_string(),
+            ));
+        }
+        for id in &spiffe.allowed_ids {
+            if !id.starts_with("spiffe://") {
+                return Err(TlsError::ConfigBuild(format!(
+                    "spiffe allowed-id '{}' must start with spiffe://",
+                    id
+                )));
+            }
+        }
+    }
+
     Ok(())
 }
 
@@ -1096,9 +1275,7 @@ fn load_certified_key(cert_path: &Path, key_path: &Path) -> Result<CertifiedKey,
         })?;
 
     // Create signing key using the default crypto provider
-    let provider = rustls::crypto::CryptoProvider::get_default()
-        .cloned()
-        .unwrap_or_else(|| Arc::new(rustls::crypto::aws_lc_rs::default_provider()));
+    let provider = default_crypto_provider();
 
     let signing_key = provider
         .key_provider
@@ -1142,46 +1319,142 @@ pub fn load_client_ca(ca_path: &Path) -> Result<RootCertStore, TlsError> {
     Ok(root_store)
 }
 
-/// Build a TLS ServerConfig from our configuration
-pub fn build_server_config(config: &TlsConfig) -> Result<ServerConfig, TlsError> {
-    let resolver = SniResolver::from_config(config)?;
+/// Get the process-wide crypto provider, falling back to aws-lc-rs
+pub(crate) fn default_crypto_provider() -> Arc<CryptoProvider> {
+    CryptoProvider::get_default()
+        .cloned()
+        .unwrap_or_else(|| Arc::new(rustls::crypto::aws_lc_rs::default_provider()))
+}
+
+/// Build a crypto provider restricted to the named cipher suites
+///
+/// Names are matched case-insensitively against the IANA names used by
+/// rustls (e.g. `TLS13_AES_256_GCM_SHA384`,
+/// `TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256`). An empty list keeps the
+/// provider defaults.
+fn crypto_provider_for(cipher_suites: &[String]) -> Result<Arc<CryptoProvider>, TlsError> {
+    let provider = default_crypto_provider();
+    if cipher_suites.is_empty() {
+        return Ok(provider);
+    }
+
+    for name in cipher_suites {
+        let known = provider
+            .cipher_suites
+            .iter()
+            .any(|cs| format!("{:?}", cs.suite()).eq_ignore_ascii_case(name));
+        if !known {
+            return Err(TlsError::ConfigBuild(format!(
+                "Unsupported cipher suite: {}",
+                name
+            )));
+        }
+    }
+
+    let mut provider = (*provider).clone();
+    provider.cipher_suites.retain(|cs| {
+        let suite = format!("{:?}", cs.suite());
+        cipher_suites
+            .iter()
+            .any(|name| suite.eq_ignore_ascii_case(name))
+    });
+
+    Ok(Arc::new(provider))
+}
+
+/// Map configured min/max versions to rustls protocol versions
+fn protocol_versions(
+    min: TlsVersion,
+    max: Option<TlsVersion>,
+) -> Result<&'static [&'static SupportedProtocolVersion], TlsError> {
+    static TLS12_AND_13: &[&SupportedProtocolVersion] =
+        &[&rustls::version::TLS13, &rustls::version::TLS12];
+    static TLS12_ONLY: &[&SupportedProtocolVersion] = &[&rustls::version::TLS12];
+    static TLS13_ONLY: &[&SupportedProtocolVersion] = &[&rustls::version::TLS13];
+
+    match (min, max) {
+        (TlsVersion::Tls13, Some(TlsVersion::Tls12)) => Err(TlsError::ConfigBuild(
+            "min_version TLS1.3 is greater than max_version TLS1.2".to_string(),
+        )),
+        (TlsVersion::Tls13, _) => Ok(TLS13_ONLY),
+        (TlsVersion::Tls12, Some(TlsVersion::Tls12)) => Ok(TLS12_ONLY),
+        (TlsVersion::Tls12, _) => Ok(TLS12_AND_13),
+    }
+}
+
+/// Build a rustls ServerConfig for a policy and certificate resolver
+fn build_policy_server_config(
+    policy: &TlsPolicy,
+    resolver: Arc<dyn ResolvesServerCert>,
+) -> Result<ServerConfig, TlsError> {
+    let provider = crypto_provider_for(&policy.cipher_suites)?;
+    let versions = protocol_versions(policy.min_version, policy.max_version)?;
 
-    let builder = ServerConfig::builder();
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_protocol_versions(versions)
+        .map_err(|e| TlsError::ConfigBuild(format!("Invalid TLS versions/ciphers: {}", e)))?;
 
     // Configure client authentication (mTLS)
-    let server_config = if config.client_auth {
-        if let Some(ca_path) = &config.ca_file {
+    let mut server_config = if policy.client_auth {
+        if let Some(ca_path) = &policy.ca_file {
             let root_store = load_client_ca(ca_path)?;
-            let verifier = rustls::server::WebPkiClientVerifier::builder(Arc::new(root_store))
-                .build()
-                .map_err(|e| {
-                    TlsError::ConfigBuild(format!("Failed to build client verifier: {}", e))
-                })?;
+            let verifier = rustls::server::WebPkiClientVerifier::builder_with_provider(
+                Arc::new(root_store),
+                provider,
+            )
+            .build()
+            .map_err(|e| {
+                TlsError::ConfigBuild(format!("Failed to build client verifier: {}", e))
+            })?;
 
-            info!("mTLS enabled: client certificates required");
+            debug!("mTLS enabled: client certificates required");
 
             builder
                 .with_client_cert_verifier(verifier)
-                .with_cert_resolver(Arc::new(resolver))
+                .with_cert_resolver(resolver)
         } else {
             warn!("client_auth enabled but no ca_file specified, disabling client auth");
-            builder
-                .with_no_client_auth()
-                .with_cert_resolver(Arc::new(resolver))
+            builder.with_no_client_auth().with_cert_resolver(resolver)
         }
     } else {
-        builder
-            .with_no_client_auth()
-            .with_cert_resolver(Arc::new(resolver))
+        builder.with_no_client_auth().with_cert_resolver(resolver)
     };
 
-    // Configure ALPN for HTTP/2 support
-    let mut config = server_config;
-    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    server_config.alpn_protocols = policy.alpn_protocols.clone();
+
+    Ok(server_config)
+}
+
+/// Build a TLS ServerConfig from our configuration
+///
+/// The returned configuration applies the listener-level policy and selects
+/// certificates by SNI. Per-hostname policy overrides require selecting a
+/// configuration after reading the ClientHello; see
+/// [`SniResolver::resolve_server_config`].
+pub fn build_server_config(config: &TlsConfig) -> Result<ServerConfig, TlsError> {
+    let resolver = SniResolver::from_config(config)?;
+    let policy = TlsPolicy::from_listener(config);
+
+    if policy.client_auth && policy.ca_file.is_some() {
+        info!("mTLS enabled: client certificates required");
+    }
+
+    let server_config = build_policy_server_config(&policy, Arc::new(resolver))?;
 
     debug!("TLS configuration built successfully");
 
-    Ok(config)
+    Ok(server_config)
+}
+
+/// Error for an SNI entry that requires client certificates without a CA
+///
+/// Unlike the listener-level setting, an SNI entry that turns on mTLS is
+/// never silently downgraded to no client authentication.
+fn sni_client_auth_without_ca(sni: &SniCertificate) -> TlsError {
+    TlsError::ConfigBuild(format!(
+        "SNI certificate {:?} requires client certificates but no ca-file is configured",
+        sni.hostnames
+    ))
 }
 
 /// Validate TLS configuration files exist and are readable
@@ -1229,6 +1502,17 @@ pub fn validate_tls_config(config: &TlsConfig) -> Result<(), TlsError> {
                 sni.key_file.display()
             )));
         }
+        if let Some(ca_path) = &sni.ca_file {
+            if !ca_path.exists() {
+                return Err(TlsError::CertificateLoad(format!(
+                    "SNI CA certificate file not found: {}",
+                    ca_path.display()
+                )));
+            }
+        }
+        if sni.client_auth == Some(true) && sni.ca_file.is_none() && config.ca_file.is_none() {
+            return Err(sni_client_auth_without_ca(sni));
+        }
     }
 
     // Check CA file if mTLS enabled
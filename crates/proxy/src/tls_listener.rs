@@ -0,0 +1,351 @@
+//! HTTPS listener with per-SNI TLS policy.
+//!
+//! Pingora's rustls listener builds one `ServerConfig` from a certificate and
+//! key path, so it cannot apply the per-hostname policy (mTLS, protocol
+//! versions, cipher suites, ALPN) carried by [`SniResolver`] entries. This
+//! listener reads the ClientHello first, selects the hostname's
+//! configuration from a [`HotReloadableSniResolver`], completes the handshake
+//! with it and then hands the decrypted stream to the HTTP application.
+//!
+//! It binds its own socket, which is not handed over on `--upgrade`, so it is
+//! only used for listeners that need it (see [`needs_sni_listener`]).
+//!
+//! [`SniResolver`]: crate::tls::SniResolver
+
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use pingora::apps::ServerApp;
+use pingora::protocols::tls::{SslDigest, ALPN};
+use pingora::protocols::{
+    GetProxyDigest, GetSocketDigest, GetTimingDigest, Peek, Shutdown, SocketDigest, Ssl, Stream,
+    TimingDigest, UniqueID, UniqueIDType,
+};
+use pingora::server::ShutdownWatch;
+use pingora::services::background::BackgroundService;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::LazyConfigAcceptor;
+use tracing::{debug, error, info, trace, warn};
+use x509_parser::prelude::*;
+
+use sentinel_common::types::TlsVersion;
+use sentinel_config::TlsConfig;
+
+use crate::client_cert::ClientCertIdentity;
+use crate::tls::HotReloadableSniResolver;
+
+/// Time allowed for a client to complete the TLS handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest pause after consecutive failed accepts (e.g. out of file descriptors)
+const MAX_ACCEPT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Whether an HTTPS listener needs a [`TlsListenerService`].
+///
+/// Pingora's listener serves a single certificate with default settings, so
+/// per-SNI certificates, client authentication, and protocol version or
+/// cipher suite limits need the SNI-aware listener.
+pub fn needs_sni_listener(config: &TlsConfig) -> bool {
+    !config.additional_certs.is_empty()
+        || config.client_auth
+        || config.min_version != TlsVersion::Tls12
+        || config.max_version.is_some()
+        || !config.cipher_suites.is_empty()
+}
+
+/// HTTP application shared between Pingora's listening service (plain HTTP
+/// listeners) and [`TlsListenerService`]s
+pub struct SharedApp<A>(pub Arc<A>);
+
+#[async_trait]
+impl<A: ServerApp + Send + Sync + 'static> ServerApp for SharedApp<A> {
+    async fn process_new(
+        self: &Arc<Self>,
+        stream: Stream,
+        shutdown: &ShutdownWatch,
+    ) -> Option<Stream> {
+        self.0.process_new(stream, shutdown).await
+    }
+
+    async fn cleanup(&self) {
+        self.0.cleanup().await
+    }
+}
+
+/// HTTPS listener selecting the TLS configuration per SNI hostname
+pub struct TlsListenerService<A> {
+    listener_id: String,
+    address: String,
+    resolver: Arc<HotReloadableSniResolver>,
+    app: Arc<A>,
+}
+
+impl<A> TlsListenerService<A> {
+    /// Create a listener for `address` serving `app`
+    pub fn new(
+        listener_id: &str,
+        address: &str,
+        resolver: Arc<HotReloadableSniResolver>,
+        app: Arc<A>,
+    ) -> Self {
+        Self {
+            listener_id: listener_id.to_string(),
+            address: address.to_string(),
+            resolver,
+            app,
+        }
+    }
+}
+
+#[async_trait]
+impl<A: ServerApp + Send + Sync + 'static> BackgroundService for TlsListenerService<A> {
+    async fn start(&self, mut shutdown: ShutdownWatch) {
+        let listener = match TcpListener::bind(&self.address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!(
+                    listener_id = %self.listener_id,
+                    address = %self.address,
+                    error = %e,
+                    "Failed to bind TLS listener"
+                );
+                return;
+            }
+        };
+        info!(
+            listener_id = %self.listener_id,
+            address = %self.address,
+            "HTTPS listening on: {}", self.address
+        );
+
+        let mut backoff = Duration::ZERO;
+        while !*shutdown.borrow() {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = shutdown.changed() => break,
+            };
+            let tcp = match accepted {
+                Ok((tcp, _)) => {
+                    backoff = Duration::ZERO;
+                    tcp
+                }
+                Err(e) => {
+                    // Back off instead of spinning while the error persists
+                    backoff = (backoff * 2).clamp(Duration::from_millis(5), MAX_ACCEPT_BACKOFF);
+                    warn!(
+                        listener_id = %self.listener_id,
+                        error = %e,
+                        backoff_ms = backoff.as_millis() as u64,
+                        "TCP accept failed"
+                    );
+                    tokio::select! {
+                        _ = tokio::time::sleep(backoff) => continue,
+                        _ = shutdown.changed() => break,
+                    }
+                }
+            };
+
+            let resolver = self.resolver.clone();
+            let app = self.app.clone();
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                let stream = match accept_tls(&resolver, tcp).await {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        debug!(error = %e, "TLS handshake failed");
+                        return;
+                    }
+                };
+                let mut stream: Stream = Box::new(stream);
+                while let Some(reused) = app.process_new(stream, &shutdown).await {
+                    stream = reused;
+                }
+            });
+        }
+
+        info!(listener_id = %self.listener_id, "TLS listener stopped");
+    }
+}
+
+/// Complete the TLS handshake of an accepted connection, using the server
+/// configuration of the hostname requested in the ClientHello
+pub async fn accept_tls(
+    resolver: &HotReloadableSniResolver,
+    tcp: TcpStream,
+) -> std::io::Result<TlsClientStream> {
+    let fd = tcp.as_raw_fd();
+    let handshake = async {
+        let start = LazyConfigAcceptor::new(rustls::server::Acceptor::default(), tcp).await?;
+        let config = resolver.resolve_server_config(start.client_hello().server_name());
+        start.into_stream(config).await
+    };
+    let stream = tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake)
+        .await
+        .map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::TimedOut, "TLS handshake timed out")
+        })??;
+
+    Ok(TlsClientStream::new(stream, fd))
+}
+
+/// Server side of an established TLS connection, as seen by Pingora
+#[derive(Debug)]
+pub struct TlsClientStream {
+    inner: TlsStream<TcpStream>,
+    fd: RawFd,
+    ssl_digest: Option<Arc<SslDigest>>,
+    alpn: Option<ALPN>,
+    socket_digest: Arc<SocketDigest>,
+    established: SystemTime,
+}
+
+impl TlsClientStream {
+    fn new(inner: TlsStream<TcpStream>, fd: RawFd) -> Self {
+        let (_, connection) = inner.get_ref();
+        let alpn = connection
+            .alpn_protocol()
+            .and_then(ALPN::from_wire_selected);
+        let ssl_digest = Some(Arc::new(ssl_digest(connection)));
+
+        trace!(
+            server_name = ?connection.server_name(),
+            alpn = ?alpn,
+            client_cert = connection.peer_certificates().is_some(),
+            "TLS handshake complete"
+        );
+
+        Self {
+            inner,
+            fd,
+            ssl_digest,
+            alpn,
+            socket_digest: Arc::new(SocketDigest::from_raw_fd(fd)),
+            established: SystemTime::now(),
+        }
+    }
+}
+
+/// TLS digest in the form Pingora reports it for its own listeners, with the
+/// client certificate identity attached
+fn ssl_digest(connection: &rustls::ServerConnection) -> SslDigest {
+    let cipher = connection
+        .negotiated_cipher_suite()
+        .and_then(|suite| suite.suite().as_str())
+        .unwrap_or_default();
+    let version = connection
+        .protocol_version()
+        .and_then(|version| version.as_str())
+        .unwrap_or_default();
+
+    let leaf = connection
+        .peer_certificates()
+        .and_then(|certs| certs.first());
+    let mut identity = None;
+    let (organization, serial_number, cert_digest) = match leaf {
+        Some(der) => {
+            let parsed = X509Certificate::from_der(der.as_ref())
+                .ok()
+                .map(|(_, cert)| cert);
+            let organization = parsed.as_ref().and_then(|cert| {
+                cert.subject()
+                    .iter_organization()
+                    .next()
+                    .and_then(|o| o.as_str().ok())
+                    .map(str::to_string)
+            });
+            let serial = parsed.as_ref().map(|cert| hex::encode(cert.raw_serial()));
+            let digest = {
+                use sha2::{Digest, Sha256};
+                Sha256::digest(der.as_ref()).to_vec()
+            };
+            identity = ClientCertIdentity::from_der(der.as_ref())
+                .inspect_err(|e| debug!(error = %e, "Client certificate identity unavailable"))
+                .ok();
+            (organization, serial, digest)
+        }
+        None => (None, None, Vec::new()),
+    };
+
+    // The request path reads the full identity (subject, issuer, SANs, DER)
+    // from the digest; without it a request with a certificate is rejected
+    let mut digest = SslDigest::new(cipher, version, organization, serial_number, cert_digest);
+    if let Some(identity) = identity {
+        digest.extension.set(identity);
+    }
+    digest
+}
+
+impl AsyncRead for TlsClientStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for TlsClientStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[async_trait]
+impl Shutdown for TlsClientStream {
+    async fn shutdown(&mut self) {
+        let _ = AsyncWriteExt::shutdown(&mut self.inner).await;
+    }
+}
+
+impl UniqueID for TlsClientStream {
+    fn id(&self) -> UniqueIDType {
+        self.fd
+    }
+}
+
+impl Ssl for TlsClientStream {
+    fn get_ssl_digest(&self) -> Option<Arc<SslDigest>> {
+        self.ssl_digest.clone()
+    }
+
+    fn selected_alpn_proto(&self) -> Option<ALPN> {
+        self.alpn
+    }
+}
+
+impl GetTimingDigest for TlsClientStream {
+    fn get_timing_digest(&self) -> Vec<Option<TimingDigest>> {
+        vec![Some(TimingDigest {
+            established_ts: self.established,
+        })]
+    }
+}
+
+impl GetProxyDigest for TlsClientStream {}
+
+impl GetSocketDigest for TlsClientStream {
+    fn get_socket_digest(&self) -> Option<Arc<SocketDigest>> {
+        Some(self.socket_digest.clone())
+    }
+}
+
+impl Peek for TlsClientStream {}
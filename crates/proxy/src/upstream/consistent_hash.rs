@@ -429,6 +429,10 @@ impl ConsistentHashBalancer {
 
     /// Extract hash key from request context
     pub fn extract_hash_key(&self, context: &RequestContext) -> Option<String> {
+        if let Some(key) = &context.hash_key {
+            return Some(key.clone());
+        }
+
         let key = match &self.config.hash_key_extractor {
             HashKeyExtractor::ClientIp => context.client_ip.map(|ip| ip.to_string()),
             HashKeyExtractor::Header(name) => context.headers.get(name).cloned(),
@@ -638,6 +642,7 @@ mod tests {
                 headers: HashMap::new(),
                 path: "/".to_string(),
                 method: "GET".to_string(),
+                hash_key: None,
             };
 
             if let Ok(selection) = balancer.select(Some(&context)).await {
@@ -683,6 +688,7 @@ mod tests {
             headers: HashMap::new(),
             path: "/".to_string(),
             method: "GET".to_string(),
+            hash_key: None,
         };
 
         let selection = balancer.select(Some(&context)).await.unwrap();
@@ -696,6 +702,30 @@ mod tests {
         assert_ne!(index, 0);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_explicit_hash_key_overrides_extractor() {
+        let balancer =
+            ConsistentHashBalancer::new(create_test_targets(3), ConsistentHashConfig::default());
+
+        let mut context = RequestContext {
+            client_ip: Some("192.168.1.1:1234".parse().unwrap()),
+            headers: HashMap::new(),
+            path: "/".to_string(),
+            method: "GET".to_string(),
+            hash_key: None,
+        };
+        assert_eq!(
+            balancer.extract_hash_key(&context).as_deref(),
+            Some("192.168.1.1:1234")
+        );
+
+        context.hash_key = Some("tenant-42".to_string());
+        assert_eq!(
+            balancer.extract_hash_key(&context).as_deref(),
+            Some("tenant-42")
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_ring_rebuild_on_health_change() {
         let targets = create_test_targets(3);
@@ -198,6 +198,10 @@ impl MaglevBalancer {
 
     /// Extract hash key from request context
     fn extract_key(&self, context: Option<&RequestContext>) -> String {
+        if let Some(key) = context.and_then(|c| c.hash_key.clone()) {
+            return key;
+        }
+
         match &self.config.key_source {
             MaglevKeySource::ClientIp => context
                 .and_then(|c| c.client_ip.map(|ip| ip.ip().to_string()))
@@ -424,6 +428,7 @@ mod tests {
             headers: HashMap::new(),
             path: "/api/test".to_string(),
             method: "GET".to_string(),
+            hash_key: None,
         };
 
         // Same context should always select same target
@@ -449,6 +454,7 @@ mod tests {
                 headers: HashMap::new(),
                 path: format!("/api/test/{}", i),
                 method: "GET".to_string(),
+                hash_key: None,
             };
             let selection = balancer.select(Some(&context)).await.unwrap();
             original_selections.insert(i, selection.address);
@@ -465,6 +471,7 @@ mod tests {
                 headers: HashMap::new(),
                 path: format!("/api/test/{}", i),
                 method: "GET".to_string(),
+                hash_key: None,
             };
             let selection = balancer.select(Some(&context)).await.unwrap();
             if selection.address != original_selections[&i] {
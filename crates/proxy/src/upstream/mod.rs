@@ -93,6 +93,7 @@ pub mod locality;
 pub mod maglev;
 pub mod p2c;
 pub mod peak_ewma;
+pub mod sigv4;
 pub mod sticky_session;
 pub mod subset;
 pub mod weighted_least_conn;
@@ -107,6 +108,7 @@ pub use locality::{LocalityAwareBalancer, LocalityAwareConfig};
 pub use maglev::{MaglevBalancer, MaglevConfig};
 pub use p2c::{P2cBalancer, P2cConfig};
 pub use peak_ewma::{PeakEwmaBalancer, PeakEwmaConfig};
+pub use sigv4::SigV4Signer;
 pub use sticky_session::{StickySessionBalancer, StickySessionRuntimeConfig};
 pub use subset::{SubsetBalancer, SubsetConfig};
 pub use weighted_least_conn::{WeightedLeastConnBalancer, WeightedLeastConnConfig};
@@ -118,6 +120,9 @@ pub struct RequestContext {
     pub headers: HashMap<String, String>,
     pub path: String,
     pub method: String,
+    /// Explicit key for hash-based balancers, taking precedence over the
+    /// configured key source (set from agent routing overrides)
+    pub hash_key: Option<String>,
 }
 
 /// Load balancer trait for different algorithms
@@ -175,6 +180,43 @@ pub struct TargetSelection {
     pub metadata: HashMap<String, String>,
 }
 
+/// Credential sent to an upstream in place of the client's own
+///
+/// Resolved from the upstream's `secret` config when the pool is created.
+#[derive(Clone)]
+pub struct UpstreamCredential {
+    header: Option<String>,
+    prefix: Option<String>,
+    value: String,
+}
+
+impl UpstreamCredential {
+    /// Header name and value to send. Without an explicit header, Anthropic
+    /// upstreams get `x-api-key` and everything else a bearer token.
+    pub fn header(&self, anthropic: bool) -> (&str, String) {
+        let name = match self.header.as_deref() {
+            Some(name) => name,
+            None if anthropic => "x-api-key",
+            None => "authorization",
+        };
+        let prefix = match self.prefix.as_deref() {
+            Some(prefix) => prefix,
+            None if name.eq_ignore_ascii_case("authorization") => "Bearer ",
+            None => "",
+        };
+        (name, format!("{}{}", prefix, self.value))
+    }
+}
+
+impl std::fmt::Debug for UpstreamCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UpstreamCredential")
+            .field("header", &self.header)
+            .field("value", &"<redacted>")
+            .finish()
+    }
+}
+
 /// Upstream pool managing multiple backend servers
 pub struct UpstreamPool {
     /// Pool identifier
@@ -193,6 +235,12 @@ pub struct UpstreamPool {
     tls_sni: Option<String>,
     /// TLS configuration for upstream mTLS (client certificates)
     tls_config: Option<sentinel_config::UpstreamTlsConfig>,
+    /// SPIFFE workload identity source (replaces static client certificates)
+    spiffe: Option<Arc<crate::spiffe::SpiffeSource>>,
+    /// Credential injected into forwarded requests
+    credential: Option<UpstreamCredential>,
+    /// AWS SigV4 signer for forwarded requests
+    signer: Option<SigV4Signer>,
     /// Circuit breakers per target
     circuit_breakers: Arc<RwLock<HashMap<String, CircuitBreaker>>>,
     /// Pool statistics
@@ -858,6 +906,85 @@ impl UpstreamPool {
             }
         }
 
+        // Start the SPIFFE Workload API source if configured
+        let spiffe = match tls_config.as_ref().and_then(|t| t.spiffe.as_ref()) {
+            Some(spiffe_config) => {
+                // Pingora speaks HTTP/1.1 over the SPIFFE connection bridge
+                if http_version.min_version >= 2 {
+                    return Err(SentinelError::Tls {
+                        message: format!(
+                            "Upstream '{}': spiffe requires HTTP/1.1 (http-version min 1)",
+                            config.id
+                        ),
+                        source: None,
+                    });
+                }
+                let source = crate::spiffe::SpiffeSource::connect(spiffe_config)
+                    .await
+                    .map_err(|e| SentinelError::Tls {
+                        message: format!("Upstream '{}': {}", config.id, e),
+                        source: None,
+                    })?;
+                info!(
+                    upstream_id = %config.id,
+                    socket = %spiffe_config.socket.display(),
+                    allowed_ids = ?spiffe_config.allowed_ids,
+                    "mTLS enabled for upstream (SPIFFE workload identity)"
+                );
+                Some(source)
+            }
+            None => None,
+        };
+
+        // Resolve the credential injected into forwarded requests
+        let credential = match config.secret {
+            Some(ref secret) => {
+                let value = secret.resolve().map_err(|e| SentinelError::Config {
+                    message: format!("Upstream '{}' secret: {}", config.id, e),
+                    source: None,
+                })?;
+                info!(
+                    upstream_id = %config.id,
+                    header = ?secret.header,
+                    "Upstream credential injection enabled"
+                );
+                Some(UpstreamCredential {
+                    header: secret.header.clone(),
+                    prefix: secret.prefix.clone(),
+                    value,
+                })
+            }
+            None => None,
+        };
+
+        // AWS SigV4 signing, over the host the upstream is addressed by
+        let signer = match config.aws_sigv4 {
+            Some(ref sigv4) => {
+                let host = tls_sni.clone().unwrap_or_else(|| {
+                    let target = &targets[0];
+                    let default_port = if tls_enabled { 443 } else { 80 };
+                    if target.port == default_port {
+                        target.address.clone()
+                    } else {
+                        format!("{}:{}", target.address, target.port)
+                    }
+                });
+                let signer =
+                    SigV4Signer::from_env(sigv4, host).map_err(|e| SentinelError::Config {
+                        message: format!("Upstream '{}' aws-sigv4: {}", config.id, e),
+                        source: None,
+                    })?;
+                info!(
+                    upstream_id = %config.id,
+                    region = %sigv4.region,
+                    service = %sigv4.service,
+                    "AWS SigV4 request signing enabled"
+                );
+                Some(signer)
+            }
+            None => None,
+        };
+
         if http_version.max_version >= 2 && tls_enabled {
             info!(
                 upstream_id = %config.id,
@@ -888,6 +1015,9 @@ impl UpstreamPool {
             tls_enabled,
             tls_sni,
             tls_config,
+            spiffe,
+            credential,
+            signer,
             circuit_breakers: Arc::new(RwLock::new(circuit_breakers)),
             stats: Arc::new(PoolStats::default()),
         };
@@ -1181,6 +1311,66 @@ impl UpstreamPool {
         ))
     }
 
+    /// Select a specific target, bypassing the load balancer
+    ///
+    /// Used when an agent pins a request to a target. Fails if the address
+    /// is not one of this pool's targets, is unhealthy, or its circuit
+    /// breaker is open.
+    pub async fn select_pinned_peer(&self, address: &str) -> SentinelResult<HttpPeer> {
+        let target = self
+            .targets
+            .iter()
+            .find(|t| t.full_address() == address)
+            .ok_or_else(|| {
+                SentinelError::upstream(
+                    self.id.to_string(),
+                    format!("Target '{}' is not part of this upstream", address),
+                )
+            })?;
+
+        if !self
+            .load_balancer
+            .healthy_targets()
+            .await
+            .iter()
+            .any(|healthy| healthy == address)
+        {
+            return Err(SentinelError::upstream(
+                self.id.to_string(),
+                format!("Pinned target '{}' is unhealthy", address),
+            ));
+        }
+
+        let breakers = self.circuit_breakers.read().await;
+        if let Some(breaker) = breakers.get(address) {
+            if !breaker.is_closed() {
+                self.stats
+                    .circuit_breaker_trips
+                    .fetch_add(1, Ordering::Relaxed);
+                return Err(SentinelError::upstream(
+                    self.id.to_string(),
+                    format!("Circuit breaker is open for pinned target '{}'", address),
+                ));
+            }
+        }
+        drop(breakers);
+
+        let peer = self.create_peer(&TargetSelection {
+            address: target.full_address(),
+            weight: target.weight,
+            metadata: HashMap::new(),
+        })?;
+
+        self.stats.requests.fetch_add(1, Ordering::Relaxed);
+        self.stats.successes.fetch_add(1, Ordering::Relaxed);
+        debug!(
+            upstream_id = %self.id,
+            target = %address,
+            "Selected pinned upstream peer"
+        );
+        Ok(peer)
+    }
+
     /// Select next upstream peer
     pub async fn select_peer(&self, context: Option<&RequestContext>) -> SentinelResult<HttpPeer> {
         // Delegate to select_peer_with_metadata and discard metadata
@@ -1240,8 +1430,11 @@ impl UpstreamPool {
                 }
             })?;
 
+        // SPIFFE upstreams are plaintext to Pingora; their connector does TLS
+        let pingora_tls = self.tls_enabled && self.spiffe.is_none();
+
         // Use the resolved IP address to create the peer
-        let mut peer = HttpPeer::new(resolved_address, self.tls_enabled, sni_hostname.clone());
+        let mut peer = HttpPeer::new(resolved_address, pingora_tls, sni_hostname.clone());
 
         // Configure connection pooling options for better performance
         // idle_timeout enables Pingora's connection pooling - connections are
@@ -1267,7 +1460,7 @@ impl UpstreamPool {
         });
 
         // Configure HTTP version and ALPN for TLS connections
-        if self.tls_enabled {
+        if pingora_tls {
             // Set ALPN protocols based on configured HTTP version range
             let alpn = match (self.http_version.min_version, self.http_version.max_version) {
                 (2, _) => {
@@ -1351,6 +1544,24 @@ impl UpstreamPool {
             );
         }
 
+        // SPIFFE: the connector presents the current SVID and verifies the
+        // peer's SPIFFE ID during the handshake
+        if let Some(ref spiffe) = self.spiffe {
+            let connector = spiffe
+                .connector(&sni_hostname)
+                .map_err(|e| SentinelError::Tls {
+                    message: format!("Upstream '{}': {}", self.id, e),
+                    source: None,
+                })?;
+            peer.options.custom_l4 = Some(Arc::new(connector));
+            peer.options.alpn = pingora::upstreams::peer::ALPN::H1;
+            trace!(
+                upstream_id = %self.id,
+                target = %selection.address,
+                "Configured SPIFFE connector for upstream connection"
+            );
+        }
+
         // Configure H2-specific settings when HTTP/2 is enabled
         if self.http_version.max_version >= 2 {
             // H2 ping interval for connection health monitoring
@@ -1522,6 +1733,11 @@ impl UpstreamPool {
         !healthy.is_empty()
     }
 
+    /// Addresses of the targets the load balancer considers healthy.
+    pub async fn healthy_targets(&self) -> Vec<String> {
+        self.load_balancer.healthy_targets().await
+    }
+
     /// Select a target for shadow traffic (returns URL components)
     ///
     /// This is a simplified selection method for shadow requests that don't need
@@ -1572,6 +1788,21 @@ impl UpstreamPool {
         self.tls_enabled
     }
 
+    /// SPIFFE workload identity source, if configured
+    pub fn spiffe_source(&self) -> Option<&Arc<crate::spiffe::SpiffeSource>> {
+        self.spiffe.as_ref()
+    }
+
+    /// Credential injected into forwarded requests, if configured
+    pub fn credential(&self) -> Option<&UpstreamCredential> {
+        self.credential.as_ref()
+    }
+
+    /// AWS SigV4 signer for forwarded requests, if configured
+    pub fn signer(&self) -> Option<&SigV4Signer> {
+        self.signer.as_ref()
+    }
+
     /// Shutdown the pool
     ///
     /// Note: Pingora manages connection pooling internally, so we just log stats.
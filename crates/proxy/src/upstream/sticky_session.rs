@@ -505,6 +505,7 @@ mod tests {
             headers,
             path: "/".to_string(),
             method: "GET".to_string(),
+            hash_key: None,
         };
 
         let selection = balancer.select(Some(&context)).await.unwrap();
@@ -554,6 +555,7 @@ mod tests {
             headers: HashMap::new(),
             path: "/".to_string(),
             method: "GET".to_string(),
+            hash_key: None,
         };
 
         let selection = balancer.select(Some(&context)).await.unwrap();
@@ -609,6 +611,7 @@ mod tests {
             headers,
             path: "/".to_string(),
             method: "GET".to_string(),
+            hash_key: None,
         };
 
         let selection = balancer.select(Some(&context)).await.unwrap();
@@ -0,0 +1,164 @@
+//! End-to-end tests for streaming body rewriting.
+//!
+//! These tests run requests through the proxy application with an agent that
+//! rewrites body chunks, and check what the upstream and the client receive.
+
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use pingora::apps::ServerApp;
+use pingora::protocols::l4::stream::Stream as L4Stream;
+use pingora::protocols::Stream;
+use pingora::server::configuration::ServerConf;
+use tempfile::tempdir;
+use tokio::net::TcpListener;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use sentinel_agent_protocol::{AgentServer, UppercaseEchoAgent};
+use sentinel_proxy::SentinelProxy;
+
+/// Proxy configuration with one route whose agent streams both bodies.
+fn streaming_config(upstream: SocketAddr, agent_socket: &Path) -> String {
+    format!(
+        r#"
+        listeners {{
+            listener "http" {{
+                address "127.0.0.1:0"
+                protocol "http"
+            }}
+        }}
+
+        upstreams {{
+            upstream "backend" {{
+                target "{upstream}" weight=1
+            }}
+        }}
+
+        filters {{
+            filter "rewrite" {{
+                type "agent"
+                agent "echo"
+            }}
+        }}
+
+        agents {{
+            agent "echo" type="custom" {{
+                unix-socket path="{socket}"
+                events "request_headers" "request_body" "response_body"
+                request-body-mode "stream"
+                response-body-mode "stream"
+                timeout-ms 1000
+            }}
+        }}
+
+        routes {{
+            route "default" {{
+                matches {{
+                    path-prefix "/"
+                }}
+                upstream "backend"
+                filters "rewrite"
+            }}
+        }}
+
+        waf {{
+            body-inspection {{
+                inspect-request-body #true
+                inspect-response-body #true
+            }}
+        }}
+        "#,
+        upstream = upstream,
+        socket = agent_socket.display(),
+    )
+}
+
+/// Serve the proxy application on a local port, as the HTTP listener does.
+async fn start_proxy(config_path: &Path) -> SocketAddr {
+    let proxy = SentinelProxy::new(config_path.to_str())
+        .await
+        .expect("Proxy should start");
+    let app = Arc::new(pingora::proxy::http_proxy(
+        &Arc::new(ServerConf::default()),
+        proxy,
+    ));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown) = tokio::sync::watch::channel(false);
+
+    tokio::spawn(async move {
+        let _shutdown_tx = shutdown_tx;
+        while let Ok((tcp, _)) = listener.accept().await {
+            let app = app.clone();
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                let mut stream: Stream = Box::new(L4Stream::from(tcp));
+                while let Some(reused) = app.process_new(stream, &shutdown).await {
+                    stream = reused;
+                }
+            });
+        }
+    });
+
+    addr
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_agent_uppercases_proxied_bodies() {
+    let dir = tempdir().unwrap();
+    let socket_path = dir.path().join("echo.sock");
+
+    let agent = AgentServer::new("echo", socket_path.clone(), Box::new(UppercaseEchoAgent));
+    let agent_handle = tokio::spawn(async move {
+        let _ = agent.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let upstream = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/echo"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("upstream says hi"))
+        .mount(&upstream)
+        .await;
+
+    let config_path = dir.path().join("sentinel.kdl");
+    std::fs::write(
+        &config_path,
+        streaming_config(*upstream.address(), &socket_path),
+    )
+    .unwrap();
+    let proxy_addr = start_proxy(&config_path).await;
+
+    let response = reqwest::Client::new()
+        .post(format!("http://{}/echo", proxy_addr))
+        .body("hello, streamed world")
+        .send()
+        .await
+        .expect("Request should be proxied");
+
+    assert_eq!(response.status(), 200);
+    assert!(
+        response.headers().get("content-length").is_none(),
+        "Rewritten response must not keep the upstream length"
+    );
+    assert_eq!(response.text().await.unwrap(), "UPSTREAM SAYS HI");
+
+    // The upstream got the rewritten request body, re-framed as chunked
+    let requests = upstream.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].body, b"HELLO, STREAMED WORLD");
+    assert!(requests[0].headers.get("content-length").is_none());
+    assert_eq!(
+        requests[0]
+            .headers
+            .get("transfer-encoding")
+            .and_then(|v| v.to_str().ok()),
+        Some("chunked")
+    );
+
+    agent_handle.abort();
+}
@@ -4,17 +4,18 @@
 //! through agent processing to proxy operation.
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tempfile::tempdir;
 
 use sentinel_agent_protocol::{
-    AgentHandler, AgentResponse, AgentServer, AuditMetadata, Decision, EventType, HeaderOp,
-    RequestHeadersEvent, RequestMetadata,
+    AgentHandler, AgentResponse, AgentServer, AuditMetadata, Decision, EchoAgent, EventType,
+    HeaderOp, RequestHeadersEvent, RequestMetadata, UppercaseEchoAgent,
 };
 use sentinel_common::CorrelationId;
-use sentinel_config::Config;
-use sentinel_proxy::agents::AgentDecision;
+use sentinel_config::{AgentExecutionMode, Config, FailureMode};
+use sentinel_proxy::agents::{AgentAction, AgentCallContext, AgentDecision, AgentManager};
 
 // ============================================================================
 // Test Agent Implementation
@@ -237,6 +238,7 @@ async fn test_agent_server_client_roundtrip() {
             upstream_id: Some("backend".to_string()),
             timestamp: chrono::Utc::now().to_rfc3339(),
             traceparent: None,
+            client_cert: None,
         },
         method: "GET".to_string(),
         uri: "/api/users".to_string(),
@@ -299,6 +301,7 @@ async fn test_blocking_agent_rejects_request() {
             upstream_id: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
             traceparent: None,
+            client_cert: None,
         },
         method: "GET".to_string(),
         uri: "/admin/secret".to_string(),
@@ -332,6 +335,7 @@ async fn test_blocking_agent_rejects_request() {
             upstream_id: None,
             timestamp: chrono::Utc::now().to_rfc3339(),
             traceparent: None,
+            client_cert: None,
         },
         method: "GET".to_string(),
         uri: "/api/users".to_string(),
@@ -349,6 +353,397 @@ async fn test_blocking_agent_rejects_request() {
     server_handle.abort();
 }
 
+// ============================================================================
+// Streaming Body Rewrite Tests
+// ============================================================================
+
+fn streaming_agent_config(socket_path: &std::path::Path) -> sentinel_config::AgentConfig {
+    use sentinel_config::{
+        AgentConfig, AgentEvent, AgentProtocolVersion, AgentTransport, AgentType, BodyStreamingMode,
+    };
+
+    AgentConfig {
+        id: "echo".to_string(),
+        agent_type: AgentType::Custom("echo".to_string()),
+        transport: AgentTransport::UnixSocket {
+            path: socket_path.to_path_buf(),
+        },
+        events: vec![AgentEvent::RequestBody, AgentEvent::ResponseBody],
+        protocol_version: AgentProtocolVersion::V1,
+        pool: None,
+        timeout_ms: 1000,
+        failure_mode: sentinel_config::FailureMode::Closed,
+        circuit_breaker: None,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        request_body_mode: BodyStreamingMode::Stream,
+        response_body_mode: BodyStreamingMode::Stream,
+        chunk_timeout_ms: 5000,
+        config: None,
+        max_concurrent_calls: 100,
+        recording: None,
+        enforce: true,
+    }
+}
+
+fn streaming_call_context(correlation_id: &str) -> AgentCallContext {
+    AgentCallContext::new(
+        CorrelationId::from_string(correlation_id),
+        RequestMetadata {
+            correlation_id: correlation_id.to_string(),
+            request_id: correlation_id.to_string(),
+            client_ip: "127.0.0.1".to_string(),
+            client_port: 12345,
+            server_name: None,
+            protocol: "HTTP/1.1".to_string(),
+            tls_version: None,
+            tls_cipher: None,
+            route_id: Some("upload".to_string()),
+            upstream_id: Some("backend".to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            traceparent: None,
+            client_cert: None,
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_echo_agent_uppercases_streamed_bodies() {
+    let dir = tempdir().unwrap();
+    let socket_path = dir.path().join("echo-agent.sock");
+
+    let server = AgentServer::new("echo", socket_path.clone(), Box::new(UppercaseEchoAgent));
+    let server_handle = tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let manager = AgentManager::new(vec![streaming_agent_config(&socket_path)])
+        .await
+        .expect("Manager should be created");
+    manager.initialize().await.expect("Agent should connect");
+
+    let route_agents = vec!["echo".to_string()];
+    let chunks: [&[u8]; 3] = [b"hello, ", b"streamed ", b"world"];
+
+    // Request path: every chunk is rewritten before it would be forwarded
+    let ctx = streaming_call_context("stream-req");
+    let mut forwarded = Vec::new();
+    let mut bytes_received = 0;
+    for (index, chunk) in chunks.iter().enumerate() {
+        bytes_received += chunk.len();
+        let decision = manager
+            .process_request_body_streaming(
+                &ctx,
+                chunk,
+                index == chunks.len() - 1,
+                index as u32,
+                bytes_received,
+                None,
+                &route_agents,
+            )
+            .await
+            .expect("Chunk should be processed");
+        assert!(decision.is_allow());
+
+        let mutation = decision
+            .request_body_mutation
+            .expect("Echo agent should rewrite the chunk");
+        assert_eq!(mutation.chunk_index, index as u32);
+        forwarded.extend(mutation.apply(chunk));
+    }
+    assert_eq!(forwarded, b"HELLO, STREAMED WORLD");
+
+    // Response path
+    let ctx = streaming_call_context("stream-resp");
+    let mut forwarded = Vec::new();
+    let mut bytes_sent = 0;
+    for (index, chunk) in chunks.iter().enumerate() {
+        bytes_sent += chunk.len();
+        let decision = manager
+            .process_response_body_streaming(
+                &ctx,
+                chunk,
+                index == chunks.len() - 1,
+                index as u32,
+                bytes_sent,
+                None,
+                &route_agents,
+            )
+            .await
+            .expect("Chunk should be processed");
+
+        let mutation = decision
+            .response_body_mutation
+            .expect("Echo agent should rewrite the chunk");
+        forwarded.extend(mutation.apply(chunk));
+    }
+    assert_eq!(forwarded, b"HELLO, STREAMED WORLD");
+
+    manager.shutdown().await;
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn test_plain_echo_agent_passes_streamed_bodies_through() {
+    let dir = tempdir().unwrap();
+    let socket_path = dir.path().join("echo-plain.sock");
+
+    let server = AgentServer::new("echo", socket_path.clone(), Box::new(EchoAgent));
+    let server_handle = tokio::spawn(async move {
+        let _ = server.run().await;
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let manager = AgentManager::new(vec![streaming_agent_config(&socket_path)])
+        .await
+        .expect("Manager should be created");
+    manager.initialize().await.expect("Agent should connect");
+
+    let decision = manager
+        .process_request_body_streaming(
+            &streaming_call_context("plain"),
+            b"hello",
+            true,
+            0,
+            5,
+            Some(5),
+            &["echo".to_string()],
+        )
+        .await
+        .expect("Chunk should be processed");
+    assert!(decision.is_allow());
+    assert!(decision.request_body_mutation.is_none());
+
+    manager.shutdown().await;
+    server_handle.abort();
+}
+
+// ============================================================================
+// Agent Execution Mode Tests
+// ============================================================================
+
+/// Agent that answers request headers after a delay and counts its calls
+struct DelayedAgent {
+    delay: Duration,
+    block_status: Option<u16>,
+    tag: &'static str,
+    calls: Arc<AtomicUsize>,
+}
+
+impl DelayedAgent {
+    fn new(delay_ms: u64, block_status: Option<u16>, tag: &'static str) -> Self {
+        Self {
+            delay: Duration::from_millis(delay_ms),
+            block_status,
+            tag,
+            calls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AgentHandler for DelayedAgent {
+    async fn on_request_headers(&self, _event: RequestHeadersEvent) -> AgentResponse {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(self.delay).await;
+        let response = match self.block_status {
+            Some(status) => AgentResponse::block(status, None),
+            None => AgentResponse::default_allow(),
+        };
+        response.with_audit(AuditMetadata {
+            tags: vec![self.tag.to_string()],
+            ..Default::default()
+        })
+    }
+}
+
+/// Start header agents and a manager connected to all of them
+///
+/// Returns the manager, the per-agent call counters and the server tasks.
+async fn start_header_agents(
+    dir: &std::path::Path,
+    agents: Vec<(&'static str, DelayedAgent)>,
+) -> (
+    AgentManager,
+    HashMap<&'static str, Arc<AtomicUsize>>,
+    Vec<tokio::task::JoinHandle<()>>,
+) {
+    use sentinel_config::{
+        AgentConfig, AgentEvent, AgentProtocolVersion, AgentTransport, AgentType,
+    };
+
+    let mut configs = Vec::new();
+    let mut calls = HashMap::new();
+    let mut servers = Vec::new();
+    for (id, agent) in agents {
+        let socket_path = dir.join(format!("{}.sock", id));
+        calls.insert(id, agent.calls.clone());
+        let server = AgentServer::new(id, socket_path.clone(), Box::new(agent));
+        servers.push(tokio::spawn(async move {
+            let _ = server.run().await;
+        }));
+        configs.push(AgentConfig {
+            id: id.to_string(),
+            agent_type: AgentType::Custom(id.to_string()),
+            transport: AgentTransport::UnixSocket { path: socket_path },
+            events: vec![AgentEvent::RequestHeaders],
+            protocol_version: AgentProtocolVersion::V1,
+            pool: None,
+            timeout_ms: 300,
+            failure_mode: FailureMode::Closed,
+            circuit_breaker: None,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            request_body_mode: Default::default(),
+            response_body_mode: Default::default(),
+            chunk_timeout_ms: 5000,
+            config: None,
+            max_concurrent_calls: 100,
+            recording: None,
+            enforce: true,
+        });
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let manager = AgentManager::new(configs)
+        .await
+        .expect("Manager should be created");
+    manager.initialize().await.expect("Agents should connect");
+    (manager, calls, servers)
+}
+
+fn request_headers() -> HashMap<String, Vec<String>> {
+    HashMap::from([
+        (":method".to_string(), vec!["GET".to_string()]),
+        (":path".to_string(), vec!["/api/orders".to_string()]),
+    ])
+}
+
+fn blocked_status(decision: &AgentDecision) -> Option<u16> {
+    match decision.action {
+        AgentAction::Block { status, .. } => Some(status),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn test_block_short_circuit_order_per_execution_mode() {
+    let dir = tempdir().unwrap();
+    let (manager, calls, servers) = start_header_agents(
+        dir.path(),
+        vec![
+            ("slow-waf", DelayedAgent::new(150, Some(429), "slow-waf")),
+            ("auth", DelayedAgent::new(0, None, "auth")),
+            ("fast-bot", DelayedAgent::new(0, Some(403), "fast-bot")),
+        ],
+    )
+    .await;
+    let route_agents: Vec<_> = ["slow-waf", "auth", "fast-bot"]
+        .iter()
+        .map(|id| (id.to_string(), FailureMode::Closed))
+        .collect();
+
+    // Sequential: the first agent in filter order blocks, later agents are
+    // never called
+    let decision = manager
+        .process_request_headers(
+            &streaming_call_context("seq-block"),
+            request_headers(),
+            &route_agents,
+            AgentExecutionMode::Sequential,
+        )
+        .await
+        .unwrap();
+    assert_eq!(blocked_status(&decision), Some(429));
+    assert_eq!(calls["slow-waf"].load(Ordering::SeqCst), 1);
+    assert_eq!(calls["auth"].load(Ordering::SeqCst), 0);
+    assert_eq!(calls["fast-bot"].load(Ordering::SeqCst), 0);
+
+    // Parallel: the first block to arrive wins without waiting for slower
+    // agents, and audit from every agent that answered is kept
+    let start = std::time::Instant::now();
+    let decision = manager
+        .process_request_headers(
+            &streaming_call_context("par-block"),
+            request_headers(),
+            &route_agents,
+            AgentExecutionMode::Parallel,
+        )
+        .await
+        .unwrap();
+    assert_eq!(blocked_status(&decision), Some(403));
+    assert!(start.elapsed() < Duration::from_millis(150));
+    let tags: Vec<_> = decision.audit.iter().flat_map(|a| a.tags.clone()).collect();
+    assert!(tags.contains(&"fast-bot".to_string()));
+    assert!(!tags.contains(&"slow-waf".to_string()));
+    assert_eq!(calls["fast-bot"].load(Ordering::SeqCst), 1);
+
+    manager.shutdown().await;
+    servers.iter().for_each(|server| server.abort());
+}
+
+#[tokio::test]
+async fn test_failure_modes_per_execution_mode() {
+    let dir = tempdir().unwrap();
+    // "stuck" never answers within the 300ms agent timeout during the test
+    // (four timeouts stay below the circuit breaker's failure threshold)
+    let (manager, calls, servers) = start_header_agents(
+        dir.path(),
+        vec![
+            ("stuck", DelayedAgent::new(10_000, None, "stuck")),
+            ("auth", DelayedAgent::new(0, None, "auth")),
+        ],
+    )
+    .await;
+
+    for mode in [AgentExecutionMode::Sequential, AgentExecutionMode::Parallel] {
+        // Fail-closed timeout blocks the request with 504
+        let route_agents = vec![
+            ("stuck".to_string(), FailureMode::Closed),
+            ("auth".to_string(), FailureMode::Open),
+        ];
+        let decision = manager
+            .process_request_headers(
+                &streaming_call_context("closed"),
+                request_headers(),
+                &route_agents,
+                mode,
+            )
+            .await
+            .unwrap();
+        assert_eq!(blocked_status(&decision), Some(504), "{:?}", mode);
+
+        // Fail-open timeout is skipped and the other agents still apply
+        let route_agents = vec![
+            ("stuck".to_string(), FailureMode::Open),
+            ("auth".to_string(), FailureMode::Closed),
+        ];
+        let before = calls["auth"].load(Ordering::SeqCst);
+        let decision = manager
+            .process_request_headers(
+                &streaming_call_context("open"),
+                request_headers(),
+                &route_agents,
+                mode,
+            )
+            .await
+            .unwrap();
+        assert!(decision.is_allow(), "{:?}", mode);
+        assert_eq!(
+            calls["auth"].load(Ordering::SeqCst),
+            before + 1,
+            "{:?}",
+            mode
+        );
+        let tags: Vec<_> = decision.audit.iter().flat_map(|a| a.tags.clone()).collect();
+        assert_eq!(tags, vec!["auth".to_string()], "{:?}", mode);
+    }
+
+    manager.shutdown().await;
+    servers.iter().for_each(|server| server.abort());
+}
+
 // ============================================================================
 // Decision Merging Tests
 // ============================================================================
@@ -0,0 +1,390 @@
+//! SPIFFE Workload API Integration Tests
+//!
+//! These tests run a stub Workload API server on a Unix socket and verify
+//! that `SpiffeSource` picks up SVIDs and rotations, presents the SVID to
+//! upstreams, and verifies upstream peers by SPIFFE ID instead of hostname.
+
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Once};
+use std::time::Duration;
+
+use futures_util::{Stream, StreamExt};
+use rcgen::{CertificateParams, DistinguishedName, DnType, Issuer, KeyPair, SanType};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, UnixListener};
+use tokio::sync::watch;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use tokio_stream::wrappers::{UnixListenerStream, WatchStream};
+use tonic::{Request, Response, Status};
+
+use sentinel_config::SpiffeConfig;
+use sentinel_proxy::spiffe::proto::spiffe_workload_api_server::{
+    SpiffeWorkloadApi, SpiffeWorkloadApiServer,
+};
+use sentinel_proxy::spiffe::proto::{X509svid, X509svidRequest, X509svidResponse};
+use sentinel_proxy::spiffe::{SpiffeSource, WORKLOAD_API_HEADER};
+
+static CRYPTO_PROVIDER_INIT: Once = Once::new();
+
+fn ensure_crypto_provider() {
+    CRYPTO_PROVIDER_INIT.call_once(|| {
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+    });
+}
+
+// ============================================================================
+// Certificate Generation
+// ============================================================================
+
+/// A trust domain CA
+struct TrustDomainCa {
+    params: CertificateParams,
+    key_pair: KeyPair,
+    cert_der: Vec<u8>,
+}
+
+impl TrustDomainCa {
+    fn new(trust_domain: &str) -> Self {
+        let mut params = CertificateParams::default();
+        params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let mut dn = DistinguishedName::new();
+        dn.push(DnType::OrganizationName, trust_domain);
+        params.distinguished_name = dn;
+        params.subject_alt_names = vec![SanType::URI(
+            format!("spiffe://{}", trust_domain).try_into().unwrap(),
+        )];
+
+        let key_pair = KeyPair::generate().unwrap();
+        let cert_der = params.self_signed(&key_pair).unwrap().der().to_vec();
+
+        Self {
+            params,
+            key_pair,
+            cert_der,
+        }
+    }
+
+    /// Issue an X.509 SVID with only a SPIFFE ID URI SAN
+    fn issue_svid(&self, spiffe_id: &str) -> Svid {
+        let mut params = CertificateParams::default();
+        params.subject_alt_names = vec![SanType::URI(spiffe_id.try_into().unwrap())];
+
+        let key_pair = KeyPair::generate().unwrap();
+        let issuer = Issuer::from_params(&self.params, &self.key_pair);
+        let cert_der = params.signed_by(&key_pair, &issuer).unwrap().der().to_vec();
+
+        Svid {
+            spiffe_id: spiffe_id.to_string(),
+            cert_der,
+            key_der: key_pair.serialize_der(),
+        }
+    }
+}
+
+struct Svid {
+    spiffe_id: String,
+    cert_der: Vec<u8>,
+    key_der: Vec<u8>,
+}
+
+impl Svid {
+    fn response(&self, ca: &TrustDomainCa) -> X509svidResponse {
+        X509svidResponse {
+            svids: vec![X509svid {
+                spiffe_id: self.spiffe_id.clone(),
+                x509_svid: self.cert_der.clone(),
+                x509_svid_key: self.key_der.clone(),
+                bundle: ca.cert_der.clone(),
+                hint: String::new(),
+            }],
+            crl: vec![],
+            federated_bundles: Default::default(),
+        }
+    }
+}
+
+// ============================================================================
+// Stub Workload API
+// ============================================================================
+
+/// Workload API stub that streams whatever response is currently published
+struct StubWorkloadApi {
+    updates: watch::Receiver<Option<X509svidResponse>>,
+}
+
+#[tonic::async_trait]
+impl SpiffeWorkloadApi for StubWorkloadApi {
+    type FetchX509SVIDStream =
+        Pin<Box<dyn Stream<Item = Result<X509svidResponse, Status>> + Send + 'static>>;
+
+    async fn fetch_x509svid(
+        &self,
+        request: Request<X509svidRequest>,
+    ) -> Result<Response<Self::FetchX509SVIDStream>, Status> {
+        if request.metadata().get(WORKLOAD_API_HEADER).is_none() {
+            return Err(Status::invalid_argument("security header missing from request"));
+        }
+
+        let stream = WatchStream::new(self.updates.clone())
+            .filter_map(|update| async move { update.map(Ok) });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Running stub server bound to a temporary Unix socket
+struct StubServer {
+    _dir: tempfile::TempDir,
+    socket: PathBuf,
+    publish: watch::Sender<Option<X509svidResponse>>,
+}
+
+impl StubServer {
+    fn start(initial: Option<X509svidResponse>) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("agent.sock");
+        let (publish, updates) = watch::channel(initial);
+
+        let listener = UnixListener::bind(&socket).unwrap();
+        tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(SpiffeWorkloadApiServer::new(StubWorkloadApi { updates }))
+                .serve_with_incoming(UnixListenerStream::new(listener))
+                .await
+                .unwrap();
+        });
+
+        Self {
+            _dir: dir,
+            socket,
+            publish,
+        }
+    }
+
+    fn config(&self, allowed_ids: &[&str]) -> SpiffeConfig {
+        SpiffeConfig {
+            socket: self.socket.clone(),
+            allowed_ids: allowed_ids.iter().map(|s| s.to_string()).collect(),
+            fetch_timeout_secs: 5,
+        }
+    }
+
+    fn publish(&self, response: X509svidResponse) {
+        self.publish.send_replace(Some(response));
+    }
+}
+
+// ============================================================================
+// Upstream TLS Server
+// ============================================================================
+
+/// Start an upstream that requires a client SVID from `ca` and presents `svid`
+async fn start_upstream(ca: &TrustDomainCa, svid: &Svid) -> std::net::SocketAddr {
+    let mut roots = RootCertStore::empty();
+    roots.add(CertificateDer::from(ca.cert_der.clone())).unwrap();
+    let verifier = WebPkiClientVerifier::builder(Arc::new(roots)).build().unwrap();
+
+    let config = ServerConfig::builder()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(
+            vec![CertificateDer::from(svid.cert_der.clone())],
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(svid.key_der.clone())),
+        )
+        .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((tcp, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                if let Ok(mut tls) = acceptor.accept(tcp).await {
+                    let mut buf = [0u8; 64];
+                    if let Ok(n) = tls.read(&mut buf).await {
+                        let _ = tls.write_all(&buf[..n]).await;
+                    }
+                }
+            });
+        }
+    });
+
+    addr
+}
+
+/// Connect with the source's rustls config and echo a message
+async fn echo_via_spiffe(
+    source: &Arc<SpiffeSource>,
+    addr: std::net::SocketAddr,
+) -> std::io::Result<Vec<u8>> {
+    let connector = TlsConnector::from(Arc::new(source.client_config()));
+    let tcp = tokio::net::TcpStream::connect(addr).await?;
+    // The hostname is irrelevant: SVIDs carry no DNS names
+    let name = "backend.internal".try_into().unwrap();
+    let mut tls = connector.connect(name, tcp).await?;
+    tls.write_all(b"ping").await?;
+    let mut buf = vec![0u8; 4];
+    tls.read_exact(&mut buf).await?;
+    Ok(buf)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_source_receives_svid() {
+    ensure_crypto_provider();
+    let ca = TrustDomainCa::new("example.org");
+    let svid = ca.issue_svid("spiffe://example.org/ns/edge/sa/sentinel");
+    let stub = StubServer::start(Some(svid.response(&ca)));
+
+    let source = SpiffeSource::connect(&stub.config(&[])).await.unwrap();
+    let ctx = source.current().unwrap();
+
+    assert_eq!(
+        ctx.svid.spiffe_id.to_string(),
+        "spiffe://example.org/ns/edge/sa/sentinel"
+    );
+    assert_eq!(ctx.svid.cert_chain[0].as_ref(), svid.cert_der.as_slice());
+    assert_eq!(ctx.bundle("example.org").unwrap().len(), 1);
+    assert!(source.cert_key().is_some());
+}
+
+#[tokio::test]
+async fn test_source_picks_up_rotation() {
+    ensure_crypto_provider();
+    let ca = TrustDomainCa::new("example.org");
+    let first = ca.issue_svid("spiffe://example.org/ns/edge/sa/sentinel");
+    let stub = StubServer::start(Some(first.response(&ca)));
+
+    let source = SpiffeSource::connect(&stub.config(&[])).await.unwrap();
+    let mut updates = source.subscribe();
+    updates.borrow_and_update();
+
+    let rotated = ca.issue_svid("spiffe://example.org/ns/edge/sa/sentinel");
+    stub.publish(rotated.response(&ca));
+
+    tokio::time::timeout(Duration::from_secs(5), updates.changed())
+        .await
+        .expect("rotation not received")
+        .unwrap();
+
+    let ctx = source.current().unwrap();
+    assert_eq!(ctx.svid.cert_chain[0].as_ref(), rotated.cert_der.as_slice());
+}
+
+#[tokio::test]
+async fn test_connect_times_out_without_svid() {
+    ensure_crypto_provider();
+    let stub = StubServer::start(None);
+    let mut config = stub.config(&[]);
+    config.fetch_timeout_secs = 1;
+
+    let err = SpiffeSource::connect(&config).await.unwrap_err();
+    assert!(err.to_string().contains("no SVID received"));
+}
+
+#[tokio::test]
+async fn test_connect_fails_without_socket() {
+    ensure_crypto_provider();
+    let config = SpiffeConfig {
+        socket: Path::new("/nonexistent/spire/agent.sock").to_path_buf(),
+        allowed_ids: vec![],
+        fetch_timeout_secs: 1,
+    };
+
+    assert!(SpiffeSource::connect(&config).await.is_err());
+}
+
+#[tokio::test]
+async fn test_upstream_verified_by_spiffe_id() {
+    ensure_crypto_provider();
+    let ca = TrustDomainCa::new("example.org");
+    let client = ca.issue_svid("spiffe://example.org/ns/edge/sa/sentinel");
+    let backend = ca.issue_svid("spiffe://example.org/ns/prod/sa/payments");
+    let stub = StubServer::start(Some(client.response(&ca)));
+    let addr = start_upstream(&ca, &backend).await;
+
+    // Exact and wildcard allow-lists
+    for allowed in ["spiffe://example.org/ns/prod/sa/payments", "spiffe://example.org/ns/prod/*"] {
+        let source = SpiffeSource::connect(&stub.config(&[allowed])).await.unwrap();
+        assert_eq!(echo_via_spiffe(&source, addr).await.unwrap(), b"ping");
+    }
+
+    // Empty allow-list accepts the workload's own trust domain
+    let source = SpiffeSource::connect(&stub.config(&[])).await.unwrap();
+    assert_eq!(echo_via_spiffe(&source, addr).await.unwrap(), b"ping");
+}
+
+#[tokio::test]
+async fn test_upstream_rejected_for_other_spiffe_id() {
+    ensure_crypto_provider();
+    let ca = TrustDomainCa::new("example.org");
+    let client = ca.issue_svid("spiffe://example.org/ns/edge/sa/sentinel");
+    let backend = ca.issue_svid("spiffe://example.org/ns/dev/sa/payments");
+    let stub = StubServer::start(Some(client.response(&ca)));
+    let addr = start_upstream(&ca, &backend).await;
+
+    let source = SpiffeSource::connect(&stub.config(&["spiffe://example.org/ns/prod/*"]))
+        .await
+        .unwrap();
+    assert!(echo_via_spiffe(&source, addr).await.is_err());
+}
+
+#[tokio::test]
+async fn test_upstream_rejected_from_untrusted_domain() {
+    ensure_crypto_provider();
+    let ca = TrustDomainCa::new("example.org");
+    let rogue_ca = TrustDomainCa::new("example.org");
+    let client = ca.issue_svid("spiffe://example.org/ns/edge/sa/sentinel");
+    // Same SPIFFE ID, but signed by a CA outside the trust bundle
+    let backend = rogue_ca.issue_svid("spiffe://example.org/ns/prod/sa/payments");
+    let stub = StubServer::start(Some(client.response(&ca)));
+    let addr = start_upstream(&rogue_ca, &backend).await;
+
+    let source = SpiffeSource::connect(&stub.config(&[])).await.unwrap();
+    assert!(echo_via_spiffe(&source, addr).await.is_err());
+}
+
+#[tokio::test]
+async fn test_connector_verifies_spiffe_id_in_handshake() {
+    use pingora_core::connectors::L4Connect;
+
+    ensure_crypto_provider();
+    let ca = TrustDomainCa::new("example.org");
+    let client = ca.issue_svid("spiffe://example.org/ns/edge/sa/sentinel");
+    let backend = ca.issue_svid("spiffe://example.org/ns/prod/sa/payments");
+    let stub = StubServer::start(Some(client.response(&ca)));
+    let addr = start_upstream(&ca, &backend).await;
+
+    // Pingora gets a plaintext stream bridged to the verified TLS connection
+    let source = SpiffeSource::connect(&stub.config(&["spiffe://example.org/ns/prod/*"]))
+        .await
+        .unwrap();
+    let mut stream = source
+        .connector("payments")
+        .unwrap()
+        .connect(&addr)
+        .await
+        .unwrap();
+    stream.write_all(b"ping").await.unwrap();
+    let mut buf = vec![0u8; 4];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(buf, b"ping");
+
+    // A peer outside the allow-list never yields a connection
+    let source = SpiffeSource::connect(&stub.config(&["spiffe://example.org/ns/dev/*"]))
+        .await
+        .unwrap();
+    assert!(source
+        .connector("payments")
+        .unwrap()
+        .connect(&addr)
+        .await
+        .is_err());
+}
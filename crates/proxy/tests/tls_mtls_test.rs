@@ -37,6 +37,7 @@ fn minimal_upstream_config() -> UpstreamTlsConfig {
         client_cert: None,
         client_key: None,
         insecure_skip_verify: false,
+        spiffe: None,
     }
 }
 
@@ -49,6 +50,7 @@ fn custom_ca_upstream_config() -> UpstreamTlsConfig {
         client_cert: None,
         client_key: None,
         insecure_skip_verify: false,
+        spiffe: None,
     }
 }
 
@@ -61,6 +63,7 @@ fn mtls_upstream_config() -> UpstreamTlsConfig {
         client_cert: Some(fixtures.join("client.crt")),
         client_key: Some(fixtures.join("client.key")),
         insecure_skip_verify: false,
+        spiffe: None,
     }
 }
 
@@ -72,6 +75,7 @@ fn insecure_upstream_config() -> UpstreamTlsConfig {
         client_cert: None,
         client_key: None,
         insecure_skip_verify: true,
+        spiffe: None,
     }
 }
 
@@ -140,6 +144,7 @@ mod build_config {
             client_cert: None,
             client_key: None,
             insecure_skip_verify: false,
+            spiffe: None,
         };
 
         let result = build_upstream_tls_config(&config);
@@ -162,6 +167,7 @@ mod build_config {
             client_cert: Some(fixtures.join("nonexistent-client.crt")),
             client_key: Some(fixtures.join("client.key")),
             insecure_skip_verify: false,
+            spiffe: None,
         };
 
         let result = build_upstream_tls_config(&config);
@@ -178,6 +184,7 @@ mod build_config {
             client_cert: Some(fixtures.join("client.crt")),
             client_key: Some(fixtures.join("nonexistent-client.key")),
             insecure_skip_verify: false,
+            spiffe: None,
         };
 
         let result = build_upstream_tls_config(&config);
@@ -229,6 +236,7 @@ mod validation {
             client_cert: None,
             client_key: None,
             insecure_skip_verify: false,
+            spiffe: None,
         };
 
         let result = validate_upstream_tls_config(&config);
@@ -250,6 +258,7 @@ mod validation {
             client_cert: Some(fixtures.join("nonexistent-client.crt")),
             client_key: Some(fixtures.join("client.key")),
             insecure_skip_verify: false,
+            spiffe: None,
         };
 
         let result = validate_upstream_tls_config(&config);
@@ -271,6 +280,7 @@ mod validation {
             client_cert: Some(fixtures.join("client.crt")),
             client_key: Some(fixtures.join("nonexistent-client.key")),
             insecure_skip_verify: false,
+            spiffe: None,
         };
 
         let result = validate_upstream_tls_config(&config);
@@ -292,6 +302,7 @@ mod validation {
             client_cert: Some(fixtures.join("client.crt")),
             client_key: None,
             insecure_skip_verify: false,
+            spiffe: None,
         };
 
         let result = validate_upstream_tls_config(&config);
@@ -313,6 +324,7 @@ mod validation {
             client_cert: None,
             client_key: Some(fixtures.join("client.key")),
             insecure_skip_verify: false,
+            spiffe: None,
         };
 
         let result = validate_upstream_tls_config(&config);
@@ -347,6 +359,7 @@ mod edge_cases {
             client_cert: None,
             client_key: None,
             insecure_skip_verify: false,
+            spiffe: None,
         };
 
         // Empty CA file should either fail to parse or produce empty root store
@@ -369,6 +382,7 @@ mod edge_cases {
             client_cert: None,
             client_key: None,
             insecure_skip_verify: false,
+            spiffe: None,
         };
 
         // Invalid content may or may not cause an error depending on parsing
@@ -388,6 +402,7 @@ mod edge_cases {
             client_cert: None,
             client_key: None,
             insecure_skip_verify: false,
+            spiffe: None,
         };
 
         let result = build_upstream_tls_config(&config);
@@ -408,6 +423,7 @@ mod edge_cases {
             client_cert: Some(fixtures.join("client.pem")),
             client_key: Some(fixtures.join("client.pem")),
             insecure_skip_verify: false,
+            spiffe: None,
         };
 
         // Combined PEM file should work for both cert and key
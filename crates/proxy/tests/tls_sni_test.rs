@@ -52,11 +52,13 @@ fn multi_sni_tls_config() -> TlsConfig {
                 hostnames: vec!["api.example.com".to_string()],
                 cert_file: fixtures.join("server-api.crt"),
                 key_file: fixtures.join("server-api.key"),
+                ..Default::default()
             },
             SniCertificate {
                 hostnames: vec!["secure.example.com".to_string()],
                 cert_file: fixtures.join("server-secure.crt"),
                 key_file: fixtures.join("server-secure.key"),
+                ..Default::default()
             },
         ],
         ca_file: None,
@@ -80,6 +82,7 @@ fn wildcard_tls_config() -> TlsConfig {
             hostnames: vec!["*.example.com".to_string()],
             cert_file: fixtures.join("server-wildcard.crt"),
             key_file: fixtures.join("server-wildcard.key"),
+            ..Default::default()
         }],
         ca_file: None,
         min_version: sentinel_common::types::TlsVersion::Tls12,
@@ -249,11 +252,13 @@ mod sni_resolver {
                     hostnames: vec!["*.example.com".to_string()],
                     cert_file: fixtures.join("server-wildcard.crt"),
                     key_file: fixtures.join("server-wildcard.key"),
+                    ..Default::default()
                 },
                 SniCertificate {
                     hostnames: vec!["api.example.com".to_string()],
                     cert_file: fixtures.join("server-api.crt"),
                     key_file: fixtures.join("server-api.key"),
+                    ..Default::default()
                 },
             ],
             ca_file: None,
@@ -340,6 +345,7 @@ mod sni_resolver {
                 hostnames: vec!["api.example.com".to_string()],
                 cert_file: fixtures.join("nonexistent.crt"),
                 key_file: fixtures.join("server-api.key"),
+                ..Default::default()
             }],
             ca_file: None,
             min_version: sentinel_common::types::TlsVersion::Tls12,
@@ -806,6 +812,7 @@ mod validation {
                 hostnames: vec!["test.example.com".to_string()],
                 cert_file: fixtures.join("nonexistent.crt"),
                 key_file: fixtures.join("server-api.key"),
+                ..Default::default()
             }],
             ca_file: None,
             min_version: sentinel_common::types::TlsVersion::Tls12,
@@ -896,3 +903,309 @@ mod server_config {
         assert!(result.is_ok(), "Failed to build wildcard server config: {:?}", result.err());
     }
 }
+
+// ============================================================================
+// Per-SNI Policy Tests
+// ============================================================================
+
+mod sni_policy {
+    use super::*;
+    use rustls::pki_types::ServerName;
+    use rustls::server::Acceptor;
+    use rustls::{ClientConfig, ClientConnection, RootCertStore};
+    use sentinel_common::types::TlsVersion;
+    use sentinel_proxy::tls::TlsPolicy;
+
+    /// Listener without mTLS, with a partners host that requires client certs,
+    /// TLS 1.3 and h2-only ALPN
+    fn per_sni_policy_config() -> TlsConfig {
+        let fixtures = fixtures_path();
+        let mut config = minimal_tls_config();
+        config.additional_certs = vec![
+            SniCertificate {
+                hostnames: vec!["api.example.com".to_string()],
+                cert_file: fixtures.join("server-api.crt"),
+                key_file: fixtures.join("server-api.key"),
+                ..Default::default()
+            },
+            SniCertificate {
+                hostnames: vec!["secure.example.com".to_string()],
+                cert_file: fixtures.join("server-secure.crt"),
+                key_file: fixtures.join("server-secure.key"),
+                ca_file: Some(fixtures.join("ca.crt")),
+                client_auth: Some(true),
+                min_version: Some(TlsVersion::Tls13),
+                alpn_protocols: vec!["h2".to_string()],
+                ..Default::default()
+            },
+        ];
+        config
+    }
+
+    fn client_config(with_client_cert: bool) -> Arc<ClientConfig> {
+        let fixtures = fixtures_path();
+        let mut roots = RootCertStore::empty();
+        roots.add_parsable_certificates(
+            rustls_pemfile::certs(&mut std::io::BufReader::new(
+                std::fs::File::open(fixtures.join("ca.crt")).unwrap(),
+            ))
+            .map(|c| c.unwrap()),
+        );
+        let builder = ClientConfig::builder_with_provider(Arc::new(
+            rustls::crypto::aws_lc_rs::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots);
+
+        let mut config = if with_client_cert {
+            let certs = rustls_pemfile::certs(&mut std::io::BufReader::new(
+                std::fs::File::open(fixtures.join("client.crt")).unwrap(),
+            ))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+            let key = rustls_pemfile::private_key(&mut std::io::BufReader::new(
+                std::fs::File::open(fixtures.join("client.key")).unwrap(),
+            ))
+            .unwrap()
+            .unwrap();
+            builder.with_client_auth_cert(certs, key).unwrap()
+        } else {
+            builder.with_no_client_auth()
+        };
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Arc::new(config)
+    }
+
+    /// Run an in-memory handshake, selecting the server config from the ClientHello
+    fn handshake(
+        resolver: &SniResolver,
+        client: Arc<ClientConfig>,
+        server_name: &str,
+    ) -> Result<(Option<Vec<u8>>, Option<rustls::ProtocolVersion>), rustls::Error> {
+        let name = ServerName::try_from(server_name.to_string()).unwrap();
+        let mut client = ClientConnection::new(client, name)?;
+
+        // Read the ClientHello and pick the per-SNI configuration
+        let mut acceptor = Acceptor::default();
+        let accepted = loop {
+            let mut buf = Vec::new();
+            client.write_tls(&mut buf).unwrap();
+            acceptor.read_tls(&mut buf.as_slice()).unwrap();
+            if let Some(accepted) = acceptor.accept().map_err(|(e, _)| e)? {
+                break accepted;
+            }
+        };
+        let config = resolver.select_server_config(&accepted);
+        let mut server = accepted.into_connection(config).map_err(|(e, _)| e)?;
+
+        // Pump records until both sides finish the handshake
+        for _ in 0..16 {
+            let mut buf = Vec::new();
+            server.write_tls(&mut buf).unwrap();
+            client.read_tls(&mut buf.as_slice()).unwrap();
+            client.process_new_packets()?;
+
+            let mut buf = Vec::new();
+            client.write_tls(&mut buf).unwrap();
+            server.read_tls(&mut buf.as_slice()).unwrap();
+            server.process_new_packets()?;
+
+            if !client.is_handshaking() && !server.is_handshaking() {
+                return Ok((
+                    server.alpn_protocol().map(|p| p.to_vec()),
+                    server.protocol_version(),
+                ));
+            }
+        }
+        panic!("handshake did not complete");
+    }
+
+    #[test]
+    fn test_sni_policy_inherits_listener_settings() {
+        let config = per_sni_policy_config();
+        let policy = TlsPolicy::for_sni(&config, &config.additional_certs[0]);
+        assert_eq!(policy, TlsPolicy::from_listener(&config));
+    }
+
+    #[test]
+    fn test_sni_policy_overrides_listener_settings() {
+        let config = per_sni_policy_config();
+        let policy = TlsPolicy::for_sni(&config, &config.additional_certs[1]);
+        assert!(policy.client_auth);
+        assert_eq!(policy.min_version, TlsVersion::Tls13);
+        assert_eq!(policy.alpn_protocols, vec![b"h2".to_vec()]);
+        assert!(!TlsPolicy::from_listener(&config).client_auth);
+    }
+
+    #[test]
+    fn test_resolve_server_config_per_hostname() {
+        let resolver = SniResolver::from_config(&per_sni_policy_config()).unwrap();
+
+        let default = resolver.resolve_server_config(None);
+        let secure = resolver.resolve_server_config(Some("SECURE.example.com"));
+
+        assert_eq!(
+            default.alpn_protocols,
+            vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+        );
+        assert_eq!(secure.alpn_protocols, vec![b"h2".to_vec()]);
+        assert!(!Arc::ptr_eq(&default, &secure));
+    }
+
+    #[test]
+    fn test_mtls_required_only_for_overriding_host() {
+        let resolver = SniResolver::from_config(&per_sni_policy_config()).unwrap();
+
+        // No client certificate: accepted on api, rejected on secure
+        assert!(handshake(&resolver, client_config(false), "api.example.com").is_ok());
+        assert!(handshake(&resolver, client_config(false), "secure.example.com").is_err());
+
+        // With a client certificate the secure host accepts the connection
+        let (alpn, version) =
+            handshake(&resolver, client_config(true), "secure.example.com").unwrap();
+        assert_eq!(alpn, Some(b"h2".to_vec()));
+        assert_eq!(version, Some(rustls::ProtocolVersion::TLSv1_3));
+    }
+
+    #[test]
+    fn test_unknown_cipher_suite_rejected() {
+        let mut config = per_sni_policy_config();
+        config.additional_certs[0].cipher_suites = vec!["TLS_NOT_A_SUITE".to_string()];
+        let result = SniResolver::from_config(&config);
+        assert!(matches!(result, Err(TlsError::ConfigBuild(_))));
+    }
+
+    #[test]
+    fn test_inverted_version_range_rejected() {
+        let mut config = per_sni_policy_config();
+        config.additional_certs[0].min_version = Some(TlsVersion::Tls13);
+        config.additional_certs[0].max_version = Some(TlsVersion::Tls12);
+        assert!(SniResolver::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_sni_client_auth_without_ca_rejected() {
+        let mut config = per_sni_policy_config();
+        config.additional_certs[1].ca_file = None;
+        assert!(matches!(
+            SniResolver::from_config(&config),
+            Err(TlsError::ConfigBuild(_))
+        ));
+        assert!(validate_tls_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_plain_listener_served_by_pingora() {
+        let mut config = minimal_tls_config();
+        assert!(!sentinel_proxy::needs_sni_listener(&config));
+
+        config.client_auth = true;
+        assert!(sentinel_proxy::needs_sni_listener(&config));
+        assert!(sentinel_proxy::needs_sni_listener(&per_sni_policy_config()));
+    }
+
+    /// Handshake over TCP against the listener's accept path, returning the
+    /// server result and the ALPN protocol and version the client negotiated
+    async fn listener_handshake(
+        resolver: Arc<HotReloadableSniResolver>,
+        client: Arc<ClientConfig>,
+        server_name: &'static str,
+    ) -> (
+        std::io::Result<()>,
+        Option<Vec<u8>>,
+        Option<rustls::ProtocolVersion>,
+    ) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut stream = sentinel_proxy::accept_tls(&resolver, tcp).await?;
+            stream.write_all(b"ok").await?;
+            stream.flush().await
+        });
+
+        let tcp = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let name = ServerName::try_from(server_name).unwrap();
+        let mut alpn = None;
+        let mut version = None;
+        if let Ok(mut stream) = tokio_rustls::TlsConnector::from(client)
+            .connect(name, tcp)
+            .await
+        {
+            let (_, connection) = stream.get_ref();
+            alpn = connection.alpn_protocol().map(|p| p.to_vec());
+            version = connection.protocol_version();
+            let mut buf = [0u8; 2];
+            let _ = stream.read_exact(&mut buf).await;
+        }
+        (server.await.unwrap(), alpn, version)
+    }
+
+    #[tokio::test]
+    async fn test_listener_applies_per_sni_policy() {
+        let resolver =
+            Arc::new(HotReloadableSniResolver::from_config(per_sni_policy_config()).unwrap());
+
+        // The default policy accepts clients without a certificate
+        let (result, alpn, _) =
+            listener_handshake(resolver.clone(), client_config(false), "api.example.com").await;
+        assert!(result.is_ok());
+        assert_eq!(alpn, Some(b"h2".to_vec()));
+
+        // The secure host requires one
+        let (result, _, _) =
+            listener_handshake(resolver.clone(), client_config(false), "secure.example.com").await;
+        assert!(result.is_err());
+
+        let (result, alpn, version) =
+            listener_handshake(resolver, client_config(true), "secure.example.com").await;
+        assert!(result.is_ok());
+        assert_eq!(alpn, Some(b"h2".to_vec()));
+        assert_eq!(version, Some(rustls::ProtocolVersion::TLSv1_3));
+    }
+    #[tokio::test]
+    async fn test_listener_client_identity_from_peer_certificate() {
+        use pingora::protocols::Ssl;
+        use sentinel_proxy::ClientCertIdentity;
+
+        let resolver =
+            Arc::new(HotReloadableSniResolver::from_config(per_sni_policy_config()).unwrap());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let stream = sentinel_proxy::accept_tls(&resolver, tcp).await.unwrap();
+            // Same extraction as the request path in `request_filter`
+            stream
+                .get_ssl_digest()
+                .and_then(|digest| ClientCertIdentity::from_ssl_digest(&digest))
+        });
+
+        let tcp = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let name = ServerName::try_from("secure.example.com").unwrap();
+        let _stream = tokio_rustls::TlsConnector::from(client_config(true))
+            .connect(name, tcp)
+            .await
+            .unwrap();
+
+        let identity = server.await.unwrap().expect("client certificate identity");
+        let client_der = rustls_pemfile::certs(&mut std::io::BufReader::new(
+            std::fs::File::open(fixtures_path().join("client.crt")).unwrap(),
+        ))
+        .next()
+        .unwrap()
+        .unwrap();
+
+        // Full subject and issuer, not just the organization from the digest
+        assert!(identity.matches_subject("*CN=test-client"));
+        assert!(identity.info().issuer.is_some());
+        assert_eq!(
+            identity.fingerprint(),
+            hex::encode(<sha2::Sha256 as sha2::Digest>::digest(client_der.as_ref()))
+        );
+        assert!(identity.rfc9440_value().is_some());
+    }
+}
@@ -22,7 +22,11 @@ use std::collections::HashMap;
 
 use crate::types::{MatchedRoute, Warning};
 use crate::{simulate, RouteDecision, SimulatedRequest};
-use sentinel_config::Config;
+use sentinel_config::{AgentExecutionMode, Config};
+
+/// Hook name for request-header processing, the only phase agents can run in
+/// parallel
+const REQUEST_HEADERS_HOOK: &str = "on_request_headers";
 
 // ============================================================================
 // Input Types
@@ -48,6 +52,13 @@ pub struct MockAgentResponse {
     /// Audit metadata for logging
     #[serde(default)]
     pub audit: AuditInfo,
+
+    /// Simulated response latency in milliseconds
+    ///
+    /// Only used on routes with parallel agent execution (the default),
+    /// where it decides which blocking agent answers first.
+    #[serde(default)]
+    pub latency_ms: u64,
 }
 
 impl Default for MockAgentResponse {
@@ -58,6 +69,7 @@ impl Default for MockAgentResponse {
             request_headers: Vec::new(),
             response_headers: Vec::new(),
             audit: AuditInfo::default(),
+            latency_ms: 0,
         }
     }
 }
@@ -207,6 +219,11 @@ pub struct AgentChainStep {
 
     /// Whether this decision short-circuited the chain
     pub short_circuited: bool,
+
+    /// Whether the call was cancelled because another agent blocked first
+    /// (parallel execution only)
+    #[serde(default)]
+    pub cancelled: bool,
 }
 
 /// The request after transformations
@@ -338,6 +355,12 @@ struct AgentHookInfo {
 /// 4. Applies header mutations to build the transformed request
 /// 5. Combines decisions (first non-Allow wins)
 ///
+/// Unless the route sets `agent-execution "sequential"`, request-header agents
+/// run concurrently: the blocking agent with the lowest `latency_ms` wins
+/// (ties go to filter order), slower agents are reported as cancelled, and
+/// mutations and audit entries of the agents that answered are merged in
+/// filter order.
+///
 /// # Arguments
 ///
 /// * `config` - The parsed Sentinel configuration
@@ -373,11 +396,40 @@ pub fn simulate_with_agents(
     let mut blocking_agent = String::new();
     let mut warnings = base.warnings.clone();
 
-    // 5. Execute agent chain
+    // 5. In parallel mode the first request-headers agent to block wins
+    let first_block = if execution_mode(&base, config) == AgentExecutionMode::Parallel {
+        first_parallel_block(&agents_to_fire, &mock_map)
+    } else {
+        None
+    };
+    if let Some((winner, _)) = first_block {
+        final_decision = mock_map[agents_to_fire[winner].agent_id.as_str()]
+            .decision
+            .clone();
+        blocking_agent = agents_to_fire[winner].agent_id.clone();
+    }
+
+    // 6. Execute agent chain
     for agent_hook in &agents_to_fire {
         let mock_response = mock_map.get(agent_hook.agent_id.as_str());
 
-        let step = if let Some(mock) = mock_response {
+        // Agents still running when the winner blocked are cancelled
+        let cancelled = first_block.is_some_and(|(_, block_latency)| {
+            agent_hook.hook == REQUEST_HEADERS_HOOK
+                && mock_response.map_or(0, |mock| mock.latency_ms) > block_latency
+        });
+
+        let step = if cancelled {
+            AgentChainStep {
+                agent_id: agent_hook.agent_id.clone(),
+                agent_type: agent_hook.agent_type.clone(),
+                hook: agent_hook.hook.clone(),
+                decision: "cancelled".to_string(),
+                mutations_applied: 0,
+                short_circuited: false,
+                cancelled: true,
+            }
+        } else if let Some(mock) = mock_response {
             // Apply this agent's request header mutations
             let mutations = apply_header_mutations(&mut transformed, &mock.request_headers);
 
@@ -407,6 +459,7 @@ pub fn simulate_with_agents(
                 decision: mock.decision.to_string(),
                 mutations_applied: mutations,
                 short_circuited: short_circuit,
+                cancelled: false,
             }
         } else {
             // No mock provided - warn and assume allow
@@ -425,13 +478,14 @@ pub fn simulate_with_agents(
                 decision: "allow".to_string(),
                 mutations_applied: 0,
                 short_circuited: false,
+                cancelled: false,
             }
         };
 
         chain_steps.push(step);
     }
 
-    // 6. Build result
+    // 7. Build result
     let (block_response, redirect_url, redirect_status, challenge) =
         extract_decision_details(&final_decision, &blocking_agent);
 
@@ -454,6 +508,35 @@ pub fn simulate_with_agents(
 // Helper Functions
 // ============================================================================
 
+/// Get the agent execution mode of the matched route
+fn execution_mode(decision: &RouteDecision, config: &Config) -> AgentExecutionMode {
+    decision
+        .matched_route
+        .as_ref()
+        .and_then(|matched| config.routes.iter().find(|r| r.id == matched.id))
+        .map(|route| route.policies.agent_execution)
+        .unwrap_or_default()
+}
+
+/// Find the request-headers agent whose block arrives first in parallel mode
+///
+/// Returns the winner's position in the chain and its latency. Agents without
+/// a mock response are assumed to allow.
+fn first_parallel_block(
+    agents: &[AgentHookInfo],
+    mock_map: &HashMap<&str, &MockAgentResponse>,
+) -> Option<(usize, u64)> {
+    agents
+        .iter()
+        .enumerate()
+        .filter(|(_, hook)| hook.hook == REQUEST_HEADERS_HOOK)
+        .filter_map(|(index, hook)| {
+            let mock = mock_map.get(hook.agent_id.as_str())?;
+            (mock.decision != AgentDecision::Allow).then_some((index, mock.latency_ms))
+        })
+        .min_by_key(|&(index, latency)| (latency, index))
+}
+
 /// Get list of agents that should fire for this route
 fn get_agents_for_route(decision: &RouteDecision, config: &Config) -> Vec<AgentHookInfo> {
     decision
@@ -780,6 +863,7 @@ mod tests {
             }],
             response_headers: vec![],
             audit: AuditInfo::default(),
+            latency_ms: 0,
         }];
 
         let result = simulate_with_agents(&config, &request, &mock_responses);
@@ -843,6 +927,7 @@ mod tests {
                 confidence: Some(0.95),
                 reason_codes: vec![],
             },
+            latency_ms: 0,
         }];
 
         let result = simulate_with_agents(&config, &request, &mock_responses);
@@ -897,6 +982,7 @@ mod tests {
             request_headers: vec![],
             response_headers: vec![],
             audit: AuditInfo::default(),
+            latency_ms: 0,
         }];
 
         let result = simulate_with_agents(&config, &request, &mock_responses);
@@ -948,4 +1034,111 @@ mod tests {
             .iter()
             .any(|w| w.code == "MISSING_MOCK_RESPONSE"));
     }
+
+    #[test]
+    fn test_simulate_with_agents_parallel() {
+        let config_kdl = r#"
+            system {}
+            listeners { listener "http" { address "0.0.0.0:8080" } }
+            agents {
+                agent "waf" { unix-socket "/var/run/waf.sock" }
+                agent "auth" { unix-socket "/var/run/auth.sock" }
+                agent "bot" { unix-socket "/var/run/bot.sock" }
+            }
+            filters {
+                filter "waf-filter" {
+                    type "agent"
+                    agent "waf"
+                }
+                filter "auth-filter" {
+                    type "agent"
+                    agent "auth"
+                }
+                filter "bot-filter" {
+                    type "agent"
+                    agent "bot"
+                }
+            }
+            routes {
+                route "api" {
+                    matches { path-prefix "/api" }
+                    filters "waf-filter" "auth-filter" "bot-filter"
+                    upstream "backend"
+                    agent-execution "parallel"
+                }
+            }
+            upstreams { upstream "backend" { target "127.0.0.1:8080" } }
+        "#;
+
+        let config = sentinel_config::Config::from_kdl(config_kdl).unwrap();
+        let request = SimulatedRequest::new("GET", "example.com", "/api/users");
+
+        let block = |status| AgentDecision::Block {
+            status,
+            body: None,
+            headers: HashMap::new(),
+        };
+        let audit = |tag: &str| AuditInfo {
+            tags: vec![tag.to_string()],
+            ..Default::default()
+        };
+        let mock_responses = vec![
+            MockAgentResponse {
+                agent_id: "waf".to_string(),
+                decision: block(403),
+                audit: audit("sqli"),
+                latency_ms: 20,
+                ..Default::default()
+            },
+            MockAgentResponse {
+                agent_id: "auth".to_string(),
+                request_headers: vec![HeaderMutation::Set {
+                    name: "X-User-ID".to_string(),
+                    value: "12345".to_string(),
+                }],
+                audit: audit("authenticated"),
+                latency_ms: 5,
+                ..Default::default()
+            },
+            MockAgentResponse {
+                agent_id: "bot".to_string(),
+                decision: block(429),
+                audit: audit("bot"),
+                latency_ms: 50,
+                ..Default::default()
+            },
+        ];
+
+        let result = simulate_with_agents(&config, &request, &mock_responses);
+
+        // The WAF answers before the bot agent, so its block wins
+        assert_eq!(result.final_decision, "block");
+        let block_response = result.block_response.unwrap();
+        assert_eq!(block_response.status, 403);
+        assert_eq!(block_response.blocking_agent, "waf");
+
+        assert_eq!(result.agent_chain.len(), 3);
+        assert!(!result.agent_chain[1].cancelled);
+        assert!(result.agent_chain[2].cancelled);
+        assert_eq!(result.agent_chain[2].decision, "cancelled");
+
+        // Audit entries of agents that answered are kept in filter order
+        let audit_agents: Vec<_> = result
+            .audit_trail
+            .iter()
+            .map(|a| a.agent_id.as_str())
+            .collect();
+        assert_eq!(audit_agents, vec!["waf", "auth"]);
+        assert_eq!(
+            result.final_request.headers.get("x-user-id"),
+            Some(&"12345".to_string())
+        );
+
+        // With equal latencies, filter order breaks the tie
+        let mut tied = mock_responses.clone();
+        tied[2].latency_ms = 20;
+        let result = simulate_with_agents(&config, &request, &tied);
+        assert_eq!(result.block_response.unwrap().blocking_agent, "waf");
+        assert!(result.agent_chain.iter().all(|step| !step.cancelled));
+    }
 }
@@ -12,6 +12,7 @@
 //! - **Policy Preview**: Show applied policies, timeouts, and limits
 //! - **Upstream Selection**: Simulate load balancer behavior (deterministic)
 //! - **Agent Hooks**: Visualize which agents would fire and in what order
+//! - **Recorded Fixtures**: Load agent traffic recorded by the proxy as mock responses
 //!
 //! # WASM Usage
 //!
@@ -20,6 +21,7 @@
 
 pub mod agents;
 mod matcher;
+pub mod recordings;
 pub mod stateful;
 mod trace;
 mod types;
@@ -41,6 +43,7 @@ pub use agents::{
     simulate_with_agents, AgentChainStep, AgentDecision, AgentSimulationResult, AuditEntry,
     AuditInfo, BlockResponse, ChallengeInfo, HeaderMutation, MockAgentResponse, TransformedRequest,
 };
+pub use recordings::{mock_responses_from_recording, RecordingError};
 
 use sentinel_config::Config;
 
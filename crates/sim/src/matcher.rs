@@ -46,6 +46,8 @@ enum CompiledMatcher {
     Method(Vec<String>),
     /// Query parameter match
     QueryParam { name: String, value: Option<String> },
+    /// Client certificate match (simulated requests never carry a certificate)
+    ClientCert { kind: &'static str, pattern: String },
 }
 
 /// Host matching variants
@@ -276,6 +278,18 @@ impl CompiledMatcher {
                 name: name.clone(),
                 value: value.clone(),
             },
+            MatchCondition::ClientCertSubject(pattern) => Self::ClientCert {
+                kind: "ClientCertSubject",
+                pattern: pattern.clone(),
+            },
+            MatchCondition::ClientCertSan(pattern) => Self::ClientCert {
+                kind: "ClientCertSan",
+                pattern: pattern.clone(),
+            },
+            MatchCondition::ClientCertFingerprint(pattern) => Self::ClientCert {
+                kind: "ClientCertFingerprint",
+                pattern: pattern.clone(),
+            },
         })
     }
 
@@ -301,6 +315,7 @@ impl CompiledMatcher {
                     false
                 }
             }
+            Self::ClientCert { .. } => false,
         }
     }
 
@@ -358,6 +373,9 @@ impl CompiledMatcher {
                     ConditionDetail::query_param(name, value.as_deref(), actual, matched),
                 )
             }
+            Self::ClientCert { kind, pattern } => {
+                (false, ConditionDetail::client_cert(kind, pattern))
+            }
         }
     }
 }
@@ -0,0 +1,217 @@
+//! Agent recordings as simulation fixtures
+//!
+//! The proxy can record a sample of the events it sends to an agent and the
+//! responses it gets back (see the agent `record` block). This module turns
+//! those JSON Lines recordings into [`MockAgentResponse`] fixtures, so a
+//! decision seen in production can be replayed through [`simulate_with_agents`].
+//!
+//! Recordings use the agent protocol's wire format for decisions and header
+//! operations; it is parsed here directly to keep this crate WASM-compatible.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let recording = std::fs::read_to_string("/var/lib/sentinel/recordings/waf/000042.jsonl")?;
+//! let mock_responses = mock_responses_from_recording(&recording)?;
+//! let result = simulate_with_agents(&config, &request, &mock_responses);
+//! ```
+//!
+//! [`simulate_with_agents`]: crate::simulate_with_agents
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::agents::{AgentDecision, AuditInfo, HeaderMutation, MockAgentResponse};
+
+/// Event type the simulator's agent chain corresponds to
+const REQUEST_HEADERS_EVENT: &str = "request_headers";
+
+/// Recording parse error
+#[derive(Debug, thiserror::Error)]
+pub enum RecordingError {
+    #[error("Invalid recording on line {line}: {error}")]
+    InvalidLine { line: usize, error: String },
+}
+
+/// One recorded exchange, reduced to the fields a fixture needs
+#[derive(Debug, Deserialize)]
+struct RecordedExchange {
+    agent_id: String,
+    event_type: String,
+    #[serde(default)]
+    response: Option<RecordedResponse>,
+    #[serde(default)]
+    duration_ms: u64,
+}
+
+/// Agent response as recorded
+#[derive(Debug, Deserialize)]
+struct RecordedResponse {
+    decision: RecordedDecision,
+    #[serde(default)]
+    request_headers: Vec<RecordedHeaderOp>,
+    #[serde(default)]
+    response_headers: Vec<RecordedHeaderOp>,
+    #[serde(default)]
+    audit: AuditInfo,
+}
+
+/// Decision in the agent protocol's wire format
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RecordedDecision {
+    Allow,
+    Block {
+        status: u16,
+        body: Option<String>,
+        headers: Option<HashMap<String, String>>,
+    },
+    Redirect {
+        url: String,
+        status: u16,
+    },
+    Challenge {
+        challenge_type: String,
+        #[serde(default)]
+        params: HashMap<String, String>,
+    },
+}
+
+impl From<RecordedDecision> for AgentDecision {
+    fn from(decision: RecordedDecision) -> Self {
+        match decision {
+            RecordedDecision::Allow => AgentDecision::Allow,
+            RecordedDecision::Block {
+                status,
+                body,
+                headers,
+            } => AgentDecision::Block {
+                status,
+                body,
+                headers: headers.unwrap_or_default(),
+            },
+            RecordedDecision::Redirect { url, status } => AgentDecision::Redirect { url, status },
+            RecordedDecision::Challenge {
+                challenge_type,
+                params,
+            } => AgentDecision::Challenge {
+                challenge_type,
+                params,
+            },
+        }
+    }
+}
+
+/// Header operation in the agent protocol's wire format
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RecordedHeaderOp {
+    Set { name: String, value: String },
+    Add { name: String, value: String },
+    Remove { name: String },
+}
+
+impl From<RecordedHeaderOp> for HeaderMutation {
+    fn from(op: RecordedHeaderOp) -> Self {
+        match op {
+            RecordedHeaderOp::Set { name, value } => HeaderMutation::Set { name, value },
+            RecordedHeaderOp::Add { name, value } => HeaderMutation::Add { name, value },
+            RecordedHeaderOp::Remove { name } => HeaderMutation::Remove { name },
+        }
+    }
+}
+
+/// Load mock agent responses from a recording
+///
+/// Takes the contents of one or more recording files (concatenated JSON
+/// Lines). Each agent's first answered `request_headers` exchange becomes its
+/// fixture, with the recorded call duration as `latency_ms`. Failed or
+/// timed-out calls and other event types are skipped, since the simulator
+/// only models the request-header phase.
+pub fn mock_responses_from_recording(
+    recording: &str,
+) -> Result<Vec<MockAgentResponse>, RecordingError> {
+    let mut responses: Vec<MockAgentResponse> = Vec::new();
+
+    for (index, line) in recording.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let exchange: RecordedExchange =
+            serde_json::from_str(line).map_err(|e| RecordingError::InvalidLine {
+                line: index + 1,
+                error: e.to_string(),
+            })?;
+
+        if exchange.event_type != REQUEST_HEADERS_EVENT
+            || responses.iter().any(|r| r.agent_id == exchange.agent_id)
+        {
+            continue;
+        }
+        let Some(response) = exchange.response else {
+            continue;
+        };
+
+        responses.push(MockAgentResponse {
+            agent_id: exchange.agent_id,
+            decision: response.decision.into(),
+            request_headers: response
+                .request_headers
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            response_headers: response
+                .response_headers
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            audit: response.audit,
+            latency_ms: exchange.duration_ms,
+        });
+    }
+
+    Ok(responses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECORDING: &str = r#"
+{"agent_id":"waf","correlation_id":"req-1","timestamp":"2026-01-01T00:00:00Z","event_type":"request_headers","event":{},"redacted":false,"response":{"version":1,"decision":{"block":{"status":403,"body":"Forbidden","headers":null}},"request_headers":[{"set":{"name":"X-WAF","value":"hit"}}],"audit":{"tags":["sqli"],"rule_ids":["942100"],"confidence":0.9,"reason_codes":[],"custom":{}}},"duration_ms":12}
+{"agent_id":"waf","correlation_id":"req-1","timestamp":"2026-01-01T00:00:00Z","event_type":"request_body_chunk","event":{"data":""},"redacted":true,"response":{"version":1,"decision":"allow"},"duration_ms":3}
+{"agent_id":"auth","correlation_id":"req-1","timestamp":"2026-01-01T00:00:00Z","event_type":"request_headers","event":{},"error":"timeout","duration_ms":100}
+"#;
+
+    #[test]
+    fn test_mock_responses_from_recording() {
+        let responses = mock_responses_from_recording(RECORDING).unwrap();
+
+        assert_eq!(responses.len(), 1);
+        let waf = &responses[0];
+        assert_eq!(waf.agent_id, "waf");
+        assert_eq!(
+            waf.decision,
+            AgentDecision::Block {
+                status: 403,
+                body: Some("Forbidden".to_string()),
+                headers: HashMap::new(),
+            }
+        );
+        assert_eq!(
+            waf.request_headers,
+            vec![HeaderMutation::Set {
+                name: "X-WAF".to_string(),
+                value: "hit".to_string(),
+            }]
+        );
+        assert_eq!(waf.audit.rule_ids, vec!["942100".to_string()]);
+        assert_eq!(waf.latency_ms, 12);
+    }
+
+    #[test]
+    fn test_invalid_recording_line() {
+        let err = mock_responses_from_recording("{\"agent_id\":\"waf\"}\nnot json").unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+    }
+}
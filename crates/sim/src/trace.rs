@@ -272,6 +272,22 @@ impl ConditionDetail {
             },
         }
     }
+
+    /// Create a client certificate condition detail
+    ///
+    /// Simulated requests are plain HTTP and never present a client
+    /// certificate, so these conditions never match.
+    pub fn client_cert(condition_type: &str, pattern: &str) -> Self {
+        Self {
+            condition_type: condition_type.to_string(),
+            pattern: pattern.to_string(),
+            matched: false,
+            actual_value: None,
+            explanation: Some(
+                "Simulated requests do not carry a client certificate".to_string(),
+            ),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -407,6 +407,8 @@ mod tests {
             timeouts: Default::default(),
             tls: None,
             http_version: Default::default(),
+            secret: None,
+            aws_sigv4: None,
         }
     }
 
@@ -0,0 +1,580 @@
+//! Agent process configuration parsed from the Sentinel KDL file.
+//!
+//! The stack reads the same `agents` block as the proxy. Keys the proxy does
+//! not understand (`command`, `restart-policy`, `depends-on`, `readiness`, ...)
+//! are ignored by the proxy parser, so one file drives both.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+/// Agent configuration for sentinel-stack
+#[derive(Debug, Clone)]
+pub struct AgentConfig {
+    pub id: String,
+    pub command: Vec<String>,
+    pub restart_policy: RestartPolicy,
+    /// Initial restart delay; doubles with each restart inside the crash-loop window
+    pub restart_delay_ms: u64,
+    /// Upper bound for the exponential restart delay
+    pub max_restart_delay_ms: u64,
+    pub max_restarts: u32,
+    /// Restarts within `crash_loop_window` that mark the agent as crash-looping
+    pub crash_loop_restarts: u32,
+    pub crash_loop_window: Duration,
+    /// Agents that must be ready before this one is started
+    pub depends_on: Vec<String>,
+    pub readiness: Option<ReadinessConfig>,
+    pub logs: AgentLogConfig,
+    pub env: HashMap<String, String>,
+}
+
+impl AgentConfig {
+    /// Restart delay after `recent_restarts` restarts inside the crash-loop window
+    pub fn restart_delay(&self, recent_restarts: u32) -> Duration {
+        let factor = 1u64 << recent_restarts.min(16);
+        let delay = self
+            .restart_delay_ms
+            .saturating_mul(factor)
+            .min(self.max_restart_delay_ms.max(self.restart_delay_ms));
+        Duration::from_millis(delay)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RestartPolicy {
+    Always,
+    OnFailure,
+    Never,
+}
+
+/// How to decide that an agent is ready to serve the proxy
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadinessProbe {
+    /// A Unix socket at this path accepts connections
+    Socket(PathBuf),
+    /// An HTTP/2 (gRPC) connection handshake succeeds at this address
+    Grpc(String),
+    /// `GET` on this URL returns a 2xx status
+    Http(String),
+}
+
+/// Readiness probe with its timing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadinessConfig {
+    pub probe: ReadinessProbe,
+    pub interval: Duration,
+    /// Overrides `--startup-timeout` for this agent
+    pub timeout: Option<Duration>,
+}
+
+impl ReadinessConfig {
+    fn new(probe: ReadinessProbe) -> Self {
+        Self {
+            probe,
+            interval: Duration::from_millis(200),
+            timeout: None,
+        }
+    }
+}
+
+/// Per-agent output handling
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentLogConfig {
+    /// Lines below this level are dropped from the combined stream and log file
+    pub level: Option<LogLevel>,
+    /// Optional file receiving the agent's output as JSON lines
+    pub file: Option<PathBuf>,
+    /// Rotate the file once it would exceed this size
+    pub max_size_bytes: u64,
+    /// Rotated files to keep (`<file>.1` .. `<file>.N`)
+    pub max_files: usize,
+}
+
+impl Default for AgentLogConfig {
+    fn default() -> Self {
+        Self {
+            level: None,
+            file: None,
+            max_size_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+        }
+    }
+}
+
+/// Severity of an agent output line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// Parse a level name as written by common loggers (`WARN`, `warning`, `err`, ...)
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "trace" => Some(LogLevel::Trace),
+            "debug" => Some(LogLevel::Debug),
+            "info" | "notice" => Some(LogLevel::Info),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "error" | "err" | "fatal" | "critical" | "panic" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+}
+
+/// Helper to get string from first entry
+fn get_first_string(node: &kdl::KdlNode) -> Option<String> {
+    node.entries()
+        .first()
+        .and_then(|e| e.value().as_string())
+        .map(|s| s.to_string())
+}
+
+/// Helper to get a named (`key=value`) or first positional string entry
+fn get_string_entry(node: &kdl::KdlNode, name: &str) -> Option<String> {
+    node.entries()
+        .iter()
+        .find(|e| e.name().map(|n| n.value()) == Some(name))
+        .or_else(|| node.entries().iter().find(|e| e.name().is_none()))
+        .and_then(|e| e.value().as_string())
+        .map(|s| s.to_string())
+}
+
+/// Helper to get string from child node
+fn get_child_string(node: &kdl::KdlNode, name: &str) -> Option<String> {
+    node.children()
+        .and_then(|c| c.get(name))
+        .and_then(|n| n.entries().first())
+        .and_then(|e| e.value().as_string())
+        .map(|s| s.to_string())
+}
+
+/// Helper to get integer from child node
+fn get_child_int(node: &kdl::KdlNode, name: &str) -> Option<i64> {
+    node.children()
+        .and_then(|c| c.get(name))
+        .and_then(|n| n.entries().first())
+        .and_then(|e| e.value().as_integer())
+        .map(|v| v as i64)
+}
+
+/// Readiness derived from the agent's proxy transport when no explicit
+/// `readiness` block is given
+fn transport_readiness(node: &kdl::KdlNode) -> Option<ReadinessProbe> {
+    let children = node.children()?;
+    if let Some(socket) = children.get("unix-socket") {
+        return get_string_entry(socket, "path").map(|p| ReadinessProbe::Socket(p.into()));
+    }
+    if let Some(grpc) = children.get("grpc") {
+        return get_string_entry(grpc, "address").map(ReadinessProbe::Grpc);
+    }
+    None
+}
+
+/// Parse an explicit `readiness { ... }` block
+///
+/// A block without `socket`, `grpc` or `http` probes the agent's transport.
+fn parse_readiness(
+    id: &str,
+    node: &kdl::KdlNode,
+    transport: Option<ReadinessProbe>,
+) -> Result<ReadinessConfig> {
+    let mut probe = None;
+    for child in node.children().map(|c| c.nodes()).unwrap_or_default() {
+        let kind = child.name().value();
+        let value = || {
+            get_first_string(child)
+                .with_context(|| format!("Agent '{}': readiness '{}' requires a value", id, kind))
+        };
+        match kind {
+            "socket" => probe = Some(ReadinessProbe::Socket(value()?.into())),
+            "grpc" => probe = Some(ReadinessProbe::Grpc(value()?)),
+            "http" => {
+                let url = value()?;
+                if !url.starts_with("http://") {
+                    bail!(
+                        "Agent '{}': readiness http probe must be an http:// URL, got '{}'",
+                        id,
+                        url
+                    );
+                }
+                probe = Some(ReadinessProbe::Http(url));
+            }
+            _ => {}
+        }
+    }
+
+    let probe = probe
+        .or(transport)
+        .with_context(|| format!("Agent '{}': readiness requires socket, grpc or http", id))?;
+
+    let mut readiness = ReadinessConfig::new(probe);
+    if let Some(interval) = get_child_int(node, "interval-ms") {
+        readiness.interval = Duration::from_millis(interval.max(10) as u64);
+    }
+    if let Some(timeout) = get_child_int(node, "timeout-secs") {
+        readiness.timeout = Some(Duration::from_secs(timeout as u64));
+    }
+    Ok(readiness)
+}
+
+/// Parse a `logs { ... }` block
+fn parse_logs(id: &str, node: &kdl::KdlNode) -> Result<AgentLogConfig> {
+    let mut logs = AgentLogConfig::default();
+
+    if let Some(level) = get_child_string(node, "level") {
+        logs.level = Some(
+            LogLevel::parse(&level)
+                .with_context(|| format!("Agent '{}': unknown log level '{}'", id, level))?,
+        );
+    }
+    logs.file = get_child_string(node, "file").map(PathBuf::from);
+    if let Some(size) = get_child_int(node, "max-size-mb") {
+        if size <= 0 {
+            bail!("Agent '{}': logs max-size-mb must be positive", id);
+        }
+        logs.max_size_bytes = size as u64 * 1024 * 1024;
+    }
+    if let Some(files) = get_child_int(node, "max-files") {
+        logs.max_files = files.max(0) as usize;
+    }
+
+    Ok(logs)
+}
+
+fn parse_agent(node: &kdl::KdlNode) -> Result<AgentConfig> {
+    // Get agent ID from first argument
+    let id = get_first_string(node).unwrap_or_else(|| "unknown".to_string());
+
+    // Parse command if present
+    let mut command = Vec::new();
+    if let Some(node_children) = node.children() {
+        if let Some(cmd_node) = node_children.get("command") {
+            for entry in cmd_node.entries() {
+                if let Some(s) = entry.value().as_string() {
+                    command.push(s.to_string());
+                }
+            }
+        }
+    }
+
+    // Parse restart policy
+    let restart_policy = get_child_string(node, "restart-policy")
+        .map(|s| match s.as_str() {
+            "always" => RestartPolicy::Always,
+            "on-failure" => RestartPolicy::OnFailure,
+            "never" => RestartPolicy::Never,
+            _ => RestartPolicy::OnFailure,
+        })
+        .unwrap_or(RestartPolicy::OnFailure);
+
+    // Parse restart delay and backoff
+    let restart_delay_ms = get_child_int(node, "restart-delay-ms").unwrap_or(1000) as u64;
+    let max_restart_delay_ms = get_child_int(node, "max-restart-delay-ms").unwrap_or(30_000) as u64;
+
+    // Parse max restarts
+    let max_restarts = get_child_int(node, "max-restarts").unwrap_or(0) as u32;
+
+    // Parse crash-loop detection
+    let crash_loop_restarts = get_child_int(node, "crash-loop-restarts").unwrap_or(5) as u32;
+    let crash_loop_window =
+        Duration::from_secs(get_child_int(node, "crash-loop-window-secs").unwrap_or(60) as u64);
+
+    // Parse dependencies and readiness
+    let mut depends_on = Vec::new();
+    let mut readiness = None;
+    if let Some(node_children) = node.children() {
+        for dep_node in node_children
+            .nodes()
+            .iter()
+            .filter(|n| n.name().value() == "depends-on")
+        {
+            for entry in dep_node.entries() {
+                if let Some(s) = entry.value().as_string() {
+                    depends_on.push(s.to_string());
+                }
+            }
+        }
+
+        if let Some(readiness_node) = node_children.get("readiness") {
+            readiness = Some(parse_readiness(
+                &id,
+                readiness_node,
+                transport_readiness(node),
+            )?);
+        }
+    }
+    if readiness.is_none() {
+        readiness = transport_readiness(node).map(ReadinessConfig::new);
+    }
+
+    // Parse output handling
+    let logs = match node.children().and_then(|c| c.get("logs")) {
+        Some(logs_node) => parse_logs(&id, logs_node)?,
+        None => AgentLogConfig::default(),
+    };
+
+    // Parse environment
+    let mut env = HashMap::new();
+    if let Some(node_children) = node.children() {
+        if let Some(env_node) = node_children.get("env") {
+            if let Some(env_children) = env_node.children() {
+                for env_entry in env_children.nodes() {
+                    let key = env_entry.name().value().to_string();
+                    if let Some(value_str) = get_first_string(env_entry) {
+                        // Expand environment variables
+                        let expanded = if value_str.starts_with("${") && value_str.ends_with("}") {
+                            let var_name = &value_str[2..value_str.len() - 1];
+                            std::env::var(var_name).unwrap_or_default()
+                        } else {
+                            value_str
+                        };
+                        env.insert(key, expanded);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(AgentConfig {
+        id,
+        command,
+        restart_policy,
+        restart_delay_ms,
+        max_restart_delay_ms,
+        max_restarts,
+        crash_loop_restarts,
+        crash_loop_window,
+        depends_on,
+        readiness,
+        logs,
+        env,
+    })
+}
+
+/// Parse agent configurations from KDL source
+pub fn parse_agent_configs_str(content: &str) -> Result<Vec<AgentConfig>> {
+    let doc: kdl::KdlDocument = content.parse().context("Failed to parse KDL config")?;
+
+    let mut agents = Vec::new();
+
+    // Find agents section
+    if let Some(agents_node) = doc.get("agents") {
+        if let Some(children) = agents_node.children() {
+            for node in children.nodes() {
+                if node.name().value() == "agent" {
+                    let agent = parse_agent(node)?;
+                    if !agent.command.is_empty() {
+                        agents.push(agent);
+                    }
+                }
+            }
+        }
+    }
+
+    // Fail early on unknown dependencies and cycles
+    startup_order(&agents)?;
+
+    Ok(agents)
+}
+
+/// Parse agent configurations from the sentinel config
+pub fn parse_agent_configs(config_path: &Path) -> Result<Vec<AgentConfig>> {
+    // Read and parse the KDL config
+    let content = std::fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read config: {:?}", config_path))?;
+    parse_agent_configs_str(&content)
+}
+
+/// Group agents into startup stages by `depends-on`
+///
+/// Every agent in a stage only depends on agents in earlier stages, so a
+/// stage can be started as soon as the previous one is ready. Returns the
+/// agent IDs of each stage, in config order within a stage.
+pub fn startup_order(agents: &[AgentConfig]) -> Result<Vec<Vec<String>>> {
+    let ids: HashSet<&str> = agents.iter().map(|a| a.id.as_str()).collect();
+    for agent in agents {
+        for dep in &agent.depends_on {
+            if !ids.contains(dep.as_str()) {
+                bail!(
+                    "Agent '{}' depends on '{}', which is not a managed agent",
+                    agent.id,
+                    dep
+                );
+            }
+        }
+    }
+
+    let mut started: HashSet<&str> = HashSet::new();
+    let mut stages = Vec::new();
+    while started.len() < agents.len() {
+        let stage: Vec<&str> = agents
+            .iter()
+            .filter(|a| !started.contains(a.id.as_str()))
+            .filter(|a| a.depends_on.iter().all(|d| started.contains(d.as_str())))
+            .map(|a| a.id.as_str())
+            .collect();
+
+        if stage.is_empty() {
+            let mut cycle: Vec<&str> = agents
+                .iter()
+                .map(|a| a.id.as_str())
+                .filter(|id| !started.contains(id))
+                .collect();
+            cycle.sort_unstable();
+            bail!("Dependency cycle between agents: {}", cycle.join(", "));
+        }
+
+        started.extend(stage.iter().copied());
+        stages.push(stage.into_iter().map(String::from).collect());
+    }
+
+    Ok(stages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_readiness_and_dependencies() {
+        let agents = parse_agent_configs_str(
+            r#"
+            agents {
+                agent "auth" {
+                    unix-socket path="/tmp/auth.sock"
+                    command "/bin/auth-agent"
+                }
+                agent "waf" {
+                    grpc address="http://127.0.0.1:50051"
+                    command "/bin/waf-agent"
+                    depends-on "auth"
+                    readiness {
+                        http "http://127.0.0.1:9090/ready"
+                        interval-ms 50
+                        timeout-secs 3
+                    }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let auth = &agents[0];
+        assert_eq!(
+            auth.readiness.as_ref().unwrap().probe,
+            ReadinessProbe::Socket("/tmp/auth.sock".into())
+        );
+
+        let waf = &agents[1];
+        assert_eq!(waf.depends_on, vec!["auth".to_string()]);
+        let readiness = waf.readiness.as_ref().unwrap();
+        assert_eq!(
+            readiness.probe,
+            ReadinessProbe::Http("http://127.0.0.1:9090/ready".to_string())
+        );
+        assert_eq!(readiness.interval, Duration::from_millis(50));
+        assert_eq!(readiness.timeout, Some(Duration::from_secs(3)));
+
+        assert_eq!(
+            startup_order(&agents).unwrap(),
+            vec![vec!["auth".to_string()], vec!["waf".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_startup_order_rejects_cycles_and_unknown_agents() {
+        let cyclic = r#"
+            agents {
+                agent "a" { command "a"; depends-on "b"; }
+                agent "b" { command "b"; depends-on "a"; }
+            }
+        "#;
+        let err = parse_agent_configs_str(cyclic).unwrap_err();
+        assert!(err.to_string().contains("cycle"));
+
+        let unknown = r#"
+            agents {
+                agent "a" { command "a"; depends-on "missing"; }
+            }
+        "#;
+        let err = parse_agent_configs_str(unknown).unwrap_err();
+        assert!(err.to_string().contains("not a managed agent"));
+    }
+
+    #[test]
+    fn test_parse_logs() {
+        let agent = parse_agent_configs_str(
+            r#"
+            agents {
+                agent "a" {
+                    command "a"
+                    logs {
+                        level "warning"
+                        file "/var/log/sentinel/a.log"
+                        max-size-mb 2
+                        max-files 3
+                    }
+                }
+            }
+            "#,
+        )
+        .unwrap()
+        .remove(0);
+
+        assert_eq!(
+            agent.logs,
+            AgentLogConfig {
+                level: Some(LogLevel::Warn),
+                file: Some("/var/log/sentinel/a.log".into()),
+                max_size_bytes: 2 * 1024 * 1024,
+                max_files: 3,
+            }
+        );
+
+        let err = parse_agent_configs_str(
+            r#"agents { agent "a" { command "a"; logs { level "loud"; }; }; }"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown log level"));
+    }
+
+    #[test]
+    fn test_restart_delay_backoff() {
+        let agent = parse_agent_configs_str(
+            r#"
+            agents {
+                agent "a" {
+                    command "a"
+                    restart-delay-ms 100
+                    max-restart-delay-ms 1000
+                }
+            }
+            "#,
+        )
+        .unwrap()
+        .remove(0);
+
+        assert_eq!(agent.restart_delay(0), Duration::from_millis(100));
+        assert_eq!(agent.restart_delay(1), Duration::from_millis(200));
+        assert_eq!(agent.restart_delay(3), Duration::from_millis(800));
+        assert_eq!(agent.restart_delay(4), Duration::from_millis(1000));
+        assert_eq!(agent.restart_delay(40), Duration::from_millis(1000));
+    }
+}
@@ -0,0 +1,358 @@
+//! Local control socket for supervising a running stack.
+//!
+//! The protocol is one JSON request line per connection, answered with one
+//! JSON response line:
+//!
+//! ```text
+//! -> {"command":"restart","agent":"waf-agent"}
+//! <- {"ok":true,"agents":[{"id":"waf-agent","state":"starting",...}]}
+//! ```
+//!
+//! The socket lives in a directory only the user running sentinel-stack can
+//! access (`0700`, checked before binding), so no other user can connect to
+//! it, even in the window before the socket itself is restricted to `0600`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tracing::{debug, info, warn};
+
+use crate::supervisor::{AgentStatus, Supervisor};
+
+/// File name of the control socket inside its private directory
+const CONTROL_SOCKET_NAME: &str = "control.sock";
+
+/// Default control socket path
+///
+/// `$XDG_RUNTIME_DIR/sentinel-stack/control.sock`, or a per-user directory in
+/// the system temp dir when no runtime directory is set.
+pub fn default_control_socket() -> PathBuf {
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) => PathBuf::from(runtime_dir).join("sentinel-stack"),
+        None => std::env::temp_dir().join(format!("sentinel-stack-{}", current_uid())),
+    };
+    dir.join(CONTROL_SOCKET_NAME)
+}
+
+fn current_uid() -> u32 {
+    // SAFETY: getuid has no preconditions and cannot fail
+    unsafe { libc::getuid() }
+}
+
+/// Create the socket's parent directory with mode `0700`, or check that an
+/// existing one is owned by us and not accessible by other users
+fn ensure_private_dir(dir: &Path) -> Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => return Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(e) => {
+            return Err(e).with_context(|| {
+                format!(
+                    "Failed to create control socket directory {}",
+                    dir.display()
+                )
+            })
+        }
+    }
+
+    let metadata = std::fs::symlink_metadata(dir)
+        .with_context(|| format!("Failed to inspect {}", dir.display()))?;
+    if !metadata.is_dir() {
+        bail!(
+            "Control socket directory {} is not a directory",
+            dir.display()
+        );
+    }
+    if metadata.uid() != current_uid() {
+        bail!(
+            "Control socket directory {} is owned by another user",
+            dir.display()
+        );
+    }
+    if metadata.mode() & 0o077 != 0 {
+        bail!(
+            "Control socket directory {} is accessible by other users (mode {:o}); use a directory with mode 0700",
+            dir.display(),
+            metadata.mode() & 0o777
+        );
+    }
+    Ok(())
+}
+
+/// Request sent to the control socket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum ControlRequest {
+    Status,
+    Restart { agent: String },
+    Logs { agent: String, lines: usize },
+}
+
+/// Response from the control socket
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<AgentStatus>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<String>,
+}
+
+impl ControlResponse {
+    fn error(error: impl std::fmt::Display) -> Self {
+        Self {
+            ok: false,
+            error: Some(error.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Control socket bound by a running stack; removes the socket file on drop
+pub struct ControlServer {
+    path: PathBuf,
+    listener: UnixListener,
+}
+
+impl ControlServer {
+    /// Bind the control socket, replacing a stale socket file
+    ///
+    /// The parent directory must be private to the current user; it is
+    /// created with mode `0700` if missing.
+    pub async fn bind(path: &Path) -> Result<Self> {
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        ensure_private_dir(dir)?;
+
+        if path.exists() {
+            if UnixStream::connect(path).await.is_ok() {
+                bail!(
+                    "Control socket {} is in use; is another sentinel-stack running?",
+                    path.display()
+                );
+            }
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+        }
+
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to bind control socket {}", path.display()))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+
+        info!(path = %path.display(), "Control socket listening");
+        Ok(Self {
+            path: path.to_path_buf(),
+            listener,
+        })
+    }
+
+    /// Serve control requests until the task is dropped
+    pub async fn serve(self, supervisor: Arc<Supervisor>) {
+        loop {
+            match self.listener.accept().await {
+                Ok((stream, _)) => {
+                    let supervisor = supervisor.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream, &supervisor).await {
+                            debug!(error = %e, "Control connection failed");
+                        }
+                    });
+                }
+                Err(e) => warn!(error = %e, "Failed to accept control connection"),
+            }
+        }
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+async fn handle_connection(stream: UnixStream, supervisor: &Supervisor) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+
+    let response = match serde_json::from_str::<ControlRequest>(&line) {
+        Ok(request) => {
+            debug!(?request, "Control request");
+            dispatch(request, supervisor).await
+        }
+        Err(e) => ControlResponse::error(format!("Invalid request: {}", e)),
+    };
+
+    let mut body = serde_json::to_vec(&response)?;
+    body.push(b'\n');
+    writer.write_all(&body).await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+async fn dispatch(request: ControlRequest, supervisor: &Supervisor) -> ControlResponse {
+    match request {
+        ControlRequest::Status => ControlResponse {
+            ok: true,
+            agents: supervisor.status().await,
+            ..Default::default()
+        },
+        ControlRequest::Restart { agent } => match supervisor.restart(&agent).await {
+            Ok(status) => ControlResponse {
+                ok: true,
+                agents: vec![status],
+                ..Default::default()
+            },
+            Err(e) => ControlResponse::error(format!("{:#}", e)),
+        },
+        ControlRequest::Logs { agent, lines } => match supervisor.logs(&agent, lines).await {
+            Ok(lines) => ControlResponse {
+                ok: true,
+                lines,
+                ..Default::default()
+            },
+            Err(e) => ControlResponse::error(format!("{:#}", e)),
+        },
+    }
+}
+
+/// Send a request to a running stack
+pub async fn send(path: &Path, request: &ControlRequest) -> Result<ControlResponse> {
+    let stream = UnixStream::connect(path).await.with_context(|| {
+        format!(
+            "Failed to connect to {}; is sentinel-stack running?",
+            path.display()
+        )
+    })?;
+    let (reader, mut writer) = stream.into_split();
+
+    let mut body = serde_json::to_vec(request)?;
+    body.push(b'\n');
+    writer.write_all(&body).await?;
+
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+    let response: ControlResponse =
+        serde_json::from_str(&line).context("Invalid response from control socket")?;
+
+    if !response.ok {
+        bail!(
+            "{}",
+            response
+                .error
+                .unwrap_or_else(|| "request failed".to_string())
+        );
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_control_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("sentinel-stack-ctl-{}", std::process::id()));
+        let path = dir.join("control.sock");
+
+        let configs = crate::config::parse_agent_configs_str(
+            r#"
+            agents {
+                agent "echo" {
+                    command "sh" "-c" "echo hello; sleep 30"
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let supervisor = Arc::new(Supervisor::new(configs, Duration::from_secs(1)).unwrap());
+        supervisor.start_all().await.unwrap();
+
+        let server = ControlServer::bind(&path).await.unwrap();
+        let serving = tokio::spawn(server.serve(supervisor.clone()));
+
+        let status = send(&path, &ControlRequest::Status).await.unwrap();
+        assert_eq!(status.agents.len(), 1);
+        assert_eq!(status.agents[0].id, "echo");
+        assert!(status.agents[0].pid.is_some());
+
+        let mut lines = Vec::new();
+        for _ in 0..50 {
+            let request = ControlRequest::Logs {
+                agent: "echo".to_string(),
+                lines: 10,
+            };
+            lines = send(&path, &request).await.unwrap().lines;
+            if !lines.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(lines, vec!["hello".to_string()]);
+
+        let err = send(
+            &path,
+            &ControlRequest::Restart {
+                agent: "missing".to_string(),
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Unknown agent"));
+
+        serving.abort();
+        supervisor.stop_all().await;
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_bind_rejects_shared_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir =
+            std::env::temp_dir().join(format!("sentinel-stack-shared-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let err = ControlServer::bind(&dir.join("control.sock"))
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("accessible by other users"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_bind_creates_private_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir =
+            std::env::temp_dir().join(format!("sentinel-stack-private-{}", std::process::id()));
+        let server = ControlServer::bind(&dir.join("control.sock"))
+            .await
+            .unwrap();
+
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        drop(server);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,596 @@
+//! Agent output multiplexing.
+//!
+//! Every line a child writes to stdout or stderr is parsed into a
+//! [`LogRecord`]: JSON lines from `tracing-subscriber` (nested `fields` or
+//! flattened) and similar structured loggers keep their level, target and
+//! fields; plain text lines get a level from a leading `INFO`/`WARN`/...
+//! token when present. Records are then
+//!
+//! - kept in the agent's [`LogBuffer`] for `sentinel-stack logs` (unfiltered),
+//! - filtered by the agent's `logs { level ... }`,
+//! - re-emitted into sentinel-stack's own log stream with `agent`, `pid` and
+//!   `stream` fields, and
+//! - optionally appended to a per-agent [`RotatingFile`] as JSON lines.
+//!
+//! Lines longer than [`MAX_LINE_BYTES`] are truncated. Log files are written
+//! by a dedicated thread per agent, so file I/O never blocks the runtime.
+
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, BufReader};
+use tracing::{debug, error, info, trace, warn};
+
+use crate::config::{AgentLogConfig, LogLevel};
+
+/// Output lines kept per agent for `sentinel-stack logs`
+pub const LOG_BUFFER_LINES: usize = 1000;
+
+/// Longest agent output line kept; the rest of a longer line is discarded
+pub const MAX_LINE_BYTES: usize = 16 * 1024;
+
+/// Lines queued for an agent's log file writer before new lines are dropped
+const FILE_QUEUE_LINES: usize = 4096;
+
+/// Target of re-emitted agent output, for `RUST_LOG` filtering
+const AGENT_TARGET: &str = "sentinel_stack::agent";
+
+/// Bounded buffer of an agent's most recent output lines
+#[derive(Debug, Clone, Default)]
+pub struct LogBuffer {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl LogBuffer {
+    pub fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        if lines.len() == LOG_BUFFER_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// The last `n` lines, oldest first
+    pub fn tail(&self, n: usize) -> Vec<String> {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        lines
+            .iter()
+            .skip(lines.len().saturating_sub(n))
+            .cloned()
+            .collect()
+    }
+}
+
+/// One parsed line of agent output
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    pub message: String,
+    /// Target/module reported by the agent's logger
+    pub target: Option<String>,
+    /// Timestamp reported by the agent's logger
+    pub timestamp: Option<String>,
+    /// Remaining structured fields
+    pub fields: Map<String, Value>,
+}
+
+impl LogRecord {
+    /// Parse a line, falling back to `default_level` for unstructured text
+    pub fn parse(line: &str, default_level: LogLevel) -> Self {
+        let trimmed = line.trim();
+        if trimmed.starts_with('{') {
+            if let Ok(Value::Object(object)) = serde_json::from_str(trimmed) {
+                return Self::from_json(object, default_level);
+            }
+        }
+        Self::from_text(trimmed, default_level)
+    }
+
+    fn from_json(mut object: Map<String, Value>, default_level: LogLevel) -> Self {
+        let level = ["level", "lvl", "severity"]
+            .iter()
+            .find_map(|key| object.remove(*key))
+            .and_then(|v| v.as_str().and_then(LogLevel::parse))
+            .unwrap_or(default_level);
+        let target = take_string(&mut object, &["target", "logger"]);
+        let timestamp = take_string(&mut object, &["timestamp", "time", "ts"]);
+
+        // tracing-subscriber's default JSON layout nests the event fields
+        let mut fields = match object.remove("fields") {
+            Some(Value::Object(fields)) => fields,
+            Some(other) => {
+                object.insert("fields".to_string(), other);
+                Map::new()
+            }
+            None => Map::new(),
+        };
+        let message = take_string(&mut fields, &["message"])
+            .or_else(|| take_string(&mut object, &["message", "msg"]))
+            .unwrap_or_default();
+
+        // Flattened events and span context stay as fields
+        fields.extend(object);
+
+        Self {
+            level,
+            message,
+            target,
+            timestamp,
+            fields,
+        }
+    }
+
+    fn from_text(line: &str, default_level: LogLevel) -> Self {
+        // Look for a level among the first few tokens, e.g.
+        // "2024-01-15T10:30:00Z  WARN waf: rule reload failed" or "[ERROR] ..."
+        let level = line
+            .split_whitespace()
+            .take(3)
+            .find_map(|token| {
+                LogLevel::parse(token.trim_matches(|c: char| !c.is_ascii_alphabetic()))
+            })
+            .unwrap_or(default_level);
+
+        Self {
+            level,
+            message: line.to_string(),
+            target: None,
+            timestamp: None,
+            fields: Map::new(),
+        }
+    }
+
+    /// JSON line written to the agent's log file
+    fn to_json_line(&self, agent: &str, pid: u32, stream: &str) -> String {
+        let mut object = Map::new();
+        object.insert(
+            "timestamp".to_string(),
+            Value::String(
+                self.timestamp
+                    .clone()
+                    .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+            ),
+        );
+        object.insert("level".to_string(), self.level.as_str().into());
+        object.insert("agent".to_string(), agent.into());
+        object.insert("pid".to_string(), pid.into());
+        object.insert("stream".to_string(), stream.into());
+        if let Some(target) = &self.target {
+            object.insert("target".to_string(), target.clone().into());
+        }
+        object.insert("message".to_string(), self.message.clone().into());
+        if !self.fields.is_empty() {
+            object.insert("fields".to_string(), Value::Object(self.fields.clone()));
+        }
+        Value::Object(object).to_string()
+    }
+}
+
+fn take_string(object: &mut Map<String, Value>, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|key| match object.remove(*key) {
+        Some(Value::String(s)) => Some(s),
+        Some(other) => {
+            object.insert((*key).to_string(), other);
+            None
+        }
+        None => None,
+    })
+}
+
+/// Append-only log file with size-based rotation
+///
+/// When a write would take the file past `max_size_bytes`, `<path>.N-1` is
+/// renamed to `<path>.N` (and so on down to `<path>` -> `<path>.1`), the
+/// oldest file is dropped and a fresh `<path>` is opened.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size_bytes: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, max_size_bytes: u64, max_files: usize) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create log directory {}", parent.display()))?;
+        }
+        let file = Self::open_append(path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_size_bytes,
+            max_files,
+        })
+    }
+
+    fn open_append(path: &Path) -> Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            // No history kept: start over in place
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = std::fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = Self::open_append(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+
+    /// Append one line, rotating first if it would exceed the size limit
+    pub fn write_line(&mut self, line: &str) -> Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_size_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.file.write_all(b"\n")?;
+        self.size += len;
+        Ok(())
+    }
+}
+
+/// Queue feeding an agent's log file writer thread
+#[derive(Debug, Clone)]
+struct FileWriter {
+    lines: SyncSender<String>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl FileWriter {
+    /// Start the writer thread; it exits once every sender is dropped
+    fn spawn(agent: &str, mut file: RotatingFile) -> Result<Self> {
+        let (lines, queue) = sync_channel::<String>(FILE_QUEUE_LINES);
+        let thread_agent = agent.to_string();
+        std::thread::Builder::new()
+            .name(format!("log-{}", agent))
+            .spawn(move || {
+                for line in queue {
+                    if let Err(e) = file.write_line(&line) {
+                        warn!(agent = %thread_agent, error = %e, "Failed to write agent log file");
+                    }
+                }
+            })
+            .context("Failed to start agent log writer")?;
+
+        Ok(Self {
+            lines,
+            dropped: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Queue a line without blocking; drops it if the writer is behind
+    fn write(&self, agent: &str, line: String) {
+        match self.lines.try_send(line) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped.is_power_of_two() {
+                    warn!(agent = %agent, dropped, "Agent log file writer is behind, dropping lines");
+                }
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                debug!(agent = %agent, "Agent log file writer stopped");
+            }
+        }
+    }
+}
+
+/// Where an agent's output goes
+#[derive(Debug, Clone)]
+pub struct LogSink {
+    agent: String,
+    level: Option<LogLevel>,
+    buffer: LogBuffer,
+    file: Option<FileWriter>,
+}
+
+impl LogSink {
+    /// Create the sink for an agent, opening its log file if configured
+    pub fn new(agent: &str, config: &AgentLogConfig) -> Result<Self> {
+        let file = match &config.file {
+            Some(path) => Some(FileWriter::spawn(
+                agent,
+                RotatingFile::open(path, config.max_size_bytes, config.max_files)?,
+            )?),
+            None => None,
+        };
+
+        Ok(Self {
+            agent: agent.to_string(),
+            level: config.level,
+            buffer: LogBuffer::default(),
+            file,
+        })
+    }
+
+    pub fn buffer(&self) -> &LogBuffer {
+        &self.buffer
+    }
+
+    /// Read a child stream line by line until it closes
+    ///
+    /// stdout lines default to `INFO` and stderr lines to `WARN` when the
+    /// line itself carries no level.
+    pub fn capture<R>(&self, pid: u32, stream: &'static str, reader: R)
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let sink = self.clone();
+        let default_level = if stream == "stderr" {
+            LogLevel::Warn
+        } else {
+            LogLevel::Info
+        };
+
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(reader);
+            let mut line = Vec::new();
+            loop {
+                match read_line_capped(&mut reader, &mut line).await {
+                    Ok(true) => {
+                        sink.record(pid, stream, &String::from_utf8_lossy(&line), default_level)
+                    }
+                    Ok(false) => break,
+                    Err(e) => {
+                        debug!(agent = %sink.agent, stream = stream, error = %e, "Agent output closed");
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    fn record(&self, pid: u32, stream: &'static str, line: &str, default_level: LogLevel) {
+        if line.trim().is_empty() {
+            return;
+        }
+        self.buffer.push(line.to_string());
+
+        let record = LogRecord::parse(line, default_level);
+        if self.level.is_some_and(|min| record.level < min) {
+            return;
+        }
+
+        if let Some(file) = &self.file {
+            file.write(&self.agent, record.to_json_line(&self.agent, pid, stream));
+        }
+
+        emit(&self.agent, pid, stream, &record);
+    }
+}
+
+/// Read one line into `line`, keeping at most [`MAX_LINE_BYTES`] of it
+///
+/// The newline (and a preceding `\r`) is not included. Returns `Ok(false)`
+/// at the end of the stream.
+async fn read_line_capped<R>(reader: &mut R, line: &mut Vec<u8>) -> std::io::Result<bool>
+where
+    R: AsyncBufRead + Unpin,
+{
+    line.clear();
+    let mut read_any = false;
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Ok(read_any);
+        }
+        read_any = true;
+
+        let newline = available.iter().position(|&b| b == b'\n');
+        let end = newline.unwrap_or(available.len());
+        let room = MAX_LINE_BYTES.saturating_sub(line.len());
+        line.extend_from_slice(&available[..end.min(room)]);
+        reader.consume(newline.map_or(end, |i| i + 1));
+
+        if newline.is_some() {
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            return Ok(true);
+        }
+    }
+}
+
+/// Re-emit a record into sentinel-stack's own log stream
+fn emit(agent: &str, pid: u32, stream: &str, record: &LogRecord) {
+    let agent_target = record.target.as_deref().unwrap_or("");
+    let fields = if record.fields.is_empty() {
+        String::new()
+    } else {
+        Value::Object(record.fields.clone()).to_string()
+    };
+
+    macro_rules! emit_at {
+        ($level:ident) => {
+            $level!(
+                target: AGENT_TARGET,
+                agent = %agent,
+                pid = pid,
+                stream = stream,
+                agent_target = agent_target,
+                fields = %fields,
+                "{}",
+                record.message
+            )
+        };
+    }
+
+    match record.level {
+        LogLevel::Trace => emit_at!(trace),
+        LogLevel::Debug => emit_at!(debug),
+        LogLevel::Info => emit_at!(info),
+        LogLevel::Warn => emit_at!(warn),
+        LogLevel::Error => emit_at!(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_buffer_is_bounded() {
+        let logs = LogBuffer::default();
+        for i in 0..(LOG_BUFFER_LINES + 10) {
+            logs.push(format!("line {}", i));
+        }
+
+        let tail = logs.tail(3);
+        assert_eq!(tail.len(), 3);
+        assert_eq!(tail[2], format!("line {}", LOG_BUFFER_LINES + 9));
+        assert_eq!(logs.tail(usize::MAX).len(), LOG_BUFFER_LINES);
+    }
+
+    #[test]
+    fn test_parse_tracing_json() {
+        let line = r#"{"timestamp":"2026-01-15T10:30:00Z","level":"WARN","fields":{"message":"rule reload failed","rules":12},"target":"waf::rules"}"#;
+        let record = LogRecord::parse(line, LogLevel::Info);
+
+        assert_eq!(record.level, LogLevel::Warn);
+        assert_eq!(record.message, "rule reload failed");
+        assert_eq!(record.target.as_deref(), Some("waf::rules"));
+        assert_eq!(record.timestamp.as_deref(), Some("2026-01-15T10:30:00Z"));
+        assert_eq!(record.fields.get("rules"), Some(&Value::from(12)));
+
+        // Flattened layout
+        let line = r#"{"level":"error","msg":"boom","request_id":"abc"}"#;
+        let record = LogRecord::parse(line, LogLevel::Info);
+        assert_eq!(record.level, LogLevel::Error);
+        assert_eq!(record.message, "boom");
+        assert_eq!(record.fields.get("request_id"), Some(&Value::from("abc")));
+    }
+
+    #[test]
+    fn test_parse_text() {
+        let record = LogRecord::parse("2026-01-15T10:30:00Z  ERROR waf: oops", LogLevel::Info);
+        assert_eq!(record.level, LogLevel::Error);
+        assert_eq!(record.message, "2026-01-15T10:30:00Z  ERROR waf: oops");
+
+        let record = LogRecord::parse("[debug] loaded", LogLevel::Info);
+        assert_eq!(record.level, LogLevel::Debug);
+
+        let record = LogRecord::parse("listening on /tmp/waf.sock", LogLevel::Warn);
+        assert_eq!(record.level, LogLevel::Warn);
+
+        let record = LogRecord::parse("{not json", LogLevel::Info);
+        assert_eq!(record.message, "{not json");
+    }
+
+    #[test]
+    fn test_rotating_file() {
+        let dir = std::env::temp_dir().join(format!("sentinel-stack-logs-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("agent.log");
+
+        let mut file = RotatingFile::open(&path, 20, 2).unwrap();
+        for i in 0..4 {
+            file.write_line(&format!("line-{:03}-xxxx", i)).unwrap();
+        }
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "line-003-xxxx\n");
+        assert_eq!(
+            std::fs::read_to_string(dir.join("agent.log.1")).unwrap(),
+            "line-002-xxxx\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("agent.log.2")).unwrap(),
+            "line-001-xxxx\n"
+        );
+        assert!(!dir.join("agent.log.3").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_sink_filters_by_level() {
+        let dir = std::env::temp_dir().join(format!("sentinel-stack-sink-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = AgentLogConfig {
+            level: Some(LogLevel::Warn),
+            file: Some(dir.join("agent.log")),
+            ..Default::default()
+        };
+        let sink = LogSink::new("waf", &config).unwrap();
+
+        sink.record(
+            42,
+            "stdout",
+            r#"{"level":"INFO","fields":{"message":"hi"}}"#,
+            LogLevel::Info,
+        );
+        sink.record(42, "stderr", "something broke", LogLevel::Warn);
+
+        // The buffer keeps everything, the file only what passes the filter
+        assert_eq!(sink.buffer().tail(10).len(), 2);
+        let mut written = String::new();
+        for _ in 0..100 {
+            written = std::fs::read_to_string(dir.join("agent.log")).unwrap();
+            if !written.is_empty() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let record: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(record["agent"], "waf");
+        assert_eq!(record["pid"], 42);
+        assert_eq!(record["stream"], "stderr");
+        assert_eq!(record["level"], "WARN");
+        assert_eq!(record["message"], "something broke");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_read_line_capped() {
+        let long = "x".repeat(MAX_LINE_BYTES + 100);
+        let input = format!("short\r\n{}\nlast", long);
+        let mut reader = BufReader::with_capacity(64, input.as_bytes());
+        let mut line = Vec::new();
+
+        assert!(read_line_capped(&mut reader, &mut line).await.unwrap());
+        assert_eq!(line, b"short");
+
+        assert!(read_line_capped(&mut reader, &mut line).await.unwrap());
+        assert_eq!(line.len(), MAX_LINE_BYTES);
+
+        assert!(read_line_capped(&mut reader, &mut line).await.unwrap());
+        assert_eq!(line, b"last");
+
+        assert!(!read_line_capped(&mut reader, &mut line).await.unwrap());
+    }
+}
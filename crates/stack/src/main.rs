@@ -3,7 +3,12 @@
 //! Spawns and manages Sentinel proxy along with configured agents as child processes.
 //! Designed for development and simple production deployments.
 
-use std::collections::HashMap;
+mod config;
+mod control;
+mod logs;
+mod readiness;
+mod supervisor;
+
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -11,10 +16,14 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use tokio::process::{Child, Command};
 use tokio::time::sleep;
-use tracing::{debug, error, info, warn};
+use tracing::{error, info, warn};
+
+use crate::config::parse_agent_configs;
+use crate::control::{default_control_socket, ControlRequest, ControlServer};
+use crate::supervisor::Supervisor;
 
 /// sentinel-stack: All-in-one launcher for Sentinel proxy and agents
 #[derive(Parser, Debug)]
@@ -44,305 +53,104 @@ struct Args {
     #[arg(long, default_value = "30")]
     shutdown_timeout: u64,
 
-    /// Startup timeout in seconds (wait for agents to be ready)
+    /// Startup timeout in seconds (per agent readiness probe)
     #[arg(long, default_value = "10")]
     startup_timeout: u64,
-}
-
-/// Agent configuration for sentinel-stack
-#[derive(Debug, Clone)]
-struct AgentConfig {
-    id: String,
-    command: Vec<String>,
-    restart_policy: RestartPolicy,
-    restart_delay_ms: u64,
-    max_restarts: u32,
-    env: HashMap<String, String>,
-}
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum RestartPolicy {
-    Always,
-    OnFailure,
-    Never,
+    /// Control socket used by `status`, `restart` and `logs`
+    #[arg(
+        long,
+        global = true,
+        default_value_os_t = default_control_socket(),
+        env = "SENTINEL_STACK_SOCKET"
+    )]
+    control_socket: PathBuf,
+
+    #[command(subcommand)]
+    command: Option<ControlCommand>,
 }
 
-/// Managed process state
-struct ManagedProcess {
-    config: AgentConfig,
-    child: Option<Child>,
-    restart_count: u32,
-    running: bool,
+/// Commands sent to a running sentinel-stack
+#[derive(Subcommand, Debug)]
+enum ControlCommand {
+    /// Show the state of every managed agent
+    Status,
+    /// Restart an agent (also clears crash-loop state)
+    Restart {
+        /// Agent ID
+        agent: String,
+    },
+    /// Print an agent's most recent output
+    Logs {
+        /// Agent ID
+        agent: String,
+        /// Number of lines to print
+        #[arg(short = 'n', long, default_value = "100")]
+        lines: usize,
+    },
 }
 
-impl ManagedProcess {
-    fn new(config: AgentConfig) -> Self {
-        Self {
-            config,
-            child: None,
-            restart_count: 0,
-            running: false,
-        }
-    }
-
-    async fn start(&mut self) -> Result<()> {
-        if self.config.command.is_empty() {
-            return Ok(()); // External agent, don't manage
-        }
-
-        let (program, args) = self.config.command.split_first().context("Empty command")?;
-
-        info!(
-            agent = %self.config.id,
-            command = %program,
-            "Starting agent"
-        );
-
-        let mut cmd = Command::new(program);
-        cmd.args(args)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .kill_on_drop(true);
-
-        // Set environment variables
-        for (key, value) in &self.config.env {
-            cmd.env(key, value);
-        }
-
-        let child = cmd
-            .spawn()
-            .with_context(|| format!("Failed to spawn agent '{}': {}", self.config.id, program))?;
-
-        let pid = child.id().unwrap_or(0);
-        info!(
-            agent = %self.config.id,
-            pid = pid,
-            "Agent started"
-        );
-
-        self.child = Some(child);
-        self.running = true;
-
-        Ok(())
-    }
-
-    async fn stop(&mut self) -> Result<()> {
-        if let Some(ref mut child) = self.child {
-            info!(agent = %self.config.id, "Stopping agent");
-
-            // Try graceful shutdown first
-            #[cfg(unix)]
-            {
-                if let Some(pid) = child.id() {
-                    unsafe {
-                        libc::kill(pid as i32, libc::SIGTERM);
-                    }
-                }
-            }
-
-            // Wait for graceful shutdown
-            tokio::select! {
-                _ = sleep(Duration::from_secs(5)) => {
-                    warn!(agent = %self.config.id, "Agent didn't stop gracefully, killing");
-                    let _ = child.kill().await;
-                }
-                result = child.wait() => {
-                    debug!(agent = %self.config.id, ?result, "Agent stopped");
-                }
+/// Run a control command against a running stack
+async fn run_control(socket: &std::path::Path, command: ControlCommand) -> Result<()> {
+    match command {
+        ControlCommand::Status => {
+            let response = control::send(socket, &ControlRequest::Status).await?;
+            println!(
+                "{:<24} {:<12} {:>8} {:>9} {:>10}  LAST EXIT",
+                "AGENT", "STATE", "PID", "RESTARTS", "UPTIME"
+            );
+            for agent in response.agents {
+                println!(
+                    "{:<24} {:<12} {:>8} {:>9} {:>10}  {}",
+                    agent.id,
+                    agent.state.to_string(),
+                    agent
+                        .pid
+                        .map(|p| p.to_string())
+                        .unwrap_or_else(|| "-".into()),
+                    agent.restarts,
+                    agent
+                        .uptime_secs
+                        .map(|s| format!("{}s", s))
+                        .unwrap_or_else(|| "-".into()),
+                    agent.last_exit.as_deref().unwrap_or("-"),
+                );
             }
         }
-
-        self.child = None;
-        self.running = false;
-
-        Ok(())
-    }
-
-    async fn check_and_restart(&mut self, shutdown: &AtomicBool) -> Result<bool> {
-        if shutdown.load(Ordering::Relaxed) {
-            return Ok(false);
-        }
-
-        if let Some(ref mut child) = self.child {
-            match child.try_wait() {
-                Ok(Some(status)) => {
-                    // Process exited
-                    self.running = false;
-                    let should_restart = match self.config.restart_policy {
-                        RestartPolicy::Always => true,
-                        RestartPolicy::OnFailure => !status.success(),
-                        RestartPolicy::Never => false,
-                    };
-
-                    if should_restart {
-                        if self.config.max_restarts > 0
-                            && self.restart_count >= self.config.max_restarts
-                        {
-                            error!(
-                                agent = %self.config.id,
-                                restarts = self.restart_count,
-                                max = self.config.max_restarts,
-                                "Max restarts exceeded"
-                            );
-                            return Ok(false);
-                        }
-
-                        warn!(
-                            agent = %self.config.id,
-                            status = ?status,
-                            restart_count = self.restart_count,
-                            "Agent exited, restarting"
-                        );
-
-                        sleep(Duration::from_millis(self.config.restart_delay_ms)).await;
-                        self.restart_count += 1;
-                        self.child = None;
-                        self.start().await?;
-                        return Ok(true);
-                    } else {
-                        info!(
-                            agent = %self.config.id,
-                            status = ?status,
-                            "Agent exited (no restart)"
-                        );
-                        return Ok(false);
-                    }
-                }
-                Ok(None) => {
-                    // Still running
-                    return Ok(true);
-                }
-                Err(e) => {
-                    error!(agent = %self.config.id, error = %e, "Failed to check process status");
-                    return Ok(false);
-                }
-            }
+        ControlCommand::Restart { agent } => {
+            let response = control::send(
+                socket,
+                &ControlRequest::Restart {
+                    agent: agent.clone(),
+                },
+            )
+            .await?;
+            let pid = response.agents.first().and_then(|a| a.pid);
+            println!(
+                "Restarted {} (pid {})",
+                agent,
+                pid.map(|p| p.to_string()).unwrap_or_else(|| "-".into())
+            );
         }
-
-        Ok(false)
-    }
-}
-
-/// Helper to get string from first entry
-fn get_first_string(node: &kdl::KdlNode) -> Option<String> {
-    node.entries()
-        .first()
-        .and_then(|e| e.value().as_string())
-        .map(|s| s.to_string())
-}
-
-/// Helper to get string from child node
-fn get_child_string(node: &kdl::KdlNode, name: &str) -> Option<String> {
-    node.children()
-        .and_then(|c| c.get(name))
-        .and_then(|n| n.entries().first())
-        .and_then(|e| e.value().as_string())
-        .map(|s| s.to_string())
-}
-
-/// Helper to get integer from child node
-fn get_child_int(node: &kdl::KdlNode, name: &str) -> Option<i64> {
-    node.children()
-        .and_then(|c| c.get(name))
-        .and_then(|n| n.entries().first())
-        .and_then(|e| e.value().as_integer())
-        .map(|v| v as i64)
-}
-
-/// Parse agent configurations from the sentinel config
-fn parse_agent_configs(config_path: &PathBuf) -> Result<Vec<AgentConfig>> {
-    // Read and parse the KDL config
-    let content = std::fs::read_to_string(config_path)
-        .with_context(|| format!("Failed to read config: {:?}", config_path))?;
-
-    let doc: kdl::KdlDocument = content.parse().context("Failed to parse KDL config")?;
-
-    let mut agents = Vec::new();
-
-    // Find agents section
-    if let Some(agents_node) = doc.get("agents") {
-        if let Some(children) = agents_node.children() {
-            for node in children.nodes() {
-                if node.name().value() == "agent" {
-                    // Get agent ID from first argument
-                    let id = get_first_string(node).unwrap_or_else(|| "unknown".to_string());
-
-                    // Parse command if present
-                    let mut command = Vec::new();
-                    if let Some(node_children) = node.children() {
-                        if let Some(cmd_node) = node_children.get("command") {
-                            for entry in cmd_node.entries() {
-                                if let Some(s) = entry.value().as_string() {
-                                    command.push(s.to_string());
-                                }
-                            }
-                        }
-                    }
-
-                    // Parse restart policy
-                    let restart_policy = get_child_string(node, "restart-policy")
-                        .map(|s| match s.as_str() {
-                            "always" => RestartPolicy::Always,
-                            "on-failure" => RestartPolicy::OnFailure,
-                            "never" => RestartPolicy::Never,
-                            _ => RestartPolicy::OnFailure,
-                        })
-                        .unwrap_or(RestartPolicy::OnFailure);
-
-                    // Parse restart delay
-                    let restart_delay_ms =
-                        get_child_int(node, "restart-delay-ms").unwrap_or(1000) as u64;
-
-                    // Parse max restarts
-                    let max_restarts = get_child_int(node, "max-restarts").unwrap_or(0) as u32;
-
-                    // Parse environment
-                    let mut env = HashMap::new();
-                    if let Some(node_children) = node.children() {
-                        if let Some(env_node) = node_children.get("env") {
-                            if let Some(env_children) = env_node.children() {
-                                for env_entry in env_children.nodes() {
-                                    let key = env_entry.name().value().to_string();
-                                    if let Some(value_str) = get_first_string(env_entry) {
-                                        // Expand environment variables
-                                        let expanded = if value_str.starts_with("${")
-                                            && value_str.ends_with("}")
-                                        {
-                                            let var_name = &value_str[2..value_str.len() - 1];
-                                            std::env::var(var_name).unwrap_or_default()
-                                        } else {
-                                            value_str
-                                        };
-                                        env.insert(key, expanded);
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    if !command.is_empty() {
-                        agents.push(AgentConfig {
-                            id,
-                            command,
-                            restart_policy,
-                            restart_delay_ms,
-                            max_restarts,
-                            env,
-                        });
-                    }
-                }
+        ControlCommand::Logs { agent, lines } => {
+            let response = control::send(socket, &ControlRequest::Logs { agent, lines }).await?;
+            for line in response.lines {
+                println!("{}", line);
             }
         }
     }
-
-    Ok(agents)
+    Ok(())
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
+    // Control commands talk to a running stack and exit
+    if let Some(command) = args.command {
+        return run_control(&args.control_socket, command).await;
+    }
+
     // Initialize logging
     tracing_subscriber::fmt()
         .with_env_filter(&args.log_level)
@@ -373,6 +181,10 @@ async fn main() -> Result<()> {
                 agent = %agent.id,
                 command = ?agent.command,
                 restart_policy = ?agent.restart_policy,
+                depends_on = ?agent.depends_on,
+                readiness = ?agent.readiness.as_ref().map(|r| r.probe.to_string()),
+                log_level = ?agent.logs.level,
+                log_file = ?agent.logs.file,
                 "Agent configuration"
             );
         }
@@ -402,25 +214,26 @@ async fn main() -> Result<()> {
         shutdown_clone.store(true, Ordering::Relaxed);
     });
 
-    // Start agents
-    let mut agents: Vec<ManagedProcess> = Vec::new();
+    // Start agents in dependency order, gated on readiness
+    let supervisor = Arc::new(Supervisor::new(
+        agent_configs,
+        Duration::from_secs(args.startup_timeout),
+    )?);
+    let mut control_task = None;
 
     if !args.proxy_only {
-        for config in agent_configs {
-            let mut process = ManagedProcess::new(config);
-            if let Err(e) = process.start().await {
-                error!(error = %e, "Failed to start agent");
-                // Continue with other agents
-            }
-            agents.push(process);
-        }
+        let control = ControlServer::bind(&args.control_socket).await?;
+        control_task = Some(tokio::spawn(control.serve(supervisor.clone())));
 
-        // Wait for agents to be ready
         info!(
             timeout = args.startup_timeout,
             "Waiting for agents to be ready"
         );
-        sleep(Duration::from_secs(args.startup_timeout.min(5))).await;
+        if let Err(e) = supervisor.start_all().await {
+            error!(error = %format!("{:#}", e), "Agent startup failed");
+            supervisor.stop_all().await;
+            return Err(e);
+        }
     }
 
     // Start proxy
@@ -476,11 +289,7 @@ async fn main() -> Result<()> {
 
         // Check agents
         if !args.proxy_only {
-            for agent in &mut agents {
-                if let Err(e) = agent.check_and_restart(&shutdown).await {
-                    error!(agent = %agent.config.id, error = %e, "Agent check failed");
-                }
-            }
+            supervisor.tick(&shutdown).await;
         }
 
         sleep(Duration::from_millis(500)).await;
@@ -513,12 +322,11 @@ async fn main() -> Result<()> {
     }
 
     // Stop agents
+    if let Some(task) = control_task {
+        task.abort();
+    }
     if !args.proxy_only {
-        for agent in &mut agents {
-            if let Err(e) = agent.stop().await {
-                error!(agent = %agent.config.id, error = %e, "Failed to stop agent");
-            }
-        }
+        supervisor.stop_all().await;
     }
 
     info!("sentinel-stack shutdown complete");
@@ -0,0 +1,244 @@
+//! Readiness probes for managed agents.
+//!
+//! Probes are deliberately dependency-free: a Unix socket connect, a raw
+//! HTTP/2 connection preface exchange (what every gRPC server answers before
+//! any RPC), or a minimal HTTP/1.1 `GET`.
+
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpStream, UnixStream};
+use tokio::time::{sleep, timeout, Instant};
+use tracing::{debug, info};
+
+use crate::config::{ReadinessConfig, ReadinessProbe};
+
+/// HTTP/2 client connection preface
+const H2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// Empty HTTP/2 SETTINGS frame (length 0, type 0x4, no flags, stream 0)
+const H2_EMPTY_SETTINGS: [u8; 9] = [0, 0, 0, 0x4, 0, 0, 0, 0, 0];
+
+/// Time allowed for a single probe attempt
+const PROBE_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(2);
+
+impl ReadinessProbe {
+    /// Run the probe once
+    pub async fn check(&self) -> Result<()> {
+        let attempt = async {
+            match self {
+                ReadinessProbe::Socket(path) => {
+                    connect_unix(path).await?;
+                    Ok(())
+                }
+                ReadinessProbe::Grpc(address) => check_grpc(address).await,
+                ReadinessProbe::Http(url) => check_http(url).await,
+            }
+        };
+        timeout(PROBE_ATTEMPT_TIMEOUT, attempt)
+            .await
+            .context("probe timed out")?
+    }
+}
+
+impl std::fmt::Display for ReadinessProbe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadinessProbe::Socket(path) => write!(f, "socket {}", path.display()),
+            ReadinessProbe::Grpc(address) => write!(f, "grpc {}", address),
+            ReadinessProbe::Http(url) => write!(f, "http {}", url),
+        }
+    }
+}
+
+/// Poll a readiness probe until it succeeds or `deadline` passes
+pub async fn wait_ready(
+    agent: &str,
+    readiness: &ReadinessConfig,
+    deadline: Duration,
+) -> Result<()> {
+    let started = Instant::now();
+
+    let last_error = loop {
+        match readiness.probe.check().await {
+            Ok(()) => {
+                info!(
+                    agent = %agent,
+                    probe = %readiness.probe,
+                    elapsed_ms = started.elapsed().as_millis() as u64,
+                    "Agent ready"
+                );
+                return Ok(());
+            }
+            Err(e) => {
+                debug!(agent = %agent, probe = %readiness.probe, error = %e, "Agent not ready yet");
+                if started.elapsed() + readiness.interval > deadline {
+                    break e;
+                }
+            }
+        }
+        sleep(readiness.interval).await;
+    };
+
+    bail!(
+        "Agent '{}' not ready after {:?} ({}): {}",
+        agent,
+        deadline,
+        readiness.probe,
+        last_error
+    )
+}
+
+async fn connect_unix(path: &std::path::Path) -> Result<UnixStream> {
+    UnixStream::connect(path)
+        .await
+        .with_context(|| format!("connect to {}", path.display()))
+}
+
+/// Check that an HTTP/2 server completes the connection preface
+///
+/// Accepts `unix:/path`, `http://host:port` or `host:port`.
+async fn check_grpc(address: &str) -> Result<()> {
+    if let Some(path) = address.strip_prefix("unix:") {
+        let stream = connect_unix(std::path::Path::new(path.trim_start_matches("//"))).await?;
+        return h2_handshake(stream).await;
+    }
+    if address.starts_with("https://") {
+        // The handshake would need TLS; a TCP connect is the best we can do
+        let authority = strip_scheme(address);
+        TcpStream::connect(authority)
+            .await
+            .with_context(|| format!("connect to {}", authority))?;
+        return Ok(());
+    }
+
+    let authority = strip_scheme(address);
+    let stream = TcpStream::connect(authority)
+        .await
+        .with_context(|| format!("connect to {}", authority))?;
+    h2_handshake(stream).await
+}
+
+async fn h2_handshake<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S) -> Result<()> {
+    stream.write_all(H2_PREFACE).await?;
+    stream.write_all(&H2_EMPTY_SETTINGS).await?;
+    stream.flush().await?;
+
+    // The server preface is a SETTINGS frame
+    let mut header = [0u8; 9];
+    stream
+        .read_exact(&mut header)
+        .await
+        .context("connection closed before HTTP/2 settings")?;
+    if header[3] != 0x4 {
+        bail!(
+            "expected HTTP/2 SETTINGS frame, got frame type {:#x}",
+            header[3]
+        );
+    }
+    Ok(())
+}
+
+/// `GET` an `http://` URL and require a 2xx status
+async fn check_http(url: &str) -> Result<()> {
+    let rest = url
+        .strip_prefix("http://")
+        .with_context(|| format!("unsupported URL '{}'", url))?;
+    let (authority, path) = match rest.find('/') {
+        Some(pos) => rest.split_at(pos),
+        None => (rest, "/"),
+    };
+
+    let mut stream = TcpStream::connect(authority)
+        .await
+        .with_context(|| format!("connect to {}", authority))?;
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: sentinel-stack\r\nConnection: close\r\n\r\n",
+        path, authority
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    // Only the status line matters
+    let mut buf = vec![0u8; 256];
+    let mut len = 0;
+    while len < buf.len() && !buf[..len].contains(&b'\n') {
+        let n = stream.read(&mut buf[len..]).await?;
+        if n == 0 {
+            break;
+        }
+        len += n;
+    }
+
+    let status_line = String::from_utf8_lossy(&buf[..len]);
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse::<u16>().ok())
+        .with_context(|| format!("invalid HTTP response from {}", url))?;
+    if !(200..300).contains(&status) {
+        bail!("{} returned status {}", url, status);
+    }
+    Ok(())
+}
+
+fn strip_scheme(address: &str) -> &str {
+    let authority = address
+        .strip_prefix("http://")
+        .or_else(|| address.strip_prefix("https://"))
+        .unwrap_or(address);
+    authority.split('/').next().unwrap_or(authority)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_http_probe() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for status in ["200 OK", "503 Service Unavailable"] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 512];
+                let _ = socket.read(&mut buf).await.unwrap();
+                let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let probe = ReadinessProbe::Http(format!("http://{}/ready", addr));
+        assert!(probe.check().await.is_ok());
+        assert!(probe.check().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_grpc_probe() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; H2_PREFACE.len()];
+            socket.read_exact(&mut buf).await.unwrap();
+            socket.write_all(&H2_EMPTY_SETTINGS).await.unwrap();
+        });
+
+        let probe = ReadinessProbe::Grpc(format!("http://{}", addr));
+        assert!(probe.check().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_wait_ready_times_out() {
+        let readiness = ReadinessConfig {
+            probe: ReadinessProbe::Socket("/nonexistent/sentinel-stack-test.sock".into()),
+            interval: Duration::from_millis(10),
+            timeout: None,
+        };
+        let err = wait_ready("test", &readiness, Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not ready"));
+    }
+}
@@ -0,0 +1,497 @@
+//! Agent process supervision.
+//!
+//! Agents are started in `depends-on` order, each stage gated on the
+//! readiness probes of the previous one. Exited agents are restarted per
+//! their `RestartPolicy` with exponential backoff; too many restarts within
+//! the crash-loop window park the agent in `crash-loop` until an operator
+//! restarts it through the control socket.
+
+use std::collections::VecDeque;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+use tokio::task::JoinSet;
+use tokio::time::{sleep, Instant};
+use tracing::{debug, error, info, warn};
+
+use crate::config::{startup_order, AgentConfig, RestartPolicy};
+use crate::logs::LogSink;
+use crate::readiness::wait_ready;
+
+/// Lifecycle state of a managed agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AgentState {
+    /// Spawned, readiness probe not yet passed
+    Starting,
+    /// Running (and ready, if a probe is configured)
+    Running,
+    /// Exited, waiting for the restart delay
+    Backoff,
+    /// Restarted too often; not restarted until an operator intervenes
+    CrashLoop,
+    /// Exited and not restarted per policy
+    Exited,
+    /// Stopped by sentinel-stack
+    Stopped,
+}
+
+impl std::fmt::Display for AgentState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            AgentState::Starting => "starting",
+            AgentState::Running => "running",
+            AgentState::Backoff => "backoff",
+            AgentState::CrashLoop => "crash-loop",
+            AgentState::Exited => "exited",
+            AgentState::Stopped => "stopped",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Point-in-time status of an agent, as reported by `sentinel-stack status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentStatus {
+    pub id: String,
+    pub state: AgentState,
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    pub uptime_secs: Option<u64>,
+    pub last_exit: Option<String>,
+}
+
+/// Managed process state
+pub struct ManagedProcess {
+    config: AgentConfig,
+    child: Option<Child>,
+    state: AgentState,
+    restart_count: u32,
+    started_at: Option<Instant>,
+    /// Restart times inside the crash-loop window
+    recent_restarts: VecDeque<Instant>,
+    next_restart: Option<Instant>,
+    last_exit: Option<String>,
+    logs: LogSink,
+}
+
+impl ManagedProcess {
+    fn new(config: AgentConfig) -> Result<Self> {
+        let logs = LogSink::new(&config.id, &config.logs)?;
+        Ok(Self {
+            config,
+            child: None,
+            state: AgentState::Stopped,
+            restart_count: 0,
+            started_at: None,
+            recent_restarts: VecDeque::new(),
+            next_restart: None,
+            last_exit: None,
+            logs,
+        })
+    }
+
+    async fn start(&mut self) -> Result<()> {
+        let (program, args) = self.config.command.split_first().context("Empty command")?;
+
+        info!(
+            agent = %self.config.id,
+            command = %program,
+            "Starting agent"
+        );
+
+        let mut cmd = Command::new(program);
+        cmd.args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        // Set environment variables
+        for (key, value) in &self.config.env {
+            cmd.env(key, value);
+        }
+
+        let mut child = cmd
+            .spawn()
+            .with_context(|| format!("Failed to spawn agent '{}': {}", self.config.id, program))?;
+
+        let pid = child.id().unwrap_or(0);
+        if let Some(stdout) = child.stdout.take() {
+            self.logs.capture(pid, "stdout", stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            self.logs.capture(pid, "stderr", stderr);
+        }
+
+        info!(
+            agent = %self.config.id,
+            pid = pid,
+            "Agent started"
+        );
+
+        self.child = Some(child);
+        self.started_at = Some(Instant::now());
+        self.next_restart = None;
+        self.state = if self.config.readiness.is_some() {
+            AgentState::Starting
+        } else {
+            AgentState::Running
+        };
+
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        if let Some(ref mut child) = self.child {
+            info!(agent = %self.config.id, "Stopping agent");
+
+            // Try graceful shutdown first
+            #[cfg(unix)]
+            {
+                if let Some(pid) = child.id() {
+                    unsafe {
+                        libc::kill(pid as i32, libc::SIGTERM);
+                    }
+                }
+            }
+
+            // Wait for graceful shutdown
+            tokio::select! {
+                _ = sleep(Duration::from_secs(5)) => {
+                    warn!(agent = %self.config.id, "Agent didn't stop gracefully, killing");
+                    let _ = child.kill().await;
+                }
+                result = child.wait() => {
+                    debug!(agent = %self.config.id, ?result, "Agent stopped");
+                }
+            }
+        }
+
+        self.child = None;
+        self.started_at = None;
+        self.next_restart = None;
+        self.state = AgentState::Stopped;
+
+        Ok(())
+    }
+
+    /// Check the process and apply the restart policy
+    ///
+    /// Never blocks on the restart delay: an exited agent is moved to
+    /// `Backoff` and started on a later call once the delay has passed.
+    /// Returns `true` when a new process was spawned.
+    async fn poll(&mut self) -> Result<bool> {
+        if self.state == AgentState::Backoff {
+            if self.next_restart.is_some_and(|at| Instant::now() >= at) {
+                self.restart_count += 1;
+                self.recent_restarts.push_back(Instant::now());
+                if let Err(e) = self.start().await {
+                    self.last_exit = Some(format!("{:#}", e));
+                    self.schedule_restart();
+                    return Err(e);
+                }
+                return Ok(true);
+            }
+            return Ok(false);
+        }
+
+        let Some(ref mut child) = self.child else {
+            return Ok(false);
+        };
+
+        let status = match child.try_wait() {
+            Ok(Some(status)) => status,
+            Ok(None) => return Ok(false), // Still running
+            Err(e) => {
+                error!(agent = %self.config.id, error = %e, "Failed to check process status");
+                return Ok(false);
+            }
+        };
+
+        // Process exited
+        self.child = None;
+        self.started_at = None;
+        self.last_exit = Some(status.to_string());
+
+        let should_restart = match self.config.restart_policy {
+            RestartPolicy::Always => true,
+            RestartPolicy::OnFailure => !status.success(),
+            RestartPolicy::Never => false,
+        };
+
+        if !should_restart {
+            info!(
+                agent = %self.config.id,
+                status = ?status,
+                "Agent exited (no restart)"
+            );
+            self.state = AgentState::Exited;
+            return Ok(false);
+        }
+
+        if self.config.max_restarts > 0 && self.restart_count >= self.config.max_restarts {
+            error!(
+                agent = %self.config.id,
+                restarts = self.restart_count,
+                max = self.config.max_restarts,
+                "Max restarts exceeded"
+            );
+            self.state = AgentState::Exited;
+            return Ok(false);
+        }
+
+        warn!(
+            agent = %self.config.id,
+            status = ?status,
+            restart_count = self.restart_count,
+            "Agent exited"
+        );
+        self.schedule_restart();
+
+        Ok(false)
+    }
+
+    /// Enter `Backoff` with an exponential delay, or `CrashLoop` when the
+    /// agent restarted too often within the window
+    fn schedule_restart(&mut self) {
+        let now = Instant::now();
+        while self
+            .recent_restarts
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > self.config.crash_loop_window)
+        {
+            self.recent_restarts.pop_front();
+        }
+
+        let recent = self.recent_restarts.len() as u32;
+        if self.config.crash_loop_restarts > 0 && recent >= self.config.crash_loop_restarts {
+            error!(
+                agent = %self.config.id,
+                restarts = recent,
+                window_secs = self.config.crash_loop_window.as_secs(),
+                "Agent is crash-looping, not restarting until restarted manually"
+            );
+            self.state = AgentState::CrashLoop;
+            self.next_restart = None;
+            return;
+        }
+
+        let delay = self.config.restart_delay(recent);
+        info!(
+            agent = %self.config.id,
+            delay_ms = delay.as_millis() as u64,
+            "Scheduling agent restart"
+        );
+        self.state = AgentState::Backoff;
+        self.next_restart = Some(now + delay);
+    }
+
+    fn status(&self) -> AgentStatus {
+        AgentStatus {
+            id: self.config.id.clone(),
+            state: self.state,
+            pid: self.child.as_ref().and_then(|c| c.id()),
+            restarts: self.restart_count,
+            uptime_secs: self.started_at.map(|at| at.elapsed().as_secs()),
+            last_exit: self.last_exit.clone(),
+        }
+    }
+}
+
+/// Supervises all managed agents
+pub struct Supervisor {
+    /// Agents in startup order
+    agents: Vec<(String, Arc<Mutex<ManagedProcess>>)>,
+    stages: Vec<Vec<String>>,
+    startup_timeout: Duration,
+}
+
+impl Supervisor {
+    pub fn new(mut configs: Vec<AgentConfig>, startup_timeout: Duration) -> Result<Self> {
+        let stages = startup_order(&configs)?;
+
+        let mut agents = Vec::with_capacity(configs.len());
+        for id in stages.iter().flatten() {
+            if let Some(pos) = configs.iter().position(|c| &c.id == id) {
+                let config = configs.swap_remove(pos);
+                let process = ManagedProcess::new(config)?;
+                agents.push((id.clone(), Arc::new(Mutex::new(process))));
+            }
+        }
+
+        Ok(Self {
+            agents,
+            stages,
+            startup_timeout,
+        })
+    }
+
+    fn get(&self, id: &str) -> Option<&Arc<Mutex<ManagedProcess>>> {
+        self.agents
+            .iter()
+            .find(|(agent_id, _)| agent_id == id)
+            .map(|(_, process)| process)
+    }
+
+    /// Start all agents in dependency order, waiting for each stage to be ready
+    pub async fn start_all(&self) -> Result<()> {
+        for (stage_index, stage) in self.stages.iter().enumerate() {
+            info!(stage = stage_index, agents = ?stage, "Starting agent stage");
+
+            let mut waiting = JoinSet::new();
+            for id in stage {
+                let process = self.get(id).context("Unknown agent")?.clone();
+                let mut guard = process.lock().await;
+                guard.start().await?;
+
+                if let Some(readiness) = guard.config.readiness.clone() {
+                    let id = id.clone();
+                    let deadline = readiness.timeout.unwrap_or(self.startup_timeout);
+                    let process = process.clone();
+                    waiting.spawn(async move {
+                        wait_ready(&id, &readiness, deadline).await?;
+                        let mut guard = process.lock().await;
+                        if guard.state == AgentState::Starting {
+                            guard.state = AgentState::Running;
+                        }
+                        Ok::<_, anyhow::Error>(())
+                    });
+                }
+            }
+
+            while let Some(result) = waiting.join_next().await {
+                result.context("Readiness task panicked")??;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Poll every agent once, restarting exited ones per policy
+    pub async fn tick(&self, shutdown: &AtomicBool) {
+        for (id, process) in &self.agents {
+            if shutdown.load(Ordering::Relaxed) {
+                return;
+            }
+            let mut guard = process.lock().await;
+            match guard.poll().await {
+                Ok(true) => self.watch_ready(process, &guard),
+                Ok(false) => {}
+                Err(e) => {
+                    error!(agent = %id, error = %e, "Agent check failed");
+                }
+            }
+        }
+    }
+
+    /// Stop and start an agent, clearing crash-loop state
+    pub async fn restart(&self, id: &str) -> Result<AgentStatus> {
+        let process = self
+            .get(id)
+            .with_context(|| format!("Unknown agent '{}'", id))?;
+        let mut guard = process.lock().await;
+
+        info!(agent = %id, "Restart requested");
+        guard.stop().await?;
+        guard.recent_restarts.clear();
+        guard.restart_count += 1;
+        guard.start().await?;
+        self.watch_ready(process, &guard);
+
+        Ok(guard.status())
+    }
+
+    /// Mark a (re)started agent as running once its probe passes
+    fn watch_ready(&self, process: &Arc<Mutex<ManagedProcess>>, guard: &ManagedProcess) {
+        let Some(readiness) = guard.config.readiness.clone() else {
+            return;
+        };
+        let id = guard.config.id.clone();
+        let pid = guard.child.as_ref().and_then(|c| c.id());
+        let deadline = readiness.timeout.unwrap_or(self.startup_timeout);
+        let process = process.clone();
+
+        tokio::spawn(async move {
+            match wait_ready(&id, &readiness, deadline).await {
+                Ok(()) => {
+                    let mut guard = process.lock().await;
+                    let same_process = guard.child.as_ref().and_then(|c| c.id()) == pid;
+                    if same_process && guard.state == AgentState::Starting {
+                        guard.state = AgentState::Running;
+                    }
+                }
+                Err(e) => warn!(agent = %id, error = %e, "Restarted agent did not become ready"),
+            }
+        });
+    }
+
+    pub async fn status(&self) -> Vec<AgentStatus> {
+        let mut statuses = Vec::with_capacity(self.agents.len());
+        for (_, process) in &self.agents {
+            statuses.push(process.lock().await.status());
+        }
+        statuses
+    }
+
+    pub async fn logs(&self, id: &str, lines: usize) -> Result<Vec<String>> {
+        let process = self
+            .get(id)
+            .with_context(|| format!("Unknown agent '{}'", id))?;
+        let logs = process.lock().await.logs.buffer().clone();
+        Ok(logs.tail(lines))
+    }
+
+    /// Stop all agents, dependents before their dependencies
+    pub async fn stop_all(&self) {
+        for (id, process) in self.agents.iter().rev() {
+            if let Err(e) = process.lock().await.stop().await {
+                error!(agent = %id, error = %e, "Failed to stop agent");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_crash_loop_detection() {
+        let config = crate::config::parse_agent_configs_str(
+            r#"
+            agents {
+                agent "crasher" {
+                    command "false"
+                    restart-policy "always"
+                    restart-delay-ms 1
+                    max-restart-delay-ms 5
+                    crash-loop-restarts 3
+                    crash-loop-window-secs 60
+                }
+            }
+            "#,
+        )
+        .unwrap()
+        .remove(0);
+
+        let mut process = ManagedProcess::new(config).unwrap();
+        process.start().await.unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while process.state != AgentState::CrashLoop && Instant::now() < deadline {
+            process.poll().await.unwrap();
+            sleep(Duration::from_millis(5)).await;
+        }
+
+        assert_eq!(process.state, AgentState::CrashLoop);
+        assert_eq!(process.restart_count, 3);
+        assert!(process.status().pid.is_none());
+    }
+}
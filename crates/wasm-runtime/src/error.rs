@@ -41,6 +41,10 @@ pub enum WasmRuntimeError {
     #[error("execution timeout after {0:?}")]
     Timeout(std::time::Duration),
 
+    /// Guest code trapped
+    #[error("WASM agent trapped: {0}")]
+    Trap(String),
+
     /// Invalid WASM module
     #[error("invalid WASM module: {0}")]
     InvalidModule(String),
@@ -12,9 +12,10 @@ use crate::error::WasmRuntimeError;
 use parking_lot::Mutex;
 use sentinel_agent_protocol::{AgentResponse, RequestMetadata};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tracing::{debug, instrument, warn};
 use wasmtime::component::{Component, Linker, ResourceTable};
-use wasmtime::{Config, Engine, Store};
+use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder, Trap};
 use wasmtime_wasi::{WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
 
 /// Information about a loaded WASM agent.
@@ -48,6 +49,8 @@ pub struct AgentState {
     wasi_ctx: WasiCtx,
     /// Resource table for WASI
     resource_table: ResourceTable,
+    /// Memory and table limits enforced by the store
+    store_limits: StoreLimits,
 }
 
 impl WasiView for AgentState {
@@ -69,6 +72,12 @@ pub struct WasmAgentInstance {
     agent: Agent,
     /// Resource limits
     limits: WasmResourceLimits,
+    /// Whether the engine meters fuel
+    fuel_enabled: bool,
+    /// Epoch ticks allowed per call (None if epoch interruption is disabled)
+    epoch_deadline: Option<u64>,
+    /// Set once a call has trapped; the instance can't be re-entered
+    poisoned: AtomicBool,
 }
 
 impl WasmAgentInstance {
@@ -78,6 +87,7 @@ impl WasmAgentInstance {
         component: &Component,
         limits: WasmResourceLimits,
         config_json: &str,
+        epoch_deadline: Option<u64>,
     ) -> Result<Self, WasmRuntimeError> {
         // Build WASI context
         let wasi_ctx = WasiCtxBuilder::new()
@@ -92,11 +102,22 @@ impl WasmAgentInstance {
             configured: false,
             wasi_ctx,
             resource_table: ResourceTable::new(),
+            store_limits: StoreLimitsBuilder::new()
+                .memory_size(limits.max_memory)
+                .table_elements(limits.max_table_elements as usize)
+                .tables(limits.max_tables as usize)
+                .memories(limits.max_memories as usize)
+                .trap_on_grow_failure(true)
+                .build(),
         };
         let mut store = Store::new(engine, state);
+        store.limiter(|state| &mut state.store_limits);
 
-        // Configure fuel metering
-        store.set_fuel(limits.max_fuel)?;
+        // Configure fuel metering; fails if the engine doesn't meter fuel
+        let fuel_enabled = store.set_fuel(limits.max_fuel).is_ok();
+        if let Some(ticks) = epoch_deadline {
+            store.set_epoch_deadline(ticks);
+        }
 
         // Create linker and add WASI
         let mut linker = Linker::new(engine);
@@ -119,6 +140,9 @@ impl WasmAgentInstance {
             store: Mutex::new(store),
             agent,
             limits,
+            fuel_enabled,
+            epoch_deadline,
+            poisoned: AtomicBool::new(false),
         })
     }
 
@@ -162,6 +186,74 @@ impl WasmAgentInstance {
         &self.info.agent_id
     }
 
+    /// Get the resource limits applied to each call.
+    pub fn limits(&self) -> &WasmResourceLimits {
+        &self.limits
+    }
+
+    /// Whether a previous call trapped.
+    ///
+    /// A component instance can't be re-entered after a trap; callers should
+    /// replace poisoned instances.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Relaxed)
+    }
+
+    /// Reset the per-call fuel and time budgets.
+    fn begin_call(&self, store: &mut Store<AgentState>) -> Result<(), WasmRuntimeError> {
+        if self.is_poisoned() {
+            return Err(WasmRuntimeError::Trap(
+                "instance poisoned by an earlier trap".to_string(),
+            ));
+        }
+        if self.fuel_enabled {
+            store.set_fuel(self.limits.max_fuel)?;
+        }
+        if let Some(ticks) = self.epoch_deadline {
+            store.set_epoch_deadline(ticks);
+        }
+        Ok(())
+    }
+
+    /// Record fuel consumed by the call that just returned.
+    fn end_call(&self, store: &mut Store<AgentState>) {
+        let consumed = if self.fuel_enabled {
+            let remaining = store.get_fuel().unwrap_or(0);
+            self.limits.max_fuel.saturating_sub(remaining)
+        } else {
+            0
+        };
+        store.data_mut().fuel_consumed = consumed;
+    }
+
+    /// Classify an error returned from a guest call and poison the instance.
+    fn call_error(&self, call: &str, err: wasmtime::Error) -> WasmRuntimeError {
+        self.poisoned.store(true, Ordering::Relaxed);
+
+        let error = match err.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => WasmRuntimeError::ResourceLimit(format!(
+                "{} exhausted its fuel limit of {}",
+                call, self.limits.max_fuel
+            )),
+            Some(Trap::Interrupt) => WasmRuntimeError::Timeout(self.limits.max_execution_time),
+            Some(trap) => WasmRuntimeError::Trap(format!("{} trapped: {}", call, trap)),
+            None if format!("{:#}", err).contains("memory") => {
+                WasmRuntimeError::ResourceLimit(format!(
+                    "{} exceeded memory limit of {} bytes",
+                    call, self.limits.max_memory
+                ))
+            }
+            None => WasmRuntimeError::FunctionCall(format!("{} failed: {:#}", call, err)),
+        };
+
+        warn!(
+            agent_id = %self.info.agent_id,
+            error = %error,
+            "WASM agent call failed; instance poisoned"
+        );
+        error
+    }
+
     /// Process request headers.
     #[instrument(skip(self, headers), fields(agent_id = %self.info.agent_id))]
     pub fn on_request_headers(
@@ -172,9 +264,7 @@ impl WasmAgentInstance {
         headers: &HashMap<String, Vec<String>>,
     ) -> Result<AgentResponse, WasmRuntimeError> {
         let mut store = self.store.lock();
-
-        // Reset fuel for this call
-        store.set_fuel(self.limits.max_fuel)?;
+        self.begin_call(&mut store)?;
 
         debug!(
             method = method,
@@ -189,16 +279,10 @@ impl WasmAgentInstance {
 
         // Call the WASM function
         let handler = self.agent.sentinel_agent_handler();
-        let wit_response = handler
-            .call_on_request_headers(&mut *store, &wit_metadata, method, uri, &wit_headers)
-            .map_err(|e| {
-                WasmRuntimeError::FunctionCall(format!("on_request_headers failed: {}", e))
-            })?;
-
-        // Track fuel consumption
-        let remaining = store.get_fuel().unwrap_or(0);
-        let consumed = self.limits.max_fuel.saturating_sub(remaining);
-        store.data_mut().fuel_consumed = consumed;
+        let result =
+            handler.call_on_request_headers(&mut *store, &wit_metadata, method, uri, &wit_headers);
+        self.end_call(&mut store);
+        let wit_response = result.map_err(|e| self.call_error("on_request_headers", e))?;
 
         // Convert response
         Ok(agent_response_from_wit(wit_response))
@@ -214,7 +298,7 @@ impl WasmAgentInstance {
         is_last: bool,
     ) -> Result<AgentResponse, WasmRuntimeError> {
         let mut store = self.store.lock();
-        store.set_fuel(self.limits.max_fuel)?;
+        self.begin_call(&mut store)?;
 
         debug!(
             correlation_id = correlation_id,
@@ -226,16 +310,10 @@ impl WasmAgentInstance {
 
         // Call the WASM function
         let handler = self.agent.sentinel_agent_handler();
-        let wit_response = handler
-            .call_on_request_body(&mut *store, correlation_id, data, chunk_index, is_last)
-            .map_err(|e| {
-                WasmRuntimeError::FunctionCall(format!("on_request_body failed: {}", e))
-            })?;
-
-        // Track fuel consumption
-        let remaining = store.get_fuel().unwrap_or(0);
-        let consumed = self.limits.max_fuel.saturating_sub(remaining);
-        store.data_mut().fuel_consumed = consumed;
+        let result =
+            handler.call_on_request_body(&mut *store, correlation_id, data, chunk_index, is_last);
+        self.end_call(&mut store);
+        let wit_response = result.map_err(|e| self.call_error("on_request_body", e))?;
 
         Ok(agent_response_from_wit(wit_response))
     }
@@ -249,7 +327,7 @@ impl WasmAgentInstance {
         headers: &HashMap<String, Vec<String>>,
     ) -> Result<AgentResponse, WasmRuntimeError> {
         let mut store = self.store.lock();
-        store.set_fuel(self.limits.max_fuel)?;
+        self.begin_call(&mut store)?;
 
         debug!(
             correlation_id = correlation_id,
@@ -263,16 +341,10 @@ impl WasmAgentInstance {
 
         // Call the WASM function
         let handler = self.agent.sentinel_agent_handler();
-        let wit_response = handler
-            .call_on_response_headers(&mut *store, correlation_id, status, &wit_headers)
-            .map_err(|e| {
-                WasmRuntimeError::FunctionCall(format!("on_response_headers failed: {}", e))
-            })?;
-
-        // Track fuel consumption
-        let remaining = store.get_fuel().unwrap_or(0);
-        let consumed = self.limits.max_fuel.saturating_sub(remaining);
-        store.data_mut().fuel_consumed = consumed;
+        let result =
+            handler.call_on_response_headers(&mut *store, correlation_id, status, &wit_headers);
+        self.end_call(&mut store);
+        let wit_response = result.map_err(|e| self.call_error("on_response_headers", e))?;
 
         Ok(agent_response_from_wit(wit_response))
     }
@@ -287,7 +359,7 @@ impl WasmAgentInstance {
         is_last: bool,
     ) -> Result<AgentResponse, WasmRuntimeError> {
         let mut store = self.store.lock();
-        store.set_fuel(self.limits.max_fuel)?;
+        self.begin_call(&mut store)?;
 
         debug!(
             correlation_id = correlation_id,
@@ -299,16 +371,10 @@ impl WasmAgentInstance {
 
         // Call the WASM function
         let handler = self.agent.sentinel_agent_handler();
-        let wit_response = handler
-            .call_on_response_body(&mut *store, correlation_id, data, chunk_index, is_last)
-            .map_err(|e| {
-                WasmRuntimeError::FunctionCall(format!("on_response_body failed: {}", e))
-            })?;
-
-        // Track fuel consumption
-        let remaining = store.get_fuel().unwrap_or(0);
-        let consumed = self.limits.max_fuel.saturating_sub(remaining);
-        store.data_mut().fuel_consumed = consumed;
+        let result =
+            handler.call_on_response_body(&mut *store, correlation_id, data, chunk_index, is_last);
+        self.end_call(&mut store);
+        let wit_response = result.map_err(|e| self.call_error("on_response_body", e))?;
 
         Ok(agent_response_from_wit(wit_response))
     }
@@ -316,12 +382,13 @@ impl WasmAgentInstance {
     /// Health check.
     pub fn health_check(&self) -> Result<String, WasmRuntimeError> {
         let mut store = self.store.lock();
-        store.set_fuel(self.limits.max_fuel)?;
+        self.begin_call(&mut store)?;
 
         let lifecycle = self.agent.sentinel_agent_lifecycle();
-        lifecycle
-            .call_health_check(&mut *store)
-            .map_err(|e| WasmRuntimeError::FunctionCall(format!("health_check failed: {}", e)))?
+        let result = lifecycle.call_health_check(&mut *store);
+        self.end_call(&mut store);
+        result
+            .map_err(|e| self.call_error("health_check", e))?
             .map_err(WasmRuntimeError::AgentError)
     }
 
@@ -330,8 +397,8 @@ impl WasmAgentInstance {
         debug!(agent_id = %self.info.agent_id, "shutting down WASM agent");
 
         let mut store = self.store.lock();
-        if let Err(e) = store.set_fuel(self.limits.max_fuel) {
-            warn!(error = %e, "failed to set fuel for shutdown");
+        if let Err(e) = self.begin_call(&mut store) {
+            debug!(error = %e, "skipping WASM agent shutdown hook");
             return;
         }
 
@@ -352,6 +419,7 @@ pub struct WasmAgentBuilder {
     agent_id: String,
     config_json: String,
     limits: WasmResourceLimits,
+    epoch_deadline: Option<u64>,
 }
 
 impl WasmAgentBuilder {
@@ -361,6 +429,7 @@ impl WasmAgentBuilder {
             agent_id: agent_id.into(),
             config_json: "{}".to_string(),
             limits: WasmResourceLimits::default(),
+            epoch_deadline: None,
         }
     }
 
@@ -376,18 +445,35 @@ impl WasmAgentBuilder {
         self
     }
 
+    /// Interrupt calls after this many epoch ticks.
+    ///
+    /// Only meaningful on engines created with epoch interruption enabled.
+    pub fn epoch_deadline(mut self, ticks: u64) -> Self {
+        self.epoch_deadline = Some(ticks);
+        self
+    }
+
     /// Build the agent instance from a Component.
     pub fn build(
         self,
         engine: &Engine,
         component: &Component,
     ) -> Result<WasmAgentInstance, WasmRuntimeError> {
-        WasmAgentInstance::new(engine, component, self.limits, &self.config_json)
+        WasmAgentInstance::new(
+            engine,
+            component,
+            self.limits,
+            &self.config_json,
+            self.epoch_deadline,
+        )
     }
 }
 
 /// Create a component-model-enabled Wasmtime engine.
-pub fn create_component_engine(fuel_enabled: bool) -> Result<Engine, WasmRuntimeError> {
+pub fn create_component_engine(
+    fuel_enabled: bool,
+    epoch_enabled: bool,
+) -> Result<Engine, WasmRuntimeError> {
     let mut config = Config::new();
     config.wasm_component_model(true);
 
@@ -395,6 +481,11 @@ pub fn create_component_engine(fuel_enabled: bool) -> Result<Engine, WasmRuntime
         config.consume_fuel(true);
     }
 
+    // Wall-clock limits: the runtime ticks the epoch, stores trap at their deadline
+    if epoch_enabled {
+        config.epoch_interruption(true);
+    }
+
     // Sync execution for now
     config.async_support(false);
 
@@ -435,11 +526,15 @@ mod tests {
     #[test]
     fn test_create_component_engine() {
         // Test that we can create an engine with fuel enabled
-        let engine = create_component_engine(true);
+        let engine = create_component_engine(true, false);
         assert!(engine.is_ok());
 
         // Test that we can create an engine without fuel
-        let engine = create_component_engine(false);
+        let engine = create_component_engine(false, false);
+        assert!(engine.is_ok());
+
+        // Test that we can create an engine with epoch interruption
+        let engine = create_component_engine(true, true);
         assert!(engine.is_ok());
     }
 }
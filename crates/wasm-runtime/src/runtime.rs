@@ -6,7 +6,9 @@ use crate::host::{create_component_engine, WasmAgentBuilder, WasmAgentInfo, Wasm
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{debug, info, instrument, warn};
 use wasmtime::component::Component;
 use wasmtime::Engine;
@@ -23,14 +25,19 @@ pub struct WasmAgentRuntime {
     components: RwLock<HashMap<String, Component>>,
     /// Active agent instances (agent_id -> Instance)
     agents: RwLock<HashMap<String, Arc<WasmAgentInstance>>>,
-    /// Shutdown flag
-    shutdown: std::sync::atomic::AtomicBool,
+    /// Shutdown flag (also stops the epoch ticker)
+    shutdown: Arc<AtomicBool>,
 }
 
 impl WasmAgentRuntime {
     /// Create a new WASM runtime with the given configuration.
     pub fn new(config: WasmAgentConfig) -> Result<Self, WasmRuntimeError> {
-        let engine = create_component_engine(config.fuel_enabled)?;
+        let engine = create_component_engine(config.fuel_enabled, config.epoch_enabled)?;
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        if config.epoch_enabled {
+            spawn_epoch_ticker(&engine, config.epoch_tick_interval, Arc::clone(&shutdown))?;
+        }
 
         info!(
             fuel_enabled = config.fuel_enabled,
@@ -44,10 +51,36 @@ impl WasmAgentRuntime {
             config,
             components: RwLock::new(HashMap::new()),
             agents: RwLock::new(HashMap::new()),
-            shutdown: std::sync::atomic::AtomicBool::new(false),
+            shutdown,
         })
     }
 
+    /// Epoch ticks a call may run before it is interrupted.
+    fn epoch_deadline(&self) -> Option<u64> {
+        if !self.config.epoch_enabled {
+            return None;
+        }
+        let tick = self.config.epoch_tick_interval.as_nanos().max(1);
+        let ticks = self
+            .config
+            .limits
+            .max_execution_time
+            .as_nanos()
+            .div_ceil(tick);
+        Some(ticks.clamp(1, u64::MAX as u128) as u64)
+    }
+
+    /// Build an agent instance builder with the runtime's limits applied.
+    fn builder(&self, agent_id: &str, config_json: &str) -> WasmAgentBuilder {
+        let builder = WasmAgentBuilder::new(agent_id)
+            .config(config_json)
+            .limits(self.config.limits.clone());
+        match self.epoch_deadline() {
+            Some(ticks) => builder.epoch_deadline(ticks),
+            None => builder,
+        }
+    }
+
     /// Get the Wasmtime engine.
     pub fn engine(&self) -> &Engine {
         &self.engine
@@ -90,7 +123,10 @@ impl WasmAgentRuntime {
             .write()
             .insert(component_id.to_string(), component);
 
-        info!(component_id = component_id, "WASM component compiled and cached");
+        info!(
+            component_id = component_id,
+            "WASM component compiled and cached"
+        );
         Ok(())
     }
 
@@ -120,7 +156,7 @@ impl WasmAgentRuntime {
         component_id: &str,
         config_json: &str,
     ) -> Result<Arc<WasmAgentInstance>, WasmRuntimeError> {
-        if self.shutdown.load(std::sync::atomic::Ordering::Relaxed) {
+        if self.shutdown.load(Ordering::Relaxed) {
             return Err(WasmRuntimeError::Shutdown);
         }
 
@@ -140,9 +176,8 @@ impl WasmAgentRuntime {
         }
 
         // Create agent instance
-        let instance = WasmAgentBuilder::new(agent_id)
-            .config(config_json)
-            .limits(self.config.limits.clone())
+        let instance = self
+            .builder(agent_id, config_json)
             .build(&self.engine, component)?;
 
         let instance = Arc::new(instance);
@@ -161,6 +196,30 @@ impl WasmAgentRuntime {
         Ok(instance)
     }
 
+    /// Instantiate a compiled component without registering it.
+    ///
+    /// Used by callers that pool several instances of the same agent; the
+    /// caller owns the instance and is responsible for shutting it down.
+    #[instrument(skip(self, config_json))]
+    pub fn instantiate(
+        &self,
+        agent_id: &str,
+        component_id: &str,
+        config_json: &str,
+    ) -> Result<WasmAgentInstance, WasmRuntimeError> {
+        if self.shutdown.load(Ordering::Relaxed) {
+            return Err(WasmRuntimeError::Shutdown);
+        }
+
+        let components = self.components.read();
+        let component = components.get(component_id).ok_or_else(|| {
+            WasmRuntimeError::InvalidModule(format!("component not found: {}", component_id))
+        })?;
+
+        self.builder(agent_id, config_json)
+            .build(&self.engine, component)
+    }
+
     /// Load an agent directly from WASM bytes (compiles and loads).
     #[instrument(skip(self, wasm_bytes, config_json))]
     pub fn load_agent_from_bytes(
@@ -218,8 +277,7 @@ impl WasmAgentRuntime {
     /// Shutdown the runtime.
     pub fn shutdown(&self) {
         info!("shutting down WASM runtime");
-        self.shutdown
-            .store(true, std::sync::atomic::Ordering::Relaxed);
+        self.shutdown.store(true, Ordering::Relaxed);
 
         // Shutdown all agents
         let agents: Vec<_> = self.agents.write().drain().collect();
@@ -237,12 +295,35 @@ impl WasmAgentRuntime {
 
 impl Drop for WasmAgentRuntime {
     fn drop(&mut self) {
-        if !self.shutdown.load(std::sync::atomic::Ordering::Relaxed) {
+        if !self.shutdown.load(Ordering::Relaxed) {
             self.shutdown();
         }
     }
 }
 
+/// Advance the engine epoch every `interval` until `shutdown` is set.
+///
+/// Stores interrupt guest code once their epoch deadline passes, which bounds
+/// wall-clock time per call independently of fuel.
+fn spawn_epoch_ticker(
+    engine: &Engine,
+    interval: Duration,
+    shutdown: Arc<AtomicBool>,
+) -> Result<(), WasmRuntimeError> {
+    let engine = engine.clone();
+    std::thread::Builder::new()
+        .name("wasm-epoch-ticker".to_string())
+        .spawn(move || {
+            while !shutdown.load(Ordering::Relaxed) {
+                std::thread::sleep(interval);
+                engine.increment_epoch();
+            }
+            debug!("WASM epoch ticker stopped");
+        })
+        .map_err(|e| WasmRuntimeError::Internal(format!("failed to spawn epoch ticker: {}", e)))?;
+    Ok(())
+}
+
 /// Runtime statistics.
 #[derive(Debug, Clone)]
 pub struct WasmRuntimeStats {
@@ -266,6 +347,20 @@ mod tests {
         assert_eq!(runtime.stats().active_agents, 0);
     }
 
+    #[test]
+    fn test_epoch_deadline() {
+        let mut config = WasmAgentConfig::minimal();
+        let runtime = WasmAgentRuntime::new(config.clone()).unwrap();
+        assert_eq!(runtime.epoch_deadline(), None);
+
+        config.epoch_enabled = true;
+        config.epoch_tick_interval = Duration::from_millis(10);
+        config.limits.max_execution_time = Duration::from_millis(25);
+        let runtime = WasmAgentRuntime::new(config).unwrap();
+        assert_eq!(runtime.epoch_deadline(), Some(3));
+        runtime.shutdown();
+    }
+
     #[test]
     fn test_runtime_shutdown() {
         let config = WasmAgentConfig::minimal();